@@ -0,0 +1,75 @@
+import { AdlSourceParams } from "@adllang/adlc-tools/utils/sources";
+import {
+  forEachDecl,
+  getAnnotation,
+  parseAdlModules,
+  scopedName,
+} from "@adllang/adlc-tools/utils/adl";
+
+import { FileWriter } from "./file-writer.ts";
+
+export interface GenRustRequestTimeoutsParams extends AdlSourceParams {
+  outputFile: string;
+}
+
+/***
+ * Generate a lookup of the timeouts of the requests that have a
+ * RequestTimeoutSecs annotation, as the generated rust code doesn't
+ * include annotations
+ */
+export async function genRustRequestTimeouts(
+  params: GenRustRequestTimeoutsParams,
+): Promise<void> {
+  const loadedAdl = await parseAdlModules(params);
+
+  const timeouts: { path: string; secs: number }[] = [];
+  forEachDecl(loadedAdl.modules, (scopedDecl) => {
+    const type_ = scopedDecl.decl.type_;
+    if (type_.kind !== "struct_") {
+      return;
+    }
+    for (const field of type_.value.fields) {
+      const secs = getAnnotation(field.annotations, SN_REQUEST_TIMEOUT_SECS);
+      if (secs === undefined) {
+        continue;
+      }
+      const fieldName =
+        `${scopedDecl.moduleName}.${scopedDecl.decl.name}.${field.name}`;
+      // A zero timeout would fail every request immediately
+      if (typeof secs !== "number" || !Number.isInteger(secs) || secs < 1) {
+        throw new Error(
+          `${fieldName}: RequestTimeoutSecs must be a whole number of at least 1`,
+        );
+      }
+      if (field.default.kind !== "just") {
+        throw new Error(
+          `${fieldName}: RequestTimeoutSecs must annotate an HttpReq with a default`,
+        );
+      }
+      const path = (field.default.value as { path: string }).path;
+      timeouts.push({ path, secs });
+    }
+  });
+  timeouts.sort((t1, t2) => t1.path < t2.path ? -1 : t1.path > t2.path ? 1 : 0);
+
+  const writer = new FileWriter(params.outputFile, false);
+  writer.write("// This file is generated from the api definitions\n");
+  writer.write("\n");
+  writer.write("/**\n");
+  writer.write(
+    " * The timeout of each request with a RequestTimeoutSecs annotation, by\n",
+  );
+  writer.write(" * the request's path\n");
+  writer.write(" */\n");
+  writer.write("pub fn request_timeout_secs(path: &str) -> Option<u32> {\n");
+  writer.write("    match path {\n");
+  for (const t of timeouts) {
+    writer.write(`        "${t.path}" => Some(${t.secs}),\n`);
+  }
+  writer.write("        _ => None,\n");
+  writer.write("    }\n");
+  writer.write("}\n");
+  writer.close();
+}
+
+const SN_REQUEST_TIMEOUT_SECS = scopedName("common.http", "RequestTimeoutSecs");
@@ -4,6 +4,7 @@ import { AdlcError, genRust, genTypescript } from "@adllang/adlc-tools";
 import { genAdlTsPackage } from "./gen-adl-ts-package.ts";
 import { genCreateSqlSchema } from "./gen-sqlschema.ts";
 import { genRustSeaQuerySchema } from "./gen-rs-seaquery-schema.ts";
+import { genRustRequestTimeouts } from "./gen-rs-request-timeouts.ts";
 
 async function main() {
   const repo = getRepoRoot();
@@ -60,6 +61,12 @@ async function main() {
       adlModules: ["protoapp.db"],
       outputFile: outputDir + "/db/schema.rs",
     });
+
+    await genRustRequestTimeouts({
+      ...commonFlags,
+      adlModules: ["protoapp.apis.ui"],
+      outputFile: outputDir + "/http/timeouts.rs",
+    });
   }
 
   {
@@ -74,17 +81,9 @@ async function main() {
       viewsFile: repo + "/sql/adl-gen/adl-views.latest.sql",
     });
 
-    // Make the first migrations these two files. Once there is a live
-    // database, these copy operations should be deleted, and migrations
-    // written by hand
-    Deno.copyFile(
-      repo + "/sql/adl-gen/adl-tables.latest.sql",
-      repo + "/rust/server/migrations/00000000000020_adl-tables.latest.sql",
-    );
-    Deno.copyFile(
-      repo + "/sql/adl-gen/adl-views.latest.sql",
-      repo + "/rust/server/migrations/00000000000030_adl-views.latest.sql",
-    );
+    // The first migrations were copies of these two files. Now that there
+    // are live databases, later schema changes are migrations written by
+    // hand in rust/server/migrations
   }
 }
 
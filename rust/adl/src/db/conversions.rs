@@ -61,6 +61,22 @@ impl DbConversions for serde_json::Value {
     }
 }
 
+// Vectors are stored as json arrays
+impl<T> DbConversions for Vec<T>
+where
+    T: serde::Serialize + serde::de::DeserializeOwned,
+{
+    type DbType = serde_json::Value;
+
+    fn to_db(&self) -> Self::DbType {
+        serde_json::to_value(self).expect("should be able to serialize a vector")
+    }
+
+    fn from_db(dbv: Self::DbType) -> Self {
+        serde_json::from_value(dbv).expect("db vector should be valid")
+    }
+}
+
 impl DbConversions for u32 {
     type DbType = i32;
 
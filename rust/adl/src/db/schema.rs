@@ -8,6 +8,58 @@ use crate::custom::common::db::DbKey;
 use crate::gen as adlgen;
 use crate::rt as adlrt;
 
+pub struct ApiKey {}
+
+impl ApiKey {
+    pub fn table_str() -> &'static str {
+        "api_key"
+    }
+
+    pub fn id_prefix() -> &'static str {
+        "K-"
+    }
+
+    pub fn table() -> DynIden {
+        Alias::new(Self::table_str()).into_iden()
+    }
+
+    pub fn id() -> ColumnSpec<DbKey<adlgen::protoapp::db::ApiKeyTable>> {
+        ColumnSpec::new(Self::table_str(), "id")
+    }
+
+    pub fn user_id() -> ColumnSpec<adlgen::protoapp::db::AppUserId> {
+        ColumnSpec::new(Self::table_str(), "user_id")
+    }
+
+    pub fn name() -> ColumnSpec<adlgen::common::strings::StringNE> {
+        ColumnSpec::new(Self::table_str(), "name")
+    }
+
+    pub fn key_hash() -> ColumnSpec<String> {
+        ColumnSpec::new(Self::table_str(), "key_hash")
+    }
+
+    pub fn scopes() -> ColumnSpec<std::vec::Vec<adlgen::protoapp::db::ApiKeyScope>> {
+        ColumnSpec::new(Self::table_str(), "scopes")
+    }
+
+    pub fn created_at() -> ColumnSpec<crate::custom::common::time::Instant> {
+        ColumnSpec::new(Self::table_str(), "created_at")
+    }
+
+    pub fn expires_at() -> ColumnSpec<crate::custom::common::time::Instant> {
+        ColumnSpec::new(Self::table_str(), "expires_at")
+    }
+
+    pub fn last_used_at() -> ColumnSpec<std::option::Option<crate::custom::common::time::Instant>> {
+        ColumnSpec::new(Self::table_str(), "last_used_at")
+    }
+
+    pub fn revoked_at() -> ColumnSpec<std::option::Option<crate::custom::common::time::Instant>> {
+        ColumnSpec::new(Self::table_str(), "revoked_at")
+    }
+}
+
 pub struct AppUser {}
 
 impl AppUser {
@@ -35,12 +87,160 @@ impl AppUser {
         ColumnSpec::new(Self::table_str(), "email")
     }
 
-    pub fn is_admin() -> ColumnSpec<bool> {
-        ColumnSpec::new(Self::table_str(), "is_admin")
+    pub fn hashed_password() -> ColumnSpec<std::option::Option<adlgen::common::strings::StringNE>> {
+        ColumnSpec::new(Self::table_str(), "hashed_password")
     }
 
-    pub fn hashed_password() -> ColumnSpec<adlgen::common::strings::StringNE> {
-        ColumnSpec::new(Self::table_str(), "hashed_password")
+    pub fn verified_at() -> ColumnSpec<std::option::Option<crate::custom::common::time::Instant>> {
+        ColumnSpec::new(Self::table_str(), "verified_at")
+    }
+
+    pub fn verification_sent_at() -> ColumnSpec<std::option::Option<crate::custom::common::time::Instant>> {
+        ColumnSpec::new(Self::table_str(), "verification_sent_at")
+    }
+
+    pub fn deactivated_at() -> ColumnSpec<std::option::Option<crate::custom::common::time::Instant>> {
+        ColumnSpec::new(Self::table_str(), "deactivated_at")
+    }
+}
+
+pub struct AuditLog {}
+
+impl AuditLog {
+    pub fn table_str() -> &'static str {
+        "audit_log"
+    }
+
+    pub fn id_prefix() -> &'static str {
+        "A-"
+    }
+
+    pub fn table() -> DynIden {
+        Alias::new(Self::table_str()).into_iden()
+    }
+
+    pub fn id() -> ColumnSpec<DbKey<adlgen::protoapp::db::AuditLogTable>> {
+        ColumnSpec::new(Self::table_str(), "id")
+    }
+
+    pub fn logged_at() -> ColumnSpec<crate::custom::common::time::Instant> {
+        ColumnSpec::new(Self::table_str(), "logged_at")
+    }
+
+    pub fn actor() -> ColumnSpec<adlgen::protoapp::db::AppUserId> {
+        ColumnSpec::new(Self::table_str(), "actor")
+    }
+
+    pub fn impersonated() -> ColumnSpec<std::option::Option<adlgen::protoapp::db::AppUserId>> {
+        ColumnSpec::new(Self::table_str(), "impersonated")
+    }
+
+    pub fn endpoint() -> ColumnSpec<String> {
+        ColumnSpec::new(Self::table_str(), "endpoint")
+    }
+
+    pub fn input() -> ColumnSpec<serde_json::Value> {
+        ColumnSpec::new(Self::table_str(), "input")
+    }
+
+    pub fn outcome() -> ColumnSpec<adlgen::protoapp::db::AuditOutcome> {
+        ColumnSpec::new(Self::table_str(), "outcome")
+    }
+
+    pub fn client_ip() -> ColumnSpec<std::option::Option<String>> {
+        ColumnSpec::new(Self::table_str(), "client_ip")
+    }
+}
+
+pub struct Invitation {}
+
+impl Invitation {
+    pub fn table_str() -> &'static str {
+        "invitation"
+    }
+
+    pub fn id_prefix() -> &'static str {
+        "N-"
+    }
+
+    pub fn table() -> DynIden {
+        Alias::new(Self::table_str()).into_iden()
+    }
+
+    pub fn id() -> ColumnSpec<DbKey<adlgen::protoapp::db::InvitationTable>> {
+        ColumnSpec::new(Self::table_str(), "id")
+    }
+
+    pub fn email() -> ColumnSpec<adlgen::common::strings::StringNE> {
+        ColumnSpec::new(Self::table_str(), "email")
+    }
+
+    pub fn roles() -> ColumnSpec<std::vec::Vec<adlgen::protoapp::db::RoleId>> {
+        ColumnSpec::new(Self::table_str(), "roles")
+    }
+
+    pub fn token_hash() -> ColumnSpec<String> {
+        ColumnSpec::new(Self::table_str(), "token_hash")
+    }
+
+    pub fn created_by() -> ColumnSpec<adlgen::protoapp::db::AppUserId> {
+        ColumnSpec::new(Self::table_str(), "created_by")
+    }
+
+    pub fn created_at() -> ColumnSpec<crate::custom::common::time::Instant> {
+        ColumnSpec::new(Self::table_str(), "created_at")
+    }
+
+    pub fn expires_at() -> ColumnSpec<crate::custom::common::time::Instant> {
+        ColumnSpec::new(Self::table_str(), "expires_at")
+    }
+
+    pub fn used_at() -> ColumnSpec<std::option::Option<crate::custom::common::time::Instant>> {
+        ColumnSpec::new(Self::table_str(), "used_at")
+    }
+
+    pub fn user_id() -> ColumnSpec<std::option::Option<adlgen::protoapp::db::AppUserId>> {
+        ColumnSpec::new(Self::table_str(), "user_id")
+    }
+
+    pub fn revoked_at() -> ColumnSpec<std::option::Option<crate::custom::common::time::Instant>> {
+        ColumnSpec::new(Self::table_str(), "revoked_at")
+    }
+}
+
+pub struct LoginFailure {}
+
+impl LoginFailure {
+    pub fn table_str() -> &'static str {
+        "login_failure"
+    }
+
+    pub fn id_prefix() -> &'static str {
+        "L-"
+    }
+
+    pub fn table() -> DynIden {
+        Alias::new(Self::table_str()).into_iden()
+    }
+
+    pub fn id() -> ColumnSpec<DbKey<adlgen::protoapp::db::LoginFailureTable>> {
+        ColumnSpec::new(Self::table_str(), "id")
+    }
+
+    pub fn email() -> ColumnSpec<adlgen::common::strings::StringNE> {
+        ColumnSpec::new(Self::table_str(), "email")
+    }
+
+    pub fn failed_count() -> ColumnSpec<u32> {
+        ColumnSpec::new(Self::table_str(), "failed_count")
+    }
+
+    pub fn last_failed_at() -> ColumnSpec<crate::custom::common::time::Instant> {
+        ColumnSpec::new(Self::table_str(), "last_failed_at")
+    }
+
+    pub fn locked_until() -> ColumnSpec<std::option::Option<crate::custom::common::time::Instant>> {
+        ColumnSpec::new(Self::table_str(), "locked_until")
     }
 }
 
@@ -74,5 +274,527 @@ impl Message {
     pub fn message() -> ColumnSpec<adlgen::common::strings::StringML> {
         ColumnSpec::new(Self::table_str(), "message")
     }
+
+    pub fn organisation_id() -> ColumnSpec<std::option::Option<adlgen::protoapp::db::OrganisationId>> {
+        ColumnSpec::new(Self::table_str(), "organisation_id")
+    }
+
+    pub fn reply_to() -> ColumnSpec<std::option::Option<adlgen::protoapp::db::MessageId>> {
+        ColumnSpec::new(Self::table_str(), "reply_to")
+    }
+
+    pub fn edited_at() -> ColumnSpec<std::option::Option<crate::custom::common::time::Instant>> {
+        ColumnSpec::new(Self::table_str(), "edited_at")
+    }
+
+    pub fn deleted_at() -> ColumnSpec<std::option::Option<crate::custom::common::time::Instant>> {
+        ColumnSpec::new(Self::table_str(), "deleted_at")
+    }
+}
+
+pub struct MessageReaction {}
+
+impl MessageReaction {
+    pub fn table_str() -> &'static str {
+        "message_reaction"
+    }
+
+    pub fn id_prefix() -> &'static str {
+        "X-"
+    }
+
+    pub fn table() -> DynIden {
+        Alias::new(Self::table_str()).into_iden()
+    }
+
+    pub fn id() -> ColumnSpec<DbKey<adlgen::protoapp::db::MessageReactionTable>> {
+        ColumnSpec::new(Self::table_str(), "id")
+    }
+
+    pub fn message_id() -> ColumnSpec<adlgen::protoapp::db::MessageId> {
+        ColumnSpec::new(Self::table_str(), "message_id")
+    }
+
+    pub fn user_id() -> ColumnSpec<adlgen::protoapp::db::AppUserId> {
+        ColumnSpec::new(Self::table_str(), "user_id")
+    }
+
+    pub fn emoji() -> ColumnSpec<adlgen::common::strings::StringNE> {
+        ColumnSpec::new(Self::table_str(), "emoji")
+    }
+
+    pub fn reacted_at() -> ColumnSpec<crate::custom::common::time::Instant> {
+        ColumnSpec::new(Self::table_str(), "reacted_at")
+    }
 }
 
+pub struct MessageRevision {}
+
+impl MessageRevision {
+    pub fn table_str() -> &'static str {
+        "message_revision"
+    }
+
+    pub fn id_prefix() -> &'static str {
+        "V-"
+    }
+
+    pub fn table() -> DynIden {
+        Alias::new(Self::table_str()).into_iden()
+    }
+
+    pub fn id() -> ColumnSpec<DbKey<adlgen::protoapp::db::MessageRevisionTable>> {
+        ColumnSpec::new(Self::table_str(), "id")
+    }
+
+    pub fn message_id() -> ColumnSpec<adlgen::protoapp::db::MessageId> {
+        ColumnSpec::new(Self::table_str(), "message_id")
+    }
+
+    pub fn message() -> ColumnSpec<adlgen::common::strings::StringML> {
+        ColumnSpec::new(Self::table_str(), "message")
+    }
+
+    pub fn written_at() -> ColumnSpec<crate::custom::common::time::Instant> {
+        ColumnSpec::new(Self::table_str(), "written_at")
+    }
+
+    pub fn replaced_at() -> ColumnSpec<crate::custom::common::time::Instant> {
+        ColumnSpec::new(Self::table_str(), "replaced_at")
+    }
+
+    pub fn replaced_by() -> ColumnSpec<adlgen::protoapp::db::AppUserId> {
+        ColumnSpec::new(Self::table_str(), "replaced_by")
+    }
+}
+
+pub struct OidcIdentity {}
+
+impl OidcIdentity {
+    pub fn table_str() -> &'static str {
+        "oidc_identity"
+    }
+
+    pub fn id_prefix() -> &'static str {
+        "I-"
+    }
+
+    pub fn table() -> DynIden {
+        Alias::new(Self::table_str()).into_iden()
+    }
+
+    pub fn id() -> ColumnSpec<DbKey<adlgen::protoapp::db::OidcIdentityTable>> {
+        ColumnSpec::new(Self::table_str(), "id")
+    }
+
+    pub fn user_id() -> ColumnSpec<adlgen::protoapp::db::AppUserId> {
+        ColumnSpec::new(Self::table_str(), "user_id")
+    }
+
+    pub fn issuer() -> ColumnSpec<String> {
+        ColumnSpec::new(Self::table_str(), "issuer")
+    }
+
+    pub fn subject() -> ColumnSpec<String> {
+        ColumnSpec::new(Self::table_str(), "subject")
+    }
+
+    pub fn linked_at() -> ColumnSpec<crate::custom::common::time::Instant> {
+        ColumnSpec::new(Self::table_str(), "linked_at")
+    }
+}
+
+pub struct OidcLogin {}
+
+impl OidcLogin {
+    pub fn table_str() -> &'static str {
+        "oidc_login"
+    }
+
+    pub fn id_prefix() -> &'static str {
+        "O-"
+    }
+
+    pub fn table() -> DynIden {
+        Alias::new(Self::table_str()).into_iden()
+    }
+
+    pub fn id() -> ColumnSpec<DbKey<adlgen::protoapp::db::OidcLoginTable>> {
+        ColumnSpec::new(Self::table_str(), "id")
+    }
+
+    pub fn state_hash() -> ColumnSpec<String> {
+        ColumnSpec::new(Self::table_str(), "state_hash")
+    }
+
+    pub fn code_verifier() -> ColumnSpec<String> {
+        ColumnSpec::new(Self::table_str(), "code_verifier")
+    }
+
+    pub fn nonce() -> ColumnSpec<String> {
+        ColumnSpec::new(Self::table_str(), "nonce")
+    }
+
+    pub fn created_at() -> ColumnSpec<crate::custom::common::time::Instant> {
+        ColumnSpec::new(Self::table_str(), "created_at")
+    }
+
+    pub fn expires_at() -> ColumnSpec<crate::custom::common::time::Instant> {
+        ColumnSpec::new(Self::table_str(), "expires_at")
+    }
+
+    pub fn used_at() -> ColumnSpec<std::option::Option<crate::custom::common::time::Instant>> {
+        ColumnSpec::new(Self::table_str(), "used_at")
+    }
+}
+
+pub struct Organisation {}
+
+impl Organisation {
+    pub fn table_str() -> &'static str {
+        "organisation"
+    }
+
+    pub fn id_prefix() -> &'static str {
+        "B-"
+    }
+
+    pub fn table() -> DynIden {
+        Alias::new(Self::table_str()).into_iden()
+    }
+
+    pub fn id() -> ColumnSpec<DbKey<adlgen::protoapp::db::OrganisationTable>> {
+        ColumnSpec::new(Self::table_str(), "id")
+    }
+
+    pub fn name() -> ColumnSpec<adlgen::common::strings::StringNE> {
+        ColumnSpec::new(Self::table_str(), "name")
+    }
+
+    pub fn created_at() -> ColumnSpec<crate::custom::common::time::Instant> {
+        ColumnSpec::new(Self::table_str(), "created_at")
+    }
+}
+
+pub struct OrganisationMember {}
+
+impl OrganisationMember {
+    pub fn table_str() -> &'static str {
+        "organisation_member"
+    }
+
+    pub fn id_prefix() -> &'static str {
+        "E-"
+    }
+
+    pub fn table() -> DynIden {
+        Alias::new(Self::table_str()).into_iden()
+    }
+
+    pub fn id() -> ColumnSpec<DbKey<adlgen::protoapp::db::OrganisationMemberTable>> {
+        ColumnSpec::new(Self::table_str(), "id")
+    }
+
+    pub fn organisation_id() -> ColumnSpec<adlgen::protoapp::db::OrganisationId> {
+        ColumnSpec::new(Self::table_str(), "organisation_id")
+    }
+
+    pub fn user_id() -> ColumnSpec<adlgen::protoapp::db::AppUserId> {
+        ColumnSpec::new(Self::table_str(), "user_id")
+    }
+
+    pub fn roles() -> ColumnSpec<std::vec::Vec<adlgen::protoapp::db::RoleId>> {
+        ColumnSpec::new(Self::table_str(), "roles")
+    }
+
+    pub fn joined_at() -> ColumnSpec<crate::custom::common::time::Instant> {
+        ColumnSpec::new(Self::table_str(), "joined_at")
+    }
+}
+
+pub struct PasswordResetToken {}
+
+impl PasswordResetToken {
+    pub fn table_str() -> &'static str {
+        "password_reset_token"
+    }
+
+    pub fn id_prefix() -> &'static str {
+        "P-"
+    }
+
+    pub fn table() -> DynIden {
+        Alias::new(Self::table_str()).into_iden()
+    }
+
+    pub fn id() -> ColumnSpec<DbKey<adlgen::protoapp::db::PasswordResetTokenTable>> {
+        ColumnSpec::new(Self::table_str(), "id")
+    }
+
+    pub fn user_id() -> ColumnSpec<adlgen::protoapp::db::AppUserId> {
+        ColumnSpec::new(Self::table_str(), "user_id")
+    }
+
+    pub fn token_hash() -> ColumnSpec<String> {
+        ColumnSpec::new(Self::table_str(), "token_hash")
+    }
+
+    pub fn created_at() -> ColumnSpec<crate::custom::common::time::Instant> {
+        ColumnSpec::new(Self::table_str(), "created_at")
+    }
+
+    pub fn expires_at() -> ColumnSpec<crate::custom::common::time::Instant> {
+        ColumnSpec::new(Self::table_str(), "expires_at")
+    }
+
+    pub fn used_at() -> ColumnSpec<std::option::Option<crate::custom::common::time::Instant>> {
+        ColumnSpec::new(Self::table_str(), "used_at")
+    }
+}
+
+pub struct Permission {}
+
+impl Permission {
+    pub fn table_str() -> &'static str {
+        "permission"
+    }
+
+    pub fn id_prefix() -> &'static str {
+        "H-"
+    }
+
+    pub fn table() -> DynIden {
+        Alias::new(Self::table_str()).into_iden()
+    }
+
+    pub fn id() -> ColumnSpec<DbKey<adlgen::protoapp::db::PermissionTable>> {
+        ColumnSpec::new(Self::table_str(), "id")
+    }
+
+    pub fn role_id() -> ColumnSpec<adlgen::protoapp::db::RoleId> {
+        ColumnSpec::new(Self::table_str(), "role_id")
+    }
+
+    pub fn name() -> ColumnSpec<adlgen::common::strings::StringNE> {
+        ColumnSpec::new(Self::table_str(), "name")
+    }
+}
+
+pub struct RefreshToken {}
+
+impl RefreshToken {
+    pub fn table_str() -> &'static str {
+        "refresh_token"
+    }
+
+    pub fn id_prefix() -> &'static str {
+        "R-"
+    }
+
+    pub fn table() -> DynIden {
+        Alias::new(Self::table_str()).into_iden()
+    }
+
+    pub fn id() -> ColumnSpec<DbKey<adlgen::protoapp::db::RefreshTokenTable>> {
+        ColumnSpec::new(Self::table_str(), "id")
+    }
+
+    pub fn user_id() -> ColumnSpec<adlgen::protoapp::db::AppUserId> {
+        ColumnSpec::new(Self::table_str(), "user_id")
+    }
+
+    pub fn family() -> ColumnSpec<String> {
+        ColumnSpec::new(Self::table_str(), "family")
+    }
+
+    pub fn issued_at() -> ColumnSpec<crate::custom::common::time::Instant> {
+        ColumnSpec::new(Self::table_str(), "issued_at")
+    }
+
+    pub fn expires_at() -> ColumnSpec<crate::custom::common::time::Instant> {
+        ColumnSpec::new(Self::table_str(), "expires_at")
+    }
+
+    pub fn used_at() -> ColumnSpec<std::option::Option<crate::custom::common::time::Instant>> {
+        ColumnSpec::new(Self::table_str(), "used_at")
+    }
+
+    pub fn revoked_at() -> ColumnSpec<std::option::Option<crate::custom::common::time::Instant>> {
+        ColumnSpec::new(Self::table_str(), "revoked_at")
+    }
+}
+
+pub struct Role {}
+
+impl Role {
+    pub fn table_str() -> &'static str {
+        "role"
+    }
+
+    pub fn id_prefix() -> &'static str {
+        "G-"
+    }
+
+    pub fn table() -> DynIden {
+        Alias::new(Self::table_str()).into_iden()
+    }
+
+    pub fn id() -> ColumnSpec<DbKey<adlgen::protoapp::db::RoleTable>> {
+        ColumnSpec::new(Self::table_str(), "id")
+    }
+
+    pub fn name() -> ColumnSpec<adlgen::common::strings::StringNE> {
+        ColumnSpec::new(Self::table_str(), "name")
+    }
+
+    pub fn description() -> ColumnSpec<String> {
+        ColumnSpec::new(Self::table_str(), "description")
+    }
+}
+
+pub struct Session {}
+
+impl Session {
+    pub fn table_str() -> &'static str {
+        "session"
+    }
+
+    pub fn id_prefix() -> &'static str {
+        "S-"
+    }
+
+    pub fn table() -> DynIden {
+        Alias::new(Self::table_str()).into_iden()
+    }
+
+    pub fn id() -> ColumnSpec<DbKey<adlgen::protoapp::db::SessionTable>> {
+        ColumnSpec::new(Self::table_str(), "id")
+    }
+
+    pub fn user_id() -> ColumnSpec<adlgen::protoapp::db::AppUserId> {
+        ColumnSpec::new(Self::table_str(), "user_id")
+    }
+
+    pub fn user_agent() -> ColumnSpec<std::option::Option<String>> {
+        ColumnSpec::new(Self::table_str(), "user_agent")
+    }
+
+    pub fn client_ip() -> ColumnSpec<std::option::Option<String>> {
+        ColumnSpec::new(Self::table_str(), "client_ip")
+    }
+
+    pub fn created_at() -> ColumnSpec<crate::custom::common::time::Instant> {
+        ColumnSpec::new(Self::table_str(), "created_at")
+    }
+
+    pub fn last_refreshed_at() -> ColumnSpec<crate::custom::common::time::Instant> {
+        ColumnSpec::new(Self::table_str(), "last_refreshed_at")
+    }
+
+    pub fn revoked_at() -> ColumnSpec<std::option::Option<crate::custom::common::time::Instant>> {
+        ColumnSpec::new(Self::table_str(), "revoked_at")
+    }
+
+    pub fn organisation_id() -> ColumnSpec<std::option::Option<adlgen::protoapp::db::OrganisationId>> {
+        ColumnSpec::new(Self::table_str(), "organisation_id")
+    }
+}
+
+pub struct TotpRecoveryCode {}
+
+impl TotpRecoveryCode {
+    pub fn table_str() -> &'static str {
+        "totp_recovery_code"
+    }
+
+    pub fn id_prefix() -> &'static str {
+        "C-"
+    }
+
+    pub fn table() -> DynIden {
+        Alias::new(Self::table_str()).into_iden()
+    }
+
+    pub fn id() -> ColumnSpec<DbKey<adlgen::protoapp::db::TotpRecoveryCodeTable>> {
+        ColumnSpec::new(Self::table_str(), "id")
+    }
+
+    pub fn user_id() -> ColumnSpec<adlgen::protoapp::db::AppUserId> {
+        ColumnSpec::new(Self::table_str(), "user_id")
+    }
+
+    pub fn code_hash() -> ColumnSpec<String> {
+        ColumnSpec::new(Self::table_str(), "code_hash")
+    }
+
+    pub fn used_at() -> ColumnSpec<std::option::Option<crate::custom::common::time::Instant>> {
+        ColumnSpec::new(Self::table_str(), "used_at")
+    }
+}
+
+pub struct UserRole {}
+
+impl UserRole {
+    pub fn table_str() -> &'static str {
+        "user_role"
+    }
+
+    pub fn id_prefix() -> &'static str {
+        "J-"
+    }
+
+    pub fn table() -> DynIden {
+        Alias::new(Self::table_str()).into_iden()
+    }
+
+    pub fn id() -> ColumnSpec<DbKey<adlgen::protoapp::db::UserRoleTable>> {
+        ColumnSpec::new(Self::table_str(), "id")
+    }
+
+    pub fn user_id() -> ColumnSpec<adlgen::protoapp::db::AppUserId> {
+        ColumnSpec::new(Self::table_str(), "user_id")
+    }
+
+    pub fn role_id() -> ColumnSpec<adlgen::protoapp::db::RoleId> {
+        ColumnSpec::new(Self::table_str(), "role_id")
+    }
+}
+
+pub struct UserTotp {}
+
+impl UserTotp {
+    pub fn table_str() -> &'static str {
+        "user_totp"
+    }
+
+    pub fn id_prefix() -> &'static str {
+        "T-"
+    }
+
+    pub fn table() -> DynIden {
+        Alias::new(Self::table_str()).into_iden()
+    }
+
+    pub fn id() -> ColumnSpec<DbKey<adlgen::protoapp::db::UserTotpTable>> {
+        ColumnSpec::new(Self::table_str(), "id")
+    }
+
+    pub fn user_id() -> ColumnSpec<adlgen::protoapp::db::AppUserId> {
+        ColumnSpec::new(Self::table_str(), "user_id")
+    }
+
+    pub fn secret() -> ColumnSpec<String> {
+        ColumnSpec::new(Self::table_str(), "secret")
+    }
+
+    pub fn confirmed_at() -> ColumnSpec<std::option::Option<crate::custom::common::time::Instant>> {
+        ColumnSpec::new(Self::table_str(), "confirmed_at")
+    }
+
+    pub fn last_used_step() -> ColumnSpec<std::option::Option<u64>> {
+        ColumnSpec::new(Self::table_str(), "last_used_step")
+    }
+}
+
+
+derive_db_conversions_adl_enum!(adlgen::protoapp::db::AuditOutcome);
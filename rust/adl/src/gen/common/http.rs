@@ -57,6 +57,12 @@ pub enum HttpMethod {
   Post,
 }
 
+/**
+ * An annotation on an HttpReq, overriding the server's default timeout
+ * for that request. It must be at least 1.
+ */
+pub type RequestTimeoutSecs = u32;
+
 #[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
 pub enum HttpSecurity {
   #[serde(rename="public")]
@@ -82,3 +88,23 @@ impl Unit {
     }
   }
 }
+
+/**
+ * The json body of an error response, where the server
+ * provides one
+ */
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub struct HttpError {
+  pub code: String,
+
+  pub message: String,
+}
+
+impl HttpError {
+  pub fn new(code: String, message: String) -> HttpError {
+    HttpError {
+      code: code,
+      message: message,
+    }
+  }
+}
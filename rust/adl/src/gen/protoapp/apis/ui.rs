@@ -9,12 +9,23 @@ use crate::gen::common::strings::EmailAddress;
 use crate::gen::common::strings::Password;
 use crate::gen::common::strings::StringML;
 use crate::gen::common::strings::StringNE;
+use crate::gen::protoapp::db::ApiKeyId;
+use crate::gen::protoapp::db::ApiKeyScope;
 use crate::gen::protoapp::db::AppUserId;
+use crate::gen::protoapp::db::AuditLog;
+use crate::gen::protoapp::db::AuditLogId;
+use crate::gen::protoapp::db::AuditOutcome;
+use crate::gen::protoapp::db::InvitationId;
 use crate::gen::protoapp::db::MessageId;
+use crate::gen::protoapp::db::MessageRevision;
+use crate::gen::protoapp::db::Organisation;
+use crate::gen::protoapp::db::OrganisationId;
+use crate::gen::protoapp::db::RoleId;
+use crate::gen::protoapp::db::SessionId;
 use serde::Deserialize;
 use serde::Serialize;
 
-#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+#[derive(Clone,Deserialize,PartialEq,Serialize)]
 pub struct ApiRequests {
   /**
    * AWS default compatible health check
@@ -22,6 +33,12 @@ pub struct ApiRequests {
   #[serde(default="ApiRequests::def_healthy")]
   pub healthy: HttpReq<(), ()>,
 
+  /**
+   * The public keys used to verify access tokens, as a JSON web key set
+   */
+  #[serde(default="ApiRequests::def_jwks")]
+  pub jwks: HttpReq<(), Jwks>,
+
   /**
    * Login a user
    * The response will set an httpOnly cookie containing the refresh token
@@ -29,19 +46,111 @@ pub struct ApiRequests {
   #[serde(default="ApiRequests::def_login")]
   pub login: HttpReq<LoginReq, LoginResp>,
 
+  /**
+   * Complete a login that requires a second factor, with the challenge
+   * token from the `totp_required` login response, and either a code
+   * from the user's authenticator app or one of their recovery codes.
+   * The response will set an httpOnly cookie containing the refresh token
+   */
+  #[serde(default="ApiRequests::def_login_totp")]
+  pub login_totp: HttpReq<LoginTotpReq, LoginTotpResp>,
+
+  /**
+   * Start a login with the OpenID Connect provider. The client should
+   * send the user to the returned authorization url, from which the
+   * provider will redirect them to the configured `redirect_url`.
+   * The response will set a short-lived httpOnly cookie binding the login
+   * to the browser, which must be sent with the `oidc_login` request
+   */
+  #[serde(default="ApiRequests::def_oidc_login_start")]
+  pub oidc_login_start: HttpReq<(), OidcLoginStartResp>,
+
+  /**
+   * Complete a login with the OpenID Connect provider, using the `code`
+   * and `state` query parameters of its redirect back to the app.
+   * The response will set an httpOnly cookie containing the refresh token
+   */
+  #[serde(default="ApiRequests::def_oidc_login")]
+  pub oidc_login: HttpReq<OidcLoginReq, OidcLoginResp>,
+
   /**
    * Get a refreshed access token
    * If the refresh token is not provided in the request body, then it will
    * be read from the refrestToken cookie in the request.
+   * Refresh tokens are single use: a new refresh token is returned (and set
+   * in the cookie) with each access token. Reuse of a refresh token revokes
+   * every refresh token descended from the same login.
+   * Requests that use the cookie must pass the CSRF checks configured by
+   * `csrf` in the server config.
    */
   #[serde(default="ApiRequests::def_refresh")]
   pub refresh: HttpReq<RefreshReq, RefreshResp>,
 
   /**
-   * Clear the `refreshToken` cookie.
+   * Revoke the refresh token, and clear the `refreshToken` cookie.
+   * If the refresh token is not provided in the request body, then it will
+   * be read from the refrestToken cookie in the request.
+   * As for `refresh`, requests that use the cookie are CSRF checked.
    */
   #[serde(default="ApiRequests::def_logout")]
-  pub logout: HttpReq<Unit, Unit>,
+  pub logout: HttpReq<LogoutReq, Unit>,
+
+  /**
+   * Request a password reset link be emailed to a user.
+   * To avoid revealing which email addresses have accounts, this
+   * succeeds whether or not the email address is known.
+   */
+  #[serde(default="ApiRequests::def_request_password_reset")]
+  pub request_password_reset: HttpReq<RequestPasswordResetReq, Unit>,
+
+  /**
+   * Set a new password, using the token from a password reset link.
+   * Each token can only be used once. A successful reset revokes all
+   * of the user's refresh tokens.
+   */
+  #[serde(default="ApiRequests::def_reset_password")]
+  pub reset_password: HttpReq<ResetPasswordReq, ResetPasswordResp>,
+
+  /**
+   * Create an account, using the token from an invitation email. The
+   * invitation can only be used once, and the new user's email address
+   * counts as verified.
+   * The response will set an httpOnly cookie containing the refresh token
+   */
+  #[serde(default="ApiRequests::def_signup")]
+  pub signup: HttpReq<SignupReq, SignupResp>,
+
+  /**
+   * Verify a user's email address, using the token from the link
+   * emailed to them.
+   */
+  #[serde(default="ApiRequests::def_verify_email")]
+  pub verify_email: HttpReq<VerifyEmailReq, VerifyEmailResp>,
+
+  /**
+   * Resend the email address verification link to an unverified user.
+   * Links are sent at most once every `email_verification_resend_secs`.
+   * To avoid revealing which email addresses have accounts, this
+   * succeeds whether or not a link was sent.
+   */
+  #[serde(default="ApiRequests::def_resend_verification_email")]
+  pub resend_verification_email: HttpReq<ResendVerificationEmailReq, Unit>,
+
+  /**
+   * Start enrolling the user in TOTP two factor authentication,
+   * replacing any unconfirmed enrolment. The enrolment takes effect
+   * once confirmed with `confirm_totp_enrolment`.
+   */
+  #[serde(default="ApiRequests::def_begin_totp_enrolment")]
+  pub begin_totp_enrolment: HttpReq<(), BeginTotpEnrolmentResp>,
+
+  /**
+   * Confirm a TOTP enrolment with a first code from the user's
+   * authenticator app. The returned recovery codes can each be used
+   * once in place of a code, and are not retrievable later.
+   */
+  #[serde(default="ApiRequests::def_confirm_totp_enrolment")]
+  pub confirm_totp_enrolment: HttpReq<ConfirmTotpEnrolmentReq, ConfirmTotpEnrolmentResp>,
 
   /**
    * Post a message to the noticeboard
@@ -50,49 +159,335 @@ pub struct ApiRequests {
   pub new_message: HttpReq<NewMessageReq, MessageId>,
 
   /**
-   * Get recent noticeboard messages
+   * Get recent top level noticeboard messages, posted within the
+   * caller's organisation
    */
   #[serde(default="ApiRequests::def_recent_messages")]
   pub recent_messages: HttpReq<RecentMessagesReq, Paginated<Message>>,
 
+  /**
+   * Reply to a top level message, starting or continuing its thread
+   */
+  #[serde(default="ApiRequests::def_reply_to_message")]
+  pub reply_to_message: HttpReq<ReplyToMessageReq, ReplyToMessageResp>,
+
+  /**
+   * Get a top level message, and a page of its replies in the order they
+   * were posted
+   */
+  #[serde(default="ApiRequests::def_message_thread")]
+  pub message_thread: HttpReq<MessageThreadReq, MessageThreadResp>,
+
+  /**
+   * React to a message with an emoji. Reacting again with the same
+   * emoji has no effect.
+   */
+  #[serde(default="ApiRequests::def_add_reaction")]
+  pub add_reaction: HttpReq<MessageReactionReq, AddReactionResp>,
+
+  /**
+   * Remove the caller's reaction to a message
+   */
+  #[serde(default="ApiRequests::def_remove_reaction")]
+  pub remove_reaction: HttpReq<MessageReactionReq, Unit>,
+
+  /**
+   * Edit the text of a message. Users can edit their own messages, and
+   * admins can edit any message. The prior text is kept as a revision.
+   */
+  #[serde(default="ApiRequests::def_edit_message")]
+  pub edit_message: HttpReq<EditMessageReq, EditMessageResp>,
+
+  /**
+   * Delete a message, leaving a tombstone in its place. Users can delete
+   * their own messages, and admins can delete any message.
+   */
+  #[serde(default="ApiRequests::def_delete_message")]
+  pub delete_message: HttpReq<MessageId, DeleteMessageResp>,
+
+  /**
+   * Get the prior versions of a message, most recent first. Available to
+   * the message's author and admins.
+   */
+  #[serde(default="ApiRequests::def_message_revisions")]
+  pub message_revisions: HttpReq<MessageId, MessageRevisionsResp>,
+
   /**
    * Gets info about the logged in user
    */
   #[serde(default="ApiRequests::def_who_am_i")]
   pub who_am_i: HttpReq<(), UserWithId>,
 
+  /**
+   * Change the logged in user's name and email address. A changed
+   * email address needs the current password, and to be verified again.
+   * Not available to admins impersonating the user.
+   */
+  #[serde(default="ApiRequests::def_update_profile")]
+  pub update_profile: HttpReq<UpdateProfileReq, UpdateProfileResp>,
+
+  /**
+   * Change the logged in user's password. This revokes all of the
+   * user's other sessions.
+   */
+  #[serde(default="ApiRequests::def_change_password")]
+  pub change_password: HttpReq<ChangePasswordReq, ChangePasswordResp>,
+
+  /**
+   * List the logged in user's active sessions, most recently
+   * refreshed first
+   */
+  #[serde(default="ApiRequests::def_my_sessions")]
+  pub my_sessions: HttpReq<(), Vec<SessionSummaryWithId>>,
+
+  /**
+   * Revoke one of the logged in user's sessions, so that it can no
+   * longer be refreshed. Access tokens already issued for the session
+   * remain valid until they expire.
+   */
+  #[serde(default="ApiRequests::def_revoke_session")]
+  pub revoke_session: HttpReq<SessionId, Unit>,
+
+  /**
+   * Revoke all of the logged in user's sessions, other than the one
+   * making the request
+   */
+  #[serde(default="ApiRequests::def_revoke_other_sessions")]
+  pub revoke_other_sessions: HttpReq<(), Unit>,
+
   /**
    * Create a new user
    */
   #[serde(default="ApiRequests::def_create_user")]
-  pub create_user: HttpReq<UserDetails, AppUserId>,
+  pub create_user: HttpReq<UserDetails, CreateUserResp>,
 
   /**
    * Update a user
    */
   #[serde(default="ApiRequests::def_update_user")]
-  pub update_user: HttpReq<WithId<AppUserId, UserDetails>, Unit>,
+  pub update_user: HttpReq<WithId<AppUserId, UserDetails>, UpdateUserResp>,
+
+  /**
+   * Unlock a user's account after too many failed logins
+   */
+  #[serde(default="ApiRequests::def_unlock_user")]
+  pub unlock_user: HttpReq<AppUserId, Unit>,
+
+  /**
+   * Issue a short lived access token with which an admin can act as
+   * a user. The token has the user's `sub` and an `act` claim with the
+   * admin's. It is refused by admin endpoints, and every request made
+   * with it is written to the audit log.
+   */
+  #[serde(default="ApiRequests::def_impersonate_user")]
+  pub impersonate_user: HttpReq<AppUserId, ImpersonateUserResp>,
+
+  /**
+   * Revoke every session of a user, forcing them to login again once
+   * their current access tokens expire
+   */
+  #[serde(default="ApiRequests::def_revoke_user_sessions")]
+  pub revoke_user_sessions: HttpReq<AppUserId, Unit>,
+
+  /**
+   * Deactivate a user, revoking their sessions and refusing their
+   * access tokens and API keys. Their messages are kept.
+   */
+  #[serde(default="ApiRequests::def_deactivate_user")]
+  pub deactivate_user: HttpReq<AppUserId, DeactivateUserResp>,
+
+  /**
+   * Reactivate a deactivated user, who can then login again
+   */
+  #[serde(default="ApiRequests::def_reactivate_user")]
+  pub reactivate_user: HttpReq<AppUserId, ReactivateUserResp>,
+
+  /**
+   * Replace a user's roles. Access tokens carry the user's roles, so
+   * the change takes effect as their tokens are refreshed.
+   */
+  #[serde(default="ApiRequests::def_set_user_roles")]
+  pub set_user_roles: HttpReq<SetUserRolesReq, SetUserRolesResp>,
+
+  /**
+   * Create a role, whose members are granted its permissions
+   */
+  #[serde(default="ApiRequests::def_create_role")]
+  pub create_role: HttpReq<CreateRoleReq, CreateRoleResp>,
+
+  /**
+   * Query roles, ordered by name
+   */
+  #[serde(default="ApiRequests::def_query_roles")]
+  pub query_roles: HttpReq<QueryRolesReq, Paginated<RoleDetailsWithId>>,
+
+  /**
+   * Replace the permissions granted by a role
+   */
+  #[serde(default="ApiRequests::def_set_role_permissions")]
+  pub set_role_permissions: HttpReq<SetRolePermissionsReq, SetRolePermissionsResp>,
+
+  /**
+   * Delete a role, removing it from its members
+   */
+  #[serde(default="ApiRequests::def_delete_role")]
+  pub delete_role: HttpReq<RoleId, DeleteRoleResp>,
+
+  /**
+   * Create an organisation
+   */
+  #[serde(default="ApiRequests::def_create_organisation")]
+  pub create_organisation: HttpReq<CreateOrganisationReq, CreateOrganisationResp>,
+
+  /**
+   * Query organisations, ordered by name
+   */
+  #[serde(default="ApiRequests::def_query_organisations")]
+  pub query_organisations: HttpReq<QueryOrganisationsReq, Paginated<OrganisationWithId>>,
+
+  /**
+   * Add a user to an organisation, or replace the roles they hold
+   * within it. The admin role can't be granted per organisation.
+   */
+  #[serde(default="ApiRequests::def_set_organisation_member")]
+  pub set_organisation_member: HttpReq<SetOrganisationMemberReq, SetOrganisationMemberResp>,
+
+  /**
+   * Remove a user from an organisation
+   */
+  #[serde(default="ApiRequests::def_remove_organisation_member")]
+  pub remove_organisation_member: HttpReq<RemoveOrganisationMemberReq, Unit>,
+
+  /**
+   * Switch the organisation that the logged in session acts within,
+   * returning an access token with the new `org` claim. Members can
+   * switch to their organisations, and admins to any, or to none to
+   * act across the whole deployment.
+   */
+  #[serde(default="ApiRequests::def_switch_organisation")]
+  pub switch_organisation: HttpReq<SwitchOrganisationReq, SwitchOrganisationResp>,
+
+  /**
+   * Invite someone to create their own account, by emailing them a
+   * signup link
+   */
+  #[serde(default="ApiRequests::def_create_invitation")]
+  pub create_invitation: HttpReq<CreateInvitationReq, CreateInvitationResp>,
+
+  /**
+   * Query invitations, most recently created first
+   */
+  #[serde(default="ApiRequests::def_query_invitations")]
+  pub query_invitations: HttpReq<QueryInvitationsReq, Paginated<InvitationSummaryWithId>>,
+
+  /**
+   * Revoke an unused invitation, so that it can no longer be used to
+   * sign up
+   */
+  #[serde(default="ApiRequests::def_revoke_invitation")]
+  pub revoke_invitation: HttpReq<InvitationId, Unit>,
 
   /**
-   * Query users
+   * Query users. Admins acting within an organisation only see its
+   * members.
    */
   #[serde(default="ApiRequests::def_query_users")]
   pub query_users: HttpReq<QueryUsersReq, Paginated<UserWithId>>,
+
+  /**
+   * Get the number of access and refresh tokens that have been
+   * rejected since the server started, by reason
+   */
+  #[serde(default="ApiRequests::def_jwt_failure_counts")]
+  pub jwt_failure_counts: HttpReq<(), JwtFailureCounts>,
+
+  /**
+   * Issue an API key, with which a service account can call endpoints
+   * secured by `token` or `tokenWithRole`, by passing it as a bearer
+   * token. The key itself is only returned by this call.
+   */
+  #[serde(default="ApiRequests::def_create_api_key")]
+  pub create_api_key: HttpReq<CreateApiKeyReq, CreateApiKeyResp>,
+
+  /**
+   * Query API keys, most recently created first
+   */
+  #[serde(default="ApiRequests::def_query_api_keys")]
+  pub query_api_keys: HttpReq<QueryApiKeysReq, Paginated<ApiKeySummaryWithId>>,
+
+  /**
+   * Revoke an API key, so that it can no longer be used
+   */
+  #[serde(default="ApiRequests::def_revoke_api_key")]
+  pub revoke_api_key: HttpReq<ApiKeyId, Unit>,
+
+  /**
+   * Query the audit log of calls to admin only endpoints
+   * Results are ordered most recent first.
+   */
+  #[serde(default="ApiRequests::def_query_audit_log")]
+  pub query_audit_log: HttpReq<QueryAuditLogReq, Paginated<AuditLogEntry>>,
 }
 
 impl ApiRequests {
   pub fn new() -> ApiRequests {
     ApiRequests {
       healthy: ApiRequests::def_healthy(),
+      jwks: ApiRequests::def_jwks(),
       login: ApiRequests::def_login(),
+      login_totp: ApiRequests::def_login_totp(),
+      oidc_login_start: ApiRequests::def_oidc_login_start(),
+      oidc_login: ApiRequests::def_oidc_login(),
       refresh: ApiRequests::def_refresh(),
       logout: ApiRequests::def_logout(),
+      request_password_reset: ApiRequests::def_request_password_reset(),
+      reset_password: ApiRequests::def_reset_password(),
+      signup: ApiRequests::def_signup(),
+      verify_email: ApiRequests::def_verify_email(),
+      resend_verification_email: ApiRequests::def_resend_verification_email(),
+      begin_totp_enrolment: ApiRequests::def_begin_totp_enrolment(),
+      confirm_totp_enrolment: ApiRequests::def_confirm_totp_enrolment(),
       new_message: ApiRequests::def_new_message(),
       recent_messages: ApiRequests::def_recent_messages(),
+      reply_to_message: ApiRequests::def_reply_to_message(),
+      message_thread: ApiRequests::def_message_thread(),
+      add_reaction: ApiRequests::def_add_reaction(),
+      remove_reaction: ApiRequests::def_remove_reaction(),
+      edit_message: ApiRequests::def_edit_message(),
+      delete_message: ApiRequests::def_delete_message(),
+      message_revisions: ApiRequests::def_message_revisions(),
       who_am_i: ApiRequests::def_who_am_i(),
+      update_profile: ApiRequests::def_update_profile(),
+      change_password: ApiRequests::def_change_password(),
+      my_sessions: ApiRequests::def_my_sessions(),
+      revoke_session: ApiRequests::def_revoke_session(),
+      revoke_other_sessions: ApiRequests::def_revoke_other_sessions(),
       create_user: ApiRequests::def_create_user(),
       update_user: ApiRequests::def_update_user(),
+      unlock_user: ApiRequests::def_unlock_user(),
+      impersonate_user: ApiRequests::def_impersonate_user(),
+      revoke_user_sessions: ApiRequests::def_revoke_user_sessions(),
+      deactivate_user: ApiRequests::def_deactivate_user(),
+      reactivate_user: ApiRequests::def_reactivate_user(),
+      set_user_roles: ApiRequests::def_set_user_roles(),
+      create_role: ApiRequests::def_create_role(),
+      query_roles: ApiRequests::def_query_roles(),
+      set_role_permissions: ApiRequests::def_set_role_permissions(),
+      delete_role: ApiRequests::def_delete_role(),
+      create_organisation: ApiRequests::def_create_organisation(),
+      query_organisations: ApiRequests::def_query_organisations(),
+      set_organisation_member: ApiRequests::def_set_organisation_member(),
+      remove_organisation_member: ApiRequests::def_remove_organisation_member(),
+      switch_organisation: ApiRequests::def_switch_organisation(),
+      create_invitation: ApiRequests::def_create_invitation(),
+      query_invitations: ApiRequests::def_query_invitations(),
+      revoke_invitation: ApiRequests::def_revoke_invitation(),
       query_users: ApiRequests::def_query_users(),
+      jwt_failure_counts: ApiRequests::def_jwt_failure_counts(),
+      create_api_key: ApiRequests::def_create_api_key(),
+      query_api_keys: ApiRequests::def_query_api_keys(),
+      revoke_api_key: ApiRequests::def_revoke_api_key(),
+      query_audit_log: ApiRequests::def_query_audit_log(),
     }
   }
 
@@ -100,16 +495,60 @@ impl ApiRequests {
     HttpReq::<(), ()>{method : HttpMethod::Get, path : "/".to_string(), security : HttpSecurity::Public, req_type : std::marker::PhantomData, resp_type : std::marker::PhantomData}
   }
 
+  pub fn def_jwks() -> HttpReq<(), Jwks> {
+    HttpReq::<(), Jwks>{method : HttpMethod::Get, path : "/.well-known/jwks.json".to_string(), security : HttpSecurity::Public, req_type : std::marker::PhantomData, resp_type : std::marker::PhantomData}
+  }
+
   pub fn def_login() -> HttpReq<LoginReq, LoginResp> {
     HttpReq::<LoginReq, LoginResp>{method : HttpMethod::Post, path : "/login".to_string(), security : HttpSecurity::Public, req_type : std::marker::PhantomData, resp_type : std::marker::PhantomData}
   }
 
+  pub fn def_login_totp() -> HttpReq<LoginTotpReq, LoginTotpResp> {
+    HttpReq::<LoginTotpReq, LoginTotpResp>{method : HttpMethod::Post, path : "/login/totp".to_string(), security : HttpSecurity::Public, req_type : std::marker::PhantomData, resp_type : std::marker::PhantomData}
+  }
+
+  pub fn def_oidc_login_start() -> HttpReq<(), OidcLoginStartResp> {
+    HttpReq::<(), OidcLoginStartResp>{method : HttpMethod::Post, path : "/oidc/start".to_string(), security : HttpSecurity::Public, req_type : std::marker::PhantomData, resp_type : std::marker::PhantomData}
+  }
+
+  pub fn def_oidc_login() -> HttpReq<OidcLoginReq, OidcLoginResp> {
+    HttpReq::<OidcLoginReq, OidcLoginResp>{method : HttpMethod::Post, path : "/oidc/login".to_string(), security : HttpSecurity::Public, req_type : std::marker::PhantomData, resp_type : std::marker::PhantomData}
+  }
+
   pub fn def_refresh() -> HttpReq<RefreshReq, RefreshResp> {
     HttpReq::<RefreshReq, RefreshResp>{method : HttpMethod::Post, path : "/refresh".to_string(), security : HttpSecurity::Public, req_type : std::marker::PhantomData, resp_type : std::marker::PhantomData}
   }
 
-  pub fn def_logout() -> HttpReq<Unit, Unit> {
-    HttpReq::<Unit, Unit>{method : HttpMethod::Post, path : "/logout".to_string(), security : HttpSecurity::Public, req_type : std::marker::PhantomData, resp_type : std::marker::PhantomData}
+  pub fn def_logout() -> HttpReq<LogoutReq, Unit> {
+    HttpReq::<LogoutReq, Unit>{method : HttpMethod::Post, path : "/logout".to_string(), security : HttpSecurity::Public, req_type : std::marker::PhantomData, resp_type : std::marker::PhantomData}
+  }
+
+  pub fn def_request_password_reset() -> HttpReq<RequestPasswordResetReq, Unit> {
+    HttpReq::<RequestPasswordResetReq, Unit>{method : HttpMethod::Post, path : "/password-reset/request".to_string(), security : HttpSecurity::Public, req_type : std::marker::PhantomData, resp_type : std::marker::PhantomData}
+  }
+
+  pub fn def_reset_password() -> HttpReq<ResetPasswordReq, ResetPasswordResp> {
+    HttpReq::<ResetPasswordReq, ResetPasswordResp>{method : HttpMethod::Post, path : "/password-reset/complete".to_string(), security : HttpSecurity::Public, req_type : std::marker::PhantomData, resp_type : std::marker::PhantomData}
+  }
+
+  pub fn def_signup() -> HttpReq<SignupReq, SignupResp> {
+    HttpReq::<SignupReq, SignupResp>{method : HttpMethod::Post, path : "/signup".to_string(), security : HttpSecurity::Public, req_type : std::marker::PhantomData, resp_type : std::marker::PhantomData}
+  }
+
+  pub fn def_verify_email() -> HttpReq<VerifyEmailReq, VerifyEmailResp> {
+    HttpReq::<VerifyEmailReq, VerifyEmailResp>{method : HttpMethod::Post, path : "/email/verify".to_string(), security : HttpSecurity::Public, req_type : std::marker::PhantomData, resp_type : std::marker::PhantomData}
+  }
+
+  pub fn def_resend_verification_email() -> HttpReq<ResendVerificationEmailReq, Unit> {
+    HttpReq::<ResendVerificationEmailReq, Unit>{method : HttpMethod::Post, path : "/email/resend-verification".to_string(), security : HttpSecurity::Public, req_type : std::marker::PhantomData, resp_type : std::marker::PhantomData}
+  }
+
+  pub fn def_begin_totp_enrolment() -> HttpReq<(), BeginTotpEnrolmentResp> {
+    HttpReq::<(), BeginTotpEnrolmentResp>{method : HttpMethod::Post, path : "/totp/enrol".to_string(), security : HttpSecurity::Token, req_type : std::marker::PhantomData, resp_type : std::marker::PhantomData}
+  }
+
+  pub fn def_confirm_totp_enrolment() -> HttpReq<ConfirmTotpEnrolmentReq, ConfirmTotpEnrolmentResp> {
+    HttpReq::<ConfirmTotpEnrolmentReq, ConfirmTotpEnrolmentResp>{method : HttpMethod::Post, path : "/totp/confirm".to_string(), security : HttpSecurity::Token, req_type : std::marker::PhantomData, resp_type : std::marker::PhantomData}
   }
 
   pub fn def_new_message() -> HttpReq<NewMessageReq, MessageId> {
@@ -120,21 +559,177 @@ impl ApiRequests {
     HttpReq::<RecentMessagesReq, Paginated<Message>>{method : HttpMethod::Get, path : "/messages/recent".to_string(), security : HttpSecurity::Token, req_type : std::marker::PhantomData, resp_type : std::marker::PhantomData}
   }
 
+  pub fn def_reply_to_message() -> HttpReq<ReplyToMessageReq, ReplyToMessageResp> {
+    HttpReq::<ReplyToMessageReq, ReplyToMessageResp>{method : HttpMethod::Post, path : "/messages/reply".to_string(), security : HttpSecurity::Token, req_type : std::marker::PhantomData, resp_type : std::marker::PhantomData}
+  }
+
+  pub fn def_message_thread() -> HttpReq<MessageThreadReq, MessageThreadResp> {
+    HttpReq::<MessageThreadReq, MessageThreadResp>{method : HttpMethod::Get, path : "/messages/thread".to_string(), security : HttpSecurity::Token, req_type : std::marker::PhantomData, resp_type : std::marker::PhantomData}
+  }
+
+  pub fn def_add_reaction() -> HttpReq<MessageReactionReq, AddReactionResp> {
+    HttpReq::<MessageReactionReq, AddReactionResp>{method : HttpMethod::Post, path : "/messages/reactions/add".to_string(), security : HttpSecurity::Token, req_type : std::marker::PhantomData, resp_type : std::marker::PhantomData}
+  }
+
+  pub fn def_remove_reaction() -> HttpReq<MessageReactionReq, Unit> {
+    HttpReq::<MessageReactionReq, Unit>{method : HttpMethod::Post, path : "/messages/reactions/remove".to_string(), security : HttpSecurity::Token, req_type : std::marker::PhantomData, resp_type : std::marker::PhantomData}
+  }
+
+  pub fn def_edit_message() -> HttpReq<EditMessageReq, EditMessageResp> {
+    HttpReq::<EditMessageReq, EditMessageResp>{method : HttpMethod::Post, path : "/messages/edit".to_string(), security : HttpSecurity::Token, req_type : std::marker::PhantomData, resp_type : std::marker::PhantomData}
+  }
+
+  pub fn def_delete_message() -> HttpReq<MessageId, DeleteMessageResp> {
+    HttpReq::<MessageId, DeleteMessageResp>{method : HttpMethod::Post, path : "/messages/delete".to_string(), security : HttpSecurity::Token, req_type : std::marker::PhantomData, resp_type : std::marker::PhantomData}
+  }
+
+  pub fn def_message_revisions() -> HttpReq<MessageId, MessageRevisionsResp> {
+    HttpReq::<MessageId, MessageRevisionsResp>{method : HttpMethod::Get, path : "/messages/revisions".to_string(), security : HttpSecurity::Token, req_type : std::marker::PhantomData, resp_type : std::marker::PhantomData}
+  }
+
   pub fn def_who_am_i() -> HttpReq<(), UserWithId> {
     HttpReq::<(), UserWithId>{method : HttpMethod::Get, path : "/whoami".to_string(), security : HttpSecurity::Token, req_type : std::marker::PhantomData, resp_type : std::marker::PhantomData}
   }
 
-  pub fn def_create_user() -> HttpReq<UserDetails, AppUserId> {
-    HttpReq::<UserDetails, AppUserId>{method : HttpMethod::Post, path : "/users/create".to_string(), security : HttpSecurity::TokenWithRole("admin".to_string()), req_type : std::marker::PhantomData, resp_type : std::marker::PhantomData}
+  pub fn def_update_profile() -> HttpReq<UpdateProfileReq, UpdateProfileResp> {
+    HttpReq::<UpdateProfileReq, UpdateProfileResp>{method : HttpMethod::Post, path : "/profile/update".to_string(), security : HttpSecurity::Token, req_type : std::marker::PhantomData, resp_type : std::marker::PhantomData}
+  }
+
+  pub fn def_change_password() -> HttpReq<ChangePasswordReq, ChangePasswordResp> {
+    HttpReq::<ChangePasswordReq, ChangePasswordResp>{method : HttpMethod::Post, path : "/profile/change-password".to_string(), security : HttpSecurity::Token, req_type : std::marker::PhantomData, resp_type : std::marker::PhantomData}
+  }
+
+  pub fn def_my_sessions() -> HttpReq<(), Vec<SessionSummaryWithId>> {
+    HttpReq::<(), Vec<SessionSummaryWithId>>{method : HttpMethod::Get, path : "/sessions".to_string(), security : HttpSecurity::Token, req_type : std::marker::PhantomData, resp_type : std::marker::PhantomData}
+  }
+
+  pub fn def_revoke_session() -> HttpReq<SessionId, Unit> {
+    HttpReq::<SessionId, Unit>{method : HttpMethod::Post, path : "/sessions/revoke".to_string(), security : HttpSecurity::Token, req_type : std::marker::PhantomData, resp_type : std::marker::PhantomData}
+  }
+
+  pub fn def_revoke_other_sessions() -> HttpReq<(), Unit> {
+    HttpReq::<(), Unit>{method : HttpMethod::Post, path : "/sessions/revoke-others".to_string(), security : HttpSecurity::Token, req_type : std::marker::PhantomData, resp_type : std::marker::PhantomData}
+  }
+
+  pub fn def_create_user() -> HttpReq<UserDetails, CreateUserResp> {
+    HttpReq::<UserDetails, CreateUserResp>{method : HttpMethod::Post, path : "/users/create".to_string(), security : HttpSecurity::TokenWithRole("admin".to_string()), req_type : std::marker::PhantomData, resp_type : std::marker::PhantomData}
+  }
+
+  pub fn def_update_user() -> HttpReq<WithId<AppUserId, UserDetails>, UpdateUserResp> {
+    HttpReq::<WithId<AppUserId, UserDetails>, UpdateUserResp>{method : HttpMethod::Post, path : "/users/update".to_string(), security : HttpSecurity::TokenWithRole("admin".to_string()), req_type : std::marker::PhantomData, resp_type : std::marker::PhantomData}
   }
 
-  pub fn def_update_user() -> HttpReq<WithId<AppUserId, UserDetails>, Unit> {
-    HttpReq::<WithId<AppUserId, UserDetails>, Unit>{method : HttpMethod::Post, path : "/users/update".to_string(), security : HttpSecurity::TokenWithRole("admin".to_string()), req_type : std::marker::PhantomData, resp_type : std::marker::PhantomData}
+  pub fn def_unlock_user() -> HttpReq<AppUserId, Unit> {
+    HttpReq::<AppUserId, Unit>{method : HttpMethod::Post, path : "/users/unlock".to_string(), security : HttpSecurity::TokenWithRole("admin".to_string()), req_type : std::marker::PhantomData, resp_type : std::marker::PhantomData}
+  }
+
+  pub fn def_impersonate_user() -> HttpReq<AppUserId, ImpersonateUserResp> {
+    HttpReq::<AppUserId, ImpersonateUserResp>{method : HttpMethod::Post, path : "/users/impersonate".to_string(), security : HttpSecurity::TokenWithRole("admin".to_string()), req_type : std::marker::PhantomData, resp_type : std::marker::PhantomData}
+  }
+
+  pub fn def_revoke_user_sessions() -> HttpReq<AppUserId, Unit> {
+    HttpReq::<AppUserId, Unit>{method : HttpMethod::Post, path : "/users/revoke-sessions".to_string(), security : HttpSecurity::TokenWithRole("admin".to_string()), req_type : std::marker::PhantomData, resp_type : std::marker::PhantomData}
+  }
+
+  pub fn def_deactivate_user() -> HttpReq<AppUserId, DeactivateUserResp> {
+    HttpReq::<AppUserId, DeactivateUserResp>{method : HttpMethod::Post, path : "/users/deactivate".to_string(), security : HttpSecurity::TokenWithRole("admin".to_string()), req_type : std::marker::PhantomData, resp_type : std::marker::PhantomData}
+  }
+
+  pub fn def_reactivate_user() -> HttpReq<AppUserId, ReactivateUserResp> {
+    HttpReq::<AppUserId, ReactivateUserResp>{method : HttpMethod::Post, path : "/users/reactivate".to_string(), security : HttpSecurity::TokenWithRole("admin".to_string()), req_type : std::marker::PhantomData, resp_type : std::marker::PhantomData}
+  }
+
+  pub fn def_set_user_roles() -> HttpReq<SetUserRolesReq, SetUserRolesResp> {
+    HttpReq::<SetUserRolesReq, SetUserRolesResp>{method : HttpMethod::Post, path : "/users/set-roles".to_string(), security : HttpSecurity::TokenWithRole("admin".to_string()), req_type : std::marker::PhantomData, resp_type : std::marker::PhantomData}
+  }
+
+  pub fn def_create_role() -> HttpReq<CreateRoleReq, CreateRoleResp> {
+    HttpReq::<CreateRoleReq, CreateRoleResp>{method : HttpMethod::Post, path : "/roles/create".to_string(), security : HttpSecurity::TokenWithRole("admin".to_string()), req_type : std::marker::PhantomData, resp_type : std::marker::PhantomData}
+  }
+
+  pub fn def_query_roles() -> HttpReq<QueryRolesReq, Paginated<RoleDetailsWithId>> {
+    HttpReq::<QueryRolesReq, Paginated<RoleDetailsWithId>>{method : HttpMethod::Get, path : "/roles/query".to_string(), security : HttpSecurity::TokenWithRole("admin".to_string()), req_type : std::marker::PhantomData, resp_type : std::marker::PhantomData}
+  }
+
+  pub fn def_set_role_permissions() -> HttpReq<SetRolePermissionsReq, SetRolePermissionsResp> {
+    HttpReq::<SetRolePermissionsReq, SetRolePermissionsResp>{method : HttpMethod::Post, path : "/roles/set-permissions".to_string(), security : HttpSecurity::TokenWithRole("admin".to_string()), req_type : std::marker::PhantomData, resp_type : std::marker::PhantomData}
+  }
+
+  pub fn def_delete_role() -> HttpReq<RoleId, DeleteRoleResp> {
+    HttpReq::<RoleId, DeleteRoleResp>{method : HttpMethod::Post, path : "/roles/delete".to_string(), security : HttpSecurity::TokenWithRole("admin".to_string()), req_type : std::marker::PhantomData, resp_type : std::marker::PhantomData}
+  }
+
+  pub fn def_create_organisation() -> HttpReq<CreateOrganisationReq, CreateOrganisationResp> {
+    HttpReq::<CreateOrganisationReq, CreateOrganisationResp>{method : HttpMethod::Post, path : "/organisations/create".to_string(), security : HttpSecurity::TokenWithRole("admin".to_string()), req_type : std::marker::PhantomData, resp_type : std::marker::PhantomData}
+  }
+
+  pub fn def_query_organisations() -> HttpReq<QueryOrganisationsReq, Paginated<OrganisationWithId>> {
+    HttpReq::<QueryOrganisationsReq, Paginated<OrganisationWithId>>{method : HttpMethod::Get, path : "/organisations/query".to_string(), security : HttpSecurity::TokenWithRole("admin".to_string()), req_type : std::marker::PhantomData, resp_type : std::marker::PhantomData}
+  }
+
+  pub fn def_set_organisation_member() -> HttpReq<SetOrganisationMemberReq, SetOrganisationMemberResp> {
+    HttpReq::<SetOrganisationMemberReq, SetOrganisationMemberResp>{method : HttpMethod::Post, path : "/organisations/set-member".to_string(), security : HttpSecurity::TokenWithRole("admin".to_string()), req_type : std::marker::PhantomData, resp_type : std::marker::PhantomData}
+  }
+
+  pub fn def_remove_organisation_member() -> HttpReq<RemoveOrganisationMemberReq, Unit> {
+    HttpReq::<RemoveOrganisationMemberReq, Unit>{method : HttpMethod::Post, path : "/organisations/remove-member".to_string(), security : HttpSecurity::TokenWithRole("admin".to_string()), req_type : std::marker::PhantomData, resp_type : std::marker::PhantomData}
+  }
+
+  pub fn def_switch_organisation() -> HttpReq<SwitchOrganisationReq, SwitchOrganisationResp> {
+    HttpReq::<SwitchOrganisationReq, SwitchOrganisationResp>{method : HttpMethod::Post, path : "/organisations/switch".to_string(), security : HttpSecurity::Token, req_type : std::marker::PhantomData, resp_type : std::marker::PhantomData}
+  }
+
+  pub fn def_create_invitation() -> HttpReq<CreateInvitationReq, CreateInvitationResp> {
+    HttpReq::<CreateInvitationReq, CreateInvitationResp>{method : HttpMethod::Post, path : "/invitations/create".to_string(), security : HttpSecurity::TokenWithRole("admin".to_string()), req_type : std::marker::PhantomData, resp_type : std::marker::PhantomData}
+  }
+
+  pub fn def_query_invitations() -> HttpReq<QueryInvitationsReq, Paginated<InvitationSummaryWithId>> {
+    HttpReq::<QueryInvitationsReq, Paginated<InvitationSummaryWithId>>{method : HttpMethod::Get, path : "/invitations/query".to_string(), security : HttpSecurity::TokenWithRole("admin".to_string()), req_type : std::marker::PhantomData, resp_type : std::marker::PhantomData}
+  }
+
+  pub fn def_revoke_invitation() -> HttpReq<InvitationId, Unit> {
+    HttpReq::<InvitationId, Unit>{method : HttpMethod::Post, path : "/invitations/revoke".to_string(), security : HttpSecurity::TokenWithRole("admin".to_string()), req_type : std::marker::PhantomData, resp_type : std::marker::PhantomData}
   }
 
   pub fn def_query_users() -> HttpReq<QueryUsersReq, Paginated<UserWithId>> {
     HttpReq::<QueryUsersReq, Paginated<UserWithId>>{method : HttpMethod::Get, path : "/users/query".to_string(), security : HttpSecurity::TokenWithRole("admin".to_string()), req_type : std::marker::PhantomData, resp_type : std::marker::PhantomData}
   }
+
+  pub fn def_jwt_failure_counts() -> HttpReq<(), JwtFailureCounts> {
+    HttpReq::<(), JwtFailureCounts>{method : HttpMethod::Get, path : "/auth/jwt-failures".to_string(), security : HttpSecurity::TokenWithRole("admin".to_string()), req_type : std::marker::PhantomData, resp_type : std::marker::PhantomData}
+  }
+
+  pub fn def_create_api_key() -> HttpReq<CreateApiKeyReq, CreateApiKeyResp> {
+    HttpReq::<CreateApiKeyReq, CreateApiKeyResp>{method : HttpMethod::Post, path : "/api-keys/create".to_string(), security : HttpSecurity::TokenWithRole("admin".to_string()), req_type : std::marker::PhantomData, resp_type : std::marker::PhantomData}
+  }
+
+  pub fn def_query_api_keys() -> HttpReq<QueryApiKeysReq, Paginated<ApiKeySummaryWithId>> {
+    HttpReq::<QueryApiKeysReq, Paginated<ApiKeySummaryWithId>>{method : HttpMethod::Get, path : "/api-keys/query".to_string(), security : HttpSecurity::TokenWithRole("admin".to_string()), req_type : std::marker::PhantomData, resp_type : std::marker::PhantomData}
+  }
+
+  pub fn def_revoke_api_key() -> HttpReq<ApiKeyId, Unit> {
+    HttpReq::<ApiKeyId, Unit>{method : HttpMethod::Post, path : "/api-keys/revoke".to_string(), security : HttpSecurity::TokenWithRole("admin".to_string()), req_type : std::marker::PhantomData, resp_type : std::marker::PhantomData}
+  }
+
+  pub fn def_query_audit_log() -> HttpReq<QueryAuditLogReq, Paginated<AuditLogEntry>> {
+    HttpReq::<QueryAuditLogReq, Paginated<AuditLogEntry>>{method : HttpMethod::Get, path : "/audit/query".to_string(), security : HttpSecurity::TokenWithRole("admin".to_string()), req_type : std::marker::PhantomData, resp_type : std::marker::PhantomData}
+  }
+}
+
+/**
+ * A JSON web key set, as per RFC 7517
+ */
+#[derive(Clone,Deserialize,PartialEq,Serialize)]
+pub struct Jwks {
+  pub keys: Vec<serde_json::Value>,
+}
+
+impl Jwks {
+  pub fn new(keys: Vec<serde_json::Value>) -> Jwks {
+    Jwks {
+      keys: keys,
+    }
+  }
 }
 
 #[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
@@ -160,11 +755,128 @@ pub enum LoginResp {
 
   #[serde(rename="invalid_credentials")]
   InvalidCredentials,
+
+  #[serde(rename="email_not_verified")]
+  EmailNotVerified,
+
+  /**
+   * The password was correct, but the user has been deactivated
+   */
+  #[serde(rename="deactivated")]
+  Deactivated,
+
+  /**
+   * The password was correct, but a second factor is required. The
+   * value is a short lived challenge token for the `login_totp` endpoint.
+   */
+  #[serde(rename="totp_required")]
+  TotpRequired(StringNE),
+
+  /**
+   * There have been too many failed logins for the email address. The
+   * value is the number of seconds until another attempt can be made.
+   */
+  #[serde(rename="too_many_attempts")]
+  TooManyAttempts(u32),
 }
 
 #[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
-pub struct RefreshReq {
-  #[serde(default="RefreshReq::def_refresh_token")]
+pub struct LoginTotpReq {
+  pub challenge_token: StringNE,
+
+  pub code: StringNE,
+}
+
+impl LoginTotpReq {
+  pub fn new(challenge_token: StringNE, code: StringNE) -> LoginTotpReq {
+    LoginTotpReq {
+      challenge_token: challenge_token,
+      code: code,
+    }
+  }
+}
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub enum LoginTotpResp {
+  #[serde(rename="tokens")]
+  Tokens(LoginTokens),
+
+  #[serde(rename="invalid_code")]
+  InvalidCode,
+
+  #[serde(rename="invalid_challenge")]
+  InvalidChallenge,
+
+  #[serde(rename="too_many_attempts")]
+  TooManyAttempts(u32),
+}
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub enum OidcLoginStartResp {
+  #[serde(rename="authorization_url")]
+  AuthorizationUrl(StringNE),
+
+  /**
+   * The server has no OpenID Connect provider configured
+   */
+  #[serde(rename="not_configured")]
+  NotConfigured,
+}
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub struct OidcLoginReq {
+  pub code: StringNE,
+
+  pub state: StringNE,
+}
+
+impl OidcLoginReq {
+  pub fn new(code: StringNE, state: StringNE) -> OidcLoginReq {
+    OidcLoginReq {
+      code: code,
+      state: state,
+    }
+  }
+}
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub enum OidcLoginResp {
+  #[serde(rename="tokens")]
+  Tokens(LoginTokens),
+
+  /**
+   * The login was unknown, expired or already completed, or the
+   * provider didn't confirm the user's identity
+   */
+  #[serde(rename="invalid_login")]
+  InvalidLogin,
+
+  /**
+   * There is no user for the provider's account, and one couldn't be
+   * linked or created
+   */
+  #[serde(rename="unknown_user")]
+  UnknownUser,
+
+  /**
+   * As for `LoginResp.totp_required`
+   */
+  #[serde(rename="totp_required")]
+  TotpRequired(StringNE),
+
+  /**
+   * The provider's account belongs to a deactivated user
+   */
+  #[serde(rename="deactivated")]
+  Deactivated,
+
+  #[serde(rename="not_configured")]
+  NotConfigured,
+}
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub struct RefreshReq {
+  #[serde(default="RefreshReq::def_refresh_token")]
   pub refresh_token: Option<StringNE>,
 }
 
@@ -182,13 +894,31 @@ impl RefreshReq {
 
 #[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
 pub enum RefreshResp {
-  #[serde(rename="access_token")]
-  AccessToken(StringNE),
+  #[serde(rename="tokens")]
+  Tokens(LoginTokens),
 
   #[serde(rename="invalid_refresh_token")]
   InvalidRefreshToken,
 }
 
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub struct LogoutReq {
+  #[serde(default="LogoutReq::def_refresh_token")]
+  pub refresh_token: Option<StringNE>,
+}
+
+impl LogoutReq {
+  pub fn new() -> LogoutReq {
+    LogoutReq {
+      refresh_token: LogoutReq::def_refresh_token(),
+    }
+  }
+
+  pub fn def_refresh_token() -> Option<StringNE> {
+    None
+  }
+}
+
 #[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
 pub struct LoginTokens {
   pub access_jwt: StringNE,
@@ -206,171 +936,1368 @@ impl LoginTokens {
 }
 
 #[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
-pub struct NewMessageReq {
-  pub message: StringML,
+pub struct RequestPasswordResetReq {
+  pub email: StringNE,
 }
 
-impl NewMessageReq {
-  pub fn new(message: StringML) -> NewMessageReq {
-    NewMessageReq {
-      message: message,
+impl RequestPasswordResetReq {
+  pub fn new(email: StringNE) -> RequestPasswordResetReq {
+    RequestPasswordResetReq {
+      email: email,
     }
   }
 }
 
 #[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
-pub struct RecentMessagesReq {
-  pub page: PageReq,
+pub struct ResetPasswordReq {
+  pub token: StringNE,
+
+  pub password: Password,
 }
 
-impl RecentMessagesReq {
-  pub fn new(page: PageReq) -> RecentMessagesReq {
-    RecentMessagesReq {
-      page: page,
+impl ResetPasswordReq {
+  pub fn new(token: StringNE, password: Password) -> ResetPasswordReq {
+    ResetPasswordReq {
+      token: token,
+      password: password,
     }
   }
 }
 
 #[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
-pub struct PageReq {
-  #[serde(default="PageReq::def_offset")]
-  pub offset: u64,
+pub enum ResetPasswordResp {
+  #[serde(rename="success")]
+  Success,
 
-  #[serde(default="PageReq::def_limit")]
-  pub limit: u64,
+  #[serde(rename="invalid_token")]
+  InvalidToken,
+
+  #[serde(rename="weak_password")]
+  WeakPassword(Vec<PasswordViolation>),
 }
 
-impl PageReq {
-  pub fn new() -> PageReq {
-    PageReq {
-      offset: PageReq::def_offset(),
-      limit: PageReq::def_limit(),
+/**
+ * A rule of the server's password policy that a password breaks
+ */
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub enum PasswordViolation {
+  /**
+   * The password is shorter than the minimum length, which is the value
+   */
+  #[serde(rename="too_short")]
+  TooShort(u32),
+
+  #[serde(rename="no_lowercase")]
+  NoLowercase,
+
+  #[serde(rename="no_uppercase")]
+  NoUppercase,
+
+  #[serde(rename="no_digit")]
+  NoDigit,
+
+  #[serde(rename="no_symbol")]
+  NoSymbol,
+
+  /**
+   * The password is known to have been exposed in a data breach
+   */
+  #[serde(rename="breached")]
+  Breached,
+
+  #[serde(rename="contains_email")]
+  ContainsEmail,
+}
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub struct VerifyEmailReq {
+  pub token: StringNE,
+}
+
+impl VerifyEmailReq {
+  pub fn new(token: StringNE) -> VerifyEmailReq {
+    VerifyEmailReq {
+      token: token,
     }
   }
+}
 
-  pub fn def_offset() -> u64 {
-    0_u64
-  }
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub enum VerifyEmailResp {
+  #[serde(rename="verified")]
+  Verified,
 
-  pub fn def_limit() -> u64 {
-    20_u64
+  #[serde(rename="invalid_token")]
+  InvalidToken,
+}
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub struct SignupReq {
+  pub token: StringNE,
+
+  pub fullname: StringNE,
+
+  pub password: Password,
+}
+
+impl SignupReq {
+  pub fn new(token: StringNE, fullname: StringNE, password: Password) -> SignupReq {
+    SignupReq {
+      token: token,
+      fullname: fullname,
+      password: password,
+    }
   }
 }
 
-/**
- * A holder for paginated results
- */
 #[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
-pub struct Paginated<T> {
+pub enum SignupResp {
+  #[serde(rename="tokens")]
+  Tokens(LoginTokens),
+
   /**
-   * The paginated items
+   * The invitation is unknown, expired, revoked or already used
    */
-  pub items: Vec<T>,
+  #[serde(rename="invalid_token")]
+  InvalidToken,
+
+  #[serde(rename="weak_password")]
+  WeakPassword(Vec<PasswordViolation>),
 
   /**
-   * The offset used for this query
+   * A user with the invited email address already exists
    */
-  pub current_offset: u64,
+  #[serde(rename="email_in_use")]
+  EmailInUse,
+}
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub struct ResendVerificationEmailReq {
+  pub email: StringNE,
+}
+
+impl ResendVerificationEmailReq {
+  pub fn new(email: StringNE) -> ResendVerificationEmailReq {
+    ResendVerificationEmailReq {
+      email: email,
+    }
+  }
+}
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub enum BeginTotpEnrolmentResp {
+  #[serde(rename="enrolment")]
+  Enrolment(TotpEnrolment),
+
+  #[serde(rename="already_enrolled")]
+  AlreadyEnrolled,
+}
 
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub struct TotpEnrolment {
   /**
-   * The size of the entire date set
+   * The base32 encoded secret, for manual entry
    */
-  pub total_count: u64,
+  pub secret: String,
+
+  /**
+   * The otpauth uri, for display as a QR code
+   */
+  pub otpauth_uri: String,
 }
 
-impl<T> Paginated<T> {
-  pub fn new(items: Vec<T>, current_offset: u64, total_count: u64) -> Paginated<T> {
-    Paginated {
-      items: items,
-      current_offset: current_offset,
-      total_count: total_count,
+impl TotpEnrolment {
+  pub fn new(secret: String, otpauth_uri: String) -> TotpEnrolment {
+    TotpEnrolment {
+      secret: secret,
+      otpauth_uri: otpauth_uri,
     }
   }
 }
 
 #[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
-pub struct Message {
-  pub id: MessageId,
+pub struct ConfirmTotpEnrolmentReq {
+  pub code: StringNE,
+}
 
-  pub posted_at: Instant,
+impl ConfirmTotpEnrolmentReq {
+  pub fn new(code: StringNE) -> ConfirmTotpEnrolmentReq {
+    ConfirmTotpEnrolmentReq {
+      code: code,
+    }
+  }
+}
 
-  pub user_fullname: String,
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub enum ConfirmTotpEnrolmentResp {
+  #[serde(rename="recovery_codes")]
+  RecoveryCodes(Vec<String>),
 
+  #[serde(rename="invalid_code")]
+  InvalidCode,
+
+  #[serde(rename="not_enrolling")]
+  NotEnrolling,
+}
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub struct NewMessageReq {
   pub message: StringML,
 }
 
-impl Message {
-  pub fn new(id: MessageId, posted_at: Instant, user_fullname: String, message: StringML) -> Message {
-    Message {
-      id: id,
-      posted_at: posted_at,
-      user_fullname: user_fullname,
+impl NewMessageReq {
+  pub fn new(message: StringML) -> NewMessageReq {
+    NewMessageReq {
       message: message,
     }
   }
 }
 
 #[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
-pub struct QueryUsersReq {
-  #[serde(default="QueryUsersReq::def_page")]
+pub struct RecentMessagesReq {
   pub page: PageReq,
 }
 
-impl QueryUsersReq {
-  pub fn new() -> QueryUsersReq {
-    QueryUsersReq {
-      page: QueryUsersReq::def_page(),
+impl RecentMessagesReq {
+  pub fn new(page: PageReq) -> RecentMessagesReq {
+    RecentMessagesReq {
+      page: page,
     }
   }
+}
 
-  pub fn def_page() -> PageReq {
-    PageReq{offset : 0_u64, limit : 20_u64}
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub struct ReplyToMessageReq {
+  pub reply_to: MessageId,
+
+  pub message: StringML,
+}
+
+impl ReplyToMessageReq {
+  pub fn new(reply_to: MessageId, message: StringML) -> ReplyToMessageReq {
+    ReplyToMessageReq {
+      reply_to: reply_to,
+      message: message,
+    }
   }
 }
 
 #[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
-pub struct User {
-  pub fullname: StringNE,
+pub enum ReplyToMessageResp {
+  #[serde(rename="success")]
+  Success(MessageId),
 
-  pub email: EmailAddress,
+  #[serde(rename="invalid_message")]
+  InvalidMessage,
+}
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub struct MessageThreadReq {
+  pub id: MessageId,
 
-  pub is_admin: bool,
+  #[serde(default="MessageThreadReq::def_page")]
+  pub page: PageReq,
 }
 
-impl User {
-  pub fn new(fullname: StringNE, email: EmailAddress, is_admin: bool) -> User {
-    User {
-      fullname: fullname,
-      email: email,
-      is_admin: is_admin,
+impl MessageThreadReq {
+  pub fn new(id: MessageId) -> MessageThreadReq {
+    MessageThreadReq {
+      id: id,
+      page: MessageThreadReq::def_page(),
     }
   }
+
+  pub fn def_page() -> PageReq {
+    PageReq{offset : 0_u64, limit : 20_u64}
+  }
 }
 
-pub type UserWithId = WithId<AppUserId, User>;
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub enum MessageThreadResp {
+  #[serde(rename="thread")]
+  Thread(MessageThread),
+
+  #[serde(rename="invalid_message")]
+  InvalidMessage,
+}
 
 #[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
-pub struct UserDetails {
-  pub fullname: StringNE,
+pub struct MessageThread {
+  pub message: Message,
 
-  pub email: EmailAddress,
+  pub replies: Paginated<Message>,
+}
+
+impl MessageThread {
+  pub fn new(message: Message, replies: Paginated<Message>) -> MessageThread {
+    MessageThread {
+      message: message,
+      replies: replies,
+    }
+  }
+}
 
-  pub is_admin: bool,
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub struct MessageReactionReq {
+  pub message_id: MessageId,
 
-  pub password: Password,
+  pub emoji: StringNE,
 }
 
-impl UserDetails {
-  pub fn new(fullname: StringNE, email: EmailAddress, is_admin: bool, password: Password) -> UserDetails {
-    UserDetails {
-      fullname: fullname,
-      email: email,
-      is_admin: is_admin,
-      password: password,
+impl MessageReactionReq {
+  pub fn new(message_id: MessageId, emoji: StringNE) -> MessageReactionReq {
+    MessageReactionReq {
+      message_id: message_id,
+      emoji: emoji,
     }
   }
 }
 
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub enum AddReactionResp {
+  #[serde(rename="success")]
+  Success,
+
+  #[serde(rename="invalid_message")]
+  InvalidMessage,
+
+  #[serde(rename="invalid_emoji")]
+  InvalidEmoji,
+}
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub struct EditMessageReq {
+  pub id: MessageId,
+
+  pub message: StringML,
+}
+
+impl EditMessageReq {
+  pub fn new(id: MessageId, message: StringML) -> EditMessageReq {
+    EditMessageReq {
+      id: id,
+      message: message,
+    }
+  }
+}
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub enum EditMessageResp {
+  #[serde(rename="success")]
+  Success,
+
+  #[serde(rename="invalid_message")]
+  InvalidMessage,
+
+  #[serde(rename="not_permitted")]
+  NotPermitted,
+}
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub enum DeleteMessageResp {
+  #[serde(rename="success")]
+  Success,
+
+  #[serde(rename="invalid_message")]
+  InvalidMessage,
+
+  #[serde(rename="not_permitted")]
+  NotPermitted,
+}
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub enum MessageRevisionsResp {
+  #[serde(rename="revisions")]
+  Revisions(Vec<MessageRevision>),
+
+  #[serde(rename="invalid_message")]
+  InvalidMessage,
+
+  #[serde(rename="not_permitted")]
+  NotPermitted,
+}
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub struct PageReq {
+  #[serde(default="PageReq::def_offset")]
+  pub offset: u64,
+
+  #[serde(default="PageReq::def_limit")]
+  pub limit: u64,
+}
+
+impl PageReq {
+  pub fn new() -> PageReq {
+    PageReq {
+      offset: PageReq::def_offset(),
+      limit: PageReq::def_limit(),
+    }
+  }
+
+  pub fn def_offset() -> u64 {
+    0_u64
+  }
+
+  pub fn def_limit() -> u64 {
+    20_u64
+  }
+}
+
+/**
+ * A holder for paginated results
+ */
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub struct Paginated<T> {
+  /**
+   * The paginated items
+   */
+  pub items: Vec<T>,
+
+  /**
+   * The offset used for this query
+   */
+  pub current_offset: u64,
+
+  /**
+   * The size of the entire date set
+   */
+  pub total_count: u64,
+}
+
+impl<T> Paginated<T> {
+  pub fn new(items: Vec<T>, current_offset: u64, total_count: u64) -> Paginated<T> {
+    Paginated {
+      items: items,
+      current_offset: current_offset,
+      total_count: total_count,
+    }
+  }
+}
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub struct Message {
+  pub id: MessageId,
+
+  pub posted_at: Instant,
+
+  pub user_fullname: String,
+
+  pub message: StringML,
+
+  #[serde(default="Message::def_edited_at")]
+  pub edited_at: Option<Instant>,
+
+  #[serde(default="Message::def_deleted_at")]
+  pub deleted_at: Option<Instant>,
+
+  #[serde(default="Message::def_reply_count")]
+  pub reply_count: u64,
+
+  #[serde(default="Message::def_latest_reply_at")]
+  pub latest_reply_at: Option<Instant>,
+
+  #[serde(default="Message::def_reactions")]
+  pub reactions: Vec<ReactionCount>,
+}
+
+impl Message {
+  pub fn new(id: MessageId, posted_at: Instant, user_fullname: String, message: StringML) -> Message {
+    Message {
+      id: id,
+      posted_at: posted_at,
+      user_fullname: user_fullname,
+      message: message,
+      edited_at: Message::def_edited_at(),
+      deleted_at: Message::def_deleted_at(),
+      reply_count: Message::def_reply_count(),
+      latest_reply_at: Message::def_latest_reply_at(),
+      reactions: Message::def_reactions(),
+    }
+  }
+
+  pub fn def_edited_at() -> Option<Instant> {
+    None
+  }
+
+  pub fn def_deleted_at() -> Option<Instant> {
+    None
+  }
+
+  pub fn def_reply_count() -> u64 {
+    0_u64
+  }
+
+  pub fn def_latest_reply_at() -> Option<Instant> {
+    None
+  }
+
+  pub fn def_reactions() -> Vec<ReactionCount> {
+    vec![]
+  }
+}
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub struct ReactionCount {
+  pub emoji: StringNE,
+
+  pub count: u64,
+
+  pub reacted_by_me: bool,
+}
+
+impl ReactionCount {
+  pub fn new(emoji: StringNE, count: u64, reacted_by_me: bool) -> ReactionCount {
+    ReactionCount {
+      emoji: emoji,
+      count: count,
+      reacted_by_me: reacted_by_me,
+    }
+  }
+}
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub struct QueryUsersReq {
+  #[serde(default="QueryUsersReq::def_page")]
+  pub page: PageReq,
+}
+
+impl QueryUsersReq {
+  pub fn new() -> QueryUsersReq {
+    QueryUsersReq {
+      page: QueryUsersReq::def_page(),
+    }
+  }
+
+  pub fn def_page() -> PageReq {
+    PageReq{offset : 0_u64, limit : 20_u64}
+  }
+}
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub struct User {
+  pub fullname: StringNE,
+
+  pub email: EmailAddress,
+
+  /**
+   * The names of the user's roles, other than the implicit "user" role
+   */
+  pub roles: Vec<StringNE>,
+
+  /**
+   * False if the user has been deactivated
+   */
+  pub active: bool,
+}
+
+impl User {
+  pub fn new(fullname: StringNE, email: EmailAddress, roles: Vec<StringNE>, active: bool) -> User {
+    User {
+      fullname: fullname,
+      email: email,
+      roles: roles,
+      active: active,
+    }
+  }
+}
+
+pub type UserWithId = WithId<AppUserId, User>;
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub enum ImpersonateUserResp {
+  #[serde(rename="access_jwt")]
+  AccessJwt(StringNE),
+
+  #[serde(rename="invalid_user")]
+  InvalidUser,
+}
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub struct JwtFailureCounts {
+  pub access: Vec<JwtFailureCount>,
+
+  pub refresh: Vec<JwtFailureCount>,
+}
+
+impl JwtFailureCounts {
+  pub fn new(access: Vec<JwtFailureCount>, refresh: Vec<JwtFailureCount>) -> JwtFailureCounts {
+    JwtFailureCounts {
+      access: access,
+      refresh: refresh,
+    }
+  }
+}
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub struct JwtFailureCount {
+  pub reason: String,
+
+  pub count: u64,
+}
+
+impl JwtFailureCount {
+  pub fn new(reason: String, count: u64) -> JwtFailureCount {
+    JwtFailureCount {
+      reason: reason,
+      count: count,
+    }
+  }
+}
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub struct QueryAuditLogReq {
+  #[serde(default="QueryAuditLogReq::def_page")]
+  pub page: PageReq,
+
+  #[serde(default="QueryAuditLogReq::def_actor")]
+  pub actor: Option<AppUserId>,
+
+  #[serde(default="QueryAuditLogReq::def_endpoint")]
+  pub endpoint: Option<String>,
+
+  #[serde(default="QueryAuditLogReq::def_outcome")]
+  pub outcome: Option<AuditOutcome>,
+
+  #[serde(default="QueryAuditLogReq::def_logged_after")]
+  pub logged_after: Option<Instant>,
+
+  #[serde(default="QueryAuditLogReq::def_logged_before")]
+  pub logged_before: Option<Instant>,
+}
+
+impl QueryAuditLogReq {
+  pub fn new() -> QueryAuditLogReq {
+    QueryAuditLogReq {
+      page: QueryAuditLogReq::def_page(),
+      actor: QueryAuditLogReq::def_actor(),
+      endpoint: QueryAuditLogReq::def_endpoint(),
+      outcome: QueryAuditLogReq::def_outcome(),
+      logged_after: QueryAuditLogReq::def_logged_after(),
+      logged_before: QueryAuditLogReq::def_logged_before(),
+    }
+  }
+
+  pub fn def_page() -> PageReq {
+    PageReq{offset : 0_u64, limit : 20_u64}
+  }
+
+  pub fn def_actor() -> Option<AppUserId> {
+    None
+  }
+
+  pub fn def_endpoint() -> Option<String> {
+    None
+  }
+
+  pub fn def_outcome() -> Option<AuditOutcome> {
+    None
+  }
+
+  pub fn def_logged_after() -> Option<Instant> {
+    None
+  }
+
+  pub fn def_logged_before() -> Option<Instant> {
+    None
+  }
+}
+
+pub type AuditLogEntry = WithId<AuditLogId, AuditLog>;
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub struct CreateApiKeyReq {
+  pub user_id: AppUserId,
+
+  pub name: StringNE,
+
+  pub scopes: Vec<ApiKeyScope>,
+
+  #[serde(default="CreateApiKeyReq::def_expires_in_days")]
+  pub expires_in_days: u32,
+}
+
+impl CreateApiKeyReq {
+  pub fn new(user_id: AppUserId, name: StringNE, scopes: Vec<ApiKeyScope>) -> CreateApiKeyReq {
+    CreateApiKeyReq {
+      user_id: user_id,
+      name: name,
+      scopes: scopes,
+      expires_in_days: CreateApiKeyReq::def_expires_in_days(),
+    }
+  }
+
+  pub fn def_expires_in_days() -> u32 {
+    90_u32
+  }
+}
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub enum CreateApiKeyResp {
+  #[serde(rename="success")]
+  Success(NewApiKey),
+
+  #[serde(rename="invalid_user")]
+  InvalidUser,
+
+  /**
+   * expires_in_days is zero or more than the configured maximum
+   */
+  #[serde(rename="invalid_expiry")]
+  InvalidExpiry,
+}
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub struct NewApiKey {
+  pub id: ApiKeyId,
+
+  pub key: StringNE,
+}
+
+impl NewApiKey {
+  pub fn new(id: ApiKeyId, key: StringNE) -> NewApiKey {
+    NewApiKey {
+      id: id,
+      key: key,
+    }
+  }
+}
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub struct QueryApiKeysReq {
+  #[serde(default="QueryApiKeysReq::def_page")]
+  pub page: PageReq,
+
+  #[serde(default="QueryApiKeysReq::def_user_id")]
+  pub user_id: Option<AppUserId>,
+}
+
+impl QueryApiKeysReq {
+  pub fn new() -> QueryApiKeysReq {
+    QueryApiKeysReq {
+      page: QueryApiKeysReq::def_page(),
+      user_id: QueryApiKeysReq::def_user_id(),
+    }
+  }
+
+  pub fn def_page() -> PageReq {
+    PageReq{offset : 0_u64, limit : 20_u64}
+  }
+
+  pub fn def_user_id() -> Option<AppUserId> {
+    None
+  }
+}
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub struct ApiKeySummary {
+  pub user_id: AppUserId,
+
+  pub name: StringNE,
+
+  pub scopes: Vec<ApiKeyScope>,
+
+  pub created_at: Instant,
+
+  pub expires_at: Instant,
+
+  pub last_used_at: Option<Instant>,
+
+  pub revoked_at: Option<Instant>,
+}
+
+impl ApiKeySummary {
+  pub fn new(user_id: AppUserId, name: StringNE, scopes: Vec<ApiKeyScope>, created_at: Instant, expires_at: Instant, last_used_at: Option<Instant>, revoked_at: Option<Instant>) -> ApiKeySummary {
+    ApiKeySummary {
+      user_id: user_id,
+      name: name,
+      scopes: scopes,
+      created_at: created_at,
+      expires_at: expires_at,
+      last_used_at: last_used_at,
+      revoked_at: revoked_at,
+    }
+  }
+}
+
+pub type ApiKeySummaryWithId = WithId<ApiKeyId, ApiKeySummary>;
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub struct CreateInvitationReq {
+  pub email: EmailAddress,
+
+  #[serde(default="CreateInvitationReq::def_roles")]
+  pub roles: Vec<RoleId>,
+
+  #[serde(default="CreateInvitationReq::def_expires_in_days")]
+  pub expires_in_days: u32,
+}
+
+impl CreateInvitationReq {
+  pub fn new(email: EmailAddress) -> CreateInvitationReq {
+    CreateInvitationReq {
+      email: email,
+      roles: CreateInvitationReq::def_roles(),
+      expires_in_days: CreateInvitationReq::def_expires_in_days(),
+    }
+  }
+
+  pub fn def_roles() -> Vec<RoleId> {
+    vec![]
+  }
+
+  pub fn def_expires_in_days() -> u32 {
+    7_u32
+  }
+}
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub enum CreateInvitationResp {
+  #[serde(rename="success")]
+  Success(InvitationId),
+
+  /**
+   * A user with the email address already exists
+   */
+  #[serde(rename="email_in_use")]
+  EmailInUse,
+
+  #[serde(rename="invalid_role")]
+  InvalidRole,
+
+  /**
+   * expires_in_days is zero or more than the configured maximum
+   */
+  #[serde(rename="invalid_expiry")]
+  InvalidExpiry,
+}
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub struct QueryInvitationsReq {
+  #[serde(default="QueryInvitationsReq::def_page")]
+  pub page: PageReq,
+
+  #[serde(default="QueryInvitationsReq::def_status")]
+  pub status: Option<InvitationStatus>,
+}
+
+impl QueryInvitationsReq {
+  pub fn new() -> QueryInvitationsReq {
+    QueryInvitationsReq {
+      page: QueryInvitationsReq::def_page(),
+      status: QueryInvitationsReq::def_status(),
+    }
+  }
+
+  pub fn def_page() -> PageReq {
+    PageReq{offset : 0_u64, limit : 20_u64}
+  }
+
+  pub fn def_status() -> Option<InvitationStatus> {
+    None
+  }
+}
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub enum InvitationStatus {
+  #[serde(rename="pending")]
+  Pending,
+
+  #[serde(rename="used")]
+  Used,
+
+  #[serde(rename="revoked")]
+  Revoked,
+
+  #[serde(rename="expired")]
+  Expired,
+}
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub struct InvitationSummary {
+  pub email: StringNE,
+
+  pub roles: Vec<RoleId>,
+
+  pub created_by: AppUserId,
+
+  pub created_at: Instant,
+
+  pub expires_at: Instant,
+
+  pub used_at: Option<Instant>,
+
+  pub user_id: Option<AppUserId>,
+
+  pub revoked_at: Option<Instant>,
+}
+
+impl InvitationSummary {
+  pub fn new(email: StringNE, roles: Vec<RoleId>, created_by: AppUserId, created_at: Instant, expires_at: Instant, used_at: Option<Instant>, user_id: Option<AppUserId>, revoked_at: Option<Instant>) -> InvitationSummary {
+    InvitationSummary {
+      email: email,
+      roles: roles,
+      created_by: created_by,
+      created_at: created_at,
+      expires_at: expires_at,
+      used_at: used_at,
+      user_id: user_id,
+      revoked_at: revoked_at,
+    }
+  }
+}
+
+pub type InvitationSummaryWithId = WithId<InvitationId, InvitationSummary>;
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub struct SessionSummary {
+  pub user_agent: Option<String>,
+
+  pub client_ip: Option<String>,
+
+  pub created_at: Instant,
+
+  pub last_refreshed_at: Instant,
+
+  /**
+   * True for the session making the request
+   */
+  pub current: bool,
+}
+
+impl SessionSummary {
+  pub fn new(user_agent: Option<String>, client_ip: Option<String>, created_at: Instant, last_refreshed_at: Instant, current: bool) -> SessionSummary {
+    SessionSummary {
+      user_agent: user_agent,
+      client_ip: client_ip,
+      created_at: created_at,
+      last_refreshed_at: last_refreshed_at,
+      current: current,
+    }
+  }
+}
+
+pub type SessionSummaryWithId = WithId<SessionId, SessionSummary>;
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub struct UpdateProfileReq {
+  pub fullname: StringNE,
+
+  pub email: EmailAddress,
+
+  /**
+   * Required to change the email address, as a stolen access token
+   * could otherwise take over the account via a password reset
+   */
+  #[serde(default="UpdateProfileReq::def_current_password")]
+  pub current_password: Option<Password>,
+}
+
+impl UpdateProfileReq {
+  pub fn new(fullname: StringNE, email: EmailAddress) -> UpdateProfileReq {
+    UpdateProfileReq {
+      fullname: fullname,
+      email: email,
+      current_password: UpdateProfileReq::def_current_password(),
+    }
+  }
+
+  pub fn def_current_password() -> Option<Password> {
+    None
+  }
+}
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub enum UpdateProfileResp {
+  #[serde(rename="success")]
+  Success,
+
+  /**
+   * Another user has the email address
+   */
+  #[serde(rename="email_in_use")]
+  EmailInUse,
+
+  /**
+   * The email address was changed without the correct current password
+   */
+  #[serde(rename="invalid_credentials")]
+  InvalidCredentials,
+
+  /**
+   * As for `LoginResp.too_many_attempts`. Wrong current passwords count
+   * as failed logins.
+   */
+  #[serde(rename="too_many_attempts")]
+  TooManyAttempts(u32),
+}
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub struct ChangePasswordReq {
+  pub current_password: Password,
+
+  pub new_password: Password,
+}
+
+impl ChangePasswordReq {
+  pub fn new(current_password: Password, new_password: Password) -> ChangePasswordReq {
+    ChangePasswordReq {
+      current_password: current_password,
+      new_password: new_password,
+    }
+  }
+}
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub enum ChangePasswordResp {
+  #[serde(rename="success")]
+  Success,
+
+  #[serde(rename="invalid_credentials")]
+  InvalidCredentials,
+
+  #[serde(rename="weak_password")]
+  WeakPassword(Vec<PasswordViolation>),
+
+  /**
+   * As for `LoginResp.too_many_attempts`. Wrong current passwords count
+   * as failed logins.
+   */
+  #[serde(rename="too_many_attempts")]
+  TooManyAttempts(u32),
+}
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub struct UserDetails {
+  pub fullname: StringNE,
+
+  pub email: EmailAddress,
+
+  pub password: Password,
+}
+
+impl UserDetails {
+  pub fn new(fullname: StringNE, email: EmailAddress, password: Password) -> UserDetails {
+    UserDetails {
+      fullname: fullname,
+      email: email,
+      password: password,
+    }
+  }
+}
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub enum CreateUserResp {
+  #[serde(rename="success")]
+  Success(AppUserId),
+
+  #[serde(rename="weak_password")]
+  WeakPassword(Vec<PasswordViolation>),
+}
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub enum UpdateUserResp {
+  #[serde(rename="success")]
+  Success,
+
+  #[serde(rename="weak_password")]
+  WeakPassword(Vec<PasswordViolation>),
+}
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub struct SetUserRolesReq {
+  pub user_id: AppUserId,
+
+  pub roles: Vec<RoleId>,
+}
+
+impl SetUserRolesReq {
+  pub fn new(user_id: AppUserId, roles: Vec<RoleId>) -> SetUserRolesReq {
+    SetUserRolesReq {
+      user_id: user_id,
+      roles: roles,
+    }
+  }
+}
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub struct CreateOrganisationReq {
+  pub name: StringNE,
+}
+
+impl CreateOrganisationReq {
+  pub fn new(name: StringNE) -> CreateOrganisationReq {
+    CreateOrganisationReq {
+      name: name,
+    }
+  }
+}
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub enum CreateOrganisationResp {
+  #[serde(rename="success")]
+  Success(OrganisationId),
+
+  #[serde(rename="name_in_use")]
+  NameInUse,
+}
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub struct QueryOrganisationsReq {
+  #[serde(default="QueryOrganisationsReq::def_page")]
+  pub page: PageReq,
+}
+
+impl QueryOrganisationsReq {
+  pub fn new() -> QueryOrganisationsReq {
+    QueryOrganisationsReq {
+      page: QueryOrganisationsReq::def_page(),
+    }
+  }
+
+  pub fn def_page() -> PageReq {
+    PageReq{offset : 0_u64, limit : 20_u64}
+  }
+}
+
+pub type OrganisationWithId = WithId<OrganisationId, Organisation>;
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub struct SetOrganisationMemberReq {
+  pub organisation_id: OrganisationId,
+
+  pub user_id: AppUserId,
+
+  #[serde(default="SetOrganisationMemberReq::def_roles")]
+  pub roles: Vec<RoleId>,
+}
+
+impl SetOrganisationMemberReq {
+  pub fn new(organisation_id: OrganisationId, user_id: AppUserId) -> SetOrganisationMemberReq {
+    SetOrganisationMemberReq {
+      organisation_id: organisation_id,
+      user_id: user_id,
+      roles: SetOrganisationMemberReq::def_roles(),
+    }
+  }
+
+  pub fn def_roles() -> Vec<RoleId> {
+    vec![]
+  }
+}
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub enum SetOrganisationMemberResp {
+  #[serde(rename="success")]
+  Success,
+
+  #[serde(rename="invalid_organisation")]
+  InvalidOrganisation,
+
+  #[serde(rename="invalid_user")]
+  InvalidUser,
+
+  #[serde(rename="invalid_role")]
+  InvalidRole,
+}
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub struct RemoveOrganisationMemberReq {
+  pub organisation_id: OrganisationId,
+
+  pub user_id: AppUserId,
+}
+
+impl RemoveOrganisationMemberReq {
+  pub fn new(organisation_id: OrganisationId, user_id: AppUserId) -> RemoveOrganisationMemberReq {
+    RemoveOrganisationMemberReq {
+      organisation_id: organisation_id,
+      user_id: user_id,
+    }
+  }
+}
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub struct SwitchOrganisationReq {
+  pub organisation_id: Option<OrganisationId>,
+}
+
+impl SwitchOrganisationReq {
+  pub fn new(organisation_id: Option<OrganisationId>) -> SwitchOrganisationReq {
+    SwitchOrganisationReq {
+      organisation_id: organisation_id,
+    }
+  }
+}
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub enum SwitchOrganisationResp {
+  #[serde(rename="access_jwt")]
+  AccessJwt(StringNE),
+
+  /**
+   * The organisation doesn't exist, or the user isn't a member, or
+   * isn't an admin when switching to none
+   */
+  #[serde(rename="invalid_organisation")]
+  InvalidOrganisation,
+}
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub enum DeactivateUserResp {
+  #[serde(rename="success")]
+  Success,
+
+  #[serde(rename="invalid_user")]
+  InvalidUser,
+
+  /**
+   * Admins can't deactivate themselves
+   */
+  #[serde(rename="cannot_deactivate_self")]
+  CannotDeactivateSelf,
+}
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub enum ReactivateUserResp {
+  #[serde(rename="success")]
+  Success,
+
+  #[serde(rename="invalid_user")]
+  InvalidUser,
+}
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub enum SetUserRolesResp {
+  #[serde(rename="success")]
+  Success,
+
+  #[serde(rename="invalid_user")]
+  InvalidUser,
+
+  #[serde(rename="invalid_role")]
+  InvalidRole,
+}
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub struct CreateRoleReq {
+  pub name: StringNE,
+
+  #[serde(default="CreateRoleReq::def_description")]
+  pub description: String,
+
+  #[serde(default="CreateRoleReq::def_permissions")]
+  pub permissions: Vec<StringNE>,
+}
+
+impl CreateRoleReq {
+  pub fn new(name: StringNE) -> CreateRoleReq {
+    CreateRoleReq {
+      name: name,
+      description: CreateRoleReq::def_description(),
+      permissions: CreateRoleReq::def_permissions(),
+    }
+  }
+
+  pub fn def_description() -> String {
+    "".to_string()
+  }
+
+  pub fn def_permissions() -> Vec<StringNE> {
+    vec![]
+  }
+}
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub enum CreateRoleResp {
+  #[serde(rename="success")]
+  Success(RoleId),
+
+  /**
+   * Another role has the name, or it is the reserved "user" role
+   */
+  #[serde(rename="name_in_use")]
+  NameInUse,
+}
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub struct QueryRolesReq {
+  #[serde(default="QueryRolesReq::def_page")]
+  pub page: PageReq,
+}
+
+impl QueryRolesReq {
+  pub fn new() -> QueryRolesReq {
+    QueryRolesReq {
+      page: QueryRolesReq::def_page(),
+    }
+  }
+
+  pub fn def_page() -> PageReq {
+    PageReq{offset : 0_u64, limit : 20_u64}
+  }
+}
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub struct RoleDetails {
+  pub name: StringNE,
+
+  pub description: String,
+
+  pub permissions: Vec<StringNE>,
+}
+
+impl RoleDetails {
+  pub fn new(name: StringNE, description: String, permissions: Vec<StringNE>) -> RoleDetails {
+    RoleDetails {
+      name: name,
+      description: description,
+      permissions: permissions,
+    }
+  }
+}
+
+pub type RoleDetailsWithId = WithId<RoleId, RoleDetails>;
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub struct SetRolePermissionsReq {
+  pub role_id: RoleId,
+
+  pub permissions: Vec<StringNE>,
+}
+
+impl SetRolePermissionsReq {
+  pub fn new(role_id: RoleId, permissions: Vec<StringNE>) -> SetRolePermissionsReq {
+    SetRolePermissionsReq {
+      role_id: role_id,
+      permissions: permissions,
+    }
+  }
+}
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub enum SetRolePermissionsResp {
+  #[serde(rename="success")]
+  Success,
+
+  #[serde(rename="invalid_role")]
+  InvalidRole,
+}
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub enum DeleteRoleResp {
+  #[serde(rename="success")]
+  Success,
+
+  #[serde(rename="invalid_role")]
+  InvalidRole,
+
+  /**
+   * The admin role can't be deleted, as admin only endpoints require it
+   */
+  #[serde(rename="builtin_role")]
+  BuiltinRole,
+}
+
 #[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
 pub struct WithId<I, T> {
   pub id: I,
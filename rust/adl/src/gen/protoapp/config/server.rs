@@ -13,31 +13,151 @@ pub struct ServerConfig {
   #[serde(default="ServerConfig::def_jwt_issuer")]
   pub jwt_issuer: String,
 
+  #[serde(default="ServerConfig::def_jwt_audience")]
+  pub jwt_audience: String,
+
+  #[serde(default="ServerConfig::def_jwt_accepted_audiences")]
+  pub jwt_accepted_audiences: Vec<String>,
+
+  #[serde(default="ServerConfig::def_jwt_leeway_secs")]
+  pub jwt_leeway_secs: u32,
+
+  #[serde(default="ServerConfig::def_jwt_access_secret")]
   pub jwt_access_secret: String,
 
   #[serde(default="ServerConfig::def_jwt_access_expiry_secs")]
   pub jwt_access_expiry_secs: u32,
 
+  #[serde(default="ServerConfig::def_jwt_impersonation_expiry_secs")]
+  pub jwt_impersonation_expiry_secs: u32,
+
+  #[serde(default="ServerConfig::def_jwt_access_signing_key")]
+  pub jwt_access_signing_key: Option<JwtKey>,
+
+  #[serde(default="ServerConfig::def_jwt_access_verification_keys")]
+  pub jwt_access_verification_keys: Vec<JwtKey>,
+
   pub jwt_refresh_secret: String,
 
   #[serde(default="ServerConfig::def_jwt_refresh_expiry_secs")]
   pub jwt_refresh_expiry_secs: u32,
 
+  #[serde(default="ServerConfig::def_deactivated_users_cache_secs")]
+  pub deactivated_users_cache_secs: u32,
+
   #[serde(default="ServerConfig::def_http_bind_addr")]
   pub http_bind_addr: String,
+
+  #[serde(default="ServerConfig::def_request_timeout_secs")]
+  pub request_timeout_secs: u32,
+
+  #[serde(default="ServerConfig::def_app_url")]
+  pub app_url: String,
+
+  #[serde(default="ServerConfig::def_csrf")]
+  pub csrf: CsrfConfig,
+
+  #[serde(default="ServerConfig::def_auth_cookie")]
+  pub auth_cookie: AuthCookieConfig,
+
+  #[serde(default="ServerConfig::def_mailer")]
+  pub mailer: MailerConfig,
+
+  #[serde(default="ServerConfig::def_mail_from")]
+  pub mail_from: String,
+
+  #[serde(default="ServerConfig::def_password_policy")]
+  pub password_policy: PasswordPolicy,
+
+  #[serde(default="ServerConfig::def_argon2")]
+  pub argon2: Argon2Config,
+
+  #[serde(default="ServerConfig::def_password_reset_expiry_secs")]
+  pub password_reset_expiry_secs: u32,
+
+  pub email_verification_secret: String,
+
+  #[serde(default="ServerConfig::def_email_verification_expiry_secs")]
+  pub email_verification_expiry_secs: u32,
+
+  #[serde(default="ServerConfig::def_email_verification_resend_secs")]
+  pub email_verification_resend_secs: u32,
+
+  #[serde(default="ServerConfig::def_require_verified_email")]
+  pub require_verified_email: bool,
+
+  #[serde(default="ServerConfig::def_totp_issuer")]
+  pub totp_issuer: String,
+
+  #[serde(default="ServerConfig::def_totp_challenge_expiry_secs")]
+  pub totp_challenge_expiry_secs: u32,
+
+  #[serde(default="ServerConfig::def_login_backoff_after_failures")]
+  pub login_backoff_after_failures: u32,
+
+  #[serde(default="ServerConfig::def_login_backoff_base_secs")]
+  pub login_backoff_base_secs: u32,
+
+  #[serde(default="ServerConfig::def_login_backoff_max_secs")]
+  pub login_backoff_max_secs: u32,
+
+  #[serde(default="ServerConfig::def_login_lockout_failures")]
+  pub login_lockout_failures: u32,
+
+  #[serde(default="ServerConfig::def_login_lockout_secs")]
+  pub login_lockout_secs: u32,
+
+  #[serde(default="ServerConfig::def_api_key_max_expiry_days")]
+  pub api_key_max_expiry_days: u32,
+
+  #[serde(default="ServerConfig::def_invitation_max_expiry_days")]
+  pub invitation_max_expiry_days: u32,
+
+  #[serde(default="ServerConfig::def_oidc")]
+  pub oidc: Option<OidcConfig>,
 }
 
 impl ServerConfig {
-  pub fn new(db: DbConnectionConfig, jwt_access_secret: String, jwt_refresh_secret: String) -> ServerConfig {
+  pub fn new(db: DbConnectionConfig, jwt_refresh_secret: String, email_verification_secret: String) -> ServerConfig {
     ServerConfig {
       db: db,
       db_connection_pool_size: ServerConfig::def_db_connection_pool_size(),
       jwt_issuer: ServerConfig::def_jwt_issuer(),
-      jwt_access_secret: jwt_access_secret,
+      jwt_audience: ServerConfig::def_jwt_audience(),
+      jwt_accepted_audiences: ServerConfig::def_jwt_accepted_audiences(),
+      jwt_leeway_secs: ServerConfig::def_jwt_leeway_secs(),
+      jwt_access_secret: ServerConfig::def_jwt_access_secret(),
       jwt_access_expiry_secs: ServerConfig::def_jwt_access_expiry_secs(),
+      jwt_impersonation_expiry_secs: ServerConfig::def_jwt_impersonation_expiry_secs(),
+      jwt_access_signing_key: ServerConfig::def_jwt_access_signing_key(),
+      jwt_access_verification_keys: ServerConfig::def_jwt_access_verification_keys(),
       jwt_refresh_secret: jwt_refresh_secret,
       jwt_refresh_expiry_secs: ServerConfig::def_jwt_refresh_expiry_secs(),
+      deactivated_users_cache_secs: ServerConfig::def_deactivated_users_cache_secs(),
       http_bind_addr: ServerConfig::def_http_bind_addr(),
+      request_timeout_secs: ServerConfig::def_request_timeout_secs(),
+      app_url: ServerConfig::def_app_url(),
+      csrf: ServerConfig::def_csrf(),
+      auth_cookie: ServerConfig::def_auth_cookie(),
+      mailer: ServerConfig::def_mailer(),
+      mail_from: ServerConfig::def_mail_from(),
+      password_policy: ServerConfig::def_password_policy(),
+      argon2: ServerConfig::def_argon2(),
+      password_reset_expiry_secs: ServerConfig::def_password_reset_expiry_secs(),
+      email_verification_secret: email_verification_secret,
+      email_verification_expiry_secs: ServerConfig::def_email_verification_expiry_secs(),
+      email_verification_resend_secs: ServerConfig::def_email_verification_resend_secs(),
+      require_verified_email: ServerConfig::def_require_verified_email(),
+      totp_issuer: ServerConfig::def_totp_issuer(),
+      totp_challenge_expiry_secs: ServerConfig::def_totp_challenge_expiry_secs(),
+      login_backoff_after_failures: ServerConfig::def_login_backoff_after_failures(),
+      login_backoff_base_secs: ServerConfig::def_login_backoff_base_secs(),
+      login_backoff_max_secs: ServerConfig::def_login_backoff_max_secs(),
+      login_lockout_failures: ServerConfig::def_login_lockout_failures(),
+      login_lockout_secs: ServerConfig::def_login_lockout_secs(),
+      api_key_max_expiry_days: ServerConfig::def_api_key_max_expiry_days(),
+      invitation_max_expiry_days: ServerConfig::def_invitation_max_expiry_days(),
+      oidc: ServerConfig::def_oidc(),
     }
   }
 
@@ -49,17 +169,508 @@ impl ServerConfig {
     "adl-protoapp.link".to_string()
   }
 
+  pub fn def_jwt_audience() -> String {
+    "adl-protoapp.link".to_string()
+  }
+
+  pub fn def_jwt_accepted_audiences() -> Vec<String> {
+    vec![]
+  }
+
+  pub fn def_jwt_leeway_secs() -> u32 {
+    60_u32
+  }
+
+  pub fn def_jwt_access_secret() -> String {
+    "".to_string()
+  }
+
   pub fn def_jwt_access_expiry_secs() -> u32 {
     300_u32
   }
 
+  pub fn def_jwt_impersonation_expiry_secs() -> u32 {
+    900_u32
+  }
+
+  pub fn def_jwt_access_signing_key() -> Option<JwtKey> {
+    None
+  }
+
+  pub fn def_jwt_access_verification_keys() -> Vec<JwtKey> {
+    vec![]
+  }
+
   pub fn def_jwt_refresh_expiry_secs() -> u32 {
     86400_u32
   }
 
+  pub fn def_deactivated_users_cache_secs() -> u32 {
+    10_u32
+  }
+
   pub fn def_http_bind_addr() -> String {
     "0.0.0.0:8080".to_string()
   }
+
+  pub fn def_request_timeout_secs() -> u32 {
+    30_u32
+  }
+
+  pub fn def_app_url() -> String {
+    "http://localhost:8080".to_string()
+  }
+
+  pub fn def_csrf() -> CsrfConfig {
+    CsrfConfig{enabled : true, required_header : "X-Requested-With".to_string(), allowed_origins : vec![]}
+  }
+
+  pub fn def_auth_cookie() -> AuthCookieConfig {
+    AuthCookieConfig{secure : true, same_site : CookieSameSite::Strict, path : "/".to_string(), domain : None, host_prefix : false}
+  }
+
+  pub fn def_mailer() -> MailerConfig {
+    MailerConfig::Log
+  }
+
+  pub fn def_mail_from() -> String {
+    "Protoapp <noreply@adl-protoapp.link>".to_string()
+  }
+
+  pub fn def_password_policy() -> PasswordPolicy {
+    PasswordPolicy{min_length : 8_u32, require_lowercase : false, require_uppercase : false, require_digit : false, require_symbol : false, breached_passwords_file : None, reject_email : true}
+  }
+
+  pub fn def_argon2() -> Argon2Config {
+    Argon2Config{memory_kib : 19456_u32, iterations : 2_u32, parallelism : 1_u32}
+  }
+
+  pub fn def_password_reset_expiry_secs() -> u32 {
+    3600_u32
+  }
+
+  pub fn def_email_verification_expiry_secs() -> u32 {
+    86400_u32
+  }
+
+  pub fn def_email_verification_resend_secs() -> u32 {
+    300_u32
+  }
+
+  pub fn def_require_verified_email() -> bool {
+    false
+  }
+
+  pub fn def_totp_issuer() -> String {
+    "Protoapp".to_string()
+  }
+
+  pub fn def_totp_challenge_expiry_secs() -> u32 {
+    300_u32
+  }
+
+  pub fn def_login_backoff_after_failures() -> u32 {
+    3_u32
+  }
+
+  pub fn def_login_backoff_base_secs() -> u32 {
+    1_u32
+  }
+
+  pub fn def_login_backoff_max_secs() -> u32 {
+    60_u32
+  }
+
+  pub fn def_login_lockout_failures() -> u32 {
+    10_u32
+  }
+
+  pub fn def_login_lockout_secs() -> u32 {
+    900_u32
+  }
+
+  pub fn def_api_key_max_expiry_days() -> u32 {
+    365_u32
+  }
+
+  pub fn def_invitation_max_expiry_days() -> u32 {
+    30_u32
+  }
+
+  pub fn def_oidc() -> Option<OidcConfig> {
+    None
+  }
+}
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub struct CsrfConfig {
+  #[serde(default="CsrfConfig::def_enabled")]
+  pub enabled: bool,
+
+  #[serde(default="CsrfConfig::def_required_header")]
+  pub required_header: String,
+
+  #[serde(default="CsrfConfig::def_allowed_origins")]
+  pub allowed_origins: Vec<String>,
+}
+
+impl CsrfConfig {
+  pub fn new() -> CsrfConfig {
+    CsrfConfig {
+      enabled: CsrfConfig::def_enabled(),
+      required_header: CsrfConfig::def_required_header(),
+      allowed_origins: CsrfConfig::def_allowed_origins(),
+    }
+  }
+
+  pub fn def_enabled() -> bool {
+    true
+  }
+
+  pub fn def_required_header() -> String {
+    "X-Requested-With".to_string()
+  }
+
+  pub fn def_allowed_origins() -> Vec<String> {
+    vec![]
+  }
+}
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub struct AuthCookieConfig {
+  #[serde(default="AuthCookieConfig::def_secure")]
+  pub secure: bool,
+
+  #[serde(default="AuthCookieConfig::def_same_site")]
+  pub same_site: CookieSameSite,
+
+  #[serde(default="AuthCookieConfig::def_path")]
+  pub path: String,
+
+  #[serde(default="AuthCookieConfig::def_domain")]
+  pub domain: Option<String>,
+
+  #[serde(default="AuthCookieConfig::def_host_prefix")]
+  pub host_prefix: bool,
+}
+
+impl AuthCookieConfig {
+  pub fn new() -> AuthCookieConfig {
+    AuthCookieConfig {
+      secure: AuthCookieConfig::def_secure(),
+      same_site: AuthCookieConfig::def_same_site(),
+      path: AuthCookieConfig::def_path(),
+      domain: AuthCookieConfig::def_domain(),
+      host_prefix: AuthCookieConfig::def_host_prefix(),
+    }
+  }
+
+  pub fn def_secure() -> bool {
+    true
+  }
+
+  pub fn def_same_site() -> CookieSameSite {
+    CookieSameSite::Strict
+  }
+
+  pub fn def_path() -> String {
+    "/".to_string()
+  }
+
+  pub fn def_domain() -> Option<String> {
+    None
+  }
+
+  pub fn def_host_prefix() -> bool {
+    false
+  }
+}
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub enum CookieSameSite {
+  #[serde(rename="strict")]
+  Strict,
+
+  #[serde(rename="lax")]
+  Lax,
+
+  #[serde(rename="none")]
+  None,
+}
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub struct OidcConfig {
+  pub issuer_url: String,
+
+  pub client_id: String,
+
+  pub client_secret: String,
+
+  pub redirect_url: String,
+
+  #[serde(default="OidcConfig::def_scopes")]
+  pub scopes: Vec<String>,
+
+  #[serde(default="OidcConfig::def_create_users")]
+  pub create_users: bool,
+
+  #[serde(default="OidcConfig::def_link_existing_users")]
+  pub link_existing_users: bool,
+
+  #[serde(default="OidcConfig::def_login_expiry_secs")]
+  pub login_expiry_secs: u32,
+
+  #[serde(default="OidcConfig::def_metadata_cache_secs")]
+  pub metadata_cache_secs: u32,
+
+  #[serde(default="OidcConfig::def_id_token_algorithms")]
+  pub id_token_algorithms: Vec<JwtAlgorithm>,
+}
+
+impl OidcConfig {
+  pub fn new(issuer_url: String, client_id: String, client_secret: String, redirect_url: String) -> OidcConfig {
+    OidcConfig {
+      issuer_url: issuer_url,
+      client_id: client_id,
+      client_secret: client_secret,
+      redirect_url: redirect_url,
+      scopes: OidcConfig::def_scopes(),
+      create_users: OidcConfig::def_create_users(),
+      link_existing_users: OidcConfig::def_link_existing_users(),
+      login_expiry_secs: OidcConfig::def_login_expiry_secs(),
+      metadata_cache_secs: OidcConfig::def_metadata_cache_secs(),
+      id_token_algorithms: OidcConfig::def_id_token_algorithms(),
+    }
+  }
+
+  pub fn def_scopes() -> Vec<String> {
+    vec!["openid".to_string(), "email".to_string(), "profile".to_string()]
+  }
+
+  pub fn def_create_users() -> bool {
+    false
+  }
+
+  pub fn def_link_existing_users() -> bool {
+    false
+  }
+
+  pub fn def_login_expiry_secs() -> u32 {
+    600_u32
+  }
+
+  pub fn def_metadata_cache_secs() -> u32 {
+    3600_u32
+  }
+
+  pub fn def_id_token_algorithms() -> Vec<JwtAlgorithm> {
+    vec![JwtAlgorithm::Rs256]
+  }
+}
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub struct PasswordPolicy {
+  #[serde(default="PasswordPolicy::def_min_length")]
+  pub min_length: u32,
+
+  #[serde(default="PasswordPolicy::def_require_lowercase")]
+  pub require_lowercase: bool,
+
+  #[serde(default="PasswordPolicy::def_require_uppercase")]
+  pub require_uppercase: bool,
+
+  #[serde(default="PasswordPolicy::def_require_digit")]
+  pub require_digit: bool,
+
+  #[serde(default="PasswordPolicy::def_require_symbol")]
+  pub require_symbol: bool,
+
+  #[serde(default="PasswordPolicy::def_breached_passwords_file")]
+  pub breached_passwords_file: Option<String>,
+
+  #[serde(default="PasswordPolicy::def_reject_email")]
+  pub reject_email: bool,
+}
+
+impl PasswordPolicy {
+  pub fn new() -> PasswordPolicy {
+    PasswordPolicy {
+      min_length: PasswordPolicy::def_min_length(),
+      require_lowercase: PasswordPolicy::def_require_lowercase(),
+      require_uppercase: PasswordPolicy::def_require_uppercase(),
+      require_digit: PasswordPolicy::def_require_digit(),
+      require_symbol: PasswordPolicy::def_require_symbol(),
+      breached_passwords_file: PasswordPolicy::def_breached_passwords_file(),
+      reject_email: PasswordPolicy::def_reject_email(),
+    }
+  }
+
+  pub fn def_min_length() -> u32 {
+    8_u32
+  }
+
+  pub fn def_require_lowercase() -> bool {
+    false
+  }
+
+  pub fn def_require_uppercase() -> bool {
+    false
+  }
+
+  pub fn def_require_digit() -> bool {
+    false
+  }
+
+  pub fn def_require_symbol() -> bool {
+    false
+  }
+
+  pub fn def_breached_passwords_file() -> Option<String> {
+    None
+  }
+
+  pub fn def_reject_email() -> bool {
+    true
+  }
+}
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub struct Argon2Config {
+  #[serde(default="Argon2Config::def_memory_kib")]
+  pub memory_kib: u32,
+
+  #[serde(default="Argon2Config::def_iterations")]
+  pub iterations: u32,
+
+  #[serde(default="Argon2Config::def_parallelism")]
+  pub parallelism: u32,
+}
+
+impl Argon2Config {
+  pub fn new() -> Argon2Config {
+    Argon2Config {
+      memory_kib: Argon2Config::def_memory_kib(),
+      iterations: Argon2Config::def_iterations(),
+      parallelism: Argon2Config::def_parallelism(),
+    }
+  }
+
+  pub fn def_memory_kib() -> u32 {
+    19456_u32
+  }
+
+  pub fn def_iterations() -> u32 {
+    2_u32
+  }
+
+  pub fn def_parallelism() -> u32 {
+    1_u32
+  }
+}
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub struct JwtKey {
+  pub kid: String,
+
+  pub algorithm: JwtAlgorithm,
+
+  pub public_key_pem_file: String,
+
+  #[serde(default="JwtKey::def_private_key_pem_file")]
+  pub private_key_pem_file: Option<String>,
+}
+
+impl JwtKey {
+  pub fn new(kid: String, algorithm: JwtAlgorithm, public_key_pem_file: String) -> JwtKey {
+    JwtKey {
+      kid: kid,
+      algorithm: algorithm,
+      public_key_pem_file: public_key_pem_file,
+      private_key_pem_file: JwtKey::def_private_key_pem_file(),
+    }
+  }
+
+  pub fn def_private_key_pem_file() -> Option<String> {
+    None
+  }
+}
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub enum JwtAlgorithm {
+  #[serde(rename="rs256")]
+  Rs256,
+
+  #[serde(rename="eddsa")]
+  Eddsa,
+}
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub enum MailerConfig {
+  #[serde(rename="log")]
+  Log,
+
+  #[serde(rename="file")]
+  File(String),
+
+  #[serde(rename="smtp")]
+  Smtp(SmtpConfig),
+}
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub struct SmtpConfig {
+  pub host: String,
+
+  #[serde(default="SmtpConfig::def_port")]
+  pub port: u16,
+
+  #[serde(default="SmtpConfig::def_security")]
+  pub security: SmtpSecurity,
+
+  #[serde(default="SmtpConfig::def_username")]
+  pub username: Option<String>,
+
+  #[serde(default="SmtpConfig::def_password")]
+  pub password: Option<String>,
+}
+
+impl SmtpConfig {
+  pub fn new(host: String) -> SmtpConfig {
+    SmtpConfig {
+      host: host,
+      port: SmtpConfig::def_port(),
+      security: SmtpConfig::def_security(),
+      username: SmtpConfig::def_username(),
+      password: SmtpConfig::def_password(),
+    }
+  }
+
+  pub fn def_port() -> u16 {
+    587_u16
+  }
+
+  pub fn def_security() -> SmtpSecurity {
+    SmtpSecurity::Starttls
+  }
+
+  pub fn def_username() -> Option<String> {
+    None
+  }
+
+  pub fn def_password() -> Option<String> {
+    None
+  }
+}
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub enum SmtpSecurity {
+  #[serde(rename="none")]
+  None,
+
+  #[serde(rename="starttls")]
+  Starttls,
+
+  #[serde(rename="tls")]
+  Tls,
 }
 
 #[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
@@ -16,24 +16,45 @@ pub struct AppUser {
 
   pub email: StringNE,
 
-  pub is_admin: bool,
-
   #[serde(default="AppUser::def_hashed_password")]
-  pub hashed_password: StringNE,
+  pub hashed_password: Option<StringNE>,
+
+  #[serde(default="AppUser::def_verified_at")]
+  pub verified_at: Option<Instant>,
+
+  #[serde(default="AppUser::def_verification_sent_at")]
+  pub verification_sent_at: Option<Instant>,
+
+  #[serde(default="AppUser::def_deactivated_at")]
+  pub deactivated_at: Option<Instant>,
 }
 
 impl AppUser {
-  pub fn new(fullname: StringNE, email: StringNE, is_admin: bool) -> AppUser {
+  pub fn new(fullname: StringNE, email: StringNE) -> AppUser {
     AppUser {
       fullname: fullname,
       email: email,
-      is_admin: is_admin,
       hashed_password: AppUser::def_hashed_password(),
+      verified_at: AppUser::def_verified_at(),
+      verification_sent_at: AppUser::def_verification_sent_at(),
+      deactivated_at: AppUser::def_deactivated_at(),
     }
   }
 
-  pub fn def_hashed_password() -> StringNE {
-    "".to_string()
+  pub fn def_hashed_password() -> Option<StringNE> {
+    None
+  }
+
+  pub fn def_verified_at() -> Option<Instant> {
+    None
+  }
+
+  pub fn def_verification_sent_at() -> Option<Instant> {
+    None
+  }
+
+  pub fn def_deactivated_at() -> Option<Instant> {
+    None
   }
 }
 
@@ -63,6 +84,232 @@ impl<'de> Deserialize<'de> for AppUserTable
 
 pub type AppUserId = DbKey<AppUserTable>;
 
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub struct Role {
+  pub name: StringNE,
+
+  #[serde(default="Role::def_description")]
+  pub description: String,
+}
+
+impl Role {
+  pub fn new(name: StringNE) -> Role {
+    Role {
+      name: name,
+      description: Role::def_description(),
+    }
+  }
+
+  pub fn def_description() -> String {
+    "".to_string()
+  }
+}
+
+#[derive(Clone,Eq,Hash,PartialEq)]
+pub struct RoleTable(pub WithId<Role>);
+
+impl Serialize for RoleTable
+{
+  fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+  where
+      S: Serializer,
+  {
+      self.0.serialize(serializer)
+  }
+}
+
+impl<'de> Deserialize<'de> for RoleTable
+{
+  fn deserialize<D>(deserializer: D) -> Result<RoleTable, D::Error>
+  where
+      D: Deserializer<'de>,
+  {
+      let v = WithId::<Role>::deserialize(deserializer)?;
+      Ok(RoleTable(v))
+  }
+}
+
+pub type RoleId = DbKey<RoleTable>;
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub struct Permission {
+  pub role_id: RoleId,
+
+  pub name: StringNE,
+}
+
+impl Permission {
+  pub fn new(role_id: RoleId, name: StringNE) -> Permission {
+    Permission {
+      role_id: role_id,
+      name: name,
+    }
+  }
+}
+
+#[derive(Clone,Eq,Hash,PartialEq)]
+pub struct PermissionTable(pub WithId<Permission>);
+
+impl Serialize for PermissionTable
+{
+  fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+  where
+      S: Serializer,
+  {
+      self.0.serialize(serializer)
+  }
+}
+
+impl<'de> Deserialize<'de> for PermissionTable
+{
+  fn deserialize<D>(deserializer: D) -> Result<PermissionTable, D::Error>
+  where
+      D: Deserializer<'de>,
+  {
+      let v = WithId::<Permission>::deserialize(deserializer)?;
+      Ok(PermissionTable(v))
+  }
+}
+
+pub type PermissionId = DbKey<PermissionTable>;
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub struct UserRole {
+  pub user_id: AppUserId,
+
+  pub role_id: RoleId,
+}
+
+impl UserRole {
+  pub fn new(user_id: AppUserId, role_id: RoleId) -> UserRole {
+    UserRole {
+      user_id: user_id,
+      role_id: role_id,
+    }
+  }
+}
+
+#[derive(Clone,Eq,Hash,PartialEq)]
+pub struct UserRoleTable(pub WithId<UserRole>);
+
+impl Serialize for UserRoleTable
+{
+  fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+  where
+      S: Serializer,
+  {
+      self.0.serialize(serializer)
+  }
+}
+
+impl<'de> Deserialize<'de> for UserRoleTable
+{
+  fn deserialize<D>(deserializer: D) -> Result<UserRoleTable, D::Error>
+  where
+      D: Deserializer<'de>,
+  {
+      let v = WithId::<UserRole>::deserialize(deserializer)?;
+      Ok(UserRoleTable(v))
+  }
+}
+
+pub type UserRoleId = DbKey<UserRoleTable>;
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub struct Organisation {
+  pub name: StringNE,
+
+  pub created_at: Instant,
+}
+
+impl Organisation {
+  pub fn new(name: StringNE, created_at: Instant) -> Organisation {
+    Organisation {
+      name: name,
+      created_at: created_at,
+    }
+  }
+}
+
+#[derive(Clone,Eq,Hash,PartialEq)]
+pub struct OrganisationTable(pub WithId<Organisation>);
+
+impl Serialize for OrganisationTable
+{
+  fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+  where
+      S: Serializer,
+  {
+      self.0.serialize(serializer)
+  }
+}
+
+impl<'de> Deserialize<'de> for OrganisationTable
+{
+  fn deserialize<D>(deserializer: D) -> Result<OrganisationTable, D::Error>
+  where
+      D: Deserializer<'de>,
+  {
+      let v = WithId::<Organisation>::deserialize(deserializer)?;
+      Ok(OrganisationTable(v))
+  }
+}
+
+pub type OrganisationId = DbKey<OrganisationTable>;
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub struct OrganisationMember {
+  pub organisation_id: OrganisationId,
+
+  pub user_id: AppUserId,
+
+  #[serde(default="OrganisationMember::def_roles")]
+  pub roles: Vec<RoleId>,
+
+  pub joined_at: Instant,
+}
+
+impl OrganisationMember {
+  pub fn new(organisation_id: OrganisationId, user_id: AppUserId, joined_at: Instant) -> OrganisationMember {
+    OrganisationMember {
+      organisation_id: organisation_id,
+      user_id: user_id,
+      roles: OrganisationMember::def_roles(),
+      joined_at: joined_at,
+    }
+  }
+
+  pub fn def_roles() -> Vec<RoleId> {
+    vec![]
+  }
+}
+
+#[derive(Clone,Eq,Hash,PartialEq)]
+pub struct OrganisationMemberTable(pub WithId<OrganisationMember>);
+
+impl Serialize for OrganisationMemberTable
+{
+  fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+  where
+      S: Serializer,
+  {
+      self.0.serialize(serializer)
+  }
+}
+
+impl<'de> Deserialize<'de> for OrganisationMemberTable
+{
+  fn deserialize<D>(deserializer: D) -> Result<OrganisationMemberTable, D::Error>
+  where
+      D: Deserializer<'de>,
+  {
+      let v = WithId::<OrganisationMember>::deserialize(deserializer)?;
+      Ok(OrganisationMemberTable(v))
+  }
+}
+
+pub type OrganisationMemberId = DbKey<OrganisationMemberTable>;
+
 #[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
 pub struct Message {
   pub posted_at: Instant,
@@ -70,6 +317,18 @@ pub struct Message {
   pub posted_by: AppUserId,
 
   pub message: StringML,
+
+  #[serde(default="Message::def_organisation_id")]
+  pub organisation_id: Option<OrganisationId>,
+
+  #[serde(default="Message::def_reply_to")]
+  pub reply_to: Option<MessageId>,
+
+  #[serde(default="Message::def_edited_at")]
+  pub edited_at: Option<Instant>,
+
+  #[serde(default="Message::def_deleted_at")]
+  pub deleted_at: Option<Instant>,
 }
 
 impl Message {
@@ -78,8 +337,28 @@ impl Message {
       posted_at: posted_at,
       posted_by: posted_by,
       message: message,
+      organisation_id: Message::def_organisation_id(),
+      reply_to: Message::def_reply_to(),
+      edited_at: Message::def_edited_at(),
+      deleted_at: Message::def_deleted_at(),
     }
   }
+
+  pub fn def_organisation_id() -> Option<OrganisationId> {
+    None
+  }
+
+  pub fn def_reply_to() -> Option<MessageId> {
+    None
+  }
+
+  pub fn def_edited_at() -> Option<Instant> {
+    None
+  }
+
+  pub fn def_deleted_at() -> Option<Instant> {
+    None
+  }
 }
 
 #[derive(Clone,Eq,Hash,PartialEq)]
@@ -107,3 +386,718 @@ impl<'de> Deserialize<'de> for MessageTable
 }
 
 pub type MessageId = DbKey<MessageTable>;
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub struct MessageReaction {
+  pub message_id: MessageId,
+
+  pub user_id: AppUserId,
+
+  pub emoji: StringNE,
+
+  pub reacted_at: Instant,
+}
+
+impl MessageReaction {
+  pub fn new(message_id: MessageId, user_id: AppUserId, emoji: StringNE, reacted_at: Instant) -> MessageReaction {
+    MessageReaction {
+      message_id: message_id,
+      user_id: user_id,
+      emoji: emoji,
+      reacted_at: reacted_at,
+    }
+  }
+}
+
+#[derive(Clone,Eq,Hash,PartialEq)]
+pub struct MessageReactionTable(pub WithId<MessageReaction>);
+
+impl Serialize for MessageReactionTable
+{
+  fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+  where
+      S: Serializer,
+  {
+      self.0.serialize(serializer)
+  }
+}
+
+impl<'de> Deserialize<'de> for MessageReactionTable
+{
+  fn deserialize<D>(deserializer: D) -> Result<MessageReactionTable, D::Error>
+  where
+      D: Deserializer<'de>,
+  {
+      let v = WithId::<MessageReaction>::deserialize(deserializer)?;
+      Ok(MessageReactionTable(v))
+  }
+}
+
+pub type MessageReactionId = DbKey<MessageReactionTable>;
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub struct MessageRevision {
+  pub message_id: MessageId,
+
+  pub message: StringML,
+
+  pub written_at: Instant,
+
+  pub replaced_at: Instant,
+
+  pub replaced_by: AppUserId,
+}
+
+impl MessageRevision {
+  pub fn new(message_id: MessageId, message: StringML, written_at: Instant, replaced_at: Instant, replaced_by: AppUserId) -> MessageRevision {
+    MessageRevision {
+      message_id: message_id,
+      message: message,
+      written_at: written_at,
+      replaced_at: replaced_at,
+      replaced_by: replaced_by,
+    }
+  }
+}
+
+#[derive(Clone,Eq,Hash,PartialEq)]
+pub struct MessageRevisionTable(pub WithId<MessageRevision>);
+
+impl Serialize for MessageRevisionTable
+{
+  fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+  where
+      S: Serializer,
+  {
+      self.0.serialize(serializer)
+  }
+}
+
+impl<'de> Deserialize<'de> for MessageRevisionTable
+{
+  fn deserialize<D>(deserializer: D) -> Result<MessageRevisionTable, D::Error>
+  where
+      D: Deserializer<'de>,
+  {
+      let v = WithId::<MessageRevision>::deserialize(deserializer)?;
+      Ok(MessageRevisionTable(v))
+  }
+}
+
+pub type MessageRevisionId = DbKey<MessageRevisionTable>;
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub struct RefreshToken {
+  pub user_id: AppUserId,
+
+  pub family: String,
+
+  pub issued_at: Instant,
+
+  pub expires_at: Instant,
+
+  pub used_at: Option<Instant>,
+
+  pub revoked_at: Option<Instant>,
+}
+
+impl RefreshToken {
+  pub fn new(user_id: AppUserId, family: String, issued_at: Instant, expires_at: Instant, used_at: Option<Instant>, revoked_at: Option<Instant>) -> RefreshToken {
+    RefreshToken {
+      user_id: user_id,
+      family: family,
+      issued_at: issued_at,
+      expires_at: expires_at,
+      used_at: used_at,
+      revoked_at: revoked_at,
+    }
+  }
+}
+
+#[derive(Clone,Eq,Hash,PartialEq)]
+pub struct RefreshTokenTable(pub WithId<RefreshToken>);
+
+impl Serialize for RefreshTokenTable
+{
+  fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+  where
+      S: Serializer,
+  {
+      self.0.serialize(serializer)
+  }
+}
+
+impl<'de> Deserialize<'de> for RefreshTokenTable
+{
+  fn deserialize<D>(deserializer: D) -> Result<RefreshTokenTable, D::Error>
+  where
+      D: Deserializer<'de>,
+  {
+      let v = WithId::<RefreshToken>::deserialize(deserializer)?;
+      Ok(RefreshTokenTable(v))
+  }
+}
+
+pub type RefreshTokenId = DbKey<RefreshTokenTable>;
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub struct Session {
+  pub user_id: AppUserId,
+
+  pub user_agent: Option<String>,
+
+  pub client_ip: Option<String>,
+
+  pub created_at: Instant,
+
+  pub last_refreshed_at: Instant,
+
+  pub revoked_at: Option<Instant>,
+
+  #[serde(default="Session::def_organisation_id")]
+  pub organisation_id: Option<OrganisationId>,
+}
+
+impl Session {
+  pub fn new(user_id: AppUserId, user_agent: Option<String>, client_ip: Option<String>, created_at: Instant, last_refreshed_at: Instant, revoked_at: Option<Instant>) -> Session {
+    Session {
+      user_id: user_id,
+      user_agent: user_agent,
+      client_ip: client_ip,
+      created_at: created_at,
+      last_refreshed_at: last_refreshed_at,
+      revoked_at: revoked_at,
+      organisation_id: Session::def_organisation_id(),
+    }
+  }
+
+  pub fn def_organisation_id() -> Option<OrganisationId> {
+    None
+  }
+}
+
+#[derive(Clone,Eq,Hash,PartialEq)]
+pub struct SessionTable(pub WithId<Session>);
+
+impl Serialize for SessionTable
+{
+  fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+  where
+      S: Serializer,
+  {
+      self.0.serialize(serializer)
+  }
+}
+
+impl<'de> Deserialize<'de> for SessionTable
+{
+  fn deserialize<D>(deserializer: D) -> Result<SessionTable, D::Error>
+  where
+      D: Deserializer<'de>,
+  {
+      let v = WithId::<Session>::deserialize(deserializer)?;
+      Ok(SessionTable(v))
+  }
+}
+
+pub type SessionId = DbKey<SessionTable>;
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub struct PasswordResetToken {
+  pub user_id: AppUserId,
+
+  pub token_hash: String,
+
+  pub created_at: Instant,
+
+  pub expires_at: Instant,
+
+  pub used_at: Option<Instant>,
+}
+
+impl PasswordResetToken {
+  pub fn new(user_id: AppUserId, token_hash: String, created_at: Instant, expires_at: Instant, used_at: Option<Instant>) -> PasswordResetToken {
+    PasswordResetToken {
+      user_id: user_id,
+      token_hash: token_hash,
+      created_at: created_at,
+      expires_at: expires_at,
+      used_at: used_at,
+    }
+  }
+}
+
+#[derive(Clone,Eq,Hash,PartialEq)]
+pub struct PasswordResetTokenTable(pub WithId<PasswordResetToken>);
+
+impl Serialize for PasswordResetTokenTable
+{
+  fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+  where
+      S: Serializer,
+  {
+      self.0.serialize(serializer)
+  }
+}
+
+impl<'de> Deserialize<'de> for PasswordResetTokenTable
+{
+  fn deserialize<D>(deserializer: D) -> Result<PasswordResetTokenTable, D::Error>
+  where
+      D: Deserializer<'de>,
+  {
+      let v = WithId::<PasswordResetToken>::deserialize(deserializer)?;
+      Ok(PasswordResetTokenTable(v))
+  }
+}
+
+pub type PasswordResetTokenId = DbKey<PasswordResetTokenTable>;
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub struct Invitation {
+  pub email: StringNE,
+
+  pub roles: Vec<RoleId>,
+
+  pub token_hash: String,
+
+  pub created_by: AppUserId,
+
+  pub created_at: Instant,
+
+  pub expires_at: Instant,
+
+  pub used_at: Option<Instant>,
+
+  pub user_id: Option<AppUserId>,
+
+  pub revoked_at: Option<Instant>,
+}
+
+impl Invitation {
+  pub fn new(email: StringNE, roles: Vec<RoleId>, token_hash: String, created_by: AppUserId, created_at: Instant, expires_at: Instant, used_at: Option<Instant>, user_id: Option<AppUserId>, revoked_at: Option<Instant>) -> Invitation {
+    Invitation {
+      email: email,
+      roles: roles,
+      token_hash: token_hash,
+      created_by: created_by,
+      created_at: created_at,
+      expires_at: expires_at,
+      used_at: used_at,
+      user_id: user_id,
+      revoked_at: revoked_at,
+    }
+  }
+}
+
+#[derive(Clone,Eq,Hash,PartialEq)]
+pub struct InvitationTable(pub WithId<Invitation>);
+
+impl Serialize for InvitationTable
+{
+  fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+  where
+      S: Serializer,
+  {
+      self.0.serialize(serializer)
+  }
+}
+
+impl<'de> Deserialize<'de> for InvitationTable
+{
+  fn deserialize<D>(deserializer: D) -> Result<InvitationTable, D::Error>
+  where
+      D: Deserializer<'de>,
+  {
+      let v = WithId::<Invitation>::deserialize(deserializer)?;
+      Ok(InvitationTable(v))
+  }
+}
+
+pub type InvitationId = DbKey<InvitationTable>;
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub struct UserTotp {
+  pub user_id: AppUserId,
+
+  pub secret: String,
+
+  pub confirmed_at: Option<Instant>,
+
+  pub last_used_step: Option<u64>,
+}
+
+impl UserTotp {
+  pub fn new(user_id: AppUserId, secret: String, confirmed_at: Option<Instant>, last_used_step: Option<u64>) -> UserTotp {
+    UserTotp {
+      user_id: user_id,
+      secret: secret,
+      confirmed_at: confirmed_at,
+      last_used_step: last_used_step,
+    }
+  }
+}
+
+#[derive(Clone,Eq,Hash,PartialEq)]
+pub struct UserTotpTable(pub WithId<UserTotp>);
+
+impl Serialize for UserTotpTable
+{
+  fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+  where
+      S: Serializer,
+  {
+      self.0.serialize(serializer)
+  }
+}
+
+impl<'de> Deserialize<'de> for UserTotpTable
+{
+  fn deserialize<D>(deserializer: D) -> Result<UserTotpTable, D::Error>
+  where
+      D: Deserializer<'de>,
+  {
+      let v = WithId::<UserTotp>::deserialize(deserializer)?;
+      Ok(UserTotpTable(v))
+  }
+}
+
+pub type UserTotpId = DbKey<UserTotpTable>;
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub struct TotpRecoveryCode {
+  pub user_id: AppUserId,
+
+  pub code_hash: String,
+
+  pub used_at: Option<Instant>,
+}
+
+impl TotpRecoveryCode {
+  pub fn new(user_id: AppUserId, code_hash: String, used_at: Option<Instant>) -> TotpRecoveryCode {
+    TotpRecoveryCode {
+      user_id: user_id,
+      code_hash: code_hash,
+      used_at: used_at,
+    }
+  }
+}
+
+#[derive(Clone,Eq,Hash,PartialEq)]
+pub struct TotpRecoveryCodeTable(pub WithId<TotpRecoveryCode>);
+
+impl Serialize for TotpRecoveryCodeTable
+{
+  fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+  where
+      S: Serializer,
+  {
+      self.0.serialize(serializer)
+  }
+}
+
+impl<'de> Deserialize<'de> for TotpRecoveryCodeTable
+{
+  fn deserialize<D>(deserializer: D) -> Result<TotpRecoveryCodeTable, D::Error>
+  where
+      D: Deserializer<'de>,
+  {
+      let v = WithId::<TotpRecoveryCode>::deserialize(deserializer)?;
+      Ok(TotpRecoveryCodeTable(v))
+  }
+}
+
+pub type TotpRecoveryCodeId = DbKey<TotpRecoveryCodeTable>;
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub struct ApiKey {
+  pub user_id: AppUserId,
+
+  pub name: StringNE,
+
+  pub key_hash: String,
+
+  pub scopes: Vec<ApiKeyScope>,
+
+  pub created_at: Instant,
+
+  pub expires_at: Instant,
+
+  pub last_used_at: Option<Instant>,
+
+  pub revoked_at: Option<Instant>,
+}
+
+impl ApiKey {
+  pub fn new(user_id: AppUserId, name: StringNE, key_hash: String, scopes: Vec<ApiKeyScope>, created_at: Instant, expires_at: Instant, last_used_at: Option<Instant>, revoked_at: Option<Instant>) -> ApiKey {
+    ApiKey {
+      user_id: user_id,
+      name: name,
+      key_hash: key_hash,
+      scopes: scopes,
+      created_at: created_at,
+      expires_at: expires_at,
+      last_used_at: last_used_at,
+      revoked_at: revoked_at,
+    }
+  }
+}
+
+#[derive(Clone,Eq,Hash,PartialEq)]
+pub struct ApiKeyTable(pub WithId<ApiKey>);
+
+impl Serialize for ApiKeyTable
+{
+  fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+  where
+      S: Serializer,
+  {
+      self.0.serialize(serializer)
+  }
+}
+
+impl<'de> Deserialize<'de> for ApiKeyTable
+{
+  fn deserialize<D>(deserializer: D) -> Result<ApiKeyTable, D::Error>
+  where
+      D: Deserializer<'de>,
+  {
+      let v = WithId::<ApiKey>::deserialize(deserializer)?;
+      Ok(ApiKeyTable(v))
+  }
+}
+
+pub type ApiKeyId = DbKey<ApiKeyTable>;
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub enum ApiKeyScope {
+  #[serde(rename="user")]
+  User,
+
+  #[serde(rename="admin")]
+  Admin,
+}
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub struct OidcLogin {
+  pub state_hash: String,
+
+  pub code_verifier: String,
+
+  pub nonce: String,
+
+  pub created_at: Instant,
+
+  pub expires_at: Instant,
+
+  pub used_at: Option<Instant>,
+}
+
+impl OidcLogin {
+  pub fn new(state_hash: String, code_verifier: String, nonce: String, created_at: Instant, expires_at: Instant, used_at: Option<Instant>) -> OidcLogin {
+    OidcLogin {
+      state_hash: state_hash,
+      code_verifier: code_verifier,
+      nonce: nonce,
+      created_at: created_at,
+      expires_at: expires_at,
+      used_at: used_at,
+    }
+  }
+}
+
+#[derive(Clone,Eq,Hash,PartialEq)]
+pub struct OidcLoginTable(pub WithId<OidcLogin>);
+
+impl Serialize for OidcLoginTable
+{
+  fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+  where
+      S: Serializer,
+  {
+      self.0.serialize(serializer)
+  }
+}
+
+impl<'de> Deserialize<'de> for OidcLoginTable
+{
+  fn deserialize<D>(deserializer: D) -> Result<OidcLoginTable, D::Error>
+  where
+      D: Deserializer<'de>,
+  {
+      let v = WithId::<OidcLogin>::deserialize(deserializer)?;
+      Ok(OidcLoginTable(v))
+  }
+}
+
+pub type OidcLoginId = DbKey<OidcLoginTable>;
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub struct OidcIdentity {
+  pub user_id: AppUserId,
+
+  pub issuer: String,
+
+  pub subject: String,
+
+  pub linked_at: Instant,
+}
+
+impl OidcIdentity {
+  pub fn new(user_id: AppUserId, issuer: String, subject: String, linked_at: Instant) -> OidcIdentity {
+    OidcIdentity {
+      user_id: user_id,
+      issuer: issuer,
+      subject: subject,
+      linked_at: linked_at,
+    }
+  }
+}
+
+#[derive(Clone,Eq,Hash,PartialEq)]
+pub struct OidcIdentityTable(pub WithId<OidcIdentity>);
+
+impl Serialize for OidcIdentityTable
+{
+  fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+  where
+      S: Serializer,
+  {
+      self.0.serialize(serializer)
+  }
+}
+
+impl<'de> Deserialize<'de> for OidcIdentityTable
+{
+  fn deserialize<D>(deserializer: D) -> Result<OidcIdentityTable, D::Error>
+  where
+      D: Deserializer<'de>,
+  {
+      let v = WithId::<OidcIdentity>::deserialize(deserializer)?;
+      Ok(OidcIdentityTable(v))
+  }
+}
+
+pub type OidcIdentityId = DbKey<OidcIdentityTable>;
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub struct LoginFailure {
+  pub email: StringNE,
+
+  pub failed_count: u32,
+
+  pub last_failed_at: Instant,
+
+  pub locked_until: Option<Instant>,
+}
+
+impl LoginFailure {
+  pub fn new(email: StringNE, failed_count: u32, last_failed_at: Instant, locked_until: Option<Instant>) -> LoginFailure {
+    LoginFailure {
+      email: email,
+      failed_count: failed_count,
+      last_failed_at: last_failed_at,
+      locked_until: locked_until,
+    }
+  }
+}
+
+#[derive(Clone,Eq,Hash,PartialEq)]
+pub struct LoginFailureTable(pub WithId<LoginFailure>);
+
+impl Serialize for LoginFailureTable
+{
+  fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+  where
+      S: Serializer,
+  {
+      self.0.serialize(serializer)
+  }
+}
+
+impl<'de> Deserialize<'de> for LoginFailureTable
+{
+  fn deserialize<D>(deserializer: D) -> Result<LoginFailureTable, D::Error>
+  where
+      D: Deserializer<'de>,
+  {
+      let v = WithId::<LoginFailure>::deserialize(deserializer)?;
+      Ok(LoginFailureTable(v))
+  }
+}
+
+pub type LoginFailureId = DbKey<LoginFailureTable>;
+
+#[derive(Clone,Deserialize,PartialEq,Serialize)]
+pub struct AuditLog {
+  pub logged_at: Instant,
+
+  pub actor: AppUserId,
+
+  #[serde(default="AuditLog::def_impersonated")]
+  pub impersonated: Option<AppUserId>,
+
+  pub endpoint: String,
+
+  pub input: serde_json::Value,
+
+  pub outcome: AuditOutcome,
+
+  pub client_ip: Option<String>,
+}
+
+impl AuditLog {
+  pub fn new(logged_at: Instant, actor: AppUserId, endpoint: String, input: serde_json::Value, outcome: AuditOutcome, client_ip: Option<String>) -> AuditLog {
+    AuditLog {
+      logged_at: logged_at,
+      actor: actor,
+      impersonated: AuditLog::def_impersonated(),
+      endpoint: endpoint,
+      input: input,
+      outcome: outcome,
+      client_ip: client_ip,
+    }
+  }
+
+  pub fn def_impersonated() -> Option<AppUserId> {
+    None
+  }
+}
+
+#[derive(Clone,PartialEq)]
+pub struct AuditLogTable(pub WithId<AuditLog>);
+
+impl Serialize for AuditLogTable
+{
+  fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+  where
+      S: Serializer,
+  {
+      self.0.serialize(serializer)
+  }
+}
+
+impl<'de> Deserialize<'de> for AuditLogTable
+{
+  fn deserialize<D>(deserializer: D) -> Result<AuditLogTable, D::Error>
+  where
+      D: Deserializer<'de>,
+  {
+      let v = WithId::<AuditLog>::deserialize(deserializer)?;
+      Ok(AuditLogTable(v))
+  }
+}
+
+pub type AuditLogId = DbKey<AuditLogTable>;
+
+#[derive(Clone,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub enum AuditOutcome {
+  #[serde(rename="success")]
+  Success,
+
+  #[serde(rename="forbidden")]
+  Forbidden,
+
+  #[serde(rename="failed")]
+  Failed,
+}
@@ -0,0 +1,12 @@
+// This file is generated from the api definitions
+
+/**
+ * The timeout of each request with a RequestTimeoutSecs annotation, by
+ * the request's path
+ */
+pub fn request_timeout_secs(path: &str) -> Option<u32> {
+    match path {
+        "/messages/recent" => Some(5),
+        _ => None,
+    }
+}
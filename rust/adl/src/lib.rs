@@ -12,3 +12,5 @@ pub mod rt;
 pub mod custom;
 #[rustfmt::skip]
 pub mod db;
+#[rustfmt::skip]
+pub mod http;
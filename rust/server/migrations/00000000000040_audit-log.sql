@@ -0,0 +1,14 @@
+create table audit_log(
+  id text not null,                    -- String
+  logged_at timestamp with time zone not null, -- Instant
+  actor text not null,                 -- AppUserId
+  endpoint text not null,              -- String
+  input jsonb not null,                -- Json
+  outcome text not null,               -- AuditOutcome
+  client_ip text,                      -- Nullable<String>
+  primary key(id)
+);
+
+alter table audit_log add constraint audit_log_actor_fk foreign key (actor) references app_user(id);
+create index audit_log_1_idx on audit_log(logged_at);
+create index audit_log_2_idx on audit_log(actor);
@@ -0,0 +1,14 @@
+create table refresh_token(
+  id text not null,                    -- String
+  user_id text not null,               -- AppUserId
+  family text not null,                -- String
+  issued_at timestamp with time zone not null, -- Instant
+  expires_at timestamp with time zone not null, -- Instant
+  used_at timestamp with time zone,    -- Nullable<Instant>
+  revoked_at timestamp with time zone, -- Nullable<Instant>
+  primary key(id)
+);
+
+alter table refresh_token add constraint refresh_token_user_id_fk foreign key (user_id) references app_user(id);
+create index refresh_token_1_idx on refresh_token(user_id);
+create index refresh_token_2_idx on refresh_token(family);
@@ -0,0 +1,13 @@
+create table password_reset_token(
+  id text not null,                    -- String
+  user_id text not null,               -- AppUserId
+  token_hash text not null,            -- String
+  created_at timestamp with time zone not null, -- Instant
+  expires_at timestamp with time zone not null, -- Instant
+  used_at timestamp with time zone,    -- Nullable<Instant>
+  primary key(id)
+);
+
+alter table password_reset_token add constraint password_reset_token_user_id_fk foreign key (user_id) references app_user(id);
+create index password_reset_token_1_idx on password_reset_token(user_id);
+alter table password_reset_token add constraint password_reset_token_1_con unique (token_hash);
@@ -0,0 +1,2 @@
+alter table app_user add column verified_at timestamp with time zone;
+alter table app_user add column verification_sent_at timestamp with time zone;
@@ -0,0 +1,21 @@
+create table user_totp(
+  id text not null,                    -- String
+  user_id text not null,               -- AppUserId
+  secret text not null,                -- String
+  confirmed_at timestamp with time zone, -- Nullable<Instant>
+  last_used_step bigint,               -- Nullable<Word64>
+  primary key(id)
+);
+
+create table totp_recovery_code(
+  id text not null,                    -- String
+  user_id text not null,               -- AppUserId
+  code_hash text not null,             -- String
+  used_at timestamp with time zone,    -- Nullable<Instant>
+  primary key(id)
+);
+
+alter table totp_recovery_code add constraint totp_recovery_code_user_id_fk foreign key (user_id) references app_user(id);
+create index totp_recovery_code_1_idx on totp_recovery_code(user_id);
+alter table user_totp add constraint user_totp_user_id_fk foreign key (user_id) references app_user(id);
+alter table user_totp add constraint user_totp_1_con unique (user_id);
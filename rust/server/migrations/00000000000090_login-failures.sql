@@ -0,0 +1,10 @@
+create table login_failure(
+  id text not null,                    -- String
+  email text not null,                 -- StringNE
+  failed_count integer not null,       -- Word32
+  last_failed_at timestamp with time zone not null, -- Instant
+  locked_until timestamp with time zone, -- Nullable<Instant>
+  primary key(id)
+);
+
+alter table login_failure add constraint login_failure_1_con unique (email);
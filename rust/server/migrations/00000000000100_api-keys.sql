@@ -0,0 +1,16 @@
+create table api_key(
+  id text not null,                    -- String
+  user_id text not null,               -- AppUserId
+  name text not null,                  -- StringNE
+  key_hash text not null,              -- String
+  scopes jsonb not null,               -- Vector<ApiKeyScope>
+  created_at timestamp with time zone not null, -- Instant
+  expires_at timestamp with time zone not null, -- Instant
+  last_used_at timestamp with time zone, -- Nullable<Instant>
+  revoked_at timestamp with time zone, -- Nullable<Instant>
+  primary key(id)
+);
+
+alter table api_key add constraint api_key_user_id_fk foreign key (user_id) references app_user(id);
+create index api_key_1_idx on api_key(user_id);
+alter table api_key add constraint api_key_1_con unique (key_hash);
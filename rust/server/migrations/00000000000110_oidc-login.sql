@@ -0,0 +1,27 @@
+-- Users created by an OpenID Connect login have no password
+alter table app_user alter column hashed_password drop not null;
+
+create table oidc_identity(
+  id text not null,                    -- String
+  user_id text not null,               -- AppUserId
+  issuer text not null,                -- String
+  subject text not null,               -- String
+  linked_at timestamp with time zone not null, -- Instant
+  primary key(id)
+);
+
+create table oidc_login(
+  id text not null,                    -- String
+  state_hash text not null,            -- String
+  code_verifier text not null,         -- String
+  nonce text not null,                 -- String
+  created_at timestamp with time zone not null, -- Instant
+  expires_at timestamp with time zone not null, -- Instant
+  used_at timestamp with time zone,    -- Nullable<Instant>
+  primary key(id)
+);
+
+alter table oidc_identity add constraint oidc_identity_user_id_fk foreign key (user_id) references app_user(id);
+create index oidc_identity_1_idx on oidc_identity(user_id);
+alter table oidc_identity add constraint oidc_identity_1_con unique (issuer, subject);
+alter table oidc_login add constraint oidc_login_1_con unique (state_hash);
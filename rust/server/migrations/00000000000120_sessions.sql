@@ -0,0 +1,13 @@
+create table session(
+  id text not null,                    -- String
+  user_id text not null,               -- AppUserId
+  user_agent text,                     -- Nullable<String>
+  client_ip text,                      -- Nullable<String>
+  created_at timestamp with time zone not null, -- Instant
+  last_refreshed_at timestamp with time zone not null, -- Instant
+  revoked_at timestamp with time zone, -- Nullable<Instant>
+  primary key(id)
+);
+
+alter table session add constraint session_user_id_fk foreign key (user_id) references app_user(id);
+create index session_1_idx on session(user_id);
@@ -0,0 +1,2 @@
+alter table audit_log add column impersonated text;
+alter table audit_log add constraint audit_log_impersonated_fk foreign key (impersonated) references app_user(id);
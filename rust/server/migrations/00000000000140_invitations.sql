@@ -0,0 +1,18 @@
+create table invitation(
+  id text not null,                    -- String
+  email text not null,                 -- StringNE
+  is_admin boolean not null,           -- Bool
+  token_hash text not null,            -- String
+  created_by text not null,            -- AppUserId
+  created_at timestamp with time zone not null, -- Instant
+  expires_at timestamp with time zone not null, -- Instant
+  used_at timestamp with time zone,    -- Nullable<Instant>
+  user_id text,                        -- Nullable<AppUserId>
+  revoked_at timestamp with time zone, -- Nullable<Instant>
+  primary key(id)
+);
+
+alter table invitation add constraint invitation_created_by_fk foreign key (created_by) references app_user(id);
+alter table invitation add constraint invitation_user_id_fk foreign key (user_id) references app_user(id);
+create index invitation_1_idx on invitation(email);
+alter table invitation add constraint invitation_1_con unique (token_hash);
@@ -0,0 +1,43 @@
+create table role(
+  id text not null,                    -- String
+  name text not null,                  -- StringNE
+  description text not null,           -- String
+  primary key(id)
+);
+
+create table permission(
+  id text not null,                    -- String
+  role_id text not null,               -- RoleId
+  name text not null,                  -- StringNE
+  primary key(id)
+);
+
+create table user_role(
+  id text not null,                    -- String
+  user_id text not null,               -- AppUserId
+  role_id text not null,               -- RoleId
+  primary key(id)
+);
+
+alter table permission add constraint permission_role_id_fk foreign key (role_id) references role(id);
+alter table permission add constraint permission_1_con unique (role_id, name);
+alter table role add constraint role_1_con unique (name);
+alter table user_role add constraint user_role_user_id_fk foreign key (user_id) references app_user(id);
+alter table user_role add constraint user_role_role_id_fk foreign key (role_id) references role(id);
+create index user_role_1_idx on user_role(role_id);
+alter table user_role add constraint user_role_1_con unique (user_id, role_id);
+
+-- The admin role, which admin only endpoints require
+insert into role(id, name, description)
+  values ('G-admin', 'admin', 'Administrators');
+
+-- Admins were flagged on app_user and invitation before roles were
+-- introduced, so move them into the admin role
+insert into user_role(id, user_id, role_id)
+  select 'J-' || id, id, 'G-admin' from app_user where is_admin;
+alter table app_user drop column is_admin;
+
+alter table invitation add column roles jsonb not null default '[]';
+update invitation set roles = '["G-admin"]' where is_admin;
+alter table invitation alter column roles drop default;
+alter table invitation drop column is_admin;
@@ -0,0 +1 @@
+alter table app_user add column deactivated_at timestamp with time zone;
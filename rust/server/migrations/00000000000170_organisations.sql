@@ -0,0 +1,27 @@
+create table organisation(
+  id text not null,                    -- String
+  name text not null,                  -- StringNE
+  created_at timestamp with time zone not null, -- Instant
+  primary key(id)
+);
+
+create table organisation_member(
+  id text not null,                    -- String
+  organisation_id text not null,       -- OrganisationId
+  user_id text not null,               -- AppUserId
+  roles jsonb not null,                -- Vector<RoleId>
+  joined_at timestamp with time zone not null, -- Instant
+  primary key(id)
+);
+
+alter table message add column organisation_id text;
+alter table session add column organisation_id text;
+
+alter table message add constraint message_organisation_id_fk foreign key (organisation_id) references organisation(id);
+create index message_2_idx on message(organisation_id);
+alter table organisation add constraint organisation_1_con unique (name);
+alter table organisation_member add constraint organisation_member_organisation_id_fk foreign key (organisation_id) references organisation(id);
+alter table organisation_member add constraint organisation_member_user_id_fk foreign key (user_id) references app_user(id);
+create index organisation_member_1_idx on organisation_member(user_id);
+alter table organisation_member add constraint organisation_member_1_con unique (organisation_id, user_id);
+alter table session add constraint session_organisation_id_fk foreign key (organisation_id) references organisation(id);
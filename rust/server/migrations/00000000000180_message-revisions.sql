@@ -0,0 +1,16 @@
+alter table message add column edited_at timestamp with time zone;
+alter table message add column deleted_at timestamp with time zone;
+
+create table message_revision(
+  id text not null,                    -- String
+  message_id text not null,            -- MessageId
+  message text not null,               -- StringML
+  written_at timestamp with time zone not null, -- Instant
+  replaced_at timestamp with time zone not null, -- Instant
+  replaced_by text not null,           -- AppUserId
+  primary key(id)
+);
+
+alter table message_revision add constraint message_revision_message_id_fk foreign key (message_id) references message(id);
+alter table message_revision add constraint message_revision_replaced_by_fk foreign key (replaced_by) references app_user(id);
+create index message_revision_1_idx on message_revision(message_id);
@@ -0,0 +1,3 @@
+alter table message add column reply_to text;
+alter table message add constraint message_reply_to_fk foreign key (reply_to) references message(id);
+create index message_3_idx on message(reply_to);
@@ -0,0 +1,12 @@
+create table message_reaction(
+  id text not null,                    -- String
+  message_id text not null,            -- MessageId
+  user_id text not null,               -- AppUserId
+  emoji text not null,                 -- StringNE
+  reacted_at timestamp with time zone not null, -- Instant
+  primary key(id)
+);
+
+alter table message_reaction add constraint message_reaction_message_id_fk foreign key (message_id) references message(id);
+alter table message_reaction add constraint message_reaction_user_id_fk foreign key (user_id) references app_user(id);
+alter table message_reaction add constraint message_reaction_1_con unique (message_id, user_id, emoji);
@@ -1,7 +1,10 @@
 use clap::{Args, Parser, Subcommand};
+use std::path::PathBuf;
 
+use adl::gen::protoapp::config::server::{PasswordPolicy, ServerConfig};
 use adl::gen::protoapp::db::AppUser;
 
+use protoapp::server::password_policy::{self, PasswordPolicyChecker};
 use protoapp::server::{db, passwords::hash_password};
 use sqlx::postgres::PgPoolOptions;
 
@@ -36,9 +39,15 @@ enum Commands {
 
 #[derive(Args)]
 struct CreateUserArgs {
+    /// Give the user the admin role
     #[arg(long, default_value_t = false)]
     pub is_admin: bool,
 
+    /// The server config file, from which the password policy and hashing
+    /// parameters are read. Without it, the defaults apply.
+    #[arg(long)]
+    pub config: Option<PathBuf>,
+
     pub email: String,
     pub full_name: String,
     pub password: String,
@@ -48,12 +57,32 @@ async fn create_user(args: &CreateUserArgs) -> anyhow::Result<()> {
     let db_connection_url =
         std::env::var("DB_CONNECTION_URL").expect("DB_CONNECTION_URL environment variable");
 
-    let hashed_password = hash_password(&args.password).expect("password can be hashed");
+    let (policy, argon2) = match &args.config {
+        Some(path) => {
+            let config_str = std::fs::read_to_string(path).map_err(|_| {
+                anyhow::anyhow!("unable to read config file from {}", path.display())
+            })?;
+            let config: ServerConfig = serde_json::from_str(&config_str)
+                .map_err(|e| anyhow::anyhow!("unable to parse config file: {}", e))?;
+            (config.password_policy, config.argon2)
+        }
+        None => (
+            ServerConfig::def_password_policy(),
+            ServerConfig::def_argon2(),
+        ),
+    };
+    check_password(&policy, &args.password, &args.email)?;
+
+    let hashed_password = hash_password(&argon2, &args.password)
+        .map_err(|e| anyhow::anyhow!("unable to hash password: {}", e))?;
     let user = AppUser {
         fullname: args.full_name.clone(),
         email: args.email.clone(),
-        is_admin: args.is_admin,
-        hashed_password,
+        hashed_password: Some(hashed_password),
+        // Users created by an operator don't need to verify their email address
+        verified_at: Some(db::instant_now()),
+        verification_sent_at: None,
+        deactivated_at: None,
     };
 
     let pool = PgPoolOptions::new()
@@ -61,7 +90,24 @@ async fn create_user(args: &CreateUserArgs) -> anyhow::Result<()> {
         .connect(&db_connection_url)
         .await
         .expect("db connection to succeed");
+    let mut roles = vec![];
+    if args.is_admin {
+        let (role_id, _) = db::get_role_with_name(&pool, "admin")
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("the admin role doesn't exist"))?;
+        roles.push(role_id);
+    }
     let id = db::create_user(&pool, &user).await?;
+    db::set_user_roles(&pool, &id, &roles).await?;
     println!("user created with id {}", id.0);
     Ok(())
 }
+
+fn check_password(policy: &PasswordPolicy, password: &str, email: &str) -> anyhow::Result<()> {
+    let violations = PasswordPolicyChecker::new(policy)?.check(password, email);
+    if !violations.is_empty() {
+        let reasons: Vec<String> = violations.iter().map(password_policy::describe).collect();
+        return Err(anyhow::anyhow!("password {}", reasons.join(", ")));
+    }
+    Ok(())
+}
@@ -0,0 +1,78 @@
+use sqlx::PgPool;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use adl::gen::protoapp::config::server::ServerConfig;
+use adl::gen::protoapp::db::ApiKeyScope;
+
+use super::db;
+use super::jwt::{self, AccessClaims};
+use super::passwords::{hash_token, new_token};
+
+// API keys are passed as bearer tokens, and are distinguished from
+// JWTs by this prefix
+const API_KEY_PREFIX: &str = "pak_";
+
+/**
+ * Generate a new API key. Only its hash is stored.
+ */
+pub fn new_api_key() -> String {
+    format!("{}{}", API_KEY_PREFIX, new_token())
+}
+
+pub fn is_api_key(token: &str) -> bool {
+    token.starts_with(API_KEY_PREFIX)
+}
+
+/**
+ * The claims for a request made with an API key, or None if the key is
+ * unknown, expired or revoked. Keys with the admin scope act with all of
+ * their user's roles while the user is an admin, and otherwise only with
+ * the user role, so demoting a user also limits their keys. They act
+ * within the first organisation their user joined.
+ */
+pub async fn access_claims(
+    pool: &PgPool,
+    cfg: &ServerConfig,
+    key: &str,
+) -> sqlx::Result<Option<AccessClaims>> {
+    let (id, api_key) = match db::get_api_key(pool, &hash_token(key)).await? {
+        Some(v) => v,
+        None => return Ok(None),
+    };
+    if api_key.revoked_at.is_some() || api_key.expires_at.0 <= SystemTime::now() {
+        return Ok(None);
+    }
+    let user_roles = db::user_role_names(pool, &[api_key.user_id.clone()])
+        .await?
+        .remove(&api_key.user_id.0)
+        .unwrap_or_default();
+    let user_is_admin = user_roles.iter().any(|r| r == jwt::ROLE_ADMIN);
+    let roles = if user_is_admin && api_key.scopes.contains(&ApiKeyScope::Admin) {
+        jwt::with_user_role(user_roles)
+    } else if api_key.scopes.contains(&ApiKeyScope::User) {
+        vec![jwt::ROLE_USER.to_owned()]
+    } else {
+        return Ok(None);
+    };
+    let org = db::default_organisation(pool, &api_key.user_id).await?;
+    db::record_api_key_used(pool, &id).await?;
+    let created_at = unix_secs(api_key.created_at.0);
+    Ok(Some(AccessClaims {
+        iss: cfg.jwt_issuer.clone(),
+        aud: cfg.jwt_audience.clone(),
+        sub: api_key.user_id.0,
+        exp: unix_secs(api_key.expires_at.0),
+        nbf: created_at,
+        iat: created_at,
+        roles,
+        sid: None,
+        act: None,
+        org: org.map(|id| id.0),
+    }))
+}
+
+fn unix_secs(t: SystemTime) -> usize {
+    t.duration_since(UNIX_EPOCH)
+        .expect("Time went backwards")
+        .as_secs() as usize
+}
@@ -0,0 +1,75 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use adl::custom::common::db::DbKey;
+use adl::gen::protoapp::db::AuditLog;
+use sqlx::PgPool;
+
+use crate::server::db;
+use crate::server::poem_adl_interop::{AuditLogger, AuditRecord, DynAuditLogger};
+
+/**
+ * Request fields whose values are never written to the audit log
+ */
+const REDACTED_FIELDS: [&str; 4] = ["password", "secret", "token", "jwt"];
+
+const REDACTED: &str = "<redacted>";
+
+struct DbAuditLogger {
+    db_pool: Arc<PgPool>,
+}
+
+pub fn new_db_audit_logger(db_pool: Arc<PgPool>) -> DynAuditLogger {
+    Arc::new(Box::new(DbAuditLogger { db_pool }))
+}
+
+impl AuditLogger for DbAuditLogger {
+    fn log(&self, record: AuditRecord) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        Box::pin(async move {
+            let audit_log = AuditLog {
+                logged_at: db::instant_now(),
+                actor: DbKey::from_string(record.actor),
+                impersonated: record.impersonated.map(DbKey::from_string),
+                endpoint: record.endpoint,
+                input: redact(record.input),
+                outcome: record.outcome,
+                client_ip: record.client_ip,
+            };
+            // A failure to write the audit log shouldn't change the response
+            // of a request that has already been processed.
+            if let Err(e) = db::insert_audit_log(&self.db_pool, &audit_log).await {
+                log::error!(
+                    "failed to write audit log for {}: {}",
+                    audit_log.endpoint,
+                    e
+                );
+            }
+        })
+    }
+}
+
+/**
+ * Replace the values of any sensitive fields, at any depth
+ */
+pub fn redact(jv: serde_json::Value) -> serde_json::Value {
+    match jv {
+        serde_json::Value::Object(fields) => serde_json::Value::Object(
+            fields
+                .into_iter()
+                .map(|(k, v)| {
+                    let lk = k.to_lowercase();
+                    if REDACTED_FIELDS.iter().any(|f| lk.contains(f)) {
+                        (k, serde_json::Value::String(REDACTED.to_owned()))
+                    } else {
+                        (k, redact(v))
+                    }
+                })
+                .collect(),
+        ),
+        serde_json::Value::Array(items) => {
+            serde_json::Value::Array(items.into_iter().map(redact).collect())
+        }
+        jv => jv,
+    }
+}
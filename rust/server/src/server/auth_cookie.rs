@@ -0,0 +1,128 @@
+use poem::session::CookieConfig;
+use poem::web::cookie::{Cookie, CookieJar, SameSite};
+use std::time::Duration;
+
+use adl::gen::protoapp::config::server::{AuthCookieConfig, CookieSameSite, ServerConfig};
+
+const REFRESH_TOKEN: &str = "refreshToken";
+const OIDC_STATE: &str = "oidcState";
+const HOST_PREFIX: &str = "__Host-";
+
+/**
+ * Check that the cookie attributes are consistent, as browsers silently
+ * drop __Host- prefixed cookies that aren't
+ */
+pub fn check_config(cfg: &AuthCookieConfig) -> Result<(), String> {
+    if cfg.host_prefix {
+        if !cfg.secure {
+            return Err("host_prefix requires secure cookies".to_owned());
+        }
+        if cfg.path != "/" {
+            return Err("host_prefix requires a path of \"/\"".to_owned());
+        }
+        if cfg.domain.is_some() {
+            return Err("host_prefix doesn't allow a domain".to_owned());
+        }
+    }
+    Ok(())
+}
+
+pub fn refresh_cookie_name(cfg: &AuthCookieConfig) -> String {
+    cookie_name(cfg, REFRESH_TOKEN)
+}
+
+pub fn get_refresh_token(cfg: &AuthCookieConfig, cookies: &CookieJar) -> Option<String> {
+    cookies
+        .get(&refresh_cookie_name(cfg))
+        .map(|cookie| cookie.value_str().to_owned())
+}
+
+/**
+ * Set the refresh token cookie, to expire with the token
+ */
+pub fn set_refresh_token(cfg: &ServerConfig, cookies: &CookieJar, refresh_jwt: &str) {
+    let mut cookie = refresh_cookie(&cfg.auth_cookie, refresh_jwt);
+    cookie.set_max_age(Duration::from_secs(cfg.jwt_refresh_expiry_secs as u64));
+    cookies.add(cookie);
+}
+
+/**
+ * Clear the refresh token cookie. The removal must have the same path
+ * and domain as the cookie, or browsers will ignore it.
+ */
+pub fn remove_refresh_token(cfg: &AuthCookieConfig, cookies: &CookieJar) {
+    let mut cookie = refresh_cookie(cfg, "");
+    cookie.make_removal();
+    cookies.add(cookie);
+}
+
+/**
+ * Set the cookie binding an OpenID Connect login to the browser that
+ * started it, to expire with the login
+ */
+pub fn set_oidc_state(cfg: &AuthCookieConfig, cookies: &CookieJar, state: &str, expiry: Duration) {
+    let mut cookie = auth_cookie(cfg, OIDC_STATE, state);
+    cookie.set_max_age(expiry);
+    cookies.add(cookie);
+}
+
+pub fn get_oidc_state(cfg: &AuthCookieConfig, cookies: &CookieJar) -> Option<String> {
+    cookies
+        .get(&cookie_name(cfg, OIDC_STATE))
+        .map(|cookie| cookie.value_str().to_owned())
+}
+
+/**
+ * Clear the OpenID Connect login cookie, which is only good for one login
+ */
+pub fn remove_oidc_state(cfg: &AuthCookieConfig, cookies: &CookieJar) {
+    let mut cookie = auth_cookie(cfg, OIDC_STATE, "");
+    cookie.make_removal();
+    cookies.add(cookie);
+}
+
+/**
+ * The configuration of the session cookie, which shares the attributes of
+ * the refresh token cookie
+ */
+pub fn session_cookie_config(cfg: &AuthCookieConfig) -> CookieConfig {
+    let config = CookieConfig::default()
+        .secure(cfg.secure)
+        .same_site(same_site(&cfg.same_site))
+        .path(cfg.path.clone());
+    match &cfg.domain {
+        Some(domain) => config.domain(domain.clone()),
+        None => config,
+    }
+}
+
+fn cookie_name(cfg: &AuthCookieConfig, name: &str) -> String {
+    match cfg.host_prefix {
+        true => format!("{}{}", HOST_PREFIX, name),
+        false => name.to_owned(),
+    }
+}
+
+fn refresh_cookie(cfg: &AuthCookieConfig, value: &str) -> Cookie {
+    auth_cookie(cfg, REFRESH_TOKEN, value)
+}
+
+fn auth_cookie(cfg: &AuthCookieConfig, name: &str, value: &str) -> Cookie {
+    let mut cookie = Cookie::new_with_str(cookie_name(cfg, name), value);
+    cookie.set_http_only(true);
+    cookie.set_secure(cfg.secure);
+    cookie.set_same_site(same_site(&cfg.same_site));
+    cookie.set_path(cfg.path.clone());
+    if let Some(domain) = &cfg.domain {
+        cookie.set_domain(domain.clone());
+    }
+    cookie
+}
+
+fn same_site(same_site: &CookieSameSite) -> SameSite {
+    match same_site {
+        CookieSameSite::Strict => SameSite::Strict,
+        CookieSameSite::Lax => SameSite::Lax,
+        CookieSameSite::None => SameSite::None,
+    }
+}
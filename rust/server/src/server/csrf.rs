@@ -0,0 +1,98 @@
+use poem::http::StatusCode;
+use poem::web::Json;
+use poem::IntoResponse;
+use std::fmt;
+
+use adl::gen::common::http::HttpError;
+use adl::gen::protoapp::config::server::ServerConfig;
+
+use crate::server::poem_adl_interop::{HandlerError, HandlerResult};
+
+/**
+ * The reasons a request that uses the refresh token cookie can be
+ * refused as a possible cross-site request forgery
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CsrfFailure {
+    MissingHeader,
+    MissingOrigin,
+    DisallowedOrigin,
+}
+
+impl fmt::Display for CsrfFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let reason = match self {
+            CsrfFailure::MissingHeader => "missing required header",
+            CsrfFailure::MissingOrigin => "missing origin and referer",
+            CsrfFailure::DisallowedOrigin => "disallowed origin",
+        };
+        f.write_str(reason)
+    }
+}
+
+/**
+ * Check that a request that uses the refresh token cookie was made by
+ * the app, rather than forged by another site
+ */
+pub fn check_request(cfg: &ServerConfig, req: &poem::Request) -> HandlerResult<()> {
+    match check(cfg, req) {
+        Ok(()) => Ok(()),
+        Err(failure) => {
+            log::warn!(
+                "refused cookie request to {}: {}",
+                req.uri().path(),
+                failure
+            );
+            Err(csrf_rejected())
+        }
+    }
+}
+
+fn check(cfg: &ServerConfig, req: &poem::Request) -> Result<(), CsrfFailure> {
+    let csrf = &cfg.csrf;
+    if !csrf.enabled {
+        return Ok(());
+    }
+    if req.header(&csrf.required_header).is_none() {
+        return Err(CsrfFailure::MissingHeader);
+    }
+
+    // Browsers send an Origin header with every POST, but some privacy
+    // settings replace it with "null", in which case fall back to the
+    // Referer
+    let origin = match req.header("origin") {
+        Some(origin) if origin != "null" => origin,
+        _ => req.header("referer").ok_or(CsrfFailure::MissingOrigin)?,
+    };
+    let origin = url_origin(origin).ok_or(CsrfFailure::DisallowedOrigin)?;
+    let allowed = std::iter::once(&cfg.app_url)
+        .chain(csrf.allowed_origins.iter())
+        .filter_map(|url| url_origin(url))
+        .any(|allowed| allowed == origin);
+    match allowed {
+        true => Ok(()),
+        false => Err(CsrfFailure::DisallowedOrigin),
+    }
+}
+
+/**
+ * The serialized origin (scheme, host and port) of a url
+ */
+fn url_origin(url: &str) -> Option<String> {
+    let origin = reqwest::Url::parse(url).ok()?.origin();
+    match origin.is_tuple() {
+        true => Some(origin.ascii_serialization()),
+        false => None,
+    }
+}
+
+fn csrf_rejected() -> HandlerError {
+    let body = HttpError {
+        code: "csrf".to_owned(),
+        message: "The request may have been forged by another site".to_owned(),
+    };
+    let resp = Json(body)
+        .with_status(StatusCode::FORBIDDEN)
+        .into_response();
+    HandlerError::Poem(poem::Error::from_response(resp))
+}
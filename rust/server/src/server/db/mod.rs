@@ -1,7 +1,12 @@
-use sea_query::{Expr, Func, Order, PostgresQueryBuilder, Query};
+use sea_query::{
+    Alias, Cond, Expr, Func, JoinType, LockType, OnConflict, Order, PostgresQueryBuilder, Query,
+    SelectStatement, UpdateStatement,
+};
 use sea_query_binder::SqlxBinder;
+use sqlx::postgres::PgRow;
 use sqlx::Row;
-use std::time::SystemTime;
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, SystemTime};
 
 use adl::{
     custom::common::{db::DbKey, time::Instant},
@@ -11,11 +16,19 @@ use adl::{
     },
     gen::protoapp::{
         apis,
-        db::{AppUser, AppUserId, MessageId},
+        db::{
+            ApiKey, ApiKeyId, AppUser, AppUserId, AuditLog, AuditLogId, Invitation, InvitationId,
+            LoginFailure, LoginFailureId, Message, MessageId, MessageReactionId, MessageRevision,
+            MessageRevisionId, OidcIdentityId, OidcLogin, OidcLoginId, Organisation,
+            OrganisationId, OrganisationMember, OrganisationMemberId, PasswordResetToken,
+            PasswordResetTokenId, PermissionId, RefreshToken, RefreshTokenId, Role, RoleId,
+            Session, SessionId, TotpRecoveryCodeId, UserRoleId, UserTotp, UserTotpId,
+        },
     },
 };
 
 type DbPool = sqlx::PgPool;
+type DbTransaction<'a> = sqlx::Transaction<'a, sqlx::Postgres>;
 
 pub async fn get_user_with_email(
     pool: &DbPool,
@@ -41,8 +54,10 @@ async fn get_user(
         .scolumn(T::id())
         .scolumn(T::fullname())
         .scolumn(T::email())
-        .scolumn(T::is_admin())
         .scolumn(T::hashed_password())
+        .scolumn(T::verified_at())
+        .scolumn(T::verification_sent_at())
+        .scolumn(T::deactivated_at())
         .and_where(where_expr)
         .build_sqlx(PostgresQueryBuilder);
 
@@ -53,8 +68,10 @@ async fn get_user(
                 AppUser {
                     fullname: T::fullname().from_row(&r),
                     email: T::email().from_row(&r),
-                    is_admin: T::is_admin().from_row(&r),
                     hashed_password: T::hashed_password().from_row(&r),
+                    verified_at: T::verified_at().from_row(&r),
+                    verification_sent_at: T::verification_sent_at().from_row(&r),
+                    deactivated_at: T::deactivated_at().from_row(&r),
                 },
             )
         })
@@ -65,6 +82,36 @@ async fn get_user(
 }
 
 pub async fn create_user(pool: &DbPool, user: &AppUser) -> sqlx::Result<AppUserId> {
+    let mut tx = pool.begin().await?;
+    let id = insert_user(&mut tx, user).await?;
+    tx.commit().await?;
+    Ok(id)
+}
+
+/**
+ * Create a user that accepts an invitation, with the given roles. Returns
+ * None, and creates no user, if the invitation has already been used or
+ * revoked. Nothing is changed unless every step succeeds, so the
+ * invitation can be used again after a failure.
+ */
+pub async fn create_invited_user(
+    pool: &DbPool,
+    invitation_id: &InvitationId,
+    user: &AppUser,
+    roles: &[RoleId],
+) -> sqlx::Result<Option<AppUserId>> {
+    let mut tx = pool.begin().await?;
+    if !use_invitation(&mut tx, invitation_id).await? {
+        return Ok(None);
+    }
+    let user_id = insert_user(&mut tx, user).await?;
+    set_invitation_user(&mut tx, invitation_id, &user_id).await?;
+    replace_user_roles(&mut tx, &user_id, roles).await?;
+    tx.commit().await?;
+    Ok(Some(user_id))
+}
+
+async fn insert_user(tx: &mut DbTransaction<'_>, user: &AppUser) -> sqlx::Result<AppUserId> {
     type T = schema::AppUser;
     let id: AppUserId = DbKey::new(T::id_prefix());
 
@@ -72,8 +119,10 @@ pub async fn create_user(pool: &DbPool, user: &AppUser) -> sqlx::Result<AppUserI
         .field(T::id(), &id)
         .field(T::email(), &user.email)
         .field(T::fullname(), &user.fullname)
-        .field(T::is_admin(), &user.is_admin)
         .field(T::hashed_password(), &user.hashed_password)
+        .field(T::verified_at(), &user.verified_at)
+        .field(T::verification_sent_at(), &user.verification_sent_at)
+        .field(T::deactivated_at(), &user.deactivated_at)
         .build();
 
     let (sql, values) = Query::insert()
@@ -81,7 +130,7 @@ pub async fn create_user(pool: &DbPool, user: &AppUser) -> sqlx::Result<AppUserI
         .columns(icolumns)
         .values_panic(ivalues)
         .build_sqlx(PostgresQueryBuilder);
-    sqlx::query_with(&sql, values).execute(pool).await?;
+    sqlx::query_with(&sql, values).execute(tx).await?;
     Ok(id)
 }
 
@@ -91,16 +140,116 @@ pub async fn update_user(pool: &DbPool, user_id: &AppUserId, user: &AppUser) ->
         .table(T::table())
         .svalue(T::email(), &user.email)
         .svalue(T::fullname(), &user.fullname)
-        .svalue(T::is_admin(), &user.is_admin)
         .svalue(T::hashed_password(), &user.hashed_password)
+        .svalue(T::verified_at(), &user.verified_at)
+        .svalue(T::verification_sent_at(), &user.verification_sent_at)
+        .and_where(T::id().eq_value(user_id))
+        .build_sqlx(PostgresQueryBuilder);
+    sqlx::query_with(&sql, values).execute(pool).await?;
+    Ok(())
+}
+
+pub async fn update_user_password(
+    pool: &DbPool,
+    user_id: &AppUserId,
+    hashed_password: &str,
+) -> sqlx::Result<()> {
+    type T = schema::AppUser;
+    let (sql, values) = Query::update()
+        .table(T::table())
+        .svalue(T::hashed_password(), &Some(hashed_password.to_owned()))
+        .and_where(T::id().eq_value(user_id))
+        .build_sqlx(PostgresQueryBuilder);
+    sqlx::query_with(&sql, values).execute(pool).await?;
+    Ok(())
+}
+
+pub async fn set_user_verified(pool: &DbPool, user_id: &AppUserId) -> sqlx::Result<()> {
+    type T = schema::AppUser;
+    let (sql, values) = Query::update()
+        .table(T::table())
+        .svalue(T::verified_at(), &Some(instant_now()))
         .and_where(T::id().eq_value(user_id))
+        .and_where(T::verified_at().expr().is_null())
         .build_sqlx(PostgresQueryBuilder);
     sqlx::query_with(&sql, values).execute(pool).await?;
     Ok(())
 }
 
+/**
+ * Set or clear the time at which a user was deactivated. Returns false if
+ * there is no such user.
+ */
+pub async fn set_user_deactivated(
+    pool: &DbPool,
+    user_id: &AppUserId,
+    deactivated_at: Option<Instant>,
+) -> sqlx::Result<bool> {
+    type T = schema::AppUser;
+    let (sql, values) = Query::update()
+        .table(T::table())
+        .svalue(T::deactivated_at(), &deactivated_at)
+        .and_where(T::id().eq_value(user_id))
+        .build_sqlx(PostgresQueryBuilder);
+    let result = sqlx::query_with(&sql, values).execute(pool).await?;
+    Ok(result.rows_affected() == 1)
+}
+
+/**
+ * The ids of every deactivated user
+ */
+pub async fn deactivated_user_ids(pool: &DbPool) -> sqlx::Result<HashSet<String>> {
+    type T = schema::AppUser;
+    let (sql, values) = Query::select()
+        .from(T::table())
+        .scolumn(T::id())
+        .and_where(T::deactivated_at().expr().is_not_null())
+        .build_sqlx(PostgresQueryBuilder);
+    let user_ids = sqlx::query_with(&sql, values)
+        .map(|r| {
+            let user_id: AppUserId = T::id().from_row(&r);
+            user_id.0
+        })
+        .fetch_all(pool)
+        .await?;
+    Ok(user_ids.into_iter().collect())
+}
+
+/**
+ * Record that a verification email is being sent to an unverified user.
+ * Returns false, without recording anything, if one was sent within the
+ * given interval.
+ */
+pub async fn record_verification_sent(
+    pool: &DbPool,
+    user_id: &AppUserId,
+    resend_interval: Duration,
+) -> sqlx::Result<bool> {
+    type T = schema::AppUser;
+    let now = instant_now();
+    let cutoff = Instant(now.0 - resend_interval);
+    let (sql, values) = Query::update()
+        .table(T::table())
+        .svalue(T::verification_sent_at(), &Some(now))
+        .and_where(T::id().eq_value(user_id))
+        .and_where(T::verified_at().expr().is_null())
+        .cond_where(
+            Cond::any()
+                .add(T::verification_sent_at().expr().is_null())
+                .add(
+                    T::verification_sent_at()
+                        .expr()
+                        .lt(T::verification_sent_at().value_expr(&Some(cutoff))),
+                ),
+        )
+        .build_sqlx(PostgresQueryBuilder);
+    let result = sqlx::query_with(&sql, values).execute(pool).await?;
+    Ok(result.rows_affected() == 1)
+}
+
 pub async fn query_users(
     pool: &DbPool,
+    organisation_id: &Option<OrganisationId>,
     offset: u64,
     limit: u64,
 ) -> sqlx::Result<Vec<apis::ui::UserWithId>> {
@@ -110,30 +259,42 @@ pub async fn query_users(
         .scolumn(T::id())
         .scolumn(T::fullname())
         .scolumn(T::email())
-        .scolumn(T::is_admin())
+        .scolumn(T::deactivated_at())
+        .cond_where(users_filter(organisation_id))
         .offset(offset)
         .limit(limit)
         .build_sqlx(PostgresQueryBuilder);
-    let users = sqlx::query_with(&sql, values)
+    let mut users = sqlx::query_with(&sql, values)
         .map(|r| apis::ui::UserWithId {
             id: T::id().from_row(&r),
             value: apis::ui::User {
                 fullname: T::fullname().from_row(&r),
                 email: T::email().from_row(&r),
-                is_admin: T::is_admin().from_row(&r),
+                roles: vec![],
+                active: T::deactivated_at().from_row(&r).is_none(),
             },
         })
         .fetch_all(pool)
         .await?;
+
+    let user_ids: Vec<AppUserId> = users.iter().map(|u| u.id.clone()).collect();
+    let mut role_names = user_role_names(pool, &user_ids).await?;
+    for user in &mut users {
+        user.value.roles = role_names.remove(&user.id.0).unwrap_or_default();
+    }
     Ok(users)
 }
 
-pub async fn user_count(pool: &DbPool) -> sqlx::Result<u64> {
+pub async fn user_count(
+    pool: &DbPool,
+    organisation_id: &Option<OrganisationId>,
+) -> sqlx::Result<u64> {
     type M = schema::AppUser;
 
     let (sql, values) = Query::select()
         .from(M::table())
         .expr(Func::count(Expr::asterisk()))
+        .cond_where(users_filter(organisation_id))
         .build_sqlx(PostgresQueryBuilder);
 
     let count: i64 = sqlx::query_with(&sql, values)
@@ -143,25 +304,129 @@ pub async fn user_count(pool: &DbPool) -> sqlx::Result<u64> {
     Ok(count as u64)
 }
 
-pub async fn new_message(
+/**
+ * Within an organisation, only its members are visible
+ */
+fn users_filter(organisation_id: &Option<OrganisationId>) -> Cond {
+    type T = schema::AppUser;
+    type O = schema::OrganisationMember;
+    match organisation_id {
+        Some(organisation_id) => Cond::all().add(
+            T::id().expr().in_subquery(
+                Query::select()
+                    .from(O::table())
+                    .scolumn(O::user_id())
+                    .and_where(O::organisation_id().eq_value(organisation_id))
+                    .to_owned(),
+            ),
+        ),
+        None => Cond::all(),
+    }
+}
+
+/**
+ * The names of the roles held by each of the given users, keyed by user id
+ */
+pub async fn user_role_names(
+    pool: &DbPool,
+    user_ids: &[AppUserId],
+) -> sqlx::Result<HashMap<String, Vec<String>>> {
+    type T = schema::UserRole;
+    type R = schema::Role;
+    let (sql, values) = Query::select()
+        .scolumn(T::user_id())
+        .scolumn(R::name())
+        .from(T::table())
+        .inner_join(R::table(), R::id().expr().eq(T::role_id().expr()))
+        .and_where(
+            T::user_id()
+                .expr()
+                .is_in(user_ids.iter().map(|id| T::user_id().value_expr(id))),
+        )
+        .order_by(R::name().iden(), Order::Asc)
+        .build_sqlx(PostgresQueryBuilder);
+
+    let rows = sqlx::query_with(&sql, values)
+        .map(|r| (T::user_id().from_row(&r), R::name().from_row(&r)))
+        .fetch_all(pool)
+        .await?;
+    let mut role_names: HashMap<String, Vec<String>> = HashMap::new();
+    for (user_id, name) in rows {
+        role_names.entry(user_id.0).or_default().push(name);
+    }
+    Ok(role_names)
+}
+
+/**
+ * Replace the roles held by a user
+ */
+pub async fn set_user_roles(
     pool: &DbPool,
     user_id: &AppUserId,
-    message: &String,
-) -> sqlx::Result<MessageId> {
-    type T = schema::Message;
+    roles: &[RoleId],
+) -> sqlx::Result<()> {
+    let mut tx = pool.begin().await?;
+    replace_user_roles(&mut tx, user_id, roles).await?;
+    tx.commit().await?;
+    Ok(())
+}
 
-    let id: MessageId = DbKey::new(T::id_prefix());
-    let posted_at = instant_now();
+/**
+ * Replace the roles held by a user. The user is locked until the
+ * transaction ends, so that concurrent replacements don't merge.
+ */
+async fn replace_user_roles(
+    tx: &mut DbTransaction<'_>,
+    user_id: &AppUserId,
+    roles: &[RoleId],
+) -> sqlx::Result<()> {
+    type A = schema::AppUser;
+    type T = schema::UserRole;
+    let (sql, values) = Query::select()
+        .from(A::table())
+        .scolumn(A::id())
+        .and_where(A::id().eq_value(user_id))
+        .lock(LockType::Update)
+        .build_sqlx(PostgresQueryBuilder);
+    sqlx::query_with(&sql, values).execute(&mut *tx).await?;
+
+    let (sql, values) = Query::delete()
+        .from_table(T::table())
+        .and_where(T::user_id().eq_value(user_id))
+        .build_sqlx(PostgresQueryBuilder);
+    sqlx::query_with(&sql, values).execute(&mut *tx).await?;
+
+    for role_id in roles {
+        let id: UserRoleId = DbKey::new(T::id_prefix());
+        let (icolumns, ivalues) = InsertRow::new()
+            .field(T::id(), &id)
+            .field(T::user_id(), user_id)
+            .field(T::role_id(), role_id)
+            .build();
+
+        let (sql, values) = Query::insert()
+            .into_table(T::table())
+            .columns(icolumns)
+            .values_panic(ivalues)
+            .on_conflict(OnConflict::new().do_nothing().to_owned())
+            .build_sqlx(PostgresQueryBuilder);
+        sqlx::query_with(&sql, values).execute(&mut *tx).await?;
+    }
+    Ok(())
+}
+
+pub async fn create_role(pool: &DbPool, role: &Role) -> sqlx::Result<RoleId> {
+    type T = schema::Role;
+    let id: RoleId = DbKey::new(T::id_prefix());
 
     let (icolumns, ivalues) = InsertRow::new()
         .field(T::id(), &id)
-        .field(T::posted_at(), &posted_at)
-        .field(T::posted_by(), user_id)
-        .field(T::message(), message)
+        .field(T::name(), &role.name)
+        .field(T::description(), &role.description)
         .build();
 
     let (sql, values) = Query::insert()
-        .into_table(schema::Message::table())
+        .into_table(T::table())
         .columns(icolumns)
         .values_panic(ivalues)
         .build_sqlx(PostgresQueryBuilder);
@@ -169,42 +434,321 @@ pub async fn new_message(
     Ok(id)
 }
 
-pub async fn recent_messages(
+pub async fn get_role_with_name(pool: &DbPool, name: &str) -> sqlx::Result<Option<(RoleId, Role)>> {
+    get_role(pool, schema::Role::name().eq_value(&name.to_owned())).await
+}
+
+pub async fn get_role_with_id(
+    pool: &DbPool,
+    role_id: &RoleId,
+) -> sqlx::Result<Option<(RoleId, Role)>> {
+    get_role(pool, schema::Role::id().eq_value(role_id)).await
+}
+
+async fn get_role(
+    pool: &DbPool,
+    where_expr: sea_query::SimpleExpr,
+) -> sqlx::Result<Option<(RoleId, Role)>> {
+    type T = schema::Role;
+    let (sql, values) = Query::select()
+        .from(T::table())
+        .scolumn(T::id())
+        .scolumn(T::name())
+        .scolumn(T::description())
+        .and_where(where_expr)
+        .build_sqlx(PostgresQueryBuilder);
+
+    let v = sqlx::query_with(&sql, values)
+        .map(|r| {
+            (
+                T::id().from_row(&r),
+                Role {
+                    name: T::name().from_row(&r),
+                    description: T::description().from_row(&r),
+                },
+            )
+        })
+        .fetch_optional(pool)
+        .await?;
+    Ok(v)
+}
+
+/**
+ * Returns true if every one of the roles exists
+ */
+pub async fn roles_exist(pool: &DbPool, roles: &[RoleId]) -> sqlx::Result<bool> {
+    type T = schema::Role;
+    let mut role_ids: Vec<&String> = roles.iter().map(|id| &id.0).collect();
+    role_ids.sort();
+    role_ids.dedup();
+
+    let (sql, values) = Query::select()
+        .from(T::table())
+        .expr(Func::count(Expr::asterisk()))
+        .and_where(
+            T::id()
+                .expr()
+                .is_in(roles.iter().map(|id| T::id().value_expr(id))),
+        )
+        .build_sqlx(PostgresQueryBuilder);
+
+    let count: i64 = sqlx::query_with(&sql, values)
+        .map(|r| r.get(0))
+        .fetch_one(pool)
+        .await?;
+    Ok(count as usize == role_ids.len())
+}
+
+/**
+ * Replace the permissions granted by a role
+ */
+pub async fn set_role_permissions(
+    pool: &DbPool,
+    role_id: &RoleId,
+    permissions: &[String],
+) -> sqlx::Result<()> {
+    type T = schema::Permission;
+    let mut tx = pool.begin().await?;
+    lock_role(&mut tx, role_id).await?;
+
+    let (sql, values) = Query::delete()
+        .from_table(T::table())
+        .and_where(T::role_id().eq_value(role_id))
+        .build_sqlx(PostgresQueryBuilder);
+    sqlx::query_with(&sql, values).execute(&mut tx).await?;
+
+    for permission in permissions {
+        let id: PermissionId = DbKey::new(T::id_prefix());
+        let (icolumns, ivalues) = InsertRow::new()
+            .field(T::id(), &id)
+            .field(T::role_id(), role_id)
+            .field(T::name(), permission)
+            .build();
+
+        let (sql, values) = Query::insert()
+            .into_table(T::table())
+            .columns(icolumns)
+            .values_panic(ivalues)
+            .on_conflict(OnConflict::new().do_nothing().to_owned())
+            .build_sqlx(PostgresQueryBuilder);
+        sqlx::query_with(&sql, values).execute(&mut tx).await?;
+    }
+    tx.commit().await?;
+    Ok(())
+}
+
+pub async fn query_roles(
     pool: &DbPool,
     offset: u64,
     limit: u64,
-) -> sqlx::Result<Vec<apis::ui::Message>> {
-    type U = schema::AppUser;
-    type M = schema::Message;
+) -> sqlx::Result<Vec<apis::ui::RoleDetailsWithId>> {
+    type T = schema::Role;
+    type P = schema::Permission;
     let (sql, values) = Query::select()
-        .scolumn(M::id())
-        .scolumn(M::posted_at())
-        .scolumn(M::message())
-        .scolumn(U::fullname())
-        .from(M::table())
-        .inner_join(U::table(), U::id().expr().eq(M::posted_by().expr()))
-        .order_by(schema::Message::posted_at().iden(), Order::Desc)
+        .from(T::table())
+        .scolumn(T::id())
+        .scolumn(T::name())
+        .scolumn(T::description())
+        .order_by(T::name().iden(), Order::Asc)
         .offset(offset)
         .limit(limit)
         .build_sqlx(PostgresQueryBuilder);
+    let mut roles = sqlx::query_with(&sql, values)
+        .map(|r| apis::ui::RoleDetailsWithId {
+            id: T::id().from_row(&r),
+            value: apis::ui::RoleDetails {
+                name: T::name().from_row(&r),
+                description: T::description().from_row(&r),
+                permissions: vec![],
+            },
+        })
+        .fetch_all(pool)
+        .await?;
 
-    let messages = sqlx::query_with(&sql, values.clone())
-        .map(|r| apis::ui::Message {
-            id: M::id().from_row(&r),
-            posted_at: M::posted_at().from_row(&r),
-            message: M::message().from_row(&r),
-            user_fullname: U::fullname().from_row(&r),
+    let (sql, values) = Query::select()
+        .from(P::table())
+        .scolumn(P::role_id())
+        .scolumn(P::name())
+        .and_where(
+            P::role_id()
+                .expr()
+                .is_in(roles.iter().map(|role| P::role_id().value_expr(&role.id))),
+        )
+        .order_by(P::name().iden(), Order::Asc)
+        .build_sqlx(PostgresQueryBuilder);
+    let permissions = sqlx::query_with(&sql, values)
+        .map(|r| (P::role_id().from_row(&r), P::name().from_row(&r)))
+        .fetch_all(pool)
+        .await?;
+    for (role_id, name) in permissions {
+        if let Some(role) = roles.iter_mut().find(|role| role.id.0 == role_id.0) {
+            role.value.permissions.push(name);
+        }
+    }
+    Ok(roles)
+}
+
+pub async fn role_count(pool: &DbPool) -> sqlx::Result<u64> {
+    type T = schema::Role;
+
+    let (sql, values) = Query::select()
+        .from(T::table())
+        .expr(Func::count(Expr::asterisk()))
+        .build_sqlx(PostgresQueryBuilder);
+
+    let count: i64 = sqlx::query_with(&sql, values)
+        .map(|r| r.get(0))
+        .fetch_one(pool)
+        .await?;
+    Ok(count as u64)
+}
+
+/**
+ * Delete a role, along with its permissions and memberships
+ */
+pub async fn delete_role(pool: &DbPool, role_id: &RoleId) -> sqlx::Result<()> {
+    type P = schema::Permission;
+    type U = schema::UserRole;
+    type T = schema::Role;
+    let mut tx = pool.begin().await?;
+    lock_role(&mut tx, role_id).await?;
+
+    let (sql, values) = Query::delete()
+        .from_table(P::table())
+        .and_where(P::role_id().eq_value(role_id))
+        .build_sqlx(PostgresQueryBuilder);
+    sqlx::query_with(&sql, values).execute(&mut tx).await?;
+    let (sql, values) = Query::delete()
+        .from_table(U::table())
+        .and_where(U::role_id().eq_value(role_id))
+        .build_sqlx(PostgresQueryBuilder);
+    sqlx::query_with(&sql, values).execute(&mut tx).await?;
+    let (sql, values) = Query::delete()
+        .from_table(T::table())
+        .and_where(T::id().eq_value(role_id))
+        .build_sqlx(PostgresQueryBuilder);
+    sqlx::query_with(&sql, values).execute(&mut tx).await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+/**
+ * Lock a role until the transaction ends, so that concurrent changes to
+ * its permissions and memberships don't interleave
+ */
+async fn lock_role(tx: &mut DbTransaction<'_>, role_id: &RoleId) -> sqlx::Result<()> {
+    type T = schema::Role;
+    let (sql, values) = Query::select()
+        .from(T::table())
+        .scolumn(T::id())
+        .and_where(T::id().eq_value(role_id))
+        .lock(LockType::Update)
+        .build_sqlx(PostgresQueryBuilder);
+    sqlx::query_with(&sql, values).execute(tx).await?;
+    Ok(())
+}
+
+pub async fn create_organisation(
+    pool: &DbPool,
+    organisation: &Organisation,
+) -> sqlx::Result<OrganisationId> {
+    type T = schema::Organisation;
+    let id: OrganisationId = DbKey::new(T::id_prefix());
+
+    let (icolumns, ivalues) = InsertRow::new()
+        .field(T::id(), &id)
+        .field(T::name(), &organisation.name)
+        .field(T::created_at(), &organisation.created_at)
+        .build();
+
+    let (sql, values) = Query::insert()
+        .into_table(T::table())
+        .columns(icolumns)
+        .values_panic(ivalues)
+        .build_sqlx(PostgresQueryBuilder);
+    sqlx::query_with(&sql, values).execute(pool).await?;
+    Ok(id)
+}
+
+pub async fn get_organisation_with_name(
+    pool: &DbPool,
+    name: &str,
+) -> sqlx::Result<Option<(OrganisationId, Organisation)>> {
+    get_organisation(
+        pool,
+        schema::Organisation::name().eq_value(&name.to_owned()),
+    )
+    .await
+}
+
+pub async fn get_organisation_with_id(
+    pool: &DbPool,
+    organisation_id: &OrganisationId,
+) -> sqlx::Result<Option<(OrganisationId, Organisation)>> {
+    get_organisation(pool, schema::Organisation::id().eq_value(organisation_id)).await
+}
+
+async fn get_organisation(
+    pool: &DbPool,
+    where_expr: sea_query::SimpleExpr,
+) -> sqlx::Result<Option<(OrganisationId, Organisation)>> {
+    type T = schema::Organisation;
+    let (sql, values) = Query::select()
+        .from(T::table())
+        .scolumn(T::id())
+        .scolumn(T::name())
+        .scolumn(T::created_at())
+        .and_where(where_expr)
+        .build_sqlx(PostgresQueryBuilder);
+
+    let v = sqlx::query_with(&sql, values)
+        .map(|r| {
+            (
+                T::id().from_row(&r),
+                Organisation {
+                    name: T::name().from_row(&r),
+                    created_at: T::created_at().from_row(&r),
+                },
+            )
+        })
+        .fetch_optional(pool)
+        .await?;
+    Ok(v)
+}
+
+pub async fn query_organisations(
+    pool: &DbPool,
+    offset: u64,
+    limit: u64,
+) -> sqlx::Result<Vec<apis::ui::OrganisationWithId>> {
+    type T = schema::Organisation;
+    let (sql, values) = Query::select()
+        .from(T::table())
+        .scolumn(T::id())
+        .scolumn(T::name())
+        .scolumn(T::created_at())
+        .order_by(T::name().iden(), Order::Asc)
+        .offset(offset)
+        .limit(limit)
+        .build_sqlx(PostgresQueryBuilder);
+    let organisations = sqlx::query_with(&sql, values)
+        .map(|r| apis::ui::OrganisationWithId {
+            id: T::id().from_row(&r),
+            value: Organisation {
+                name: T::name().from_row(&r),
+                created_at: T::created_at().from_row(&r),
+            },
         })
         .fetch_all(pool)
         .await?;
-    Ok(messages)
+    Ok(organisations)
 }
 
-pub async fn message_count(pool: &DbPool) -> sqlx::Result<u64> {
-    type M = schema::Message;
+pub async fn organisation_count(pool: &DbPool) -> sqlx::Result<u64> {
+    type T = schema::Organisation;
 
     let (sql, values) = Query::select()
-        .from(M::table())
+        .from(T::table())
         .expr(Func::count(Expr::asterisk()))
         .build_sqlx(PostgresQueryBuilder);
 
@@ -215,6 +759,1897 @@ pub async fn message_count(pool: &DbPool) -> sqlx::Result<u64> {
     Ok(count as u64)
 }
 
-fn instant_now() -> Instant {
+/**
+ * Add a user to an organisation, or replace the roles of an existing
+ * member
+ */
+pub async fn set_organisation_member(
+    pool: &DbPool,
+    organisation_id: &OrganisationId,
+    user_id: &AppUserId,
+    roles: &Vec<RoleId>,
+) -> sqlx::Result<()> {
+    type T = schema::OrganisationMember;
+    let id: OrganisationMemberId = DbKey::new(T::id_prefix());
+
+    let (icolumns, ivalues) = InsertRow::new()
+        .field(T::id(), &id)
+        .field(T::organisation_id(), organisation_id)
+        .field(T::user_id(), user_id)
+        .field(T::roles(), roles)
+        .field(T::joined_at(), &instant_now())
+        .build();
+
+    let (sql, values) = Query::insert()
+        .into_table(T::table())
+        .columns(icolumns)
+        .values_panic(ivalues)
+        .on_conflict(
+            OnConflict::columns([T::organisation_id().iden(), T::user_id().iden()])
+                .update_column(T::roles().iden())
+                .to_owned(),
+        )
+        .build_sqlx(PostgresQueryBuilder);
+    sqlx::query_with(&sql, values).execute(pool).await?;
+    Ok(())
+}
+
+pub async fn remove_organisation_member(
+    pool: &DbPool,
+    organisation_id: &OrganisationId,
+    user_id: &AppUserId,
+) -> sqlx::Result<()> {
+    type T = schema::OrganisationMember;
+    let (sql, values) = Query::delete()
+        .from_table(T::table())
+        .and_where(T::organisation_id().eq_value(organisation_id))
+        .and_where(T::user_id().eq_value(user_id))
+        .build_sqlx(PostgresQueryBuilder);
+    sqlx::query_with(&sql, values).execute(pool).await?;
+    Ok(())
+}
+
+pub async fn get_organisation_member(
+    pool: &DbPool,
+    organisation_id: &OrganisationId,
+    user_id: &AppUserId,
+) -> sqlx::Result<Option<OrganisationMember>> {
+    type T = schema::OrganisationMember;
+    let (sql, values) = Query::select()
+        .from(T::table())
+        .scolumn(T::organisation_id())
+        .scolumn(T::user_id())
+        .scolumn(T::roles())
+        .scolumn(T::joined_at())
+        .and_where(T::organisation_id().eq_value(organisation_id))
+        .and_where(T::user_id().eq_value(user_id))
+        .build_sqlx(PostgresQueryBuilder);
+
+    let v = sqlx::query_with(&sql, values)
+        .map(|r| OrganisationMember {
+            organisation_id: T::organisation_id().from_row(&r),
+            user_id: T::user_id().from_row(&r),
+            roles: T::roles().from_row(&r),
+            joined_at: T::joined_at().from_row(&r),
+        })
+        .fetch_optional(pool)
+        .await?;
+    Ok(v)
+}
+
+/**
+ * The organisation that a user acts within when they login, which is the
+ * first that they joined
+ */
+pub async fn default_organisation(
+    pool: &DbPool,
+    user_id: &AppUserId,
+) -> sqlx::Result<Option<OrganisationId>> {
+    type T = schema::OrganisationMember;
+    let (sql, values) = Query::select()
+        .from(T::table())
+        .scolumn(T::organisation_id())
+        .and_where(T::user_id().eq_value(user_id))
+        .order_by(T::joined_at().iden(), Order::Asc)
+        .limit(1)
+        .build_sqlx(PostgresQueryBuilder);
+
+    let v = sqlx::query_with(&sql, values)
+        .map(|r| T::organisation_id().from_row(&r))
+        .fetch_optional(pool)
+        .await?;
+    Ok(v)
+}
+
+/**
+ * The names of those of the given roles that still exist
+ */
+pub async fn role_names(pool: &DbPool, roles: &[RoleId]) -> sqlx::Result<Vec<String>> {
+    type T = schema::Role;
+    let (sql, values) = Query::select()
+        .from(T::table())
+        .scolumn(T::name())
+        .and_where(
+            T::id()
+                .expr()
+                .is_in(roles.iter().map(|id| T::id().value_expr(id))),
+        )
+        .order_by(T::name().iden(), Order::Asc)
+        .build_sqlx(PostgresQueryBuilder);
+
+    sqlx::query_with(&sql, values)
+        .map(|r| T::name().from_row(&r))
+        .fetch_all(pool)
+        .await
+}
+
+pub async fn new_message(
+    pool: &DbPool,
+    user_id: &AppUserId,
+    organisation_id: &Option<OrganisationId>,
+    reply_to: &Option<MessageId>,
+    message: &String,
+) -> sqlx::Result<MessageId> {
+    type T = schema::Message;
+
+    let id: MessageId = DbKey::new(T::id_prefix());
+    let posted_at = instant_now();
+
+    let (icolumns, ivalues) = InsertRow::new()
+        .field(T::id(), &id)
+        .field(T::posted_at(), &posted_at)
+        .field(T::posted_by(), user_id)
+        .field(T::message(), message)
+        .field(T::organisation_id(), organisation_id)
+        .field(T::reply_to(), reply_to)
+        .field(T::edited_at(), &None)
+        .field(T::deleted_at(), &None)
+        .build();
+
+    let (sql, values) = Query::insert()
+        .into_table(schema::Message::table())
+        .columns(icolumns)
+        .values_panic(ivalues)
+        .build_sqlx(PostgresQueryBuilder);
+    sqlx::query_with(&sql, values).execute(pool).await?;
+    Ok(id)
+}
+
+pub async fn recent_messages(
+    pool: &DbPool,
+    user_id: &AppUserId,
+    organisation_id: &Option<OrganisationId>,
+    offset: u64,
+    limit: u64,
+) -> sqlx::Result<Vec<apis::ui::Message>> {
+    type M = schema::Message;
+    let (sql, values) = select_messages(user_id)
+        .and_where(message_filter(organisation_id))
+        .and_where(M::reply_to().expr().is_null())
+        .order_by(M::posted_at().iden(), Order::Desc)
+        .offset(offset)
+        .limit(limit)
+        .build_sqlx(PostgresQueryBuilder);
+
+    let messages = sqlx::query_with(&sql, values)
+        .map(|r| message_from_row(&r))
+        .fetch_all(pool)
+        .await?;
+    Ok(messages)
+}
+
+/**
+ * The number of top level messages visible within the given organisation
+ */
+pub async fn message_count(
+    pool: &DbPool,
+    organisation_id: &Option<OrganisationId>,
+) -> sqlx::Result<u64> {
+    type M = schema::Message;
+
+    let (sql, values) = Query::select()
+        .from(M::table())
+        .expr(Func::count(Expr::asterisk()))
+        .and_where(message_filter(organisation_id))
+        .and_where(M::reply_to().expr().is_null())
+        .build_sqlx(PostgresQueryBuilder);
+
+    let count: i64 = sqlx::query_with(&sql, values)
+        .map(|r| r.get(0))
+        .fetch_one(pool)
+        .await?;
+    Ok(count as u64)
+}
+
+/**
+ * Get a top level message, if it is visible within the given organisation
+ */
+pub async fn get_top_level_message(
+    pool: &DbPool,
+    user_id: &AppUserId,
+    organisation_id: &Option<OrganisationId>,
+    message_id: &MessageId,
+) -> sqlx::Result<Option<apis::ui::Message>> {
+    type M = schema::Message;
+    let (sql, values) = select_messages(user_id)
+        .and_where(M::id().eq_value(message_id))
+        .and_where(message_filter(organisation_id))
+        .and_where(M::reply_to().expr().is_null())
+        .build_sqlx(PostgresQueryBuilder);
+
+    let message = sqlx::query_with(&sql, values)
+        .map(|r| message_from_row(&r))
+        .fetch_optional(pool)
+        .await?;
+    Ok(message)
+}
+
+/**
+ * The replies to a message, in the order they were posted
+ */
+pub async fn message_replies(
+    pool: &DbPool,
+    user_id: &AppUserId,
+    message_id: &MessageId,
+    offset: u64,
+    limit: u64,
+) -> sqlx::Result<Vec<apis::ui::Message>> {
+    type M = schema::Message;
+    let (sql, values) = select_messages(user_id)
+        .and_where(M::reply_to().eq_value(&Some(message_id.clone())))
+        .order_by(M::posted_at().cref(), Order::Asc)
+        .order_by(M::id().cref(), Order::Asc)
+        .offset(offset)
+        .limit(limit)
+        .build_sqlx(PostgresQueryBuilder);
+
+    let messages = sqlx::query_with(&sql, values)
+        .map(|r| message_from_row(&r))
+        .fetch_all(pool)
+        .await?;
+    Ok(messages)
+}
+
+/**
+ * The number of replies to a message, including deleted ones, which are
+ * listed in its thread
+ */
+pub async fn reply_count(pool: &DbPool, message_id: &MessageId) -> sqlx::Result<u64> {
+    type M = schema::Message;
+
+    let (sql, values) = Query::select()
+        .from(M::table())
+        .expr(Func::count(Expr::asterisk()))
+        .and_where(M::reply_to().eq_value(&Some(message_id.clone())))
+        .build_sqlx(PostgresQueryBuilder);
+
+    let count: i64 = sqlx::query_with(&sql, values)
+        .map(|r| r.get(0))
+        .fetch_one(pool)
+        .await?;
+    Ok(count as u64)
+}
+
+/**
+ * Select messages along with their poster's name, their reply counts, and
+ * their reactions as seen by the given user. Deleted replies aren't counted. The replies and reactions are
+ * aggregated by lateral subqueries, which use the indexes on reply_to and
+ * message_id for just the selected messages.
+ */
+fn select_messages(user_id: &AppUserId) -> SelectStatement {
+    type U = schema::AppUser;
+    type M = schema::Message;
+    type R = schema::MessageReaction;
+    let reply = Alias::new("reply");
+    let replies = Query::select()
+        .from_as(M::table(), reply.clone())
+        .expr_as(Func::count(Expr::asterisk()), Alias::new(REPLY_COUNT))
+        .expr_as(
+            Func::max(Expr::col((reply.clone(), M::posted_at().iden()))),
+            Alias::new(LATEST_REPLY_AT),
+        )
+        .and_where(Expr::col((reply.clone(), M::reply_to().iden())).equals(M::id().cref()))
+        .and_where(Expr::col((reply, M::deleted_at().iden())).is_null())
+        .to_owned();
+
+    // The reactions are returned as parallel arrays, one element per emoji
+    let emoji_counts = Query::select()
+        .expr_as(R::emoji().expr(), Alias::new("emoji"))
+        .expr_as(Func::count(Expr::asterisk()), Alias::new("emoji_count"))
+        .expr_as(
+            Func::cust(Alias::new("bool_or")).arg(R::user_id().eq_value(user_id)),
+            Alias::new("mine"),
+        )
+        .expr_as(Func::min(R::reacted_at().expr()), Alias::new("first_at"))
+        .from(R::table())
+        .and_where(R::message_id().expr().equals(M::id().cref()))
+        .group_by_col(R::emoji().cref())
+        .to_owned();
+    let reactions = Query::select()
+        .expr_as(
+            Expr::cust(r#"array_agg("emoji" ORDER BY "first_at", "emoji")"#),
+            Alias::new(REACTION_EMOJIS),
+        )
+        .expr_as(
+            Expr::cust(r#"array_agg("emoji_count" ORDER BY "first_at", "emoji")"#),
+            Alias::new(REACTION_COUNTS),
+        )
+        .expr_as(
+            Expr::cust(r#"array_agg("mine" ORDER BY "first_at", "emoji")"#),
+            Alias::new(REACTED_BY_ME),
+        )
+        .from_subquery(emoji_counts, Alias::new("emoji_counts"))
+        .to_owned();
+
+    Query::select()
+        .scolumn(M::id())
+        .scolumn(M::posted_at())
+        .scolumn(M::message())
+        .scolumn(M::edited_at())
+        .scolumn(M::deleted_at())
+        .scolumn(U::fullname())
+        .expr(Expr::col((Alias::new("replies"), Alias::new(REPLY_COUNT))))
+        .expr(Expr::col((
+            Alias::new("replies"),
+            Alias::new(LATEST_REPLY_AT),
+        )))
+        .expr(Expr::col((
+            Alias::new("reactions"),
+            Alias::new(REACTION_EMOJIS),
+        )))
+        .expr(Expr::col((
+            Alias::new("reactions"),
+            Alias::new(REACTION_COUNTS),
+        )))
+        .expr(Expr::col((
+            Alias::new("reactions"),
+            Alias::new(REACTED_BY_ME),
+        )))
+        .from(M::table())
+        .inner_join(U::table(), U::id().expr().eq(M::posted_by().expr()))
+        .join_lateral(
+            JoinType::InnerJoin,
+            replies,
+            Alias::new("replies"),
+            Expr::value(true),
+        )
+        .join_lateral(
+            JoinType::InnerJoin,
+            reactions,
+            Alias::new("reactions"),
+            Expr::value(true),
+        )
+        .to_owned()
+}
+
+const REPLY_COUNT: &str = "reply_count";
+const LATEST_REPLY_AT: &str = "latest_reply_at";
+const REACTION_EMOJIS: &str = "reaction_emojis";
+const REACTION_COUNTS: &str = "reaction_counts";
+const REACTED_BY_ME: &str = "reacted_by_me";
+
+fn message_from_row(r: &PgRow) -> apis::ui::Message {
+    type U = schema::AppUser;
+    type M = schema::Message;
+    let reply_count: i64 = r.get(REPLY_COUNT);
+    let latest_reply_at = r
+        .get::<Option<_>, _>(LATEST_REPLY_AT)
+        .map(|t| M::posted_at().from_db(t));
+
+    // The arrays are null for messages without reactions
+    let emojis: Option<Vec<String>> = r.get(REACTION_EMOJIS);
+    let counts: Option<Vec<i64>> = r.get(REACTION_COUNTS);
+    let reacted_by_me: Option<Vec<bool>> = r.get(REACTED_BY_ME);
+    let reactions = emojis
+        .unwrap_or_default()
+        .into_iter()
+        .zip(counts.unwrap_or_default())
+        .zip(reacted_by_me.unwrap_or_default())
+        .map(|((emoji, count), reacted_by_me)| apis::ui::ReactionCount {
+            emoji,
+            count: count as u64,
+            reacted_by_me,
+        })
+        .collect();
+    apis::ui::Message {
+        id: M::id().from_row(r),
+        posted_at: M::posted_at().from_row(r),
+        message: M::message().from_row(r),
+        edited_at: M::edited_at().from_row(r),
+        deleted_at: M::deleted_at().from_row(r),
+        user_fullname: U::fullname().from_row(r),
+        reply_count: reply_count as u64,
+        latest_reply_at,
+        reactions,
+    }
+}
+
+/**
+ * Add a user's reaction to a message, unless they have already made it
+ */
+pub async fn add_message_reaction(
+    pool: &DbPool,
+    message_id: &MessageId,
+    user_id: &AppUserId,
+    emoji: &String,
+) -> sqlx::Result<()> {
+    type T = schema::MessageReaction;
+    let id: MessageReactionId = DbKey::new(T::id_prefix());
+
+    let (icolumns, ivalues) = InsertRow::new()
+        .field(T::id(), &id)
+        .field(T::message_id(), message_id)
+        .field(T::user_id(), user_id)
+        .field(T::emoji(), emoji)
+        .field(T::reacted_at(), &instant_now())
+        .build();
+
+    let (sql, values) = Query::insert()
+        .into_table(T::table())
+        .columns(icolumns)
+        .values_panic(ivalues)
+        .on_conflict(
+            OnConflict::columns([
+                T::message_id().iden(),
+                T::user_id().iden(),
+                T::emoji().iden(),
+            ])
+            .do_nothing()
+            .to_owned(),
+        )
+        .build_sqlx(PostgresQueryBuilder);
+    sqlx::query_with(&sql, values).execute(pool).await?;
+    Ok(())
+}
+
+pub async fn remove_message_reaction(
+    pool: &DbPool,
+    message_id: &MessageId,
+    user_id: &AppUserId,
+    emoji: &String,
+) -> sqlx::Result<()> {
+    type T = schema::MessageReaction;
+    let (sql, values) = Query::delete()
+        .from_table(T::table())
+        .and_where(T::message_id().eq_value(message_id))
+        .and_where(T::user_id().eq_value(user_id))
+        .and_where(T::emoji().eq_value(emoji))
+        .build_sqlx(PostgresQueryBuilder);
+    sqlx::query_with(&sql, values).execute(pool).await?;
+    Ok(())
+}
+
+/**
+ * Get a message, if it is visible within the given organisation
+ */
+pub async fn get_message(
+    pool: &DbPool,
+    organisation_id: &Option<OrganisationId>,
+    message_id: &MessageId,
+) -> sqlx::Result<Option<Message>> {
+    type M = schema::Message;
+    let (sql, values) = Query::select()
+        .from(M::table())
+        .scolumn(M::posted_at())
+        .scolumn(M::posted_by())
+        .scolumn(M::message())
+        .scolumn(M::organisation_id())
+        .scolumn(M::reply_to())
+        .scolumn(M::edited_at())
+        .scolumn(M::deleted_at())
+        .and_where(M::id().eq_value(message_id))
+        .and_where(message_filter(organisation_id))
+        .build_sqlx(PostgresQueryBuilder);
+
+    let v = sqlx::query_with(&sql, values)
+        .map(|r| Message {
+            posted_at: M::posted_at().from_row(&r),
+            posted_by: M::posted_by().from_row(&r),
+            message: M::message().from_row(&r),
+            organisation_id: M::organisation_id().from_row(&r),
+            reply_to: M::reply_to().from_row(&r),
+            edited_at: M::edited_at().from_row(&r),
+            deleted_at: M::deleted_at().from_row(&r),
+        })
+        .fetch_optional(pool)
+        .await?;
+    Ok(v)
+}
+
+/**
+ * Replace the text of a message that hasn't been deleted, saving its prior
+ * version as a revision. Returns false if the message was deleted.
+ */
+pub async fn edit_message(
+    pool: &DbPool,
+    message_id: &MessageId,
+    message: &String,
+    user_id: &AppUserId,
+) -> sqlx::Result<bool> {
+    type M = schema::Message;
+    let mut update = Query::update();
+    update
+        .svalue(M::message(), message)
+        .svalue(M::edited_at(), &Some(instant_now()));
+    revise_message(pool, message_id, user_id, update).await
+}
+
+/**
+ * Mark a message as deleted and clear its text, saving its last version as
+ * a revision. Returns false if it was already deleted.
+ */
+pub async fn delete_message(
+    pool: &DbPool,
+    message_id: &MessageId,
+    user_id: &AppUserId,
+) -> sqlx::Result<bool> {
+    type M = schema::Message;
+    let mut update = Query::update();
+    update
+        .svalue(M::message(), &"".to_owned())
+        .svalue(M::deleted_at(), &Some(instant_now()));
+    revise_message(pool, message_id, user_id, update).await
+}
+
+/**
+ * Apply an update to a message that hasn't been deleted, saving the
+ * version it replaces as a revision. The message is locked until both are
+ * written, so that concurrent changes each save the version they actually
+ * replaced. Returns false if the message was deleted.
+ */
+async fn revise_message(
+    pool: &DbPool,
+    message_id: &MessageId,
+    user_id: &AppUserId,
+    mut update: UpdateStatement,
+) -> sqlx::Result<bool> {
+    type M = schema::Message;
+    let mut tx = pool.begin().await?;
+    let (sql, values) = Query::select()
+        .from(M::table())
+        .scolumn(M::message())
+        .scolumn(M::posted_at())
+        .scolumn(M::edited_at())
+        .and_where(M::id().eq_value(message_id))
+        .and_where(M::deleted_at().expr().is_null())
+        .lock(LockType::Update)
+        .build_sqlx(PostgresQueryBuilder);
+    let previous = sqlx::query_with(&sql, values)
+        .map(|r| {
+            let message: String = M::message().from_row(&r);
+            let posted_at: Instant = M::posted_at().from_row(&r);
+            let edited_at: Option<Instant> = M::edited_at().from_row(&r);
+            (message, edited_at.unwrap_or(posted_at))
+        })
+        .fetch_optional(&mut tx)
+        .await?;
+    let (previous, written_at) = match previous {
+        Some(previous) => previous,
+        None => return Ok(false),
+    };
+
+    let (sql, values) = update
+        .table(M::table())
+        .and_where(M::id().eq_value(message_id))
+        .build_sqlx(PostgresQueryBuilder);
+    sqlx::query_with(&sql, values).execute(&mut tx).await?;
+    create_message_revision(&mut tx, message_id, &previous, &written_at, user_id).await?;
+    tx.commit().await?;
+    Ok(true)
+}
+
+async fn create_message_revision(
+    tx: &mut DbTransaction<'_>,
+    message_id: &MessageId,
+    message: &String,
+    written_at: &Instant,
+    user_id: &AppUserId,
+) -> sqlx::Result<MessageRevisionId> {
+    type T = schema::MessageRevision;
+    let id: MessageRevisionId = DbKey::new(T::id_prefix());
+
+    let (icolumns, ivalues) = InsertRow::new()
+        .field(T::id(), &id)
+        .field(T::message_id(), message_id)
+        .field(T::message(), message)
+        .field(T::written_at(), written_at)
+        .field(T::replaced_at(), &instant_now())
+        .field(T::replaced_by(), user_id)
+        .build();
+
+    let (sql, values) = Query::insert()
+        .into_table(T::table())
+        .columns(icolumns)
+        .values_panic(ivalues)
+        .build_sqlx(PostgresQueryBuilder);
+    sqlx::query_with(&sql, values).execute(tx).await?;
+    Ok(id)
+}
+
+/**
+ * The prior versions of a message, most recent first
+ */
+pub async fn message_revisions(
+    pool: &DbPool,
+    message_id: &MessageId,
+) -> sqlx::Result<Vec<MessageRevision>> {
+    type T = schema::MessageRevision;
+    let (sql, values) = Query::select()
+        .from(T::table())
+        .scolumn(T::message_id())
+        .scolumn(T::message())
+        .scolumn(T::written_at())
+        .scolumn(T::replaced_at())
+        .scolumn(T::replaced_by())
+        .and_where(T::message_id().eq_value(message_id))
+        .order_by(T::replaced_at().iden(), Order::Desc)
+        .build_sqlx(PostgresQueryBuilder);
+
+    let revisions = sqlx::query_with(&sql, values)
+        .map(|r| MessageRevision {
+            message_id: T::message_id().from_row(&r),
+            message: T::message().from_row(&r),
+            written_at: T::written_at().from_row(&r),
+            replaced_at: T::replaced_at().from_row(&r),
+            replaced_by: T::replaced_by().from_row(&r),
+        })
+        .fetch_all(pool)
+        .await?;
+    Ok(revisions)
+}
+
+/**
+ * Messages are only visible within the organisation they were posted in.
+ * Those posted outside of any organisation are only visible outside of
+ * any organisation.
+ */
+fn message_filter(organisation_id: &Option<OrganisationId>) -> sea_query::SimpleExpr {
+    type M = schema::Message;
+    match organisation_id {
+        Some(_) => M::organisation_id().eq_value(organisation_id),
+        None => M::organisation_id().expr().is_null(),
+    }
+}
+
+/**
+ * Create a refresh token for a user. If no family is given, then the token
+ * starts a new family, identified by the token's id.
+ */
+pub async fn create_refresh_token(
+    pool: &DbPool,
+    user_id: &AppUserId,
+    family: Option<String>,
+    expiry: Duration,
+) -> sqlx::Result<RefreshTokenId> {
+    type T = schema::RefreshToken;
+    let id: RefreshTokenId = DbKey::new(T::id_prefix());
+    let family = family.unwrap_or_else(|| id.0.clone());
+    let issued_at = instant_now();
+    let expires_at = Instant(issued_at.0 + expiry);
+
+    let (icolumns, ivalues) = InsertRow::new()
+        .field(T::id(), &id)
+        .field(T::user_id(), user_id)
+        .field(T::family(), &family)
+        .field(T::issued_at(), &issued_at)
+        .field(T::expires_at(), &expires_at)
+        .field(T::used_at(), &None)
+        .field(T::revoked_at(), &None)
+        .build();
+
+    let (sql, values) = Query::insert()
+        .into_table(T::table())
+        .columns(icolumns)
+        .values_panic(ivalues)
+        .build_sqlx(PostgresQueryBuilder);
+    sqlx::query_with(&sql, values).execute(pool).await?;
+    Ok(id)
+}
+
+pub async fn get_refresh_token(
+    pool: &DbPool,
+    id: &RefreshTokenId,
+) -> sqlx::Result<Option<RefreshToken>> {
+    type T = schema::RefreshToken;
+    let (sql, values) = Query::select()
+        .from(T::table())
+        .scolumn(T::user_id())
+        .scolumn(T::family())
+        .scolumn(T::issued_at())
+        .scolumn(T::expires_at())
+        .scolumn(T::used_at())
+        .scolumn(T::revoked_at())
+        .and_where(T::id().eq_value(id))
+        .build_sqlx(PostgresQueryBuilder);
+
+    let v = sqlx::query_with(&sql, values)
+        .map(|r| RefreshToken {
+            user_id: T::user_id().from_row(&r),
+            family: T::family().from_row(&r),
+            issued_at: T::issued_at().from_row(&r),
+            expires_at: T::expires_at().from_row(&r),
+            used_at: T::used_at().from_row(&r),
+            revoked_at: T::revoked_at().from_row(&r),
+        })
+        .fetch_optional(pool)
+        .await?;
+    Ok(v)
+}
+
+/**
+ * Mark a refresh token as used. Returns false if the token has already
+ * been used or revoked.
+ */
+pub async fn use_refresh_token(pool: &DbPool, id: &RefreshTokenId) -> sqlx::Result<bool> {
+    type T = schema::RefreshToken;
+    let (sql, values) = Query::update()
+        .table(T::table())
+        .svalue(T::used_at(), &Some(instant_now()))
+        .and_where(T::id().eq_value(id))
+        .and_where(T::used_at().expr().is_null())
+        .and_where(T::revoked_at().expr().is_null())
+        .build_sqlx(PostgresQueryBuilder);
+    let result = sqlx::query_with(&sql, values).execute(pool).await?;
+    Ok(result.rows_affected() == 1)
+}
+
+async fn revoke_refresh_token_family(pool: &DbPool, family: &String) -> sqlx::Result<()> {
+    type T = schema::RefreshToken;
+    let (sql, values) = Query::update()
+        .table(T::table())
+        .svalue(T::revoked_at(), &Some(instant_now()))
+        .and_where(T::family().eq_value(family))
+        .and_where(T::revoked_at().expr().is_null())
+        .build_sqlx(PostgresQueryBuilder);
+    sqlx::query_with(&sql, values).execute(pool).await?;
+    Ok(())
+}
+
+pub async fn create_session(
+    pool: &DbPool,
+    user_id: &AppUserId,
+    user_agent: &Option<String>,
+    client_ip: &Option<String>,
+    organisation_id: &Option<OrganisationId>,
+) -> sqlx::Result<SessionId> {
+    type T = schema::Session;
+    let id: SessionId = DbKey::new(T::id_prefix());
+    let now = instant_now();
+
+    let (icolumns, ivalues) = InsertRow::new()
+        .field(T::id(), &id)
+        .field(T::user_id(), user_id)
+        .field(T::user_agent(), user_agent)
+        .field(T::client_ip(), client_ip)
+        .field(T::created_at(), &now)
+        .field(T::last_refreshed_at(), &now)
+        .field(T::revoked_at(), &None)
+        .field(T::organisation_id(), organisation_id)
+        .build();
+
+    let (sql, values) = Query::insert()
+        .into_table(T::table())
+        .columns(icolumns)
+        .values_panic(ivalues)
+        .build_sqlx(PostgresQueryBuilder);
+    sqlx::query_with(&sql, values).execute(pool).await?;
+    Ok(id)
+}
+
+pub async fn set_session_organisation(
+    pool: &DbPool,
+    id: &SessionId,
+    organisation_id: &Option<OrganisationId>,
+) -> sqlx::Result<()> {
+    type T = schema::Session;
+    let (sql, values) = Query::update()
+        .table(T::table())
+        .svalue(T::organisation_id(), organisation_id)
+        .and_where(T::id().eq_value(id))
+        .build_sqlx(PostgresQueryBuilder);
+    sqlx::query_with(&sql, values).execute(pool).await?;
+    Ok(())
+}
+
+pub async fn get_session(pool: &DbPool, id: &SessionId) -> sqlx::Result<Option<Session>> {
+    type T = schema::Session;
+    let (sql, values) = Query::select()
+        .from(T::table())
+        .scolumn(T::user_id())
+        .scolumn(T::user_agent())
+        .scolumn(T::client_ip())
+        .scolumn(T::created_at())
+        .scolumn(T::last_refreshed_at())
+        .scolumn(T::revoked_at())
+        .scolumn(T::organisation_id())
+        .and_where(T::id().eq_value(id))
+        .build_sqlx(PostgresQueryBuilder);
+
+    let v = sqlx::query_with(&sql, values)
+        .map(|r| Session {
+            user_id: T::user_id().from_row(&r),
+            user_agent: T::user_agent().from_row(&r),
+            client_ip: T::client_ip().from_row(&r),
+            created_at: T::created_at().from_row(&r),
+            last_refreshed_at: T::last_refreshed_at().from_row(&r),
+            revoked_at: T::revoked_at().from_row(&r),
+            organisation_id: T::organisation_id().from_row(&r),
+        })
+        .fetch_optional(pool)
+        .await?;
+    Ok(v)
+}
+
+/**
+ * The sessions of a user that haven't been revoked, and were refreshed
+ * after the given time, most recently refreshed first
+ */
+pub async fn user_sessions(
+    pool: &DbPool,
+    user_id: &AppUserId,
+    refreshed_after: &Instant,
+) -> sqlx::Result<Vec<(SessionId, Session)>> {
+    type T = schema::Session;
+    let (sql, values) = Query::select()
+        .from(T::table())
+        .scolumn(T::id())
+        .scolumn(T::user_id())
+        .scolumn(T::user_agent())
+        .scolumn(T::client_ip())
+        .scolumn(T::created_at())
+        .scolumn(T::last_refreshed_at())
+        .scolumn(T::revoked_at())
+        .scolumn(T::organisation_id())
+        .and_where(T::user_id().eq_value(user_id))
+        .and_where(T::revoked_at().expr().is_null())
+        .and_where(
+            T::last_refreshed_at()
+                .expr()
+                .gt(T::last_refreshed_at().value_expr(refreshed_after)),
+        )
+        .order_by(T::last_refreshed_at().iden(), Order::Desc)
+        .build_sqlx(PostgresQueryBuilder);
+
+    let v = sqlx::query_with(&sql, values)
+        .map(|r| {
+            (
+                T::id().from_row(&r),
+                Session {
+                    user_id: T::user_id().from_row(&r),
+                    user_agent: T::user_agent().from_row(&r),
+                    client_ip: T::client_ip().from_row(&r),
+                    created_at: T::created_at().from_row(&r),
+                    last_refreshed_at: T::last_refreshed_at().from_row(&r),
+                    revoked_at: T::revoked_at().from_row(&r),
+                    organisation_id: T::organisation_id().from_row(&r),
+                },
+            )
+        })
+        .fetch_all(pool)
+        .await?;
+    Ok(v)
+}
+
+/**
+ * Record that a session's refresh token has been renewed, and where from
+ */
+pub async fn record_session_refresh(
+    pool: &DbPool,
+    id: &SessionId,
+    user_agent: &Option<String>,
+    client_ip: &Option<String>,
+) -> sqlx::Result<()> {
+    type T = schema::Session;
+    let (sql, values) = Query::update()
+        .table(T::table())
+        .svalue(T::last_refreshed_at(), &instant_now())
+        .svalue(T::user_agent(), user_agent)
+        .svalue(T::client_ip(), client_ip)
+        .and_where(T::id().eq_value(id))
+        .build_sqlx(PostgresQueryBuilder);
+    sqlx::query_with(&sql, values).execute(pool).await?;
+    Ok(())
+}
+
+/**
+ * Revoke a session, and every refresh token issued for it
+ */
+pub async fn revoke_session(pool: &DbPool, id: &SessionId) -> sqlx::Result<()> {
+    type T = schema::Session;
+    let (sql, values) = Query::update()
+        .table(T::table())
+        .svalue(T::revoked_at(), &Some(instant_now()))
+        .and_where(T::id().eq_value(id))
+        .and_where(T::revoked_at().expr().is_null())
+        .build_sqlx(PostgresQueryBuilder);
+    sqlx::query_with(&sql, values).execute(pool).await?;
+    revoke_refresh_token_family(pool, &id.0).await
+}
+
+/**
+ * Revoke all of a user's sessions, other than the excepted one, and their
+ * refresh tokens
+ */
+pub async fn revoke_user_sessions(
+    pool: &DbPool,
+    user_id: &AppUserId,
+    except: Option<&SessionId>,
+) -> sqlx::Result<()> {
+    {
+        type T = schema::Session;
+        let (sql, values) = Query::update()
+            .table(T::table())
+            .svalue(T::revoked_at(), &Some(instant_now()))
+            .and_where(T::user_id().eq_value(user_id))
+            .and_where(T::revoked_at().expr().is_null())
+            .and_where_option(except.map(|id| T::id().expr().ne(T::id().value_expr(id))))
+            .build_sqlx(PostgresQueryBuilder);
+        sqlx::query_with(&sql, values).execute(pool).await?;
+    }
+    {
+        type T = schema::RefreshToken;
+        let (sql, values) = Query::update()
+            .table(T::table())
+            .svalue(T::revoked_at(), &Some(instant_now()))
+            .and_where(T::user_id().eq_value(user_id))
+            .and_where(T::revoked_at().expr().is_null())
+            .and_where_option(except.map(|id| T::family().expr().ne(T::family().value_expr(&id.0))))
+            .build_sqlx(PostgresQueryBuilder);
+        sqlx::query_with(&sql, values).execute(pool).await?;
+    }
+    Ok(())
+}
+
+pub async fn create_password_reset_token(
+    pool: &DbPool,
+    user_id: &AppUserId,
+    token_hash: &String,
+    expiry: Duration,
+) -> sqlx::Result<PasswordResetTokenId> {
+    type T = schema::PasswordResetToken;
+    let id: PasswordResetTokenId = DbKey::new(T::id_prefix());
+    let created_at = instant_now();
+    let expires_at = Instant(created_at.0 + expiry);
+
+    let (icolumns, ivalues) = InsertRow::new()
+        .field(T::id(), &id)
+        .field(T::user_id(), user_id)
+        .field(T::token_hash(), token_hash)
+        .field(T::created_at(), &created_at)
+        .field(T::expires_at(), &expires_at)
+        .field(T::used_at(), &None)
+        .build();
+
+    let (sql, values) = Query::insert()
+        .into_table(T::table())
+        .columns(icolumns)
+        .values_panic(ivalues)
+        .build_sqlx(PostgresQueryBuilder);
+    sqlx::query_with(&sql, values).execute(pool).await?;
+    Ok(id)
+}
+
+pub async fn get_password_reset_token(
+    pool: &DbPool,
+    token_hash: &String,
+) -> sqlx::Result<Option<(PasswordResetTokenId, PasswordResetToken)>> {
+    type T = schema::PasswordResetToken;
+    let (sql, values) = Query::select()
+        .from(T::table())
+        .scolumn(T::id())
+        .scolumn(T::user_id())
+        .scolumn(T::token_hash())
+        .scolumn(T::created_at())
+        .scolumn(T::expires_at())
+        .scolumn(T::used_at())
+        .and_where(T::token_hash().eq_value(token_hash))
+        .build_sqlx(PostgresQueryBuilder);
+
+    let v = sqlx::query_with(&sql, values)
+        .map(|r| {
+            (
+                T::id().from_row(&r),
+                PasswordResetToken {
+                    user_id: T::user_id().from_row(&r),
+                    token_hash: T::token_hash().from_row(&r),
+                    created_at: T::created_at().from_row(&r),
+                    expires_at: T::expires_at().from_row(&r),
+                    used_at: T::used_at().from_row(&r),
+                },
+            )
+        })
+        .fetch_optional(pool)
+        .await?;
+    Ok(v)
+}
+
+/**
+ * Mark a password reset token as used, along with every other unused token
+ * of its user, so that links from earlier reset emails stop working too.
+ * Returns false if the token has already been used.
+ */
+pub async fn use_password_reset_token(
+    pool: &DbPool,
+    id: &PasswordResetTokenId,
+    user_id: &AppUserId,
+) -> sqlx::Result<bool> {
+    type T = schema::PasswordResetToken;
+    let (sql, values) = Query::update()
+        .table(T::table())
+        .svalue(T::used_at(), &Some(instant_now()))
+        .and_where(T::user_id().eq_value(user_id))
+        .and_where(T::used_at().expr().is_null())
+        .returning_col(T::id().iden())
+        .build_sqlx(PostgresQueryBuilder);
+    let used_ids: Vec<String> = sqlx::query_with(&sql, values)
+        .map(|r| r.get(0))
+        .fetch_all(pool)
+        .await?;
+    Ok(used_ids.contains(&id.0))
+}
+
+pub async fn create_invitation(
+    pool: &DbPool,
+    invitation: &Invitation,
+) -> sqlx::Result<InvitationId> {
+    type T = schema::Invitation;
+    let id: InvitationId = DbKey::new(T::id_prefix());
+
+    let (icolumns, ivalues) = InsertRow::new()
+        .field(T::id(), &id)
+        .field(T::email(), &invitation.email)
+        .field(T::roles(), &invitation.roles)
+        .field(T::token_hash(), &invitation.token_hash)
+        .field(T::created_by(), &invitation.created_by)
+        .field(T::created_at(), &invitation.created_at)
+        .field(T::expires_at(), &invitation.expires_at)
+        .field(T::used_at(), &invitation.used_at)
+        .field(T::user_id(), &invitation.user_id)
+        .field(T::revoked_at(), &invitation.revoked_at)
+        .build();
+
+    let (sql, values) = Query::insert()
+        .into_table(T::table())
+        .columns(icolumns)
+        .values_panic(ivalues)
+        .build_sqlx(PostgresQueryBuilder);
+    sqlx::query_with(&sql, values).execute(pool).await?;
+    Ok(id)
+}
+
+pub async fn get_invitation(
+    pool: &DbPool,
+    token_hash: &String,
+) -> sqlx::Result<Option<(InvitationId, Invitation)>> {
+    type T = schema::Invitation;
+    let (sql, values) = Query::select()
+        .from(T::table())
+        .scolumn(T::id())
+        .scolumn(T::email())
+        .scolumn(T::roles())
+        .scolumn(T::token_hash())
+        .scolumn(T::created_by())
+        .scolumn(T::created_at())
+        .scolumn(T::expires_at())
+        .scolumn(T::used_at())
+        .scolumn(T::user_id())
+        .scolumn(T::revoked_at())
+        .and_where(T::token_hash().eq_value(token_hash))
+        .build_sqlx(PostgresQueryBuilder);
+
+    let v = sqlx::query_with(&sql, values)
+        .map(|r| {
+            (
+                T::id().from_row(&r),
+                Invitation {
+                    email: T::email().from_row(&r),
+                    roles: T::roles().from_row(&r),
+                    token_hash: T::token_hash().from_row(&r),
+                    created_by: T::created_by().from_row(&r),
+                    created_at: T::created_at().from_row(&r),
+                    expires_at: T::expires_at().from_row(&r),
+                    used_at: T::used_at().from_row(&r),
+                    user_id: T::user_id().from_row(&r),
+                    revoked_at: T::revoked_at().from_row(&r),
+                },
+            )
+        })
+        .fetch_optional(pool)
+        .await?;
+    Ok(v)
+}
+
+/**
+ * Mark an invitation as used. Returns false if the invitation has
+ * already been used or revoked.
+ */
+async fn use_invitation(tx: &mut DbTransaction<'_>, id: &InvitationId) -> sqlx::Result<bool> {
+    type T = schema::Invitation;
+    let (sql, values) = Query::update()
+        .table(T::table())
+        .svalue(T::used_at(), &Some(instant_now()))
+        .and_where(T::id().eq_value(id))
+        .and_where(T::used_at().expr().is_null())
+        .and_where(T::revoked_at().expr().is_null())
+        .build_sqlx(PostgresQueryBuilder);
+    let result = sqlx::query_with(&sql, values).execute(tx).await?;
+    Ok(result.rows_affected() == 1)
+}
+
+async fn set_invitation_user(
+    tx: &mut DbTransaction<'_>,
+    id: &InvitationId,
+    user_id: &AppUserId,
+) -> sqlx::Result<()> {
+    type T = schema::Invitation;
+    let (sql, values) = Query::update()
+        .table(T::table())
+        .svalue(T::user_id(), &Some(user_id.clone()))
+        .and_where(T::id().eq_value(id))
+        .build_sqlx(PostgresQueryBuilder);
+    sqlx::query_with(&sql, values).execute(tx).await?;
+    Ok(())
+}
+
+/**
+ * Revoke an invitation, unless it has already been used
+ */
+pub async fn revoke_invitation(pool: &DbPool, id: &InvitationId) -> sqlx::Result<()> {
+    type T = schema::Invitation;
+    let (sql, values) = Query::update()
+        .table(T::table())
+        .svalue(T::revoked_at(), &Some(instant_now()))
+        .and_where(T::id().eq_value(id))
+        .and_where(T::used_at().expr().is_null())
+        .and_where(T::revoked_at().expr().is_null())
+        .build_sqlx(PostgresQueryBuilder);
+    sqlx::query_with(&sql, values).execute(pool).await?;
+    Ok(())
+}
+
+pub async fn query_invitations(
+    pool: &DbPool,
+    req: &apis::ui::QueryInvitationsReq,
+) -> sqlx::Result<Vec<apis::ui::InvitationSummaryWithId>> {
+    type T = schema::Invitation;
+    let (sql, values) = Query::select()
+        .from(T::table())
+        .scolumn(T::id())
+        .scolumn(T::email())
+        .scolumn(T::roles())
+        .scolumn(T::created_by())
+        .scolumn(T::created_at())
+        .scolumn(T::expires_at())
+        .scolumn(T::used_at())
+        .scolumn(T::user_id())
+        .scolumn(T::revoked_at())
+        .cond_where(invitation_filter(req))
+        .order_by(T::created_at().iden(), Order::Desc)
+        .offset(req.page.offset)
+        .limit(req.page.limit)
+        .build_sqlx(PostgresQueryBuilder);
+    let invitations = sqlx::query_with(&sql, values)
+        .map(|r| apis::ui::InvitationSummaryWithId {
+            id: T::id().from_row(&r),
+            value: apis::ui::InvitationSummary {
+                email: T::email().from_row(&r),
+                roles: T::roles().from_row(&r),
+                created_by: T::created_by().from_row(&r),
+                created_at: T::created_at().from_row(&r),
+                expires_at: T::expires_at().from_row(&r),
+                used_at: T::used_at().from_row(&r),
+                user_id: T::user_id().from_row(&r),
+                revoked_at: T::revoked_at().from_row(&r),
+            },
+        })
+        .fetch_all(pool)
+        .await?;
+    Ok(invitations)
+}
+
+pub async fn invitation_count(
+    pool: &DbPool,
+    req: &apis::ui::QueryInvitationsReq,
+) -> sqlx::Result<u64> {
+    type T = schema::Invitation;
+
+    let (sql, values) = Query::select()
+        .from(T::table())
+        .expr(Func::count(Expr::asterisk()))
+        .cond_where(invitation_filter(req))
+        .build_sqlx(PostgresQueryBuilder);
+
+    let count: i64 = sqlx::query_with(&sql, values)
+        .map(|r| r.get(0))
+        .fetch_one(pool)
+        .await?;
+    Ok(count as u64)
+}
+
+fn invitation_filter(req: &apis::ui::QueryInvitationsReq) -> Cond {
+    use apis::ui::InvitationStatus;
+    type T = schema::Invitation;
+    let now = T::expires_at().value_expr(&instant_now());
+    let unused = || {
+        Cond::all()
+            .add(T::used_at().expr().is_null())
+            .add(T::revoked_at().expr().is_null())
+    };
+    match req.status {
+        None => Cond::all(),
+        Some(InvitationStatus::Pending) => unused().add(T::expires_at().expr().gt(now)),
+        Some(InvitationStatus::Used) => Cond::all().add(T::used_at().expr().is_not_null()),
+        Some(InvitationStatus::Revoked) => Cond::all().add(T::revoked_at().expr().is_not_null()),
+        Some(InvitationStatus::Expired) => unused().add(T::expires_at().expr().lte(now)),
+    }
+}
+
+pub async fn create_oidc_login(
+    pool: &DbPool,
+    state_hash: &String,
+    code_verifier: &String,
+    nonce: &String,
+    expiry: Duration,
+) -> sqlx::Result<OidcLoginId> {
+    type T = schema::OidcLogin;
+    let id: OidcLoginId = DbKey::new(T::id_prefix());
+    let created_at = instant_now();
+    let expires_at = Instant(created_at.0 + expiry);
+
+    let (icolumns, ivalues) = InsertRow::new()
+        .field(T::id(), &id)
+        .field(T::state_hash(), state_hash)
+        .field(T::code_verifier(), code_verifier)
+        .field(T::nonce(), nonce)
+        .field(T::created_at(), &created_at)
+        .field(T::expires_at(), &expires_at)
+        .field(T::used_at(), &None)
+        .build();
+
+    let (sql, values) = Query::insert()
+        .into_table(T::table())
+        .columns(icolumns)
+        .values_panic(ivalues)
+        .build_sqlx(PostgresQueryBuilder);
+    sqlx::query_with(&sql, values).execute(pool).await?;
+    Ok(id)
+}
+
+pub async fn get_oidc_login(
+    pool: &DbPool,
+    state_hash: &String,
+) -> sqlx::Result<Option<(OidcLoginId, OidcLogin)>> {
+    type T = schema::OidcLogin;
+    let (sql, values) = Query::select()
+        .from(T::table())
+        .scolumn(T::id())
+        .scolumn(T::state_hash())
+        .scolumn(T::code_verifier())
+        .scolumn(T::nonce())
+        .scolumn(T::created_at())
+        .scolumn(T::expires_at())
+        .scolumn(T::used_at())
+        .and_where(T::state_hash().eq_value(state_hash))
+        .build_sqlx(PostgresQueryBuilder);
+
+    let v = sqlx::query_with(&sql, values)
+        .map(|r| {
+            (
+                T::id().from_row(&r),
+                OidcLogin {
+                    state_hash: T::state_hash().from_row(&r),
+                    code_verifier: T::code_verifier().from_row(&r),
+                    nonce: T::nonce().from_row(&r),
+                    created_at: T::created_at().from_row(&r),
+                    expires_at: T::expires_at().from_row(&r),
+                    used_at: T::used_at().from_row(&r),
+                },
+            )
+        })
+        .fetch_optional(pool)
+        .await?;
+    Ok(v)
+}
+
+/**
+ * Mark an OpenID Connect login as used. Returns false if it has already
+ * been used.
+ */
+pub async fn use_oidc_login(pool: &DbPool, id: &OidcLoginId) -> sqlx::Result<bool> {
+    type T = schema::OidcLogin;
+    let (sql, values) = Query::update()
+        .table(T::table())
+        .svalue(T::used_at(), &Some(instant_now()))
+        .and_where(T::id().eq_value(id))
+        .and_where(T::used_at().expr().is_null())
+        .build_sqlx(PostgresQueryBuilder);
+    let result = sqlx::query_with(&sql, values).execute(pool).await?;
+    Ok(result.rows_affected() == 1)
+}
+
+/**
+ * The user linked to an account at an OpenID Connect provider
+ */
+pub async fn get_oidc_identity_user(
+    pool: &DbPool,
+    issuer: &String,
+    subject: &String,
+) -> sqlx::Result<Option<AppUserId>> {
+    type T = schema::OidcIdentity;
+    let (sql, values) = Query::select()
+        .from(T::table())
+        .scolumn(T::user_id())
+        .and_where(T::issuer().eq_value(issuer))
+        .and_where(T::subject().eq_value(subject))
+        .build_sqlx(PostgresQueryBuilder);
+
+    let v = sqlx::query_with(&sql, values)
+        .map(|r| T::user_id().from_row(&r))
+        .fetch_optional(pool)
+        .await?;
+    Ok(v)
+}
+
+pub async fn create_oidc_identity(
+    pool: &DbPool,
+    user_id: &AppUserId,
+    issuer: &String,
+    subject: &String,
+) -> sqlx::Result<OidcIdentityId> {
+    type T = schema::OidcIdentity;
+    let id: OidcIdentityId = DbKey::new(T::id_prefix());
+
+    let (icolumns, ivalues) = InsertRow::new()
+        .field(T::id(), &id)
+        .field(T::user_id(), user_id)
+        .field(T::issuer(), issuer)
+        .field(T::subject(), subject)
+        .field(T::linked_at(), &instant_now())
+        .build();
+
+    let (sql, values) = Query::insert()
+        .into_table(T::table())
+        .columns(icolumns)
+        .values_panic(ivalues)
+        .build_sqlx(PostgresQueryBuilder);
+    sqlx::query_with(&sql, values).execute(pool).await?;
+    Ok(id)
+}
+
+pub async fn get_user_totp(
+    pool: &DbPool,
+    user_id: &AppUserId,
+) -> sqlx::Result<Option<(UserTotpId, UserTotp)>> {
+    type T = schema::UserTotp;
+    let (sql, values) = Query::select()
+        .from(T::table())
+        .scolumn(T::id())
+        .scolumn(T::user_id())
+        .scolumn(T::secret())
+        .scolumn(T::confirmed_at())
+        .scolumn(T::last_used_step())
+        .and_where(T::user_id().eq_value(user_id))
+        .build_sqlx(PostgresQueryBuilder);
+
+    let v = sqlx::query_with(&sql, values)
+        .map(|r| {
+            (
+                T::id().from_row(&r),
+                UserTotp {
+                    user_id: T::user_id().from_row(&r),
+                    secret: T::secret().from_row(&r),
+                    confirmed_at: T::confirmed_at().from_row(&r),
+                    last_used_step: T::last_used_step().from_row(&r),
+                },
+            )
+        })
+        .fetch_optional(pool)
+        .await?;
+    Ok(v)
+}
+
+/**
+ * Start a TOTP enrolment for a user, replacing any existing enrolment
+ */
+pub async fn create_user_totp(
+    pool: &DbPool,
+    user_id: &AppUserId,
+    secret: &String,
+) -> sqlx::Result<UserTotpId> {
+    type T = schema::UserTotp;
+    let id: UserTotpId = DbKey::new(T::id_prefix());
+    let mut tx = pool.begin().await?;
+
+    let (sql, values) = Query::delete()
+        .from_table(T::table())
+        .and_where(T::user_id().eq_value(user_id))
+        .build_sqlx(PostgresQueryBuilder);
+    sqlx::query_with(&sql, values).execute(&mut tx).await?;
+
+    let (icolumns, ivalues) = InsertRow::new()
+        .field(T::id(), &id)
+        .field(T::user_id(), user_id)
+        .field(T::secret(), secret)
+        .field(T::confirmed_at(), &None)
+        .field(T::last_used_step(), &None)
+        .build();
+
+    let (sql, values) = Query::insert()
+        .into_table(T::table())
+        .columns(icolumns)
+        .values_panic(ivalues)
+        .build_sqlx(PostgresQueryBuilder);
+    sqlx::query_with(&sql, values).execute(&mut tx).await?;
+    tx.commit().await?;
+    Ok(id)
+}
+
+/**
+ * Confirm a TOTP enrolment with the time step of its first code. Returns
+ * false if the enrolment has already been confirmed.
+ */
+pub async fn confirm_user_totp(pool: &DbPool, id: &UserTotpId, step: u64) -> sqlx::Result<bool> {
+    type T = schema::UserTotp;
+    let (sql, values) = Query::update()
+        .table(T::table())
+        .svalue(T::confirmed_at(), &Some(instant_now()))
+        .svalue(T::last_used_step(), &Some(step))
+        .and_where(T::id().eq_value(id))
+        .and_where(T::confirmed_at().expr().is_null())
+        .build_sqlx(PostgresQueryBuilder);
+    let result = sqlx::query_with(&sql, values).execute(pool).await?;
+    Ok(result.rows_affected() == 1)
+}
+
+/**
+ * Record the time step of a TOTP code being used. Returns false if a code
+ * for the same or a later step has already been used.
+ */
+pub async fn use_totp_step(pool: &DbPool, id: &UserTotpId, step: u64) -> sqlx::Result<bool> {
+    type T = schema::UserTotp;
+    let (sql, values) = Query::update()
+        .table(T::table())
+        .svalue(T::last_used_step(), &Some(step))
+        .and_where(T::id().eq_value(id))
+        .cond_where(
+            Cond::any().add(T::last_used_step().expr().is_null()).add(
+                T::last_used_step()
+                    .expr()
+                    .lt(T::last_used_step().value_expr(&Some(step))),
+            ),
+        )
+        .build_sqlx(PostgresQueryBuilder);
+    let result = sqlx::query_with(&sql, values).execute(pool).await?;
+    Ok(result.rows_affected() == 1)
+}
+
+/**
+ * Replace a user's TOTP recovery codes
+ */
+pub async fn replace_totp_recovery_codes(
+    pool: &DbPool,
+    user_id: &AppUserId,
+    code_hashes: &[String],
+) -> sqlx::Result<()> {
+    type T = schema::TotpRecoveryCode;
+    let mut tx = pool.begin().await?;
+
+    let (sql, values) = Query::delete()
+        .from_table(T::table())
+        .and_where(T::user_id().eq_value(user_id))
+        .build_sqlx(PostgresQueryBuilder);
+    sqlx::query_with(&sql, values).execute(&mut tx).await?;
+
+    for code_hash in code_hashes {
+        let id: TotpRecoveryCodeId = DbKey::new(T::id_prefix());
+        let (icolumns, ivalues) = InsertRow::new()
+            .field(T::id(), &id)
+            .field(T::user_id(), user_id)
+            .field(T::code_hash(), code_hash)
+            .field(T::used_at(), &None)
+            .build();
+
+        let (sql, values) = Query::insert()
+            .into_table(T::table())
+            .columns(icolumns)
+            .values_panic(ivalues)
+            .build_sqlx(PostgresQueryBuilder);
+        sqlx::query_with(&sql, values).execute(&mut tx).await?;
+    }
+    tx.commit().await?;
+    Ok(())
+}
+
+/**
+ * Mark one of a user's recovery codes as used. Returns false if the user has
+ * no such unused code.
+ */
+pub async fn use_totp_recovery_code(
+    pool: &DbPool,
+    user_id: &AppUserId,
+    code_hash: &String,
+) -> sqlx::Result<bool> {
+    type T = schema::TotpRecoveryCode;
+    let (sql, values) = Query::update()
+        .table(T::table())
+        .svalue(T::used_at(), &Some(instant_now()))
+        .and_where(T::user_id().eq_value(user_id))
+        .and_where(T::code_hash().eq_value(code_hash))
+        .and_where(T::used_at().expr().is_null())
+        .build_sqlx(PostgresQueryBuilder);
+    let result = sqlx::query_with(&sql, values).execute(pool).await?;
+    Ok(result.rows_affected() == 1)
+}
+
+pub async fn create_api_key(pool: &DbPool, api_key: &ApiKey) -> sqlx::Result<ApiKeyId> {
+    type T = schema::ApiKey;
+    let id: ApiKeyId = DbKey::new(T::id_prefix());
+
+    let (icolumns, ivalues) = InsertRow::new()
+        .field(T::id(), &id)
+        .field(T::user_id(), &api_key.user_id)
+        .field(T::name(), &api_key.name)
+        .field(T::key_hash(), &api_key.key_hash)
+        .field(T::scopes(), &api_key.scopes)
+        .field(T::created_at(), &api_key.created_at)
+        .field(T::expires_at(), &api_key.expires_at)
+        .field(T::last_used_at(), &api_key.last_used_at)
+        .field(T::revoked_at(), &api_key.revoked_at)
+        .build();
+
+    let (sql, values) = Query::insert()
+        .into_table(T::table())
+        .columns(icolumns)
+        .values_panic(ivalues)
+        .build_sqlx(PostgresQueryBuilder);
+    sqlx::query_with(&sql, values).execute(pool).await?;
+    Ok(id)
+}
+
+pub async fn get_api_key(
+    pool: &DbPool,
+    key_hash: &String,
+) -> sqlx::Result<Option<(ApiKeyId, ApiKey)>> {
+    type T = schema::ApiKey;
+    let (sql, values) = Query::select()
+        .scolumn(T::id())
+        .scolumn(T::user_id())
+        .scolumn(T::name())
+        .scolumn(T::key_hash())
+        .scolumn(T::scopes())
+        .scolumn(T::created_at())
+        .scolumn(T::expires_at())
+        .scolumn(T::last_used_at())
+        .scolumn(T::revoked_at())
+        .from(T::table())
+        .and_where(T::key_hash().eq_value(key_hash))
+        .build_sqlx(PostgresQueryBuilder);
+
+    let v = sqlx::query_with(&sql, values)
+        .map(|r| {
+            (
+                T::id().from_row(&r),
+                ApiKey {
+                    user_id: T::user_id().from_row(&r),
+                    name: T::name().from_row(&r),
+                    key_hash: T::key_hash().from_row(&r),
+                    scopes: T::scopes().from_row(&r),
+                    created_at: T::created_at().from_row(&r),
+                    expires_at: T::expires_at().from_row(&r),
+                    last_used_at: T::last_used_at().from_row(&r),
+                    revoked_at: T::revoked_at().from_row(&r),
+                },
+            )
+        })
+        .fetch_optional(pool)
+        .await?;
+    Ok(v)
+}
+
+pub async fn record_api_key_used(pool: &DbPool, id: &ApiKeyId) -> sqlx::Result<()> {
+    type T = schema::ApiKey;
+    let (sql, values) = Query::update()
+        .table(T::table())
+        .svalue(T::last_used_at(), &Some(instant_now()))
+        .and_where(T::id().eq_value(id))
+        .build_sqlx(PostgresQueryBuilder);
+    sqlx::query_with(&sql, values).execute(pool).await?;
+    Ok(())
+}
+
+pub async fn revoke_api_key(pool: &DbPool, id: &ApiKeyId) -> sqlx::Result<()> {
+    type T = schema::ApiKey;
+    let (sql, values) = Query::update()
+        .table(T::table())
+        .svalue(T::revoked_at(), &Some(instant_now()))
+        .and_where(T::id().eq_value(id))
+        .and_where(T::revoked_at().expr().is_null())
+        .build_sqlx(PostgresQueryBuilder);
+    sqlx::query_with(&sql, values).execute(pool).await?;
+    Ok(())
+}
+
+pub async fn query_api_keys(
+    pool: &DbPool,
+    req: &apis::ui::QueryApiKeysReq,
+) -> sqlx::Result<Vec<apis::ui::ApiKeySummaryWithId>> {
+    type T = schema::ApiKey;
+    let (sql, values) = Query::select()
+        .from(T::table())
+        .scolumn(T::id())
+        .scolumn(T::user_id())
+        .scolumn(T::name())
+        .scolumn(T::scopes())
+        .scolumn(T::created_at())
+        .scolumn(T::expires_at())
+        .scolumn(T::last_used_at())
+        .scolumn(T::revoked_at())
+        .cond_where(api_key_filter(req))
+        .order_by(T::created_at().iden(), Order::Desc)
+        .offset(req.page.offset)
+        .limit(req.page.limit)
+        .build_sqlx(PostgresQueryBuilder);
+    let keys = sqlx::query_with(&sql, values)
+        .map(|r| apis::ui::ApiKeySummaryWithId {
+            id: T::id().from_row(&r),
+            value: apis::ui::ApiKeySummary {
+                user_id: T::user_id().from_row(&r),
+                name: T::name().from_row(&r),
+                scopes: T::scopes().from_row(&r),
+                created_at: T::created_at().from_row(&r),
+                expires_at: T::expires_at().from_row(&r),
+                last_used_at: T::last_used_at().from_row(&r),
+                revoked_at: T::revoked_at().from_row(&r),
+            },
+        })
+        .fetch_all(pool)
+        .await?;
+    Ok(keys)
+}
+
+pub async fn api_key_count(pool: &DbPool, req: &apis::ui::QueryApiKeysReq) -> sqlx::Result<u64> {
+    type T = schema::ApiKey;
+
+    let (sql, values) = Query::select()
+        .from(T::table())
+        .expr(Func::count(Expr::asterisk()))
+        .cond_where(api_key_filter(req))
+        .build_sqlx(PostgresQueryBuilder);
+
+    let count: i64 = sqlx::query_with(&sql, values)
+        .map(|r| r.get(0))
+        .fetch_one(pool)
+        .await?;
+    Ok(count as u64)
+}
+
+fn api_key_filter(req: &apis::ui::QueryApiKeysReq) -> Cond {
+    type T = schema::ApiKey;
+    Cond::all().add_option(req.user_id.as_ref().map(|v| T::user_id().eq_value(v)))
+}
+
+pub async fn get_login_failure(
+    pool: &DbPool,
+    email: &String,
+) -> sqlx::Result<Option<LoginFailure>> {
+    type T = schema::LoginFailure;
+    let (sql, values) = Query::select()
+        .from(T::table())
+        .scolumn(T::email())
+        .scolumn(T::failed_count())
+        .scolumn(T::last_failed_at())
+        .scolumn(T::locked_until())
+        .and_where(T::email().eq_value(email))
+        .build_sqlx(PostgresQueryBuilder);
+
+    let v = sqlx::query_with(&sql, values)
+        .map(|r| LoginFailure {
+            email: T::email().from_row(&r),
+            failed_count: T::failed_count().from_row(&r),
+            last_failed_at: T::last_failed_at().from_row(&r),
+            locked_until: T::locked_until().from_row(&r),
+        })
+        .fetch_optional(pool)
+        .await?;
+    Ok(v)
+}
+
+/**
+ * Record a failed login for an email address. Once there have been
+ * `lockout_failures` consecutive failures the address is locked for
+ * `lockout`, and its count restarts. Returns true if this failure
+ * locked the address.
+ */
+pub async fn record_login_failure(
+    pool: &DbPool,
+    email: &String,
+    lockout_failures: u32,
+    lockout: Duration,
+) -> sqlx::Result<bool> {
+    type T = schema::LoginFailure;
+    let id: LoginFailureId = DbKey::new(T::id_prefix());
+    let now = instant_now();
+
+    let (icolumns, ivalues) = InsertRow::new()
+        .field(T::id(), &id)
+        .field(T::email(), email)
+        .field(T::failed_count(), &1)
+        .field(T::last_failed_at(), &now)
+        .field(T::locked_until(), &None)
+        .build();
+    let (sql, values) = Query::insert()
+        .into_table(T::table())
+        .columns(icolumns)
+        .values_panic(ivalues)
+        .on_conflict(
+            OnConflict::column(T::email().iden())
+                .values([
+                    (T::failed_count().iden(), T::failed_count().expr().add(1)),
+                    (
+                        T::last_failed_at().iden(),
+                        T::last_failed_at().value_expr(&now),
+                    ),
+                ])
+                .to_owned(),
+        )
+        .build_sqlx(PostgresQueryBuilder);
+    sqlx::query_with(&sql, values).execute(pool).await?;
+
+    let (sql, values) = Query::update()
+        .table(T::table())
+        .svalue(T::failed_count(), &0)
+        .svalue(T::locked_until(), &Some(Instant(now.0 + lockout)))
+        .and_where(T::email().eq_value(email))
+        .and_where(
+            T::failed_count()
+                .expr()
+                .gte(T::failed_count().value_expr(&lockout_failures)),
+        )
+        .build_sqlx(PostgresQueryBuilder);
+    let result = sqlx::query_with(&sql, values).execute(pool).await?;
+    Ok(result.rows_affected() == 1)
+}
+
+/**
+ * Forget the failed logins that are older than `forget_after` and no longer
+ * locked, so that attempts with ever new addresses don't grow the table
+ * without limit
+ */
+pub async fn delete_stale_login_failures(
+    pool: &DbPool,
+    forget_after: Duration,
+) -> sqlx::Result<()> {
+    type T = schema::LoginFailure;
+    let now = instant_now();
+    let cutoff = Instant(now.0 - forget_after);
+    let (sql, values) = Query::delete()
+        .from_table(T::table())
+        .and_where(
+            T::last_failed_at()
+                .expr()
+                .lt(T::last_failed_at().value_expr(&cutoff)),
+        )
+        .cond_where(
+            Cond::any().add(T::locked_until().expr().is_null()).add(
+                T::locked_until()
+                    .expr()
+                    .lt(T::locked_until().value_expr(&Some(now))),
+            ),
+        )
+        .build_sqlx(PostgresQueryBuilder);
+    sqlx::query_with(&sql, values).execute(pool).await?;
+    Ok(())
+}
+
+/**
+ * Forget the failed logins for an email address, unlocking it
+ */
+pub async fn clear_login_failures(pool: &DbPool, email: &String) -> sqlx::Result<()> {
+    type T = schema::LoginFailure;
+    let (sql, values) = Query::delete()
+        .from_table(T::table())
+        .and_where(T::email().eq_value(email))
+        .build_sqlx(PostgresQueryBuilder);
+    sqlx::query_with(&sql, values).execute(pool).await?;
+    Ok(())
+}
+
+pub async fn insert_audit_log(pool: &DbPool, audit_log: &AuditLog) -> sqlx::Result<AuditLogId> {
+    type T = schema::AuditLog;
+    let id: AuditLogId = DbKey::new(T::id_prefix());
+
+    let (icolumns, ivalues) = InsertRow::new()
+        .field(T::id(), &id)
+        .field(T::logged_at(), &audit_log.logged_at)
+        .field(T::actor(), &audit_log.actor)
+        .field(T::impersonated(), &audit_log.impersonated)
+        .field(T::endpoint(), &audit_log.endpoint)
+        .field(T::input(), &audit_log.input)
+        .field(T::outcome(), &audit_log.outcome)
+        .field(T::client_ip(), &audit_log.client_ip)
+        .build();
+
+    let (sql, values) = Query::insert()
+        .into_table(T::table())
+        .columns(icolumns)
+        .values_panic(ivalues)
+        .build_sqlx(PostgresQueryBuilder);
+    sqlx::query_with(&sql, values).execute(pool).await?;
+    Ok(id)
+}
+
+pub async fn query_audit_log(
+    pool: &DbPool,
+    req: &apis::ui::QueryAuditLogReq,
+) -> sqlx::Result<Vec<apis::ui::AuditLogEntry>> {
+    type T = schema::AuditLog;
+    let (sql, values) = Query::select()
+        .from(T::table())
+        .scolumn(T::id())
+        .scolumn(T::logged_at())
+        .scolumn(T::actor())
+        .scolumn(T::impersonated())
+        .scolumn(T::endpoint())
+        .scolumn(T::input())
+        .scolumn(T::outcome())
+        .scolumn(T::client_ip())
+        .cond_where(audit_log_filter(req))
+        .order_by(T::logged_at().iden(), Order::Desc)
+        .offset(req.page.offset)
+        .limit(req.page.limit)
+        .build_sqlx(PostgresQueryBuilder);
+    let entries = sqlx::query_with(&sql, values)
+        .map(|r| apis::ui::AuditLogEntry {
+            id: T::id().from_row(&r),
+            value: AuditLog {
+                logged_at: T::logged_at().from_row(&r),
+                actor: T::actor().from_row(&r),
+                impersonated: T::impersonated().from_row(&r),
+                endpoint: T::endpoint().from_row(&r),
+                input: T::input().from_row(&r),
+                outcome: T::outcome().from_row(&r),
+                client_ip: T::client_ip().from_row(&r),
+            },
+        })
+        .fetch_all(pool)
+        .await?;
+    Ok(entries)
+}
+
+pub async fn audit_log_count(pool: &DbPool, req: &apis::ui::QueryAuditLogReq) -> sqlx::Result<u64> {
+    type T = schema::AuditLog;
+
+    let (sql, values) = Query::select()
+        .from(T::table())
+        .expr(Func::count(Expr::asterisk()))
+        .cond_where(audit_log_filter(req))
+        .build_sqlx(PostgresQueryBuilder);
+
+    let count: i64 = sqlx::query_with(&sql, values)
+        .map(|r| r.get(0))
+        .fetch_one(pool)
+        .await?;
+    Ok(count as u64)
+}
+
+fn audit_log_filter(req: &apis::ui::QueryAuditLogReq) -> Cond {
+    type T = schema::AuditLog;
+    Cond::all()
+        .add_option(req.actor.as_ref().map(|v| T::actor().eq_value(v)))
+        .add_option(req.endpoint.as_ref().map(|v| T::endpoint().eq_value(v)))
+        .add_option(req.outcome.as_ref().map(|v| T::outcome().eq_value(v)))
+        .add_option(
+            req.logged_after
+                .as_ref()
+                .map(|v| T::logged_at().expr().gte(T::logged_at().value_expr(v))),
+        )
+        .add_option(
+            req.logged_before
+                .as_ref()
+                .map(|v| T::logged_at().expr().lt(T::logged_at().value_expr(v))),
+        )
+}
+
+pub fn instant_now() -> Instant {
     Instant(SystemTime::now())
 }
@@ -0,0 +1,84 @@
+use sqlx::PgPool;
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+use super::db;
+
+/**
+ * A cache of the ids of deactivated users, so that access tokens can be
+ * checked against it without a db query per request. It is reloaded when
+ * older than its ttl, and immediately after a user is deactivated or
+ * reactivated by this server.
+ */
+pub struct DeactivatedUsers {
+    ttl: Duration,
+    cached: RwLock<Option<Cached>>,
+
+    // Held while reloading, so that concurrent misses wait for a single
+    // reload rather than each querying the db
+    reloading: Mutex<()>,
+
+    // Incremented by each invalidation, so that a load that started
+    // before it isn't cached
+    generation: AtomicU64,
+}
+
+struct Cached {
+    user_ids: HashSet<String>,
+    loaded_at: Instant,
+}
+
+impl DeactivatedUsers {
+    pub fn new(ttl: Duration) -> Self {
+        DeactivatedUsers {
+            ttl,
+            cached: RwLock::new(None),
+            reloading: Mutex::new(()),
+            generation: AtomicU64::new(0),
+        }
+    }
+
+    pub async fn contains(&self, pool: &PgPool, user_id: &str) -> sqlx::Result<bool> {
+        if let Some(contains) = self.cached_contains(user_id) {
+            return Ok(contains);
+        }
+        let _reloading = self.reloading.lock().await;
+        // Another caller may have reloaded while this one waited
+        if let Some(contains) = self.cached_contains(user_id) {
+            return Ok(contains);
+        }
+        let generation = self.generation.load(Ordering::SeqCst);
+        let user_ids = db::deactivated_user_ids(pool).await?;
+        let contains = user_ids.contains(user_id);
+        let mut cached = self.cached.write().unwrap();
+        if self.generation.load(Ordering::SeqCst) == generation {
+            *cached = Some(Cached {
+                user_ids,
+                loaded_at: Instant::now(),
+            });
+        }
+        Ok(contains)
+    }
+
+    /**
+     * Discard the cached ids, so that the next check reloads them
+     */
+    pub fn invalidate(&self) {
+        let mut cached = self.cached.write().unwrap();
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        *cached = None;
+    }
+
+    fn cached_contains(&self, user_id: &str) -> Option<bool> {
+        let cached = self.cached.read().unwrap();
+        match &*cached {
+            Some(cached) if cached.loaded_at.elapsed() < self.ttl => {
+                Some(cached.user_ids.contains(user_id))
+            }
+            _ => None,
+        }
+    }
+}
@@ -1,23 +1,48 @@
 use poem::handler;
-use poem::web::cookie::{Cookie, CookieJar};
+use poem::web::cookie::CookieJar;
 use poem::web::Json;
+use std::time::{Duration, SystemTime};
 
 use adl::custom::common::db::DbKey;
+use adl::custom::common::time::Instant;
 use adl::gen::common::http::Unit;
 use adl::gen::protoapp::apis::ui::{
-    ApiRequests, LoginReq, LoginResp, LoginTokens, Message, Paginated, QueryUsersReq,
-    RecentMessagesReq, RefreshReq, RefreshResp, User, UserDetails, UserWithId, WithId,
+    AddReactionResp, ApiKeySummaryWithId, ApiRequests, AuditLogEntry, BeginTotpEnrolmentResp,
+    ChangePasswordReq, ChangePasswordResp, ConfirmTotpEnrolmentReq, ConfirmTotpEnrolmentResp,
+    CreateApiKeyReq, CreateApiKeyResp, CreateInvitationReq, CreateInvitationResp,
+    CreateOrganisationReq, CreateOrganisationResp, CreateRoleReq, CreateRoleResp, CreateUserResp,
+    DeactivateUserResp, DeleteMessageResp, DeleteRoleResp, EditMessageReq, EditMessageResp,
+    ImpersonateUserResp, InvitationSummaryWithId, Jwks, JwtFailureCounts, LoginReq, LoginResp,
+    LoginTokens, LoginTotpReq, LoginTotpResp, LogoutReq, Message, MessageReactionReq,
+    MessageRevisionsResp, MessageThread, MessageThreadReq, MessageThreadResp, NewApiKey,
+    OidcLoginReq, OidcLoginResp, OidcLoginStartResp, OrganisationWithId, Paginated,
+    QueryApiKeysReq, QueryAuditLogReq, QueryInvitationsReq, QueryOrganisationsReq, QueryRolesReq,
+    QueryUsersReq, ReactivateUserResp, RecentMessagesReq, RefreshReq, RefreshResp,
+    RemoveOrganisationMemberReq, ReplyToMessageReq, ReplyToMessageResp, RequestPasswordResetReq,
+    ResendVerificationEmailReq, ResetPasswordReq, ResetPasswordResp, RoleDetailsWithId,
+    SessionSummary, SessionSummaryWithId, SetOrganisationMemberReq, SetOrganisationMemberResp,
+    SetRolePermissionsReq, SetRolePermissionsResp, SetUserRolesReq, SetUserRolesResp, SignupReq,
+    SignupResp, SwitchOrganisationReq, SwitchOrganisationResp, TotpEnrolment, UpdateProfileReq,
+    UpdateProfileResp, UpdateUserResp, User, UserDetails, UserWithId, VerifyEmailReq,
+    VerifyEmailResp, WithId,
+};
+use adl::gen::protoapp::config::server::{OidcConfig, ServerConfig};
+use adl::gen::protoapp::db::{
+    ApiKey, ApiKeyId, AppUser, AppUserId, Invitation, InvitationId, Organisation, OrganisationId,
+    RefreshTokenId, Role, RoleId, SessionId,
 };
-use adl::gen::protoapp::config::server::ServerConfig;
-use adl::gen::protoapp::db::{AppUser, AppUserId};
 use adl::gen::protoapp::{apis::ui::NewMessageReq, db::MessageId};
 
 use crate::server::jwt::AccessClaims;
-use crate::server::passwords::{hash_password, verify_password};
+use crate::server::mail::Email;
+use crate::server::passwords::{
+    hash_password, hash_token, needs_rehash, new_token, verify_password,
+};
 use crate::server::poem_adl_interop::{
     forbidden, get_adl_request_context, AdlReqContext, HandlerResult,
 };
-use crate::server::{db, jwt, AppState};
+use crate::server::timeouts::{request_timeout, with_timeout};
+use crate::server::{api_keys, auth_cookie, csrf, db, jwt, oidc, totp, AppState};
 
 type ReqContext = AdlReqContext<AppState>;
 
@@ -25,55 +50,456 @@ pub async fn healthy(_ctx: ReqContext, _i: ()) -> HandlerResult<()> {
     Ok(())
 }
 
+pub async fn jwks(ctx: ReqContext, _i: ()) -> HandlerResult<Jwks> {
+    Ok(ctx.state.access_keys.jwks().clone())
+}
+
 pub async fn login(ctx: ReqContext, i: LoginReq) -> HandlerResult<LoginResp> {
+    // Refuse attempts for addresses with too many recent failures, before
+    // even looking up the user, so as not to reveal whether they exist
+    let failure_key = login_failure_key(&i.email);
+    if let Some(secs) = login_retry_after(&ctx.state, &failure_key).await? {
+        return Ok(LoginResp::TooManyAttempts(secs));
+    }
+
     // Lookup the user details
     let user = db::get_user_with_email(&ctx.state.db_pool, &i.email).await?;
     match user {
-        None => Ok(LoginResp::InvalidCredentials),
+        None => {
+            // Take as long to reject an unknown address as a wrong password
+            let _ = verify_password(&i.password, &ctx.state.dummy_password_hash);
+            record_login_failure(&ctx.state, &failure_key).await?;
+            Ok(LoginResp::InvalidCredentials)
+        }
         Some((user_id, user)) => {
-            if verify_password(&i.password, &user.hashed_password) {
+            if password_matches(&ctx.state, &user_id, &i.password, &user.hashed_password) {
+                rehash_password_if_needed(&ctx.state, &user_id, &i.password, &user).await?;
+                if user.deactivated_at.is_some() {
+                    return Ok(LoginResp::Deactivated);
+                }
+                if !email_verified(&ctx.state, &user) {
+                    return Ok(LoginResp::EmailNotVerified);
+                }
+                // Users enrolled in TOTP need to provide a code before they
+                // get any tokens
+                if let Some((_, totp)) = db::get_user_totp(&ctx.state.db_pool, &user_id).await? {
+                    if totp.confirmed_at.is_some() {
+                        let challenge = jwt::create_totp_challenge(&ctx.state.config, user_id.0);
+                        return Ok(LoginResp::TotpRequired(challenge));
+                    }
+                }
                 // If found and we have a valid password return an access token and refresh token
-                let access_jwt = access_jwt_from_user(&ctx.state.config, &user_id, &user);
-                let refresh_jwt = jwt::create_refresh(&ctx.state.config, user_id.clone().0);
-                Ok(LoginResp::Tokens(LoginTokens {
-                    access_jwt,
-                    refresh_jwt,
-                }))
+                db::clear_login_failures(&ctx.state.db_pool, &failure_key).await?;
+                let tokens = login_tokens(&ctx, &user_id).await?;
+                Ok(LoginResp::Tokens(tokens))
             } else {
+                record_login_failure(&ctx.state, &failure_key).await?;
                 Ok(LoginResp::InvalidCredentials)
             }
         }
     }
 }
 
+pub async fn login_totp(ctx: ReqContext, i: LoginTotpReq) -> HandlerResult<LoginTotpResp> {
+    let pool = &ctx.state.db_pool;
+    let claims = match jwt::decode_totp_challenge(&ctx.state.config, &i.challenge_token) {
+        Ok(claims) => claims,
+        Err(failure) => {
+            log::info!("rejected totp challenge token: {}", failure);
+            return Ok(LoginTotpResp::InvalidChallenge);
+        }
+    };
+    let user_id: AppUserId = DbKey::from_string(claims.sub);
+    let user = db::get_user_with_id(pool, &user_id).await?;
+    let totp = db::get_user_totp(pool, &user_id).await?;
+    let (user, totp_id, totp) = match (user, totp) {
+        (Some((_, user)), Some((totp_id, totp)))
+            if totp.confirmed_at.is_some() && user.deactivated_at.is_none() =>
+        {
+            (user, totp_id, totp)
+        }
+        _ => return Ok(LoginTotpResp::InvalidChallenge),
+    };
+
+    // Failed codes count towards the same limit as failed passwords
+    let failure_key = login_failure_key(&user.email);
+    if let Some(secs) = login_retry_after(&ctx.state, &failure_key).await? {
+        return Ok(LoginTotpResp::TooManyAttempts(secs));
+    }
+
+    let code_accepted = match totp::verify(&totp.secret, &i.code, totp.last_used_step) {
+        Some(step) => db::use_totp_step(pool, &totp_id, step).await?,
+        None => {
+            let code_hash = hash_token(&totp::normalise_recovery_code(&i.code));
+            db::use_totp_recovery_code(pool, &user_id, &code_hash).await?
+        }
+    };
+    if !code_accepted {
+        record_login_failure(&ctx.state, &failure_key).await?;
+        return Ok(LoginTotpResp::InvalidCode);
+    }
+    db::clear_login_failures(pool, &failure_key).await?;
+    let tokens = login_tokens(&ctx, &user_id).await?;
+    Ok(LoginTotpResp::Tokens(tokens))
+}
+
+/**
+ * Start an OpenID Connect login with the given state, which the caller
+ * must also bind to the browser, so that the login can only be completed
+ * by the browser that started it
+ */
+pub async fn oidc_login_start(ctx: ReqContext, state: &str) -> HandlerResult<OidcLoginStartResp> {
+    let cfg = match &ctx.state.config.oidc {
+        Some(cfg) => cfg,
+        None => return Ok(OidcLoginStartResp::NotConfigured),
+    };
+    let provider = oidc::Provider::discover(cfg, &ctx.state.oidc_providers).await?;
+    let nonce = new_token();
+    let code_verifier = new_token();
+    let expiry = Duration::from_secs(cfg.login_expiry_secs as u64);
+    db::create_oidc_login(
+        &ctx.state.db_pool,
+        &hash_token(state),
+        &code_verifier,
+        &nonce,
+        expiry,
+    )
+    .await?;
+    let url = provider.authorization_url(state, &nonce, &code_verifier);
+    Ok(OidcLoginStartResp::AuthorizationUrl(url))
+}
+
+/**
+ * Complete an OpenID Connect login, given the state bound to the browser
+ * by `oidc_login_start`. Without that binding an attacker could have a
+ * victim's browser complete a login to the attacker's account.
+ */
+pub async fn oidc_login(
+    ctx: ReqContext,
+    i: OidcLoginReq,
+    browser_state: Option<String>,
+) -> HandlerResult<OidcLoginResp> {
+    let pool = &ctx.state.db_pool;
+    let cfg = match &ctx.state.config.oidc {
+        Some(cfg) => cfg,
+        None => return Ok(OidcLoginResp::NotConfigured),
+    };
+    if browser_state.as_deref() != Some(i.state.as_str()) {
+        log::warn!("oidc login state doesn't match the browser's");
+        return Ok(OidcLoginResp::InvalidLogin);
+    }
+    let (login_id, login) = match db::get_oidc_login(pool, &hash_token(&i.state)).await? {
+        Some((login_id, login)) if login.expires_at.0 > SystemTime::now() => (login_id, login),
+        _ => return Ok(OidcLoginResp::InvalidLogin),
+    };
+    if !db::use_oidc_login(pool, &login_id).await? {
+        return Ok(OidcLoginResp::InvalidLogin);
+    }
+
+    let provider = oidc::Provider::discover(cfg, &ctx.state.oidc_providers).await?;
+    let leeway_secs = ctx.state.config.jwt_leeway_secs as u64;
+    let claims = match provider
+        .exchange_code(&i.code, &login.code_verifier, &login.nonce, leeway_secs)
+        .await?
+    {
+        Some(claims) => claims,
+        None => return Ok(OidcLoginResp::InvalidLogin),
+    };
+    let (user_id, user) = match oidc_user(&ctx.state, cfg, &claims).await? {
+        Some(user) => user,
+        None => {
+            log::info!("no user for oidc subject {} of {}", claims.sub, claims.iss);
+            return Ok(OidcLoginResp::UnknownUser);
+        }
+    };
+    if user.deactivated_at.is_some() {
+        return Ok(OidcLoginResp::Deactivated);
+    }
+
+    // The provider's authentication replaces the password, but not our
+    // own second factor
+    if let Some((_, totp)) = db::get_user_totp(pool, &user_id).await? {
+        if totp.confirmed_at.is_some() {
+            let challenge = jwt::create_totp_challenge(&ctx.state.config, user_id.0);
+            return Ok(OidcLoginResp::TotpRequired(challenge));
+        }
+    }
+    let tokens = login_tokens(&ctx, &user_id).await?;
+    Ok(OidcLoginResp::Tokens(tokens))
+}
+
 pub async fn refresh(ctx: ReqContext, i: RefreshReq) -> HandlerResult<RefreshResp> {
-    match i.refresh_token {
-        None => Ok(RefreshResp::InvalidRefreshToken),
-        Some(refresh_jwt) => {
-            let claims =
-                match jwt::decode_refresh(&ctx.state.config.jwt_refresh_secret, &refresh_jwt) {
-                    Ok(claims) => claims,
-                    Err(_) => return Ok(RefreshResp::InvalidRefreshToken),
-                };
-            let user_id: AppUserId = DbKey::from_string(claims.sub.clone());
-            let user = db::get_user_with_id(&ctx.state.db_pool, &user_id).await?;
-            let user = match user {
-                Some((_, user)) => user,
-                None => return Ok(RefreshResp::InvalidRefreshToken),
-            };
-            let access_jwt = access_jwt_from_user(&ctx.state.config, &user_id, &user);
-            Ok(RefreshResp::AccessToken(access_jwt))
+    let pool = &ctx.state.db_pool;
+    let token_id = match refresh_token_id(&ctx.state, &i.refresh_token) {
+        Some(token_id) => token_id,
+        None => return Ok(RefreshResp::InvalidRefreshToken),
+    };
+    let token = match db::get_refresh_token(pool, &token_id).await? {
+        Some(token) => token,
+        None => return Ok(RefreshResp::InvalidRefreshToken),
+    };
+    if !db::use_refresh_token(pool, &token_id).await? {
+        if token.revoked_at.is_none() {
+            // The token has been used before, so may have been stolen. Revoke
+            // every token from the same login, forcing the user to login again.
+            log::warn!(
+                "refresh token {} reused, revoking session {}",
+                token_id.0,
+                token.family
+            );
+            db::revoke_session(pool, &DbKey::from_string(token.family)).await?;
+        }
+        return Ok(RefreshResp::InvalidRefreshToken);
+    }
+    match db::get_user_with_id(pool, &token.user_id).await? {
+        Some((_, user)) if email_verified(&ctx.state, &user) && user.deactivated_at.is_none() => {}
+        _ => return Ok(RefreshResp::InvalidRefreshToken),
+    };
+    let session_id: SessionId = DbKey::from_string(token.family);
+    db::record_session_refresh(pool, &session_id, &ctx.user_agent, &ctx.client_ip).await?;
+    let access_jwt = access_jwt_from_user(&ctx.state, &token.user_id, &session_id).await?;
+    let refresh_jwt = create_refresh_jwt(&ctx.state, &token.user_id, Some(session_id.0)).await?;
+    Ok(RefreshResp::Tokens(LoginTokens {
+        access_jwt,
+        refresh_jwt,
+    }))
+}
+
+pub async fn logout(ctx: ReqContext, i: LogoutReq) -> HandlerResult<Unit> {
+    let pool = &ctx.state.db_pool;
+    if let Some(token_id) = refresh_token_id(&ctx.state, &i.refresh_token) {
+        if let Some(token) = db::get_refresh_token(pool, &token_id).await? {
+            db::revoke_session(pool, &DbKey::from_string(token.family)).await?;
+        }
+    }
+    Ok(Unit {})
+}
+
+pub async fn request_password_reset(
+    ctx: ReqContext,
+    i: RequestPasswordResetReq,
+) -> HandlerResult<Unit> {
+    let state = &ctx.state;
+    let (user_id, user) = match db::get_user_with_email(&state.db_pool, &i.email).await? {
+        Some(user) => user,
+        None => {
+            log::info!("password reset requested for unknown email {}", i.email);
+            return Ok(Unit {});
+        }
+    };
+    let token = new_token();
+    let expiry_secs = state.config.password_reset_expiry_secs;
+    db::create_password_reset_token(
+        &state.db_pool,
+        &user_id,
+        &hash_token(&token),
+        Duration::from_secs(expiry_secs as u64),
+    )
+    .await?;
+    let email = Email {
+        to: user.email,
+        subject: "Reset your password".to_owned(),
+        body: format!(
+            "Hi {},\n\n\
+             To choose a new password, follow this link within {} minutes:\n\n\
+             {}/reset-password?token={}\n\n\
+             If you didn't ask to reset your password, you can ignore this email.\n",
+            user.fullname,
+            expiry_secs / 60,
+            state.config.app_url,
+            token
+        ),
+    };
+    // Report success regardless, so as not to reveal that the account exists
+    if let Err(e) = state.mailer.send(email).await {
+        log::error!(
+            "failed to send password reset email to {}: {}",
+            user_id.0,
+            e
+        );
+    }
+    Ok(Unit {})
+}
+
+pub async fn reset_password(
+    ctx: ReqContext,
+    i: ResetPasswordReq,
+) -> HandlerResult<ResetPasswordResp> {
+    let pool = &ctx.state.db_pool;
+    let (token_id, token) = match db::get_password_reset_token(pool, &hash_token(&i.token)).await? {
+        Some(token) => token,
+        None => return Ok(ResetPasswordResp::InvalidToken),
+    };
+    if token.expires_at.0 < db::instant_now().0 {
+        return Ok(ResetPasswordResp::InvalidToken);
+    }
+    let (_, user) = match db::get_user_with_id(pool, &token.user_id).await? {
+        Some(user) => user,
+        None => return Ok(ResetPasswordResp::InvalidToken),
+    };
+
+    // Check the password before using the token, so the user can try again
+    let violations = ctx.state.password_policy.check(&i.password, &user.email);
+    if !violations.is_empty() {
+        return Ok(ResetPasswordResp::WeakPassword(violations));
+    }
+    if !db::use_password_reset_token(pool, &token_id, &token.user_id).await? {
+        return Ok(ResetPasswordResp::InvalidToken);
+    }
+    let hashed_password =
+        hash_password(&ctx.state.config.argon2, &i.password).expect("password can be hashed");
+    db::update_user_password(pool, &token.user_id, &hashed_password).await?;
+    db::revoke_user_sessions(pool, &token.user_id, None).await?;
+    Ok(ResetPasswordResp::Success)
+}
+
+pub async fn signup(ctx: ReqContext, i: SignupReq) -> HandlerResult<SignupResp> {
+    let pool = &ctx.state.db_pool;
+    let (invitation_id, invitation) = match db::get_invitation(pool, &hash_token(&i.token)).await? {
+        Some(invitation) => invitation,
+        None => return Ok(SignupResp::InvalidToken),
+    };
+    if invitation.used_at.is_some()
+        || invitation.revoked_at.is_some()
+        || invitation.expires_at.0 < db::instant_now().0
+    {
+        return Ok(SignupResp::InvalidToken);
+    }
+    if db::get_user_with_email(pool, &invitation.email)
+        .await?
+        .is_some()
+    {
+        return Ok(SignupResp::EmailInUse);
+    }
+
+    // Check the password before using the invitation, so the user can try again
+    let violations = ctx
+        .state
+        .password_policy
+        .check(&i.password, &invitation.email);
+    if !violations.is_empty() {
+        return Ok(SignupResp::WeakPassword(violations));
+    }
+    let hashed_password =
+        hash_password(&ctx.state.config.argon2, &i.password).expect("password can be hashed");
+    let user = AppUser {
+        fullname: i.fullname,
+        email: invitation.email,
+        hashed_password: Some(hashed_password),
+        // The invitation was emailed to the address
+        verified_at: Some(db::instant_now()),
+        verification_sent_at: None,
+        deactivated_at: None,
+    };
+
+    // Roles deleted since the invitation was created are dropped
+    let mut roles = vec![];
+    for role_id in invitation.roles {
+        if db::get_role_with_id(pool, &role_id).await?.is_some() {
+            roles.push(role_id);
         }
     }
+    let user_id = match db::create_invited_user(pool, &invitation_id, &user, &roles).await? {
+        Some(user_id) => user_id,
+        None => return Ok(SignupResp::InvalidToken),
+    };
+    log::info!(
+        "user {} signed up with invitation {}",
+        user_id.0,
+        invitation_id.0
+    );
+    Ok(SignupResp::Tokens(login_tokens(&ctx, &user_id).await?))
 }
 
-pub async fn logout(_ctx: ReqContext, _i: Unit) -> HandlerResult<Unit> {
+pub async fn verify_email(ctx: ReqContext, i: VerifyEmailReq) -> HandlerResult<VerifyEmailResp> {
+    let claims = match jwt::decode_email_verification(&ctx.state.config, &i.token) {
+        Ok(claims) => claims,
+        Err(failure) => {
+            log::info!("rejected email verification token: {}", failure);
+            return Ok(VerifyEmailResp::InvalidToken);
+        }
+    };
+    let user_id = DbKey::from_string(claims.sub);
+    match db::get_user_with_id(&ctx.state.db_pool, &user_id).await? {
+        // The link is only valid for the address it was sent to
+        Some((_, user)) if user.email == claims.email => {
+            db::set_user_verified(&ctx.state.db_pool, &user_id).await?;
+            Ok(VerifyEmailResp::Verified)
+        }
+        _ => Ok(VerifyEmailResp::InvalidToken),
+    }
+}
+
+pub async fn resend_verification_email(
+    ctx: ReqContext,
+    i: ResendVerificationEmailReq,
+) -> HandlerResult<Unit> {
+    if let Some((user_id, user)) = db::get_user_with_email(&ctx.state.db_pool, &i.email).await? {
+        send_verification_email(&ctx.state, &user_id, &user).await?;
+    }
     Ok(Unit {})
 }
 
+pub async fn begin_totp_enrolment(
+    ctx: ReqContext,
+    _i: (),
+) -> HandlerResult<BeginTotpEnrolmentResp> {
+    let pool = &ctx.state.db_pool;
+    let user_id = own_user_from_claims(&ctx.claims)?;
+    if let Some((_, totp)) = db::get_user_totp(pool, &user_id).await? {
+        if totp.confirmed_at.is_some() {
+            return Ok(BeginTotpEnrolmentResp::AlreadyEnrolled);
+        }
+    }
+    let (_, user) = db::get_user_with_id(pool, &user_id)
+        .await?
+        .ok_or(forbidden())?;
+    let secret = totp::new_secret();
+    db::create_user_totp(pool, &user_id, &secret).await?;
+    let otpauth_uri = totp::otpauth_uri(&ctx.state.config.totp_issuer, &user.email, &secret);
+    Ok(BeginTotpEnrolmentResp::Enrolment(TotpEnrolment {
+        secret,
+        otpauth_uri,
+    }))
+}
+
+pub async fn confirm_totp_enrolment(
+    ctx: ReqContext,
+    i: ConfirmTotpEnrolmentReq,
+) -> HandlerResult<ConfirmTotpEnrolmentResp> {
+    let pool = &ctx.state.db_pool;
+    let user_id = own_user_from_claims(&ctx.claims)?;
+    let (totp_id, totp) = match db::get_user_totp(pool, &user_id).await? {
+        Some((totp_id, totp)) if totp.confirmed_at.is_none() => (totp_id, totp),
+        _ => return Ok(ConfirmTotpEnrolmentResp::NotEnrolling),
+    };
+    let step = match totp::verify(&totp.secret, &i.code, None) {
+        Some(step) => step,
+        None => return Ok(ConfirmTotpEnrolmentResp::InvalidCode),
+    };
+    if !db::confirm_user_totp(pool, &totp_id, step).await? {
+        return Ok(ConfirmTotpEnrolmentResp::NotEnrolling);
+    }
+    let recovery_codes = totp::new_recovery_codes();
+    let code_hashes: Vec<String> = recovery_codes
+        .iter()
+        .map(|code| hash_token(&totp::normalise_recovery_code(code)))
+        .collect();
+    db::replace_totp_recovery_codes(pool, &user_id, &code_hashes).await?;
+    Ok(ConfirmTotpEnrolmentResp::RecoveryCodes(recovery_codes))
+}
+
 pub async fn new_message(ctx: ReqContext, i: NewMessageReq) -> HandlerResult<MessageId> {
     let user_id = user_from_claims(&ctx.claims)?;
-    let message_id = db::new_message(&ctx.state.db_pool, &user_id, &i.message).await?;
+    let organisation_id = organisation_from_claims(&ctx.claims);
+    let message_id = db::new_message(
+        &ctx.state.db_pool,
+        &user_id,
+        &organisation_id,
+        &None,
+        &i.message,
+    )
+    .await?;
     Ok(message_id)
 }
 
@@ -81,8 +507,17 @@ pub async fn recent_messages(
     ctx: ReqContext,
     i: RecentMessagesReq,
 ) -> HandlerResult<Paginated<Message>> {
-    let messages = db::recent_messages(&ctx.state.db_pool, i.page.offset, i.page.limit).await?;
-    let total_count = db::message_count(&ctx.state.db_pool).await?;
+    let user_id = user_from_claims(&ctx.claims)?;
+    let organisation_id = organisation_from_claims(&ctx.claims);
+    let messages = db::recent_messages(
+        &ctx.state.db_pool,
+        &user_id,
+        &organisation_id,
+        i.page.offset,
+        i.page.limit,
+    )
+    .await?;
+    let total_count = db::message_count(&ctx.state.db_pool, &organisation_id).await?;
     Ok(Paginated {
         items: messages,
         current_offset: i.page.offset,
@@ -90,46 +525,690 @@ pub async fn recent_messages(
     })
 }
 
+pub async fn reply_to_message(
+    ctx: ReqContext,
+    i: ReplyToMessageReq,
+) -> HandlerResult<ReplyToMessageResp> {
+    let user_id = user_from_claims(&ctx.claims)?;
+    let organisation_id = organisation_from_claims(&ctx.claims);
+    let pool = &ctx.state.db_pool;
+    // Threads are a single level deep, so replies can't be replied to
+    match db::get_message(pool, &organisation_id, &i.reply_to).await? {
+        Some(parent) if parent.reply_to.is_none() && parent.deleted_at.is_none() => {}
+        _ => return Ok(ReplyToMessageResp::InvalidMessage),
+    }
+    let message_id = db::new_message(
+        pool,
+        &user_id,
+        &organisation_id,
+        &Some(i.reply_to),
+        &i.message,
+    )
+    .await?;
+    Ok(ReplyToMessageResp::Success(message_id))
+}
+
+pub async fn message_thread(
+    ctx: ReqContext,
+    i: MessageThreadReq,
+) -> HandlerResult<MessageThreadResp> {
+    let user_id = user_from_claims(&ctx.claims)?;
+    let organisation_id = organisation_from_claims(&ctx.claims);
+    let pool = &ctx.state.db_pool;
+    let message = match db::get_top_level_message(pool, &user_id, &organisation_id, &i.id).await? {
+        Some(message) => message,
+        None => return Ok(MessageThreadResp::InvalidMessage),
+    };
+    let replies = db::message_replies(pool, &user_id, &i.id, i.page.offset, i.page.limit).await?;
+    let total_count = db::reply_count(pool, &i.id).await?;
+    Ok(MessageThreadResp::Thread(MessageThread {
+        message,
+        replies: Paginated {
+            items: replies,
+            current_offset: i.page.offset,
+            total_count,
+        },
+    }))
+}
+
+pub async fn add_reaction(
+    ctx: ReqContext,
+    i: MessageReactionReq,
+) -> HandlerResult<AddReactionResp> {
+    let user_id = user_from_claims(&ctx.claims)?;
+    let organisation_id = organisation_from_claims(&ctx.claims);
+    let pool = &ctx.state.db_pool;
+    if !is_valid_reaction(&i.emoji) {
+        return Ok(AddReactionResp::InvalidEmoji);
+    }
+    match db::get_message(pool, &organisation_id, &i.message_id).await? {
+        Some(message) if message.deleted_at.is_none() => {}
+        _ => return Ok(AddReactionResp::InvalidMessage),
+    }
+    db::add_message_reaction(pool, &i.message_id, &user_id, &i.emoji).await?;
+    Ok(AddReactionResp::Success)
+}
+
+pub async fn remove_reaction(ctx: ReqContext, i: MessageReactionReq) -> HandlerResult<Unit> {
+    let user_id = user_from_claims(&ctx.claims)?;
+    db::remove_message_reaction(&ctx.state.db_pool, &i.message_id, &user_id, &i.emoji).await?;
+    Ok(Unit {})
+}
+
+const MAX_REACTION_CHARS: usize = 16;
+
+/**
+ * Reactions are limited to a short run of non-ASCII characters, which
+ * admits emoji along with their modifiers and joiners
+ */
+fn is_valid_reaction(emoji: &str) -> bool {
+    let len = emoji.chars().count();
+    (1..=MAX_REACTION_CHARS).contains(&len)
+        && emoji.chars().all(|c| !c.is_ascii() && !c.is_whitespace())
+}
+
+pub async fn edit_message(ctx: ReqContext, i: EditMessageReq) -> HandlerResult<EditMessageResp> {
+    let user_id = user_from_claims(&ctx.claims)?;
+    let pool = &ctx.state.db_pool;
+    let message = match own_message(&ctx, &user_id, &i.id).await? {
+        MessageAccess::Permitted(message) => message,
+        MessageAccess::Invalid => return Ok(EditMessageResp::InvalidMessage),
+        MessageAccess::NotPermitted => return Ok(EditMessageResp::NotPermitted),
+    };
+    if message.deleted_at.is_some() || !db::edit_message(pool, &i.id, &i.message, &user_id).await? {
+        return Ok(EditMessageResp::InvalidMessage);
+    }
+    Ok(EditMessageResp::Success)
+}
+
+pub async fn delete_message(ctx: ReqContext, i: MessageId) -> HandlerResult<DeleteMessageResp> {
+    let user_id = user_from_claims(&ctx.claims)?;
+    let pool = &ctx.state.db_pool;
+    let message = match own_message(&ctx, &user_id, &i).await? {
+        MessageAccess::Permitted(message) => message,
+        MessageAccess::Invalid => return Ok(DeleteMessageResp::InvalidMessage),
+        MessageAccess::NotPermitted => return Ok(DeleteMessageResp::NotPermitted),
+    };
+    if message.deleted_at.is_some() || !db::delete_message(pool, &i, &user_id).await? {
+        return Ok(DeleteMessageResp::InvalidMessage);
+    }
+    Ok(DeleteMessageResp::Success)
+}
+
+pub async fn message_revisions(
+    ctx: ReqContext,
+    i: MessageId,
+) -> HandlerResult<MessageRevisionsResp> {
+    let user_id = user_from_claims(&ctx.claims)?;
+    match own_message(&ctx, &user_id, &i).await? {
+        MessageAccess::Permitted(_) => {}
+        MessageAccess::Invalid => return Ok(MessageRevisionsResp::InvalidMessage),
+        MessageAccess::NotPermitted => return Ok(MessageRevisionsResp::NotPermitted),
+    };
+    let revisions = db::message_revisions(&ctx.state.db_pool, &i).await?;
+    Ok(MessageRevisionsResp::Revisions(revisions))
+}
+
+enum MessageAccess {
+    Permitted(adl::gen::protoapp::db::Message),
+    Invalid,
+    NotPermitted,
+}
+
+/**
+ * Get a message visible within the caller's organisation, checking that
+ * they posted it or are an admin
+ */
+async fn own_message(
+    ctx: &ReqContext,
+    user_id: &AppUserId,
+    message_id: &MessageId,
+) -> HandlerResult<MessageAccess> {
+    let organisation_id = organisation_from_claims(&ctx.claims);
+    let message = match db::get_message(&ctx.state.db_pool, &organisation_id, message_id).await? {
+        Some(message) => message,
+        None => return Ok(MessageAccess::Invalid),
+    };
+    let is_admin = ctx
+        .claims
+        .as_ref()
+        .is_some_and(|claims| claims.has_role(jwt::ROLE_ADMIN));
+    if message.posted_by != *user_id && !is_admin {
+        return Ok(MessageAccess::NotPermitted);
+    }
+    Ok(MessageAccess::Permitted(message))
+}
+
 pub async fn who_am_i(ctx: ReqContext, _i: ()) -> HandlerResult<UserWithId> {
     let user_id = user_from_claims(&ctx.claims)?;
     let user = db::get_user_with_id(&ctx.state.db_pool, &user_id).await?;
     match user {
-        Some((user_id, user)) => Ok(UserWithId {
-            id: user_id.clone(),
-            value: User {
-                fullname: user.fullname,
-                email: user.email,
-                is_admin: user.is_admin,
-            },
-        }),
+        Some((user_id, user)) => {
+            let roles = db::user_role_names(&ctx.state.db_pool, &[user_id.clone()])
+                .await?
+                .remove(&user_id.0)
+                .unwrap_or_default();
+            Ok(UserWithId {
+                id: user_id,
+                value: User {
+                    fullname: user.fullname,
+                    email: user.email,
+                    roles,
+                    active: user.deactivated_at.is_none(),
+                },
+            })
+        }
         None => Err(forbidden()),
     }
 }
 
-pub async fn create_user(ctx: ReqContext, i: UserDetails) -> HandlerResult<AppUserId> {
-    let hashed_password = hash_password(&i.password).expect("password can be hashed");
+pub async fn update_profile(
+    ctx: ReqContext,
+    i: UpdateProfileReq,
+) -> HandlerResult<UpdateProfileResp> {
+    let pool = &ctx.state.db_pool;
+    // Admins impersonating a user can't take over their account, by
+    // changing its email address and then resetting the password
+    let user_id = own_user_from_claims(&ctx.claims)?;
+    let current = match db::get_user_with_id(pool, &user_id).await? {
+        Some((_, user)) => user,
+        None => return Err(forbidden()),
+    };
+    // A change of case or whitespace is the same address, which is kept
+    if normalise_email(&current.email) == normalise_email(&i.email) {
+        let user = AppUser {
+            fullname: i.fullname,
+            ..current
+        };
+        db::update_user(pool, &user_id, &user).await?;
+        return Ok(UpdateProfileResp::Success);
+    }
+
+    let failure_key = login_failure_key(&current.email);
+    if let Some(secs) = login_retry_after(&ctx.state, &failure_key).await? {
+        return Ok(UpdateProfileResp::TooManyAttempts(secs));
+    }
+    let password = i.current_password.as_deref().unwrap_or_default();
+    if !password_matches(&ctx.state, &user_id, password, &current.hashed_password) {
+        record_login_failure(&ctx.state, &failure_key).await?;
+        return Ok(UpdateProfileResp::InvalidCredentials);
+    }
+    if db::get_user_with_email(pool, &i.email).await?.is_some() {
+        return Ok(UpdateProfileResp::EmailInUse);
+    }
+    // A changed email address needs to be verified again
+    let user = AppUser {
+        fullname: i.fullname,
+        email: i.email,
+        verified_at: None,
+        verification_sent_at: None,
+        ..current
+    };
+    db::update_user(pool, &user_id, &user).await?;
+    send_verification_email(&ctx.state, &user_id, &user).await?;
+    Ok(UpdateProfileResp::Success)
+}
+
+pub async fn change_password(
+    ctx: ReqContext,
+    i: ChangePasswordReq,
+) -> HandlerResult<ChangePasswordResp> {
+    let pool = &ctx.state.db_pool;
+    let user_id = own_user_from_claims(&ctx.claims)?;
+    let user = match db::get_user_with_id(pool, &user_id).await? {
+        Some((_, user)) => user,
+        None => return Err(forbidden()),
+    };
+
+    // A stolen access token shouldn't allow the password to be guessed
+    let failure_key = login_failure_key(&user.email);
+    if let Some(secs) = login_retry_after(&ctx.state, &failure_key).await? {
+        return Ok(ChangePasswordResp::TooManyAttempts(secs));
+    }
+    if !password_matches(
+        &ctx.state,
+        &user_id,
+        &i.current_password,
+        &user.hashed_password,
+    ) {
+        record_login_failure(&ctx.state, &failure_key).await?;
+        return Ok(ChangePasswordResp::InvalidCredentials);
+    }
+    let violations = ctx
+        .state
+        .password_policy
+        .check(&i.new_password, &user.email);
+    if !violations.is_empty() {
+        return Ok(ChangePasswordResp::WeakPassword(violations));
+    }
+    let hashed_password =
+        hash_password(&ctx.state.config.argon2, &i.new_password).expect("password can be hashed");
+    db::update_user_password(pool, &user_id, &hashed_password).await?;
+    let current = current_session(&ctx.claims);
+    db::revoke_user_sessions(pool, &user_id, current.as_ref()).await?;
+    Ok(ChangePasswordResp::Success)
+}
+
+pub async fn my_sessions(ctx: ReqContext, _i: ()) -> HandlerResult<Vec<SessionSummaryWithId>> {
+    let user_id = user_from_claims(&ctx.claims)?;
+    let current = current_session(&ctx.claims);
+    // Sessions can't be refreshed after their last refresh token expires
+    let expiry = Duration::from_secs(ctx.state.config.jwt_refresh_expiry_secs as u64);
+    let refreshed_after = Instant(SystemTime::now() - expiry);
+    let sessions = db::user_sessions(&ctx.state.db_pool, &user_id, &refreshed_after).await?;
+    Ok(sessions
+        .into_iter()
+        .map(|(id, session)| SessionSummaryWithId {
+            value: SessionSummary {
+                user_agent: session.user_agent,
+                client_ip: session.client_ip,
+                created_at: session.created_at,
+                last_refreshed_at: session.last_refreshed_at,
+                current: current.as_ref() == Some(&id),
+            },
+            id,
+        })
+        .collect())
+}
+
+pub async fn revoke_session(ctx: ReqContext, i: SessionId) -> HandlerResult<Unit> {
+    let user_id = own_user_from_claims(&ctx.claims)?;
+    match db::get_session(&ctx.state.db_pool, &i).await? {
+        Some(session) if session.user_id == user_id => {
+            db::revoke_session(&ctx.state.db_pool, &i).await?;
+            Ok(Unit {})
+        }
+        _ => Err(forbidden()),
+    }
+}
+
+pub async fn revoke_other_sessions(ctx: ReqContext, _i: ()) -> HandlerResult<Unit> {
+    let user_id = own_user_from_claims(&ctx.claims)?;
+    let current = current_session(&ctx.claims);
+    db::revoke_user_sessions(&ctx.state.db_pool, &user_id, current.as_ref()).await?;
+    Ok(Unit {})
+}
+
+pub async fn create_user(ctx: ReqContext, i: UserDetails) -> HandlerResult<CreateUserResp> {
+    let violations = ctx.state.password_policy.check(&i.password, &i.email);
+    if !violations.is_empty() {
+        return Ok(CreateUserResp::WeakPassword(violations));
+    }
+    let hashed_password =
+        hash_password(&ctx.state.config.argon2, &i.password).expect("password can be hashed");
     let user = AppUser {
         fullname: i.fullname.clone(),
         email: i.email.clone(),
-        is_admin: i.is_admin,
-        hashed_password,
+        hashed_password: Some(hashed_password),
+        verified_at: None,
+        verification_sent_at: None,
+        deactivated_at: None,
     };
     let id = db::create_user(&ctx.state.db_pool, &user).await?;
-    Ok(id)
+    send_verification_email(&ctx.state, &id, &user).await?;
+    Ok(CreateUserResp::Success(id))
 }
 
 pub async fn update_user(
     ctx: ReqContext,
     i: WithId<AppUserId, UserDetails>,
-) -> HandlerResult<Unit> {
-    let hashed_password = hash_password(&i.value.password).expect("password can be hashed");
+) -> HandlerResult<UpdateUserResp> {
+    let violations = ctx
+        .state
+        .password_policy
+        .check(&i.value.password, &i.value.email);
+    if !violations.is_empty() {
+        return Ok(UpdateUserResp::WeakPassword(violations));
+    }
+    let hashed_password =
+        hash_password(&ctx.state.config.argon2, &i.value.password).expect("password can be hashed");
+    let current = db::get_user_with_id(&ctx.state.db_pool, &i.id).await?;
+
+    let deactivated_at = current.as_ref().and_then(|(_, c)| c.deactivated_at.clone());
+
+    // A changed email address needs to be verified again
+    let (verified_at, verification_sent_at) = match current {
+        Some((_, current)) if current.email == i.value.email => {
+            (current.verified_at, current.verification_sent_at)
+        }
+        _ => (None, None),
+    };
     let user = AppUser {
         fullname: i.value.fullname.clone(),
         email: i.value.email.clone(),
-        is_admin: i.value.is_admin,
-        hashed_password,
+        hashed_password: Some(hashed_password),
+        verified_at,
+        verification_sent_at,
+        deactivated_at,
     };
     db::update_user(&ctx.state.db_pool, &i.id, &user).await?;
+    send_verification_email(&ctx.state, &i.id, &user).await?;
+    Ok(UpdateUserResp::Success)
+}
+
+pub async fn unlock_user(ctx: ReqContext, i: AppUserId) -> HandlerResult<Unit> {
+    let admin_id = user_from_claims(&ctx.claims)?;
+    if let Some((_, user)) = db::get_user_with_id(&ctx.state.db_pool, &i).await? {
+        db::clear_login_failures(&ctx.state.db_pool, &login_failure_key(&user.email)).await?;
+        log::info!("logins for user {} unlocked by {}", i.0, admin_id.0);
+    }
+    Ok(Unit {})
+}
+
+pub async fn impersonate_user(ctx: ReqContext, i: AppUserId) -> HandlerResult<ImpersonateUserResp> {
+    let admin_id = user_from_claims(&ctx.claims)?;
+    if db::get_user_with_id(&ctx.state.db_pool, &i)
+        .await?
+        .is_none()
+    {
+        return Ok(ImpersonateUserResp::InvalidUser);
+    }
+    log::warn!("user {} impersonated by {}", i.0, admin_id.0);
+    let organisation_id = db::default_organisation(&ctx.state.db_pool, &i).await?;
+    let access_jwt = jwt::create_impersonation_access(
+        &ctx.state.config,
+        &ctx.state.access_keys,
+        i.0,
+        admin_id.0,
+        organisation_id.map(|id| id.0),
+    );
+    Ok(ImpersonateUserResp::AccessJwt(access_jwt))
+}
+
+pub async fn revoke_user_sessions(ctx: ReqContext, i: AppUserId) -> HandlerResult<Unit> {
+    let admin_id = user_from_claims(&ctx.claims)?;
+    db::revoke_user_sessions(&ctx.state.db_pool, &i, None).await?;
+    log::info!("sessions for user {} revoked by {}", i.0, admin_id.0);
+    Ok(Unit {})
+}
+
+pub async fn deactivate_user(ctx: ReqContext, i: AppUserId) -> HandlerResult<DeactivateUserResp> {
+    let admin_id = user_from_claims(&ctx.claims)?;
+    if i.0 == admin_id.0 {
+        return Ok(DeactivateUserResp::CannotDeactivateSelf);
+    }
+    let pool = &ctx.state.db_pool;
+    if !db::set_user_deactivated(pool, &i, Some(db::instant_now())).await? {
+        return Ok(DeactivateUserResp::InvalidUser);
+    }
+    db::revoke_user_sessions(pool, &i, None).await?;
+    ctx.state.deactivated_users.invalidate();
+    log::warn!("user {} deactivated by {}", i.0, admin_id.0);
+    Ok(DeactivateUserResp::Success)
+}
+
+pub async fn reactivate_user(ctx: ReqContext, i: AppUserId) -> HandlerResult<ReactivateUserResp> {
+    let admin_id = user_from_claims(&ctx.claims)?;
+    if !db::set_user_deactivated(&ctx.state.db_pool, &i, None).await? {
+        return Ok(ReactivateUserResp::InvalidUser);
+    }
+    ctx.state.deactivated_users.invalidate();
+    log::info!("user {} reactivated by {}", i.0, admin_id.0);
+    Ok(ReactivateUserResp::Success)
+}
+
+pub async fn set_user_roles(
+    ctx: ReqContext,
+    i: SetUserRolesReq,
+) -> HandlerResult<SetUserRolesResp> {
+    let admin_id = user_from_claims(&ctx.claims)?;
+    let pool = &ctx.state.db_pool;
+    if db::get_user_with_id(pool, &i.user_id).await?.is_none() {
+        return Ok(SetUserRolesResp::InvalidUser);
+    }
+    if !db::roles_exist(pool, &i.roles).await? {
+        return Ok(SetUserRolesResp::InvalidRole);
+    }
+    db::set_user_roles(pool, &i.user_id, &i.roles).await?;
+    log::info!("roles for user {} set by {}", i.user_id.0, admin_id.0);
+    Ok(SetUserRolesResp::Success)
+}
+
+pub async fn create_role(ctx: ReqContext, i: CreateRoleReq) -> HandlerResult<CreateRoleResp> {
+    let pool = &ctx.state.db_pool;
+    // Every user implicitly has the user role
+    if i.name == jwt::ROLE_USER || db::get_role_with_name(pool, &i.name).await?.is_some() {
+        return Ok(CreateRoleResp::NameInUse);
+    }
+    let role = Role {
+        name: i.name,
+        description: i.description,
+    };
+    let role_id = db::create_role(pool, &role).await?;
+    db::set_role_permissions(pool, &role_id, &i.permissions).await?;
+    Ok(CreateRoleResp::Success(role_id))
+}
+
+pub async fn query_roles(
+    ctx: ReqContext,
+    i: QueryRolesReq,
+) -> HandlerResult<Paginated<RoleDetailsWithId>> {
+    let roles = db::query_roles(&ctx.state.db_pool, i.page.offset, i.page.limit).await?;
+    let total_count = db::role_count(&ctx.state.db_pool).await?;
+    let page = Paginated {
+        items: roles,
+        current_offset: i.page.offset,
+        total_count,
+    };
+    Ok(page)
+}
+
+pub async fn set_role_permissions(
+    ctx: ReqContext,
+    i: SetRolePermissionsReq,
+) -> HandlerResult<SetRolePermissionsResp> {
+    let pool = &ctx.state.db_pool;
+    if db::get_role_with_id(pool, &i.role_id).await?.is_none() {
+        return Ok(SetRolePermissionsResp::InvalidRole);
+    }
+    db::set_role_permissions(pool, &i.role_id, &i.permissions).await?;
+    Ok(SetRolePermissionsResp::Success)
+}
+
+pub async fn delete_role(ctx: ReqContext, i: RoleId) -> HandlerResult<DeleteRoleResp> {
+    let admin_id = user_from_claims(&ctx.claims)?;
+    let pool = &ctx.state.db_pool;
+    let role = match db::get_role_with_id(pool, &i).await? {
+        Some((_, role)) => role,
+        None => return Ok(DeleteRoleResp::InvalidRole),
+    };
+    if role.name == jwt::ROLE_ADMIN {
+        return Ok(DeleteRoleResp::BuiltinRole);
+    }
+    db::delete_role(pool, &i).await?;
+    log::info!("role {} deleted by {}", role.name, admin_id.0);
+    Ok(DeleteRoleResp::Success)
+}
+
+pub async fn create_organisation(
+    ctx: ReqContext,
+    i: CreateOrganisationReq,
+) -> HandlerResult<CreateOrganisationResp> {
+    let pool = &ctx.state.db_pool;
+    if db::get_organisation_with_name(pool, &i.name)
+        .await?
+        .is_some()
+    {
+        return Ok(CreateOrganisationResp::NameInUse);
+    }
+    let organisation = Organisation {
+        name: i.name,
+        created_at: db::instant_now(),
+    };
+    let organisation_id = db::create_organisation(pool, &organisation).await?;
+    Ok(CreateOrganisationResp::Success(organisation_id))
+}
+
+pub async fn query_organisations(
+    ctx: ReqContext,
+    i: QueryOrganisationsReq,
+) -> HandlerResult<Paginated<OrganisationWithId>> {
+    let organisations =
+        db::query_organisations(&ctx.state.db_pool, i.page.offset, i.page.limit).await?;
+    let total_count = db::organisation_count(&ctx.state.db_pool).await?;
+    let page = Paginated {
+        items: organisations,
+        current_offset: i.page.offset,
+        total_count,
+    };
+    Ok(page)
+}
+
+pub async fn set_organisation_member(
+    ctx: ReqContext,
+    i: SetOrganisationMemberReq,
+) -> HandlerResult<SetOrganisationMemberResp> {
+    let admin_id = user_from_claims(&ctx.claims)?;
+    let pool = &ctx.state.db_pool;
+    if db::get_organisation_with_id(pool, &i.organisation_id)
+        .await?
+        .is_none()
+    {
+        return Ok(SetOrganisationMemberResp::InvalidOrganisation);
+    }
+    if db::get_user_with_id(pool, &i.user_id).await?.is_none() {
+        return Ok(SetOrganisationMemberResp::InvalidUser);
+    }
+    // Admin endpoints act across organisations, so their role can't be
+    // granted within one
+    if !db::roles_exist(pool, &i.roles).await?
+        || db::role_names(pool, &i.roles)
+            .await?
+            .iter()
+            .any(|r| r == jwt::ROLE_ADMIN)
+    {
+        return Ok(SetOrganisationMemberResp::InvalidRole);
+    }
+    db::set_organisation_member(pool, &i.organisation_id, &i.user_id, &i.roles).await?;
+    log::info!(
+        "user {} added to organisation {} by {}",
+        i.user_id.0,
+        i.organisation_id.0,
+        admin_id.0
+    );
+    Ok(SetOrganisationMemberResp::Success)
+}
+
+pub async fn remove_organisation_member(
+    ctx: ReqContext,
+    i: RemoveOrganisationMemberReq,
+) -> HandlerResult<Unit> {
+    let admin_id = user_from_claims(&ctx.claims)?;
+    db::remove_organisation_member(&ctx.state.db_pool, &i.organisation_id, &i.user_id).await?;
+    log::info!(
+        "user {} removed from organisation {} by {}",
+        i.user_id.0,
+        i.organisation_id.0,
+        admin_id.0
+    );
+    Ok(Unit {})
+}
+
+pub async fn switch_organisation(
+    ctx: ReqContext,
+    i: SwitchOrganisationReq,
+) -> HandlerResult<SwitchOrganisationResp> {
+    let user_id = user_from_claims(&ctx.claims)?;
+    // The organisation is recorded against the session, so requests made
+    // with API keys or impersonation tokens can't switch
+    let session_id = current_session(&ctx.claims).ok_or_else(forbidden)?;
+    let pool = &ctx.state.db_pool;
+    let is_admin = ctx
+        .claims
+        .as_ref()
+        .is_some_and(|claims| claims.has_role(jwt::ROLE_ADMIN));
+    if let Some(organisation_id) = &i.organisation_id {
+        if db::get_organisation_with_id(pool, organisation_id)
+            .await?
+            .is_none()
+        {
+            return Ok(SwitchOrganisationResp::InvalidOrganisation);
+        }
+        let roles = db::user_role_names(pool, &[user_id.clone()])
+            .await?
+            .remove(&user_id.0)
+            .unwrap_or_default();
+        if organisation_roles(&ctx.state, &user_id, &roles, organisation_id)
+            .await?
+            .is_none()
+        {
+            return Ok(SwitchOrganisationResp::InvalidOrganisation);
+        }
+    } else if !is_admin {
+        // Only admins can act across the whole deployment
+        return Ok(SwitchOrganisationResp::InvalidOrganisation);
+    }
+    db::set_session_organisation(pool, &session_id, &i.organisation_id).await?;
+    let access_jwt = access_jwt_from_user(&ctx.state, &user_id, &session_id).await?;
+    Ok(SwitchOrganisationResp::AccessJwt(access_jwt))
+}
+
+pub async fn create_invitation(
+    ctx: ReqContext,
+    i: CreateInvitationReq,
+) -> HandlerResult<CreateInvitationResp> {
+    let admin_id = user_from_claims(&ctx.claims)?;
+    let state = &ctx.state;
+    if db::get_user_with_email(&state.db_pool, &i.email)
+        .await?
+        .is_some()
+    {
+        return Ok(CreateInvitationResp::EmailInUse);
+    }
+    if !db::roles_exist(&state.db_pool, &i.roles).await? {
+        return Ok(CreateInvitationResp::InvalidRole);
+    }
+    if i.expires_in_days == 0 || i.expires_in_days > state.config.invitation_max_expiry_days {
+        return Ok(CreateInvitationResp::InvalidExpiry);
+    }
+    let token = new_token();
+    let created_at = db::instant_now();
+    let expires_at = Instant(created_at.0 + Duration::from_secs(i.expires_in_days as u64 * 86400));
+    let invitation = Invitation {
+        email: i.email,
+        roles: i.roles,
+        token_hash: hash_token(&token),
+        created_by: admin_id,
+        created_at,
+        expires_at,
+        used_at: None,
+        user_id: None,
+        revoked_at: None,
+    };
+    let id = db::create_invitation(&state.db_pool, &invitation).await?;
+    let email = Email {
+        to: invitation.email,
+        subject: "You're invited".to_owned(),
+        body: format!(
+            "Hi,\n\n\
+             You've been invited to create an account. To sign up, follow this\n\
+             link within {} days:\n\n\
+             {}/signup?token={}\n",
+            i.expires_in_days, state.config.app_url, token
+        ),
+    };
+    if let Err(e) = state.mailer.send(email).await {
+        log::error!("failed to send invitation email for {}: {}", id.0, e);
+    }
+    Ok(CreateInvitationResp::Success(id))
+}
+
+pub async fn query_invitations(
+    ctx: ReqContext,
+    i: QueryInvitationsReq,
+) -> HandlerResult<Paginated<InvitationSummaryWithId>> {
+    let invitations = db::query_invitations(&ctx.state.db_pool, &i).await?;
+    let total_count = db::invitation_count(&ctx.state.db_pool, &i).await?;
+    let page = Paginated {
+        items: invitations,
+        current_offset: i.page.offset,
+        total_count,
+    };
+    Ok(page)
+}
+
+pub async fn revoke_invitation(ctx: ReqContext, i: InvitationId) -> HandlerResult<Unit> {
+    db::revoke_invitation(&ctx.state.db_pool, &i).await?;
     Ok(Unit {})
 }
 
@@ -137,8 +1216,15 @@ pub async fn query_users(
     ctx: ReqContext,
     i: QueryUsersReq,
 ) -> HandlerResult<Paginated<UserWithId>> {
-    let users = db::query_users(&ctx.state.db_pool, i.page.offset, i.page.limit).await?;
-    let total_count = db::user_count(&ctx.state.db_pool).await?;
+    let organisation_id = organisation_from_claims(&ctx.claims);
+    let users = db::query_users(
+        &ctx.state.db_pool,
+        &organisation_id,
+        i.page.offset,
+        i.page.limit,
+    )
+    .await?;
+    let total_count = db::user_count(&ctx.state.db_pool, &organisation_id).await?;
     let page = Paginated {
         items: users,
         current_offset: i.page.offset,
@@ -147,18 +1233,155 @@ pub async fn query_users(
     Ok(page)
 }
 
+pub async fn jwt_failure_counts(ctx: ReqContext, _i: ()) -> HandlerResult<JwtFailureCounts> {
+    Ok(ctx.state.jwt_failures.counts())
+}
+
+pub async fn query_audit_log(
+    ctx: ReqContext,
+    i: QueryAuditLogReq,
+) -> HandlerResult<Paginated<AuditLogEntry>> {
+    let entries = db::query_audit_log(&ctx.state.db_pool, &i).await?;
+    let total_count = db::audit_log_count(&ctx.state.db_pool, &i).await?;
+    let page = Paginated {
+        items: entries,
+        current_offset: i.page.offset,
+        total_count,
+    };
+    Ok(page)
+}
+
+pub async fn create_api_key(
+    ctx: ReqContext,
+    i: CreateApiKeyReq,
+) -> HandlerResult<CreateApiKeyResp> {
+    let pool = &ctx.state.db_pool;
+    if db::get_user_with_id(pool, &i.user_id).await?.is_none() {
+        return Ok(CreateApiKeyResp::InvalidUser);
+    }
+    if i.expires_in_days == 0 || i.expires_in_days > ctx.state.config.api_key_max_expiry_days {
+        return Ok(CreateApiKeyResp::InvalidExpiry);
+    }
+    let key = api_keys::new_api_key();
+    let created_at = db::instant_now();
+    let expires_at = Instant(created_at.0 + Duration::from_secs(i.expires_in_days as u64 * 86400));
+    let api_key = ApiKey {
+        user_id: i.user_id,
+        name: i.name,
+        key_hash: hash_token(&key),
+        scopes: i.scopes,
+        created_at,
+        expires_at,
+        last_used_at: None,
+        revoked_at: None,
+    };
+    let id = db::create_api_key(pool, &api_key).await?;
+    Ok(CreateApiKeyResp::Success(NewApiKey { id, key }))
+}
+
+pub async fn query_api_keys(
+    ctx: ReqContext,
+    i: QueryApiKeysReq,
+) -> HandlerResult<Paginated<ApiKeySummaryWithId>> {
+    let keys = db::query_api_keys(&ctx.state.db_pool, &i).await?;
+    let total_count = db::api_key_count(&ctx.state.db_pool, &i).await?;
+    let page = Paginated {
+        items: keys,
+        current_offset: i.page.offset,
+        total_count,
+    };
+    Ok(page)
+}
+
+pub async fn revoke_api_key(ctx: ReqContext, i: ApiKeyId) -> HandlerResult<Unit> {
+    db::revoke_api_key(&ctx.state.db_pool, &i).await?;
+    Ok(Unit {})
+}
+
 #[handler]
 pub async fn login_with_cookies(
     req: &poem::Request,
     cookies: &CookieJar,
     i: Json<LoginReq>,
 ) -> poem::Result<Json<LoginResp>> {
-    let ctx = get_adl_request_context(req, &ApiRequests::def_login().security)?;
-    let eresp = login(ctx, i.0).await;
+    let def = ApiRequests::def_login();
+    let ctx: ReqContext = get_adl_request_context(req, &def.security).await?;
+    let config = ctx.state.config.clone();
+    let eresp = with_timeout(request_timeout(req, &def), login(ctx, i.0)).await;
     if let Ok(LoginResp::Tokens(tokens)) = &eresp {
-        let mut cookie = Cookie::new_with_str(REFRESH_TOKEN, tokens.refresh_jwt.clone());
-        cookie.set_http_only(true);
-        cookies.add(cookie);
+        add_refresh_cookie(&config, cookies, tokens);
+    }
+    eresp.map(Json).map_err(poem::Error::from)
+}
+
+#[handler]
+pub async fn login_totp_with_cookies(
+    req: &poem::Request,
+    cookies: &CookieJar,
+    i: Json<LoginTotpReq>,
+) -> poem::Result<Json<LoginTotpResp>> {
+    let def = ApiRequests::def_login_totp();
+    let ctx: ReqContext = get_adl_request_context(req, &def.security).await?;
+    let config = ctx.state.config.clone();
+    let eresp = with_timeout(request_timeout(req, &def), login_totp(ctx, i.0)).await;
+    if let Ok(LoginTotpResp::Tokens(tokens)) = &eresp {
+        add_refresh_cookie(&config, cookies, tokens);
+    }
+    eresp.map(Json).map_err(poem::Error::from)
+}
+
+#[handler]
+pub async fn oidc_login_start_with_cookies(
+    req: &poem::Request,
+    cookies: &CookieJar,
+    _i: Json<()>,
+) -> poem::Result<Json<OidcLoginStartResp>> {
+    let def = ApiRequests::def_oidc_login_start();
+    let ctx: ReqContext = get_adl_request_context(req, &def.security).await?;
+    let config = ctx.state.config.clone();
+    let state = new_token();
+    let eresp = with_timeout(request_timeout(req, &def), oidc_login_start(ctx, &state)).await;
+    if let (Ok(OidcLoginStartResp::AuthorizationUrl(_)), Some(oidc)) = (&eresp, &config.oidc) {
+        let expiry = Duration::from_secs(oidc.login_expiry_secs as u64);
+        auth_cookie::set_oidc_state(&config.auth_cookie, cookies, &state, expiry);
+    }
+    eresp.map(Json).map_err(poem::Error::from)
+}
+
+#[handler]
+pub async fn oidc_login_with_cookies(
+    req: &poem::Request,
+    cookies: &CookieJar,
+    i: Json<OidcLoginReq>,
+) -> poem::Result<Json<OidcLoginResp>> {
+    let def = ApiRequests::def_oidc_login();
+    let ctx: ReqContext = get_adl_request_context(req, &def.security).await?;
+    let config = ctx.state.config.clone();
+    let browser_state = auth_cookie::get_oidc_state(&config.auth_cookie, cookies);
+    auth_cookie::remove_oidc_state(&config.auth_cookie, cookies);
+    let eresp = with_timeout(
+        request_timeout(req, &def),
+        oidc_login(ctx, i.0, browser_state),
+    )
+    .await;
+    if let Ok(OidcLoginResp::Tokens(tokens)) = &eresp {
+        add_refresh_cookie(&config, cookies, tokens);
+    }
+    eresp.map(Json).map_err(poem::Error::from)
+}
+
+#[handler]
+pub async fn signup_with_cookies(
+    req: &poem::Request,
+    cookies: &CookieJar,
+    i: Json<SignupReq>,
+) -> poem::Result<Json<SignupResp>> {
+    let def = ApiRequests::def_signup();
+    let ctx: ReqContext = get_adl_request_context(req, &def.security).await?;
+    let config = ctx.state.config.clone();
+    let eresp = with_timeout(request_timeout(req, &def), signup(ctx, i.0)).await;
+    if let Ok(SignupResp::Tokens(tokens)) = &eresp {
+        add_refresh_cookie(&config, cookies, tokens);
     }
     eresp.map(Json).map_err(poem::Error::from)
 }
@@ -167,15 +1390,26 @@ pub async fn login_with_cookies(
 pub async fn refresh_with_cookies(
     req: &poem::Request,
     cookies: &CookieJar,
-    mut i: Json<RefreshReq>,
+    i: Json<RefreshReq>,
 ) -> poem::Result<Json<RefreshResp>> {
-    let ctx = get_adl_request_context(req, &ApiRequests::def_refresh().security)?;
-    let token_from_cookie = cookies.get(REFRESH_TOKEN).map(|c| c.value_str().to_owned());
+    let def = ApiRequests::def_refresh();
+    let ctx: ReqContext = get_adl_request_context(req, &def.security).await?;
+    let config = ctx.state.config.clone();
 
     // If there's no refresh token in the request, use the one from the cookie
-    let refresh_token = i.0.refresh_token.or(token_from_cookie);
+    let refresh_token = match i.0.refresh_token {
+        Some(token) => Some(token),
+        None => refresh_token_from_cookie(&config, req, cookies)?,
+    };
 
-    let eresp = refresh(ctx, RefreshReq { refresh_token }).await;
+    let eresp = with_timeout(
+        request_timeout(req, &def),
+        refresh(ctx, RefreshReq { refresh_token }),
+    )
+    .await;
+    if let Ok(RefreshResp::Tokens(tokens)) = &eresp {
+        add_refresh_cookie(&config, cookies, tokens);
+    }
     eresp.map(Json).map_err(poem::Error::from)
 }
 
@@ -183,29 +1417,408 @@ pub async fn refresh_with_cookies(
 pub async fn logout_with_cookies(
     req: &poem::Request,
     cookies: &CookieJar,
-    mut i: Json<Unit>,
+    i: Json<LogoutReq>,
 ) -> poem::Result<Json<Unit>> {
-    let ctx = get_adl_request_context(req, &ApiRequests::def_refresh().security)?;
-    cookies.remove(REFRESH_TOKEN);
-    let eresp = logout(ctx, i.0).await;
+    let def = ApiRequests::def_logout();
+    let ctx: ReqContext = get_adl_request_context(req, &def.security).await?;
+    let config = ctx.state.config.clone();
+
+    // If there's no refresh token in the request, use the one from the cookie
+    let refresh_token = match i.0.refresh_token {
+        Some(token) => Some(token),
+        None => refresh_token_from_cookie(&config, req, cookies)?,
+    };
+    auth_cookie::remove_refresh_token(&config.auth_cookie, cookies);
+
+    let eresp = with_timeout(
+        request_timeout(req, &def),
+        logout(ctx, LogoutReq { refresh_token }),
+    )
+    .await;
     eresp.map(Json).map_err(poem::Error::from)
 }
 
-const REFRESH_TOKEN: &str = "refreshToken";
+/**
+ * The refresh token from the request's cookie, provided that the request
+ * passes the CSRF checks
+ */
+fn refresh_token_from_cookie(
+    config: &ServerConfig,
+    req: &poem::Request,
+    cookies: &CookieJar,
+) -> poem::Result<Option<String>> {
+    match auth_cookie::get_refresh_token(&config.auth_cookie, cookies) {
+        Some(token) => {
+            csrf::check_request(config, req)?;
+            Ok(Some(token))
+        }
+        None => Ok(None),
+    }
+}
 
-fn access_jwt_from_user(cfg: &ServerConfig, user_id: &AppUserId, user: &AppUser) -> String {
-    if user.is_admin {
-        jwt::create_admin_access(cfg, user_id.0.clone())
-    } else {
-        jwt::create_user_access(cfg, user_id.0.clone())
+fn add_refresh_cookie(config: &ServerConfig, cookies: &CookieJar, tokens: &LoginTokens) {
+    auth_cookie::set_refresh_token(config, cookies, &tokens.refresh_jwt);
+}
+
+/**
+ * Create a refresh token in the db, and the jwt that references it. If no family
+ * is given, then a new one is started.
+ */
+async fn create_refresh_jwt(
+    state: &AppState,
+    user_id: &AppUserId,
+    family: Option<String>,
+) -> HandlerResult<String> {
+    let expiry = Duration::from_secs(state.config.jwt_refresh_expiry_secs as u64);
+    let token_id = db::create_refresh_token(&state.db_pool, user_id, family, expiry).await?;
+    Ok(jwt::create_refresh(
+        &state.config,
+        user_id.0.clone(),
+        token_id.0,
+    ))
+}
+
+fn refresh_token_id(state: &AppState, refresh_jwt: &Option<String>) -> Option<RefreshTokenId> {
+    let refresh_jwt = refresh_jwt.as_ref()?;
+    match jwt::decode_refresh(&state.config, refresh_jwt) {
+        Ok(claims) => Some(DbKey::from_string(claims.jti)),
+        Err(failure) => {
+            state.jwt_failures.record_refresh(failure);
+            None
+        }
+    }
+}
+
+/**
+ * Start a new session for a user, from the client making the request
+ */
+async fn login_tokens(ctx: &ReqContext, user_id: &AppUserId) -> HandlerResult<LoginTokens> {
+    let state = &ctx.state;
+    let organisation_id = db::default_organisation(&state.db_pool, user_id).await?;
+    let session_id = db::create_session(
+        &state.db_pool,
+        user_id,
+        &ctx.user_agent,
+        &ctx.client_ip,
+        &organisation_id,
+    )
+    .await?;
+    let access_jwt = access_jwt_from_user(state, user_id, &session_id).await?;
+    let refresh_jwt = create_refresh_jwt(state, user_id, Some(session_id.0)).await?;
+    Ok(LoginTokens {
+        access_jwt,
+        refresh_jwt,
+    })
+}
+
+/**
+ * Create an access token carrying the user's current roles, including
+ * those they hold within the session's organisation
+ */
+async fn access_jwt_from_user(
+    state: &AppState,
+    user_id: &AppUserId,
+    session_id: &SessionId,
+) -> HandlerResult<String> {
+    let pool = &state.db_pool;
+    let mut roles = db::user_role_names(pool, &[user_id.clone()])
+        .await?
+        .remove(&user_id.0)
+        .unwrap_or_default();
+
+    // A user removed from the session's organisation no longer acts
+    // within it
+    let session = db::get_session(pool, session_id).await?;
+    let mut org = None;
+    if let Some(organisation_id) = session.and_then(|s| s.organisation_id) {
+        if let Some(org_roles) =
+            organisation_roles(state, user_id, &roles, &organisation_id).await?
+        {
+            for role in org_roles {
+                if !roles.contains(&role) {
+                    roles.push(role);
+                }
+            }
+            org = Some(organisation_id.0);
+        }
     }
+    let sub = user_id.0.clone();
+    let sid = Some(session_id.0.clone());
+    Ok(jwt::create_access(
+        &state.config,
+        &state.access_keys,
+        sub,
+        roles,
+        sid,
+        org,
+    ))
+}
+
+/**
+ * The names of the roles a user holds within an organisation, or None if
+ * they can't act within it. Admins can act within any organisation.
+ */
+async fn organisation_roles(
+    state: &AppState,
+    user_id: &AppUserId,
+    user_roles: &[String],
+    organisation_id: &OrganisationId,
+) -> HandlerResult<Option<Vec<String>>> {
+    let pool = &state.db_pool;
+    match db::get_organisation_member(pool, organisation_id, user_id).await? {
+        Some(member) => Ok(Some(db::role_names(pool, &member.roles).await?)),
+        None if user_roles.iter().any(|r| r == jwt::ROLE_ADMIN) => Ok(Some(vec![])),
+        None => Ok(None),
+    }
+}
+
+/**
+ * Failed logins are tracked against the email address as entered, so
+ * normalise it to stop trivial variations getting a fresh allowance
+ */
+fn login_failure_key(email: &str) -> String {
+    normalise_email(email)
+}
+
+fn normalise_email(email: &str) -> String {
+    email.trim().to_lowercase()
+}
+
+/**
+ * If logins for an address are currently refused, the number of seconds
+ * until they will be accepted again
+ */
+async fn login_retry_after(state: &AppState, failure_key: &String) -> HandlerResult<Option<u32>> {
+    let failure = match db::get_login_failure(&state.db_pool, failure_key).await? {
+        Some(failure) => failure,
+        None => return Ok(None),
+    };
+    let cfg = &state.config;
+    let mut retry_at = failure.locked_until.map(|t| t.0);
+    if failure.failed_count >= cfg.login_backoff_after_failures {
+        // Each failure beyond the threshold doubles the delay
+        let doublings = failure.failed_count - cfg.login_backoff_after_failures;
+        let delay_secs = 2u32
+            .checked_pow(doublings)
+            .and_then(|m| m.checked_mul(cfg.login_backoff_base_secs))
+            .map_or(cfg.login_backoff_max_secs, |d| {
+                d.min(cfg.login_backoff_max_secs)
+            });
+        let backoff_until = failure.last_failed_at.0 + Duration::from_secs(delay_secs as u64);
+        retry_at = retry_at.max(Some(backoff_until));
+    }
+    let remaining = retry_at.and_then(|t| t.duration_since(SystemTime::now()).ok());
+    Ok(remaining.map(|d| d.as_millis().div_ceil(1000) as u32))
+}
+
+async fn record_login_failure(state: &AppState, failure_key: &String) -> HandlerResult<()> {
+    let cfg = &state.config;
+    let lockout = Duration::from_secs(cfg.login_lockout_secs as u64);
+    // Failures are forgotten once they can no longer delay a login, which
+    // also restarts the count for an address that has been quiet as long
+    let forget_after = lockout.max(Duration::from_secs(cfg.login_backoff_max_secs as u64));
+    db::delete_stale_login_failures(&state.db_pool, forget_after).await?;
+    if db::record_login_failure(
+        &state.db_pool,
+        failure_key,
+        cfg.login_lockout_failures,
+        lockout,
+    )
+    .await?
+    {
+        log::warn!(
+            "logins for {} locked for {}s after {} consecutive failures",
+            failure_key,
+            cfg.login_lockout_secs,
+            cfg.login_lockout_failures
+        );
+    }
+    Ok(())
+}
+
+/**
+ * Check a login password, treating a corrupt stored hash as a mismatch
+ */
+fn password_matches(
+    state: &AppState,
+    user_id: &AppUserId,
+    password: &str,
+    hashed_password: &Option<String>,
+) -> bool {
+    // Users created by an OpenID Connect login have no password, but take
+    // as long to reject as everyone else
+    let hashed_password = match hashed_password {
+        Some(hashed_password) => hashed_password,
+        None => {
+            let _ = verify_password(password, &state.dummy_password_hash);
+            return false;
+        }
+    };
+    match verify_password(password, hashed_password) {
+        Ok(matches) => matches,
+        Err(e) => {
+            log::error!("unable to check password for user {}: {}", user_id.0, e);
+            false
+        }
+    }
+}
+
+/**
+ * Upgrade a user's password hash if it was made with other than the
+ * configured Argon2 parameters, now that we have the password to do so
+ */
+async fn rehash_password_if_needed(
+    state: &AppState,
+    user_id: &AppUserId,
+    password: &str,
+    user: &AppUser,
+) -> HandlerResult<()> {
+    let needs_rehash = user
+        .hashed_password
+        .as_ref()
+        .is_some_and(|h| needs_rehash(&state.config.argon2, h));
+    if needs_rehash {
+        let hashed_password =
+            hash_password(&state.config.argon2, password).expect("password can be hashed");
+        db::update_user_password(&state.db_pool, user_id, &hashed_password).await?;
+        log::info!("upgraded password hash for user {}", user_id.0);
+    }
+    Ok(())
+}
+
+/**
+ * The user for an account at the OpenID Connect provider. On its first
+ * login an account is linked to the user with its verified email address,
+ * if the config allows, or a user is created for it if the config allows.
+ */
+async fn oidc_user(
+    state: &AppState,
+    cfg: &OidcConfig,
+    claims: &oidc::IdTokenClaims,
+) -> HandlerResult<Option<(AppUserId, AppUser)>> {
+    let pool = &state.db_pool;
+    if let Some(user_id) = db::get_oidc_identity_user(pool, &claims.iss, &claims.sub).await? {
+        return Ok(db::get_user_with_id(pool, &user_id).await?);
+    }
+    let email = match claims.verified_email() {
+        Some(email) => email,
+        None => return Ok(None),
+    };
+    let user_id = match db::get_user_with_email(pool, email).await? {
+        Some((user_id, _)) => {
+            if !cfg.link_existing_users {
+                return Ok(None);
+            }
+            let roles = db::user_role_names(pool, &[user_id.clone()]).await?;
+            if roles.contains_key(&user_id.0) {
+                log::warn!(
+                    "not linking oidc subject {} to user {} with roles",
+                    claims.sub,
+                    user_id.0
+                );
+                return Ok(None);
+            }
+            user_id
+        }
+        None if cfg.create_users => {
+            let user = AppUser {
+                fullname: claims
+                    .name
+                    .clone()
+                    .filter(|name| !name.trim().is_empty())
+                    .unwrap_or_else(|| email.clone()),
+                email: email.clone(),
+                hashed_password: None,
+                verified_at: None,
+                verification_sent_at: None,
+                deactivated_at: None,
+            };
+            let user_id = db::create_user(pool, &user).await?;
+            log::info!("created user {} for oidc subject {}", user_id.0, claims.sub);
+            user_id
+        }
+        None => return Ok(None),
+    };
+    // The provider has verified the email address
+    db::set_user_verified(pool, &user_id).await?;
+    db::create_oidc_identity(pool, &user_id, &claims.iss, &claims.sub).await?;
+    Ok(db::get_user_with_id(pool, &user_id).await?)
+}
+
+fn email_verified(state: &AppState, user: &AppUser) -> bool {
+    user.verified_at.is_some() || !state.config.require_verified_email
+}
+
+/**
+ * Email a link to verify the user's email address, unless the address is
+ * already verified or a link was sent recently.
+ */
+async fn send_verification_email(
+    state: &AppState,
+    user_id: &AppUserId,
+    user: &AppUser,
+) -> HandlerResult<()> {
+    if user.verified_at.is_some() {
+        return Ok(());
+    }
+    let resend_interval = Duration::from_secs(state.config.email_verification_resend_secs as u64);
+    if !db::record_verification_sent(&state.db_pool, user_id, resend_interval).await? {
+        log::info!("not resending verification email to {}", user_id.0);
+        return Ok(());
+    }
+    let token =
+        jwt::create_email_verification(&state.config, user_id.0.clone(), user.email.clone());
+    let email = Email {
+        to: user.email.clone(),
+        subject: "Verify your email address".to_owned(),
+        body: format!(
+            "Hi {},\n\n\
+             To verify your email address, follow this link:\n\n\
+             {}/verify-email?token={}\n",
+            user.fullname, state.config.app_url, token
+        ),
+    };
+    if let Err(e) = state.mailer.send(email).await {
+        log::error!("failed to send verification email to {}: {}", user_id.0, e);
+    }
+    Ok(())
+}
+
+/**
+ * The session that the request's access token was issued for. Requests
+ * made with an API key have none.
+ */
+fn current_session(oclaims: &Option<AccessClaims>) -> Option<SessionId> {
+    let sid = oclaims.as_ref()?.sid.as_ref()?;
+    Some(DbKey::from_string(sid.clone()))
+}
+
+/**
+ * The organisation that the request's access token acts within, to which
+ * messages and users are scoped
+ */
+fn organisation_from_claims(oclaims: &Option<AccessClaims>) -> Option<OrganisationId> {
+    let org = oclaims.as_ref()?.org.as_ref()?;
+    Some(DbKey::from_string(org.clone()))
 }
 
 fn user_from_claims(oclaims: &Option<AccessClaims>) -> HandlerResult<AppUserId> {
     if let Some(claims) = oclaims {
-        if claims.role == jwt::ROLE_USER || claims.role == jwt::ROLE_ADMIN {
+        if claims.has_role(jwt::ROLE_USER) {
             return Ok(DbKey::from_string(claims.sub.clone()));
         }
     }
     Err(forbidden())
 }
+
+/**
+ * The user making the request, refusing tokens from an admin impersonating
+ * them, so that impersonation can't be used to take over or lock out the
+ * user's account
+ */
+fn own_user_from_claims(oclaims: &Option<AccessClaims>) -> HandlerResult<AppUserId> {
+    if oclaims.as_ref().is_some_and(|c| c.act.is_some()) {
+        return Err(forbidden());
+    }
+    user_from_claims(oclaims)
+}
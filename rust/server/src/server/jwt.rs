@@ -1,62 +1,546 @@
-use jsonwebtoken::{DecodingKey, EncodingKey, Header, Validation};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use jsonwebtoken::errors::ErrorKind;
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use spki::der::{Decode, DecodePem};
+use spki::{ObjectIdentifier, SubjectPublicKeyInfoOwned};
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
+use adl::gen::protoapp::apis::ui::{Jwks, JwtFailureCount, JwtFailureCounts};
+use adl::gen::protoapp::config::server::{JwtAlgorithm, JwtKey};
+
 use super::ServerConfig;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct AccessClaims {
     pub iss: String,
+    pub aud: String,
     pub sub: String,
     pub exp: usize,
-    pub role: String,
+    pub nbf: usize,
+    pub iat: usize,
+
+    // The names of the subject's roles, which always include "user"
+    pub roles: Vec<String>,
+
+    // The session the token was issued for, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sid: Option<String>,
+
+    // The admin acting as the subject, for impersonation tokens (RFC 8693)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub act: Option<ActorClaim>,
+
+    // The organisation the subject is acting within, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub org: Option<String>,
+}
+
+impl AccessClaims {
+    pub fn has_role(&self, role: &str) -> bool {
+        self.roles.iter().any(|r| r == role)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ActorClaim {
+    pub sub: String,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct RefreshClaims {
     pub iss: String,
+    pub aud: String,
+    pub sub: String,
+    pub exp: usize,
+    pub nbf: usize,
+    pub iat: usize,
+    pub jti: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct EmailVerificationClaims {
+    pub iss: String,
+    pub aud: String,
     pub sub: String,
     pub exp: usize,
+    pub nbf: usize,
+    pub iat: usize,
+    pub email: String,
 }
 
+/**
+ * The claims of the token that lets a user who has entered their password
+ * complete their login with a second factor. It is signed with the refresh
+ * secret, and the purpose claim stops it being confused with a refresh token.
+ */
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TotpChallengeClaims {
+    pub iss: String,
+    pub aud: String,
+    pub sub: String,
+    pub exp: usize,
+    pub nbf: usize,
+    pub iat: usize,
+    pub purpose: String,
+}
+
+const PURPOSE_TOTP_CHALLENGE: &str = "totp_challenge";
+
 pub const ROLE_ADMIN: &str = "admin";
 pub const ROLE_USER: &str = "user";
 
-pub fn create_admin_access(cfg: &ServerConfig, sub: String) -> String {
-    create_access_token(cfg, ROLE_ADMIN, sub)
+/**
+ * The registered claims that every token must include. jsonwebtoken
+ * doesn't know about iat, so that is checked separately.
+ */
+const REQUIRED_CLAIMS: [&str; 5] = ["iss", "aud", "sub", "exp", "nbf"];
+
+/**
+ * The reasons a token can be rejected
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JwtFailure {
+    Malformed,
+    UnknownKey,
+    InvalidSignature,
+    Expired,
+    NotYetValid,
+    IssuedInFuture,
+    InvalidIssuer,
+    InvalidAudience,
+    MissingClaim,
+}
+
+impl JwtFailure {
+    pub const ALL: [JwtFailure; 9] = [
+        JwtFailure::Malformed,
+        JwtFailure::UnknownKey,
+        JwtFailure::InvalidSignature,
+        JwtFailure::Expired,
+        JwtFailure::NotYetValid,
+        JwtFailure::IssuedInFuture,
+        JwtFailure::InvalidIssuer,
+        JwtFailure::InvalidAudience,
+        JwtFailure::MissingClaim,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JwtFailure::Malformed => "malformed",
+            JwtFailure::UnknownKey => "unknown_key",
+            JwtFailure::InvalidSignature => "invalid_signature",
+            JwtFailure::Expired => "expired",
+            JwtFailure::NotYetValid => "not_yet_valid",
+            JwtFailure::IssuedInFuture => "issued_in_future",
+            JwtFailure::InvalidIssuer => "invalid_issuer",
+            JwtFailure::InvalidAudience => "invalid_audience",
+            JwtFailure::MissingClaim => "missing_claim",
+        }
+    }
+}
+
+impl fmt::Display for JwtFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl From<jsonwebtoken::errors::Error> for JwtFailure {
+    fn from(err: jsonwebtoken::errors::Error) -> JwtFailure {
+        match err.kind() {
+            ErrorKind::InvalidSignature => JwtFailure::InvalidSignature,
+            ErrorKind::ExpiredSignature => JwtFailure::Expired,
+            ErrorKind::ImmatureSignature => JwtFailure::NotYetValid,
+            ErrorKind::InvalidIssuer => JwtFailure::InvalidIssuer,
+            ErrorKind::InvalidAudience => JwtFailure::InvalidAudience,
+            ErrorKind::MissingRequiredClaim(_) => JwtFailure::MissingClaim,
+            ErrorKind::InvalidAlgorithm => JwtFailure::UnknownKey,
+            _ => JwtFailure::Malformed,
+        }
+    }
+}
+
+/**
+ * Counts of the tokens rejected since the server started, by reason
+ */
+#[derive(Default)]
+pub struct JwtFailures {
+    access: [AtomicU64; JwtFailure::ALL.len()],
+    refresh: [AtomicU64; JwtFailure::ALL.len()],
+}
+
+impl JwtFailures {
+    pub fn record_access(&self, failure: JwtFailure) {
+        log::error!("rejected access token: {}", failure);
+        self.access[failure as usize].fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_refresh(&self, failure: JwtFailure) {
+        log::error!("rejected refresh token: {}", failure);
+        self.refresh[failure as usize].fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn counts(&self) -> JwtFailureCounts {
+        JwtFailureCounts {
+            access: failure_counts(&self.access),
+            refresh: failure_counts(&self.refresh),
+        }
+    }
+}
+
+fn failure_counts(counts: &[AtomicU64; JwtFailure::ALL.len()]) -> Vec<JwtFailureCount> {
+    JwtFailure::ALL
+        .iter()
+        .map(|f| JwtFailureCount {
+            reason: f.as_str().to_owned(),
+            count: counts[*f as usize].load(Ordering::Relaxed),
+        })
+        .collect()
+}
+
+/**
+ * The checks applied to the registered claims of a token
+ */
+struct ClaimValidation {
+    issuer: String,
+    audiences: Vec<String>,
+    leeway_secs: u64,
+}
+
+impl ClaimValidation {
+    /**
+     * Verify and decode a token. The claims are validated before they are
+     * deserialized, so that a missing claim is reported as such, rather than
+     * as a malformed token.
+     */
+    fn decode<T: DeserializeOwned>(
+        &self,
+        jwt: &str,
+        key: &DecodingKey,
+        algorithm: Algorithm,
+    ) -> Result<T, JwtFailure> {
+        let mut validation = Validation::new(algorithm);
+        validation.set_issuer(&[&self.issuer]);
+        validation.set_audience(&self.audiences);
+        validation.set_required_spec_claims(&REQUIRED_CLAIMS);
+        validation.validate_nbf = true;
+        validation.leeway = self.leeway_secs;
+        let token = jsonwebtoken::decode::<serde_json::Value>(jwt, key, &validation)?;
+
+        let iat = token
+            .claims
+            .get("iat")
+            .and_then(|iat| iat.as_u64())
+            .ok_or(JwtFailure::MissingClaim)?;
+        if iat > now_secs() + self.leeway_secs {
+            return Err(JwtFailure::IssuedInFuture);
+        }
+
+        serde_json::from_value(token.claims).map_err(|_| JwtFailure::Malformed)
+    }
+}
+
+/**
+ * The keys used to sign and verify access tokens
+ */
+pub struct AccessKeys {
+    header: Header,
+    encoding_key: EncodingKey,
+    decoding_keys: Vec<AccessDecodingKey>,
+    jwks: Jwks,
+    claims: ClaimValidation,
+}
+
+struct AccessDecodingKey {
+    kid: Option<String>,
+    algorithm: Algorithm,
+    key: DecodingKey,
+}
+
+impl AccessKeys {
+    /**
+     * Load the access token keys specified in the server config
+     */
+    pub fn from_config(cfg: &ServerConfig) -> anyhow::Result<AccessKeys> {
+        let mut decoding_keys = Vec::new();
+        let mut jwks = Jwks { keys: Vec::new() };
+
+        let (header, encoding_key) = match &cfg.jwt_access_signing_key {
+            None => {
+                if cfg.jwt_access_secret.is_empty() {
+                    return Err(anyhow::anyhow!(
+                        "one of jwt_access_secret or jwt_access_signing_key is required"
+                    ));
+                }
+                let secret = cfg.jwt_access_secret.as_bytes();
+                decoding_keys.push(AccessDecodingKey {
+                    kid: None,
+                    algorithm: Algorithm::HS256,
+                    key: DecodingKey::from_secret(secret),
+                });
+                (Header::default(), EncodingKey::from_secret(secret))
+            }
+            Some(signing_key) => {
+                let private_key_pem_file =
+                    signing_key
+                        .private_key_pem_file
+                        .as_ref()
+                        .ok_or(anyhow::anyhow!(
+                            "jwt_access_signing_key requires a private_key_pem_file"
+                        ))?;
+                let pem = read_pem_file(private_key_pem_file)?;
+                let encoding_key = match signing_key.algorithm {
+                    JwtAlgorithm::Rs256 => EncodingKey::from_rsa_pem(pem.as_bytes()),
+                    JwtAlgorithm::Eddsa => EncodingKey::from_ed_pem(pem.as_bytes()),
+                }
+                .map_err(|e| {
+                    anyhow::anyhow!("invalid private key in {}: {}", private_key_pem_file, e)
+                })?;
+                let mut header = Header::new(algorithm(&signing_key.algorithm));
+                header.kid = Some(signing_key.kid.clone());
+                (header, encoding_key)
+            }
+        };
+
+        let verification_keys = cfg
+            .jwt_access_signing_key
+            .iter()
+            .chain(cfg.jwt_access_verification_keys.iter());
+        for key in verification_keys {
+            let pem = read_pem_file(&key.public_key_pem_file)?;
+            let decoding_key = match key.algorithm {
+                JwtAlgorithm::Rs256 => DecodingKey::from_rsa_pem(pem.as_bytes()),
+                JwtAlgorithm::Eddsa => DecodingKey::from_ed_pem(pem.as_bytes()),
+            }
+            .map_err(|e| {
+                anyhow::anyhow!("invalid public key in {}: {}", key.public_key_pem_file, e)
+            })?;
+            decoding_keys.push(AccessDecodingKey {
+                kid: Some(key.kid.clone()),
+                algorithm: algorithm(&key.algorithm),
+                key: decoding_key,
+            });
+            jwks.keys.push(public_jwk(key, &pem).map_err(|e| {
+                anyhow::anyhow!("invalid public key in {}: {}", key.public_key_pem_file, e)
+            })?);
+        }
+
+        let mut audiences = vec![cfg.jwt_audience.clone()];
+        audiences.extend(cfg.jwt_accepted_audiences.iter().cloned());
+        let claims = ClaimValidation {
+            issuer: cfg.jwt_issuer.clone(),
+            audiences,
+            leeway_secs: cfg.jwt_leeway_secs as u64,
+        };
+
+        Ok(AccessKeys {
+            header,
+            encoding_key,
+            decoding_keys,
+            jwks,
+            claims,
+        })
+    }
+
+    /**
+     * The public keys that can verify access tokens
+     */
+    pub fn jwks(&self) -> &Jwks {
+        &self.jwks
+    }
+
+    fn decoding_key(&self, kid: &Option<String>) -> Option<&AccessDecodingKey> {
+        self.decoding_keys.iter().find(|k| k.kid == *kid)
+    }
+}
+
+pub(crate) fn algorithm(algorithm: &JwtAlgorithm) -> Algorithm {
+    match algorithm {
+        JwtAlgorithm::Rs256 => Algorithm::RS256,
+        JwtAlgorithm::Eddsa => Algorithm::EdDSA,
+    }
+}
+
+fn read_pem_file(path: &str) -> anyhow::Result<String> {
+    std::fs::read_to_string(path).map_err(|e| anyhow::anyhow!("unable to read {}: {}", path, e))
+}
+
+const OID_RSA_ENCRYPTION: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.1.1");
+const OID_ED25519: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.101.112");
+
+/**
+ * Construct the JSON web key (RFC 7517) for a public key in SPKI PEM format
+ */
+fn public_jwk(key: &JwtKey, pem: &str) -> anyhow::Result<serde_json::Value> {
+    let spki = SubjectPublicKeyInfoOwned::from_pem(pem).map_err(|e| anyhow::anyhow!("{}", e))?;
+    let public_key = spki
+        .subject_public_key
+        .as_bytes()
+        .ok_or(anyhow::anyhow!("unexpected public key encoding"))?;
+    let jwk = match key.algorithm {
+        JwtAlgorithm::Rs256 => {
+            if spki.algorithm.oid != OID_RSA_ENCRYPTION {
+                return Err(anyhow::anyhow!("expected an RSA public key"));
+            }
+            let rsa_key =
+                pkcs1::RsaPublicKey::from_der(public_key).map_err(|e| anyhow::anyhow!("{}", e))?;
+            serde_json::json!({
+                "kty": "RSA",
+                "alg": "RS256",
+                "n": URL_SAFE_NO_PAD.encode(rsa_key.modulus.as_bytes()),
+                "e": URL_SAFE_NO_PAD.encode(rsa_key.public_exponent.as_bytes()),
+            })
+        }
+        JwtAlgorithm::Eddsa => {
+            if spki.algorithm.oid != OID_ED25519 {
+                return Err(anyhow::anyhow!("expected an Ed25519 public key"));
+            }
+            serde_json::json!({
+                "kty": "OKP",
+                "alg": "EdDSA",
+                "crv": "Ed25519",
+                "x": URL_SAFE_NO_PAD.encode(public_key),
+            })
+        }
+    };
+    let mut jwk = jwk;
+    jwk["kid"] = serde_json::Value::String(key.kid.clone());
+    jwk["use"] = serde_json::Value::String("sig".to_owned());
+    Ok(jwk)
 }
 
-pub fn create_user_access(cfg: &ServerConfig, sub: String) -> String {
-    create_access_token(cfg, ROLE_USER, sub)
+/**
+ * Create an access token for a user with the given roles, in addition to
+ * the user role that every user has
+ */
+pub fn create_access(
+    cfg: &ServerConfig,
+    keys: &AccessKeys,
+    sub: String,
+    roles: Vec<String>,
+    sid: Option<String>,
+    org: Option<String>,
+) -> String {
+    let now = now_secs() as usize;
+    let exp = calc_access_exp(cfg.jwt_access_expiry_secs as u64);
+
+    let claims = AccessClaims {
+        iss: cfg.jwt_issuer.clone(),
+        aud: cfg.jwt_audience.clone(),
+        sub,
+        exp,
+        nbf: now,
+        iat: now,
+        roles: with_user_role(roles),
+        sid,
+        act: None,
+        org,
+    };
+
+    jsonwebtoken::encode(&keys.header, &claims, &keys.encoding_key)
+        .expect("jwt encode should succeed")
 }
 
-pub fn create_refresh(cfg: &ServerConfig, sub: String) -> String {
+/**
+ * Add the user role to a list of role names, if it's not already there
+ */
+pub fn with_user_role(mut roles: Vec<String>) -> Vec<String> {
+    if !roles.iter().any(|r| r == ROLE_USER) {
+        roles.insert(0, ROLE_USER.to_owned());
+    }
+    roles
+}
+
+pub fn create_refresh(cfg: &ServerConfig, sub: String, jti: String) -> String {
+    let now = now_secs() as usize;
     let exp = calc_access_exp(cfg.jwt_refresh_expiry_secs as u64);
 
     let claims = RefreshClaims {
         iss: cfg.jwt_issuer.clone(),
+        aud: cfg.jwt_audience.clone(),
         sub,
         exp,
+        nbf: now,
+        iat: now,
+        jti,
     };
 
     let key = EncodingKey::from_secret(cfg.jwt_refresh_secret.as_bytes());
     jsonwebtoken::encode(&Header::default(), &claims, &key).expect("jwt encode should succeed")
 }
 
-fn create_access_token(cfg: &ServerConfig, role: &str, sub: String) -> String {
-    let exp = calc_access_exp(cfg.jwt_access_expiry_secs as u64);
+/**
+ * Create the token for a link that verifies a user's email address
+ */
+pub fn create_email_verification(cfg: &ServerConfig, sub: String, email: String) -> String {
+    let now = now_secs() as usize;
+    let exp = calc_access_exp(cfg.email_verification_expiry_secs as u64);
 
-    let claims = AccessClaims {
+    let claims = EmailVerificationClaims {
         iss: cfg.jwt_issuer.clone(),
+        aud: cfg.jwt_audience.clone(),
         sub,
         exp,
-        role: role.to_owned(),
+        nbf: now,
+        iat: now,
+        email,
     };
 
-    let key = EncodingKey::from_secret(cfg.jwt_access_secret.as_bytes());
+    let key = EncodingKey::from_secret(cfg.email_verification_secret.as_bytes());
     jsonwebtoken::encode(&Header::default(), &claims, &key).expect("jwt encode should succeed")
 }
 
+pub fn create_totp_challenge(cfg: &ServerConfig, sub: String) -> String {
+    let now = now_secs() as usize;
+    let exp = calc_access_exp(cfg.totp_challenge_expiry_secs as u64);
+
+    let claims = TotpChallengeClaims {
+        iss: cfg.jwt_issuer.clone(),
+        aud: cfg.jwt_audience.clone(),
+        sub,
+        exp,
+        nbf: now,
+        iat: now,
+        purpose: PURPOSE_TOTP_CHALLENGE.to_owned(),
+    };
+
+    let key = EncodingKey::from_secret(cfg.jwt_refresh_secret.as_bytes());
+    jsonwebtoken::encode(&Header::default(), &claims, &key).expect("jwt encode should succeed")
+}
+
+/**
+ * Create a token with which an admin can act as another user. The token
+ * only ever has the user role, and can't be refreshed.
+ */
+pub fn create_impersonation_access(
+    cfg: &ServerConfig,
+    keys: &AccessKeys,
+    sub: String,
+    actor: String,
+    org: Option<String>,
+) -> String {
+    let now = now_secs() as usize;
+    let claims = AccessClaims {
+        iss: cfg.jwt_issuer.clone(),
+        aud: cfg.jwt_audience.clone(),
+        sub,
+        exp: calc_access_exp(cfg.jwt_impersonation_expiry_secs as u64),
+        nbf: now,
+        iat: now,
+        roles: vec![ROLE_USER.to_owned()],
+        sid: None,
+        act: Some(ActorClaim { sub: actor }),
+        org,
+    };
+    jsonwebtoken::encode(&keys.header, &claims, &keys.encoding_key)
+        .expect("jwt encode should succeed")
+}
+
 fn calc_access_exp(expiry_secs: u64) -> usize {
     SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -66,16 +550,61 @@ fn calc_access_exp(expiry_secs: u64) -> usize {
         .as_secs() as usize
 }
 
-pub fn decode_access(jwt_secret: &str, jwt: &str) -> anyhow::Result<AccessClaims> {
-    let key = DecodingKey::from_secret(jwt_secret.as_bytes());
-    let token = jsonwebtoken::decode::<AccessClaims>(jwt, &key, &Validation::default())?;
-    Ok(token.claims)
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Time went backwards")
+        .as_secs()
+}
+
+pub fn decode_access(keys: &AccessKeys, jwt: &str) -> Result<AccessClaims, JwtFailure> {
+    let header = jsonwebtoken::decode_header(jwt)?;
+    let key = keys
+        .decoding_key(&header.kid)
+        .ok_or(JwtFailure::UnknownKey)?;
+    keys.claims.decode(jwt, &key.key, key.algorithm)
 }
 
-pub fn decode_refresh(jwt_secret: &str, jwt: &str) -> anyhow::Result<RefreshClaims> {
-    let key = DecodingKey::from_secret(jwt_secret.as_bytes());
-    let token = jsonwebtoken::decode::<RefreshClaims>(jwt, &key, &Validation::default())?;
-    Ok(token.claims)
+pub fn decode_refresh(cfg: &ServerConfig, jwt: &str) -> Result<RefreshClaims, JwtFailure> {
+    // Refresh tokens are only ever consumed by this server, so
+    // other audiences aren't accepted.
+    let claims = ClaimValidation {
+        issuer: cfg.jwt_issuer.clone(),
+        audiences: vec![cfg.jwt_audience.clone()],
+        leeway_secs: cfg.jwt_leeway_secs as u64,
+    };
+    let key = DecodingKey::from_secret(cfg.jwt_refresh_secret.as_bytes());
+    claims.decode(jwt, &key, Algorithm::HS256)
+}
+
+pub fn decode_email_verification(
+    cfg: &ServerConfig,
+    jwt: &str,
+) -> Result<EmailVerificationClaims, JwtFailure> {
+    let claims = ClaimValidation {
+        issuer: cfg.jwt_issuer.clone(),
+        audiences: vec![cfg.jwt_audience.clone()],
+        leeway_secs: cfg.jwt_leeway_secs as u64,
+    };
+    let key = DecodingKey::from_secret(cfg.email_verification_secret.as_bytes());
+    claims.decode(jwt, &key, Algorithm::HS256)
+}
+
+pub fn decode_totp_challenge(
+    cfg: &ServerConfig,
+    jwt: &str,
+) -> Result<TotpChallengeClaims, JwtFailure> {
+    let claims = ClaimValidation {
+        issuer: cfg.jwt_issuer.clone(),
+        audiences: vec![cfg.jwt_audience.clone()],
+        leeway_secs: cfg.jwt_leeway_secs as u64,
+    };
+    let key = DecodingKey::from_secret(cfg.jwt_refresh_secret.as_bytes());
+    let claims: TotpChallengeClaims = claims.decode(jwt, &key, Algorithm::HS256)?;
+    if claims.purpose != PURPOSE_TOTP_CHALLENGE {
+        return Err(JwtFailure::Malformed);
+    }
+    Ok(claims)
 }
 
 pub fn bearer_token_from_auth_header(auth_header: &str) -> Option<String> {
@@ -0,0 +1,128 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use lettre::message::header::ContentType;
+use lettre::message::Mailbox;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use serde::{Deserialize, Serialize};
+use tokio::io::AsyncWriteExt;
+
+use adl::gen::protoapp::config::server::{MailerConfig, ServerConfig, SmtpConfig, SmtpSecurity};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Email {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+/**
+ * Delivers emails on behalf of the server
+ */
+pub trait Mailer {
+    fn send(&self, email: Email) -> Pin<Box<dyn Future<Output = anyhow::Result<()>> + Send + '_>>;
+}
+
+pub type DynMailer = Arc<Box<dyn Mailer + Send + Sync>>;
+
+/**
+ * Construct the mailer specified in the server config
+ */
+pub fn new_mailer(cfg: &ServerConfig) -> anyhow::Result<DynMailer> {
+    let mailer: Box<dyn Mailer + Send + Sync> = match &cfg.mailer {
+        MailerConfig::Log => Box::new(LocalMailer { file: None }),
+        MailerConfig::File(file) => Box::new(LocalMailer {
+            file: Some(file.clone()),
+        }),
+        MailerConfig::Smtp(smtp) => Box::new(SmtpMailer::new(smtp, &cfg.mail_from)?),
+    };
+    Ok(Arc::new(mailer))
+}
+
+/**
+ * A mailer for local development and tests, that writes emails to the
+ * log, or appends them to a file
+ */
+struct LocalMailer {
+    file: Option<String>,
+}
+
+impl Mailer for LocalMailer {
+    fn send(&self, email: Email) -> Pin<Box<dyn Future<Output = anyhow::Result<()>> + Send + '_>> {
+        Box::pin(async move {
+            match &self.file {
+                None => {
+                    log::info!(
+                        "email to {}\nSubject: {}\n\n{}",
+                        email.to,
+                        email.subject,
+                        email.body
+                    );
+                }
+                Some(file) => {
+                    let mut line = serde_json::to_string(&email)?;
+                    line.push('\n');
+                    let mut f = tokio::fs::OpenOptions::new()
+                        .create(true)
+                        .append(true)
+                        .open(file)
+                        .await?;
+                    f.write_all(line.as_bytes()).await?;
+                    // Writes complete in the background unless flushed
+                    f.flush().await?;
+                }
+            }
+            Ok(())
+        })
+    }
+}
+
+struct SmtpMailer {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+impl SmtpMailer {
+    fn new(cfg: &SmtpConfig, from: &str) -> anyhow::Result<SmtpMailer> {
+        let builder = match cfg.security {
+            SmtpSecurity::None => {
+                AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&cfg.host)
+            }
+            SmtpSecurity::Starttls => {
+                AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&cfg.host)?
+            }
+            SmtpSecurity::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(&cfg.host)?,
+        };
+        let builder = builder.port(cfg.port);
+        let builder = match (&cfg.username, &cfg.password) {
+            (Some(username), Some(password)) => {
+                builder.credentials(Credentials::new(username.clone(), password.clone()))
+            }
+            _ => builder,
+        };
+        let from = from
+            .parse()
+            .map_err(|e| anyhow::anyhow!("invalid mail_from address {}: {}", from, e))?;
+        Ok(SmtpMailer {
+            transport: builder.build(),
+            from,
+        })
+    }
+}
+
+impl Mailer for SmtpMailer {
+    fn send(&self, email: Email) -> Pin<Box<dyn Future<Output = anyhow::Result<()>> + Send + '_>> {
+        Box::pin(async move {
+            let message = Message::builder()
+                .from(self.from.clone())
+                .to(email.to.parse()?)
+                .subject(email.subject)
+                .header(ContentType::TEXT_PLAIN)
+                .body(email.body)?;
+            self.transport.send(message).await?;
+            Ok(())
+        })
+    }
+}
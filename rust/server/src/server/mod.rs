@@ -2,16 +2,27 @@ use poem::listener::TcpListener;
 use routing::build_routes;
 use sqlx::{postgres::PgPoolOptions, PgPool};
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::{sync::oneshot, task::JoinHandle};
 
 use adl::gen::protoapp::config::server::ServerConfig;
 
+mod api_keys;
+mod audit;
+mod auth_cookie;
+mod csrf;
 pub mod db;
+mod deactivated_users;
 mod handlers;
 mod jwt;
+pub mod mail;
+mod oidc;
+pub mod password_policy;
 pub mod passwords;
 mod poem_adl_interop;
 mod routing;
+mod timeouts;
+mod totp;
 
 #[cfg(test)]
 pub mod tests;
@@ -20,13 +31,40 @@ pub mod tests;
 pub struct AppState {
     pub config: Arc<ServerConfig>,
     pub db_pool: Arc<PgPool>,
+    pub(crate) access_keys: Arc<jwt::AccessKeys>,
+    pub(crate) jwt_failures: Arc<jwt::JwtFailures>,
+    pub(crate) deactivated_users: Arc<deactivated_users::DeactivatedUsers>,
+    pub(crate) oidc_providers: Arc<oidc::ProviderCache>,
+    // The hash of an unknowable password, checked when there's no user's
+    // hash to check, so that logins take as long whether or not the
+    // user exists
+    pub(crate) dummy_password_hash: Arc<String>,
+    pub mailer: mail::DynMailer,
+    pub password_policy: Arc<password_policy::PasswordPolicyChecker>,
 }
 
 impl AppState {
     pub fn new(config: ServerConfig, db_pool: PgPool) -> Self {
+        let access_keys = jwt::AccessKeys::from_config(&config).expect("jwt keys should load");
+        let mailer = mail::new_mailer(&config).expect("mailer should be configured");
+        let dummy_password_hash = passwords::hash_password(&config.argon2, &passwords::new_token())
+            .expect("argon2 parameters should be valid");
+        auth_cookie::check_config(&config.auth_cookie).expect("auth cookie should be valid");
+        let password_policy = password_policy::PasswordPolicyChecker::new(&config.password_policy)
+            .expect("password policy should load");
+        let deactivated_users = deactivated_users::DeactivatedUsers::new(Duration::from_secs(
+            config.deactivated_users_cache_secs as u64,
+        ));
         AppState {
             config: Arc::new(config),
             db_pool: Arc::new(db_pool),
+            access_keys: Arc::new(access_keys),
+            jwt_failures: Arc::new(jwt::JwtFailures::default()),
+            deactivated_users: Arc::new(deactivated_users),
+            oidc_providers: Arc::new(oidc::ProviderCache::default()),
+            dummy_password_hash: Arc::new(dummy_password_hash),
+            mailer,
+            password_policy: Arc::new(password_policy),
         }
     }
 }
@@ -37,7 +75,7 @@ pub async fn run(config: ServerConfig) -> Result<(), std::io::Error> {
         "postgres://{}:{}@{}:{}/{}",
         db.user, db.password, db.host, db.port, db.dbname
     );
-    let db_pool: PgPool = PgPoolOptions::new()
+    let db_pool: PgPool = timeouts::with_statement_timeouts(PgPoolOptions::new())
         .max_connections(config.db_connection_pool_size)
         .connect(&db_connection_url)
         .await
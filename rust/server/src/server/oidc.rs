@@ -0,0 +1,336 @@
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use jsonwebtoken::jwk::{AlgorithmParameters, EllipticCurve, Jwk, JwkSet};
+use jsonwebtoken::{Algorithm, DecodingKey, Validation};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+use adl::gen::protoapp::config::server::OidcConfig;
+
+use super::jwt;
+
+/**
+ * The endpoints of an OpenID Connect provider, from its discovery document
+ */
+#[derive(Debug, Deserialize)]
+struct ProviderMetadata {
+    issuer: String,
+    authorization_endpoint: String,
+    token_endpoint: String,
+    jwks_uri: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    id_token: String,
+}
+
+/**
+ * The claims of an ID token that identify the user
+ */
+#[derive(Debug, Deserialize)]
+pub struct IdTokenClaims {
+    pub iss: String,
+    pub sub: String,
+    pub nonce: Option<String>,
+    pub email: Option<String>,
+    #[serde(default)]
+    pub email_verified: bool,
+    pub name: Option<String>,
+}
+
+impl IdTokenClaims {
+    /**
+     * The user's email address, if the provider has verified it
+     */
+    pub fn verified_email(&self) -> Option<&String> {
+        self.email.as_ref().filter(|_| self.email_verified)
+    }
+}
+
+/**
+ * The provider's discovery document and keys, cached between logins so
+ * that each doesn't make extra requests to the provider
+ */
+#[derive(Default)]
+pub struct ProviderCache {
+    metadata: RwLock<Option<Cached<ProviderMetadata>>>,
+    jwks: RwLock<Option<Cached<JwkSet>>>,
+}
+
+struct Cached<T> {
+    value: Arc<T>,
+    loaded_at: Instant,
+}
+
+impl ProviderCache {
+    fn get<T>(cached: &RwLock<Option<Cached<T>>>, ttl: Duration) -> Option<Arc<T>> {
+        match &*cached.read().unwrap() {
+            Some(cached) if cached.loaded_at.elapsed() < ttl => Some(cached.value.clone()),
+            _ => None,
+        }
+    }
+
+    fn set<T>(cached: &RwLock<Option<Cached<T>>>, value: T) -> Arc<T> {
+        let value = Arc::new(value);
+        *cached.write().unwrap() = Some(Cached {
+            value: value.clone(),
+            loaded_at: Instant::now(),
+        });
+        value
+    }
+}
+
+pub struct Provider<'a> {
+    cfg: &'a OidcConfig,
+    cache: &'a ProviderCache,
+    metadata: Arc<ProviderMetadata>,
+    http: reqwest::Client,
+}
+
+impl<'a> Provider<'a> {
+    /**
+     * The provider, from its cached discovery document if that is
+     * recent enough, otherwise fetching it
+     */
+    pub async fn discover(
+        cfg: &'a OidcConfig,
+        cache: &'a ProviderCache,
+    ) -> anyhow::Result<Provider<'a>> {
+        let http = reqwest::Client::new();
+        let ttl = Duration::from_secs(cfg.metadata_cache_secs as u64);
+        let metadata = match ProviderCache::get(&cache.metadata, ttl) {
+            Some(metadata) => metadata,
+            None => {
+                let metadata = fetch_metadata(cfg, &http).await?;
+                // The keys may have moved with the endpoints
+                *cache.jwks.write().unwrap() = None;
+                ProviderCache::set(&cache.metadata, metadata)
+            }
+        };
+        Ok(Provider {
+            cfg,
+            cache,
+            metadata,
+            http,
+        })
+    }
+
+    /**
+     * The url to send the user to, to authenticate with the provider
+     */
+    pub fn authorization_url(&self, state: &str, nonce: &str, code_verifier: &str) -> String {
+        let params = [
+            ("response_type", "code"),
+            ("client_id", &self.cfg.client_id),
+            ("redirect_uri", &self.cfg.redirect_url),
+            ("scope", &self.cfg.scopes.join(" ")),
+            ("state", state),
+            ("nonce", nonce),
+            ("code_challenge", &pkce_challenge(code_verifier)),
+            ("code_challenge_method", "S256"),
+        ];
+        let query: Vec<String> = params
+            .iter()
+            .map(|(k, v)| format!("{}={}", k, urlencoding::encode(v)))
+            .collect();
+        let separator = match self.metadata.authorization_endpoint.contains('?') {
+            true => '&',
+            false => '?',
+        };
+        format!(
+            "{}{}{}",
+            self.metadata.authorization_endpoint,
+            separator,
+            query.join("&")
+        )
+    }
+
+    /**
+     * Exchange an authorization code for an ID token, and validate it.
+     * Returns None if the provider rejects the code, or its token is
+     * invalid, and an error if the provider can't be reached.
+     */
+    pub async fn exchange_code(
+        &self,
+        code: &str,
+        code_verifier: &str,
+        nonce: &str,
+        leeway_secs: u64,
+    ) -> anyhow::Result<Option<IdTokenClaims>> {
+        let params = [
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", &self.cfg.redirect_url),
+            ("client_id", &self.cfg.client_id),
+            ("client_secret", &self.cfg.client_secret),
+            ("code_verifier", code_verifier),
+        ];
+        let resp = self
+            .http
+            .post(&self.metadata.token_endpoint)
+            .form(&params)
+            .send()
+            .await?;
+        if resp.status().is_client_error() {
+            log::info!("oidc provider rejected code: {}", resp.status());
+            return Ok(None);
+        }
+        let token: TokenResponse = resp.error_for_status()?.json().await?;
+        let claims = match self.validate_id_token(&token.id_token, leeway_secs).await? {
+            Some(claims) => claims,
+            None => return Ok(None),
+        };
+        if claims.nonce.as_deref() != Some(nonce) {
+            log::info!("rejected oidc id token: nonce mismatch");
+            return Ok(None);
+        }
+        Ok(Some(claims))
+    }
+
+    async fn validate_id_token(
+        &self,
+        id_token: &str,
+        leeway_secs: u64,
+    ) -> anyhow::Result<Option<IdTokenClaims>> {
+        let header = match jsonwebtoken::decode_header(id_token) {
+            Ok(header) => header,
+            Err(e) => {
+                log::info!("rejected oidc id token: {}", e);
+                return Ok(None);
+            }
+        };
+        let jwk = match self.find_key(&header.kid).await? {
+            Some(jwk) => jwk,
+            None => {
+                log::info!("rejected oidc id token: no key for kid {:?}", header.kid);
+                return Ok(None);
+            }
+        };
+        let key = DecodingKey::from_jwk(&jwk)?;
+
+        let mut validation = Validation::default();
+        validation.algorithms = self.key_algorithms(&jwk);
+        validation.set_issuer(&[&self.metadata.issuer]);
+        validation.set_audience(&[&self.cfg.client_id]);
+        validation.set_required_spec_claims(&["exp", "iss", "aud", "sub"]);
+        validation.leeway = leeway_secs;
+        match jsonwebtoken::decode::<IdTokenClaims>(id_token, &key, &validation) {
+            Ok(token) => Ok(Some(token.claims)),
+            Err(e) => {
+                log::info!("rejected oidc id token: {}", e);
+                Ok(None)
+            }
+        }
+    }
+
+    /**
+     * The provider's key with the given id, from the cached keys if they
+     * include it, otherwise refetching them in case the provider has
+     * rotated its keys
+     */
+    async fn find_key(&self, kid: &Option<String>) -> anyhow::Result<Option<Jwk>> {
+        let ttl = Duration::from_secs(self.cfg.metadata_cache_secs as u64);
+        if let Some(jwks) = ProviderCache::get(&self.cache.jwks, ttl) {
+            if let Some(jwk) = find_jwk(&jwks, kid) {
+                return Ok(Some(jwk.clone()));
+            }
+        }
+        let jwks: JwkSet = self
+            .http
+            .get(&self.metadata.jwks_uri)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        let jwks = ProviderCache::set(&self.cache.jwks, jwks);
+        Ok(find_jwk(&jwks, kid).cloned())
+    }
+
+    /**
+     * The algorithms that tokens signed with a key may use: the one that
+     * the key declares, or otherwise the configured ones, limited to
+     * those the key's type supports
+     */
+    fn key_algorithms(&self, jwk: &Jwk) -> Vec<Algorithm> {
+        let algorithms = match jwk.common.algorithm {
+            Some(algorithm) => vec![algorithm],
+            None => self
+                .cfg
+                .id_token_algorithms
+                .iter()
+                .map(jwt::algorithm)
+                .collect(),
+        };
+        algorithms
+            .into_iter()
+            .filter(|algorithm| key_supports(jwk, *algorithm))
+            .collect()
+    }
+}
+
+async fn fetch_metadata(
+    cfg: &OidcConfig,
+    http: &reqwest::Client,
+) -> anyhow::Result<ProviderMetadata> {
+    let issuer_url = cfg.issuer_url.trim_end_matches('/');
+    let metadata: ProviderMetadata = http
+        .get(format!("{}/.well-known/openid-configuration", issuer_url))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    if metadata.issuer.trim_end_matches('/') != issuer_url {
+        return Err(anyhow::anyhow!(
+            "oidc provider reports issuer {}, expected {}",
+            metadata.issuer,
+            cfg.issuer_url
+        ));
+    }
+    Ok(metadata)
+}
+
+fn find_jwk<'j>(jwks: &'j JwkSet, kid: &Option<String>) -> Option<&'j Jwk> {
+    match kid {
+        Some(kid) => jwks.find(kid),
+        None if jwks.keys.len() == 1 => jwks.keys.first(),
+        None => None,
+    }
+}
+
+/**
+ * Whether a key can verify signatures made with the algorithm. Only the
+ * provider's public keys can verify its tokens, never a shared secret.
+ */
+fn key_supports(jwk: &Jwk, algorithm: Algorithm) -> bool {
+    match &jwk.algorithm {
+        AlgorithmParameters::RSA(_) => matches!(
+            algorithm,
+            Algorithm::RS256
+                | Algorithm::RS384
+                | Algorithm::RS512
+                | Algorithm::PS256
+                | Algorithm::PS384
+                | Algorithm::PS512
+        ),
+        AlgorithmParameters::EllipticCurve(key) => matches!(
+            (&key.curve, algorithm),
+            (EllipticCurve::P256, Algorithm::ES256) | (EllipticCurve::P384, Algorithm::ES384)
+        ),
+        AlgorithmParameters::OctetKeyPair(key) => {
+            key.curve == EllipticCurve::Ed25519 && algorithm == Algorithm::EdDSA
+        }
+        AlgorithmParameters::OctetKey(_) => false,
+    }
+}
+
+/**
+ * The PKCE code challenge (RFC 7636) for a code verifier
+ */
+pub fn pkce_challenge(code_verifier: &str) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(code_verifier.as_bytes()))
+}
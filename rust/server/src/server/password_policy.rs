@@ -0,0 +1,87 @@
+use std::collections::HashSet;
+
+use adl::gen::protoapp::apis::ui::PasswordViolation;
+use adl::gen::protoapp::config::server::PasswordPolicy;
+
+/**
+ * Checks new passwords against the configured policy
+ */
+pub struct PasswordPolicyChecker {
+    policy: PasswordPolicy,
+    breached: HashSet<String>,
+}
+
+impl PasswordPolicyChecker {
+    pub fn new(policy: &PasswordPolicy) -> anyhow::Result<PasswordPolicyChecker> {
+        let breached = match &policy.breached_passwords_file {
+            None => HashSet::new(),
+            Some(path) => std::fs::read_to_string(path)
+                .map_err(|e| {
+                    anyhow::anyhow!("unable to read breached passwords from {}: {}", path, e)
+                })?
+                .lines()
+                .map(|line| line.trim().to_lowercase())
+                .filter(|line| !line.is_empty())
+                .collect(),
+        };
+        Ok(PasswordPolicyChecker {
+            policy: policy.clone(),
+            breached,
+        })
+    }
+
+    /**
+     * Every rule of the policy that a password for the given email address
+     * breaks. An empty result means that the password is acceptable.
+     */
+    pub fn check(&self, password: &str, email: &str) -> Vec<PasswordViolation> {
+        let policy = &self.policy;
+        let mut violations = Vec::new();
+        if (password.chars().count() as u32) < policy.min_length {
+            violations.push(PasswordViolation::TooShort(policy.min_length));
+        }
+        if policy.require_lowercase && !password.chars().any(|c| c.is_lowercase()) {
+            violations.push(PasswordViolation::NoLowercase);
+        }
+        if policy.require_uppercase && !password.chars().any(|c| c.is_uppercase()) {
+            violations.push(PasswordViolation::NoUppercase);
+        }
+        if policy.require_digit && !password.chars().any(|c| c.is_ascii_digit()) {
+            violations.push(PasswordViolation::NoDigit);
+        }
+        if policy.require_symbol && password.chars().all(|c| c.is_alphanumeric()) {
+            violations.push(PasswordViolation::NoSymbol);
+        }
+        let password = password.to_lowercase();
+        if self.breached.contains(&password) {
+            violations.push(PasswordViolation::Breached);
+        }
+        if policy.reject_email && contains_email(&password, &email.trim().to_lowercase()) {
+            violations.push(PasswordViolation::ContainsEmail);
+        }
+        violations
+    }
+}
+
+fn contains_email(password: &str, email: &str) -> bool {
+    // Very short local parts would rule out too many passwords
+    const MIN_LOCAL_PART: usize = 3;
+    let local_part = email.split('@').next().unwrap_or("");
+    (!email.is_empty() && password.contains(email))
+        || (local_part.chars().count() >= MIN_LOCAL_PART && password.contains(local_part))
+}
+
+/**
+ * A human readable description of a violation, for command line tools
+ */
+pub fn describe(violation: &PasswordViolation) -> String {
+    match violation {
+        PasswordViolation::TooShort(min) => format!("must be at least {} characters", min),
+        PasswordViolation::NoLowercase => "must include a lowercase letter".to_owned(),
+        PasswordViolation::NoUppercase => "must include an uppercase letter".to_owned(),
+        PasswordViolation::NoDigit => "must include a digit".to_owned(),
+        PasswordViolation::NoSymbol => "must include a symbol".to_owned(),
+        PasswordViolation::Breached => "has appeared in a data breach".to_owned(),
+        PasswordViolation::ContainsEmail => "must not contain the email address".to_owned(),
+    }
+}
@@ -1,12 +1,27 @@
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use sha2::{Digest, Sha256};
+
 use argon2::{
     password_hash::{
-        rand_core::OsRng, Error, PasswordHash, PasswordHasher, PasswordVerifier, SaltString,
+        rand_core::{OsRng, RngCore},
+        Error, PasswordHash, PasswordHasher, PasswordVerifier, SaltString,
     },
-    Argon2,
+    Algorithm, Argon2, Params, Version,
 };
 
-pub fn hash_password(password_cleartext: &str) -> Result<String, Error> {
-    let argon2 = Argon2::default();
+use adl::gen::protoapp::config::server::Argon2Config;
+
+/**
+ * The hasher for the configured parameters, which fails if they are invalid
+ */
+pub fn argon2(cfg: &Argon2Config) -> Result<Argon2<'static>, Error> {
+    let params = Params::new(cfg.memory_kib, cfg.iterations, cfg.parallelism, None)?;
+    Ok(Argon2::new(Algorithm::Argon2id, Version::V0x13, params))
+}
+
+pub fn hash_password(cfg: &Argon2Config, password_cleartext: &str) -> Result<String, Error> {
+    let argon2 = argon2(cfg)?;
     let salt = SaltString::generate(&mut OsRng);
     let password_hashed = argon2
         .hash_password(password_cleartext.as_bytes(), &salt)?
@@ -14,9 +29,52 @@ pub fn hash_password(password_cleartext: &str) -> Result<String, Error> {
     Ok(password_hashed)
 }
 
-pub fn verify_password(password_cleartext: &str, password_hashed: &str) -> bool {
-    let parsed_hash = PasswordHash::new(password_hashed).expect("valid hashed password");
-    Argon2::default()
-        .verify_password(password_cleartext.as_bytes(), &parsed_hash)
-        .is_ok()
+/**
+ * Generate a random token, suitable for sending to a user in a link
+ */
+pub fn new_token() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+/**
+ * Hash a token for storage. Tokens are random, so unlike passwords
+ * they don't need a salted, slow hash.
+ */
+pub fn hash_token(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
+
+/**
+ * Check a password against a stored hash. The hash's own parameters are
+ * used, so this works for hashes made with earlier configurations. Fails
+ * only if the stored hash is malformed.
+ */
+pub fn verify_password(password_cleartext: &str, password_hashed: &str) -> Result<bool, Error> {
+    let parsed_hash = PasswordHash::new(password_hashed)?;
+    match Argon2::default().verify_password(password_cleartext.as_bytes(), &parsed_hash) {
+        Ok(()) => Ok(true),
+        Err(Error::Password) => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+/**
+ * True if a valid hash was made with other than the configured algorithm
+ * and parameters
+ */
+pub fn needs_rehash(cfg: &Argon2Config, password_hashed: &str) -> bool {
+    let parsed_hash = match PasswordHash::new(password_hashed) {
+        Ok(hash) => hash,
+        Err(_) => return true,
+    };
+    let params_match = Params::try_from(&parsed_hash).is_ok_and(|p| {
+        p.m_cost() == cfg.memory_kib
+            && p.t_cost() == cfg.iterations
+            && p.p_cost() == cfg.parallelism
+    });
+    parsed_hash.algorithm != Algorithm::Argon2id.ident()
+        || parsed_hash.version != Some(Version::V0x13.into())
+        || !params_match
 }
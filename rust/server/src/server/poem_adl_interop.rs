@@ -7,11 +7,19 @@ use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use std::future::Future;
 use std::marker::PhantomData;
+use std::pin::Pin;
 use std::sync::Arc;
 
 use adl::gen::common::http::{HttpMethod, HttpReq, HttpSecurity};
+use adl::gen::protoapp::config::server::ServerConfig;
+use adl::gen::protoapp::db::AuditOutcome;
+use sqlx::PgPool;
 
-use super::jwt;
+use super::deactivated_users::DeactivatedUsers;
+use super::timeouts::{
+    default_request_timeout, is_statement_timeout, request_timeout, timed_out, with_timeout,
+};
+use super::{api_keys, jwt, AppState};
 
 /**
  * Contextual information available to ADL request handlers
@@ -19,6 +27,8 @@ use super::jwt;
 pub struct AdlReqContext<S> {
     pub state: S,
     pub claims: Option<jwt::AccessClaims>,
+    pub client_ip: Option<String>,
+    pub user_agent: Option<String>,
 }
 
 /**
@@ -38,7 +48,7 @@ pub trait RouteExt {
     fn adl_req<S, I, O, FO>(self, req: HttpReq<I, O>, f: fn(AdlReqContext<S>, I) -> FO) -> Self
     where
         S: Send + Sync + Clone + 'static,
-        I: Send + Sync + DeserializeOwned + 'static,
+        I: Send + Sync + Serialize + DeserializeOwned + 'static,
         O: Send + Sync + Serialize + 'static,
         FO: Future<Output = HandlerResult<O>> + Send + 'static;
 }
@@ -47,7 +57,7 @@ impl RouteExt for Route {
     fn adl_req<S, I, O, FO>(self, req: HttpReq<I, O>, f: fn(AdlReqContext<S>, I) -> FO) -> Self
     where
         S: Send + Sync + Clone + 'static,
-        I: Send + Sync + DeserializeOwned + 'static,
+        I: Send + Sync + Serialize + DeserializeOwned + 'static,
         O: Send + Sync + Serialize + 'static,
         FO: Future<Output = HandlerResult<O>> + Send + 'static,
     {
@@ -61,15 +71,34 @@ impl RouteExt for Route {
 }
 
 pub trait JwtSecurityCheck {
-    fn check_security(
-        &self,
-        security: &HttpSecurity,
-        auth_header: Option<&str>,
-    ) -> HandlerResult<Option<jwt::AccessClaims>>;
+    fn check_security<'a>(
+        &'a self,
+        security: &'a HttpSecurity,
+        auth_header: Option<&'a str>,
+    ) -> Pin<Box<dyn Future<Output = HandlerResult<Option<jwt::AccessClaims>>> + Send + 'a>>;
 }
 
 pub type DynJwtSecurityCheck = Arc<Box<dyn JwtSecurityCheck + Send + Sync>>;
 
+/**
+ * A record of a call to an endpoint secured with `tokenWithRole`, or
+ * made with an impersonation token
+ */
+pub struct AuditRecord {
+    pub actor: String,
+    pub impersonated: Option<String>,
+    pub endpoint: String,
+    pub input: serde_json::Value,
+    pub outcome: AuditOutcome,
+    pub client_ip: Option<String>,
+}
+
+pub trait AuditLogger {
+    fn log(&self, record: AuditRecord) -> Pin<Box<dyn Future<Output = ()> + Send + '_>>;
+}
+
+pub type DynAuditLogger = Arc<Box<dyn AuditLogger + Send + Sync>>;
+
 //---------------------------------------------------------------------------
 
 pub struct AdlReq<S, I, O, FO> {
@@ -81,7 +110,7 @@ pub struct AdlReq<S, I, O, FO> {
 impl<S, I, O, FO> AdlReq<S, I, O, FO>
 where
     S: Send + Sync + Clone + 'static,
-    I: Send + Sync + DeserializeOwned + 'static,
+    I: Send + Sync + Serialize + DeserializeOwned + 'static,
     O: Send + Sync + Serialize + 'static,
     FO: Future<Output = HandlerResult<O>> + Send,
 {
@@ -116,26 +145,131 @@ struct Params {
 impl<S, I, O, FO> Endpoint for AdlReq<S, I, O, FO>
 where
     S: Send + Sync + Clone + 'static,
-    I: Send + Sync + DeserializeOwned + 'static,
+    I: Send + Sync + Serialize + DeserializeOwned + 'static,
     O: Send + Sync + Serialize + 'static,
     FO: Future<Output = HandlerResult<O>> + Send,
 {
     type Output = Response;
     async fn call(&self, mut req: Request) -> poem::Result<Self::Output> {
         let mut body = RequestBody::new(req.take_body());
-        let ctx = get_adl_request_context(&req, &self.req.security)?;
-        let i: I = match self.req.method {
-            HttpMethod::Get => Self::decode_query_string(req)?,
-            HttpMethod::Post => Json::from_request(&req, &mut body).await?.0,
+        let timeout = request_timeout(&req, &self.req);
+        let admin_only = matches!(self.req.security, HttpSecurity::TokenWithRole(_));
+        let ctx = match get_adl_request_context(&req, &self.req.security).await {
+            Ok(ctx) => ctx,
+            Err(e) => {
+                // Requests rejected as unauthorized have an invalid token,
+                // so there is no actor to attribute them to.
+                if admin_only && e.status() == StatusCode::FORBIDDEN {
+                    let audit = AuditContext::from_rejected_request(&req, &self.req.path).await;
+                    AuditContext::log(audit, serde_json::Value::Null, AuditOutcome::Forbidden)
+                        .await;
+                }
+                return Err(e);
+            }
+        };
+        let impersonated = ctx.claims.as_ref().is_some_and(|c| c.act.is_some());
+        let audit = match admin_only || impersonated {
+            true => AuditContext::new(&req, &self.req.path, &ctx.claims),
+            false => None,
+        };
+        let i: poem::Result<I> = match self.req.method {
+            HttpMethod::Get => Self::decode_query_string(req),
+            HttpMethod::Post => Json::from_request(&req, &mut body).await.map(|i| i.0),
+        };
+        let i = match i {
+            Ok(i) => i,
+            Err(e) => {
+                AuditContext::log(audit, serde_json::Value::Null, AuditOutcome::Failed).await;
+                return Err(e);
+            }
+        };
+        let input = match &audit {
+            Some(_) => serde_json::to_value(&i).unwrap_or(serde_json::Value::Null),
+            None => serde_json::Value::Null,
         };
-        let o = (self.handler)(ctx, i).await?;
-        Ok(Json(o).into_response())
+        let result = with_timeout(timeout, (self.handler)(ctx, i)).await;
+        let outcome = match &result {
+            Ok(_) => AuditOutcome::Success,
+            Err(_) => AuditOutcome::Failed,
+        };
+        AuditContext::log(audit, input, outcome).await;
+        Ok(Json(result?).into_response())
     }
 }
 
+/**
+ * The details needed to write an audit record, captured before the
+ * request is consumed. Only calls made with a valid access token are
+ * audited, as otherwise there is no actor to attribute them to.
+ */
+struct AuditContext {
+    logger: DynAuditLogger,
+    actor: String,
+    impersonated: Option<String>,
+    endpoint: String,
+    client_ip: Option<String>,
+}
+
+impl AuditContext {
+    fn new(req: &Request, endpoint: &str, claims: &Option<jwt::AccessClaims>) -> Option<Self> {
+        let logger = req.data::<DynAuditLogger>()?.clone();
+        let claims = claims.as_ref()?;
+        // Attribute impersonated requests to the real admin
+        let (actor, impersonated) = match &claims.act {
+            Some(act) => (act.sub.clone(), Some(claims.sub.clone())),
+            None => (claims.sub.clone(), None),
+        };
+        Some(AuditContext {
+            logger,
+            actor,
+            impersonated,
+            endpoint: endpoint.to_owned(),
+            client_ip: client_ip(req),
+        })
+    }
+
+    /**
+     * Construct the context for a request that failed its security check,
+     * attributing it to the bearer of the access token, if that is valid.
+     */
+    async fn from_rejected_request(req: &Request, endpoint: &str) -> Option<Self> {
+        let jwt_checker = req
+            .data::<DynJwtSecurityCheck>()
+            .expect("JwtChecker should be configured");
+        let claims = jwt_checker
+            .check_security(&HttpSecurity::Token, req.header("Authorization"))
+            .await
+            .ok()?;
+        Self::new(req, endpoint, &claims)
+    }
+
+    async fn log(actx: Option<Self>, input: serde_json::Value, outcome: AuditOutcome) {
+        if let Some(actx) = actx {
+            let record = AuditRecord {
+                actor: actx.actor,
+                impersonated: actx.impersonated,
+                endpoint: actx.endpoint,
+                input,
+                outcome,
+                client_ip: actx.client_ip,
+            };
+            actx.logger.log(record).await;
+        }
+    }
+}
+
+/**
+ * The address of the connected client, if known
+ */
+pub fn client_ip(req: &Request) -> Option<String> {
+    req.remote_addr()
+        .as_socket_addr()
+        .map(|addr| addr.ip().to_string())
+}
+
 //---------------------------------------------------------------------------
 
-pub fn get_adl_request_context<S: Send + Sync + Clone + 'static>(
+pub async fn get_adl_request_context<S: Send + Sync + Clone + 'static>(
     req: &poem::Request,
     security: &HttpSecurity,
 ) -> poem::Result<AdlReqContext<S>> {
@@ -144,66 +278,124 @@ pub fn get_adl_request_context<S: Send + Sync + Clone + 'static>(
         .expect("JwtChecker should be configured");
     let state = req.data::<S>().expect("State should be configured").clone();
     let auth_header = req.header("Authorization");
-    let claims = jwt_checker.check_security(security, auth_header)?;
-    let ctx = AdlReqContext { state, claims };
+
+    // Checking a token may query the db, for API keys and deactivated
+    // users, so is limited by the default request timeout
+    let claims = with_timeout(
+        default_request_timeout(req),
+        jwt_checker.check_security(security, auth_header),
+    )
+    .await?;
+    let ctx = AdlReqContext {
+        state,
+        claims,
+        client_ip: client_ip(req),
+        user_agent: req.header("User-Agent").map(|ua| ua.to_owned()),
+    };
     Ok(ctx)
 }
 
 #[derive(Clone)]
 struct AccessTokenChecker {
-    jwt_access_secret: String,
+    access_keys: Arc<jwt::AccessKeys>,
+    failures: Arc<jwt::JwtFailures>,
+    config: Arc<ServerConfig>,
+    db_pool: Arc<PgPool>,
+    deactivated_users: Arc<DeactivatedUsers>,
 }
 
-pub fn new_access_token_checker(jwt_access_secret: String) -> DynJwtSecurityCheck {
-    Arc::new(Box::new(AccessTokenChecker { jwt_access_secret }))
+/**
+ * Construct a checker that accepts either access tokens or API keys
+ * as bearer tokens
+ */
+pub fn new_access_token_checker(state: &AppState) -> DynJwtSecurityCheck {
+    Arc::new(Box::new(AccessTokenChecker {
+        access_keys: state.access_keys.clone(),
+        failures: state.jwt_failures.clone(),
+        config: state.config.clone(),
+        db_pool: state.db_pool.clone(),
+        deactivated_users: state.deactivated_users.clone(),
+    }))
 }
 
 impl JwtSecurityCheck for AccessTokenChecker {
-    fn check_security(
-        &self,
-        security: &HttpSecurity,
-        auth_header: Option<&str>,
-    ) -> HandlerResult<Option<jwt::AccessClaims>> {
-        // Get the claims from the auth header, if there is one
-        let claims = match auth_header {
-            Some(ah) => Some(claims_from_bearer_token(&self.jwt_access_secret, ah)?),
-            None => None,
-        };
+    fn check_security<'a>(
+        &'a self,
+        security: &'a HttpSecurity,
+        auth_header: Option<&'a str>,
+    ) -> Pin<Box<dyn Future<Output = HandlerResult<Option<jwt::AccessClaims>>> + Send + 'a>> {
+        Box::pin(async move {
+            // Get the claims from the auth header, if there is one
+            let claims = match auth_header {
+                Some(ah) => Some(self.claims_from_bearer_token(ah).await?),
+                None => None,
+            };
 
-        // Check whether the claims match the endpoints security rules
-        let request_allowed = match security {
-            HttpSecurity::Public => true,
-            HttpSecurity::Token => claims.is_some(),
-            HttpSecurity::TokenWithRole(role) => {
-                if let Some(claims) = &claims {
-                    claims.role == *role
-                } else {
-                    false
+            // Check whether the claims match the endpoints security rules
+            let request_allowed = match security {
+                HttpSecurity::Public => true,
+                HttpSecurity::Token => claims.is_some(),
+                HttpSecurity::TokenWithRole(role) => {
+                    // Impersonation tokens are never accepted for
+                    // role restricted endpoints
+                    if let Some(claims) = &claims {
+                        claims.has_role(role) && claims.act.is_none()
+                    } else {
+                        false
+                    }
                 }
+            };
+
+            if request_allowed {
+                Ok(claims)
+            } else {
+                log::error!("request without valid jwt claims");
+                Err(forbidden())
             }
-        };
+        })
+    }
+}
 
-        if request_allowed {
-            Ok(claims)
+impl AccessTokenChecker {
+    async fn claims_from_bearer_token(
+        &self,
+        auth_header: &str,
+    ) -> HandlerResult<jwt::AccessClaims> {
+        let jwt = jwt::bearer_token_from_auth_header(auth_header).ok_or_else(|| {
+            self.failures.record_access(jwt::JwtFailure::Malformed);
+            unauthorized()
+        })?;
+        let claims = if api_keys::is_api_key(&jwt) {
+            api_keys::access_claims(&self.db_pool, &self.config, &jwt)
+                .await?
+                .ok_or_else(|| {
+                    log::info!("rejected invalid api key");
+                    unauthorized()
+                })?
         } else {
-            log::error!("request without valid jwt claims");
-            Err(forbidden())
+            jwt::decode_access(&self.access_keys, &jwt).map_err(|failure| {
+                self.failures.record_access(failure);
+                unauthorized()
+            })?
+        };
+
+        // Tokens remain valid until they expire, so check that neither
+        // their user nor any impersonating admin has since been deactivated
+        let actor = claims.act.as_ref().map(|act| &act.sub);
+        for user_id in std::iter::once(&claims.sub).chain(actor) {
+            if self
+                .deactivated_users
+                .contains(&self.db_pool, user_id)
+                .await?
+            {
+                log::info!("rejected token of deactivated user {}", user_id);
+                return Err(unauthorized());
+            }
         }
+        Ok(claims)
     }
 }
 
-fn claims_from_bearer_token(
-    jwt_secret: &str,
-    auth_header: &str,
-) -> HandlerResult<jwt::AccessClaims> {
-    let jwt = jwt::bearer_token_from_auth_header(auth_header).ok_or(unauthorized())?;
-    let claims = jwt::decode_access(jwt_secret, &jwt).map_err(|e| {
-        log::error!("failed to validate jwt: {}", e);
-        unauthorized()
-    })?;
-    Ok(claims)
-}
-
 pub fn forbidden() -> HandlerError {
     HandlerError::Poem(poem::Error::from_status(StatusCode::FORBIDDEN))
 }
@@ -222,6 +414,10 @@ impl From<anyhow::Error> for HandlerError {
 
 impl From<sqlx::Error> for HandlerError {
     fn from(err: sqlx::Error) -> HandlerError {
+        if is_statement_timeout(&err) {
+            log::error!("database statement timed out");
+            return timed_out();
+        }
         HandlerError::Anyhow(anyhow::anyhow!("sqlx error: {}", err.to_string()))
     }
 }
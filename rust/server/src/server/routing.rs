@@ -1,30 +1,153 @@
 use poem::endpoint::{DynEndpoint, ToDynEndpoint};
-use poem::session::{CookieConfig, CookieSession};
+use poem::session::CookieSession;
 use poem::{post, EndpointExt, Route};
+use std::time::Duration;
 
 use adl::gen::protoapp::apis::ui::ApiRequests;
 
+use crate::server::audit::new_db_audit_logger;
+use crate::server::auth_cookie;
 use crate::server::handlers;
 use crate::server::poem_adl_interop::{new_access_token_checker, RouteExt};
+use crate::server::timeouts::DefaultRequestTimeout;
 use crate::server::AppState;
 
 pub fn build_routes(state: AppState) -> Box<dyn DynEndpoint<Output = poem::Response>> {
-    let access_token_checker = new_access_token_checker(state.config.jwt_access_secret.clone());
+    let access_token_checker = new_access_token_checker(&state);
+    let audit_logger = new_db_audit_logger(state.db_pool.clone());
+    let session_cookie = auth_cookie::session_cookie_config(&state.config.auth_cookie);
+    let default_timeout = DefaultRequestTimeout(Duration::from_secs(
+        state.config.request_timeout_secs as u64,
+    ));
 
     let routes = Route::new();
 
     // Add standard ADL implemented handlers
     let routes = routes
         .adl_req(ApiRequests::def_healthy(), handlers::healthy)
+        .adl_req(ApiRequests::def_jwks(), handlers::jwks)
         .adl_req(ApiRequests::def_who_am_i(), handlers::who_am_i)
+        .adl_req(ApiRequests::def_update_profile(), handlers::update_profile)
+        .adl_req(
+            ApiRequests::def_change_password(),
+            handlers::change_password,
+        )
+        .adl_req(ApiRequests::def_my_sessions(), handlers::my_sessions)
+        .adl_req(ApiRequests::def_revoke_session(), handlers::revoke_session)
+        .adl_req(
+            ApiRequests::def_revoke_other_sessions(),
+            handlers::revoke_other_sessions,
+        )
+        .adl_req(
+            ApiRequests::def_request_password_reset(),
+            handlers::request_password_reset,
+        )
+        .adl_req(ApiRequests::def_reset_password(), handlers::reset_password)
+        .adl_req(ApiRequests::def_verify_email(), handlers::verify_email)
+        .adl_req(
+            ApiRequests::def_resend_verification_email(),
+            handlers::resend_verification_email,
+        )
+        .adl_req(
+            ApiRequests::def_begin_totp_enrolment(),
+            handlers::begin_totp_enrolment,
+        )
+        .adl_req(
+            ApiRequests::def_confirm_totp_enrolment(),
+            handlers::confirm_totp_enrolment,
+        )
         .adl_req(ApiRequests::def_new_message(), handlers::new_message)
         .adl_req(
             ApiRequests::def_recent_messages(),
             handlers::recent_messages,
         )
+        .adl_req(
+            ApiRequests::def_reply_to_message(),
+            handlers::reply_to_message,
+        )
+        .adl_req(ApiRequests::def_message_thread(), handlers::message_thread)
+        .adl_req(ApiRequests::def_add_reaction(), handlers::add_reaction)
+        .adl_req(
+            ApiRequests::def_remove_reaction(),
+            handlers::remove_reaction,
+        )
+        .adl_req(ApiRequests::def_edit_message(), handlers::edit_message)
+        .adl_req(ApiRequests::def_delete_message(), handlers::delete_message)
+        .adl_req(
+            ApiRequests::def_message_revisions(),
+            handlers::message_revisions,
+        )
         .adl_req(ApiRequests::def_create_user(), handlers::create_user)
         .adl_req(ApiRequests::def_update_user(), handlers::update_user)
-        .adl_req(ApiRequests::def_query_users(), handlers::query_users);
+        .adl_req(ApiRequests::def_unlock_user(), handlers::unlock_user)
+        .adl_req(
+            ApiRequests::def_impersonate_user(),
+            handlers::impersonate_user,
+        )
+        .adl_req(
+            ApiRequests::def_revoke_user_sessions(),
+            handlers::revoke_user_sessions,
+        )
+        .adl_req(
+            ApiRequests::def_deactivate_user(),
+            handlers::deactivate_user,
+        )
+        .adl_req(
+            ApiRequests::def_reactivate_user(),
+            handlers::reactivate_user,
+        )
+        .adl_req(ApiRequests::def_set_user_roles(), handlers::set_user_roles)
+        .adl_req(ApiRequests::def_create_role(), handlers::create_role)
+        .adl_req(ApiRequests::def_query_roles(), handlers::query_roles)
+        .adl_req(
+            ApiRequests::def_set_role_permissions(),
+            handlers::set_role_permissions,
+        )
+        .adl_req(ApiRequests::def_delete_role(), handlers::delete_role)
+        .adl_req(
+            ApiRequests::def_create_organisation(),
+            handlers::create_organisation,
+        )
+        .adl_req(
+            ApiRequests::def_query_organisations(),
+            handlers::query_organisations,
+        )
+        .adl_req(
+            ApiRequests::def_set_organisation_member(),
+            handlers::set_organisation_member,
+        )
+        .adl_req(
+            ApiRequests::def_remove_organisation_member(),
+            handlers::remove_organisation_member,
+        )
+        .adl_req(
+            ApiRequests::def_switch_organisation(),
+            handlers::switch_organisation,
+        )
+        .adl_req(
+            ApiRequests::def_create_invitation(),
+            handlers::create_invitation,
+        )
+        .adl_req(
+            ApiRequests::def_query_invitations(),
+            handlers::query_invitations,
+        )
+        .adl_req(
+            ApiRequests::def_revoke_invitation(),
+            handlers::revoke_invitation,
+        )
+        .adl_req(ApiRequests::def_query_users(), handlers::query_users)
+        .adl_req(
+            ApiRequests::def_jwt_failure_counts(),
+            handlers::jwt_failure_counts,
+        )
+        .adl_req(ApiRequests::def_create_api_key(), handlers::create_api_key)
+        .adl_req(ApiRequests::def_query_api_keys(), handlers::query_api_keys)
+        .adl_req(ApiRequests::def_revoke_api_key(), handlers::revoke_api_key)
+        .adl_req(
+            ApiRequests::def_query_audit_log(),
+            handlers::query_audit_log,
+        );
 
     // Add handlers that need custom cookie handling
     let routes = routes
@@ -32,6 +155,22 @@ pub fn build_routes(state: AppState) -> Box<dyn DynEndpoint<Output = poem::Respo
             ApiRequests::def_login().path,
             post(handlers::login_with_cookies),
         )
+        .at(
+            ApiRequests::def_login_totp().path,
+            post(handlers::login_totp_with_cookies),
+        )
+        .at(
+            ApiRequests::def_oidc_login_start().path,
+            post(handlers::oidc_login_start_with_cookies),
+        )
+        .at(
+            ApiRequests::def_oidc_login().path,
+            post(handlers::oidc_login_with_cookies),
+        )
+        .at(
+            ApiRequests::def_signup().path,
+            post(handlers::signup_with_cookies),
+        )
         .at(
             ApiRequests::def_refresh().path,
             post(handlers::refresh_with_cookies),
@@ -45,7 +184,9 @@ pub fn build_routes(state: AppState) -> Box<dyn DynEndpoint<Output = poem::Respo
     let routes = routes
         .data(state)
         .data(access_token_checker)
-        .with(CookieSession::new(CookieConfig::default().secure(false)));
+        .data(audit_logger)
+        .data(default_timeout)
+        .with(CookieSession::new(session_cookie));
 
     Box::new(ToDynEndpoint(routes))
 }
@@ -2,15 +2,17 @@ use rand::Rng;
 use reqwest::header::{HeaderMap, HeaderValue};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
-use sqlx::postgres::PgPoolOptions;
-use sqlx::Executor;
+use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
+use std::str::FromStr;
 
 use adl::gen::common::http::{HttpMethod, HttpReq};
 use adl::gen::protoapp::apis;
 use adl::gen::protoapp::apis::ui::LoginReq;
 use adl::gen::protoapp::config::server::{DbConnectionConfig, ServerConfig};
 
+use crate::server::mail::Email;
 use crate::server::passwords::hash_password;
+use crate::server::timeouts::with_statement_timeouts;
 
 pub struct DbTestEnv {
     pub pool: sqlx::PgPool,
@@ -19,6 +21,21 @@ pub struct DbTestEnv {
 
 impl DbTestEnv {
     pub async fn new() -> Self {
+        let db = DbTestEnv::new_unmigrated().await;
+        db.migrate(None).await;
+        db
+    }
+
+    /**
+     * A test db with only the migrations up to and including `version`
+     */
+    pub async fn new_at_version(version: i64) -> Self {
+        let db = DbTestEnv::new_unmigrated().await;
+        db.migrate(Some(version)).await;
+        db
+    }
+
+    async fn new_unmigrated() -> Self {
         let db_connection_url =
             std::env::var("DB_CONNECTION_URL").expect("DB_CONNECTION_URL environment variable");
         let pool0 = PgPoolOptions::new()
@@ -42,30 +59,36 @@ impl DbTestEnv {
 
         // Create a db pool where connections go to the new schema
         let pool = {
-            let schema = schema.clone();
-            PgPoolOptions::new()
+            let connect_options = PgConnectOptions::from_str(&db_connection_url)
+                .expect("valid DB_CONNECTION_URL")
+                .options([("search_path", &schema)]);
+            with_statement_timeouts(PgPoolOptions::new())
                 .max_connections(5)
-                .after_connect(move |conn, _meta| {
-                    let schema = schema.clone();
-                    Box::pin(async move {
-                        conn.execute(format!("SET search_path = '{}';", schema).as_ref())
-                            .await
-                            .expect("new test db schema to be created");
-                        Ok(())
-                    })
-                })
-                .connect(&db_connection_url)
+                .connect_with(connect_options)
                 .await
                 .expect("db connection to succeed")
         };
 
-        // Run all the migrations
-        sqlx::migrate!()
-            .run(&pool)
+        DbTestEnv { pool, schema }
+    }
+
+    /**
+     * Run the migrations up to and including `to_version`, or all of them
+     */
+    pub async fn migrate(&self, to_version: Option<i64>) {
+        let mut migrator = sqlx::migrate!();
+        if let Some(version) = to_version {
+            migrator.migrations = migrator
+                .migrations
+                .iter()
+                .filter(|m| m.version <= version)
+                .cloned()
+                .collect();
+        }
+        migrator
+            .run(&self.pool)
             .await
             .expect("migrations should run correctly");
-
-        DbTestEnv { pool, schema }
     }
 
     pub async fn execute(&mut self, sql: &str) {
@@ -156,14 +179,77 @@ pub fn test_server_config() -> ServerConfig {
         },
         db_connection_pool_size: ServerConfig::def_db_connection_pool_size(),
         jwt_issuer: "adl-protoapp.link".to_owned(),
+        jwt_audience: "adl-protoapp.link".to_owned(),
+        jwt_accepted_audiences: vec![],
+        jwt_leeway_secs: 60,
         jwt_access_secret: "treyweyetry".to_owned(),
         jwt_access_expiry_secs: 300,
+        jwt_impersonation_expiry_secs: ServerConfig::def_jwt_impersonation_expiry_secs(),
+        jwt_access_signing_key: None,
+        jwt_access_verification_keys: vec![],
         jwt_refresh_secret: "treyweyetryxx".to_owned(),
         jwt_refresh_expiry_secs: 300,
+        deactivated_users_cache_secs: ServerConfig::def_deactivated_users_cache_secs(),
         http_bind_addr: "0.0.0.0:8181".to_owned(),
+        request_timeout_secs: ServerConfig::def_request_timeout_secs(),
+        app_url: ServerConfig::def_app_url(),
+        csrf: ServerConfig::def_csrf(),
+        auth_cookie: ServerConfig::def_auth_cookie(),
+        mailer: ServerConfig::def_mailer(),
+        mail_from: ServerConfig::def_mail_from(),
+        password_policy: ServerConfig::def_password_policy(),
+        argon2: ServerConfig::def_argon2(),
+        password_reset_expiry_secs: ServerConfig::def_password_reset_expiry_secs(),
+        email_verification_secret: "treyweyetryyy".to_owned(),
+        email_verification_expiry_secs: ServerConfig::def_email_verification_expiry_secs(),
+        email_verification_resend_secs: ServerConfig::def_email_verification_resend_secs(),
+        require_verified_email: false,
+        totp_issuer: ServerConfig::def_totp_issuer(),
+        totp_challenge_expiry_secs: ServerConfig::def_totp_challenge_expiry_secs(),
+        login_backoff_after_failures: ServerConfig::def_login_backoff_after_failures(),
+        login_backoff_base_secs: ServerConfig::def_login_backoff_base_secs(),
+        login_backoff_max_secs: ServerConfig::def_login_backoff_max_secs(),
+        login_lockout_failures: ServerConfig::def_login_lockout_failures(),
+        login_lockout_secs: ServerConfig::def_login_lockout_secs(),
+        api_key_max_expiry_days: ServerConfig::def_api_key_max_expiry_days(),
+        invitation_max_expiry_days: ServerConfig::def_invitation_max_expiry_days(),
+        oidc: None,
     }
 }
 
+/**
+ * A fresh file for the test mailer to write to
+ */
+pub fn test_mail_file(name: &str) -> String {
+    let path = std::env::temp_dir().join(format!("protoapp-{}-{}.jsonl", name, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path.to_str().unwrap().to_owned()
+}
+
+/**
+ * The emails written by the test mailer
+ */
+pub fn sent_emails(mail_file: &str) -> Vec<Email> {
+    match std::fs::read_to_string(mail_file) {
+        Ok(s) => s
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect(),
+        Err(_) => vec![],
+    }
+}
+
+/**
+ * The path of one of the PEM files used for testing jwt signing
+ */
+pub fn test_key_file(name: &str) -> String {
+    format!(
+        "{}/src/server/tests/keys/{}",
+        env!("CARGO_MANIFEST_DIR"),
+        name
+    )
+}
+
 pub async fn create_test_user(
     db: &mut DbTestEnv,
     key: &str,
@@ -172,17 +258,24 @@ pub async fn create_test_user(
     password: &str,
     is_admin: bool,
 ) -> LoginReq {
-    let hashed_password = hash_password(password).expect("password hash to success");
+    let hashed_password =
+        hash_password(&ServerConfig::def_argon2(), password).expect("password hash to success");
 
     db.execute(
-        &format!("INSERT INTO app_user(id,fullname,email,is_admin,hashed_password) VALUES ('{}', '{}', '{}', {}, '{}');",
+        &format!("INSERT INTO app_user(id,fullname,email,hashed_password,verified_at) VALUES ('{}', '{}', '{}', '{}', now());",
             key,
             fullname,
             email,
-            is_admin,
             hashed_password,
             )
     ).await;
+    if is_admin {
+        db.execute(&format!(
+            "INSERT INTO user_role(id,user_id,role_id) SELECT 'J-{}', '{}', id FROM role WHERE name = 'admin';",
+            key, key,
+        ))
+        .await;
+    }
     LoginReq {
         email: email.to_owned(),
         password: password.to_owned(),
@@ -194,5 +287,9 @@ pub async fn login_user(login_req: &LoginReq) -> String {
     match resp {
         apis::ui::LoginResp::Tokens(tokens) => tokens.access_jwt,
         apis::ui::LoginResp::InvalidCredentials => panic!("invalid credentials"),
+        apis::ui::LoginResp::EmailNotVerified => panic!("email not verified"),
+        apis::ui::LoginResp::Deactivated => panic!("deactivated"),
+        apis::ui::LoginResp::TotpRequired(_) => panic!("totp required"),
+        apis::ui::LoginResp::TooManyAttempts(_) => panic!("too many attempts"),
     }
 }
@@ -0,0 +1,291 @@
+use jsonwebtoken::{Algorithm, EncodingKey, Header};
+use poem::http::StatusCode;
+use poem::listener::TcpListener;
+use poem::web::{Data, Form, Json, Query};
+use poem::{get, handler, post, EndpointExt, IntoResponse, Response, Route};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::{sync::oneshot, task::JoinHandle};
+
+use adl::gen::protoapp::config::server::{JwtAlgorithm, JwtKey, OidcConfig};
+
+use crate::server::jwt::AccessKeys;
+use crate::server::oidc::pkce_challenge;
+use crate::server::passwords::new_token;
+use crate::server::tests::helpers::{test_key_file, test_server_config};
+
+pub const MOCK_IDP_URL: &str = "http://localhost:8282";
+const MOCK_IDP_ADDR: &str = "0.0.0.0:8282";
+const MOCK_IDP_KID: &str = "mock-idp";
+
+/**
+ * A minimal OpenID Connect provider, that authenticates every
+ * authorization request as its current user
+ */
+pub struct MockIdp {
+    state: Arc<Mutex<IdpState>>,
+    shutdown: oneshot::Sender<()>,
+    joinhandle: JoinHandle<()>,
+}
+
+/**
+ * The provider's account for the current user
+ */
+#[derive(Clone)]
+pub struct IdpUser {
+    pub sub: String,
+    pub email: String,
+    pub email_verified: bool,
+    pub name: String,
+}
+
+struct IdpState {
+    client: OidcConfig,
+    user: Option<IdpUser>,
+    // Claims that replace the correct values in issued id tokens
+    claim_overrides: serde_json::Map<String, serde_json::Value>,
+    codes: HashMap<String, IssuedCode>,
+    // The id and algorithm of the key that signs id tokens
+    kid: String,
+    algorithm: Algorithm,
+    discovery_fetches: usize,
+    jwks_fetches: usize,
+}
+
+struct IssuedCode {
+    user: IdpUser,
+    nonce: String,
+    code_challenge: String,
+}
+
+impl MockIdp {
+    /**
+     * Start the provider, with a single client registered
+     */
+    pub fn spawn(client: &OidcConfig) -> Self {
+        let state = Arc::new(Mutex::new(IdpState {
+            client: client.clone(),
+            user: None,
+            claim_overrides: serde_json::Map::new(),
+            codes: HashMap::new(),
+            kid: MOCK_IDP_KID.to_owned(),
+            algorithm: Algorithm::RS256,
+            discovery_fetches: 0,
+            jwks_fetches: 0,
+        }));
+        let app = Route::new()
+            .at("/.well-known/openid-configuration", get(discovery))
+            .at("/authorize", get(authorization))
+            .at("/token", post(token))
+            .at("/jwks", get(jwks))
+            .data(state.clone());
+        let (shutdown, shutdown_notify) = oneshot::channel::<()>();
+        let joinhandle = tokio::spawn(async move {
+            let server = poem::Server::new(TcpListener::bind(MOCK_IDP_ADDR))
+                .run_with_graceful_shutdown(
+                    app,
+                    async {
+                        let _ = shutdown_notify.await;
+                    },
+                    None,
+                );
+            let _ = server.await;
+        });
+        MockIdp {
+            state,
+            shutdown,
+            joinhandle,
+        }
+    }
+
+    pub fn set_user(&self, user: IdpUser) {
+        self.state.lock().unwrap().user = Some(user);
+    }
+
+    pub fn set_claim_override(&self, claim: &str, value: serde_json::Value) {
+        let mut state = self.state.lock().unwrap();
+        state.claim_overrides.insert(claim.to_owned(), value);
+    }
+
+    pub fn clear_claim_overrides(&self) {
+        self.state.lock().unwrap().claim_overrides.clear();
+    }
+
+    /**
+     * Replace the provider's key with a new one, with the given id
+     */
+    pub fn rotate_key(&self, kid: &str) {
+        self.state.lock().unwrap().kid = kid.to_owned();
+    }
+
+    /**
+     * Sign id tokens with the given algorithm, using the private key's PEM
+     * as the secret for HMAC algorithms
+     */
+    pub fn set_signing_algorithm(&self, algorithm: Algorithm) {
+        self.state.lock().unwrap().algorithm = algorithm;
+    }
+
+    /**
+     * The number of times the discovery document and keys have been fetched
+     */
+    pub fn fetches(&self) -> (usize, usize) {
+        let state = self.state.lock().unwrap();
+        (state.discovery_fetches, state.jwks_fetches)
+    }
+
+    pub async fn shutdown(self) -> Result<(), ()> {
+        self.shutdown.send(())?;
+        self.joinhandle.await.map_err(|_| ())?;
+        Ok(())
+    }
+}
+
+/**
+ * Follow an authorization url, returning the code and state from the
+ * provider's redirect back to the app
+ */
+pub async fn authorize(authorization_url: &str) -> (String, String) {
+    let client = reqwest::Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .unwrap();
+    let resp = client.get(authorization_url).send().await.unwrap();
+    assert_eq!(resp.status(), 302);
+    let location = resp.headers()["location"].to_str().unwrap();
+    let params: HashMap<String, String> = reqwest::Url::parse(location)
+        .unwrap()
+        .query_pairs()
+        .into_owned()
+        .collect();
+    (params["code"].clone(), params["state"].clone())
+}
+
+#[handler]
+fn discovery(state: Data<&Arc<Mutex<IdpState>>>) -> Json<serde_json::Value> {
+    state.lock().unwrap().discovery_fetches += 1;
+    Json(serde_json::json!({
+        "issuer": MOCK_IDP_URL,
+        "authorization_endpoint": format!("{}/authorize", MOCK_IDP_URL),
+        "token_endpoint": format!("{}/token", MOCK_IDP_URL),
+        "jwks_uri": format!("{}/jwks", MOCK_IDP_URL),
+    }))
+}
+
+#[handler]
+fn authorization(
+    state: Data<&Arc<Mutex<IdpState>>>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Response {
+    let mut state = state.lock().unwrap();
+    let param = |name: &str| params.get(name).cloned().unwrap_or_default();
+    if param("client_id") != state.client.client_id
+        || param("redirect_uri") != state.client.redirect_url
+        || param("response_type") != "code"
+        || param("code_challenge_method") != "S256"
+    {
+        return StatusCode::BAD_REQUEST.into_response();
+    }
+    let user = match &state.user {
+        Some(user) => user.clone(),
+        None => return StatusCode::UNAUTHORIZED.into_response(),
+    };
+    let code = new_token();
+    state.codes.insert(
+        code.clone(),
+        IssuedCode {
+            user,
+            nonce: param("nonce"),
+            code_challenge: param("code_challenge"),
+        },
+    );
+    let location = format!(
+        "{}?code={}&state={}",
+        state.client.redirect_url,
+        code,
+        urlencoding::encode(&param("state"))
+    );
+    Response::builder()
+        .status(StatusCode::FOUND)
+        .header("location", location)
+        .finish()
+}
+
+#[handler]
+fn token(
+    state: Data<&Arc<Mutex<IdpState>>>,
+    Form(params): Form<HashMap<String, String>>,
+) -> Response {
+    let mut state = state.lock().unwrap();
+    let param = |name: &str| params.get(name).cloned().unwrap_or_default();
+    let invalid_grant = (
+        StatusCode::BAD_REQUEST,
+        Json(serde_json::json!({"error": "invalid_grant"})),
+    );
+    if param("grant_type") != "authorization_code"
+        || param("client_id") != state.client.client_id
+        || param("client_secret") != state.client.client_secret
+        || param("redirect_uri") != state.client.redirect_url
+    {
+        return invalid_grant.into_response();
+    }
+    // Codes are single use
+    let issued = match state.codes.remove(&param("code")) {
+        Some(issued) => issued,
+        None => return invalid_grant.into_response(),
+    };
+    if pkce_challenge(&param("code_verifier")) != issued.code_challenge {
+        return invalid_grant.into_response();
+    }
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    let mut claims = serde_json::json!({
+        "iss": MOCK_IDP_URL,
+        "aud": state.client.client_id,
+        "sub": issued.user.sub,
+        "iat": now,
+        "exp": now + 300,
+        "nonce": issued.nonce,
+        "email": issued.user.email,
+        "email_verified": issued.user.email_verified,
+        "name": issued.user.name,
+    });
+    for (claim, value) in &state.claim_overrides {
+        claims[claim] = value.clone();
+    }
+    let mut header = Header::new(state.algorithm);
+    header.kid = Some(state.kid.clone());
+    let pem = std::fs::read(test_key_file("rsa-private.pem")).unwrap();
+    let key = match state.algorithm {
+        Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512 => EncodingKey::from_secret(&pem),
+        _ => EncodingKey::from_rsa_pem(&pem).unwrap(),
+    };
+    let id_token = jsonwebtoken::encode(&header, &claims, &key).unwrap();
+    Json(serde_json::json!({
+        "access_token": "mock-access-token",
+        "token_type": "Bearer",
+        "id_token": id_token,
+    }))
+    .into_response()
+}
+
+#[handler]
+fn jwks(state: Data<&Arc<Mutex<IdpState>>>) -> Json<serde_json::Value> {
+    let mut state = state.lock().unwrap();
+    state.jwks_fetches += 1;
+
+    // Publish the test RSA key, as our own server would
+    let mut cfg = test_server_config();
+    cfg.jwt_access_signing_key = Some(JwtKey {
+        kid: state.kid.clone(),
+        algorithm: JwtAlgorithm::Rs256,
+        public_key_pem_file: test_key_file("rsa-public.pem"),
+        private_key_pem_file: Some(test_key_file("rsa-private.pem")),
+    });
+    let keys = AccessKeys::from_config(&cfg).unwrap();
+    Json(serde_json::to_value(keys.jwks()).unwrap())
+}
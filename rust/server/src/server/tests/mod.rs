@@ -1,16 +1,48 @@
-use adl::gen::common::http::Unit;
+use adl::custom::common::db::DbKey;
+use adl::gen::common::http::{HttpError, Unit};
 use adl::gen::protoapp::apis;
 use adl::gen::protoapp::apis::ui::{
-    LoginReq, LoginTokens, Message, PageReq, Paginated, RefreshReq,
+    AddReactionResp, AuditLogEntry, BeginTotpEnrolmentResp, ChangePasswordReq, ChangePasswordResp,
+    ConfirmTotpEnrolmentReq, ConfirmTotpEnrolmentResp, CreateApiKeyReq, CreateApiKeyResp,
+    CreateInvitationReq, CreateInvitationResp, CreateOrganisationReq, CreateOrganisationResp,
+    CreateRoleReq, CreateRoleResp, CreateUserResp, DeactivateUserResp, DeleteMessageResp,
+    DeleteRoleResp, EditMessageReq, EditMessageResp, ImpersonateUserResp, InvitationStatus,
+    JwtFailureCount, LoginReq, LoginTokens, LoginTotpReq, LoginTotpResp, LogoutReq, Message,
+    MessageReactionReq, MessageRevisionsResp, MessageThreadReq, MessageThreadResp, OidcLoginReq,
+    OidcLoginResp, OidcLoginStartResp, PageReq, Paginated, PasswordViolation, QueryApiKeysReq,
+    QueryAuditLogReq, QueryInvitationsReq, QueryOrganisationsReq, QueryRolesReq,
+    ReactivateUserResp, RefreshReq, RemoveOrganisationMemberReq, ReplyToMessageReq,
+    ReplyToMessageResp, RequestPasswordResetReq, ResendVerificationEmailReq, ResetPasswordReq,
+    ResetPasswordResp, SetOrganisationMemberReq, SetOrganisationMemberResp, SetRolePermissionsReq,
+    SetRolePermissionsResp, SetUserRolesReq, SetUserRolesResp, SignupReq, SignupResp,
+    SwitchOrganisationReq, SwitchOrganisationResp, UpdateProfileReq, UpdateProfileResp,
+    UpdateUserResp, UserDetails, VerifyEmailReq, VerifyEmailResp, WithId,
 };
+use adl::gen::protoapp::config::server::{
+    Argon2Config, AuthCookieConfig, CookieSameSite, CsrfConfig, JwtAlgorithm, JwtKey, MailerConfig,
+    OidcConfig, PasswordPolicy, ServerConfig,
+};
+use adl::gen::protoapp::db::{
+    ApiKeyId, ApiKeyScope, AppUserId, AuditOutcome, MessageId, OrganisationId, RoleId,
+};
+use jsonwebtoken::{Algorithm, EncodingKey, Header};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
+use crate::server::deactivated_users::DeactivatedUsers;
+use crate::server::jwt::{decode_access, AccessClaims, AccessKeys, ROLE_USER};
+use crate::server::mail::Email;
+use crate::server::password_policy::PasswordPolicyChecker;
 use crate::server::tests::helpers::{
-    create_test_user, login_user, server_auth_req, server_public_req, server_req,
-    test_server_config, DbTestEnv,
+    create_test_user, login_user, sent_emails, server_auth_req, server_public_req, server_req,
+    test_key_file, test_mail_file, test_server_config, DbTestEnv,
 };
+use crate::server::tests::mock_idp::{IdpUser, MockIdp, MOCK_IDP_URL};
+use crate::server::{auth_cookie, db, totp};
 use crate::server::{AppState, OServer};
 
 mod helpers;
+mod mock_idp;
 
 #[tokio::test]
 async fn schema_setup() {
@@ -58,7 +90,7 @@ async fn server_login() {
 
     {
         // Check that we can logout
-        server_public_req(apis::ui::ApiRequests::def_logout(), &Unit {}).await;
+        server_public_req(apis::ui::ApiRequests::def_logout(), &LogoutReq::new()).await;
     }
 
     {
@@ -92,161 +124,3429 @@ async fn server_login() {
 }
 
 #[tokio::test]
-async fn server_user_profile() {
+async fn server_refresh_rotation() {
     let mut db = DbTestEnv::new().await;
     let oserver = OServer::spawn(AppState::new(test_server_config(), db.pool.clone()));
 
     let u1 = create_test_user_joe(&mut db).await;
-    let u1_jwt = login_user(&u1).await;
+    let login_tokens = || async {
+        get_login_tokens(server_public_req(apis::ui::ApiRequests::def_login(), &u1).await)
+    };
 
-    let resp = server_auth_req(apis::ui::ApiRequests::def_who_am_i(), &u1_jwt, &()).await;
-    assert_eq!(resp.value.fullname, "Joe");
-    assert_eq!(resp.value.email, "joe@test.com");
-    assert!(!resp.value.is_admin);
+    {
+        // Each refresh returns a new refresh token, and the old one can't be reused
+        let t0 = login_tokens().await.unwrap();
+        let t1 = refresh_tokens(&t0.refresh_jwt).await.unwrap();
+        assert_ne!(t0.refresh_jwt, t1.refresh_jwt);
+        let t2 = refresh_tokens(&t1.refresh_jwt).await.unwrap();
+        assert!(refresh_tokens(&t0.refresh_jwt).await.is_none());
+
+        // The reuse revokes the whole family, including the latest token
+        assert!(refresh_tokens(&t2.refresh_jwt).await.is_none());
+    }
+
+    {
+        // Other logins are unaffected by the revocation
+        let t0 = login_tokens().await.unwrap();
+        let t1 = refresh_tokens(&t0.refresh_jwt).await.unwrap();
+
+        // but logout revokes the refresh token server side
+        server_public_req(
+            apis::ui::ApiRequests::def_logout(),
+            &LogoutReq {
+                refresh_token: Some(t1.refresh_jwt.clone()),
+            },
+        )
+        .await;
+        assert!(refresh_tokens(&t1.refresh_jwt).await.is_none());
+    }
 
     oserver.shutdown().await.unwrap();
     db.cleanup().await;
 }
 
 #[tokio::test]
-async fn server_messages() {
+async fn server_password_reset() {
     let mut db = DbTestEnv::new().await;
-    let oserver = OServer::spawn(AppState::new(test_server_config(), db.pool.clone()));
+    let mail_file = test_mail_file("password-reset");
+    let config = ServerConfig {
+        mailer: MailerConfig::File(mail_file.clone()),
+        ..test_server_config()
+    };
+    let oserver = OServer::spawn(AppState::new(config, db.pool.clone()));
 
     let u1 = create_test_user_joe(&mut db).await;
-    let u2 = create_test_user_sarah(&mut db).await;
+    let request_reset = |email: &str| {
+        let req = RequestPasswordResetReq {
+            email: email.to_owned(),
+        };
+        async move { server_public_req(apis::ui::ApiRequests::def_request_password_reset(), &req).await }
+    };
+    let reset_password = |token: &str, password: &str| {
+        let req = ResetPasswordReq {
+            token: token.to_owned(),
+            password: password.to_owned(),
+        };
+        async move { server_public_req(apis::ui::ApiRequests::def_reset_password(), &req).await }
+    };
+    let reset_token = |email: &Email| {
+        let (_, token) = email.body.split_once("token=").unwrap();
+        token.split_whitespace().next().unwrap().to_owned()
+    };
+
+    // Unknown addresses succeed, but nothing is sent
+    request_reset("nobody@test.com").await;
+    assert!(sent_emails(&mail_file).is_empty());
+
+    let t0 =
+        get_login_tokens(server_public_req(apis::ui::ApiRequests::def_login(), &u1).await).unwrap();
+    request_reset(&u1.email).await;
+    let emails = sent_emails(&mail_file);
+    assert_eq!(emails.len(), 1);
+    assert_eq!(emails[0].to, u1.email);
+    let token = reset_token(&emails[0]);
+
+    // Tokens are stored hashed
+    let stored: i64 =
+        sqlx::query_scalar("select count(*) from password_reset_token where token_hash = $1")
+            .bind(&token)
+            .fetch_one(&db.pool)
+            .await
+            .unwrap();
+    assert_eq!(stored, 0);
+
+    assert!(matches!(
+        reset_password("not-a-token", "newpassword").await,
+        ResetPasswordResp::InvalidToken
+    ));
+    // A password that breaks the policy doesn't use up the token
+    assert!(
+        reset_password(&token, "short").await
+            == ResetPasswordResp::WeakPassword(vec![PasswordViolation::TooShort(8)])
+    );
+    assert!(matches!(
+        reset_password(&token, "newpassword").await,
+        ResetPasswordResp::Success
+    ));
+
+    // The token is single use
+    assert!(matches!(
+        reset_password(&token, "anotherpassword").await,
+        ResetPasswordResp::InvalidToken
+    ));
+
+    // Only the new password works, and existing sessions are revoked
+    let login = |password: &str| {
+        let req = LoginReq {
+            email: u1.email.clone(),
+            password: password.to_owned(),
+        };
+        async move {
+            get_login_tokens(server_public_req(apis::ui::ApiRequests::def_login(), &req).await)
+        }
+    };
+    assert!(login(&u1.password).await.is_none());
+    assert!(login("newpassword").await.is_some());
+    assert!(refresh_tokens(&t0.refresh_jwt).await.is_none());
+
+    // Using a token invalidates the user's other outstanding tokens
+    request_reset(&u1.email).await;
+    let earlier_token = reset_token(sent_emails(&mail_file).last().unwrap());
+    request_reset(&u1.email).await;
+    let token = reset_token(sent_emails(&mail_file).last().unwrap());
+    assert_ne!(earlier_token, token);
+    assert!(matches!(
+        reset_password(&token, "newerpassword").await,
+        ResetPasswordResp::Success
+    ));
+    assert!(matches!(
+        reset_password(&earlier_token, "anotherpassword").await,
+        ResetPasswordResp::InvalidToken
+    ));
+    assert!(login("newerpassword").await.is_some());
+
+    // Expired tokens are rejected
+    request_reset(&u1.email).await;
+    let token = reset_token(sent_emails(&mail_file).last().unwrap());
+    db.execute("update password_reset_token set expires_at = now() - interval '1 minute'")
+        .await;
+    assert!(matches!(
+        reset_password(&token, "anotherpassword").await,
+        ResetPasswordResp::InvalidToken
+    ));
+
+    oserver.shutdown().await.unwrap();
+    db.cleanup().await;
+}
+
+#[tokio::test]
+async fn server_email_verification() {
+    let mut db = DbTestEnv::new().await;
+    let mail_file = test_mail_file("email-verification");
+    let config = ServerConfig {
+        mailer: MailerConfig::File(mail_file.clone()),
+        require_verified_email: true,
+        ..test_server_config()
+    };
+    let oserver = OServer::spawn(AppState::new(config, db.pool.clone()));
+
+    let admin = create_test_user_sarah(&mut db).await;
+    let admin_jwt = login_user(&admin).await;
+    let u1 = LoginReq {
+        email: "pat@test.com".to_owned(),
+        password: "sukpepolup".to_owned(),
+    };
+    let u1_details = |email: &str| UserDetails {
+        fullname: "Pat".to_owned(),
+        email: email.to_owned(),
+        password: u1.password.clone(),
+    };
+    let login = |req: LoginReq| async move {
+        server_public_req(apis::ui::ApiRequests::def_login(), &req).await
+    };
+    let verify_email = |token: String| async move {
+        server_public_req(
+            apis::ui::ApiRequests::def_verify_email(),
+            &VerifyEmailReq { token },
+        )
+        .await
+    };
+    let resend = |email: &str| {
+        let req = ResendVerificationEmailReq {
+            email: email.to_owned(),
+        };
+        async move {
+            server_public_req(apis::ui::ApiRequests::def_resend_verification_email(), &req).await
+        }
+    };
+    let verification_token = |email: &Email| {
+        let (_, token) = email.body.split_once("token=").unwrap();
+        token.split_whitespace().next().unwrap().to_owned()
+    };
+
+    // Creating a user sends a verification link, and they can't login until it's used
+    let u1_id = match server_auth_req(
+        apis::ui::ApiRequests::def_create_user(),
+        &admin_jwt,
+        &u1_details(&u1.email),
+    )
+    .await
+    {
+        CreateUserResp::Success(id) => id,
+        CreateUserResp::WeakPassword(_) => panic!("weak password"),
+    };
+    let emails = sent_emails(&mail_file);
+    assert_eq!(emails.len(), 1);
+    assert_eq!(emails[0].to, u1.email);
+    assert!(matches!(
+        login(u1.clone()).await,
+        apis::ui::LoginResp::EmailNotVerified
+    ));
+
+    // Resends are throttled
+    resend(&u1.email).await;
+    assert_eq!(sent_emails(&mail_file).len(), 1);
+    db.execute("update app_user set verification_sent_at = now() - interval '1 hour'")
+        .await;
+    resend(&u1.email).await;
+    let emails = sent_emails(&mail_file);
+    assert_eq!(emails.len(), 2);
+
+    assert!(matches!(
+        verify_email("not-a-token".to_owned()).await,
+        VerifyEmailResp::InvalidToken
+    ));
+    let token = verification_token(&emails[1]);
+    assert!(matches!(
+        verify_email(token.clone()).await,
+        VerifyEmailResp::Verified
+    ));
+    assert!(matches!(
+        login(u1.clone()).await,
+        apis::ui::LoginResp::Tokens(_)
+    ));
+
+    // Verified users aren't sent more links
+    db.execute("update app_user set verification_sent_at = now() - interval '1 hour'")
+        .await;
+    resend(&u1.email).await;
+    assert_eq!(sent_emails(&mail_file).len(), 2);
+
+    // Changing the email address requires it to be verified again, and
+    // links for the old address no longer work
+    let resp = server_auth_req(
+        apis::ui::ApiRequests::def_update_user(),
+        &admin_jwt,
+        &WithId {
+            id: u1_id,
+            value: u1_details("pat2@test.com"),
+        },
+    )
+    .await;
+    assert!(matches!(resp, UpdateUserResp::Success));
+    let emails = sent_emails(&mail_file);
+    assert_eq!(emails.len(), 3);
+    assert_eq!(emails[2].to, "pat2@test.com");
+    let u1 = LoginReq {
+        email: "pat2@test.com".to_owned(),
+        ..u1
+    };
+    assert!(matches!(
+        login(u1.clone()).await,
+        apis::ui::LoginResp::EmailNotVerified
+    ));
+    assert!(matches!(
+        verify_email(token).await,
+        VerifyEmailResp::InvalidToken
+    ));
+    assert!(matches!(
+        verify_email(verification_token(&emails[2])).await,
+        VerifyEmailResp::Verified
+    ));
+    assert!(matches!(
+        login(u1.clone()).await,
+        apis::ui::LoginResp::Tokens(_)
+    ));
+
+    oserver.shutdown().await.unwrap();
+    db.cleanup().await;
+}
+
+#[test]
+fn totp_rfc6238_vectors() {
+    let secret = b"12345678901234567890";
+    assert_eq!(totp::code(secret, 59 / 30), "287082");
+    assert_eq!(totp::code(secret, 1111111109 / 30), "081804");
+    assert_eq!(totp::code(secret, 1234567890 / 30), "005924");
+    assert_eq!(totp::code(secret, 2000000000 / 30), "279037");
+}
 
+#[tokio::test]
+async fn server_totp_login() {
+    let mut db = DbTestEnv::new().await;
+    let oserver = OServer::spawn(AppState::new(test_server_config(), db.pool.clone()));
+
+    let u1 = create_test_user_joe(&mut db).await;
     let u1_jwt = login_user(&u1).await;
+    let now = || {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+    };
+    let login_totp = |challenge_token: &str, code: &str| {
+        let req = LoginTotpReq {
+            challenge_token: challenge_token.to_owned(),
+            code: code.to_owned(),
+        };
+        async move { server_public_req(apis::ui::ApiRequests::def_login_totp(), &req).await }
+    };
+
+    // Enrol, confirming with a first code
+    let enrolment = match server_auth_req(
+        apis::ui::ApiRequests::def_begin_totp_enrolment(),
+        &u1_jwt,
+        &(),
+    )
+    .await
+    {
+        BeginTotpEnrolmentResp::Enrolment(enrolment) => enrolment,
+        BeginTotpEnrolmentResp::AlreadyEnrolled => panic!("unexpected enrolment"),
+    };
+    assert!(enrolment
+        .otpauth_uri
+        .starts_with("otpauth://totp/Protoapp:joe%40test.com?secret="));
+    let confirm = |code: String| {
+        let u1_jwt = u1_jwt.clone();
+        async move {
+            server_auth_req(
+                apis::ui::ApiRequests::def_confirm_totp_enrolment(),
+                &u1_jwt,
+                &ConfirmTotpEnrolmentReq { code },
+            )
+            .await
+        }
+    };
+    assert!(matches!(
+        confirm("abcdef".to_owned()).await,
+        ConfirmTotpEnrolmentResp::InvalidCode
+    ));
+    let t0 = now();
+    let recovery_codes = match confirm(totp::code_at(&enrolment.secret, t0).unwrap()).await {
+        ConfirmTotpEnrolmentResp::RecoveryCodes(codes) => codes,
+        _ => panic!("enrolment not confirmed"),
+    };
+    assert_eq!(recovery_codes.len(), 10);
+    assert!(matches!(
+        server_auth_req(
+            apis::ui::ApiRequests::def_begin_totp_enrolment(),
+            &u1_jwt,
+            &()
+        )
+        .await,
+        BeginTotpEnrolmentResp::AlreadyEnrolled
+    ));
+
+    // The password alone is no longer enough to login
+    let login = || async { server_public_req(apis::ui::ApiRequests::def_login(), &u1).await };
+    let challenge = match login().await {
+        apis::ui::LoginResp::TotpRequired(challenge) => challenge,
+        _ => panic!("expected a totp challenge"),
+    };
+    assert!(matches!(
+        login_totp("not-a-challenge", "123456").await,
+        LoginTotpResp::InvalidChallenge
+    ));
+    assert!(matches!(
+        login_totp(&challenge, "abcdef").await,
+        LoginTotpResp::InvalidCode
+    ));
+
+    // Codes can't be replayed
+    assert!(matches!(
+        login_totp(&challenge, &totp::code_at(&enrolment.secret, t0).unwrap()).await,
+        LoginTotpResp::InvalidCode
+    ));
+    assert!(matches!(
+        login_totp(
+            &challenge,
+            &totp::code_at(&enrolment.secret, t0 + 30).unwrap()
+        )
+        .await,
+        LoginTotpResp::Tokens(_)
+    ));
+
+    // Recovery codes work once each, however they are formatted
+    let recovery_code = recovery_codes[0].to_uppercase().replace('-', " ");
+    assert!(matches!(
+        login_totp(&challenge, &recovery_code).await,
+        LoginTotpResp::Tokens(_)
+    ));
+    assert!(matches!(
+        login_totp(&challenge, &recovery_codes[0]).await,
+        LoginTotpResp::InvalidCode
+    ));
+    assert!(matches!(
+        login_totp(&challenge, &recovery_codes[1]).await,
+        LoginTotpResp::Tokens(_)
+    ));
+
+    oserver.shutdown().await.unwrap();
+    db.cleanup().await;
+}
+
+#[tokio::test]
+async fn server_login_lockout() {
+    let mut db = DbTestEnv::new().await;
+    let config = ServerConfig {
+        login_backoff_after_failures: 2,
+        login_backoff_base_secs: 60,
+        login_backoff_max_secs: 60,
+        login_lockout_failures: 4,
+        login_lockout_secs: 600,
+        ..test_server_config()
+    };
+    let oserver = OServer::spawn(AppState::new(config, db.pool.clone()));
+
+    let u1 = create_test_user_joe(&mut db).await;
+    let admin = create_test_user_sarah(&mut db).await;
+    let admin_jwt = login_user(&admin).await;
+    let u1_id: AppUserId = DbKey::from_string("U-1".to_owned());
+    let login = |email: &str, password: &str| {
+        let req = LoginReq {
+            email: email.to_owned(),
+            password: password.to_owned(),
+        };
+        async move { server_public_req(apis::ui::ApiRequests::def_login(), &req).await }
+    };
+    let is_throttled = |resp: &apis::ui::LoginResp, max_secs: u32| matches!(resp, apis::ui::LoginResp::TooManyAttempts(secs) if *secs > 0 && *secs <= max_secs);
+    let skip_backoff = "update login_failure set last_failed_at = now() - interval '2 minutes'";
+
+    // Unknown addresses are throttled the same as existing accounts
+    for email in [&u1.email, "mike@test.com"] {
+        for _ in 0..2 {
+            assert!(matches!(
+                login(email, "xxxxxx").await,
+                apis::ui::LoginResp::InvalidCredentials
+            ));
+        }
+        assert!(is_throttled(&login(email, "xxxxxx").await, 60));
+    }
+
+    // Even the correct password is refused during the backoff, including
+    // for variations of the address
+    assert!(is_throttled(&login(&u1.email, &u1.password).await, 60));
+    assert!(is_throttled(
+        &login("JOE@test.com ", &u1.password).await,
+        60
+    ));
+
+    // Once the delay has passed attempts are accepted again, until the
+    // account is locked
+    db.execute(skip_backoff).await;
+    assert!(matches!(
+        login(&u1.email, "xxxxxx").await,
+        apis::ui::LoginResp::InvalidCredentials
+    ));
+    db.execute(skip_backoff).await;
+    assert!(matches!(
+        login(&u1.email, "xxxxxx").await,
+        apis::ui::LoginResp::InvalidCredentials
+    ));
+    db.execute(skip_backoff).await;
+    assert!(is_throttled(&login(&u1.email, &u1.password).await, 600));
+
+    // Only admins can unlock an account
+    let u2 = create_test_user(&mut db, "U-3", "Pat", "pat@test.com", "sukpepolup", false).await;
     let u2_jwt = login_user(&u2).await;
+    let resp = server_req(
+        apis::ui::ApiRequests::def_unlock_user(),
+        Some(&u2_jwt),
+        &u1_id,
+    )
+    .await;
+    assert_eq!(resp.status(), 403);
+    assert!(is_throttled(&login(&u1.email, &u1.password).await, 600));
 
-    send_message(&u1_jwt, "A first message").await;
-    send_message(&u1_jwt, "Another message").await;
-    send_message(&u1_jwt, "Still going").await;
+    let _: Unit =
+        server_auth_req(apis::ui::ApiRequests::def_unlock_user(), &admin_jwt, &u1_id).await;
+    let _ = login_user(&u1).await;
 
-    // Check page 1
-    let m = recent_messages(&u2_jwt, 0, 2).await;
-    assert_eq!(m.current_offset, 0);
-    assert_eq!(m.total_count, 3);
-    assert_eq!(m.items.len(), 2);
-    assert_eq!(m.items.first().unwrap().message, "Still going");
-    assert_eq!(m.items.first().unwrap().user_fullname, "Joe");
-    assert_eq!(m.items.get(1).unwrap().message, "Another message");
+    // A successful login resets the count
+    for _ in 0..2 {
+        assert!(matches!(
+            login(&u1.email, "xxxxxx").await,
+            apis::ui::LoginResp::InvalidCredentials
+        ));
+        db.execute(skip_backoff).await;
+    }
+    let _ = login_user(&u1).await;
+    for _ in 0..2 {
+        assert!(matches!(
+            login(&u1.email, "xxxxxx").await,
+            apis::ui::LoginResp::InvalidCredentials
+        ));
+    }
 
-    // Check page 2
-    let m = recent_messages(&u2_jwt, 2, 2).await;
-    assert_eq!(m.current_offset, 2);
-    assert_eq!(m.total_count, 3);
-    assert_eq!(m.items.len(), 1);
-    assert_eq!(m.items.first().unwrap().message, "A first message");
+    // Failures are forgotten once the lockout has passed without another,
+    // so attempts with ever new addresses don't accumulate
+    db.execute("update login_failure set last_failed_at = now() - interval '11 minutes'")
+        .await;
+    assert!(matches!(
+        login("kim@test.com", "xxxxxx").await,
+        apis::ui::LoginResp::InvalidCredentials
+    ));
+    let emails: Vec<String> = sqlx::query_scalar("select email from login_failure")
+        .fetch_all(&db.pool)
+        .await
+        .unwrap();
+    assert_eq!(emails, vec!["kim@test.com"]);
 
     oserver.shutdown().await.unwrap();
     db.cleanup().await;
 }
 
 #[tokio::test]
-async fn server_user_crud() {
+async fn server_asymmetric_jwt() {
     let mut db = DbTestEnv::new().await;
-    let oserver = OServer::spawn(AppState::new(test_server_config(), db.pool.clone()));
+    let config = ServerConfig {
+        jwt_access_signing_key: Some(JwtKey {
+            kid: "k2".to_owned(),
+            algorithm: JwtAlgorithm::Eddsa,
+            public_key_pem_file: test_key_file("ed25519-public.pem"),
+            private_key_pem_file: Some(test_key_file("ed25519-private.pem")),
+        }),
+        jwt_access_verification_keys: vec![JwtKey::new(
+            "k1".to_owned(),
+            JwtAlgorithm::Rs256,
+            test_key_file("rsa-public.pem"),
+        )],
+        ..test_server_config()
+    };
+    let oserver = OServer::spawn(AppState::new(config.clone(), db.pool.clone()));
 
     let u1 = create_test_user_joe(&mut db).await;
-    let u2 = create_test_user_sarah(&mut db).await;
 
-    let u1_jwt = get_login_tokens(server_public_req(apis::ui::ApiRequests::def_login(), &u1).await)
-        .unwrap()
-        .access_jwt;
-    let u2_jwt = get_login_tokens(server_public_req(apis::ui::ApiRequests::def_login(), &u2).await)
-        .unwrap()
-        .access_jwt;
+    // Both keys are published
+    {
+        let resp = server_public_req(apis::ui::ApiRequests::def_jwks(), &()).await;
+        assert_eq!(resp.keys.len(), 2);
+        assert_eq!(resp.keys[0]["kid"], "k2");
+        assert_eq!(resp.keys[0]["kty"], "OKP");
+        assert_eq!(resp.keys[1]["kid"], "k1");
+        assert_eq!(resp.keys[1]["kty"], "RSA");
+        assert_eq!(resp.keys[1]["e"], "AQAB");
+    }
 
-    // u1 is not an admin, so shouldn't be allowed to create new users
+    // Access tokens are signed with the signing key
     {
+        let access_jwt = login_user(&u1).await;
+        let header = jsonwebtoken::decode_header(&access_jwt).unwrap();
+        assert_eq!(header.kid, Some("k2".to_owned()));
+        assert_eq!(header.alg, Algorithm::EdDSA);
         let http_resp = server_req(
-            apis::ui::ApiRequests::def_create_user(),
-            Some(&u1_jwt),
-            &apis::ui::UserDetails {
-                fullname: "Austin".to_owned(),
-                email: "austin@mycompany.org".to_owned(),
-                is_admin: false,
-                password: "sukpepolup".to_owned(),
-            },
+            apis::ui::ApiRequests::def_who_am_i(),
+            Some(&access_jwt),
+            &(),
         )
         .await;
-        assert_eq!(http_resp.status(), 403);
+        assert_eq!(http_resp.status(), 200);
     }
 
-    // u2 is an admin, so can create new users
+    // Tokens signed by a verification key are accepted
     {
+        let key = EncodingKey::from_rsa_pem(
+            std::fs::read_to_string(test_key_file("rsa-private.pem"))
+                .unwrap()
+                .as_bytes(),
+        )
+        .unwrap();
+        let access_jwt = test_access_jwt(Algorithm::RS256, Some("k1"), &key);
         let http_resp = server_req(
-            apis::ui::ApiRequests::def_create_user(),
-            Some(&u2_jwt),
-            &apis::ui::UserDetails {
-                fullname: "Austin".to_owned(),
-                email: "austin@mycompany.org".to_owned(),
-                is_admin: false,
-                password: "sukpepolup".to_owned(),
-            },
+            apis::ui::ApiRequests::def_who_am_i(),
+            Some(&access_jwt),
+            &(),
         )
         .await;
         assert_eq!(http_resp.status(), 200);
+
+        // but not if the kid doesn't match the key
+        let access_jwt = test_access_jwt(Algorithm::RS256, Some("k2"), &key);
+        let http_resp = server_req(
+            apis::ui::ApiRequests::def_who_am_i(),
+            Some(&access_jwt),
+            &(),
+        )
+        .await;
+        assert_eq!(http_resp.status(), 401);
     }
 
-    // and can query existing users
+    // Tokens signed with the shared secret are no longer accepted
     {
-        let resp = server_auth_req(
-            apis::ui::ApiRequests::def_query_users(),
-            &u2_jwt,
-            &apis::ui::QueryUsersReq {
-                page: PageReq {
-                    offset: 0,
-                    limit: 100,
-                },
-            },
+        let key = EncodingKey::from_secret(config.jwt_access_secret.as_bytes());
+        let access_jwt = test_access_jwt(Algorithm::HS256, None, &key);
+        let http_resp = server_req(
+            apis::ui::ApiRequests::def_who_am_i(),
+            Some(&access_jwt),
+            &(),
         )
         .await;
-        assert_eq!(resp.items.len(), 3);
+        assert_eq!(http_resp.status(), 401);
     }
 
     oserver.shutdown().await.unwrap();
     db.cleanup().await;
 }
 
-async fn send_message(jwt: &str, message: &str) {
-    let _ = server_auth_req(
-        apis::ui::ApiRequests::def_new_message(),
-        jwt,
-        &apis::ui::NewMessageReq {
-            message: message.to_owned(),
+#[tokio::test]
+async fn server_jwt_claim_validation() {
+    let mut db = DbTestEnv::new().await;
+    let mut config = test_server_config();
+    config.jwt_accepted_audiences = vec!["other.link".to_owned()];
+    let oserver = OServer::spawn(AppState::new(config.clone(), db.pool.clone()));
+
+    let _u1 = create_test_user_joe(&mut db).await;
+    let u2 = create_test_user_sarah(&mut db).await;
+    let key = EncodingKey::from_secret(config.jwt_access_secret.as_bytes());
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as usize;
+
+    let who_am_i_status = |claims: serde_json::Value| {
+        let access_jwt = jsonwebtoken::encode(&Header::default(), &claims, &key).unwrap();
+        async move {
+            server_req(
+                apis::ui::ApiRequests::def_who_am_i(),
+                Some(&access_jwt),
+                &(),
+            )
+            .await
+            .status()
+        }
+    };
+    let claims = serde_json::to_value(test_access_claims()).unwrap();
+    let with_claim = |name: &str, value: serde_json::Value| {
+        let mut claims = claims.clone();
+        claims[name] = value;
+        claims
+    };
+
+    // Accepted tokens
+    assert_eq!(who_am_i_status(claims.clone()).await, 200);
+    assert_eq!(
+        who_am_i_status(with_claim("aud", "other.link".into())).await,
+        200
+    );
+    assert_eq!(
+        who_am_i_status(with_claim("nbf", (now + 30).into())).await,
+        200
+    );
+
+    // Rejected tokens
+    assert_eq!(
+        who_am_i_status(with_claim("iss", "elsewhere.link".into())).await,
+        401
+    );
+    assert_eq!(
+        who_am_i_status(with_claim("aud", "elsewhere.link".into())).await,
+        401
+    );
+    assert_eq!(
+        who_am_i_status(with_claim("nbf", (now + 300).into())).await,
+        401
+    );
+    assert_eq!(
+        who_am_i_status(with_claim("iat", (now + 300).into())).await,
+        401
+    );
+    assert_eq!(
+        who_am_i_status(with_claim("exp", (now - 300).into())).await,
+        401
+    );
+    let mut without_aud = claims.clone();
+    without_aud.as_object_mut().unwrap().remove("aud");
+    assert_eq!(who_am_i_status(without_aud).await, 401);
+
+    // Refresh tokens are validated too
+    let resp: reqwest::Response = server_req(
+        apis::ui::ApiRequests::def_refresh(),
+        None,
+        &RefreshReq {
+            refresh_token: Some("not-a-jwt".to_owned()),
         },
     )
     .await;
-}
+    assert_eq!(resp.status(), 200);
 
-async fn recent_messages(jwt: &str, offset: u64, limit: u64) -> Paginated<Message> {
-    server_auth_req(
-        apis::ui::ApiRequests::def_recent_messages(),
-        jwt,
-        &apis::ui::RecentMessagesReq {
-            page: PageReq { offset, limit },
-        },
+    // Each reason is counted separately
+    let admin_jwt = login_user(&u2).await;
+    let counts = server_auth_req(
+        apis::ui::ApiRequests::def_jwt_failure_counts(),
+        &admin_jwt,
+        &(),
     )
-    .await
+    .await;
+    let count = |counts: &Vec<JwtFailureCount>, reason: &str| {
+        counts.iter().find(|c| c.reason == reason).unwrap().count
+    };
+    assert_eq!(count(&counts.access, "invalid_issuer"), 1);
+    assert_eq!(count(&counts.access, "invalid_audience"), 1);
+    assert_eq!(count(&counts.access, "not_yet_valid"), 1);
+    assert_eq!(count(&counts.access, "issued_in_future"), 1);
+    assert_eq!(count(&counts.access, "expired"), 1);
+    assert_eq!(count(&counts.access, "missing_claim"), 1);
+    assert_eq!(count(&counts.access, "malformed"), 0);
+    assert_eq!(count(&counts.refresh, "malformed"), 1);
+
+    // and only admins can see them
+    let user_jwt = jsonwebtoken::encode(&Header::default(), &claims, &key).unwrap();
+    let resp = server_req(
+        apis::ui::ApiRequests::def_jwt_failure_counts(),
+        Some(&user_jwt),
+        &(),
+    )
+    .await;
+    assert_eq!(resp.status(), 403);
+
+    oserver.shutdown().await.unwrap();
+    db.cleanup().await;
 }
 
-fn is_valid_login(resp: &apis::ui::LoginResp) -> bool {
-    match resp {
-        apis::ui::LoginResp::Tokens(_) => true,
-        apis::ui::LoginResp::InvalidCredentials => false,
+fn test_access_claims() -> AccessClaims {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as usize;
+    AccessClaims {
+        iss: "adl-protoapp.link".to_owned(),
+        aud: "adl-protoapp.link".to_owned(),
+        sub: "U-1".to_owned(),
+        exp: now + 300,
+        nbf: now,
+        iat: now,
+        roles: vec![ROLE_USER.to_owned()],
+        sid: None,
+        act: None,
+        org: None,
     }
 }
 
-fn is_valid_refresh(resp: &apis::ui::RefreshResp) -> bool {
-    matches!(resp, apis::ui::RefreshResp::AccessToken(_))
+fn test_access_jwt(alg: Algorithm, kid: Option<&str>, key: &EncodingKey) -> String {
+    let mut header = Header::new(alg);
+    header.kid = kid.map(|kid| kid.to_owned());
+    jsonwebtoken::encode(&header, &test_access_claims(), key).unwrap()
+}
+
+#[tokio::test]
+async fn server_user_profile() {
+    let mut db = DbTestEnv::new().await;
+    let oserver = OServer::spawn(AppState::new(test_server_config(), db.pool.clone()));
+
+    let u1 = create_test_user_joe(&mut db).await;
+    let u1_jwt = login_user(&u1).await;
+
+    let resp = server_auth_req(apis::ui::ApiRequests::def_who_am_i(), &u1_jwt, &()).await;
+    assert_eq!(resp.value.fullname, "Joe");
+    assert_eq!(resp.value.email, "joe@test.com");
+    assert!(resp.value.roles.is_empty());
+
+    oserver.shutdown().await.unwrap();
+    db.cleanup().await;
+}
+
+#[tokio::test]
+async fn server_messages() {
+    let mut db = DbTestEnv::new().await;
+    let oserver = OServer::spawn(AppState::new(test_server_config(), db.pool.clone()));
+
+    let u1 = create_test_user_joe(&mut db).await;
+    let u2 = create_test_user_sarah(&mut db).await;
+
+    let u1_jwt = login_user(&u1).await;
+    let u2_jwt = login_user(&u2).await;
+
+    send_message(&u1_jwt, "A first message").await;
+    send_message(&u1_jwt, "Another message").await;
+    send_message(&u1_jwt, "Still going").await;
+
+    // Check page 1
+    let m = recent_messages(&u2_jwt, 0, 2).await;
+    assert_eq!(m.current_offset, 0);
+    assert_eq!(m.total_count, 3);
+    assert_eq!(m.items.len(), 2);
+    assert_eq!(m.items.first().unwrap().message, "Still going");
+    assert_eq!(m.items.first().unwrap().user_fullname, "Joe");
+    assert_eq!(m.items.get(1).unwrap().message, "Another message");
+
+    // Check page 2
+    let m = recent_messages(&u2_jwt, 2, 2).await;
+    assert_eq!(m.current_offset, 2);
+    assert_eq!(m.total_count, 3);
+    assert_eq!(m.items.len(), 1);
+    assert_eq!(m.items.first().unwrap().message, "A first message");
+
+    oserver.shutdown().await.unwrap();
+    db.cleanup().await;
+}
+
+#[tokio::test]
+async fn server_self_service_profile() {
+    let mut db = DbTestEnv::new().await;
+    let mail_file = test_mail_file("self-service-profile");
+    let config = ServerConfig {
+        mailer: MailerConfig::File(mail_file.clone()),
+        ..test_server_config()
+    };
+    let oserver = OServer::spawn(AppState::new(config, db.pool.clone()));
+
+    let u1 = create_test_user_joe(&mut db).await;
+    let _ = create_test_user_sarah(&mut db).await;
+    let login = |req: LoginReq| async move {
+        get_login_tokens(server_public_req(apis::ui::ApiRequests::def_login(), &req).await).unwrap()
+    };
+    let t1 = login(u1.clone()).await;
+    let t2 = login(u1.clone()).await;
+    let update_profile = |email: &str, current_password: Option<&str>| {
+        let req = UpdateProfileReq {
+            fullname: "Joseph".to_owned(),
+            email: email.to_owned(),
+            current_password: current_password.map(|p| p.to_owned()),
+        };
+        let jwt = t1.access_jwt.clone();
+        async move { server_auth_req(apis::ui::ApiRequests::def_update_profile(), &jwt, &req).await }
+    };
+    let change_password = |current_password: &str, new_password: &str| {
+        let req = ChangePasswordReq {
+            current_password: current_password.to_owned(),
+            new_password: new_password.to_owned(),
+        };
+        let jwt = t1.access_jwt.clone();
+        async move { server_auth_req(apis::ui::ApiRequests::def_change_password(), &jwt, &req).await }
+    };
+
+    // Users can change their own name
+    assert!(matches!(
+        update_profile("joe@test.com", None).await,
+        UpdateProfileResp::Success
+    ));
+    let user = server_auth_req(apis::ui::ApiRequests::def_who_am_i(), &t1.access_jwt, &()).await;
+    assert_eq!(user.value.fullname, "Joseph");
+    assert!(sent_emails(&mail_file).is_empty());
+
+    // A change of case is the same address, which needs no password and
+    // stays verified
+    assert!(matches!(
+        update_profile("Joe@Test.com", None).await,
+        UpdateProfileResp::Success
+    ));
+    let user = server_auth_req(apis::ui::ApiRequests::def_who_am_i(), &t1.access_jwt, &()).await;
+    assert_eq!(user.value.email, "joe@test.com");
+    assert!(sent_emails(&mail_file).is_empty());
+    let verified: bool =
+        sqlx::query_scalar("select verified_at is not null from app_user where id = 'U-1'")
+            .fetch_one(&db.pool)
+            .await
+            .unwrap();
+    assert!(verified);
+
+    // And email address, given their current password, which must be
+    // verified again
+    assert!(matches!(
+        update_profile("joseph@test.com", None).await,
+        UpdateProfileResp::InvalidCredentials
+    ));
+    assert!(matches!(
+        update_profile("joseph@test.com", Some("wrong-password")).await,
+        UpdateProfileResp::InvalidCredentials
+    ));
+    assert!(matches!(
+        update_profile("sarah@test.com", Some(&u1.password)).await,
+        UpdateProfileResp::EmailInUse
+    ));
+    assert!(matches!(
+        update_profile("joseph@test.com", Some(&u1.password)).await,
+        UpdateProfileResp::Success
+    ));
+    let emails = sent_emails(&mail_file);
+    assert_eq!(emails.len(), 1);
+    assert_eq!(emails[0].to, "joseph@test.com");
+    let verified: bool =
+        sqlx::query_scalar("select verified_at is not null from app_user where id = 'U-1'")
+            .fetch_one(&db.pool)
+            .await
+            .unwrap();
+    assert!(!verified);
+
+    // Changing the password needs the current one, and a new one that
+    // follows the policy
+    assert!(matches!(
+        change_password("wrong-password", "correct-horse").await,
+        ChangePasswordResp::InvalidCredentials
+    ));
+    assert!(matches!(
+        change_password(&u1.password, "short").await,
+        ChangePasswordResp::WeakPassword(_)
+    ));
+    assert!(matches!(
+        change_password(&u1.password, "correct-horse").await,
+        ChangePasswordResp::Success
+    ));
+
+    // Only the session that changed the password continues
+    assert!(refresh_tokens(&t2.refresh_jwt).await.is_none());
+    assert!(refresh_tokens(&t1.refresh_jwt).await.is_some());
+    let resp = server_public_req(
+        apis::ui::ApiRequests::def_login(),
+        &LoginReq {
+            email: "joseph@test.com".to_owned(),
+            password: "correct-horse".to_owned(),
+        },
+    )
+    .await;
+    assert!(is_valid_login(&resp));
+
+    oserver.shutdown().await.unwrap();
+    db.cleanup().await;
+}
+
+#[tokio::test]
+async fn server_invitations() {
+    let mut db = DbTestEnv::new().await;
+    let mail_file = test_mail_file("invitations");
+    let config = ServerConfig {
+        mailer: MailerConfig::File(mail_file.clone()),
+        ..test_server_config()
+    };
+    let oserver = OServer::spawn(AppState::new(config, db.pool.clone()));
+
+    let u1 = create_test_user_joe(&mut db).await;
+    let admin = create_test_user_sarah(&mut db).await;
+    let admin_jwt = login_user(&admin).await;
+    let u1_jwt = login_user(&u1).await;
+    let invite = |email: &str, roles: Vec<RoleId>| {
+        let req = CreateInvitationReq {
+            email: email.to_owned(),
+            roles,
+            expires_in_days: 7,
+        };
+        let admin_jwt = admin_jwt.clone();
+        async move {
+            server_auth_req(
+                apis::ui::ApiRequests::def_create_invitation(),
+                &admin_jwt,
+                &req,
+            )
+            .await
+        }
+    };
+    let signup = |token: &str, password: &str| {
+        let req = SignupReq {
+            token: token.to_owned(),
+            fullname: "Newbie".to_owned(),
+            password: password.to_owned(),
+        };
+        async move { server_public_req(apis::ui::ApiRequests::def_signup(), &req).await }
+    };
+    let query_invitations = |status: Option<InvitationStatus>| {
+        let req = QueryInvitationsReq {
+            page: PageReq::new(),
+            status,
+        };
+        let admin_jwt = admin_jwt.clone();
+        async move {
+            server_auth_req(
+                apis::ui::ApiRequests::def_query_invitations(),
+                &admin_jwt,
+                &req,
+            )
+            .await
+        }
+    };
+    let invitation_token = |email: &Email| {
+        let (_, token) = email.body.split_once("token=").unwrap();
+        token.split_whitespace().next().unwrap().to_owned()
+    };
+
+    // Only admins can invite
+    let req = CreateInvitationReq {
+        email: "newbie@test.com".to_owned(),
+        roles: vec![],
+        expires_in_days: 7,
+    };
+    let resp = server_req(
+        apis::ui::ApiRequests::def_create_invitation(),
+        Some(&u1_jwt),
+        &req,
+    )
+    .await;
+    assert_eq!(resp.status(), 403);
+
+    // Existing users can't be invited
+    assert!(invite(&u1.email, vec![]).await == CreateInvitationResp::EmailInUse);
+
+    // Invitations must expire, within the configured maximum
+    for expires_in_days in [0, ServerConfig::def_invitation_max_expiry_days() + 1] {
+        let req = CreateInvitationReq {
+            email: "newbie@test.com".to_owned(),
+            roles: vec![],
+            expires_in_days,
+        };
+        let resp = server_auth_req(
+            apis::ui::ApiRequests::def_create_invitation(),
+            &admin_jwt,
+            &req,
+        )
+        .await;
+        assert!(resp == CreateInvitationResp::InvalidExpiry);
+    }
+
+    let invitation_id = match invite("newbie@test.com", vec![]).await {
+        CreateInvitationResp::Success(id) => id,
+        _ => panic!("invitation failed"),
+    };
+    let emails = sent_emails(&mail_file);
+    assert_eq!(emails.len(), 1);
+    assert_eq!(emails[0].to, "newbie@test.com");
+    let token = invitation_token(&emails[0]);
+
+    let pending = query_invitations(Some(InvitationStatus::Pending)).await;
+    assert_eq!(pending.total_count, 1);
+    assert_eq!(pending.items[0].id.0, invitation_id.0);
+    assert_eq!(pending.items[0].value.created_by.0, "U-2");
+
+    assert!(signup("not-a-token", "newpassword").await == SignupResp::InvalidToken);
+    // A password that breaks the policy doesn't use up the invitation
+    assert!(
+        signup(&token, "short").await
+            == SignupResp::WeakPassword(vec![PasswordViolation::TooShort(8)])
+    );
+    // Nor does a signup that fails partway
+    db.execute("CREATE FUNCTION fail_insert() RETURNS trigger AS $$ BEGIN RAISE EXCEPTION 'insert failed'; END $$ LANGUAGE plpgsql").await;
+    db.execute("CREATE TRIGGER fail_user_insert BEFORE INSERT ON app_user FOR EACH ROW EXECUTE FUNCTION fail_insert()").await;
+    let resp = server_req(
+        apis::ui::ApiRequests::def_signup(),
+        None,
+        &SignupReq {
+            token: token.clone(),
+            fullname: "Newbie".to_owned(),
+            password: "newpassword".to_owned(),
+        },
+    )
+    .await;
+    assert_eq!(resp.status(), 500);
+    db.execute("DROP TRIGGER fail_user_insert ON app_user")
+        .await;
+    let pending = query_invitations(Some(InvitationStatus::Pending)).await;
+    assert_eq!(pending.total_count, 1);
+    let tokens = match signup(&token, "newpassword").await {
+        SignupResp::Tokens(tokens) => tokens,
+        _ => panic!("signup failed"),
+    };
+    let user = server_auth_req(
+        apis::ui::ApiRequests::def_who_am_i(),
+        &tokens.access_jwt,
+        &(),
+    )
+    .await;
+    assert_eq!(user.value.email, "newbie@test.com");
+    assert_eq!(user.value.fullname, "Newbie");
+    assert!(user.value.roles.is_empty());
+    assert!(refresh_tokens(&tokens.refresh_jwt).await.is_some());
+
+    // The new user can login, without verifying their email address
+    let login = LoginReq {
+        email: "newbie@test.com".to_owned(),
+        password: "newpassword".to_owned(),
+    };
+    assert!(is_valid_login(
+        &server_public_req(apis::ui::ApiRequests::def_login(), &login).await
+    ));
+
+    // Invitations are single use
+    assert!(signup(&token, "otherpassword").await == SignupResp::InvalidToken);
+    let used = query_invitations(Some(InvitationStatus::Used)).await;
+    assert_eq!(used.total_count, 1);
+    assert_eq!(used.items[0].value.user_id.as_ref().unwrap().0, user.id.0);
+
+    // Invitations carry their role
+    let unknown_role: RoleId = DbKey::from_string("G-unknown".to_owned());
+    assert!(
+        invite("admin@test.com", vec![unknown_role]).await == CreateInvitationResp::InvalidRole
+    );
+    let admin_role: RoleId = DbKey::from_string("G-admin".to_owned());
+    let _ = invite("admin@test.com", vec![admin_role]).await;
+    let token = invitation_token(&sent_emails(&mail_file)[1]);
+    let tokens = match signup(&token, "strongpassword").await {
+        SignupResp::Tokens(tokens) => tokens,
+        _ => panic!("signup failed"),
+    };
+    let resp = server_req(
+        apis::ui::ApiRequests::def_query_users(),
+        Some(&tokens.access_jwt),
+        &apis::ui::QueryUsersReq::new(),
+    )
+    .await;
+    assert_eq!(resp.status(), 200);
+
+    // Revoked invitations can't be used
+    let invitation_id = match invite("revoked@test.com", vec![]).await {
+        CreateInvitationResp::Success(id) => id,
+        _ => panic!("invitation failed"),
+    };
+    let token = invitation_token(&sent_emails(&mail_file)[2]);
+    let _: Unit = server_auth_req(
+        apis::ui::ApiRequests::def_revoke_invitation(),
+        &admin_jwt,
+        &invitation_id,
+    )
+    .await;
+    assert!(signup(&token, "newpassword").await == SignupResp::InvalidToken);
+    let revoked = query_invitations(Some(InvitationStatus::Revoked)).await;
+    assert_eq!(revoked.total_count, 1);
+    assert_eq!(revoked.items[0].id.0, invitation_id.0);
+
+    // As are expired ones
+    let _ = invite("expired@test.com", vec![]).await;
+    let token = invitation_token(&sent_emails(&mail_file)[3]);
+    sqlx::query("update invitation set expires_at = now() where email = 'expired@test.com'")
+        .execute(&db.pool)
+        .await
+        .unwrap();
+    assert!(signup(&token, "newpassword").await == SignupResp::InvalidToken);
+    assert_eq!(
+        query_invitations(Some(InvitationStatus::Expired))
+            .await
+            .total_count,
+        1
+    );
+    assert_eq!(
+        query_invitations(Some(InvitationStatus::Pending))
+            .await
+            .total_count,
+        0
+    );
+    assert_eq!(query_invitations(None).await.total_count, 4);
+
+    oserver.shutdown().await.unwrap();
+    db.cleanup().await;
+}
+
+#[tokio::test]
+async fn server_roles() {
+    let mut db = DbTestEnv::new().await;
+    let config = test_server_config();
+    let access_keys = AccessKeys::from_config(&config).unwrap();
+    let oserver = OServer::spawn(AppState::new(config, db.pool.clone()));
+
+    let u1 = create_test_user_joe(&mut db).await;
+    let admin = create_test_user_sarah(&mut db).await;
+    let admin_jwt = login_user(&admin).await;
+    let joe_id: AppUserId = DbKey::from_string("U-1".to_owned());
+    let admin_role: RoleId = DbKey::from_string("G-admin".to_owned());
+    let create_role = |name: &str, permissions: Vec<&str>| {
+        let req = CreateRoleReq {
+            name: name.to_owned(),
+            description: "".to_owned(),
+            permissions: permissions.into_iter().map(|p| p.to_owned()).collect(),
+        };
+        let admin_jwt = admin_jwt.clone();
+        async move { server_auth_req(apis::ui::ApiRequests::def_create_role(), &admin_jwt, &req).await }
+    };
+    let set_user_roles = |user_id: &AppUserId, roles: Vec<RoleId>| {
+        let req = SetUserRolesReq {
+            user_id: user_id.clone(),
+            roles,
+        };
+        let admin_jwt = admin_jwt.clone();
+        async move {
+            server_auth_req(
+                apis::ui::ApiRequests::def_set_user_roles(),
+                &admin_jwt,
+                &req,
+            )
+            .await
+        }
+    };
+    let query_roles = || {
+        let admin_jwt = admin_jwt.clone();
+        async move {
+            server_auth_req(
+                apis::ui::ApiRequests::def_query_roles(),
+                &admin_jwt,
+                &QueryRolesReq::new(),
+            )
+            .await
+        }
+    };
+    let query_users_status = |jwt: String| async move {
+        server_req(
+            apis::ui::ApiRequests::def_query_users(),
+            Some(&jwt),
+            &apis::ui::QueryUsersReq::new(),
+        )
+        .await
+        .status()
+    };
+
+    // Tokens carry the user's roles
+    let claims = decode_access(&access_keys, &admin_jwt).unwrap();
+    assert_eq!(claims.roles, vec!["user", "admin"]);
+    let u1_jwt = login_user(&u1).await;
+    let claims = decode_access(&access_keys, &u1_jwt).unwrap();
+    assert_eq!(claims.roles, vec!["user"]);
+    assert_eq!(query_users_status(u1_jwt.clone()).await, 403);
+
+    let moderator = match create_role("moderator", vec!["messages.delete"]).await {
+        CreateRoleResp::Success(role_id) => role_id,
+        CreateRoleResp::NameInUse => panic!("name in use"),
+    };
+    assert!(create_role("moderator", vec![]).await == CreateRoleResp::NameInUse);
+    assert!(create_role("user", vec![]).await == CreateRoleResp::NameInUse);
+
+    let resp: SetRolePermissionsResp = server_auth_req(
+        apis::ui::ApiRequests::def_set_role_permissions(),
+        &admin_jwt,
+        &SetRolePermissionsReq {
+            role_id: moderator.clone(),
+            permissions: vec!["messages.edit".to_owned(), "messages.delete".to_owned()],
+        },
+    )
+    .await;
+    assert!(resp == SetRolePermissionsResp::Success);
+    let roles = query_roles().await;
+    assert_eq!(roles.total_count, 2);
+    assert_eq!(roles.items[0].value.name, "admin");
+    assert_eq!(roles.items[1].value.name, "moderator");
+    assert_eq!(
+        roles.items[1].value.permissions,
+        vec!["messages.delete", "messages.edit"]
+    );
+
+    // Roles take effect on the next token
+    let unknown_user: AppUserId = DbKey::from_string("U-unknown".to_owned());
+    assert!(
+        set_user_roles(&unknown_user, vec![moderator.clone()]).await
+            == SetUserRolesResp::InvalidUser
+    );
+    let unknown_role: RoleId = DbKey::from_string("G-unknown".to_owned());
+    assert!(set_user_roles(&joe_id, vec![unknown_role]).await == SetUserRolesResp::InvalidRole);
+
+    // Concurrent replacements leave one of the sets of roles, not a mix
+    let (r1, r2) = tokio::join!(
+        set_user_roles(&joe_id, vec![moderator.clone()]),
+        set_user_roles(&joe_id, vec![admin_role.clone()]),
+    );
+    assert!(r1 == SetUserRolesResp::Success && r2 == SetUserRolesResp::Success);
+    let role_names = db::user_role_names(&db.pool, &[joe_id.clone()])
+        .await
+        .unwrap();
+    assert_eq!(role_names[&joe_id.0].len(), 1);
+    assert!(
+        set_user_roles(&joe_id, vec![moderator.clone(), admin_role.clone()]).await
+            == SetUserRolesResp::Success
+    );
+    assert_eq!(query_users_status(u1_jwt.clone()).await, 403);
+    let u1_jwt = login_user(&u1).await;
+    let claims = decode_access(&access_keys, &u1_jwt).unwrap();
+    assert_eq!(claims.roles, vec!["user", "admin", "moderator"]);
+    assert_eq!(query_users_status(u1_jwt.clone()).await, 200);
+    let user = server_auth_req(apis::ui::ApiRequests::def_who_am_i(), &u1_jwt, &()).await;
+    assert_eq!(user.value.roles, vec!["admin", "moderator"]);
+
+    // The admin role can't be deleted, but others can
+    let resp: DeleteRoleResp = server_auth_req(
+        apis::ui::ApiRequests::def_delete_role(),
+        &admin_jwt,
+        &admin_role,
+    )
+    .await;
+    assert!(resp == DeleteRoleResp::BuiltinRole);
+    let resp: DeleteRoleResp = server_auth_req(
+        apis::ui::ApiRequests::def_delete_role(),
+        &admin_jwt,
+        &moderator,
+    )
+    .await;
+    assert!(resp == DeleteRoleResp::Success);
+    assert_eq!(query_roles().await.total_count, 1);
+    let users = server_auth_req(
+        apis::ui::ApiRequests::def_query_users(),
+        &admin_jwt,
+        &apis::ui::QueryUsersReq::new(),
+    )
+    .await;
+    assert!(users.items.iter().all(|u| u.value.roles == vec!["admin"]));
+
+    oserver.shutdown().await.unwrap();
+    db.cleanup().await;
+}
+
+#[tokio::test]
+async fn admin_role_migration() {
+    // Admins were flagged on app_user in the baseline schema
+    let mut db = DbTestEnv::new_at_version(30).await;
+    db.execute("INSERT INTO app_user(id,fullname,email,is_admin,hashed_password) VALUES ('U-1', 'Joe', 'joe@test.com', false, 'x'), ('U-2', 'Sarah', 'sarah@test.com', true, 'x')").await;
+
+    // and on invitations before roles were introduced
+    db.migrate(Some(140)).await;
+    db.execute("INSERT INTO invitation(id,email,is_admin,token_hash,created_by,created_at,expires_at) VALUES ('I-1', 'kim@test.com', false, 'h1', 'U-2', now(), now()), ('I-2', 'lee@test.com', true, 'h2', 'U-2', now(), now())").await;
+
+    db.migrate(None).await;
+    let role_names = db::user_role_names(
+        &db.pool,
+        &[
+            DbKey::from_string("U-1".to_owned()),
+            DbKey::from_string("U-2".to_owned()),
+        ],
+    )
+    .await
+    .unwrap();
+    assert!(!role_names.contains_key("U-1"));
+    assert_eq!(role_names["U-2"], vec!["admin"]);
+    let has_flag: bool = sqlx::query_scalar(
+        "select exists (select 1 from information_schema.columns where table_schema = current_schema() and table_name = 'app_user' and column_name = 'is_admin')",
+    )
+    .fetch_one(&db.pool)
+    .await
+    .unwrap();
+    assert!(!has_flag);
+    let invitation_roles: Vec<(String, serde_json::Value)> =
+        sqlx::query_as("select id, roles from invitation order by id")
+            .fetch_all(&db.pool)
+            .await
+            .unwrap();
+    assert_eq!(
+        invitation_roles,
+        vec![
+            ("I-1".to_owned(), serde_json::json!([])),
+            ("I-2".to_owned(), serde_json::json!(["G-admin"])),
+        ]
+    );
+
+    db.cleanup().await;
+}
+
+#[tokio::test]
+async fn server_sessions() {
+    let mut db = DbTestEnv::new().await;
+    let oserver = OServer::spawn(AppState::new(test_server_config(), db.pool.clone()));
+
+    let u1 = create_test_user_joe(&mut db).await;
+    let u2 = create_test_user_sarah(&mut db).await;
+    let joe_id: AppUserId = DbKey::from_string("U-1".to_owned());
+    let login = |req: LoginReq| async move {
+        get_login_tokens(server_public_req(apis::ui::ApiRequests::def_login(), &req).await).unwrap()
+    };
+    let my_sessions = |jwt: String| async move {
+        server_auth_req(apis::ui::ApiRequests::def_my_sessions(), &jwt, &()).await
+    };
+
+    // Each login is a session
+    let t1 = login(u1.clone()).await;
+    let t2 = login(u1.clone()).await;
+    let sessions = my_sessions(t1.access_jwt.clone()).await;
+    assert_eq!(sessions.len(), 2);
+    assert_eq!(sessions.iter().filter(|s| s.value.current).count(), 1);
+    assert!(sessions.iter().all(|s| s.value.client_ip.is_some()));
+    let t1_session = sessions
+        .iter()
+        .find(|s| s.value.current)
+        .unwrap()
+        .id
+        .clone();
+
+    // Refreshing continues the session
+    let t2 = refresh_tokens(&t2.refresh_jwt).await.unwrap();
+    let sessions = my_sessions(t2.access_jwt.clone()).await;
+    assert_eq!(sessions.len(), 2);
+    assert_ne!(sessions[0].id.0, t1_session.0);
+    assert!(sessions[0].value.current);
+
+    // Revoking the other sessions leaves only the caller's
+    let _: Unit = server_auth_req(
+        apis::ui::ApiRequests::def_revoke_other_sessions(),
+        &t1.access_jwt,
+        &(),
+    )
+    .await;
+    assert!(refresh_tokens(&t2.refresh_jwt).await.is_none());
+    let sessions = my_sessions(t1.access_jwt.clone()).await;
+    assert_eq!(sessions.len(), 1);
+    assert_eq!(sessions[0].id.0, t1_session.0);
+
+    // A single session can be revoked, but only by its user
+    let t3 = login(u1.clone()).await;
+    let t3_session = my_sessions(t3.access_jwt.clone())
+        .await
+        .into_iter()
+        .find(|s| s.value.current)
+        .unwrap()
+        .id;
+    let t4 = login(u2.clone()).await;
+    let resp = server_req(
+        apis::ui::ApiRequests::def_revoke_session(),
+        Some(&t4.access_jwt),
+        &t3_session,
+    )
+    .await;
+    assert_eq!(resp.status(), 403);
+    let _: Unit = server_auth_req(
+        apis::ui::ApiRequests::def_revoke_session(),
+        &t1.access_jwt,
+        &t3_session,
+    )
+    .await;
+    assert!(refresh_tokens(&t3.refresh_jwt).await.is_none());
+
+    // Logging out ends the session
+    server_public_req(
+        apis::ui::ApiRequests::def_logout(),
+        &LogoutReq {
+            refresh_token: Some(t4.refresh_jwt.clone()),
+        },
+    )
+    .await;
+    assert!(my_sessions(t4.access_jwt.clone()).await.is_empty());
+
+    // Admins can revoke every session of a user
+    let admin = login(u2.clone()).await;
+    let resp = server_req(
+        apis::ui::ApiRequests::def_revoke_user_sessions(),
+        Some(&t1.access_jwt),
+        &joe_id,
+    )
+    .await;
+    assert_eq!(resp.status(), 403);
+    let _: Unit = server_auth_req(
+        apis::ui::ApiRequests::def_revoke_user_sessions(),
+        &admin.access_jwt,
+        &joe_id,
+    )
+    .await;
+    assert!(refresh_tokens(&t1.refresh_jwt).await.is_none());
+    assert!(my_sessions(t1.access_jwt.clone()).await.is_empty());
+
+    oserver.shutdown().await.unwrap();
+    db.cleanup().await;
+}
+
+#[tokio::test]
+async fn server_user_crud() {
+    let mut db = DbTestEnv::new().await;
+    let oserver = OServer::spawn(AppState::new(test_server_config(), db.pool.clone()));
+
+    let u1 = create_test_user_joe(&mut db).await;
+    let u2 = create_test_user_sarah(&mut db).await;
+
+    let u1_jwt = get_login_tokens(server_public_req(apis::ui::ApiRequests::def_login(), &u1).await)
+        .unwrap()
+        .access_jwt;
+    let u2_jwt = get_login_tokens(server_public_req(apis::ui::ApiRequests::def_login(), &u2).await)
+        .unwrap()
+        .access_jwt;
+
+    // u1 is not an admin, so shouldn't be allowed to create new users
+    {
+        let http_resp = server_req(
+            apis::ui::ApiRequests::def_create_user(),
+            Some(&u1_jwt),
+            &apis::ui::UserDetails {
+                fullname: "Austin".to_owned(),
+                email: "austin@mycompany.org".to_owned(),
+                password: "sukpepolup".to_owned(),
+            },
+        )
+        .await;
+        assert_eq!(http_resp.status(), 403);
+    }
+
+    // u2 is an admin, so can create new users
+    {
+        let http_resp = server_req(
+            apis::ui::ApiRequests::def_create_user(),
+            Some(&u2_jwt),
+            &apis::ui::UserDetails {
+                fullname: "Austin".to_owned(),
+                email: "austin@mycompany.org".to_owned(),
+                password: "sukpepolup".to_owned(),
+            },
+        )
+        .await;
+        assert_eq!(http_resp.status(), 200);
+    }
+
+    // and can query existing users
+    {
+        let resp = server_auth_req(
+            apis::ui::ApiRequests::def_query_users(),
+            &u2_jwt,
+            &apis::ui::QueryUsersReq {
+                page: PageReq {
+                    offset: 0,
+                    limit: 100,
+                },
+            },
+        )
+        .await;
+        assert_eq!(resp.items.len(), 3);
+    }
+
+    oserver.shutdown().await.unwrap();
+    db.cleanup().await;
+}
+
+#[test]
+fn password_policy_rules() {
+    let breached_file = std::env::temp_dir().join(format!(
+        "protoapp-breached-passwords-{}.txt",
+        std::process::id()
+    ));
+    std::fs::write(&breached_file, "password1\nCorrectHorse9!\n").unwrap();
+    let policy = PasswordPolicy {
+        min_length: 10,
+        require_lowercase: true,
+        require_uppercase: true,
+        require_digit: true,
+        require_symbol: true,
+        breached_passwords_file: Some(breached_file.to_str().unwrap().to_owned()),
+        reject_email: true,
+    };
+    let checker = PasswordPolicyChecker::new(&policy).unwrap();
+    let email = "austin@mycompany.org";
+
+    assert!(checker.check("Batt3ry-Staple", email).is_empty());
+
+    // Every broken rule is reported
+    assert!(
+        checker.check("austin", email)
+            == vec![
+                PasswordViolation::TooShort(10),
+                PasswordViolation::NoUppercase,
+                PasswordViolation::NoDigit,
+                PasswordViolation::NoSymbol,
+                PasswordViolation::ContainsEmail,
+            ]
+    );
+    assert!(
+        checker.check("correcthorse9!", email)
+            == vec![PasswordViolation::NoUppercase, PasswordViolation::Breached]
+    );
+    assert!(checker.check("My-Austin-Pa55", email) == vec![PasswordViolation::ContainsEmail]);
+
+    // Missing breached password files are reported
+    let missing = PasswordPolicy {
+        breached_passwords_file: Some("/nonexistent/breached.txt".to_owned()),
+        ..policy
+    };
+    assert!(PasswordPolicyChecker::new(&missing).is_err());
+    std::fs::remove_file(&breached_file).unwrap();
+}
+
+#[tokio::test]
+async fn server_password_policy() {
+    let mut db = DbTestEnv::new().await;
+    let config = ServerConfig {
+        password_policy: PasswordPolicy {
+            require_digit: true,
+            ..ServerConfig::def_password_policy()
+        },
+        ..test_server_config()
+    };
+    let oserver = OServer::spawn(AppState::new(config, db.pool.clone()));
+
+    let admin = create_test_user_sarah(&mut db).await;
+    let admin_jwt = login_user(&admin).await;
+    let details = |password: &str| UserDetails {
+        fullname: "Austin".to_owned(),
+        email: "austin@mycompany.org".to_owned(),
+        password: password.to_owned(),
+    };
+
+    let resp = server_auth_req(
+        apis::ui::ApiRequests::def_create_user(),
+        &admin_jwt,
+        &details("austin"),
+    )
+    .await;
+    assert!(
+        resp == CreateUserResp::WeakPassword(vec![
+            PasswordViolation::TooShort(8),
+            PasswordViolation::NoDigit,
+            PasswordViolation::ContainsEmail,
+        ])
+    );
+    let user_id = match server_auth_req(
+        apis::ui::ApiRequests::def_create_user(),
+        &admin_jwt,
+        &details("sukpepolup1"),
+    )
+    .await
+    {
+        CreateUserResp::Success(id) => id,
+        CreateUserResp::WeakPassword(_) => panic!("weak password"),
+    };
+
+    // Updates are checked too, and leave the user unchanged if rejected
+    let update = |password: &str| WithId {
+        id: user_id.clone(),
+        value: details(password),
+    };
+    let resp = server_auth_req(
+        apis::ui::ApiRequests::def_update_user(),
+        &admin_jwt,
+        &update("sukpepolup"),
+    )
+    .await;
+    assert!(resp == UpdateUserResp::WeakPassword(vec![PasswordViolation::NoDigit]));
+    let _ = login_user(&LoginReq {
+        email: "austin@mycompany.org".to_owned(),
+        password: "sukpepolup1".to_owned(),
+    })
+    .await;
+
+    oserver.shutdown().await.unwrap();
+    db.cleanup().await;
+}
+
+#[tokio::test]
+async fn server_password_rehash() {
+    let mut db = DbTestEnv::new().await;
+    let config = ServerConfig {
+        argon2: Argon2Config {
+            memory_kib: 8192,
+            iterations: 1,
+            parallelism: 1,
+        },
+        ..test_server_config()
+    };
+    let oserver = OServer::spawn(AppState::new(config, db.pool.clone()));
+
+    let u1 = create_test_user_joe(&mut db).await;
+    let stored_hash = || async {
+        sqlx::query_scalar::<_, String>("select hashed_password from app_user where id = 'U-1'")
+            .fetch_one(&db.pool)
+            .await
+            .unwrap()
+    };
+
+    // The test user was hashed with the default parameters, and is
+    // upgraded to the configured ones on login
+    assert!(stored_hash().await.contains("m=19456,t=2,p=1"));
+    let _ = login_user(&u1).await;
+    let upgraded = stored_hash().await;
+    assert!(upgraded.contains("m=8192,t=1,p=1"));
+    let _ = login_user(&u1).await;
+    assert_eq!(stored_hash().await, upgraded);
+
+    // A corrupt hash fails the login, rather than the request
+    db.execute("update app_user set hashed_password = 'not-a-hash' where id = 'U-1'")
+        .await;
+    let resp = server_public_req(apis::ui::ApiRequests::def_login(), &u1).await;
+    assert!(matches!(resp, apis::ui::LoginResp::InvalidCredentials));
+
+    oserver.shutdown().await.unwrap();
+    db.cleanup().await;
+}
+
+#[tokio::test]
+async fn server_api_keys() {
+    let mut db = DbTestEnv::new().await;
+    let oserver = OServer::spawn(AppState::new(test_server_config(), db.pool.clone()));
+
+    let _ = create_test_user_joe(&mut db).await;
+    let admin = create_test_user_sarah(&mut db).await;
+    let admin_jwt = login_user(&admin).await;
+    let joe_id: AppUserId = DbKey::from_string("U-1".to_owned());
+    let sarah_id: AppUserId = DbKey::from_string("U-2".to_owned());
+    let create_key = |user_id: &AppUserId, scopes: Vec<ApiKeyScope>| {
+        let req = CreateApiKeyReq {
+            user_id: user_id.clone(),
+            name: "integration job".to_owned(),
+            scopes,
+            expires_in_days: 30,
+        };
+        let admin_jwt = admin_jwt.clone();
+        async move {
+            server_auth_req(
+                apis::ui::ApiRequests::def_create_api_key(),
+                &admin_jwt,
+                &req,
+            )
+            .await
+        }
+    };
+    let new_key = |resp: CreateApiKeyResp| match resp {
+        CreateApiKeyResp::Success(new_key) => new_key,
+        CreateApiKeyResp::InvalidUser => panic!("invalid user"),
+        CreateApiKeyResp::InvalidExpiry => panic!("invalid expiry"),
+    };
+    let who_am_i_status = |key: String| async move {
+        server_req(apis::ui::ApiRequests::def_who_am_i(), Some(&key), &())
+            .await
+            .status()
+    };
+    let query_users_status = |key: String| async move {
+        server_req(
+            apis::ui::ApiRequests::def_query_users(),
+            Some(&key),
+            &apis::ui::QueryUsersReq::new(),
+        )
+        .await
+        .status()
+    };
+
+    // A key acts as its user, within its scopes
+    let joe_key = new_key(create_key(&joe_id, vec![ApiKeyScope::User]).await);
+    let user = server_auth_req(apis::ui::ApiRequests::def_who_am_i(), &joe_key.key, &()).await;
+    assert_eq!(user.value.email, "joe@test.com");
+    assert_eq!(query_users_status(joe_key.key.clone()).await, 403);
+
+    let sarah_key = new_key(create_key(&sarah_id, vec![ApiKeyScope::Admin]).await);
+    assert_eq!(query_users_status(sarah_key.key.clone()).await, 200);
+    assert_eq!(who_am_i_status(sarah_key.key.clone()).await, 200);
+
+    // Keys can't exceed the roles of their user
+    let joe_admin_key = new_key(create_key(&joe_id, vec![ApiKeyScope::Admin]).await);
+    assert_eq!(who_am_i_status(joe_admin_key.key.clone()).await, 401);
+
+    assert!(matches!(
+        create_key(
+            &DbKey::from_string("U-99".to_owned()),
+            vec![ApiKeyScope::User]
+        )
+        .await,
+        CreateApiKeyResp::InvalidUser
+    ));
+
+    // Keys must expire, within the configured maximum
+    for expires_in_days in [0, ServerConfig::def_api_key_max_expiry_days() + 1] {
+        let resp = server_auth_req(
+            apis::ui::ApiRequests::def_create_api_key(),
+            &admin_jwt,
+            &CreateApiKeyReq {
+                user_id: joe_id.clone(),
+                name: "integration job".to_owned(),
+                scopes: vec![ApiKeyScope::User],
+                expires_in_days,
+            },
+        )
+        .await;
+        assert!(matches!(resp, CreateApiKeyResp::InvalidExpiry));
+    }
+
+    // Keys are stored hashed
+    let stored: i64 = sqlx::query_scalar("select count(*) from api_key where key_hash = $1")
+        .bind(&joe_key.key)
+        .fetch_one(&db.pool)
+        .await
+        .unwrap();
+    assert_eq!(stored, 0);
+
+    // Keys can be listed, with when they were last used
+    let keys = server_auth_req(
+        apis::ui::ApiRequests::def_query_api_keys(),
+        &admin_jwt,
+        &QueryApiKeysReq {
+            user_id: Some(joe_id.clone()),
+            ..QueryApiKeysReq::new()
+        },
+    )
+    .await;
+    assert_eq!(keys.total_count, 2);
+    let summary = |id: &ApiKeyId| {
+        keys.items
+            .iter()
+            .find(|k| k.id == *id)
+            .map(|k| k.value.clone())
+            .unwrap()
+    };
+    assert!(summary(&joe_key.id).last_used_at.is_some());
+    assert!(summary(&joe_admin_key.id).last_used_at.is_none());
+
+    // Revoked and expired keys are rejected
+    let _: Unit = server_auth_req(
+        apis::ui::ApiRequests::def_revoke_api_key(),
+        &admin_jwt,
+        &joe_key.id,
+    )
+    .await;
+    assert_eq!(who_am_i_status(joe_key.key.clone()).await, 401);
+    db.execute("update api_key set expires_at = now() - interval '1 minute'")
+        .await;
+    assert_eq!(who_am_i_status(sarah_key.key.clone()).await, 401);
+
+    oserver.shutdown().await.unwrap();
+    db.cleanup().await;
+}
+
+#[tokio::test]
+async fn server_oidc_login() {
+    let mut db = DbTestEnv::new().await;
+    let mut config = test_server_config();
+    config.oidc = Some(test_oidc_config());
+    let idp = MockIdp::spawn(config.oidc.as_ref().unwrap());
+    let oserver = OServer::spawn(AppState::new(config, db.pool.clone()));
+
+    let _ = create_test_user_joe(&mut db).await;
+    let _ = create_test_user_sarah(&mut db).await;
+    let idp_joe = IdpUser {
+        sub: "idp-joe".to_owned(),
+        email: "joe@test.com".to_owned(),
+        email_verified: true,
+        name: "Joe".to_owned(),
+    };
+
+    // A verified email address links the provider's account to the user
+    idp.set_user(idp_joe.clone());
+    let (url, state_cookie) = oidc_login_start().await;
+    let (code, state) = mock_idp::authorize(&url).await;
+    let login_req = OidcLoginReq { code, state };
+    let resp = oidc_login_complete(&login_req, Some(&state_cookie)).await;
+    let tokens = match resp {
+        OidcLoginResp::Tokens(tokens) => tokens,
+        _ => panic!("expected tokens"),
+    };
+    let user = server_auth_req(
+        apis::ui::ApiRequests::def_who_am_i(),
+        &tokens.access_jwt,
+        &(),
+    )
+    .await;
+    assert_eq!(user.value.email, "joe@test.com");
+    assert!(refresh_tokens(&tokens.refresh_jwt).await.is_some());
+
+    // Each login can only be completed once
+    let resp = oidc_login_complete(&login_req, Some(&state_cookie)).await;
+    assert!(matches!(resp, OidcLoginResp::InvalidLogin));
+
+    // And only by the browser that started it, so that another site can't
+    // complete a login to its own account in the user's browser
+    let (url, state_cookie) = oidc_login_start().await;
+    let (_, other_state_cookie) = oidc_login_start().await;
+    let (code, state) = mock_idp::authorize(&url).await;
+    let login_req = OidcLoginReq { code, state };
+    let resp = oidc_login_complete(&login_req, None).await;
+    assert!(matches!(resp, OidcLoginResp::InvalidLogin));
+    let resp = oidc_login_complete(&login_req, Some(&other_state_cookie)).await;
+    assert!(matches!(resp, OidcLoginResp::InvalidLogin));
+    let resp = oidc_login_complete(&login_req, Some(&state_cookie)).await;
+    assert!(matches!(resp, OidcLoginResp::Tokens(_)));
+
+    // Once linked, the account identifies the user whatever its email
+    idp.set_user(IdpUser {
+        email: "joseph@elsewhere.com".to_owned(),
+        ..idp_joe.clone()
+    });
+    assert!(matches!(oidc_login().await, OidcLoginResp::Tokens(_)));
+
+    // Users aren't created unless configured, and unverified addresses
+    // aren't trusted
+    idp.set_user(IdpUser {
+        sub: "idp-mike".to_owned(),
+        email: "mike@test.com".to_owned(),
+        email_verified: true,
+        name: "Mike".to_owned(),
+    });
+    assert!(matches!(oidc_login().await, OidcLoginResp::UnknownUser));
+    idp.set_user(IdpUser {
+        sub: "idp-sarah".to_owned(),
+        email: "sarah@test.com".to_owned(),
+        email_verified: false,
+        name: "Sarah".to_owned(),
+    });
+    assert!(matches!(oidc_login().await, OidcLoginResp::UnknownUser));
+
+    // Users holding roles are never linked by their email address
+    idp.set_user(IdpUser {
+        sub: "idp-sarah".to_owned(),
+        email: "sarah@test.com".to_owned(),
+        email_verified: true,
+        name: "Sarah".to_owned(),
+    });
+    assert!(matches!(oidc_login().await, OidcLoginResp::UnknownUser));
+
+    // ID tokens must be for this login and this client
+    idp.set_user(idp_joe.clone());
+    idp.set_claim_override("nonce", serde_json::json!("another-nonce"));
+    assert!(matches!(oidc_login().await, OidcLoginResp::InvalidLogin));
+    idp.clear_claim_overrides();
+    idp.set_claim_override("aud", serde_json::json!("another-client"));
+    assert!(matches!(oidc_login().await, OidcLoginResp::InvalidLogin));
+    idp.clear_claim_overrides();
+
+    // As must the state
+    let resp = oidc_login_complete(
+        &OidcLoginReq {
+            code: "a-code".to_owned(),
+            state: "a-state".to_owned(),
+        },
+        Some("oidcState=a-state"),
+    )
+    .await;
+    assert!(matches!(resp, OidcLoginResp::InvalidLogin));
+
+    // The provider's discovery document and keys are cached, with the
+    // keys refetched when the provider rotates them
+    assert_eq!(idp.fetches(), (1, 1));
+    idp.rotate_key("rotated-key");
+    assert!(matches!(oidc_login().await, OidcLoginResp::Tokens(_)));
+    assert_eq!(idp.fetches(), (1, 2));
+    assert!(matches!(oidc_login().await, OidcLoginResp::Tokens(_)));
+    assert_eq!(idp.fetches(), (1, 2));
+
+    // A token's header can't choose an algorithm that its key isn't for
+    idp.set_signing_algorithm(Algorithm::HS256);
+    assert!(matches!(oidc_login().await, OidcLoginResp::InvalidLogin));
+
+    oserver.shutdown().await.unwrap();
+    idp.shutdown().await.unwrap();
+    db.cleanup().await;
+}
+
+#[tokio::test]
+async fn server_oidc_create_users() {
+    let mut db = DbTestEnv::new().await;
+    let mut config = test_server_config();
+    config.oidc = Some(OidcConfig {
+        create_users: true,
+        link_existing_users: false,
+        ..test_oidc_config()
+    });
+    let idp = MockIdp::spawn(config.oidc.as_ref().unwrap());
+    let oserver = OServer::spawn(AppState::new(config, db.pool.clone()));
+
+    let _ = create_test_user_joe(&mut db).await;
+    idp.set_user(IdpUser {
+        sub: "idp-mike".to_owned(),
+        email: "mike@test.com".to_owned(),
+        email_verified: true,
+        name: "Mike".to_owned(),
+    });
+    let tokens = match oidc_login().await {
+        OidcLoginResp::Tokens(tokens) => tokens,
+        _ => panic!("expected tokens"),
+    };
+    let user = server_auth_req(
+        apis::ui::ApiRequests::def_who_am_i(),
+        &tokens.access_jwt,
+        &(),
+    )
+    .await;
+    assert_ne!(user.id.0, "U-1");
+    assert_eq!(user.value.fullname, "Mike");
+    assert!(user.value.roles.is_empty());
+
+    // The created user has no password
+    let hashed_password = sqlx::query_scalar::<_, Option<String>>(
+        "select hashed_password from app_user where email = 'mike@test.com'",
+    )
+    .fetch_one(&db.pool)
+    .await
+    .unwrap();
+    assert!(hashed_password.is_none());
+    let resp = server_public_req(
+        apis::ui::ApiRequests::def_login(),
+        &LoginReq {
+            email: "mike@test.com".to_owned(),
+            password: "".to_owned(),
+        },
+    )
+    .await;
+    assert!(matches!(resp, apis::ui::LoginResp::InvalidCredentials));
+
+    // Existing users aren't linked unless configured, nor duplicated
+    idp.set_user(IdpUser {
+        sub: "idp-joe".to_owned(),
+        email: "joe@test.com".to_owned(),
+        email_verified: true,
+        name: "Joe".to_owned(),
+    });
+    assert!(matches!(oidc_login().await, OidcLoginResp::UnknownUser));
+
+    oserver.shutdown().await.unwrap();
+    idp.shutdown().await.unwrap();
+    db.cleanup().await;
+}
+
+#[tokio::test]
+async fn server_impersonation() {
+    let mut db = DbTestEnv::new().await;
+    let config = test_server_config();
+    let access_keys = AccessKeys::from_config(&config).unwrap();
+    let oserver = OServer::spawn(AppState::new(config, db.pool.clone()));
+
+    let u1 = create_test_user_joe(&mut db).await;
+    let admin = create_test_user_sarah(&mut db).await;
+    let joe_jwt = login_user(&u1).await;
+    let admin_jwt = login_user(&admin).await;
+    let joe_id: AppUserId = DbKey::from_string("U-1".to_owned());
+    let sarah_id: AppUserId = DbKey::from_string("U-2".to_owned());
+
+    // Only admins can impersonate
+    let resp = server_req(
+        apis::ui::ApiRequests::def_impersonate_user(),
+        Some(&joe_jwt),
+        &sarah_id,
+    )
+    .await;
+    assert_eq!(resp.status(), 403);
+    let resp = server_auth_req(
+        apis::ui::ApiRequests::def_impersonate_user(),
+        &admin_jwt,
+        &DbKey::from_string("U-99".to_owned()),
+    )
+    .await;
+    assert!(matches!(resp, ImpersonateUserResp::InvalidUser));
+
+    // The token acts as the user, and records the admin
+    let resp = server_auth_req(
+        apis::ui::ApiRequests::def_impersonate_user(),
+        &admin_jwt,
+        &joe_id,
+    )
+    .await;
+    let jwt = match resp {
+        ImpersonateUserResp::AccessJwt(jwt) => jwt,
+        ImpersonateUserResp::InvalidUser => panic!("invalid user"),
+    };
+    let claims = decode_access(&access_keys, &jwt).unwrap();
+    assert_eq!(claims.sub, "U-1");
+    assert_eq!(claims.act.unwrap().sub, "U-2");
+    let user = server_auth_req(apis::ui::ApiRequests::def_who_am_i(), &jwt, &()).await;
+    assert_eq!(user.value.email, "joe@test.com");
+    send_message(&jwt, "sent by support").await;
+
+    // Admin endpoints refuse it, even when impersonating an admin
+    let resp = server_req(
+        apis::ui::ApiRequests::def_query_users(),
+        Some(&jwt),
+        &apis::ui::QueryUsersReq::new(),
+    )
+    .await;
+    assert_eq!(resp.status(), 403);
+    let resp = server_auth_req(
+        apis::ui::ApiRequests::def_impersonate_user(),
+        &admin_jwt,
+        &sarah_id,
+    )
+    .await;
+    let admin_as_admin_jwt = match resp {
+        ImpersonateUserResp::AccessJwt(jwt) => jwt,
+        ImpersonateUserResp::InvalidUser => panic!("invalid user"),
+    };
+    let resp = server_req(
+        apis::ui::ApiRequests::def_query_users(),
+        Some(&admin_as_admin_jwt),
+        &apis::ui::QueryUsersReq::new(),
+    )
+    .await;
+    assert_eq!(resp.status(), 403);
+
+    // Every request made with it is audited against the admin
+    let entries = query_audit_log(
+        &admin_jwt,
+        QueryAuditLogReq {
+            actor: Some(sarah_id.clone()),
+            ..QueryAuditLogReq::new()
+        },
+    )
+    .await;
+    let impersonated: Vec<_> = entries
+        .items
+        .iter()
+        .filter(|e| e.value.impersonated.as_ref() == Some(&joe_id))
+        .collect();
+    assert_eq!(impersonated.len(), 3);
+    assert_eq!(impersonated[0].value.endpoint, "/users/query");
+    assert!(impersonated[0].value.outcome == AuditOutcome::Forbidden);
+    assert_eq!(impersonated[1].value.endpoint, "/messages/new");
+    assert_eq!(impersonated[2].value.endpoint, "/whoami");
+
+    // Nor can it change the user's email address or password, which
+    // would let the admin take over the account
+    let resp = server_req(
+        apis::ui::ApiRequests::def_update_profile(),
+        Some(&jwt),
+        &UpdateProfileReq {
+            fullname: "Joe".to_owned(),
+            email: "support@test.com".to_owned(),
+            current_password: None,
+        },
+    )
+    .await;
+    assert_eq!(resp.status(), 403);
+    let resp = server_req(
+        apis::ui::ApiRequests::def_change_password(),
+        Some(&jwt),
+        &ChangePasswordReq {
+            current_password: u1.password.clone(),
+            new_password: "correct-horse".to_owned(),
+        },
+    )
+    .await;
+    assert_eq!(resp.status(), 403);
+    let user = server_auth_req(apis::ui::ApiRequests::def_who_am_i(), &joe_jwt, &()).await;
+    assert_eq!(user.value.email, "joe@test.com");
+
+    // Nor enrol the user in TOTP with a secret only the admin knows, which
+    // would lock them out
+    let resp = server_req(
+        apis::ui::ApiRequests::def_begin_totp_enrolment(),
+        Some(&jwt),
+        &(),
+    )
+    .await;
+    assert_eq!(resp.status(), 403);
+    let resp = server_req(
+        apis::ui::ApiRequests::def_confirm_totp_enrolment(),
+        Some(&jwt),
+        &ConfirmTotpEnrolmentReq {
+            code: "123456".to_owned(),
+        },
+    )
+    .await;
+    assert_eq!(resp.status(), 403);
+
+    // Nor end the user's sessions
+    let sessions = server_auth_req(apis::ui::ApiRequests::def_my_sessions(), &joe_jwt, &()).await;
+    let resp = server_req(
+        apis::ui::ApiRequests::def_revoke_session(),
+        Some(&jwt),
+        &sessions[0].id,
+    )
+    .await;
+    assert_eq!(resp.status(), 403);
+    let resp = server_req(
+        apis::ui::ApiRequests::def_revoke_other_sessions(),
+        Some(&jwt),
+        &(),
+    )
+    .await;
+    assert_eq!(resp.status(), 403);
+    let after = server_auth_req(apis::ui::ApiRequests::def_my_sessions(), &joe_jwt, &()).await;
+    assert_eq!(after.len(), sessions.len());
+
+    oserver.shutdown().await.unwrap();
+    db.cleanup().await;
+}
+
+#[tokio::test]
+async fn server_audit_log() {
+    let mut db = DbTestEnv::new().await;
+    let oserver = OServer::spawn(AppState::new(test_server_config(), db.pool.clone()));
+
+    let u1 = create_test_user_joe(&mut db).await;
+    let u2 = create_test_user_sarah(&mut db).await;
+
+    let u1_jwt = login_user(&u1).await;
+    let u2_jwt = login_user(&u2).await;
+
+    let new_user = apis::ui::UserDetails {
+        fullname: "Austin".to_owned(),
+        email: "austin@mycompany.org".to_owned(),
+        password: "sukpepolup".to_owned(),
+    };
+
+    // A forbidden call by a non admin, and a successful one by an admin
+    {
+        let http_resp = server_req(
+            apis::ui::ApiRequests::def_create_user(),
+            Some(&u1_jwt),
+            &new_user,
+        )
+        .await;
+        assert_eq!(http_resp.status(), 403);
+        server_auth_req(apis::ui::ApiRequests::def_create_user(), &u2_jwt, &new_user).await;
+    }
+
+    // Calls to endpoints not requiring a role are not audited
+    send_message(&u1_jwt, "A first message").await;
+
+    // Both create_user calls are logged, most recent first, without the password
+    {
+        let resp = query_audit_log(&u2_jwt, QueryAuditLogReq::new()).await;
+        assert_eq!(resp.total_count, 2);
+        let entry = &resp.items.first().unwrap().value;
+        assert_eq!(entry.actor.0, "U-2");
+        assert_eq!(entry.endpoint, "/users/create");
+        assert!(entry.outcome == AuditOutcome::Success);
+        assert_eq!(entry.input["fullname"], "Austin");
+        assert_eq!(entry.input["password"], "<redacted>");
+        assert!(entry.client_ip.is_some());
+        let entry = &resp.items.get(1).unwrap().value;
+        assert_eq!(entry.actor.0, "U-1");
+        assert!(entry.outcome == AuditOutcome::Forbidden);
+    }
+
+    // Results can be filtered, and include earlier audit log queries
+    {
+        let resp = query_audit_log(
+            &u2_jwt,
+            QueryAuditLogReq {
+                outcome: Some(AuditOutcome::Success),
+                ..QueryAuditLogReq::new()
+            },
+        )
+        .await;
+        assert_eq!(resp.total_count, 2);
+        assert_eq!(resp.items.first().unwrap().value.endpoint, "/audit/query");
+
+        let resp = query_audit_log(
+            &u2_jwt,
+            QueryAuditLogReq {
+                actor: Some(DbKey::from_string("U-1".to_owned())),
+                ..QueryAuditLogReq::new()
+            },
+        )
+        .await;
+        assert_eq!(resp.total_count, 1);
+    }
+
+    // Non admins can't read the audit log
+    {
+        let http_resp = server_req(
+            apis::ui::ApiRequests::def_query_audit_log(),
+            Some(&u1_jwt),
+            &QueryAuditLogReq::new(),
+        )
+        .await;
+        assert_eq!(http_resp.status(), 403);
+    }
+
+    oserver.shutdown().await.unwrap();
+    db.cleanup().await;
+}
+
+#[tokio::test]
+async fn server_request_timeout() {
+    let mut db = DbTestEnv::new().await;
+    let config = ServerConfig {
+        request_timeout_secs: 1,
+        ..test_server_config()
+    };
+    let oserver = OServer::spawn(AppState::new(config, db.pool.clone()));
+
+    let u1 = create_test_user_joe(&mut db).await;
+    let u1_jwt = login_user(&u1).await;
+
+    // Hold a lock that blocks any query of the user table
+    let mut tx = db.pool.begin().await.unwrap();
+    sqlx::query("LOCK TABLE app_user IN ACCESS EXCLUSIVE MODE")
+        .execute(&mut tx)
+        .await
+        .unwrap();
+
+    // A request that needs the user table times out, with an error body
+    {
+        let http_resp = server_req(apis::ui::ApiRequests::def_who_am_i(), Some(&u1_jwt), &()).await;
+        assert_eq!(http_resp.status(), 503);
+        let body: HttpError = http_resp.json().await.unwrap();
+        assert_eq!(body.code, "timeout");
+    }
+
+    // and the blocked query is cancelled in the database, rather than
+    // left waiting on the lock
+    {
+        let mut waiting: i64 = 1;
+        for _ in 0..20 {
+            waiting = sqlx::query_scalar(
+                "SELECT count(*) FROM pg_stat_activity WHERE wait_event_type = 'Lock'",
+            )
+            .fetch_one(&db.pool)
+            .await
+            .unwrap();
+            if waiting == 0 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        assert_eq!(waiting, 0);
+    }
+
+    // Once the lock is released, requests succeed again
+    tx.rollback().await.unwrap();
+    let resp = server_auth_req(apis::ui::ApiRequests::def_who_am_i(), &u1_jwt, &()).await;
+    assert_eq!(resp.value.fullname, "Joe");
+
+    // Endpoints with a RequestTimeoutSecs annotation use it instead of the
+    // default
+    let mut tx = db.pool.begin().await.unwrap();
+    sqlx::query("LOCK TABLE message IN ACCESS EXCLUSIVE MODE")
+        .execute(&mut tx)
+        .await
+        .unwrap();
+    let started = std::time::Instant::now();
+    let http_resp = server_req(
+        apis::ui::ApiRequests::def_recent_messages(),
+        Some(&u1_jwt),
+        &apis::ui::RecentMessagesReq {
+            page: PageReq {
+                offset: 0,
+                limit: 10,
+            },
+        },
+    )
+    .await;
+    assert_eq!(http_resp.status(), 503);
+    let elapsed = started.elapsed();
+    assert!(elapsed >= Duration::from_secs(5), "{:?}", elapsed);
+    assert!(elapsed < Duration::from_secs(10), "{:?}", elapsed);
+    tx.rollback().await.unwrap();
+
+    oserver.shutdown().await.unwrap();
+    db.cleanup().await;
+}
+
+#[tokio::test]
+async fn server_csrf() {
+    let mut db = DbTestEnv::new().await;
+    let config = ServerConfig {
+        app_url: "http://app.test".to_owned(),
+        csrf: CsrfConfig {
+            allowed_origins: vec!["https://admin.test:8443".to_owned()],
+            ..CsrfConfig::new()
+        },
+        ..test_server_config()
+    };
+    let oserver = OServer::spawn(AppState::new(config, db.pool.clone()));
+
+    let u1 = create_test_user_joe(&mut db).await;
+    let refresh_jwt = || async {
+        let resp = server_public_req(apis::ui::ApiRequests::def_login(), &u1).await;
+        get_login_tokens(resp).unwrap().refresh_jwt
+    };
+    // Make a request that authenticates with the refresh token cookie
+    let cookie_req = |path: &'static str,
+                      refresh_jwt: String,
+                      headers: Vec<(&'static str, &'static str)>| async move {
+        let mut req = reqwest::Client::new()
+            .post(format!("http://localhost:8181{}", path))
+            .header("Cookie", format!("refreshToken={}", refresh_jwt))
+            .json(&serde_json::json!({}));
+        for (name, value) in headers {
+            req = req.header(name, value);
+        }
+        req.send().await.unwrap()
+    };
+    let refresh = |refresh_jwt: String, headers: Vec<(&'static str, &'static str)>| {
+        cookie_req("/refresh", refresh_jwt, headers)
+    };
+    let status = |resp: reqwest::Response| resp.status().as_u16();
+
+    // The custom header, and an allowed origin, are required
+    let token = refresh_jwt().await;
+    assert_eq!(status(refresh(token.clone(), vec![]).await), 403);
+    assert_eq!(
+        status(refresh(token.clone(), vec![("X-Requested-With", "fetch")]).await),
+        403
+    );
+    assert_eq!(
+        status(refresh(token.clone(), vec![("Origin", "http://app.test")]).await),
+        403
+    );
+    let resp = refresh(
+        token.clone(),
+        vec![
+            ("X-Requested-With", "fetch"),
+            ("Origin", "http://evil.test"),
+        ],
+    )
+    .await;
+    assert_eq!(resp.status(), 403);
+    let error: HttpError = resp.json().await.unwrap();
+    assert_eq!(error.code, "csrf");
+    assert_eq!(
+        status(
+            refresh(
+                token.clone(),
+                vec![
+                    ("X-Requested-With", "fetch"),
+                    ("Origin", "http://app.test.evil.test")
+                ]
+            )
+            .await
+        ),
+        403
+    );
+
+    // Refused requests don't use up the token
+    let resp = refresh(
+        token.clone(),
+        vec![("X-Requested-With", "fetch"), ("Origin", "http://app.test")],
+    )
+    .await;
+    assert_eq!(resp.status(), 200);
+    let resp: apis::ui::RefreshResp = resp.json().await.unwrap();
+    assert!(is_valid_refresh(&resp));
+
+    // The referer is checked in the absence of an origin, and other
+    // configured origins are allowed
+    let token = refresh_jwt().await;
+    assert_eq!(
+        status(
+            refresh(
+                token,
+                vec![
+                    ("X-Requested-With", "fetch"),
+                    ("Referer", "http://app.test/messages")
+                ]
+            )
+            .await
+        ),
+        200
+    );
+    let token = refresh_jwt().await;
+    assert_eq!(
+        status(
+            refresh(
+                token,
+                vec![
+                    ("X-Requested-With", "fetch"),
+                    ("Origin", "https://admin.test:8443")
+                ]
+            )
+            .await
+        ),
+        200
+    );
+
+    // Tokens passed in the request body aren't checked
+    let token = refresh_jwt().await;
+    assert!(refresh_tokens(&token).await.is_some());
+
+    // Logout is also protected, so a forged request can't end the session
+    let token = refresh_jwt().await;
+    assert_eq!(
+        status(cookie_req("/logout", token.clone(), vec![]).await),
+        403
+    );
+    assert!(refresh_tokens(&token).await.is_some());
+
+    oserver.shutdown().await.unwrap();
+    db.cleanup().await;
+}
+
+#[tokio::test]
+async fn server_auth_cookie() {
+    let mut db = DbTestEnv::new().await;
+    let config = ServerConfig {
+        csrf: CsrfConfig {
+            enabled: false,
+            ..CsrfConfig::new()
+        },
+        auth_cookie: AuthCookieConfig {
+            same_site: CookieSameSite::Lax,
+            host_prefix: true,
+            ..AuthCookieConfig::new()
+        },
+        ..test_server_config()
+    };
+    let oserver = OServer::spawn(AppState::new(config, db.pool.clone()));
+
+    let u1 = create_test_user_joe(&mut db).await;
+    let cookie_req = |path: &'static str, cookie: Option<String>, body: serde_json::Value| async move {
+        let mut req = reqwest::Client::new()
+            .post(format!("http://localhost:8181{}", path))
+            .json(&body);
+        if let Some(cookie) = cookie {
+            req = req.header("Cookie", cookie);
+        }
+        req.send().await.unwrap()
+    };
+    let set_cookie = |resp: &reqwest::Response| {
+        let values: Vec<&str> = resp
+            .headers()
+            .get_all("set-cookie")
+            .iter()
+            .map(|v| v.to_str().unwrap())
+            .filter(|v| v.starts_with("__Host-refreshToken="))
+            .collect();
+        assert_eq!(values.len(), 1);
+        let value = values[0].to_owned();
+        let token = value
+            .trim_start_matches("__Host-refreshToken=")
+            .split(';')
+            .next()
+            .unwrap()
+            .to_owned();
+        let attrs: Vec<String> = value.split("; ").skip(1).map(|a| a.to_owned()).collect();
+        (token, attrs)
+    };
+    let has_attrs =
+        |attrs: &[String], expected: &[&str]| expected.iter().all(|e| attrs.iter().any(|a| a == e));
+
+    // Logging in sets the cookie with the configured attributes, and to
+    // expire with the refresh token
+    let resp = cookie_req("/login", None, serde_json::to_value(&u1).unwrap()).await;
+    assert_eq!(resp.status(), 200);
+    let (token, attrs) = set_cookie(&resp);
+    assert!(has_attrs(
+        &attrs,
+        &[
+            "HttpOnly",
+            "SameSite=Lax",
+            "Secure",
+            "Path=/",
+            "Max-Age=300"
+        ]
+    ));
+    assert!(!attrs.iter().any(|a| a.starts_with("Domain=")));
+
+    // The cookie is only read with its prefixed name
+    let resp = cookie_req(
+        "/refresh",
+        Some(format!("refreshToken={}", token)),
+        serde_json::json!({}),
+    )
+    .await;
+    let resp: apis::ui::RefreshResp = resp.json().await.unwrap();
+    assert!(!is_valid_refresh(&resp));
+    let resp = cookie_req(
+        "/refresh",
+        Some(format!("__Host-refreshToken={}", token)),
+        serde_json::json!({}),
+    )
+    .await;
+    assert_eq!(resp.status(), 200);
+    let (token, attrs) = set_cookie(&resp);
+    assert!(has_attrs(&attrs, &["Secure", "Path=/", "Max-Age=300"]));
+
+    // Logout clears the cookie with the same attributes, so that browsers
+    // apply the removal
+    let resp = cookie_req(
+        "/logout",
+        Some(format!("__Host-refreshToken={}", token)),
+        serde_json::json!({}),
+    )
+    .await;
+    assert_eq!(resp.status(), 200);
+    let (value, attrs) = set_cookie(&resp);
+    assert_eq!(value, "");
+    assert!(has_attrs(
+        &attrs,
+        &["HttpOnly", "SameSite=Lax", "Secure", "Path=/", "Max-Age=0"]
+    ));
+    assert!(refresh_tokens(&token).await.is_none());
+
+    // The __Host- prefix is only accepted by browsers for secure, host
+    // only cookies with a path of "/"
+    let prefixed = AuthCookieConfig {
+        host_prefix: true,
+        ..AuthCookieConfig::new()
+    };
+    assert!(auth_cookie::check_config(&prefixed).is_ok());
+    assert!(auth_cookie::check_config(&AuthCookieConfig {
+        secure: false,
+        ..prefixed.clone()
+    })
+    .is_err());
+    assert!(auth_cookie::check_config(&AuthCookieConfig {
+        path: "/api".to_owned(),
+        ..prefixed.clone()
+    })
+    .is_err());
+    assert!(auth_cookie::check_config(&AuthCookieConfig {
+        domain: Some("app.test".to_owned()),
+        ..prefixed
+    })
+    .is_err());
+
+    oserver.shutdown().await.unwrap();
+    db.cleanup().await;
+}
+
+#[tokio::test]
+async fn server_user_deactivation() {
+    let mut db = DbTestEnv::new().await;
+    let config = ServerConfig {
+        deactivated_users_cache_secs: 2,
+        ..test_server_config()
+    };
+    let oserver = OServer::spawn(AppState::new(config, db.pool.clone()));
+
+    let u1 = create_test_user_joe(&mut db).await;
+    let admin = create_test_user_sarah(&mut db).await;
+    let admin_jwt = login_user(&admin).await;
+    let joe_id: AppUserId = DbKey::from_string("U-1".to_owned());
+    let sarah_id: AppUserId = DbKey::from_string("U-2".to_owned());
+    let deactivate = |user_id: &AppUserId| {
+        let admin_jwt = admin_jwt.clone();
+        let user_id = user_id.clone();
+        async move {
+            server_auth_req(
+                apis::ui::ApiRequests::def_deactivate_user(),
+                &admin_jwt,
+                &user_id,
+            )
+            .await
+        }
+    };
+    let reactivate = |user_id: &AppUserId| {
+        let admin_jwt = admin_jwt.clone();
+        let user_id = user_id.clone();
+        async move {
+            server_auth_req(
+                apis::ui::ApiRequests::def_reactivate_user(),
+                &admin_jwt,
+                &user_id,
+            )
+            .await
+        }
+    };
+    let who_am_i_status = |jwt: String| async move {
+        server_req(apis::ui::ApiRequests::def_who_am_i(), Some(&jwt), &())
+            .await
+            .status()
+    };
+
+    let joe_tokens =
+        get_login_tokens(server_public_req(apis::ui::ApiRequests::def_login(), &u1).await).unwrap();
+    let joe_key = match server_auth_req(
+        apis::ui::ApiRequests::def_create_api_key(),
+        &admin_jwt,
+        &CreateApiKeyReq {
+            user_id: joe_id.clone(),
+            name: "integration job".to_owned(),
+            scopes: vec![ApiKeyScope::User],
+            expires_in_days: 30,
+        },
+    )
+    .await
+    {
+        CreateApiKeyResp::Success(new_key) => new_key.key,
+        CreateApiKeyResp::InvalidUser => panic!("invalid user"),
+        CreateApiKeyResp::InvalidExpiry => panic!("invalid expiry"),
+    };
+    send_message(&joe_tokens.access_jwt, "Hello from Joe").await;
+
+    assert!(deactivate(&sarah_id).await == DeactivateUserResp::CannotDeactivateSelf);
+    assert!(
+        deactivate(&DbKey::from_string("U-99".to_owned())).await == DeactivateUserResp::InvalidUser
+    );
+    assert!(
+        reactivate(&DbKey::from_string("U-99".to_owned())).await == ReactivateUserResp::InvalidUser
+    );
+
+    // Deactivation takes effect immediately for existing access tokens and
+    // API keys, and revokes refresh tokens
+    assert_eq!(who_am_i_status(joe_tokens.access_jwt.clone()).await, 200);
+    assert!(deactivate(&joe_id).await == DeactivateUserResp::Success);
+    assert_eq!(who_am_i_status(joe_tokens.access_jwt.clone()).await, 401);
+    assert_eq!(who_am_i_status(joe_key.clone()).await, 401);
+    assert!(refresh_tokens(&joe_tokens.refresh_jwt).await.is_none());
+    assert!(
+        server_public_req(apis::ui::ApiRequests::def_login(), &u1).await
+            == apis::ui::LoginResp::Deactivated
+    );
+
+    // The user is listed as inactive, and their messages are kept
+    let users = server_auth_req(
+        apis::ui::ApiRequests::def_query_users(),
+        &admin_jwt,
+        &apis::ui::QueryUsersReq::new(),
+    )
+    .await;
+    let joe = users.items.iter().find(|u| u.id.0 == joe_id.0).unwrap();
+    assert!(!joe.value.active);
+    let messages = recent_messages(&admin_jwt, 0, 10).await;
+    assert_eq!(messages.items.len(), 1);
+    assert_eq!(messages.items[0].user_fullname, "Joe");
+
+    // Once reactivated, the user can login again and their API keys work
+    assert!(reactivate(&joe_id).await == ReactivateUserResp::Success);
+    let joe_jwt = login_user(&u1).await;
+    let user = server_auth_req(apis::ui::ApiRequests::def_who_am_i(), &joe_jwt, &()).await;
+    assert!(user.value.active);
+    assert_eq!(who_am_i_status(joe_key.clone()).await, 200);
+
+    // Deactivations made elsewhere take effect once the cache expires
+    db::set_user_deactivated(&db.pool, &joe_id, Some(db::instant_now()))
+        .await
+        .unwrap();
+    assert_eq!(who_am_i_status(joe_jwt.clone()).await, 200);
+    tokio::time::sleep(Duration::from_millis(2100)).await;
+    assert_eq!(who_am_i_status(joe_jwt.clone()).await, 401);
+
+    oserver.shutdown().await.unwrap();
+    db.cleanup().await;
+}
+
+#[tokio::test]
+async fn deactivated_users_single_reload() {
+    let mut db = DbTestEnv::new().await;
+    let _ = create_test_user_joe(&mut db).await;
+    let deactivated_users = Arc::new(DeactivatedUsers::new(Duration::from_secs(60)));
+
+    // Block reloads until the lock is released
+    let mut lock = db.pool.begin().await.unwrap();
+    sqlx::query("LOCK TABLE app_user IN ACCESS EXCLUSIVE MODE")
+        .execute(&mut lock)
+        .await
+        .unwrap();
+    let checks: Vec<_> = (0..3)
+        .map(|_| {
+            let deactivated_users = deactivated_users.clone();
+            let pool = db.pool.clone();
+            tokio::spawn(async move { deactivated_users.contains(&pool, "U-1").await })
+        })
+        .collect();
+    tokio::time::sleep(Duration::from_millis(500)).await;
+
+    // Concurrent misses wait for a single reload
+    let reloads: i64 = sqlx::query_scalar(
+        "select count(*) from pg_stat_activity where wait_event_type = 'Lock' and query like '%deactivated_at%'",
+    )
+    .fetch_one(&db.pool)
+    .await
+    .unwrap();
+    assert_eq!(reloads, 1);
+    lock.rollback().await.unwrap();
+    for check in checks {
+        assert!(!check.await.unwrap().unwrap());
+    }
+
+    db.cleanup().await;
+}
+
+#[tokio::test]
+async fn server_organisations() {
+    let mut db = DbTestEnv::new().await;
+    let config = test_server_config();
+    let access_keys = AccessKeys::from_config(&config).unwrap();
+    let oserver = OServer::spawn(AppState::new(config, db.pool.clone()));
+
+    let u1 = create_test_user_joe(&mut db).await;
+    let admin = create_test_user_sarah(&mut db).await;
+    let u3 = create_test_user(&mut db, "U-3", "Kim", "kim@test.com", "fghij", false).await;
+    let admin_jwt = login_user(&admin).await;
+    let joe_id: AppUserId = DbKey::from_string("U-1".to_owned());
+    let kim_id: AppUserId = DbKey::from_string("U-3".to_owned());
+    let admin_role: RoleId = DbKey::from_string("G-admin".to_owned());
+    let create_organisation = |name: &str| {
+        let req = CreateOrganisationReq {
+            name: name.to_owned(),
+        };
+        let admin_jwt = admin_jwt.clone();
+        async move {
+            server_auth_req(
+                apis::ui::ApiRequests::def_create_organisation(),
+                &admin_jwt,
+                &req,
+            )
+            .await
+        }
+    };
+    let set_member = |organisation_id: &OrganisationId, user_id: &AppUserId, roles: Vec<RoleId>| {
+        let req = SetOrganisationMemberReq {
+            organisation_id: organisation_id.clone(),
+            user_id: user_id.clone(),
+            roles,
+        };
+        let admin_jwt = admin_jwt.clone();
+        async move {
+            server_auth_req(
+                apis::ui::ApiRequests::def_set_organisation_member(),
+                &admin_jwt,
+                &req,
+            )
+            .await
+        }
+    };
+    let switch = |jwt: &str, organisation_id: Option<&OrganisationId>| {
+        let req = SwitchOrganisationReq {
+            organisation_id: organisation_id.cloned(),
+        };
+        let jwt = jwt.to_owned();
+        async move {
+            server_auth_req(apis::ui::ApiRequests::def_switch_organisation(), &jwt, &req).await
+        }
+    };
+    let query_user_ids = |jwt: String| async move {
+        let users = server_auth_req(
+            apis::ui::ApiRequests::def_query_users(),
+            &jwt,
+            &apis::ui::QueryUsersReq::new(),
+        )
+        .await;
+        users.items.into_iter().map(|u| u.id.0).collect::<Vec<_>>()
+    };
+    let message_texts = |jwt: String| async move {
+        let messages = recent_messages(&jwt, 0, 10).await;
+        messages
+            .items
+            .into_iter()
+            .map(|m| m.message)
+            .collect::<Vec<_>>()
+    };
+
+    let acme = match create_organisation("Acme").await {
+        CreateOrganisationResp::Success(id) => id,
+        CreateOrganisationResp::NameInUse => panic!("name in use"),
+    };
+    let globex = match create_organisation("Globex").await {
+        CreateOrganisationResp::Success(id) => id,
+        CreateOrganisationResp::NameInUse => panic!("name in use"),
+    };
+    assert!(create_organisation("Acme").await == CreateOrganisationResp::NameInUse);
+    let organisations = server_auth_req(
+        apis::ui::ApiRequests::def_query_organisations(),
+        &admin_jwt,
+        &QueryOrganisationsReq::new(),
+    )
+    .await;
+    assert_eq!(organisations.total_count, 2);
+    assert_eq!(organisations.items[0].value.name, "Acme");
+    assert_eq!(organisations.items[1].value.name, "Globex");
+
+    // Members can be granted roles within an organisation, but not the
+    // admin role
+    let moderator = match server_auth_req(
+        apis::ui::ApiRequests::def_create_role(),
+        &admin_jwt,
+        &CreateRoleReq {
+            name: "moderator".to_owned(),
+            description: "".to_owned(),
+            permissions: vec![],
+        },
+    )
+    .await
+    {
+        CreateRoleResp::Success(role_id) => role_id,
+        CreateRoleResp::NameInUse => panic!("name in use"),
+    };
+    assert!(
+        set_member(&DbKey::from_string("B-99".to_owned()), &joe_id, vec![]).await
+            == SetOrganisationMemberResp::InvalidOrganisation
+    );
+    assert!(
+        set_member(&acme, &DbKey::from_string("U-99".to_owned()), vec![]).await
+            == SetOrganisationMemberResp::InvalidUser
+    );
+    assert!(
+        set_member(&acme, &joe_id, vec![admin_role]).await
+            == SetOrganisationMemberResp::InvalidRole
+    );
+    assert!(
+        set_member(&acme, &joe_id, vec![moderator]).await == SetOrganisationMemberResp::Success
+    );
+    assert!(set_member(&globex, &kim_id, vec![]).await == SetOrganisationMemberResp::Success);
+
+    // Logins act within the user's organisation, with their roles there
+    let joe_tokens =
+        get_login_tokens(server_public_req(apis::ui::ApiRequests::def_login(), &u1).await).unwrap();
+    let claims = decode_access(&access_keys, &joe_tokens.access_jwt).unwrap();
+    assert_eq!(claims.org, Some(acme.0.clone()));
+    assert_eq!(claims.roles, vec!["user", "moderator"]);
+    let kim_jwt = login_user(&u3).await;
+    let claims = decode_access(&access_keys, &kim_jwt).unwrap();
+    assert_eq!(claims.org, Some(globex.0.clone()));
+    let claims = decode_access(&access_keys, &admin_jwt).unwrap();
+    assert_eq!(claims.org, None);
+
+    // Messages are only visible within the organisation they were sent in
+    send_message(&joe_tokens.access_jwt, "Hello from Acme").await;
+    send_message(&kim_jwt, "Hello from Globex").await;
+    send_message(&admin_jwt, "Hello from nowhere").await;
+    assert_eq!(
+        message_texts(joe_tokens.access_jwt.clone()).await,
+        vec!["Hello from Acme"]
+    );
+    assert_eq!(
+        message_texts(kim_jwt.clone()).await,
+        vec!["Hello from Globex"]
+    );
+    assert_eq!(
+        message_texts(admin_jwt.clone()).await,
+        vec!["Hello from nowhere"]
+    );
+
+    // Users can only switch to organisations they belong to, while admins
+    // can switch to any
+    assert!(
+        switch(&joe_tokens.access_jwt, Some(&globex)).await
+            == SwitchOrganisationResp::InvalidOrganisation
+    );
+    assert!(
+        switch(
+            &joe_tokens.access_jwt,
+            Some(&DbKey::from_string("B-99".to_owned()))
+        )
+        .await
+            == SwitchOrganisationResp::InvalidOrganisation
+    );
+    assert!(
+        switch(&joe_tokens.access_jwt, None).await == SwitchOrganisationResp::InvalidOrganisation
+    );
+    assert!(switch(&kim_jwt, None).await == SwitchOrganisationResp::InvalidOrganisation);
+    let admin_acme_jwt = match switch(&admin_jwt, Some(&acme)).await {
+        SwitchOrganisationResp::AccessJwt(jwt) => jwt,
+        SwitchOrganisationResp::InvalidOrganisation => panic!("invalid organisation"),
+    };
+    let claims = decode_access(&access_keys, &admin_acme_jwt).unwrap();
+    assert_eq!(claims.org, Some(acme.0.clone()));
+    assert_eq!(claims.roles, vec!["user", "admin"]);
+    assert_eq!(
+        message_texts(admin_acme_jwt.clone()).await,
+        vec!["Hello from Acme"]
+    );
+    assert_eq!(query_user_ids(admin_acme_jwt.clone()).await, vec!["U-1"]);
+    assert_eq!(query_user_ids(admin_jwt.clone()).await.len(), 3);
+    match switch(&admin_acme_jwt, None).await {
+        SwitchOrganisationResp::AccessJwt(jwt) => {
+            let claims = decode_access(&access_keys, &jwt).unwrap();
+            assert_eq!(claims.org, None);
+        }
+        SwitchOrganisationResp::InvalidOrganisation => panic!("invalid organisation"),
+    }
+
+    // Refreshed tokens keep the session's organisation until the user is
+    // removed from it
+    let tokens = refresh_tokens(&joe_tokens.refresh_jwt).await.unwrap();
+    let claims = decode_access(&access_keys, &tokens.access_jwt).unwrap();
+    assert_eq!(claims.org, Some(acme.0.clone()));
+    let _: Unit = server_auth_req(
+        apis::ui::ApiRequests::def_remove_organisation_member(),
+        &admin_jwt,
+        &RemoveOrganisationMemberReq {
+            organisation_id: acme.clone(),
+            user_id: joe_id.clone(),
+        },
+    )
+    .await;
+    let tokens = refresh_tokens(&tokens.refresh_jwt).await.unwrap();
+    let claims = decode_access(&access_keys, &tokens.access_jwt).unwrap();
+    assert_eq!(claims.org, None);
+    assert_eq!(claims.roles, vec!["user"]);
+
+    oserver.shutdown().await.unwrap();
+    db.cleanup().await;
+}
+
+#[tokio::test]
+async fn server_message_editing() {
+    let mut db = DbTestEnv::new().await;
+    let oserver = OServer::spawn(AppState::new(test_server_config(), db.pool.clone()));
+
+    let u1 = create_test_user_joe(&mut db).await;
+    let admin = create_test_user_sarah(&mut db).await;
+    let u3 = create_test_user(&mut db, "U-3", "Kim", "kim@test.com", "fghij", false).await;
+    let joe_jwt = login_user(&u1).await;
+    let admin_jwt = login_user(&admin).await;
+    let kim_jwt = login_user(&u3).await;
+    let edit = |jwt: &str, id: &MessageId, message: &str| {
+        let req = EditMessageReq {
+            id: id.clone(),
+            message: message.to_owned(),
+        };
+        let jwt = jwt.to_owned();
+        async move { server_auth_req(apis::ui::ApiRequests::def_edit_message(), &jwt, &req).await }
+    };
+    let delete = |jwt: &str, id: &MessageId| {
+        let jwt = jwt.to_owned();
+        let id = id.clone();
+        async move { server_auth_req(apis::ui::ApiRequests::def_delete_message(), &jwt, &id).await }
+    };
+    let revisions = |jwt: &str, id: &MessageId| {
+        let jwt = jwt.to_owned();
+        let id = id.clone();
+        async move { server_auth_req(apis::ui::ApiRequests::def_message_revisions(), &jwt, &id).await }
+    };
+
+    send_message(&joe_jwt, "Helo").await;
+    send_message(&joe_jwt, "Another message").await;
+    let m = recent_messages(&joe_jwt, 0, 10).await;
+    let id = m.items[1].id.clone();
+    assert!(m.items[1].edited_at.is_none());
+    let unknown_id: MessageId = DbKey::from_string("M-99".to_owned());
+
+    // Users can only edit their own messages, while admins can edit any
+    assert!(edit(&joe_jwt, &unknown_id, "Hi").await == EditMessageResp::InvalidMessage);
+    assert!(edit(&kim_jwt, &id, "Goodbye").await == EditMessageResp::NotPermitted);
+    assert!(edit(&joe_jwt, &id, "Hello").await == EditMessageResp::Success);
+    assert!(edit(&admin_jwt, &id, "Hello everyone").await == EditMessageResp::Success);
+    let m = recent_messages(&joe_jwt, 0, 10).await;
+    assert_eq!(m.items[1].message, "Hello everyone");
+    assert!(m.items[1].edited_at.is_some());
+    assert!(m.items[1].deleted_at.is_none());
+
+    // The prior versions are kept, and visible to the author and admins
+    assert!(revisions(&kim_jwt, &id).await == MessageRevisionsResp::NotPermitted);
+    assert!(revisions(&joe_jwt, &unknown_id).await == MessageRevisionsResp::InvalidMessage);
+    let history = match revisions(&joe_jwt, &id).await {
+        MessageRevisionsResp::Revisions(revisions) => revisions,
+        _ => panic!("expected revisions"),
+    };
+    let texts: Vec<&str> = history.iter().map(|r| r.message.as_str()).collect();
+    assert_eq!(texts, vec!["Hello", "Helo"]);
+    assert_eq!(history[0].replaced_by.0, "U-2");
+    assert_eq!(history[1].replaced_by.0, "U-1");
+    assert!(history[1].written_at == m.items[1].posted_at);
+
+    // Deleted messages remain as tombstones, and can't be changed further
+    assert!(delete(&kim_jwt, &id).await == DeleteMessageResp::NotPermitted);
+    assert!(delete(&joe_jwt, &unknown_id).await == DeleteMessageResp::InvalidMessage);
+    assert!(delete(&joe_jwt, &id).await == DeleteMessageResp::Success);
+    assert!(delete(&joe_jwt, &id).await == DeleteMessageResp::InvalidMessage);
+    assert!(edit(&joe_jwt, &id, "Hello again").await == EditMessageResp::InvalidMessage);
+    let m = recent_messages(&joe_jwt, 0, 10).await;
+    assert_eq!(m.total_count, 2);
+    assert_eq!(m.items[1].message, "");
+    assert!(m.items[1].deleted_at.is_some());
+    assert_eq!(m.items[0].message, "Another message");
+    match revisions(&admin_jwt, &id).await {
+        MessageRevisionsResp::Revisions(revisions) => {
+            assert_eq!(revisions.len(), 3);
+            assert_eq!(revisions[0].message, "Hello everyone");
+        }
+        _ => panic!("expected revisions"),
+    }
+
+    // Concurrent edits each save the version that they replaced
+    let other_id = m.items[0].id.clone();
+    let (a, b) = tokio::join!(
+        edit(&joe_jwt, &other_id, "Edit A"),
+        edit(&admin_jwt, &other_id, "Edit B")
+    );
+    assert!(a == EditMessageResp::Success && b == EditMessageResp::Success);
+    let latest = recent_messages(&joe_jwt, 0, 10).await.items[0]
+        .message
+        .clone();
+    let replaced = match latest.as_str() {
+        "Edit A" => "Edit B",
+        _ => "Edit A",
+    };
+    match revisions(&joe_jwt, &other_id).await {
+        MessageRevisionsResp::Revisions(revisions) => {
+            let mut texts: Vec<&str> = revisions.iter().map(|r| r.message.as_str()).collect();
+            texts.sort();
+            assert_eq!(texts, vec!["Another message", replaced]);
+        }
+        _ => panic!("expected revisions"),
+    }
+
+    oserver.shutdown().await.unwrap();
+    db.cleanup().await;
+}
+
+#[tokio::test]
+async fn server_message_threads() {
+    let mut db = DbTestEnv::new().await;
+    let oserver = OServer::spawn(AppState::new(test_server_config(), db.pool.clone()));
+
+    let u1 = create_test_user_joe(&mut db).await;
+    let u2 = create_test_user_sarah(&mut db).await;
+    let joe_jwt = login_user(&u1).await;
+    let sarah_jwt = login_user(&u2).await;
+    let reply = |jwt: &str, reply_to: &MessageId, message: &str| {
+        let req = ReplyToMessageReq {
+            reply_to: reply_to.clone(),
+            message: message.to_owned(),
+        };
+        let jwt = jwt.to_owned();
+        async move { server_auth_req(apis::ui::ApiRequests::def_reply_to_message(), &jwt, &req).await }
+    };
+    let thread = |id: &MessageId, offset: u64, limit: u64| {
+        let req = MessageThreadReq {
+            id: id.clone(),
+            page: PageReq { offset, limit },
+        };
+        let jwt = joe_jwt.clone();
+        async move { server_auth_req(apis::ui::ApiRequests::def_message_thread(), &jwt, &req).await }
+    };
+
+    send_message(&joe_jwt, "Any questions?").await;
+    send_message(&joe_jwt, "Lunch is at noon").await;
+    let m = recent_messages(&joe_jwt, 0, 10).await;
+    let question_id = m.items[1].id.clone();
+    let lunch_id = m.items[0].id.clone();
+
+    let first_id = match reply(&sarah_jwt, &question_id, "Where is the meeting?").await {
+        ReplyToMessageResp::Success(id) => id,
+        ReplyToMessageResp::InvalidMessage => panic!("invalid message"),
+    };
+    assert!(reply(&joe_jwt, &question_id, "Room 4").await != ReplyToMessageResp::InvalidMessage);
+
+    // Threads are a single level deep
+    assert!(reply(&joe_jwt, &first_id, "Room 4").await == ReplyToMessageResp::InvalidMessage);
+    assert!(
+        reply(&joe_jwt, &DbKey::from_string("M-99".to_owned()), "Hi").await
+            == ReplyToMessageResp::InvalidMessage
+    );
+
+    // Replies are excluded from the recent messages, and counted against
+    // their top level message instead
+    let m = recent_messages(&joe_jwt, 0, 10).await;
+    assert_eq!(m.total_count, 2);
+    assert_eq!(m.items.len(), 2);
+    assert_eq!(m.items[1].message, "Any questions?");
+    assert_eq!(m.items[1].reply_count, 2);
+    assert!(m.items[1].latest_reply_at.is_some());
+    assert_eq!(m.items[0].reply_count, 0);
+    assert!(m.items[0].latest_reply_at.is_none());
+
+    // Threads list their replies in the order they were posted
+    let t = match thread(&question_id, 0, 10).await {
+        MessageThreadResp::Thread(thread) => thread,
+        MessageThreadResp::InvalidMessage => panic!("invalid message"),
+    };
+    assert_eq!(t.message.message, "Any questions?");
+    assert_eq!(t.message.reply_count, 2);
+    assert_eq!(t.replies.total_count, 2);
+    let texts: Vec<&str> = t.replies.items.iter().map(|r| r.message.as_str()).collect();
+    assert_eq!(texts, vec!["Where is the meeting?", "Room 4"]);
+    assert_eq!(t.replies.items[0].user_fullname, "Sarah");
+    assert!(t.message.latest_reply_at == Some(t.replies.items[1].posted_at.clone()));
+    match thread(&question_id, 1, 1).await {
+        MessageThreadResp::Thread(thread) => {
+            assert_eq!(thread.replies.current_offset, 1);
+            assert_eq!(thread.replies.items.len(), 1);
+            assert_eq!(thread.replies.items[0].message, "Room 4");
+        }
+        MessageThreadResp::InvalidMessage => panic!("invalid message"),
+    }
+    assert!(thread(&first_id, 0, 10).await == MessageThreadResp::InvalidMessage);
+
+    // Deleted messages keep their threads, but can't be replied to
+    assert!(
+        server_auth_req(
+            apis::ui::ApiRequests::def_delete_message(),
+            &joe_jwt,
+            &lunch_id
+        )
+        .await
+            == DeleteMessageResp::Success
+    );
+    assert!(reply(&joe_jwt, &lunch_id, "Noted").await == ReplyToMessageResp::InvalidMessage);
+
+    // Deleted replies stay in the thread, but aren't counted
+    let resp: DeleteMessageResp = server_auth_req(
+        apis::ui::ApiRequests::def_delete_message(),
+        &sarah_jwt,
+        &first_id,
+    )
+    .await;
+    assert!(resp == DeleteMessageResp::Success);
+    let t = match thread(&question_id, 0, 10).await {
+        MessageThreadResp::Thread(thread) => thread,
+        MessageThreadResp::InvalidMessage => panic!("invalid message"),
+    };
+    assert_eq!(t.message.reply_count, 1);
+    assert_eq!(t.replies.total_count, 2);
+    assert!(t.replies.items[0].deleted_at.is_some());
+    let m = recent_messages(&joe_jwt, 0, 10).await;
+    assert_eq!(m.items[1].reply_count, 1);
+
+    oserver.shutdown().await.unwrap();
+    db.cleanup().await;
+}
+
+#[tokio::test]
+async fn server_message_reactions() {
+    let mut db = DbTestEnv::new().await;
+    let oserver = OServer::spawn(AppState::new(test_server_config(), db.pool.clone()));
+
+    let u1 = create_test_user_joe(&mut db).await;
+    let u2 = create_test_user_sarah(&mut db).await;
+    let joe_jwt = login_user(&u1).await;
+    let sarah_jwt = login_user(&u2).await;
+    let react = |jwt: &str, message_id: &MessageId, emoji: &str| {
+        let req = MessageReactionReq {
+            message_id: message_id.clone(),
+            emoji: emoji.to_owned(),
+        };
+        let jwt = jwt.to_owned();
+        async move { server_auth_req(apis::ui::ApiRequests::def_add_reaction(), &jwt, &req).await }
+    };
+    let unreact = |jwt: &str, message_id: &MessageId, emoji: &str| {
+        let req = MessageReactionReq {
+            message_id: message_id.clone(),
+            emoji: emoji.to_owned(),
+        };
+        let jwt = jwt.to_owned();
+        async move {
+            let _: Unit =
+                server_auth_req(apis::ui::ApiRequests::def_remove_reaction(), &jwt, &req).await;
+        }
+    };
+    let reactions = |jwt: &str| {
+        let jwt = jwt.to_owned();
+        async move {
+            recent_messages(&jwt, 0, 10)
+                .await
+                .items
+                .into_iter()
+                .map(|m| {
+                    m.reactions
+                        .into_iter()
+                        .map(|r| (r.emoji, r.count, r.reacted_by_me))
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>()
+        }
+    };
+    let thumbs_up = "👍".to_owned();
+    let party = "🎉".to_owned();
+
+    send_message(&joe_jwt, "Release is out").await;
+    send_message(&joe_jwt, "Standup moved to 10am").await;
+    let m = recent_messages(&joe_jwt, 0, 10).await;
+    let release_id = m.items[1].id.clone();
+    let standup_id = m.items[0].id.clone();
+
+    assert!(react(&joe_jwt, &release_id, &thumbs_up).await == AddReactionResp::Success);
+    assert!(react(&sarah_jwt, &release_id, &thumbs_up).await == AddReactionResp::Success);
+    assert!(react(&sarah_jwt, &release_id, &party).await == AddReactionResp::Success);
+    assert!(react(&sarah_jwt, &release_id, &thumbs_up).await == AddReactionResp::Success);
+    assert!(
+        react(&joe_jwt, &DbKey::from_string("M-99".to_owned()), &thumbs_up).await
+            == AddReactionResp::InvalidMessage
+    );
+    for emoji in ["ok", "", "👍 👍", "👍👍👍👍👍👍👍👍👍👍👍👍👍👍👍👍👍"]
+    {
+        assert!(react(&joe_jwt, &release_id, emoji).await == AddReactionResp::InvalidEmoji);
+    }
+
+    // Reactions are counted per emoji, in the order they were first made,
+    // and flagged if made by the caller
+    assert_eq!(
+        reactions(&joe_jwt).await,
+        vec![
+            vec![],
+            vec![(thumbs_up.clone(), 2, true), (party.clone(), 1, false)]
+        ]
+    );
+    assert_eq!(
+        reactions(&sarah_jwt).await,
+        vec![
+            vec![],
+            vec![(thumbs_up.clone(), 2, true), (party.clone(), 1, true)]
+        ]
+    );
+
+    unreact(&sarah_jwt, &release_id, &thumbs_up).await;
+    unreact(&sarah_jwt, &standup_id, &thumbs_up).await;
+    assert_eq!(
+        reactions(&sarah_jwt).await,
+        vec![
+            vec![],
+            vec![(thumbs_up.clone(), 1, false), (party.clone(), 1, true)]
+        ]
+    );
+
+    // Replies can be reacted to, while deleted messages can't
+    let reply_id = match server_auth_req(
+        apis::ui::ApiRequests::def_reply_to_message(),
+        &sarah_jwt,
+        &ReplyToMessageReq {
+            reply_to: release_id.clone(),
+            message: "Nice work".to_owned(),
+        },
+    )
+    .await
+    {
+        ReplyToMessageResp::Success(id) => id,
+        ReplyToMessageResp::InvalidMessage => panic!("invalid message"),
+    };
+    assert!(react(&joe_jwt, &reply_id, &party).await == AddReactionResp::Success);
+    match server_auth_req(
+        apis::ui::ApiRequests::def_message_thread(),
+        &sarah_jwt,
+        &MessageThreadReq {
+            id: release_id.clone(),
+            page: PageReq::new(),
+        },
+    )
+    .await
+    {
+        MessageThreadResp::Thread(thread) => {
+            let reply = &thread.replies.items[0];
+            assert_eq!(reply.reactions.len(), 1);
+            assert_eq!(reply.reactions[0].emoji, party);
+            assert_eq!(reply.reactions[0].count, 1);
+            assert!(!reply.reactions[0].reacted_by_me);
+        }
+        MessageThreadResp::InvalidMessage => panic!("invalid message"),
+    }
+    let _ = server_auth_req(
+        apis::ui::ApiRequests::def_delete_message(),
+        &joe_jwt,
+        &standup_id,
+    )
+    .await;
+    assert!(react(&joe_jwt, &standup_id, &party).await == AddReactionResp::InvalidMessage);
+
+    oserver.shutdown().await.unwrap();
+    db.cleanup().await;
+}
+
+async fn query_audit_log(jwt: &str, req: QueryAuditLogReq) -> Paginated<AuditLogEntry> {
+    server_auth_req(apis::ui::ApiRequests::def_query_audit_log(), jwt, &req).await
+}
+
+async fn send_message(jwt: &str, message: &str) {
+    let _ = server_auth_req(
+        apis::ui::ApiRequests::def_new_message(),
+        jwt,
+        &apis::ui::NewMessageReq {
+            message: message.to_owned(),
+        },
+    )
+    .await;
+}
+
+async fn recent_messages(jwt: &str, offset: u64, limit: u64) -> Paginated<Message> {
+    server_auth_req(
+        apis::ui::ApiRequests::def_recent_messages(),
+        jwt,
+        &apis::ui::RecentMessagesReq {
+            page: PageReq { offset, limit },
+        },
+    )
+    .await
+}
+
+fn test_oidc_config() -> OidcConfig {
+    OidcConfig {
+        issuer_url: MOCK_IDP_URL.to_owned(),
+        client_id: "protoapp".to_owned(),
+        client_secret: "idp-secret".to_owned(),
+        redirect_url: "http://localhost:8080/oidc/callback".to_owned(),
+        scopes: OidcConfig::def_scopes(),
+        create_users: false,
+        link_existing_users: true,
+        login_expiry_secs: OidcConfig::def_login_expiry_secs(),
+        metadata_cache_secs: OidcConfig::def_metadata_cache_secs(),
+        id_token_algorithms: OidcConfig::def_id_token_algorithms(),
+    }
+}
+
+/**
+ * Login with the mock provider's current user
+ */
+async fn oidc_login() -> OidcLoginResp {
+    let (url, state_cookie) = oidc_login_start().await;
+    let (code, state) = mock_idp::authorize(&url).await;
+    oidc_login_complete(&OidcLoginReq { code, state }, Some(&state_cookie)).await
+}
+
+/**
+ * Start an OpenID Connect login, returning the authorization url and the
+ * cookie that binds the login to the browser
+ */
+async fn oidc_login_start() -> (String, String) {
+    let resp = server_req(apis::ui::ApiRequests::def_oidc_login_start(), None, &()).await;
+    assert_eq!(resp.status(), 200);
+    let state_cookie = resp
+        .headers()
+        .get_all("set-cookie")
+        .iter()
+        .map(|v| v.to_str().unwrap())
+        .find(|v| v.starts_with("oidcState="))
+        .expect("oidc state cookie")
+        .split(';')
+        .next()
+        .unwrap()
+        .to_owned();
+    let url = match resp.json().await.unwrap() {
+        OidcLoginStartResp::AuthorizationUrl(url) => url,
+        OidcLoginStartResp::NotConfigured => panic!("oidc not configured"),
+    };
+    (url, state_cookie)
+}
+
+async fn oidc_login_complete(req: &OidcLoginReq, state_cookie: Option<&str>) -> OidcLoginResp {
+    let mut http_req = reqwest::Client::new()
+        .post(format!(
+            "http://localhost:8181{}",
+            apis::ui::ApiRequests::def_oidc_login().path
+        ))
+        .json(req);
+    if let Some(state_cookie) = state_cookie {
+        http_req = http_req.header("Cookie", state_cookie);
+    }
+    let resp = http_req.send().await.unwrap();
+    assert_eq!(resp.status(), 200);
+    resp.json().await.unwrap()
+}
+
+fn is_valid_login(resp: &apis::ui::LoginResp) -> bool {
+    match resp {
+        apis::ui::LoginResp::Tokens(_) => true,
+        apis::ui::LoginResp::InvalidCredentials => false,
+        apis::ui::LoginResp::EmailNotVerified => false,
+        apis::ui::LoginResp::Deactivated => false,
+        apis::ui::LoginResp::TotpRequired(_) => false,
+        apis::ui::LoginResp::TooManyAttempts(_) => false,
+    }
+}
+
+fn is_valid_refresh(resp: &apis::ui::RefreshResp) -> bool {
+    matches!(resp, apis::ui::RefreshResp::Tokens(_))
+}
+
+async fn refresh_tokens(refresh_jwt: &str) -> Option<LoginTokens> {
+    let resp = server_public_req(
+        apis::ui::ApiRequests::def_refresh(),
+        &RefreshReq {
+            refresh_token: Some(refresh_jwt.to_owned()),
+        },
+    )
+    .await;
+    match resp {
+        apis::ui::RefreshResp::Tokens(tokens) => Some(tokens),
+        apis::ui::RefreshResp::InvalidRefreshToken => None,
+    }
 }
 
 fn get_login_tokens(resp: apis::ui::LoginResp) -> Option<LoginTokens> {
     match resp {
         apis::ui::LoginResp::Tokens(tokens) => Some(tokens),
         apis::ui::LoginResp::InvalidCredentials => None,
+        apis::ui::LoginResp::EmailNotVerified => None,
+        apis::ui::LoginResp::Deactivated => None,
+        apis::ui::LoginResp::TotpRequired(_) => None,
+        apis::ui::LoginResp::TooManyAttempts(_) => None,
     }
 }
 
@@ -0,0 +1,120 @@
+use poem::http::StatusCode;
+use poem::web::Json;
+use poem::IntoResponse;
+use sqlx::postgres::{PgConnection, PgPoolOptions};
+use sqlx::Executor;
+use std::future::Future;
+use std::time::Duration;
+use tokio::time::Instant;
+
+use adl::gen::common::http::{HttpError, HttpReq};
+use adl::http::timeouts::request_timeout_secs;
+
+use crate::server::poem_adl_interop::{HandlerError, HandlerResult};
+
+tokio::task_local! {
+    static REQUEST_DEADLINE: Instant;
+}
+
+/**
+ * The timeout applied to requests whose endpoint doesn't specify one
+ */
+#[derive(Clone, Copy)]
+pub struct DefaultRequestTimeout(pub Duration);
+
+/**
+ * Get the timeout for a request, from the endpoint's RequestTimeoutSecs
+ * annotation if it has one
+ */
+pub fn request_timeout<I, O>(req: &poem::Request, endpoint: &HttpReq<I, O>) -> Duration {
+    match request_timeout_secs(&endpoint.path) {
+        Some(secs) => Duration::from_secs(secs as u64),
+        None => default_request_timeout(req),
+    }
+}
+
+pub fn default_request_timeout(req: &poem::Request) -> Duration {
+    req.data::<DefaultRequestTimeout>()
+        .expect("DefaultRequestTimeout should be configured")
+        .0
+}
+
+/**
+ * Run a request handler, failing if it takes longer than the given timeout.
+ *
+ * Database connections acquired by the handler have their statement_timeout
+ * limited to the time remaining, so postgres stops any in-flight work when
+ * the timeout fires, rather than leaving it running after the response has
+ * been sent.
+ */
+pub async fn with_timeout<T, F>(timeout: Duration, f: F) -> HandlerResult<T>
+where
+    F: Future<Output = HandlerResult<T>>,
+{
+    let deadline = Instant::now() + timeout;
+    let result = REQUEST_DEADLINE
+        .scope(deadline, tokio::time::timeout_at(deadline, f))
+        .await;
+    match result {
+        Ok(result) => result,
+        Err(_) => {
+            log::error!("request timed out after {:?}", timeout);
+            Err(timed_out())
+        }
+    }
+}
+
+pub fn timed_out() -> HandlerError {
+    let body = HttpError {
+        code: "timeout".to_owned(),
+        message: "The request took too long to complete".to_owned(),
+    };
+    let resp = Json(body)
+        .with_status(StatusCode::SERVICE_UNAVAILABLE)
+        .into_response();
+    HandlerError::Poem(poem::Error::from_response(resp))
+}
+
+/**
+ * Configure a connection pool such that each connection's statement_timeout
+ * matches the deadline of the request that acquires it. Connections acquired
+ * outside of a request have no statement timeout.
+ *
+ * This costs an extra round trip to the database on each acquire.
+ */
+pub fn with_statement_timeouts(options: PgPoolOptions) -> PgPoolOptions {
+    options
+        .after_connect(|conn, _meta| Box::pin(async move { set_statement_timeout(conn).await }))
+        .before_acquire(|conn, _meta| {
+            Box::pin(async move {
+                set_statement_timeout(conn).await?;
+                Ok(true)
+            })
+        })
+}
+
+async fn set_statement_timeout(conn: &mut PgConnection) -> sqlx::Result<()> {
+    let timeout_ms = match REQUEST_DEADLINE.try_with(|deadline| *deadline) {
+        // A zero statement_timeout disables it, so always allow at least 1ms
+        Ok(deadline) => deadline
+            .saturating_duration_since(Instant::now())
+            .as_millis()
+            .max(1),
+        Err(_) => 0,
+    };
+    conn.execute(format!("SET statement_timeout = {}", timeout_ms).as_ref())
+        .await?;
+    Ok(())
+}
+
+/**
+ * Whether a database error was caused by a statement being cancelled
+ * by its statement_timeout
+ */
+pub fn is_statement_timeout(err: &sqlx::Error) -> bool {
+    // 57014 is postgres's query_canceled error code
+    match err.as_database_error().and_then(|e| e.code()) {
+        Some(code) => code == "57014",
+        None => false,
+    }
+}
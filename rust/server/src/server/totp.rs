@@ -0,0 +1,115 @@
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use data_encoding::BASE32_NOPAD;
+use hmac::{Hmac, Mac};
+use sha1::Sha1;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// The parameters assumed by most authenticator apps
+const PERIOD_SECS: u64 = 30;
+const DIGITS: u32 = 6;
+
+// Codes from this many periods either side of the current one are accepted,
+// to allow for clock skew
+const SKEW_STEPS: u64 = 1;
+
+const RECOVERY_CODE_COUNT: usize = 10;
+
+/**
+ * Generate a new base32 encoded TOTP secret
+ */
+pub fn new_secret() -> String {
+    let mut bytes = [0u8; 20];
+    OsRng.fill_bytes(&mut bytes);
+    BASE32_NOPAD.encode(&bytes)
+}
+
+/**
+ * The uri from which authenticator apps enrol a secret, typically
+ * presented as a QR code
+ */
+pub fn otpauth_uri(issuer: &str, account: &str, secret: &str) -> String {
+    format!(
+        "otpauth://totp/{}:{}?secret={}&issuer={}&algorithm=SHA1&digits={}&period={}",
+        urlencoding::encode(issuer),
+        urlencoding::encode(account),
+        secret,
+        urlencoding::encode(issuer),
+        DIGITS,
+        PERIOD_SECS
+    )
+}
+
+/**
+ * The code for a base32 encoded secret at the given unix time
+ */
+#[cfg(test)]
+pub fn code_at(secret: &str, unix_secs: u64) -> Option<String> {
+    let secret = BASE32_NOPAD.decode(secret.as_bytes()).ok()?;
+    Some(code(&secret, unix_secs / PERIOD_SECS))
+}
+
+/**
+ * Check a code against a base32 encoded secret, returning the time step
+ * that it matched. Codes for steps up to and including `last_used_step`
+ * are rejected, so that a code can't be replayed.
+ */
+pub fn verify(secret: &str, code_to_check: &str, last_used_step: Option<u64>) -> Option<u64> {
+    let secret = BASE32_NOPAD.decode(secret.as_bytes()).ok()?;
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Time went backwards")
+        .as_secs()
+        / PERIOD_SECS;
+    (now - SKEW_STEPS..=now + SKEW_STEPS)
+        .filter(|step| last_used_step.is_none_or(|last| *step > last))
+        .find(|step| code(&secret, *step) == code_to_check.trim())
+}
+
+/**
+ * The RFC 6238 code for a secret at a time step
+ */
+pub fn code(secret: &[u8], step: u64) -> String {
+    let mut mac = Hmac::<Sha1>::new_from_slice(secret).expect("hmac accepts any key length");
+    mac.update(&step.to_be_bytes());
+    let hash = mac.finalize().into_bytes();
+    let offset = (hash[hash.len() - 1] & 0xf) as usize;
+    let value = u32::from_be_bytes([
+        hash[offset] & 0x7f,
+        hash[offset + 1],
+        hash[offset + 2],
+        hash[offset + 3],
+    ]);
+    format!(
+        "{:0width$}",
+        value % 10u32.pow(DIGITS),
+        width = DIGITS as usize
+    )
+}
+
+/**
+ * Generate a set of single use recovery codes, of the form xxxx-xxxx-xxxx-xxxx
+ */
+pub fn new_recovery_codes() -> Vec<String> {
+    (0..RECOVERY_CODE_COUNT)
+        .map(|_| {
+            let mut bytes = [0u8; 10];
+            OsRng.fill_bytes(&mut bytes);
+            let code = BASE32_NOPAD.encode(&bytes).to_lowercase();
+            let groups: Vec<&str> = (0..code.len())
+                .step_by(4)
+                .map(|i| &code[i..i + 4])
+                .collect();
+            groups.join("-")
+        })
+        .collect()
+}
+
+/**
+ * Normalise a recovery code as entered by a user, so that it can be hashed
+ */
+pub fn normalise_recovery_code(code: &str) -> String {
+    code.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
@@ -2,12 +2,62 @@
 --
 -- column comments show original ADL types
 
+create table api_key(
+  id text not null,                    -- String
+  user_id text not null,               -- AppUserId
+  name text not null,                  -- StringNE
+  key_hash text not null,              -- String
+  scopes jsonb not null,               -- Vector<ApiKeyScope>
+  created_at timestamp with time zone not null, -- Instant
+  expires_at timestamp with time zone not null, -- Instant
+  last_used_at timestamp with time zone, -- Nullable<Instant>
+  revoked_at timestamp with time zone, -- Nullable<Instant>
+  primary key(id)
+);
+
 create table app_user(
   id text not null,                    -- String
   fullname text not null,              -- StringNE
   email text not null,                 -- StringNE
-  is_admin boolean not null,           -- Bool
-  hashed_password text not null,       -- StringNE
+  hashed_password text,                -- Nullable<StringNE>
+  verified_at timestamp with time zone, -- Nullable<Instant>
+  verification_sent_at timestamp with time zone, -- Nullable<Instant>
+  deactivated_at timestamp with time zone, -- Nullable<Instant>
+  primary key(id)
+);
+
+create table audit_log(
+  id text not null,                    -- String
+  logged_at timestamp with time zone not null, -- Instant
+  actor text not null,                 -- AppUserId
+  impersonated text,                   -- Nullable<AppUserId>
+  endpoint text not null,              -- String
+  input jsonb not null,                -- Json
+  outcome text not null,               -- AuditOutcome
+  client_ip text,                      -- Nullable<String>
+  primary key(id)
+);
+
+create table invitation(
+  id text not null,                    -- String
+  email text not null,                 -- StringNE
+  roles jsonb not null,                -- Vector<RoleId>
+  token_hash text not null,            -- String
+  created_by text not null,            -- AppUserId
+  created_at timestamp with time zone not null, -- Instant
+  expires_at timestamp with time zone not null, -- Instant
+  used_at timestamp with time zone,    -- Nullable<Instant>
+  user_id text,                        -- Nullable<AppUserId>
+  revoked_at timestamp with time zone, -- Nullable<Instant>
+  primary key(id)
+);
+
+create table login_failure(
+  id text not null,                    -- String
+  email text not null,                 -- StringNE
+  failed_count integer not null,       -- Word32
+  last_failed_at timestamp with time zone not null, -- Instant
+  locked_until timestamp with time zone, -- Nullable<Instant>
   primary key(id)
 );
 
@@ -16,10 +66,191 @@ create table message(
   posted_at timestamp with time zone not null, -- Instant
   posted_by text not null,             -- AppUserId
   message text not null,               -- StringML
+  organisation_id text,                -- Nullable<OrganisationId>
+  reply_to text,                       -- Nullable<MessageId>
+  edited_at timestamp with time zone,  -- Nullable<Instant>
+  deleted_at timestamp with time zone, -- Nullable<Instant>
+  primary key(id)
+);
+
+create table message_reaction(
+  id text not null,                    -- String
+  message_id text not null,            -- MessageId
+  user_id text not null,               -- AppUserId
+  emoji text not null,                 -- StringNE
+  reacted_at timestamp with time zone not null, -- Instant
+  primary key(id)
+);
+
+create table message_revision(
+  id text not null,                    -- String
+  message_id text not null,            -- MessageId
+  message text not null,               -- StringML
+  written_at timestamp with time zone not null, -- Instant
+  replaced_at timestamp with time zone not null, -- Instant
+  replaced_by text not null,           -- AppUserId
+  primary key(id)
+);
+
+create table oidc_identity(
+  id text not null,                    -- String
+  user_id text not null,               -- AppUserId
+  issuer text not null,                -- String
+  subject text not null,               -- String
+  linked_at timestamp with time zone not null, -- Instant
+  primary key(id)
+);
+
+create table oidc_login(
+  id text not null,                    -- String
+  state_hash text not null,            -- String
+  code_verifier text not null,         -- String
+  nonce text not null,                 -- String
+  created_at timestamp with time zone not null, -- Instant
+  expires_at timestamp with time zone not null, -- Instant
+  used_at timestamp with time zone,    -- Nullable<Instant>
+  primary key(id)
+);
+
+create table organisation(
+  id text not null,                    -- String
+  name text not null,                  -- StringNE
+  created_at timestamp with time zone not null, -- Instant
+  primary key(id)
+);
+
+create table organisation_member(
+  id text not null,                    -- String
+  organisation_id text not null,       -- OrganisationId
+  user_id text not null,               -- AppUserId
+  roles jsonb not null,                -- Vector<RoleId>
+  joined_at timestamp with time zone not null, -- Instant
+  primary key(id)
+);
+
+create table password_reset_token(
+  id text not null,                    -- String
+  user_id text not null,               -- AppUserId
+  token_hash text not null,            -- String
+  created_at timestamp with time zone not null, -- Instant
+  expires_at timestamp with time zone not null, -- Instant
+  used_at timestamp with time zone,    -- Nullable<Instant>
+  primary key(id)
+);
+
+create table permission(
+  id text not null,                    -- String
+  role_id text not null,               -- RoleId
+  name text not null,                  -- StringNE
+  primary key(id)
+);
+
+create table refresh_token(
+  id text not null,                    -- String
+  user_id text not null,               -- AppUserId
+  family text not null,                -- String
+  issued_at timestamp with time zone not null, -- Instant
+  expires_at timestamp with time zone not null, -- Instant
+  used_at timestamp with time zone,    -- Nullable<Instant>
+  revoked_at timestamp with time zone, -- Nullable<Instant>
+  primary key(id)
+);
+
+create table role(
+  id text not null,                    -- String
+  name text not null,                  -- StringNE
+  description text not null,           -- String
+  primary key(id)
+);
+
+create table session(
+  id text not null,                    -- String
+  user_id text not null,               -- AppUserId
+  user_agent text,                     -- Nullable<String>
+  client_ip text,                      -- Nullable<String>
+  created_at timestamp with time zone not null, -- Instant
+  last_refreshed_at timestamp with time zone not null, -- Instant
+  revoked_at timestamp with time zone, -- Nullable<Instant>
+  organisation_id text,                -- Nullable<OrganisationId>
+  primary key(id)
+);
+
+create table totp_recovery_code(
+  id text not null,                    -- String
+  user_id text not null,               -- AppUserId
+  code_hash text not null,             -- String
+  used_at timestamp with time zone,    -- Nullable<Instant>
+  primary key(id)
+);
+
+create table user_role(
+  id text not null,                    -- String
+  user_id text not null,               -- AppUserId
+  role_id text not null,               -- RoleId
+  primary key(id)
+);
+
+create table user_totp(
+  id text not null,                    -- String
+  user_id text not null,               -- AppUserId
+  secret text not null,                -- String
+  confirmed_at timestamp with time zone, -- Nullable<Instant>
+  last_used_step bigint,               -- Nullable<Word64>
   primary key(id)
 );
 
+alter table api_key add constraint api_key_user_id_fk foreign key (user_id) references app_user(id);
+create index api_key_1_idx on api_key(user_id);
+alter table api_key add constraint api_key_1_con unique (key_hash);
 create index app_user_1_idx on app_user(email);
 alter table app_user add constraint app_user_1_con unique (email);
+alter table audit_log add constraint audit_log_actor_fk foreign key (actor) references app_user(id);
+alter table audit_log add constraint audit_log_impersonated_fk foreign key (impersonated) references app_user(id);
+create index audit_log_1_idx on audit_log(logged_at);
+create index audit_log_2_idx on audit_log(actor);
+alter table invitation add constraint invitation_created_by_fk foreign key (created_by) references app_user(id);
+alter table invitation add constraint invitation_user_id_fk foreign key (user_id) references app_user(id);
+create index invitation_1_idx on invitation(email);
+alter table invitation add constraint invitation_1_con unique (token_hash);
+alter table login_failure add constraint login_failure_1_con unique (email);
 alter table message add constraint message_posted_by_fk foreign key (posted_by) references app_user(id);
+alter table message add constraint message_organisation_id_fk foreign key (organisation_id) references organisation(id);
+alter table message add constraint message_reply_to_fk foreign key (reply_to) references message(id);
 create index message_1_idx on message(posted_at);
+create index message_2_idx on message(organisation_id);
+create index message_3_idx on message(reply_to);
+alter table message_reaction add constraint message_reaction_message_id_fk foreign key (message_id) references message(id);
+alter table message_reaction add constraint message_reaction_user_id_fk foreign key (user_id) references app_user(id);
+alter table message_reaction add constraint message_reaction_1_con unique (message_id, user_id, emoji);
+alter table message_revision add constraint message_revision_message_id_fk foreign key (message_id) references message(id);
+alter table message_revision add constraint message_revision_replaced_by_fk foreign key (replaced_by) references app_user(id);
+create index message_revision_1_idx on message_revision(message_id);
+alter table oidc_identity add constraint oidc_identity_user_id_fk foreign key (user_id) references app_user(id);
+create index oidc_identity_1_idx on oidc_identity(user_id);
+alter table oidc_identity add constraint oidc_identity_1_con unique (issuer, subject);
+alter table oidc_login add constraint oidc_login_1_con unique (state_hash);
+alter table organisation add constraint organisation_1_con unique (name);
+alter table organisation_member add constraint organisation_member_organisation_id_fk foreign key (organisation_id) references organisation(id);
+alter table organisation_member add constraint organisation_member_user_id_fk foreign key (user_id) references app_user(id);
+create index organisation_member_1_idx on organisation_member(user_id);
+alter table organisation_member add constraint organisation_member_1_con unique (organisation_id, user_id);
+alter table password_reset_token add constraint password_reset_token_user_id_fk foreign key (user_id) references app_user(id);
+create index password_reset_token_1_idx on password_reset_token(user_id);
+alter table password_reset_token add constraint password_reset_token_1_con unique (token_hash);
+alter table permission add constraint permission_role_id_fk foreign key (role_id) references role(id);
+alter table permission add constraint permission_1_con unique (role_id, name);
+alter table refresh_token add constraint refresh_token_user_id_fk foreign key (user_id) references app_user(id);
+create index refresh_token_1_idx on refresh_token(user_id);
+create index refresh_token_2_idx on refresh_token(family);
+alter table role add constraint role_1_con unique (name);
+alter table session add constraint session_user_id_fk foreign key (user_id) references app_user(id);
+alter table session add constraint session_organisation_id_fk foreign key (organisation_id) references organisation(id);
+create index session_1_idx on session(user_id);
+alter table totp_recovery_code add constraint totp_recovery_code_user_id_fk foreign key (user_id) references app_user(id);
+create index totp_recovery_code_1_idx on totp_recovery_code(user_id);
+alter table user_role add constraint user_role_user_id_fk foreign key (user_id) references app_user(id);
+alter table user_role add constraint user_role_role_id_fk foreign key (role_id) references role(id);
+create index user_role_1_idx on user_role(role_id);
+alter table user_role add constraint user_role_1_con unique (user_id, role_id);
+alter table user_totp add constraint user_totp_user_id_fk foreign key (user_id) references app_user(id);
+alter table user_totp add constraint user_totp_1_con unique (user_id);
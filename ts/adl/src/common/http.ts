@@ -34,6 +34,21 @@ export function texprHttpMethod(): ADL.ATypeExpr<HttpMethod> {
   return {value : {typeRef : {kind: "reference", value : snHttpMethod}, parameters : []}};
 }
 
+/**
+ * An annotation on an HttpReq, overriding the server's default timeout
+ * for that request. It must be at least 1.
+ */
+export type RequestTimeoutSecs = number;
+
+const RequestTimeoutSecs_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"An annotation on an HttpReq, overriding the server's default timeout\nfor that request. It must be at least 1.\n"}],"name":"RequestTimeoutSecs","type_":{"kind":"type_","value":{"typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Word32"}},"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"common.http"};
+
+export const snRequestTimeoutSecs: ADL.ScopedName = {moduleName:"common.http", name:"RequestTimeoutSecs"};
+
+export function texprRequestTimeoutSecs(): ADL.ATypeExpr<RequestTimeoutSecs> {
+  return {value : {typeRef : {kind: "reference", value : snRequestTimeoutSecs}, parameters : []}};
+}
+
 export interface HttpSecurity_Public {
   kind: 'public';
 }
@@ -87,9 +102,41 @@ export function texprUnit(): ADL.ATypeExpr<Unit> {
   return {value : {typeRef : {kind: "reference", value : snUnit}, parameters : []}};
 }
 
+/**
+ * The json body of an error response, where the server
+ * provides one
+ */
+export interface HttpError {
+  code: string;
+  message: string;
+}
+
+export function makeHttpError(
+  input: {
+    code: string,
+    message: string,
+  }
+): HttpError {
+  return {
+    code: input.code,
+    message: input.message,
+  };
+}
+
+const HttpError_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"The json body of an error response, where the server\nprovides one\n"}],"name":"HttpError","type_":{"kind":"struct_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"code","serializedName":"code","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"String"}}},{"annotations":[],"default":{"kind":"nothing"},"name":"message","serializedName":"message","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"String"}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"common.http"};
+
+export const snHttpError: ADL.ScopedName = {moduleName:"common.http", name:"HttpError"};
+
+export function texprHttpError(): ADL.ATypeExpr<HttpError> {
+  return {value : {typeRef : {kind: "reference", value : snHttpError}, parameters : []}};
+}
+
 export const _AST_MAP: { [key: string]: ADL.ScopedDecl } = {
   "common.http.HttpReq" : HttpReq_AST,
   "common.http.HttpMethod" : HttpMethod_AST,
+  "common.http.RequestTimeoutSecs" : RequestTimeoutSecs_AST,
   "common.http.HttpSecurity" : HttpSecurity_AST,
-  "common.http.Unit" : Unit_AST
+  "common.http.Unit" : Unit_AST,
+  "common.http.HttpError" : HttpError_AST
 };
@@ -11,77 +11,415 @@ export interface ApiRequests {
    * AWS default compatible health check
    */
   healthy: common_http.HttpReq<null, null>;
+  /**
+   * The public keys used to verify access tokens, as a JSON web key set
+   */
+  jwks: common_http.HttpReq<null, Jwks>;
   /**
    * Login a user
    * The response will set an httpOnly cookie containing the refresh token
    */
   login: common_http.HttpReq<LoginReq, LoginResp>;
+  /**
+   * Complete a login that requires a second factor, with the challenge
+   * token from the `totp_required` login response, and either a code
+   * from the user's authenticator app or one of their recovery codes.
+   * The response will set an httpOnly cookie containing the refresh token
+   */
+  login_totp: common_http.HttpReq<LoginTotpReq, LoginTotpResp>;
+  /**
+   * Start a login with the OpenID Connect provider. The client should
+   * send the user to the returned authorization url, from which the
+   * provider will redirect them to the configured `redirect_url`.
+   * The response will set a short-lived httpOnly cookie binding the login
+   * to the browser, which must be sent with the `oidc_login` request
+   */
+  oidc_login_start: common_http.HttpReq<null, OidcLoginStartResp>;
+  /**
+   * Complete a login with the OpenID Connect provider, using the `code`
+   * and `state` query parameters of its redirect back to the app.
+   * The response will set an httpOnly cookie containing the refresh token
+   */
+  oidc_login: common_http.HttpReq<OidcLoginReq, OidcLoginResp>;
   /**
    * Get a refreshed access token
    * If the refresh token is not provided in the request body, then it will
    * be read from the refrestToken cookie in the request.
+   * Refresh tokens are single use: a new refresh token is returned (and set
+   * in the cookie) with each access token. Reuse of a refresh token revokes
+   * every refresh token descended from the same login.
+   * Requests that use the cookie must pass the CSRF checks configured by
+   * `csrf` in the server config.
    */
   refresh: common_http.HttpReq<RefreshReq, RefreshResp>;
   /**
-   * Clear the `refreshToken` cookie.
+   * Revoke the refresh token, and clear the `refreshToken` cookie.
+   * If the refresh token is not provided in the request body, then it will
+   * be read from the refrestToken cookie in the request.
+   * As for `refresh`, requests that use the cookie are CSRF checked.
+   */
+  logout: common_http.HttpReq<LogoutReq, common_http.Unit>;
+  /**
+   * Request a password reset link be emailed to a user.
+   * To avoid revealing which email addresses have accounts, this
+   * succeeds whether or not the email address is known.
+   */
+  request_password_reset: common_http.HttpReq<RequestPasswordResetReq, common_http.Unit>;
+  /**
+   * Set a new password, using the token from a password reset link.
+   * Each token can only be used once. A successful reset revokes all
+   * of the user's refresh tokens.
    */
-  logout: common_http.HttpReq<common_http.Unit, common_http.Unit>;
+  reset_password: common_http.HttpReq<ResetPasswordReq, ResetPasswordResp>;
+  /**
+   * Create an account, using the token from an invitation email. The
+   * invitation can only be used once, and the new user's email address
+   * counts as verified.
+   * The response will set an httpOnly cookie containing the refresh token
+   */
+  signup: common_http.HttpReq<SignupReq, SignupResp>;
+  /**
+   * Verify a user's email address, using the token from the link
+   * emailed to them.
+   */
+  verify_email: common_http.HttpReq<VerifyEmailReq, VerifyEmailResp>;
+  /**
+   * Resend the email address verification link to an unverified user.
+   * Links are sent at most once every `email_verification_resend_secs`.
+   * To avoid revealing which email addresses have accounts, this
+   * succeeds whether or not a link was sent.
+   */
+  resend_verification_email: common_http.HttpReq<ResendVerificationEmailReq, common_http.Unit>;
+  /**
+   * Start enrolling the user in TOTP two factor authentication,
+   * replacing any unconfirmed enrolment. The enrolment takes effect
+   * once confirmed with `confirm_totp_enrolment`.
+   */
+  begin_totp_enrolment: common_http.HttpReq<null, BeginTotpEnrolmentResp>;
+  /**
+   * Confirm a TOTP enrolment with a first code from the user's
+   * authenticator app. The returned recovery codes can each be used
+   * once in place of a code, and are not retrievable later.
+   */
+  confirm_totp_enrolment: common_http.HttpReq<ConfirmTotpEnrolmentReq, ConfirmTotpEnrolmentResp>;
   /**
    * Post a message to the noticeboard
    */
   new_message: common_http.HttpReq<NewMessageReq, protoapp_db.MessageId>;
   /**
-   * Get recent noticeboard messages
+   * Get recent top level noticeboard messages, posted within the
+   * caller's organisation
    */
   recent_messages: common_http.HttpReq<RecentMessagesReq, Paginated<Message>>;
+  /**
+   * Reply to a top level message, starting or continuing its thread
+   */
+  reply_to_message: common_http.HttpReq<ReplyToMessageReq, ReplyToMessageResp>;
+  /**
+   * Get a top level message, and a page of its replies in the order they
+   * were posted
+   */
+  message_thread: common_http.HttpReq<MessageThreadReq, MessageThreadResp>;
+  /**
+   * React to a message with an emoji. Reacting again with the same
+   * emoji has no effect.
+   */
+  add_reaction: common_http.HttpReq<MessageReactionReq, AddReactionResp>;
+  /**
+   * Remove the caller's reaction to a message
+   */
+  remove_reaction: common_http.HttpReq<MessageReactionReq, common_http.Unit>;
+  /**
+   * Edit the text of a message. Users can edit their own messages, and
+   * admins can edit any message. The prior text is kept as a revision.
+   */
+  edit_message: common_http.HttpReq<EditMessageReq, EditMessageResp>;
+  /**
+   * Delete a message, leaving a tombstone in its place. Users can delete
+   * their own messages, and admins can delete any message.
+   */
+  delete_message: common_http.HttpReq<protoapp_db.MessageId, DeleteMessageResp>;
+  /**
+   * Get the prior versions of a message, most recent first. Available to
+   * the message's author and admins.
+   */
+  message_revisions: common_http.HttpReq<protoapp_db.MessageId, MessageRevisionsResp>;
   /**
    * Gets info about the logged in user
    */
   who_am_i: common_http.HttpReq<null, UserWithId>;
+  /**
+   * Change the logged in user's name and email address. A changed
+   * email address needs the current password, and to be verified again.
+   * Not available to admins impersonating the user.
+   */
+  update_profile: common_http.HttpReq<UpdateProfileReq, UpdateProfileResp>;
+  /**
+   * Change the logged in user's password. This revokes all of the
+   * user's other sessions.
+   */
+  change_password: common_http.HttpReq<ChangePasswordReq, ChangePasswordResp>;
+  /**
+   * List the logged in user's active sessions, most recently
+   * refreshed first
+   */
+  my_sessions: common_http.HttpReq<null, SessionSummaryWithId[]>;
+  /**
+   * Revoke one of the logged in user's sessions, so that it can no
+   * longer be refreshed. Access tokens already issued for the session
+   * remain valid until they expire.
+   */
+  revoke_session: common_http.HttpReq<protoapp_db.SessionId, common_http.Unit>;
+  /**
+   * Revoke all of the logged in user's sessions, other than the one
+   * making the request
+   */
+  revoke_other_sessions: common_http.HttpReq<null, common_http.Unit>;
   /**
    * Create a new user
    */
-  create_user: common_http.HttpReq<UserDetails, protoapp_db.AppUserId>;
+  create_user: common_http.HttpReq<UserDetails, CreateUserResp>;
   /**
    * Update a user
    */
-  update_user: common_http.HttpReq<WithId<protoapp_db.AppUserId, UserDetails>, common_http.Unit>;
+  update_user: common_http.HttpReq<WithId<protoapp_db.AppUserId, UserDetails>, UpdateUserResp>;
+  /**
+   * Unlock a user's account after too many failed logins
+   */
+  unlock_user: common_http.HttpReq<protoapp_db.AppUserId, common_http.Unit>;
+  /**
+   * Issue a short lived access token with which an admin can act as
+   * a user. The token has the user's `sub` and an `act` claim with the
+   * admin's. It is refused by admin endpoints, and every request made
+   * with it is written to the audit log.
+   */
+  impersonate_user: common_http.HttpReq<protoapp_db.AppUserId, ImpersonateUserResp>;
+  /**
+   * Revoke every session of a user, forcing them to login again once
+   * their current access tokens expire
+   */
+  revoke_user_sessions: common_http.HttpReq<protoapp_db.AppUserId, common_http.Unit>;
+  /**
+   * Deactivate a user, revoking their sessions and refusing their
+   * access tokens and API keys. Their messages are kept.
+   */
+  deactivate_user: common_http.HttpReq<protoapp_db.AppUserId, DeactivateUserResp>;
+  /**
+   * Reactivate a deactivated user, who can then login again
+   */
+  reactivate_user: common_http.HttpReq<protoapp_db.AppUserId, ReactivateUserResp>;
+  /**
+   * Replace a user's roles. Access tokens carry the user's roles, so
+   * the change takes effect as their tokens are refreshed.
+   */
+  set_user_roles: common_http.HttpReq<SetUserRolesReq, SetUserRolesResp>;
+  /**
+   * Create a role, whose members are granted its permissions
+   */
+  create_role: common_http.HttpReq<CreateRoleReq, CreateRoleResp>;
+  /**
+   * Query roles, ordered by name
+   */
+  query_roles: common_http.HttpReq<QueryRolesReq, Paginated<RoleDetailsWithId>>;
+  /**
+   * Replace the permissions granted by a role
+   */
+  set_role_permissions: common_http.HttpReq<SetRolePermissionsReq, SetRolePermissionsResp>;
+  /**
+   * Delete a role, removing it from its members
+   */
+  delete_role: common_http.HttpReq<protoapp_db.RoleId, DeleteRoleResp>;
+  /**
+   * Create an organisation
+   */
+  create_organisation: common_http.HttpReq<CreateOrganisationReq, CreateOrganisationResp>;
+  /**
+   * Query organisations, ordered by name
+   */
+  query_organisations: common_http.HttpReq<QueryOrganisationsReq, Paginated<OrganisationWithId>>;
+  /**
+   * Add a user to an organisation, or replace the roles they hold
+   * within it. The admin role can't be granted per organisation.
+   */
+  set_organisation_member: common_http.HttpReq<SetOrganisationMemberReq, SetOrganisationMemberResp>;
+  /**
+   * Remove a user from an organisation
+   */
+  remove_organisation_member: common_http.HttpReq<RemoveOrganisationMemberReq, common_http.Unit>;
+  /**
+   * Switch the organisation that the logged in session acts within,
+   * returning an access token with the new `org` claim. Members can
+   * switch to their organisations, and admins to any, or to none to
+   * act across the whole deployment.
+   */
+  switch_organisation: common_http.HttpReq<SwitchOrganisationReq, SwitchOrganisationResp>;
+  /**
+   * Invite someone to create their own account, by emailing them a
+   * signup link
+   */
+  create_invitation: common_http.HttpReq<CreateInvitationReq, CreateInvitationResp>;
+  /**
+   * Query invitations, most recently created first
+   */
+  query_invitations: common_http.HttpReq<QueryInvitationsReq, Paginated<InvitationSummaryWithId>>;
+  /**
+   * Revoke an unused invitation, so that it can no longer be used to
+   * sign up
+   */
+  revoke_invitation: common_http.HttpReq<protoapp_db.InvitationId, common_http.Unit>;
   /**
-   * Query users
+   * Query users. Admins acting within an organisation only see its
+   * members.
    */
   query_users: common_http.HttpReq<QueryUsersReq, Paginated<UserWithId>>;
+  /**
+   * Get the number of access and refresh tokens that have been
+   * rejected since the server started, by reason
+   */
+  jwt_failure_counts: common_http.HttpReq<null, JwtFailureCounts>;
+  /**
+   * Issue an API key, with which a service account can call endpoints
+   * secured by `token` or `tokenWithRole`, by passing it as a bearer
+   * token. The key itself is only returned by this call.
+   */
+  create_api_key: common_http.HttpReq<CreateApiKeyReq, CreateApiKeyResp>;
+  /**
+   * Query API keys, most recently created first
+   */
+  query_api_keys: common_http.HttpReq<QueryApiKeysReq, Paginated<ApiKeySummaryWithId>>;
+  /**
+   * Revoke an API key, so that it can no longer be used
+   */
+  revoke_api_key: common_http.HttpReq<protoapp_db.ApiKeyId, common_http.Unit>;
+  /**
+   * Query the audit log of calls to admin only endpoints
+   * Results are ordered most recent first.
+   */
+  query_audit_log: common_http.HttpReq<QueryAuditLogReq, Paginated<AuditLogEntry>>;
 }
 
 export function makeApiRequests(
   input: {
     healthy?: common_http.HttpReq<null, null>,
+    jwks?: common_http.HttpReq<null, Jwks>,
     login?: common_http.HttpReq<LoginReq, LoginResp>,
+    login_totp?: common_http.HttpReq<LoginTotpReq, LoginTotpResp>,
+    oidc_login_start?: common_http.HttpReq<null, OidcLoginStartResp>,
+    oidc_login?: common_http.HttpReq<OidcLoginReq, OidcLoginResp>,
     refresh?: common_http.HttpReq<RefreshReq, RefreshResp>,
-    logout?: common_http.HttpReq<common_http.Unit, common_http.Unit>,
+    logout?: common_http.HttpReq<LogoutReq, common_http.Unit>,
+    request_password_reset?: common_http.HttpReq<RequestPasswordResetReq, common_http.Unit>,
+    reset_password?: common_http.HttpReq<ResetPasswordReq, ResetPasswordResp>,
+    signup?: common_http.HttpReq<SignupReq, SignupResp>,
+    verify_email?: common_http.HttpReq<VerifyEmailReq, VerifyEmailResp>,
+    resend_verification_email?: common_http.HttpReq<ResendVerificationEmailReq, common_http.Unit>,
+    begin_totp_enrolment?: common_http.HttpReq<null, BeginTotpEnrolmentResp>,
+    confirm_totp_enrolment?: common_http.HttpReq<ConfirmTotpEnrolmentReq, ConfirmTotpEnrolmentResp>,
     new_message?: common_http.HttpReq<NewMessageReq, protoapp_db.MessageId>,
     recent_messages?: common_http.HttpReq<RecentMessagesReq, Paginated<Message>>,
+    reply_to_message?: common_http.HttpReq<ReplyToMessageReq, ReplyToMessageResp>,
+    message_thread?: common_http.HttpReq<MessageThreadReq, MessageThreadResp>,
+    add_reaction?: common_http.HttpReq<MessageReactionReq, AddReactionResp>,
+    remove_reaction?: common_http.HttpReq<MessageReactionReq, common_http.Unit>,
+    edit_message?: common_http.HttpReq<EditMessageReq, EditMessageResp>,
+    delete_message?: common_http.HttpReq<protoapp_db.MessageId, DeleteMessageResp>,
+    message_revisions?: common_http.HttpReq<protoapp_db.MessageId, MessageRevisionsResp>,
     who_am_i?: common_http.HttpReq<null, UserWithId>,
-    create_user?: common_http.HttpReq<UserDetails, protoapp_db.AppUserId>,
-    update_user?: common_http.HttpReq<WithId<protoapp_db.AppUserId, UserDetails>, common_http.Unit>,
+    update_profile?: common_http.HttpReq<UpdateProfileReq, UpdateProfileResp>,
+    change_password?: common_http.HttpReq<ChangePasswordReq, ChangePasswordResp>,
+    my_sessions?: common_http.HttpReq<null, SessionSummaryWithId[]>,
+    revoke_session?: common_http.HttpReq<protoapp_db.SessionId, common_http.Unit>,
+    revoke_other_sessions?: common_http.HttpReq<null, common_http.Unit>,
+    create_user?: common_http.HttpReq<UserDetails, CreateUserResp>,
+    update_user?: common_http.HttpReq<WithId<protoapp_db.AppUserId, UserDetails>, UpdateUserResp>,
+    unlock_user?: common_http.HttpReq<protoapp_db.AppUserId, common_http.Unit>,
+    impersonate_user?: common_http.HttpReq<protoapp_db.AppUserId, ImpersonateUserResp>,
+    revoke_user_sessions?: common_http.HttpReq<protoapp_db.AppUserId, common_http.Unit>,
+    deactivate_user?: common_http.HttpReq<protoapp_db.AppUserId, DeactivateUserResp>,
+    reactivate_user?: common_http.HttpReq<protoapp_db.AppUserId, ReactivateUserResp>,
+    set_user_roles?: common_http.HttpReq<SetUserRolesReq, SetUserRolesResp>,
+    create_role?: common_http.HttpReq<CreateRoleReq, CreateRoleResp>,
+    query_roles?: common_http.HttpReq<QueryRolesReq, Paginated<RoleDetailsWithId>>,
+    set_role_permissions?: common_http.HttpReq<SetRolePermissionsReq, SetRolePermissionsResp>,
+    delete_role?: common_http.HttpReq<protoapp_db.RoleId, DeleteRoleResp>,
+    create_organisation?: common_http.HttpReq<CreateOrganisationReq, CreateOrganisationResp>,
+    query_organisations?: common_http.HttpReq<QueryOrganisationsReq, Paginated<OrganisationWithId>>,
+    set_organisation_member?: common_http.HttpReq<SetOrganisationMemberReq, SetOrganisationMemberResp>,
+    remove_organisation_member?: common_http.HttpReq<RemoveOrganisationMemberReq, common_http.Unit>,
+    switch_organisation?: common_http.HttpReq<SwitchOrganisationReq, SwitchOrganisationResp>,
+    create_invitation?: common_http.HttpReq<CreateInvitationReq, CreateInvitationResp>,
+    query_invitations?: common_http.HttpReq<QueryInvitationsReq, Paginated<InvitationSummaryWithId>>,
+    revoke_invitation?: common_http.HttpReq<protoapp_db.InvitationId, common_http.Unit>,
     query_users?: common_http.HttpReq<QueryUsersReq, Paginated<UserWithId>>,
+    jwt_failure_counts?: common_http.HttpReq<null, JwtFailureCounts>,
+    create_api_key?: common_http.HttpReq<CreateApiKeyReq, CreateApiKeyResp>,
+    query_api_keys?: common_http.HttpReq<QueryApiKeysReq, Paginated<ApiKeySummaryWithId>>,
+    revoke_api_key?: common_http.HttpReq<protoapp_db.ApiKeyId, common_http.Unit>,
+    query_audit_log?: common_http.HttpReq<QueryAuditLogReq, Paginated<AuditLogEntry>>,
   }
 ): ApiRequests {
   return {
     healthy: input.healthy === undefined ? {method : "get", path : "/", security : {kind : "public"}, reqType : ADL.texprVoid(), respType : ADL.texprVoid()} : input.healthy,
+    jwks: input.jwks === undefined ? {method : "get", path : "/.well-known/jwks.json", security : {kind : "public"}, reqType : ADL.texprVoid(), respType : texprJwks()} : input.jwks,
     login: input.login === undefined ? {method : "post", path : "/login", security : {kind : "public"}, reqType : texprLoginReq(), respType : texprLoginResp()} : input.login,
+    login_totp: input.login_totp === undefined ? {method : "post", path : "/login/totp", security : {kind : "public"}, reqType : texprLoginTotpReq(), respType : texprLoginTotpResp()} : input.login_totp,
+    oidc_login_start: input.oidc_login_start === undefined ? {method : "post", path : "/oidc/start", security : {kind : "public"}, reqType : ADL.texprVoid(), respType : texprOidcLoginStartResp()} : input.oidc_login_start,
+    oidc_login: input.oidc_login === undefined ? {method : "post", path : "/oidc/login", security : {kind : "public"}, reqType : texprOidcLoginReq(), respType : texprOidcLoginResp()} : input.oidc_login,
     refresh: input.refresh === undefined ? {method : "post", path : "/refresh", security : {kind : "public"}, reqType : texprRefreshReq(), respType : texprRefreshResp()} : input.refresh,
-    logout: input.logout === undefined ? {method : "post", path : "/logout", security : {kind : "public"}, reqType : common_http.texprUnit(), respType : common_http.texprUnit()} : input.logout,
+    logout: input.logout === undefined ? {method : "post", path : "/logout", security : {kind : "public"}, reqType : texprLogoutReq(), respType : common_http.texprUnit()} : input.logout,
+    request_password_reset: input.request_password_reset === undefined ? {method : "post", path : "/password-reset/request", security : {kind : "public"}, reqType : texprRequestPasswordResetReq(), respType : common_http.texprUnit()} : input.request_password_reset,
+    reset_password: input.reset_password === undefined ? {method : "post", path : "/password-reset/complete", security : {kind : "public"}, reqType : texprResetPasswordReq(), respType : texprResetPasswordResp()} : input.reset_password,
+    signup: input.signup === undefined ? {method : "post", path : "/signup", security : {kind : "public"}, reqType : texprSignupReq(), respType : texprSignupResp()} : input.signup,
+    verify_email: input.verify_email === undefined ? {method : "post", path : "/email/verify", security : {kind : "public"}, reqType : texprVerifyEmailReq(), respType : texprVerifyEmailResp()} : input.verify_email,
+    resend_verification_email: input.resend_verification_email === undefined ? {method : "post", path : "/email/resend-verification", security : {kind : "public"}, reqType : texprResendVerificationEmailReq(), respType : common_http.texprUnit()} : input.resend_verification_email,
+    begin_totp_enrolment: input.begin_totp_enrolment === undefined ? {method : "post", path : "/totp/enrol", security : {kind : "token"}, reqType : ADL.texprVoid(), respType : texprBeginTotpEnrolmentResp()} : input.begin_totp_enrolment,
+    confirm_totp_enrolment: input.confirm_totp_enrolment === undefined ? {method : "post", path : "/totp/confirm", security : {kind : "token"}, reqType : texprConfirmTotpEnrolmentReq(), respType : texprConfirmTotpEnrolmentResp()} : input.confirm_totp_enrolment,
     new_message: input.new_message === undefined ? {method : "post", path : "/messages/new", security : {kind : "token"}, reqType : texprNewMessageReq(), respType : protoapp_db.texprMessageId()} : input.new_message,
     recent_messages: input.recent_messages === undefined ? {method : "get", path : "/messages/recent", security : {kind : "token"}, reqType : texprRecentMessagesReq(), respType : texprPaginated(texprMessage())} : input.recent_messages,
+    reply_to_message: input.reply_to_message === undefined ? {method : "post", path : "/messages/reply", security : {kind : "token"}, reqType : texprReplyToMessageReq(), respType : texprReplyToMessageResp()} : input.reply_to_message,
+    message_thread: input.message_thread === undefined ? {method : "get", path : "/messages/thread", security : {kind : "token"}, reqType : texprMessageThreadReq(), respType : texprMessageThreadResp()} : input.message_thread,
+    add_reaction: input.add_reaction === undefined ? {method : "post", path : "/messages/reactions/add", security : {kind : "token"}, reqType : texprMessageReactionReq(), respType : texprAddReactionResp()} : input.add_reaction,
+    remove_reaction: input.remove_reaction === undefined ? {method : "post", path : "/messages/reactions/remove", security : {kind : "token"}, reqType : texprMessageReactionReq(), respType : common_http.texprUnit()} : input.remove_reaction,
+    edit_message: input.edit_message === undefined ? {method : "post", path : "/messages/edit", security : {kind : "token"}, reqType : texprEditMessageReq(), respType : texprEditMessageResp()} : input.edit_message,
+    delete_message: input.delete_message === undefined ? {method : "post", path : "/messages/delete", security : {kind : "token"}, reqType : protoapp_db.texprMessageId(), respType : texprDeleteMessageResp()} : input.delete_message,
+    message_revisions: input.message_revisions === undefined ? {method : "get", path : "/messages/revisions", security : {kind : "token"}, reqType : protoapp_db.texprMessageId(), respType : texprMessageRevisionsResp()} : input.message_revisions,
     who_am_i: input.who_am_i === undefined ? {method : "get", path : "/whoami", security : {kind : "token"}, reqType : ADL.texprVoid(), respType : texprUserWithId()} : input.who_am_i,
-    create_user: input.create_user === undefined ? {method : "post", path : "/users/create", security : {kind : "tokenWithRole", value : "admin"}, reqType : texprUserDetails(), respType : protoapp_db.texprAppUserId()} : input.create_user,
-    update_user: input.update_user === undefined ? {method : "post", path : "/users/update", security : {kind : "tokenWithRole", value : "admin"}, reqType : texprWithId(protoapp_db.texprAppUserId(), texprUserDetails()), respType : common_http.texprUnit()} : input.update_user,
+    update_profile: input.update_profile === undefined ? {method : "post", path : "/profile/update", security : {kind : "token"}, reqType : texprUpdateProfileReq(), respType : texprUpdateProfileResp()} : input.update_profile,
+    change_password: input.change_password === undefined ? {method : "post", path : "/profile/change-password", security : {kind : "token"}, reqType : texprChangePasswordReq(), respType : texprChangePasswordResp()} : input.change_password,
+    my_sessions: input.my_sessions === undefined ? {method : "get", path : "/sessions", security : {kind : "token"}, reqType : ADL.texprVoid(), respType : ADL.texprVector(texprSessionSummaryWithId())} : input.my_sessions,
+    revoke_session: input.revoke_session === undefined ? {method : "post", path : "/sessions/revoke", security : {kind : "token"}, reqType : protoapp_db.texprSessionId(), respType : common_http.texprUnit()} : input.revoke_session,
+    revoke_other_sessions: input.revoke_other_sessions === undefined ? {method : "post", path : "/sessions/revoke-others", security : {kind : "token"}, reqType : ADL.texprVoid(), respType : common_http.texprUnit()} : input.revoke_other_sessions,
+    create_user: input.create_user === undefined ? {method : "post", path : "/users/create", security : {kind : "tokenWithRole", value : "admin"}, reqType : texprUserDetails(), respType : texprCreateUserResp()} : input.create_user,
+    update_user: input.update_user === undefined ? {method : "post", path : "/users/update", security : {kind : "tokenWithRole", value : "admin"}, reqType : texprWithId(protoapp_db.texprAppUserId(), texprUserDetails()), respType : texprUpdateUserResp()} : input.update_user,
+    unlock_user: input.unlock_user === undefined ? {method : "post", path : "/users/unlock", security : {kind : "tokenWithRole", value : "admin"}, reqType : protoapp_db.texprAppUserId(), respType : common_http.texprUnit()} : input.unlock_user,
+    impersonate_user: input.impersonate_user === undefined ? {method : "post", path : "/users/impersonate", security : {kind : "tokenWithRole", value : "admin"}, reqType : protoapp_db.texprAppUserId(), respType : texprImpersonateUserResp()} : input.impersonate_user,
+    revoke_user_sessions: input.revoke_user_sessions === undefined ? {method : "post", path : "/users/revoke-sessions", security : {kind : "tokenWithRole", value : "admin"}, reqType : protoapp_db.texprAppUserId(), respType : common_http.texprUnit()} : input.revoke_user_sessions,
+    deactivate_user: input.deactivate_user === undefined ? {method : "post", path : "/users/deactivate", security : {kind : "tokenWithRole", value : "admin"}, reqType : protoapp_db.texprAppUserId(), respType : texprDeactivateUserResp()} : input.deactivate_user,
+    reactivate_user: input.reactivate_user === undefined ? {method : "post", path : "/users/reactivate", security : {kind : "tokenWithRole", value : "admin"}, reqType : protoapp_db.texprAppUserId(), respType : texprReactivateUserResp()} : input.reactivate_user,
+    set_user_roles: input.set_user_roles === undefined ? {method : "post", path : "/users/set-roles", security : {kind : "tokenWithRole", value : "admin"}, reqType : texprSetUserRolesReq(), respType : texprSetUserRolesResp()} : input.set_user_roles,
+    create_role: input.create_role === undefined ? {method : "post", path : "/roles/create", security : {kind : "tokenWithRole", value : "admin"}, reqType : texprCreateRoleReq(), respType : texprCreateRoleResp()} : input.create_role,
+    query_roles: input.query_roles === undefined ? {method : "get", path : "/roles/query", security : {kind : "tokenWithRole", value : "admin"}, reqType : texprQueryRolesReq(), respType : texprPaginated(texprRoleDetailsWithId())} : input.query_roles,
+    set_role_permissions: input.set_role_permissions === undefined ? {method : "post", path : "/roles/set-permissions", security : {kind : "tokenWithRole", value : "admin"}, reqType : texprSetRolePermissionsReq(), respType : texprSetRolePermissionsResp()} : input.set_role_permissions,
+    delete_role: input.delete_role === undefined ? {method : "post", path : "/roles/delete", security : {kind : "tokenWithRole", value : "admin"}, reqType : protoapp_db.texprRoleId(), respType : texprDeleteRoleResp()} : input.delete_role,
+    create_organisation: input.create_organisation === undefined ? {method : "post", path : "/organisations/create", security : {kind : "tokenWithRole", value : "admin"}, reqType : texprCreateOrganisationReq(), respType : texprCreateOrganisationResp()} : input.create_organisation,
+    query_organisations: input.query_organisations === undefined ? {method : "get", path : "/organisations/query", security : {kind : "tokenWithRole", value : "admin"}, reqType : texprQueryOrganisationsReq(), respType : texprPaginated(texprOrganisationWithId())} : input.query_organisations,
+    set_organisation_member: input.set_organisation_member === undefined ? {method : "post", path : "/organisations/set-member", security : {kind : "tokenWithRole", value : "admin"}, reqType : texprSetOrganisationMemberReq(), respType : texprSetOrganisationMemberResp()} : input.set_organisation_member,
+    remove_organisation_member: input.remove_organisation_member === undefined ? {method : "post", path : "/organisations/remove-member", security : {kind : "tokenWithRole", value : "admin"}, reqType : texprRemoveOrganisationMemberReq(), respType : common_http.texprUnit()} : input.remove_organisation_member,
+    switch_organisation: input.switch_organisation === undefined ? {method : "post", path : "/organisations/switch", security : {kind : "token"}, reqType : texprSwitchOrganisationReq(), respType : texprSwitchOrganisationResp()} : input.switch_organisation,
+    create_invitation: input.create_invitation === undefined ? {method : "post", path : "/invitations/create", security : {kind : "tokenWithRole", value : "admin"}, reqType : texprCreateInvitationReq(), respType : texprCreateInvitationResp()} : input.create_invitation,
+    query_invitations: input.query_invitations === undefined ? {method : "get", path : "/invitations/query", security : {kind : "tokenWithRole", value : "admin"}, reqType : texprQueryInvitationsReq(), respType : texprPaginated(texprInvitationSummaryWithId())} : input.query_invitations,
+    revoke_invitation: input.revoke_invitation === undefined ? {method : "post", path : "/invitations/revoke", security : {kind : "tokenWithRole", value : "admin"}, reqType : protoapp_db.texprInvitationId(), respType : common_http.texprUnit()} : input.revoke_invitation,
     query_users: input.query_users === undefined ? {method : "get", path : "/users/query", security : {kind : "tokenWithRole", value : "admin"}, reqType : texprQueryUsersReq(), respType : texprPaginated(texprUserWithId())} : input.query_users,
+    jwt_failure_counts: input.jwt_failure_counts === undefined ? {method : "get", path : "/auth/jwt-failures", security : {kind : "tokenWithRole", value : "admin"}, reqType : ADL.texprVoid(), respType : texprJwtFailureCounts()} : input.jwt_failure_counts,
+    create_api_key: input.create_api_key === undefined ? {method : "post", path : "/api-keys/create", security : {kind : "tokenWithRole", value : "admin"}, reqType : texprCreateApiKeyReq(), respType : texprCreateApiKeyResp()} : input.create_api_key,
+    query_api_keys: input.query_api_keys === undefined ? {method : "get", path : "/api-keys/query", security : {kind : "tokenWithRole", value : "admin"}, reqType : texprQueryApiKeysReq(), respType : texprPaginated(texprApiKeySummaryWithId())} : input.query_api_keys,
+    revoke_api_key: input.revoke_api_key === undefined ? {method : "post", path : "/api-keys/revoke", security : {kind : "tokenWithRole", value : "admin"}, reqType : protoapp_db.texprApiKeyId(), respType : common_http.texprUnit()} : input.revoke_api_key,
+    query_audit_log: input.query_audit_log === undefined ? {method : "get", path : "/audit/query", security : {kind : "tokenWithRole", value : "admin"}, reqType : texprQueryAuditLogReq(), respType : texprPaginated(texprAuditLogEntry())} : input.query_audit_log,
   };
 }
 
 const ApiRequests_AST : ADL.ScopedDecl =
-  {"decl":{"annotations":[],"name":"ApiRequests","type_":{"kind":"struct_","value":{"fields":[{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"AWS default compatible health check\n"}],"default":{"kind":"just","value":{"method":"get","path":"/","security":"public"}},"name":"healthy","serializedName":"healthy","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}},{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.http","name":"HttpReq"}}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"Login a user\n\nThe response will set an httpOnly cookie containing the refresh token\n"}],"default":{"kind":"just","value":{"path":"/login","security":"public"}},"name":"login","serializedName":"login","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"LoginReq"}}},{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"LoginResp"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.http","name":"HttpReq"}}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"Get a refreshed access token\n\nIf the refresh token is not provided in the request body, then it will\nbe read from the refrestToken cookie in the request.\n"}],"default":{"kind":"just","value":{"path":"/refresh","security":"public"}},"name":"refresh","serializedName":"refresh","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"RefreshReq"}}},{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"RefreshResp"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.http","name":"HttpReq"}}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"Clear the `refreshToken` cookie.\n"}],"default":{"kind":"just","value":{"path":"/logout","security":"public"}},"name":"logout","serializedName":"logout","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.http","name":"Unit"}}},{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.http","name":"Unit"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.http","name":"HttpReq"}}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"Post a message to the noticeboard\n"}],"default":{"kind":"just","value":{"path":"/messages/new","security":"token"}},"name":"new_message","serializedName":"new_message","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"NewMessageReq"}}},{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"MessageId"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.http","name":"HttpReq"}}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"Get recent noticeboard messages\n"}],"default":{"kind":"just","value":{"method":"get","path":"/messages/recent","security":"token"}},"name":"recent_messages","serializedName":"recent_messages","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"RecentMessagesReq"}}},{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"Message"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"Paginated"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.http","name":"HttpReq"}}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"Gets info about the logged in user\n"}],"default":{"kind":"just","value":{"method":"get","path":"/whoami","security":"token"}},"name":"who_am_i","serializedName":"who_am_i","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}},{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"UserWithId"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.http","name":"HttpReq"}}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"Create a new user\n"}],"default":{"kind":"just","value":{"path":"/users/create","security":{"tokenWithRole":"admin"}}},"name":"create_user","serializedName":"create_user","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"UserDetails"}}},{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"AppUserId"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.http","name":"HttpReq"}}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"Update a user\n"}],"default":{"kind":"just","value":{"path":"/users/update","security":{"tokenWithRole":"admin"}}},"name":"update_user","serializedName":"update_user","typeExpr":{"parameters":[{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"AppUserId"}}},{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"UserDetails"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"WithId"}}},{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.http","name":"Unit"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.http","name":"HttpReq"}}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"Query users\n"}],"default":{"kind":"just","value":{"method":"get","path":"/users/query","security":{"tokenWithRole":"admin"}}},"name":"query_users","serializedName":"query_users","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"QueryUsersReq"}}},{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"UserWithId"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"Paginated"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.http","name":"HttpReq"}}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+  {"decl":{"annotations":[],"name":"ApiRequests","type_":{"kind":"struct_","value":{"fields":[{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"AWS default compatible health check\n"}],"default":{"kind":"just","value":{"method":"get","path":"/","security":"public"}},"name":"healthy","serializedName":"healthy","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}},{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.http","name":"HttpReq"}}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"The public keys used to verify access tokens, as a JSON web key set\n"}],"default":{"kind":"just","value":{"method":"get","path":"/.well-known/jwks.json","security":"public"}},"name":"jwks","serializedName":"jwks","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}},{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"Jwks"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.http","name":"HttpReq"}}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"Login a user\n\nThe response will set an httpOnly cookie containing the refresh token\n"}],"default":{"kind":"just","value":{"path":"/login","security":"public"}},"name":"login","serializedName":"login","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"LoginReq"}}},{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"LoginResp"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.http","name":"HttpReq"}}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"Complete a login that requires a second factor, with the challenge\ntoken from the `totp_required` login response, and either a code\nfrom the user's authenticator app or one of their recovery codes.\n\nThe response will set an httpOnly cookie containing the refresh token\n"}],"default":{"kind":"just","value":{"path":"/login/totp","security":"public"}},"name":"login_totp","serializedName":"login_totp","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"LoginTotpReq"}}},{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"LoginTotpResp"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.http","name":"HttpReq"}}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"Start a login with the OpenID Connect provider. The client should\nsend the user to the returned authorization url, from which the\nprovider will redirect them to the configured `redirect_url`.\n\nThe response will set a short-lived httpOnly cookie binding the login\nto the browser, which must be sent with the `oidc_login` request\n"}],"default":{"kind":"just","value":{"path":"/oidc/start","security":"public"}},"name":"oidc_login_start","serializedName":"oidc_login_start","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}},{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"OidcLoginStartResp"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.http","name":"HttpReq"}}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"Complete a login with the OpenID Connect provider, using the `code`\nand `state` query parameters of its redirect back to the app.\n\nThe response will set an httpOnly cookie containing the refresh token\n"}],"default":{"kind":"just","value":{"path":"/oidc/login","security":"public"}},"name":"oidc_login","serializedName":"oidc_login","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"OidcLoginReq"}}},{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"OidcLoginResp"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.http","name":"HttpReq"}}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"Get a refreshed access token\n\nIf the refresh token is not provided in the request body, then it will\nbe read from the refrestToken cookie in the request.\n\nRefresh tokens are single use: a new refresh token is returned (and set\nin the cookie) with each access token. Reuse of a refresh token revokes\nevery refresh token descended from the same login.\n\nRequests that use the cookie must pass the CSRF checks configured by\n`csrf` in the server config.\n"}],"default":{"kind":"just","value":{"path":"/refresh","security":"public"}},"name":"refresh","serializedName":"refresh","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"RefreshReq"}}},{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"RefreshResp"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.http","name":"HttpReq"}}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"Revoke the refresh token, and clear the `refreshToken` cookie.\n\nIf the refresh token is not provided in the request body, then it will\nbe read from the refrestToken cookie in the request.\nAs for `refresh`, requests that use the cookie are CSRF checked.\n"}],"default":{"kind":"just","value":{"path":"/logout","security":"public"}},"name":"logout","serializedName":"logout","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"LogoutReq"}}},{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.http","name":"Unit"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.http","name":"HttpReq"}}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"Request a password reset link be emailed to a user.\n\nTo avoid revealing which email addresses have accounts, this\nsucceeds whether or not the email address is known.\n"}],"default":{"kind":"just","value":{"path":"/password-reset/request","security":"public"}},"name":"request_password_reset","serializedName":"request_password_reset","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"RequestPasswordResetReq"}}},{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.http","name":"Unit"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.http","name":"HttpReq"}}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"Set a new password, using the token from a password reset link.\n\nEach token can only be used once. A successful reset revokes all\nof the user's refresh tokens.\n"}],"default":{"kind":"just","value":{"path":"/password-reset/complete","security":"public"}},"name":"reset_password","serializedName":"reset_password","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"ResetPasswordReq"}}},{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"ResetPasswordResp"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.http","name":"HttpReq"}}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"Create an account, using the token from an invitation email. The\ninvitation can only be used once, and the new user's email address\ncounts as verified.\n\nThe response will set an httpOnly cookie containing the refresh token\n"}],"default":{"kind":"just","value":{"path":"/signup","security":"public"}},"name":"signup","serializedName":"signup","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"SignupReq"}}},{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"SignupResp"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.http","name":"HttpReq"}}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"Verify a user's email address, using the token from the link\nemailed to them.\n"}],"default":{"kind":"just","value":{"path":"/email/verify","security":"public"}},"name":"verify_email","serializedName":"verify_email","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"VerifyEmailReq"}}},{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"VerifyEmailResp"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.http","name":"HttpReq"}}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"Resend the email address verification link to an unverified user.\n\nLinks are sent at most once every `email_verification_resend_secs`.\nTo avoid revealing which email addresses have accounts, this\nsucceeds whether or not a link was sent.\n"}],"default":{"kind":"just","value":{"path":"/email/resend-verification","security":"public"}},"name":"resend_verification_email","serializedName":"resend_verification_email","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"ResendVerificationEmailReq"}}},{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.http","name":"Unit"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.http","name":"HttpReq"}}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"Start enrolling the user in TOTP two factor authentication,\nreplacing any unconfirmed enrolment. The enrolment takes effect\nonce confirmed with `confirm_totp_enrolment`.\n"}],"default":{"kind":"just","value":{"path":"/totp/enrol","security":"token"}},"name":"begin_totp_enrolment","serializedName":"begin_totp_enrolment","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}},{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"BeginTotpEnrolmentResp"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.http","name":"HttpReq"}}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"Confirm a TOTP enrolment with a first code from the user's\nauthenticator app. The returned recovery codes can each be used\nonce in place of a code, and are not retrievable later.\n"}],"default":{"kind":"just","value":{"path":"/totp/confirm","security":"token"}},"name":"confirm_totp_enrolment","serializedName":"confirm_totp_enrolment","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"ConfirmTotpEnrolmentReq"}}},{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"ConfirmTotpEnrolmentResp"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.http","name":"HttpReq"}}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"Post a message to the noticeboard\n"}],"default":{"kind":"just","value":{"path":"/messages/new","security":"token"}},"name":"new_message","serializedName":"new_message","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"NewMessageReq"}}},{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"MessageId"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.http","name":"HttpReq"}}}},{"annotations":[{"key":{"moduleName":"common.http","name":"RequestTimeoutSecs"},"value":5},{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"Get recent top level noticeboard messages, posted within the\ncaller's organisation\n"}],"default":{"kind":"just","value":{"method":"get","path":"/messages/recent","security":"token"}},"name":"recent_messages","serializedName":"recent_messages","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"RecentMessagesReq"}}},{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"Message"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"Paginated"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.http","name":"HttpReq"}}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"Reply to a top level message, starting or continuing its thread\n"}],"default":{"kind":"just","value":{"path":"/messages/reply","security":"token"}},"name":"reply_to_message","serializedName":"reply_to_message","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"ReplyToMessageReq"}}},{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"ReplyToMessageResp"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.http","name":"HttpReq"}}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"Get a top level message, and a page of its replies in the order they\nwere posted\n"}],"default":{"kind":"just","value":{"method":"get","path":"/messages/thread","security":"token"}},"name":"message_thread","serializedName":"message_thread","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"MessageThreadReq"}}},{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"MessageThreadResp"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.http","name":"HttpReq"}}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"React to a message with an emoji. Reacting again with the same\nemoji has no effect.\n"}],"default":{"kind":"just","value":{"path":"/messages/reactions/add","security":"token"}},"name":"add_reaction","serializedName":"add_reaction","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"MessageReactionReq"}}},{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"AddReactionResp"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.http","name":"HttpReq"}}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"Remove the caller's reaction to a message\n"}],"default":{"kind":"just","value":{"path":"/messages/reactions/remove","security":"token"}},"name":"remove_reaction","serializedName":"remove_reaction","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"MessageReactionReq"}}},{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.http","name":"Unit"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.http","name":"HttpReq"}}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"Edit the text of a message. Users can edit their own messages, and\nadmins can edit any message. The prior text is kept as a revision.\n"}],"default":{"kind":"just","value":{"path":"/messages/edit","security":"token"}},"name":"edit_message","serializedName":"edit_message","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"EditMessageReq"}}},{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"EditMessageResp"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.http","name":"HttpReq"}}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"Delete a message, leaving a tombstone in its place. Users can delete\ntheir own messages, and admins can delete any message.\n"}],"default":{"kind":"just","value":{"path":"/messages/delete","security":"token"}},"name":"delete_message","serializedName":"delete_message","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"MessageId"}}},{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"DeleteMessageResp"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.http","name":"HttpReq"}}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"Get the prior versions of a message, most recent first. Available to\nthe message's author and admins.\n"}],"default":{"kind":"just","value":{"method":"get","path":"/messages/revisions","security":"token"}},"name":"message_revisions","serializedName":"message_revisions","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"MessageId"}}},{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"MessageRevisionsResp"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.http","name":"HttpReq"}}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"Gets info about the logged in user\n"}],"default":{"kind":"just","value":{"method":"get","path":"/whoami","security":"token"}},"name":"who_am_i","serializedName":"who_am_i","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}},{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"UserWithId"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.http","name":"HttpReq"}}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"Change the logged in user's name and email address. A changed\nemail address needs the current password, and to be verified again.\nNot available to admins impersonating the user.\n"}],"default":{"kind":"just","value":{"path":"/profile/update","security":"token"}},"name":"update_profile","serializedName":"update_profile","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"UpdateProfileReq"}}},{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"UpdateProfileResp"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.http","name":"HttpReq"}}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"Change the logged in user's password. This revokes all of the\nuser's other sessions.\n"}],"default":{"kind":"just","value":{"path":"/profile/change-password","security":"token"}},"name":"change_password","serializedName":"change_password","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"ChangePasswordReq"}}},{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"ChangePasswordResp"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.http","name":"HttpReq"}}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"List the logged in user's active sessions, most recently\nrefreshed first\n"}],"default":{"kind":"just","value":{"method":"get","path":"/sessions","security":"token"}},"name":"my_sessions","serializedName":"my_sessions","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}},{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"SessionSummaryWithId"}}}],"typeRef":{"kind":"primitive","value":"Vector"}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.http","name":"HttpReq"}}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"Revoke one of the logged in user's sessions, so that it can no\nlonger be refreshed. Access tokens already issued for the session\nremain valid until they expire.\n"}],"default":{"kind":"just","value":{"path":"/sessions/revoke","security":"token"}},"name":"revoke_session","serializedName":"revoke_session","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"SessionId"}}},{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.http","name":"Unit"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.http","name":"HttpReq"}}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"Revoke all of the logged in user's sessions, other than the one\nmaking the request\n"}],"default":{"kind":"just","value":{"path":"/sessions/revoke-others","security":"token"}},"name":"revoke_other_sessions","serializedName":"revoke_other_sessions","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}},{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.http","name":"Unit"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.http","name":"HttpReq"}}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"Create a new user\n"}],"default":{"kind":"just","value":{"path":"/users/create","security":{"tokenWithRole":"admin"}}},"name":"create_user","serializedName":"create_user","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"UserDetails"}}},{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"CreateUserResp"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.http","name":"HttpReq"}}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"Update a user\n"}],"default":{"kind":"just","value":{"path":"/users/update","security":{"tokenWithRole":"admin"}}},"name":"update_user","serializedName":"update_user","typeExpr":{"parameters":[{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"AppUserId"}}},{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"UserDetails"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"WithId"}}},{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"UpdateUserResp"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.http","name":"HttpReq"}}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"Unlock a user's account after too many failed logins\n"}],"default":{"kind":"just","value":{"path":"/users/unlock","security":{"tokenWithRole":"admin"}}},"name":"unlock_user","serializedName":"unlock_user","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"AppUserId"}}},{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.http","name":"Unit"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.http","name":"HttpReq"}}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"Issue a short lived access token with which an admin can act as\na user. The token has the user's `sub` and an `act` claim with the\nadmin's. It is refused by admin endpoints, and every request made\nwith it is written to the audit log.\n"}],"default":{"kind":"just","value":{"path":"/users/impersonate","security":{"tokenWithRole":"admin"}}},"name":"impersonate_user","serializedName":"impersonate_user","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"AppUserId"}}},{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"ImpersonateUserResp"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.http","name":"HttpReq"}}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"Revoke every session of a user, forcing them to login again once\ntheir current access tokens expire\n"}],"default":{"kind":"just","value":{"path":"/users/revoke-sessions","security":{"tokenWithRole":"admin"}}},"name":"revoke_user_sessions","serializedName":"revoke_user_sessions","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"AppUserId"}}},{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.http","name":"Unit"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.http","name":"HttpReq"}}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"Deactivate a user, revoking their sessions and refusing their\naccess tokens and API keys. Their messages are kept.\n"}],"default":{"kind":"just","value":{"path":"/users/deactivate","security":{"tokenWithRole":"admin"}}},"name":"deactivate_user","serializedName":"deactivate_user","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"AppUserId"}}},{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"DeactivateUserResp"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.http","name":"HttpReq"}}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"Reactivate a deactivated user, who can then login again\n"}],"default":{"kind":"just","value":{"path":"/users/reactivate","security":{"tokenWithRole":"admin"}}},"name":"reactivate_user","serializedName":"reactivate_user","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"AppUserId"}}},{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"ReactivateUserResp"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.http","name":"HttpReq"}}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"Replace a user's roles. Access tokens carry the user's roles, so\nthe change takes effect as their tokens are refreshed.\n"}],"default":{"kind":"just","value":{"path":"/users/set-roles","security":{"tokenWithRole":"admin"}}},"name":"set_user_roles","serializedName":"set_user_roles","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"SetUserRolesReq"}}},{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"SetUserRolesResp"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.http","name":"HttpReq"}}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"Create a role, whose members are granted its permissions\n"}],"default":{"kind":"just","value":{"path":"/roles/create","security":{"tokenWithRole":"admin"}}},"name":"create_role","serializedName":"create_role","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"CreateRoleReq"}}},{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"CreateRoleResp"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.http","name":"HttpReq"}}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"Query roles, ordered by name\n"}],"default":{"kind":"just","value":{"method":"get","path":"/roles/query","security":{"tokenWithRole":"admin"}}},"name":"query_roles","serializedName":"query_roles","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"QueryRolesReq"}}},{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"RoleDetailsWithId"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"Paginated"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.http","name":"HttpReq"}}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"Replace the permissions granted by a role\n"}],"default":{"kind":"just","value":{"path":"/roles/set-permissions","security":{"tokenWithRole":"admin"}}},"name":"set_role_permissions","serializedName":"set_role_permissions","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"SetRolePermissionsReq"}}},{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"SetRolePermissionsResp"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.http","name":"HttpReq"}}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"Delete a role, removing it from its members\n"}],"default":{"kind":"just","value":{"path":"/roles/delete","security":{"tokenWithRole":"admin"}}},"name":"delete_role","serializedName":"delete_role","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"RoleId"}}},{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"DeleteRoleResp"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.http","name":"HttpReq"}}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"Create an organisation\n"}],"default":{"kind":"just","value":{"path":"/organisations/create","security":{"tokenWithRole":"admin"}}},"name":"create_organisation","serializedName":"create_organisation","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"CreateOrganisationReq"}}},{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"CreateOrganisationResp"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.http","name":"HttpReq"}}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"Query organisations, ordered by name\n"}],"default":{"kind":"just","value":{"method":"get","path":"/organisations/query","security":{"tokenWithRole":"admin"}}},"name":"query_organisations","serializedName":"query_organisations","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"QueryOrganisationsReq"}}},{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"OrganisationWithId"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"Paginated"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.http","name":"HttpReq"}}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"Add a user to an organisation, or replace the roles they hold\nwithin it. The admin role can't be granted per organisation.\n"}],"default":{"kind":"just","value":{"path":"/organisations/set-member","security":{"tokenWithRole":"admin"}}},"name":"set_organisation_member","serializedName":"set_organisation_member","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"SetOrganisationMemberReq"}}},{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"SetOrganisationMemberResp"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.http","name":"HttpReq"}}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"Remove a user from an organisation\n"}],"default":{"kind":"just","value":{"path":"/organisations/remove-member","security":{"tokenWithRole":"admin"}}},"name":"remove_organisation_member","serializedName":"remove_organisation_member","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"RemoveOrganisationMemberReq"}}},{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.http","name":"Unit"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.http","name":"HttpReq"}}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"Switch the organisation that the logged in session acts within,\nreturning an access token with the new `org` claim. Members can\nswitch to their organisations, and admins to any, or to none to\nact across the whole deployment.\n"}],"default":{"kind":"just","value":{"path":"/organisations/switch","security":"token"}},"name":"switch_organisation","serializedName":"switch_organisation","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"SwitchOrganisationReq"}}},{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"SwitchOrganisationResp"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.http","name":"HttpReq"}}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"Invite someone to create their own account, by emailing them a\nsignup link\n"}],"default":{"kind":"just","value":{"path":"/invitations/create","security":{"tokenWithRole":"admin"}}},"name":"create_invitation","serializedName":"create_invitation","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"CreateInvitationReq"}}},{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"CreateInvitationResp"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.http","name":"HttpReq"}}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"Query invitations, most recently created first\n"}],"default":{"kind":"just","value":{"method":"get","path":"/invitations/query","security":{"tokenWithRole":"admin"}}},"name":"query_invitations","serializedName":"query_invitations","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"QueryInvitationsReq"}}},{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"InvitationSummaryWithId"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"Paginated"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.http","name":"HttpReq"}}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"Revoke an unused invitation, so that it can no longer be used to\nsign up\n"}],"default":{"kind":"just","value":{"path":"/invitations/revoke","security":{"tokenWithRole":"admin"}}},"name":"revoke_invitation","serializedName":"revoke_invitation","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"InvitationId"}}},{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.http","name":"Unit"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.http","name":"HttpReq"}}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"Query users. Admins acting within an organisation only see its\nmembers.\n"}],"default":{"kind":"just","value":{"method":"get","path":"/users/query","security":{"tokenWithRole":"admin"}}},"name":"query_users","serializedName":"query_users","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"QueryUsersReq"}}},{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"UserWithId"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"Paginated"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.http","name":"HttpReq"}}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"Get the number of access and refresh tokens that have been\nrejected since the server started, by reason\n"}],"default":{"kind":"just","value":{"method":"get","path":"/auth/jwt-failures","security":{"tokenWithRole":"admin"}}},"name":"jwt_failure_counts","serializedName":"jwt_failure_counts","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}},{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"JwtFailureCounts"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.http","name":"HttpReq"}}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"Issue an API key, with which a service account can call endpoints\nsecured by `token` or `tokenWithRole`, by passing it as a bearer\ntoken. The key itself is only returned by this call.\n"}],"default":{"kind":"just","value":{"path":"/api-keys/create","security":{"tokenWithRole":"admin"}}},"name":"create_api_key","serializedName":"create_api_key","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"CreateApiKeyReq"}}},{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"CreateApiKeyResp"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.http","name":"HttpReq"}}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"Query API keys, most recently created first\n"}],"default":{"kind":"just","value":{"method":"get","path":"/api-keys/query","security":{"tokenWithRole":"admin"}}},"name":"query_api_keys","serializedName":"query_api_keys","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"QueryApiKeysReq"}}},{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"ApiKeySummaryWithId"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"Paginated"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.http","name":"HttpReq"}}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"Revoke an API key, so that it can no longer be used\n"}],"default":{"kind":"just","value":{"path":"/api-keys/revoke","security":{"tokenWithRole":"admin"}}},"name":"revoke_api_key","serializedName":"revoke_api_key","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"ApiKeyId"}}},{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.http","name":"Unit"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.http","name":"HttpReq"}}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"Query the audit log of calls to admin only endpoints\n\nResults are ordered most recent first.\n"}],"default":{"kind":"just","value":{"method":"get","path":"/audit/query","security":{"tokenWithRole":"admin"}}},"name":"query_audit_log","serializedName":"query_audit_log","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"QueryAuditLogReq"}}},{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"AuditLogEntry"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"Paginated"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.http","name":"HttpReq"}}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
 
 export const snApiRequests: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"ApiRequests"};
 
@@ -89,6 +427,32 @@ export function texprApiRequests(): ADL.ATypeExpr<ApiRequests> {
   return {value : {typeRef : {kind: "reference", value : snApiRequests}, parameters : []}};
 }
 
+/**
+ * A JSON web key set, as per RFC 7517
+ */
+export interface Jwks {
+  keys: {}|null[];
+}
+
+export function makeJwks(
+  input: {
+    keys: {}|null[],
+  }
+): Jwks {
+  return {
+    keys: input.keys,
+  };
+}
+
+const Jwks_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"A JSON web key set, as per RFC 7517\n"}],"name":"Jwks","type_":{"kind":"struct_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"keys","serializedName":"keys","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"primitive","value":"Json"}}],"typeRef":{"kind":"primitive","value":"Vector"}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+
+export const snJwks: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"Jwks"};
+
+export function texprJwks(): ADL.ATypeExpr<Jwks> {
+  return {value : {typeRef : {kind: "reference", value : snJwks}, parameters : []}};
+}
+
 export interface LoginReq {
   email: common_strings.StringNE;
   password: common_strings.Password;
@@ -122,18 +486,36 @@ export interface LoginResp_Tokens {
 export interface LoginResp_Invalid_credentials {
   kind: 'invalid_credentials';
 }
+export interface LoginResp_Email_not_verified {
+  kind: 'email_not_verified';
+}
+export interface LoginResp_Deactivated {
+  kind: 'deactivated';
+}
+export interface LoginResp_Totp_required {
+  kind: 'totp_required';
+  value: common_strings.StringNE;
+}
+export interface LoginResp_Too_many_attempts {
+  kind: 'too_many_attempts';
+  value: number;
+}
 
-export type LoginResp = LoginResp_Tokens | LoginResp_Invalid_credentials;
+export type LoginResp = LoginResp_Tokens | LoginResp_Invalid_credentials | LoginResp_Email_not_verified | LoginResp_Deactivated | LoginResp_Totp_required | LoginResp_Too_many_attempts;
 
 export interface LoginRespOpts {
   tokens: LoginTokens;
   invalid_credentials: null;
+  email_not_verified: null;
+  deactivated: null;
+  totp_required: common_strings.StringNE;
+  too_many_attempts: number;
 }
 
 export function makeLoginResp<K extends keyof LoginRespOpts>(kind: K, value: LoginRespOpts[K]) { return {kind, value}; }
 
 const LoginResp_AST : ADL.ScopedDecl =
-  {"decl":{"annotations":[],"name":"LoginResp","type_":{"kind":"union_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"tokens","serializedName":"tokens","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"LoginTokens"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"invalid_credentials","serializedName":"invalid_credentials","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+  {"decl":{"annotations":[],"name":"LoginResp","type_":{"kind":"union_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"tokens","serializedName":"tokens","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"LoginTokens"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"invalid_credentials","serializedName":"invalid_credentials","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}},{"annotations":[],"default":{"kind":"nothing"},"name":"email_not_verified","serializedName":"email_not_verified","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"The password was correct, but the user has been deactivated\n"}],"default":{"kind":"nothing"},"name":"deactivated","serializedName":"deactivated","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"The password was correct, but a second factor is required. The\nvalue is a short lived challenge token for the `login_totp` endpoint.\n"}],"default":{"kind":"nothing"},"name":"totp_required","serializedName":"totp_required","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.strings","name":"StringNE"}}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"There have been too many failed logins for the email address. The\nvalue is the number of seconds until another attempt can be made.\n"}],"default":{"kind":"nothing"},"name":"too_many_attempts","serializedName":"too_many_attempts","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Word32"}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
 
 export const snLoginResp: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"LoginResp"};
 
@@ -141,6 +523,162 @@ export function texprLoginResp(): ADL.ATypeExpr<LoginResp> {
   return {value : {typeRef : {kind: "reference", value : snLoginResp}, parameters : []}};
 }
 
+export interface LoginTotpReq {
+  challenge_token: common_strings.StringNE;
+  code: common_strings.StringNE;
+}
+
+export function makeLoginTotpReq(
+  input: {
+    challenge_token: common_strings.StringNE,
+    code: common_strings.StringNE,
+  }
+): LoginTotpReq {
+  return {
+    challenge_token: input.challenge_token,
+    code: input.code,
+  };
+}
+
+const LoginTotpReq_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"LoginTotpReq","type_":{"kind":"struct_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"challenge_token","serializedName":"challenge_token","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.strings","name":"StringNE"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"code","serializedName":"code","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.strings","name":"StringNE"}}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+
+export const snLoginTotpReq: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"LoginTotpReq"};
+
+export function texprLoginTotpReq(): ADL.ATypeExpr<LoginTotpReq> {
+  return {value : {typeRef : {kind: "reference", value : snLoginTotpReq}, parameters : []}};
+}
+
+export interface LoginTotpResp_Tokens {
+  kind: 'tokens';
+  value: LoginTokens;
+}
+export interface LoginTotpResp_Invalid_code {
+  kind: 'invalid_code';
+}
+export interface LoginTotpResp_Invalid_challenge {
+  kind: 'invalid_challenge';
+}
+export interface LoginTotpResp_Too_many_attempts {
+  kind: 'too_many_attempts';
+  value: number;
+}
+
+export type LoginTotpResp = LoginTotpResp_Tokens | LoginTotpResp_Invalid_code | LoginTotpResp_Invalid_challenge | LoginTotpResp_Too_many_attempts;
+
+export interface LoginTotpRespOpts {
+  tokens: LoginTokens;
+  invalid_code: null;
+  invalid_challenge: null;
+  too_many_attempts: number;
+}
+
+export function makeLoginTotpResp<K extends keyof LoginTotpRespOpts>(kind: K, value: LoginTotpRespOpts[K]) { return {kind, value}; }
+
+const LoginTotpResp_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"LoginTotpResp","type_":{"kind":"union_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"tokens","serializedName":"tokens","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"LoginTokens"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"invalid_code","serializedName":"invalid_code","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}},{"annotations":[],"default":{"kind":"nothing"},"name":"invalid_challenge","serializedName":"invalid_challenge","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}},{"annotations":[],"default":{"kind":"nothing"},"name":"too_many_attempts","serializedName":"too_many_attempts","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Word32"}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+
+export const snLoginTotpResp: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"LoginTotpResp"};
+
+export function texprLoginTotpResp(): ADL.ATypeExpr<LoginTotpResp> {
+  return {value : {typeRef : {kind: "reference", value : snLoginTotpResp}, parameters : []}};
+}
+
+export interface OidcLoginStartResp_Authorization_url {
+  kind: 'authorization_url';
+  value: common_strings.StringNE;
+}
+export interface OidcLoginStartResp_Not_configured {
+  kind: 'not_configured';
+}
+
+export type OidcLoginStartResp = OidcLoginStartResp_Authorization_url | OidcLoginStartResp_Not_configured;
+
+export interface OidcLoginStartRespOpts {
+  authorization_url: common_strings.StringNE;
+  not_configured: null;
+}
+
+export function makeOidcLoginStartResp<K extends keyof OidcLoginStartRespOpts>(kind: K, value: OidcLoginStartRespOpts[K]) { return {kind, value}; }
+
+const OidcLoginStartResp_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"OidcLoginStartResp","type_":{"kind":"union_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"authorization_url","serializedName":"authorization_url","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.strings","name":"StringNE"}}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"The server has no OpenID Connect provider configured\n"}],"default":{"kind":"nothing"},"name":"not_configured","serializedName":"not_configured","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+
+export const snOidcLoginStartResp: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"OidcLoginStartResp"};
+
+export function texprOidcLoginStartResp(): ADL.ATypeExpr<OidcLoginStartResp> {
+  return {value : {typeRef : {kind: "reference", value : snOidcLoginStartResp}, parameters : []}};
+}
+
+export interface OidcLoginReq {
+  code: common_strings.StringNE;
+  state: common_strings.StringNE;
+}
+
+export function makeOidcLoginReq(
+  input: {
+    code: common_strings.StringNE,
+    state: common_strings.StringNE,
+  }
+): OidcLoginReq {
+  return {
+    code: input.code,
+    state: input.state,
+  };
+}
+
+const OidcLoginReq_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"OidcLoginReq","type_":{"kind":"struct_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"code","serializedName":"code","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.strings","name":"StringNE"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"state","serializedName":"state","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.strings","name":"StringNE"}}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+
+export const snOidcLoginReq: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"OidcLoginReq"};
+
+export function texprOidcLoginReq(): ADL.ATypeExpr<OidcLoginReq> {
+  return {value : {typeRef : {kind: "reference", value : snOidcLoginReq}, parameters : []}};
+}
+
+export interface OidcLoginResp_Tokens {
+  kind: 'tokens';
+  value: LoginTokens;
+}
+export interface OidcLoginResp_Invalid_login {
+  kind: 'invalid_login';
+}
+export interface OidcLoginResp_Unknown_user {
+  kind: 'unknown_user';
+}
+export interface OidcLoginResp_Totp_required {
+  kind: 'totp_required';
+  value: common_strings.StringNE;
+}
+export interface OidcLoginResp_Deactivated {
+  kind: 'deactivated';
+}
+export interface OidcLoginResp_Not_configured {
+  kind: 'not_configured';
+}
+
+export type OidcLoginResp = OidcLoginResp_Tokens | OidcLoginResp_Invalid_login | OidcLoginResp_Unknown_user | OidcLoginResp_Totp_required | OidcLoginResp_Deactivated | OidcLoginResp_Not_configured;
+
+export interface OidcLoginRespOpts {
+  tokens: LoginTokens;
+  invalid_login: null;
+  unknown_user: null;
+  totp_required: common_strings.StringNE;
+  deactivated: null;
+  not_configured: null;
+}
+
+export function makeOidcLoginResp<K extends keyof OidcLoginRespOpts>(kind: K, value: OidcLoginRespOpts[K]) { return {kind, value}; }
+
+const OidcLoginResp_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"OidcLoginResp","type_":{"kind":"union_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"tokens","serializedName":"tokens","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"LoginTokens"}}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"The login was unknown, expired or already completed, or the\nprovider didn't confirm the user's identity\n"}],"default":{"kind":"nothing"},"name":"invalid_login","serializedName":"invalid_login","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"There is no user for the provider's account, and one couldn't be\nlinked or created\n"}],"default":{"kind":"nothing"},"name":"unknown_user","serializedName":"unknown_user","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"As for `LoginResp.totp_required`\n"}],"default":{"kind":"nothing"},"name":"totp_required","serializedName":"totp_required","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.strings","name":"StringNE"}}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"The provider's account belongs to a deactivated user\n"}],"default":{"kind":"nothing"},"name":"deactivated","serializedName":"deactivated","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}},{"annotations":[],"default":{"kind":"nothing"},"name":"not_configured","serializedName":"not_configured","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+
+export const snOidcLoginResp: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"OidcLoginResp"};
+
+export function texprOidcLoginResp(): ADL.ATypeExpr<OidcLoginResp> {
+  return {value : {typeRef : {kind: "reference", value : snOidcLoginResp}, parameters : []}};
+}
+
 export interface RefreshReq {
   refresh_token: (common_strings.StringNE|null);
 }
@@ -164,25 +702,25 @@ export function texprRefreshReq(): ADL.ATypeExpr<RefreshReq> {
   return {value : {typeRef : {kind: "reference", value : snRefreshReq}, parameters : []}};
 }
 
-export interface RefreshResp_Access_token {
-  kind: 'access_token';
-  value: common_strings.StringNE;
+export interface RefreshResp_Tokens {
+  kind: 'tokens';
+  value: LoginTokens;
 }
 export interface RefreshResp_Invalid_refresh_token {
   kind: 'invalid_refresh_token';
 }
 
-export type RefreshResp = RefreshResp_Access_token | RefreshResp_Invalid_refresh_token;
+export type RefreshResp = RefreshResp_Tokens | RefreshResp_Invalid_refresh_token;
 
 export interface RefreshRespOpts {
-  access_token: common_strings.StringNE;
+  tokens: LoginTokens;
   invalid_refresh_token: null;
 }
 
 export function makeRefreshResp<K extends keyof RefreshRespOpts>(kind: K, value: RefreshRespOpts[K]) { return {kind, value}; }
 
 const RefreshResp_AST : ADL.ScopedDecl =
-  {"decl":{"annotations":[],"name":"RefreshResp","type_":{"kind":"union_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"access_token","serializedName":"access_token","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.strings","name":"StringNE"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"invalid_refresh_token","serializedName":"invalid_refresh_token","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+  {"decl":{"annotations":[],"name":"RefreshResp","type_":{"kind":"union_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"tokens","serializedName":"tokens","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"LoginTokens"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"invalid_refresh_token","serializedName":"invalid_refresh_token","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
 
 export const snRefreshResp: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"RefreshResp"};
 
@@ -190,6 +728,29 @@ export function texprRefreshResp(): ADL.ATypeExpr<RefreshResp> {
   return {value : {typeRef : {kind: "reference", value : snRefreshResp}, parameters : []}};
 }
 
+export interface LogoutReq {
+  refresh_token: (common_strings.StringNE|null);
+}
+
+export function makeLogoutReq(
+  input: {
+    refresh_token?: (common_strings.StringNE|null),
+  }
+): LogoutReq {
+  return {
+    refresh_token: input.refresh_token === undefined ? null : input.refresh_token,
+  };
+}
+
+const LogoutReq_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"LogoutReq","type_":{"kind":"struct_","value":{"fields":[{"annotations":[],"default":{"kind":"just","value":null},"name":"refresh_token","serializedName":"refresh_token","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.strings","name":"StringNE"}}}],"typeRef":{"kind":"primitive","value":"Nullable"}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+
+export const snLogoutReq: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"LogoutReq"};
+
+export function texprLogoutReq(): ADL.ATypeExpr<LogoutReq> {
+  return {value : {typeRef : {kind: "reference", value : snLogoutReq}, parameters : []}};
+}
+
 export interface LoginTokens {
   access_jwt: common_strings.StringNE;
   refresh_jwt: common_strings.StringNE;
@@ -216,244 +777,2011 @@ export function texprLoginTokens(): ADL.ATypeExpr<LoginTokens> {
   return {value : {typeRef : {kind: "reference", value : snLoginTokens}, parameters : []}};
 }
 
-export interface NewMessageReq {
-  message: common_strings.StringML;
+export interface RequestPasswordResetReq {
+  email: common_strings.StringNE;
 }
 
-export function makeNewMessageReq(
+export function makeRequestPasswordResetReq(
   input: {
-    message: common_strings.StringML,
+    email: common_strings.StringNE,
   }
-): NewMessageReq {
+): RequestPasswordResetReq {
   return {
-    message: input.message,
+    email: input.email,
   };
 }
 
-const NewMessageReq_AST : ADL.ScopedDecl =
-  {"decl":{"annotations":[],"name":"NewMessageReq","type_":{"kind":"struct_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"message","serializedName":"message","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.strings","name":"StringML"}}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+const RequestPasswordResetReq_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"RequestPasswordResetReq","type_":{"kind":"struct_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"email","serializedName":"email","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.strings","name":"StringNE"}}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
 
-export const snNewMessageReq: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"NewMessageReq"};
+export const snRequestPasswordResetReq: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"RequestPasswordResetReq"};
 
-export function texprNewMessageReq(): ADL.ATypeExpr<NewMessageReq> {
-  return {value : {typeRef : {kind: "reference", value : snNewMessageReq}, parameters : []}};
+export function texprRequestPasswordResetReq(): ADL.ATypeExpr<RequestPasswordResetReq> {
+  return {value : {typeRef : {kind: "reference", value : snRequestPasswordResetReq}, parameters : []}};
 }
 
-export interface RecentMessagesReq {
-  page: PageReq;
+export interface ResetPasswordReq {
+  token: common_strings.StringNE;
+  password: common_strings.Password;
 }
 
-export function makeRecentMessagesReq(
+export function makeResetPasswordReq(
   input: {
-    page: PageReq,
+    token: common_strings.StringNE,
+    password: common_strings.Password,
   }
-): RecentMessagesReq {
+): ResetPasswordReq {
   return {
-    page: input.page,
+    token: input.token,
+    password: input.password,
   };
 }
 
-const RecentMessagesReq_AST : ADL.ScopedDecl =
-  {"decl":{"annotations":[],"name":"RecentMessagesReq","type_":{"kind":"struct_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"page","serializedName":"page","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"PageReq"}}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+const ResetPasswordReq_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"ResetPasswordReq","type_":{"kind":"struct_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"token","serializedName":"token","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.strings","name":"StringNE"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"password","serializedName":"password","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.strings","name":"Password"}}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
 
-export const snRecentMessagesReq: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"RecentMessagesReq"};
+export const snResetPasswordReq: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"ResetPasswordReq"};
 
-export function texprRecentMessagesReq(): ADL.ATypeExpr<RecentMessagesReq> {
-  return {value : {typeRef : {kind: "reference", value : snRecentMessagesReq}, parameters : []}};
+export function texprResetPasswordReq(): ADL.ATypeExpr<ResetPasswordReq> {
+  return {value : {typeRef : {kind: "reference", value : snResetPasswordReq}, parameters : []}};
 }
 
-export interface PageReq {
-  offset: number;
-  limit: number;
+export interface ResetPasswordResp_Success {
+  kind: 'success';
+}
+export interface ResetPasswordResp_Invalid_token {
+  kind: 'invalid_token';
+}
+export interface ResetPasswordResp_Weak_password {
+  kind: 'weak_password';
+  value: PasswordViolation[];
 }
 
-export function makePageReq(
-  input: {
-    offset?: number,
-    limit?: number,
-  }
-): PageReq {
-  return {
-    offset: input.offset === undefined ? 0 : input.offset,
-    limit: input.limit === undefined ? 20 : input.limit,
-  };
+export type ResetPasswordResp = ResetPasswordResp_Success | ResetPasswordResp_Invalid_token | ResetPasswordResp_Weak_password;
+
+export interface ResetPasswordRespOpts {
+  success: null;
+  invalid_token: null;
+  weak_password: PasswordViolation[];
 }
 
-const PageReq_AST : ADL.ScopedDecl =
-  {"decl":{"annotations":[],"name":"PageReq","type_":{"kind":"struct_","value":{"fields":[{"annotations":[],"default":{"kind":"just","value":0},"name":"offset","serializedName":"offset","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Word64"}}},{"annotations":[],"default":{"kind":"just","value":20},"name":"limit","serializedName":"limit","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Word64"}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+export function makeResetPasswordResp<K extends keyof ResetPasswordRespOpts>(kind: K, value: ResetPasswordRespOpts[K]) { return {kind, value}; }
 
-export const snPageReq: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"PageReq"};
+const ResetPasswordResp_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"ResetPasswordResp","type_":{"kind":"union_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"success","serializedName":"success","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}},{"annotations":[],"default":{"kind":"nothing"},"name":"invalid_token","serializedName":"invalid_token","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}},{"annotations":[],"default":{"kind":"nothing"},"name":"weak_password","serializedName":"weak_password","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"PasswordViolation"}}}],"typeRef":{"kind":"primitive","value":"Vector"}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
 
-export function texprPageReq(): ADL.ATypeExpr<PageReq> {
-  return {value : {typeRef : {kind: "reference", value : snPageReq}, parameters : []}};
+export const snResetPasswordResp: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"ResetPasswordResp"};
+
+export function texprResetPasswordResp(): ADL.ATypeExpr<ResetPasswordResp> {
+  return {value : {typeRef : {kind: "reference", value : snResetPasswordResp}, parameters : []}};
 }
 
 /**
- * A holder for paginated results
+ * A rule of the server's password policy that a password breaks
  */
-export interface Paginated<T> {
-  /**
-   * The paginated items
-   */
-  items: T[];
+export interface PasswordViolation_Too_short {
+  kind: 'too_short';
+  value: number;
+}
+export interface PasswordViolation_No_lowercase {
+  kind: 'no_lowercase';
+}
+export interface PasswordViolation_No_uppercase {
+  kind: 'no_uppercase';
+}
+export interface PasswordViolation_No_digit {
+  kind: 'no_digit';
+}
+export interface PasswordViolation_No_symbol {
+  kind: 'no_symbol';
+}
+export interface PasswordViolation_Breached {
+  kind: 'breached';
+}
+export interface PasswordViolation_Contains_email {
+  kind: 'contains_email';
+}
+
+export type PasswordViolation = PasswordViolation_Too_short | PasswordViolation_No_lowercase | PasswordViolation_No_uppercase | PasswordViolation_No_digit | PasswordViolation_No_symbol | PasswordViolation_Breached | PasswordViolation_Contains_email;
+
+export interface PasswordViolationOpts {
+  too_short: number;
+  no_lowercase: null;
+  no_uppercase: null;
+  no_digit: null;
+  no_symbol: null;
+  breached: null;
+  contains_email: null;
+}
+
+export function makePasswordViolation<K extends keyof PasswordViolationOpts>(kind: K, value: PasswordViolationOpts[K]) { return {kind, value}; }
+
+const PasswordViolation_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"A rule of the server's password policy that a password breaks\n"}],"name":"PasswordViolation","type_":{"kind":"union_","value":{"fields":[{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"The password is shorter than the minimum length, which is the value\n"}],"default":{"kind":"nothing"},"name":"too_short","serializedName":"too_short","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Word32"}}},{"annotations":[],"default":{"kind":"nothing"},"name":"no_lowercase","serializedName":"no_lowercase","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}},{"annotations":[],"default":{"kind":"nothing"},"name":"no_uppercase","serializedName":"no_uppercase","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}},{"annotations":[],"default":{"kind":"nothing"},"name":"no_digit","serializedName":"no_digit","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}},{"annotations":[],"default":{"kind":"nothing"},"name":"no_symbol","serializedName":"no_symbol","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"The password is known to have been exposed in a data breach\n"}],"default":{"kind":"nothing"},"name":"breached","serializedName":"breached","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}},{"annotations":[],"default":{"kind":"nothing"},"name":"contains_email","serializedName":"contains_email","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+
+export const snPasswordViolation: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"PasswordViolation"};
+
+export function texprPasswordViolation(): ADL.ATypeExpr<PasswordViolation> {
+  return {value : {typeRef : {kind: "reference", value : snPasswordViolation}, parameters : []}};
+}
+
+export interface VerifyEmailReq {
+  token: common_strings.StringNE;
+}
+
+export function makeVerifyEmailReq(
+  input: {
+    token: common_strings.StringNE,
+  }
+): VerifyEmailReq {
+  return {
+    token: input.token,
+  };
+}
+
+const VerifyEmailReq_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"VerifyEmailReq","type_":{"kind":"struct_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"token","serializedName":"token","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.strings","name":"StringNE"}}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+
+export const snVerifyEmailReq: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"VerifyEmailReq"};
+
+export function texprVerifyEmailReq(): ADL.ATypeExpr<VerifyEmailReq> {
+  return {value : {typeRef : {kind: "reference", value : snVerifyEmailReq}, parameters : []}};
+}
+
+export type VerifyEmailResp = 'verified' | 'invalid_token';
+export const valuesVerifyEmailResp : VerifyEmailResp[] = ['verified', 'invalid_token'];
+
+const VerifyEmailResp_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"VerifyEmailResp","type_":{"kind":"union_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"verified","serializedName":"verified","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}},{"annotations":[],"default":{"kind":"nothing"},"name":"invalid_token","serializedName":"invalid_token","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+
+export const snVerifyEmailResp: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"VerifyEmailResp"};
+
+export function texprVerifyEmailResp(): ADL.ATypeExpr<VerifyEmailResp> {
+  return {value : {typeRef : {kind: "reference", value : snVerifyEmailResp}, parameters : []}};
+}
+
+export interface SignupReq {
+  token: common_strings.StringNE;
+  fullname: common_strings.StringNE;
+  password: common_strings.Password;
+}
+
+export function makeSignupReq(
+  input: {
+    token: common_strings.StringNE,
+    fullname: common_strings.StringNE,
+    password: common_strings.Password,
+  }
+): SignupReq {
+  return {
+    token: input.token,
+    fullname: input.fullname,
+    password: input.password,
+  };
+}
+
+const SignupReq_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"SignupReq","type_":{"kind":"struct_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"token","serializedName":"token","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.strings","name":"StringNE"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"fullname","serializedName":"fullname","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.strings","name":"StringNE"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"password","serializedName":"password","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.strings","name":"Password"}}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+
+export const snSignupReq: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"SignupReq"};
+
+export function texprSignupReq(): ADL.ATypeExpr<SignupReq> {
+  return {value : {typeRef : {kind: "reference", value : snSignupReq}, parameters : []}};
+}
+
+export interface SignupResp_Tokens {
+  kind: 'tokens';
+  value: LoginTokens;
+}
+export interface SignupResp_Invalid_token {
+  kind: 'invalid_token';
+}
+export interface SignupResp_Weak_password {
+  kind: 'weak_password';
+  value: PasswordViolation[];
+}
+export interface SignupResp_Email_in_use {
+  kind: 'email_in_use';
+}
+
+export type SignupResp = SignupResp_Tokens | SignupResp_Invalid_token | SignupResp_Weak_password | SignupResp_Email_in_use;
+
+export interface SignupRespOpts {
+  tokens: LoginTokens;
+  invalid_token: null;
+  weak_password: PasswordViolation[];
+  email_in_use: null;
+}
+
+export function makeSignupResp<K extends keyof SignupRespOpts>(kind: K, value: SignupRespOpts[K]) { return {kind, value}; }
+
+const SignupResp_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"SignupResp","type_":{"kind":"union_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"tokens","serializedName":"tokens","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"LoginTokens"}}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"The invitation is unknown, expired, revoked or already used\n"}],"default":{"kind":"nothing"},"name":"invalid_token","serializedName":"invalid_token","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}},{"annotations":[],"default":{"kind":"nothing"},"name":"weak_password","serializedName":"weak_password","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"PasswordViolation"}}}],"typeRef":{"kind":"primitive","value":"Vector"}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"A user with the invited email address already exists\n"}],"default":{"kind":"nothing"},"name":"email_in_use","serializedName":"email_in_use","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+
+export const snSignupResp: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"SignupResp"};
+
+export function texprSignupResp(): ADL.ATypeExpr<SignupResp> {
+  return {value : {typeRef : {kind: "reference", value : snSignupResp}, parameters : []}};
+}
+
+export interface ResendVerificationEmailReq {
+  email: common_strings.StringNE;
+}
+
+export function makeResendVerificationEmailReq(
+  input: {
+    email: common_strings.StringNE,
+  }
+): ResendVerificationEmailReq {
+  return {
+    email: input.email,
+  };
+}
+
+const ResendVerificationEmailReq_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"ResendVerificationEmailReq","type_":{"kind":"struct_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"email","serializedName":"email","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.strings","name":"StringNE"}}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+
+export const snResendVerificationEmailReq: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"ResendVerificationEmailReq"};
+
+export function texprResendVerificationEmailReq(): ADL.ATypeExpr<ResendVerificationEmailReq> {
+  return {value : {typeRef : {kind: "reference", value : snResendVerificationEmailReq}, parameters : []}};
+}
+
+export interface BeginTotpEnrolmentResp_Enrolment {
+  kind: 'enrolment';
+  value: TotpEnrolment;
+}
+export interface BeginTotpEnrolmentResp_Already_enrolled {
+  kind: 'already_enrolled';
+}
+
+export type BeginTotpEnrolmentResp = BeginTotpEnrolmentResp_Enrolment | BeginTotpEnrolmentResp_Already_enrolled;
+
+export interface BeginTotpEnrolmentRespOpts {
+  enrolment: TotpEnrolment;
+  already_enrolled: null;
+}
+
+export function makeBeginTotpEnrolmentResp<K extends keyof BeginTotpEnrolmentRespOpts>(kind: K, value: BeginTotpEnrolmentRespOpts[K]) { return {kind, value}; }
+
+const BeginTotpEnrolmentResp_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"BeginTotpEnrolmentResp","type_":{"kind":"union_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"enrolment","serializedName":"enrolment","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"TotpEnrolment"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"already_enrolled","serializedName":"already_enrolled","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+
+export const snBeginTotpEnrolmentResp: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"BeginTotpEnrolmentResp"};
+
+export function texprBeginTotpEnrolmentResp(): ADL.ATypeExpr<BeginTotpEnrolmentResp> {
+  return {value : {typeRef : {kind: "reference", value : snBeginTotpEnrolmentResp}, parameters : []}};
+}
+
+export interface TotpEnrolment {
   /**
-   * The offset used for this query
+   * The base32 encoded secret, for manual entry
    */
-  current_offset: number;
+  secret: string;
   /**
-   * The size of the entire date set
+   * The otpauth uri, for display as a QR code
    */
-  total_count: number;
+  otpauth_uri: string;
 }
 
-export function makePaginated<T>(
+export function makeTotpEnrolment(
   input: {
-    items: T[],
-    current_offset: number,
-    total_count: number,
+    secret: string,
+    otpauth_uri: string,
   }
-): Paginated<T> {
+): TotpEnrolment {
   return {
-    items: input.items,
-    current_offset: input.current_offset,
-    total_count: input.total_count,
+    secret: input.secret,
+    otpauth_uri: input.otpauth_uri,
   };
 }
 
-const Paginated_AST : ADL.ScopedDecl =
-  {"decl":{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"A holder for paginated results\n"}],"name":"Paginated","type_":{"kind":"struct_","value":{"fields":[{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"The paginated items\n"}],"default":{"kind":"nothing"},"name":"items","serializedName":"items","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"typeParam","value":"T"}}],"typeRef":{"kind":"primitive","value":"Vector"}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"The offset used for this query\n"}],"default":{"kind":"nothing"},"name":"current_offset","serializedName":"current_offset","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Word64"}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"The size of the entire date set\n"}],"default":{"kind":"nothing"},"name":"total_count","serializedName":"total_count","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Word64"}}}],"typeParams":["T"]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+const TotpEnrolment_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"TotpEnrolment","type_":{"kind":"struct_","value":{"fields":[{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"The base32 encoded secret, for manual entry\n"}],"default":{"kind":"nothing"},"name":"secret","serializedName":"secret","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"String"}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"The otpauth uri, for display as a QR code\n"}],"default":{"kind":"nothing"},"name":"otpauth_uri","serializedName":"otpauth_uri","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"String"}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
 
-export const snPaginated: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"Paginated"};
+export const snTotpEnrolment: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"TotpEnrolment"};
 
-export function texprPaginated<T>(texprT : ADL.ATypeExpr<T>): ADL.ATypeExpr<Paginated<T>> {
-  return {value : {typeRef : {kind: "reference", value : {moduleName : "protoapp.apis.ui",name : "Paginated"}}, parameters : [texprT.value]}};
+export function texprTotpEnrolment(): ADL.ATypeExpr<TotpEnrolment> {
+  return {value : {typeRef : {kind: "reference", value : snTotpEnrolment}, parameters : []}};
 }
 
-export interface Message {
-  id: protoapp_db.MessageId;
-  posted_at: common_time.Instant;
-  user_fullname: string;
+export interface ConfirmTotpEnrolmentReq {
+  code: common_strings.StringNE;
+}
+
+export function makeConfirmTotpEnrolmentReq(
+  input: {
+    code: common_strings.StringNE,
+  }
+): ConfirmTotpEnrolmentReq {
+  return {
+    code: input.code,
+  };
+}
+
+const ConfirmTotpEnrolmentReq_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"ConfirmTotpEnrolmentReq","type_":{"kind":"struct_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"code","serializedName":"code","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.strings","name":"StringNE"}}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+
+export const snConfirmTotpEnrolmentReq: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"ConfirmTotpEnrolmentReq"};
+
+export function texprConfirmTotpEnrolmentReq(): ADL.ATypeExpr<ConfirmTotpEnrolmentReq> {
+  return {value : {typeRef : {kind: "reference", value : snConfirmTotpEnrolmentReq}, parameters : []}};
+}
+
+export interface ConfirmTotpEnrolmentResp_Recovery_codes {
+  kind: 'recovery_codes';
+  value: string[];
+}
+export interface ConfirmTotpEnrolmentResp_Invalid_code {
+  kind: 'invalid_code';
+}
+export interface ConfirmTotpEnrolmentResp_Not_enrolling {
+  kind: 'not_enrolling';
+}
+
+export type ConfirmTotpEnrolmentResp = ConfirmTotpEnrolmentResp_Recovery_codes | ConfirmTotpEnrolmentResp_Invalid_code | ConfirmTotpEnrolmentResp_Not_enrolling;
+
+export interface ConfirmTotpEnrolmentRespOpts {
+  recovery_codes: string[];
+  invalid_code: null;
+  not_enrolling: null;
+}
+
+export function makeConfirmTotpEnrolmentResp<K extends keyof ConfirmTotpEnrolmentRespOpts>(kind: K, value: ConfirmTotpEnrolmentRespOpts[K]) { return {kind, value}; }
+
+const ConfirmTotpEnrolmentResp_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"ConfirmTotpEnrolmentResp","type_":{"kind":"union_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"recovery_codes","serializedName":"recovery_codes","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"primitive","value":"String"}}],"typeRef":{"kind":"primitive","value":"Vector"}}},{"annotations":[],"default":{"kind":"nothing"},"name":"invalid_code","serializedName":"invalid_code","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}},{"annotations":[],"default":{"kind":"nothing"},"name":"not_enrolling","serializedName":"not_enrolling","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+
+export const snConfirmTotpEnrolmentResp: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"ConfirmTotpEnrolmentResp"};
+
+export function texprConfirmTotpEnrolmentResp(): ADL.ATypeExpr<ConfirmTotpEnrolmentResp> {
+  return {value : {typeRef : {kind: "reference", value : snConfirmTotpEnrolmentResp}, parameters : []}};
+}
+
+export interface NewMessageReq {
   message: common_strings.StringML;
 }
 
-export function makeMessage(
+export function makeNewMessageReq(
   input: {
-    id: protoapp_db.MessageId,
-    posted_at: common_time.Instant,
-    user_fullname: string,
     message: common_strings.StringML,
   }
-): Message {
+): NewMessageReq {
   return {
-    id: input.id,
-    posted_at: input.posted_at,
-    user_fullname: input.user_fullname,
     message: input.message,
   };
 }
 
-const Message_AST : ADL.ScopedDecl =
-  {"decl":{"annotations":[],"name":"Message","type_":{"kind":"struct_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"id","serializedName":"id","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"MessageId"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"posted_at","serializedName":"posted_at","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.time","name":"Instant"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"user_fullname","serializedName":"user_fullname","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"String"}}},{"annotations":[],"default":{"kind":"nothing"},"name":"message","serializedName":"message","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.strings","name":"StringML"}}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+const NewMessageReq_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"NewMessageReq","type_":{"kind":"struct_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"message","serializedName":"message","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.strings","name":"StringML"}}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
 
-export const snMessage: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"Message"};
+export const snNewMessageReq: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"NewMessageReq"};
 
-export function texprMessage(): ADL.ATypeExpr<Message> {
-  return {value : {typeRef : {kind: "reference", value : snMessage}, parameters : []}};
+export function texprNewMessageReq(): ADL.ATypeExpr<NewMessageReq> {
+  return {value : {typeRef : {kind: "reference", value : snNewMessageReq}, parameters : []}};
 }
 
-export interface QueryUsersReq {
+export interface RecentMessagesReq {
   page: PageReq;
 }
 
-export function makeQueryUsersReq(
+export function makeRecentMessagesReq(
   input: {
-    page?: PageReq,
+    page: PageReq,
   }
-): QueryUsersReq {
+): RecentMessagesReq {
   return {
-    page: input.page === undefined ? {offset : 0, limit : 20} : input.page,
+    page: input.page,
   };
 }
 
-const QueryUsersReq_AST : ADL.ScopedDecl =
-  {"decl":{"annotations":[],"name":"QueryUsersReq","type_":{"kind":"struct_","value":{"fields":[{"annotations":[],"default":{"kind":"just","value":{}},"name":"page","serializedName":"page","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"PageReq"}}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+const RecentMessagesReq_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"RecentMessagesReq","type_":{"kind":"struct_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"page","serializedName":"page","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"PageReq"}}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
 
-export const snQueryUsersReq: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"QueryUsersReq"};
+export const snRecentMessagesReq: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"RecentMessagesReq"};
 
-export function texprQueryUsersReq(): ADL.ATypeExpr<QueryUsersReq> {
-  return {value : {typeRef : {kind: "reference", value : snQueryUsersReq}, parameters : []}};
+export function texprRecentMessagesReq(): ADL.ATypeExpr<RecentMessagesReq> {
+  return {value : {typeRef : {kind: "reference", value : snRecentMessagesReq}, parameters : []}};
 }
 
-export interface User {
-  fullname: common_strings.StringNE;
-  email: common_strings.EmailAddress;
-  is_admin: boolean;
+export interface ReplyToMessageReq {
+  reply_to: protoapp_db.MessageId;
+  message: common_strings.StringML;
 }
 
-export function makeUser(
+export function makeReplyToMessageReq(
   input: {
-    fullname: common_strings.StringNE,
-    email: common_strings.EmailAddress,
-    is_admin: boolean,
+    reply_to: protoapp_db.MessageId,
+    message: common_strings.StringML,
   }
-): User {
+): ReplyToMessageReq {
   return {
-    fullname: input.fullname,
-    email: input.email,
-    is_admin: input.is_admin,
+    reply_to: input.reply_to,
+    message: input.message,
   };
 }
 
-const User_AST : ADL.ScopedDecl =
-  {"decl":{"annotations":[],"name":"User","type_":{"kind":"struct_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"fullname","serializedName":"fullname","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.strings","name":"StringNE"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"email","serializedName":"email","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.strings","name":"EmailAddress"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"is_admin","serializedName":"is_admin","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Bool"}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+const ReplyToMessageReq_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"ReplyToMessageReq","type_":{"kind":"struct_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"reply_to","serializedName":"reply_to","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"MessageId"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"message","serializedName":"message","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.strings","name":"StringML"}}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
 
-export const snUser: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"User"};
+export const snReplyToMessageReq: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"ReplyToMessageReq"};
 
-export function texprUser(): ADL.ATypeExpr<User> {
-  return {value : {typeRef : {kind: "reference", value : snUser}, parameters : []}};
+export function texprReplyToMessageReq(): ADL.ATypeExpr<ReplyToMessageReq> {
+  return {value : {typeRef : {kind: "reference", value : snReplyToMessageReq}, parameters : []}};
 }
 
-export type UserWithId = WithId<protoapp_db.AppUserId, User>;
+export interface ReplyToMessageResp_Success {
+  kind: 'success';
+  value: protoapp_db.MessageId;
+}
+export interface ReplyToMessageResp_Invalid_message {
+  kind: 'invalid_message';
+}
 
-const UserWithId_AST : ADL.ScopedDecl =
-  {"decl":{"annotations":[],"name":"UserWithId","type_":{"kind":"type_","value":{"typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"AppUserId"}}},{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"User"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"WithId"}}},"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+export type ReplyToMessageResp = ReplyToMessageResp_Success | ReplyToMessageResp_Invalid_message;
 
-export const snUserWithId: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"UserWithId"};
+export interface ReplyToMessageRespOpts {
+  success: protoapp_db.MessageId;
+  invalid_message: null;
+}
 
-export function texprUserWithId(): ADL.ATypeExpr<UserWithId> {
-  return {value : {typeRef : {kind: "reference", value : snUserWithId}, parameters : []}};
+export function makeReplyToMessageResp<K extends keyof ReplyToMessageRespOpts>(kind: K, value: ReplyToMessageRespOpts[K]) { return {kind, value}; }
+
+const ReplyToMessageResp_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"ReplyToMessageResp","type_":{"kind":"union_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"success","serializedName":"success","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"MessageId"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"invalid_message","serializedName":"invalid_message","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+
+export const snReplyToMessageResp: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"ReplyToMessageResp"};
+
+export function texprReplyToMessageResp(): ADL.ATypeExpr<ReplyToMessageResp> {
+  return {value : {typeRef : {kind: "reference", value : snReplyToMessageResp}, parameters : []}};
 }
 
-export interface UserDetails {
-  fullname: common_strings.StringNE;
-  email: common_strings.EmailAddress;
-  is_admin: boolean;
-  password: common_strings.Password;
+export interface MessageThreadReq {
+  id: protoapp_db.MessageId;
+  page: PageReq;
 }
 
-export function makeUserDetails(
+export function makeMessageThreadReq(
   input: {
-    fullname: common_strings.StringNE,
-    email: common_strings.EmailAddress,
-    is_admin: boolean,
-    password: common_strings.Password,
+    id: protoapp_db.MessageId,
+    page?: PageReq,
   }
-): UserDetails {
+): MessageThreadReq {
   return {
-    fullname: input.fullname,
-    email: input.email,
-    is_admin: input.is_admin,
-    password: input.password,
+    id: input.id,
+    page: input.page === undefined ? {offset : 0, limit : 20} : input.page,
   };
 }
 
-const UserDetails_AST : ADL.ScopedDecl =
-  {"decl":{"annotations":[],"name":"UserDetails","type_":{"kind":"struct_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"fullname","serializedName":"fullname","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.strings","name":"StringNE"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"email","serializedName":"email","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.strings","name":"EmailAddress"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"is_admin","serializedName":"is_admin","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Bool"}}},{"annotations":[],"default":{"kind":"nothing"},"name":"password","serializedName":"password","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.strings","name":"Password"}}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+const MessageThreadReq_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"MessageThreadReq","type_":{"kind":"struct_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"id","serializedName":"id","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"MessageId"}}}},{"annotations":[],"default":{"kind":"just","value":{}},"name":"page","serializedName":"page","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"PageReq"}}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
 
-export const snUserDetails: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"UserDetails"};
+export const snMessageThreadReq: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"MessageThreadReq"};
 
-export function texprUserDetails(): ADL.ATypeExpr<UserDetails> {
-  return {value : {typeRef : {kind: "reference", value : snUserDetails}, parameters : []}};
+export function texprMessageThreadReq(): ADL.ATypeExpr<MessageThreadReq> {
+  return {value : {typeRef : {kind: "reference", value : snMessageThreadReq}, parameters : []}};
+}
+
+export interface MessageThreadResp_Thread {
+  kind: 'thread';
+  value: MessageThread;
+}
+export interface MessageThreadResp_Invalid_message {
+  kind: 'invalid_message';
+}
+
+export type MessageThreadResp = MessageThreadResp_Thread | MessageThreadResp_Invalid_message;
+
+export interface MessageThreadRespOpts {
+  thread: MessageThread;
+  invalid_message: null;
+}
+
+export function makeMessageThreadResp<K extends keyof MessageThreadRespOpts>(kind: K, value: MessageThreadRespOpts[K]) { return {kind, value}; }
+
+const MessageThreadResp_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"MessageThreadResp","type_":{"kind":"union_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"thread","serializedName":"thread","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"MessageThread"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"invalid_message","serializedName":"invalid_message","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+
+export const snMessageThreadResp: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"MessageThreadResp"};
+
+export function texprMessageThreadResp(): ADL.ATypeExpr<MessageThreadResp> {
+  return {value : {typeRef : {kind: "reference", value : snMessageThreadResp}, parameters : []}};
+}
+
+export interface MessageThread {
+  message: Message;
+  replies: Paginated<Message>;
+}
+
+export function makeMessageThread(
+  input: {
+    message: Message,
+    replies: Paginated<Message>,
+  }
+): MessageThread {
+  return {
+    message: input.message,
+    replies: input.replies,
+  };
+}
+
+const MessageThread_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"MessageThread","type_":{"kind":"struct_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"message","serializedName":"message","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"Message"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"replies","serializedName":"replies","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"Message"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"Paginated"}}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+
+export const snMessageThread: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"MessageThread"};
+
+export function texprMessageThread(): ADL.ATypeExpr<MessageThread> {
+  return {value : {typeRef : {kind: "reference", value : snMessageThread}, parameters : []}};
+}
+
+export interface MessageReactionReq {
+  message_id: protoapp_db.MessageId;
+  emoji: common_strings.StringNE;
+}
+
+export function makeMessageReactionReq(
+  input: {
+    message_id: protoapp_db.MessageId,
+    emoji: common_strings.StringNE,
+  }
+): MessageReactionReq {
+  return {
+    message_id: input.message_id,
+    emoji: input.emoji,
+  };
+}
+
+const MessageReactionReq_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"MessageReactionReq","type_":{"kind":"struct_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"message_id","serializedName":"message_id","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"MessageId"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"emoji","serializedName":"emoji","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.strings","name":"StringNE"}}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+
+export const snMessageReactionReq: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"MessageReactionReq"};
+
+export function texprMessageReactionReq(): ADL.ATypeExpr<MessageReactionReq> {
+  return {value : {typeRef : {kind: "reference", value : snMessageReactionReq}, parameters : []}};
+}
+
+export type AddReactionResp = 'success' | 'invalid_message' | 'invalid_emoji';
+export const valuesAddReactionResp : AddReactionResp[] = ['success', 'invalid_message', 'invalid_emoji'];
+
+const AddReactionResp_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"AddReactionResp","type_":{"kind":"union_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"success","serializedName":"success","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}},{"annotations":[],"default":{"kind":"nothing"},"name":"invalid_message","serializedName":"invalid_message","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}},{"annotations":[],"default":{"kind":"nothing"},"name":"invalid_emoji","serializedName":"invalid_emoji","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+
+export const snAddReactionResp: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"AddReactionResp"};
+
+export function texprAddReactionResp(): ADL.ATypeExpr<AddReactionResp> {
+  return {value : {typeRef : {kind: "reference", value : snAddReactionResp}, parameters : []}};
+}
+
+export interface EditMessageReq {
+  id: protoapp_db.MessageId;
+  message: common_strings.StringML;
+}
+
+export function makeEditMessageReq(
+  input: {
+    id: protoapp_db.MessageId,
+    message: common_strings.StringML,
+  }
+): EditMessageReq {
+  return {
+    id: input.id,
+    message: input.message,
+  };
+}
+
+const EditMessageReq_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"EditMessageReq","type_":{"kind":"struct_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"id","serializedName":"id","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"MessageId"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"message","serializedName":"message","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.strings","name":"StringML"}}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+
+export const snEditMessageReq: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"EditMessageReq"};
+
+export function texprEditMessageReq(): ADL.ATypeExpr<EditMessageReq> {
+  return {value : {typeRef : {kind: "reference", value : snEditMessageReq}, parameters : []}};
+}
+
+export type EditMessageResp = 'success' | 'invalid_message' | 'not_permitted';
+export const valuesEditMessageResp : EditMessageResp[] = ['success', 'invalid_message', 'not_permitted'];
+
+const EditMessageResp_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"EditMessageResp","type_":{"kind":"union_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"success","serializedName":"success","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}},{"annotations":[],"default":{"kind":"nothing"},"name":"invalid_message","serializedName":"invalid_message","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}},{"annotations":[],"default":{"kind":"nothing"},"name":"not_permitted","serializedName":"not_permitted","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+
+export const snEditMessageResp: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"EditMessageResp"};
+
+export function texprEditMessageResp(): ADL.ATypeExpr<EditMessageResp> {
+  return {value : {typeRef : {kind: "reference", value : snEditMessageResp}, parameters : []}};
+}
+
+export type DeleteMessageResp = 'success' | 'invalid_message' | 'not_permitted';
+export const valuesDeleteMessageResp : DeleteMessageResp[] = ['success', 'invalid_message', 'not_permitted'];
+
+const DeleteMessageResp_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"DeleteMessageResp","type_":{"kind":"union_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"success","serializedName":"success","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}},{"annotations":[],"default":{"kind":"nothing"},"name":"invalid_message","serializedName":"invalid_message","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}},{"annotations":[],"default":{"kind":"nothing"},"name":"not_permitted","serializedName":"not_permitted","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+
+export const snDeleteMessageResp: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"DeleteMessageResp"};
+
+export function texprDeleteMessageResp(): ADL.ATypeExpr<DeleteMessageResp> {
+  return {value : {typeRef : {kind: "reference", value : snDeleteMessageResp}, parameters : []}};
+}
+
+export interface MessageRevisionsResp_Revisions {
+  kind: 'revisions';
+  value: protoapp_db.MessageRevision[];
+}
+export interface MessageRevisionsResp_Invalid_message {
+  kind: 'invalid_message';
+}
+export interface MessageRevisionsResp_Not_permitted {
+  kind: 'not_permitted';
+}
+
+export type MessageRevisionsResp = MessageRevisionsResp_Revisions | MessageRevisionsResp_Invalid_message | MessageRevisionsResp_Not_permitted;
+
+export interface MessageRevisionsRespOpts {
+  revisions: protoapp_db.MessageRevision[];
+  invalid_message: null;
+  not_permitted: null;
+}
+
+export function makeMessageRevisionsResp<K extends keyof MessageRevisionsRespOpts>(kind: K, value: MessageRevisionsRespOpts[K]) { return {kind, value}; }
+
+const MessageRevisionsResp_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"MessageRevisionsResp","type_":{"kind":"union_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"revisions","serializedName":"revisions","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"MessageRevision"}}}],"typeRef":{"kind":"primitive","value":"Vector"}}},{"annotations":[],"default":{"kind":"nothing"},"name":"invalid_message","serializedName":"invalid_message","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}},{"annotations":[],"default":{"kind":"nothing"},"name":"not_permitted","serializedName":"not_permitted","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+
+export const snMessageRevisionsResp: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"MessageRevisionsResp"};
+
+export function texprMessageRevisionsResp(): ADL.ATypeExpr<MessageRevisionsResp> {
+  return {value : {typeRef : {kind: "reference", value : snMessageRevisionsResp}, parameters : []}};
+}
+
+export interface PageReq {
+  offset: number;
+  limit: number;
+}
+
+export function makePageReq(
+  input: {
+    offset?: number,
+    limit?: number,
+  }
+): PageReq {
+  return {
+    offset: input.offset === undefined ? 0 : input.offset,
+    limit: input.limit === undefined ? 20 : input.limit,
+  };
+}
+
+const PageReq_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"PageReq","type_":{"kind":"struct_","value":{"fields":[{"annotations":[],"default":{"kind":"just","value":0},"name":"offset","serializedName":"offset","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Word64"}}},{"annotations":[],"default":{"kind":"just","value":20},"name":"limit","serializedName":"limit","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Word64"}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+
+export const snPageReq: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"PageReq"};
+
+export function texprPageReq(): ADL.ATypeExpr<PageReq> {
+  return {value : {typeRef : {kind: "reference", value : snPageReq}, parameters : []}};
+}
+
+/**
+ * A holder for paginated results
+ */
+export interface Paginated<T> {
+  /**
+   * The paginated items
+   */
+  items: T[];
+  /**
+   * The offset used for this query
+   */
+  current_offset: number;
+  /**
+   * The size of the entire date set
+   */
+  total_count: number;
+}
+
+export function makePaginated<T>(
+  input: {
+    items: T[],
+    current_offset: number,
+    total_count: number,
+  }
+): Paginated<T> {
+  return {
+    items: input.items,
+    current_offset: input.current_offset,
+    total_count: input.total_count,
+  };
+}
+
+const Paginated_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"A holder for paginated results\n"}],"name":"Paginated","type_":{"kind":"struct_","value":{"fields":[{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"The paginated items\n"}],"default":{"kind":"nothing"},"name":"items","serializedName":"items","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"typeParam","value":"T"}}],"typeRef":{"kind":"primitive","value":"Vector"}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"The offset used for this query\n"}],"default":{"kind":"nothing"},"name":"current_offset","serializedName":"current_offset","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Word64"}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"The size of the entire date set\n"}],"default":{"kind":"nothing"},"name":"total_count","serializedName":"total_count","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Word64"}}}],"typeParams":["T"]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+
+export const snPaginated: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"Paginated"};
+
+export function texprPaginated<T>(texprT : ADL.ATypeExpr<T>): ADL.ATypeExpr<Paginated<T>> {
+  return {value : {typeRef : {kind: "reference", value : {moduleName : "protoapp.apis.ui",name : "Paginated"}}, parameters : [texprT.value]}};
+}
+
+export interface Message {
+  id: protoapp_db.MessageId;
+  posted_at: common_time.Instant;
+  user_fullname: string;
+  message: common_strings.StringML;
+  edited_at: (common_time.Instant|null);
+  deleted_at: (common_time.Instant|null);
+  reply_count: number;
+  latest_reply_at: (common_time.Instant|null);
+  reactions: ReactionCount[];
+}
+
+export function makeMessage(
+  input: {
+    id: protoapp_db.MessageId,
+    posted_at: common_time.Instant,
+    user_fullname: string,
+    message: common_strings.StringML,
+    edited_at?: (common_time.Instant|null),
+    deleted_at?: (common_time.Instant|null),
+    reply_count?: number,
+    latest_reply_at?: (common_time.Instant|null),
+    reactions?: ReactionCount[],
+  }
+): Message {
+  return {
+    id: input.id,
+    posted_at: input.posted_at,
+    user_fullname: input.user_fullname,
+    message: input.message,
+    edited_at: input.edited_at === undefined ? null : input.edited_at,
+    deleted_at: input.deleted_at === undefined ? null : input.deleted_at,
+    reply_count: input.reply_count === undefined ? 0 : input.reply_count,
+    latest_reply_at: input.latest_reply_at === undefined ? null : input.latest_reply_at,
+    reactions: input.reactions === undefined ? [] : input.reactions,
+  };
+}
+
+const Message_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"Message","type_":{"kind":"struct_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"id","serializedName":"id","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"MessageId"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"posted_at","serializedName":"posted_at","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.time","name":"Instant"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"user_fullname","serializedName":"user_fullname","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"String"}}},{"annotations":[],"default":{"kind":"nothing"},"name":"message","serializedName":"message","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.strings","name":"StringML"}}}},{"annotations":[],"default":{"kind":"just","value":null},"name":"edited_at","serializedName":"edited_at","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.time","name":"Instant"}}}],"typeRef":{"kind":"primitive","value":"Nullable"}}},{"annotations":[],"default":{"kind":"just","value":null},"name":"deleted_at","serializedName":"deleted_at","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.time","name":"Instant"}}}],"typeRef":{"kind":"primitive","value":"Nullable"}}},{"annotations":[],"default":{"kind":"just","value":0},"name":"reply_count","serializedName":"reply_count","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Word64"}}},{"annotations":[],"default":{"kind":"just","value":null},"name":"latest_reply_at","serializedName":"latest_reply_at","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.time","name":"Instant"}}}],"typeRef":{"kind":"primitive","value":"Nullable"}}},{"annotations":[],"default":{"kind":"just","value":[]},"name":"reactions","serializedName":"reactions","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"ReactionCount"}}}],"typeRef":{"kind":"primitive","value":"Vector"}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+
+export const snMessage: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"Message"};
+
+export function texprMessage(): ADL.ATypeExpr<Message> {
+  return {value : {typeRef : {kind: "reference", value : snMessage}, parameters : []}};
+}
+
+export interface ReactionCount {
+  emoji: common_strings.StringNE;
+  count: number;
+  reacted_by_me: boolean;
+}
+
+export function makeReactionCount(
+  input: {
+    emoji: common_strings.StringNE,
+    count: number,
+    reacted_by_me: boolean,
+  }
+): ReactionCount {
+  return {
+    emoji: input.emoji,
+    count: input.count,
+    reacted_by_me: input.reacted_by_me,
+  };
+}
+
+const ReactionCount_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"ReactionCount","type_":{"kind":"struct_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"emoji","serializedName":"emoji","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.strings","name":"StringNE"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"count","serializedName":"count","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Word64"}}},{"annotations":[],"default":{"kind":"nothing"},"name":"reacted_by_me","serializedName":"reacted_by_me","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Bool"}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+
+export const snReactionCount: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"ReactionCount"};
+
+export function texprReactionCount(): ADL.ATypeExpr<ReactionCount> {
+  return {value : {typeRef : {kind: "reference", value : snReactionCount}, parameters : []}};
+}
+
+export interface QueryUsersReq {
+  page: PageReq;
+}
+
+export function makeQueryUsersReq(
+  input: {
+    page?: PageReq,
+  }
+): QueryUsersReq {
+  return {
+    page: input.page === undefined ? {offset : 0, limit : 20} : input.page,
+  };
+}
+
+const QueryUsersReq_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"QueryUsersReq","type_":{"kind":"struct_","value":{"fields":[{"annotations":[],"default":{"kind":"just","value":{}},"name":"page","serializedName":"page","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"PageReq"}}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+
+export const snQueryUsersReq: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"QueryUsersReq"};
+
+export function texprQueryUsersReq(): ADL.ATypeExpr<QueryUsersReq> {
+  return {value : {typeRef : {kind: "reference", value : snQueryUsersReq}, parameters : []}};
+}
+
+export interface User {
+  fullname: common_strings.StringNE;
+  email: common_strings.EmailAddress;
+  /**
+   * The names of the user's roles, other than the implicit "user" role
+   */
+  roles: common_strings.StringNE[];
+  /**
+   * False if the user has been deactivated
+   */
+  active: boolean;
+}
+
+export function makeUser(
+  input: {
+    fullname: common_strings.StringNE,
+    email: common_strings.EmailAddress,
+    roles: common_strings.StringNE[],
+    active: boolean,
+  }
+): User {
+  return {
+    fullname: input.fullname,
+    email: input.email,
+    roles: input.roles,
+    active: input.active,
+  };
+}
+
+const User_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"User","type_":{"kind":"struct_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"fullname","serializedName":"fullname","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.strings","name":"StringNE"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"email","serializedName":"email","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.strings","name":"EmailAddress"}}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"The names of the user's roles, other than the implicit \"user\" role\n"}],"default":{"kind":"nothing"},"name":"roles","serializedName":"roles","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.strings","name":"StringNE"}}}],"typeRef":{"kind":"primitive","value":"Vector"}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"False if the user has been deactivated\n"}],"default":{"kind":"nothing"},"name":"active","serializedName":"active","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Bool"}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+
+export const snUser: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"User"};
+
+export function texprUser(): ADL.ATypeExpr<User> {
+  return {value : {typeRef : {kind: "reference", value : snUser}, parameters : []}};
+}
+
+export type UserWithId = WithId<protoapp_db.AppUserId, User>;
+
+const UserWithId_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"UserWithId","type_":{"kind":"type_","value":{"typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"AppUserId"}}},{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"User"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"WithId"}}},"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+
+export const snUserWithId: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"UserWithId"};
+
+export function texprUserWithId(): ADL.ATypeExpr<UserWithId> {
+  return {value : {typeRef : {kind: "reference", value : snUserWithId}, parameters : []}};
+}
+
+export interface ImpersonateUserResp_Access_jwt {
+  kind: 'access_jwt';
+  value: common_strings.StringNE;
+}
+export interface ImpersonateUserResp_Invalid_user {
+  kind: 'invalid_user';
+}
+
+export type ImpersonateUserResp = ImpersonateUserResp_Access_jwt | ImpersonateUserResp_Invalid_user;
+
+export interface ImpersonateUserRespOpts {
+  access_jwt: common_strings.StringNE;
+  invalid_user: null;
+}
+
+export function makeImpersonateUserResp<K extends keyof ImpersonateUserRespOpts>(kind: K, value: ImpersonateUserRespOpts[K]) { return {kind, value}; }
+
+const ImpersonateUserResp_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"ImpersonateUserResp","type_":{"kind":"union_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"access_jwt","serializedName":"access_jwt","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.strings","name":"StringNE"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"invalid_user","serializedName":"invalid_user","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+
+export const snImpersonateUserResp: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"ImpersonateUserResp"};
+
+export function texprImpersonateUserResp(): ADL.ATypeExpr<ImpersonateUserResp> {
+  return {value : {typeRef : {kind: "reference", value : snImpersonateUserResp}, parameters : []}};
+}
+
+export interface JwtFailureCounts {
+  access: JwtFailureCount[];
+  refresh: JwtFailureCount[];
+}
+
+export function makeJwtFailureCounts(
+  input: {
+    access: JwtFailureCount[],
+    refresh: JwtFailureCount[],
+  }
+): JwtFailureCounts {
+  return {
+    access: input.access,
+    refresh: input.refresh,
+  };
+}
+
+const JwtFailureCounts_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"JwtFailureCounts","type_":{"kind":"struct_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"access","serializedName":"access","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"JwtFailureCount"}}}],"typeRef":{"kind":"primitive","value":"Vector"}}},{"annotations":[],"default":{"kind":"nothing"},"name":"refresh","serializedName":"refresh","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"JwtFailureCount"}}}],"typeRef":{"kind":"primitive","value":"Vector"}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+
+export const snJwtFailureCounts: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"JwtFailureCounts"};
+
+export function texprJwtFailureCounts(): ADL.ATypeExpr<JwtFailureCounts> {
+  return {value : {typeRef : {kind: "reference", value : snJwtFailureCounts}, parameters : []}};
+}
+
+export interface JwtFailureCount {
+  reason: string;
+  count: number;
+}
+
+export function makeJwtFailureCount(
+  input: {
+    reason: string,
+    count: number,
+  }
+): JwtFailureCount {
+  return {
+    reason: input.reason,
+    count: input.count,
+  };
+}
+
+const JwtFailureCount_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"JwtFailureCount","type_":{"kind":"struct_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"reason","serializedName":"reason","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"String"}}},{"annotations":[],"default":{"kind":"nothing"},"name":"count","serializedName":"count","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Word64"}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+
+export const snJwtFailureCount: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"JwtFailureCount"};
+
+export function texprJwtFailureCount(): ADL.ATypeExpr<JwtFailureCount> {
+  return {value : {typeRef : {kind: "reference", value : snJwtFailureCount}, parameters : []}};
+}
+
+export interface QueryAuditLogReq {
+  page: PageReq;
+  actor: (protoapp_db.AppUserId|null);
+  endpoint: (string|null);
+  outcome: (protoapp_db.AuditOutcome|null);
+  logged_after: (common_time.Instant|null);
+  logged_before: (common_time.Instant|null);
+}
+
+export function makeQueryAuditLogReq(
+  input: {
+    page?: PageReq,
+    actor?: (protoapp_db.AppUserId|null),
+    endpoint?: (string|null),
+    outcome?: (protoapp_db.AuditOutcome|null),
+    logged_after?: (common_time.Instant|null),
+    logged_before?: (common_time.Instant|null),
+  }
+): QueryAuditLogReq {
+  return {
+    page: input.page === undefined ? {offset : 0, limit : 20} : input.page,
+    actor: input.actor === undefined ? null : input.actor,
+    endpoint: input.endpoint === undefined ? null : input.endpoint,
+    outcome: input.outcome === undefined ? null : input.outcome,
+    logged_after: input.logged_after === undefined ? null : input.logged_after,
+    logged_before: input.logged_before === undefined ? null : input.logged_before,
+  };
+}
+
+const QueryAuditLogReq_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"QueryAuditLogReq","type_":{"kind":"struct_","value":{"fields":[{"annotations":[],"default":{"kind":"just","value":{}},"name":"page","serializedName":"page","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"PageReq"}}}},{"annotations":[],"default":{"kind":"just","value":null},"name":"actor","serializedName":"actor","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"AppUserId"}}}],"typeRef":{"kind":"primitive","value":"Nullable"}}},{"annotations":[],"default":{"kind":"just","value":null},"name":"endpoint","serializedName":"endpoint","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"primitive","value":"String"}}],"typeRef":{"kind":"primitive","value":"Nullable"}}},{"annotations":[],"default":{"kind":"just","value":null},"name":"outcome","serializedName":"outcome","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"AuditOutcome"}}}],"typeRef":{"kind":"primitive","value":"Nullable"}}},{"annotations":[],"default":{"kind":"just","value":null},"name":"logged_after","serializedName":"logged_after","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.time","name":"Instant"}}}],"typeRef":{"kind":"primitive","value":"Nullable"}}},{"annotations":[],"default":{"kind":"just","value":null},"name":"logged_before","serializedName":"logged_before","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.time","name":"Instant"}}}],"typeRef":{"kind":"primitive","value":"Nullable"}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+
+export const snQueryAuditLogReq: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"QueryAuditLogReq"};
+
+export function texprQueryAuditLogReq(): ADL.ATypeExpr<QueryAuditLogReq> {
+  return {value : {typeRef : {kind: "reference", value : snQueryAuditLogReq}, parameters : []}};
+}
+
+export type AuditLogEntry = WithId<protoapp_db.AuditLogId, protoapp_db.AuditLog>;
+
+const AuditLogEntry_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"AuditLogEntry","type_":{"kind":"type_","value":{"typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"AuditLogId"}}},{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"AuditLog"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"WithId"}}},"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+
+export const snAuditLogEntry: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"AuditLogEntry"};
+
+export function texprAuditLogEntry(): ADL.ATypeExpr<AuditLogEntry> {
+  return {value : {typeRef : {kind: "reference", value : snAuditLogEntry}, parameters : []}};
+}
+
+export interface CreateApiKeyReq {
+  user_id: protoapp_db.AppUserId;
+  name: common_strings.StringNE;
+  scopes: protoapp_db.ApiKeyScope[];
+  expires_in_days: number;
+}
+
+export function makeCreateApiKeyReq(
+  input: {
+    user_id: protoapp_db.AppUserId,
+    name: common_strings.StringNE,
+    scopes: protoapp_db.ApiKeyScope[],
+    expires_in_days?: number,
+  }
+): CreateApiKeyReq {
+  return {
+    user_id: input.user_id,
+    name: input.name,
+    scopes: input.scopes,
+    expires_in_days: input.expires_in_days === undefined ? 90 : input.expires_in_days,
+  };
+}
+
+const CreateApiKeyReq_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"CreateApiKeyReq","type_":{"kind":"struct_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"user_id","serializedName":"user_id","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"AppUserId"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"name","serializedName":"name","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.strings","name":"StringNE"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"scopes","serializedName":"scopes","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"ApiKeyScope"}}}],"typeRef":{"kind":"primitive","value":"Vector"}}},{"annotations":[],"default":{"kind":"just","value":90},"name":"expires_in_days","serializedName":"expires_in_days","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Word32"}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+
+export const snCreateApiKeyReq: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"CreateApiKeyReq"};
+
+export function texprCreateApiKeyReq(): ADL.ATypeExpr<CreateApiKeyReq> {
+  return {value : {typeRef : {kind: "reference", value : snCreateApiKeyReq}, parameters : []}};
+}
+
+export interface CreateApiKeyResp_Success {
+  kind: 'success';
+  value: NewApiKey;
+}
+export interface CreateApiKeyResp_Invalid_user {
+  kind: 'invalid_user';
+}
+export interface CreateApiKeyResp_Invalid_expiry {
+  kind: 'invalid_expiry';
+}
+
+export type CreateApiKeyResp = CreateApiKeyResp_Success | CreateApiKeyResp_Invalid_user | CreateApiKeyResp_Invalid_expiry;
+
+export interface CreateApiKeyRespOpts {
+  success: NewApiKey;
+  invalid_user: null;
+  invalid_expiry: null;
+}
+
+export function makeCreateApiKeyResp<K extends keyof CreateApiKeyRespOpts>(kind: K, value: CreateApiKeyRespOpts[K]) { return {kind, value}; }
+
+const CreateApiKeyResp_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"CreateApiKeyResp","type_":{"kind":"union_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"success","serializedName":"success","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"NewApiKey"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"invalid_user","serializedName":"invalid_user","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"expires_in_days is zero or more than the configured maximum\n"}],"default":{"kind":"nothing"},"name":"invalid_expiry","serializedName":"invalid_expiry","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+
+export const snCreateApiKeyResp: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"CreateApiKeyResp"};
+
+export function texprCreateApiKeyResp(): ADL.ATypeExpr<CreateApiKeyResp> {
+  return {value : {typeRef : {kind: "reference", value : snCreateApiKeyResp}, parameters : []}};
+}
+
+export interface NewApiKey {
+  id: protoapp_db.ApiKeyId;
+  key: common_strings.StringNE;
+}
+
+export function makeNewApiKey(
+  input: {
+    id: protoapp_db.ApiKeyId,
+    key: common_strings.StringNE,
+  }
+): NewApiKey {
+  return {
+    id: input.id,
+    key: input.key,
+  };
+}
+
+const NewApiKey_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"NewApiKey","type_":{"kind":"struct_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"id","serializedName":"id","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"ApiKeyId"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"key","serializedName":"key","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.strings","name":"StringNE"}}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+
+export const snNewApiKey: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"NewApiKey"};
+
+export function texprNewApiKey(): ADL.ATypeExpr<NewApiKey> {
+  return {value : {typeRef : {kind: "reference", value : snNewApiKey}, parameters : []}};
+}
+
+export interface QueryApiKeysReq {
+  page: PageReq;
+  user_id: (protoapp_db.AppUserId|null);
+}
+
+export function makeQueryApiKeysReq(
+  input: {
+    page?: PageReq,
+    user_id?: (protoapp_db.AppUserId|null),
+  }
+): QueryApiKeysReq {
+  return {
+    page: input.page === undefined ? {offset : 0, limit : 20} : input.page,
+    user_id: input.user_id === undefined ? null : input.user_id,
+  };
+}
+
+const QueryApiKeysReq_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"QueryApiKeysReq","type_":{"kind":"struct_","value":{"fields":[{"annotations":[],"default":{"kind":"just","value":{}},"name":"page","serializedName":"page","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"PageReq"}}}},{"annotations":[],"default":{"kind":"just","value":null},"name":"user_id","serializedName":"user_id","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"AppUserId"}}}],"typeRef":{"kind":"primitive","value":"Nullable"}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+
+export const snQueryApiKeysReq: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"QueryApiKeysReq"};
+
+export function texprQueryApiKeysReq(): ADL.ATypeExpr<QueryApiKeysReq> {
+  return {value : {typeRef : {kind: "reference", value : snQueryApiKeysReq}, parameters : []}};
+}
+
+export interface ApiKeySummary {
+  user_id: protoapp_db.AppUserId;
+  name: common_strings.StringNE;
+  scopes: protoapp_db.ApiKeyScope[];
+  created_at: common_time.Instant;
+  expires_at: common_time.Instant;
+  last_used_at: (common_time.Instant|null);
+  revoked_at: (common_time.Instant|null);
+}
+
+export function makeApiKeySummary(
+  input: {
+    user_id: protoapp_db.AppUserId,
+    name: common_strings.StringNE,
+    scopes: protoapp_db.ApiKeyScope[],
+    created_at: common_time.Instant,
+    expires_at: common_time.Instant,
+    last_used_at: (common_time.Instant|null),
+    revoked_at: (common_time.Instant|null),
+  }
+): ApiKeySummary {
+  return {
+    user_id: input.user_id,
+    name: input.name,
+    scopes: input.scopes,
+    created_at: input.created_at,
+    expires_at: input.expires_at,
+    last_used_at: input.last_used_at,
+    revoked_at: input.revoked_at,
+  };
+}
+
+const ApiKeySummary_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"ApiKeySummary","type_":{"kind":"struct_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"user_id","serializedName":"user_id","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"AppUserId"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"name","serializedName":"name","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.strings","name":"StringNE"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"scopes","serializedName":"scopes","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"ApiKeyScope"}}}],"typeRef":{"kind":"primitive","value":"Vector"}}},{"annotations":[],"default":{"kind":"nothing"},"name":"created_at","serializedName":"created_at","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.time","name":"Instant"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"expires_at","serializedName":"expires_at","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.time","name":"Instant"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"last_used_at","serializedName":"last_used_at","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.time","name":"Instant"}}}],"typeRef":{"kind":"primitive","value":"Nullable"}}},{"annotations":[],"default":{"kind":"nothing"},"name":"revoked_at","serializedName":"revoked_at","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.time","name":"Instant"}}}],"typeRef":{"kind":"primitive","value":"Nullable"}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+
+export const snApiKeySummary: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"ApiKeySummary"};
+
+export function texprApiKeySummary(): ADL.ATypeExpr<ApiKeySummary> {
+  return {value : {typeRef : {kind: "reference", value : snApiKeySummary}, parameters : []}};
+}
+
+export type ApiKeySummaryWithId = WithId<protoapp_db.ApiKeyId, ApiKeySummary>;
+
+const ApiKeySummaryWithId_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"ApiKeySummaryWithId","type_":{"kind":"type_","value":{"typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"ApiKeyId"}}},{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"ApiKeySummary"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"WithId"}}},"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+
+export const snApiKeySummaryWithId: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"ApiKeySummaryWithId"};
+
+export function texprApiKeySummaryWithId(): ADL.ATypeExpr<ApiKeySummaryWithId> {
+  return {value : {typeRef : {kind: "reference", value : snApiKeySummaryWithId}, parameters : []}};
+}
+
+export interface CreateInvitationReq {
+  email: common_strings.EmailAddress;
+  roles: protoapp_db.RoleId[];
+  expires_in_days: number;
+}
+
+export function makeCreateInvitationReq(
+  input: {
+    email: common_strings.EmailAddress,
+    roles?: protoapp_db.RoleId[],
+    expires_in_days?: number,
+  }
+): CreateInvitationReq {
+  return {
+    email: input.email,
+    roles: input.roles === undefined ? [] : input.roles,
+    expires_in_days: input.expires_in_days === undefined ? 7 : input.expires_in_days,
+  };
+}
+
+const CreateInvitationReq_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"CreateInvitationReq","type_":{"kind":"struct_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"email","serializedName":"email","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.strings","name":"EmailAddress"}}}},{"annotations":[],"default":{"kind":"just","value":[]},"name":"roles","serializedName":"roles","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"RoleId"}}}],"typeRef":{"kind":"primitive","value":"Vector"}}},{"annotations":[],"default":{"kind":"just","value":7},"name":"expires_in_days","serializedName":"expires_in_days","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Word32"}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+
+export const snCreateInvitationReq: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"CreateInvitationReq"};
+
+export function texprCreateInvitationReq(): ADL.ATypeExpr<CreateInvitationReq> {
+  return {value : {typeRef : {kind: "reference", value : snCreateInvitationReq}, parameters : []}};
+}
+
+export interface CreateInvitationResp_Success {
+  kind: 'success';
+  value: protoapp_db.InvitationId;
+}
+export interface CreateInvitationResp_Email_in_use {
+  kind: 'email_in_use';
+}
+export interface CreateInvitationResp_Invalid_role {
+  kind: 'invalid_role';
+}
+export interface CreateInvitationResp_Invalid_expiry {
+  kind: 'invalid_expiry';
+}
+
+export type CreateInvitationResp = CreateInvitationResp_Success | CreateInvitationResp_Email_in_use | CreateInvitationResp_Invalid_role | CreateInvitationResp_Invalid_expiry;
+
+export interface CreateInvitationRespOpts {
+  success: protoapp_db.InvitationId;
+  email_in_use: null;
+  invalid_role: null;
+  invalid_expiry: null;
+}
+
+export function makeCreateInvitationResp<K extends keyof CreateInvitationRespOpts>(kind: K, value: CreateInvitationRespOpts[K]) { return {kind, value}; }
+
+const CreateInvitationResp_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"CreateInvitationResp","type_":{"kind":"union_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"success","serializedName":"success","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"InvitationId"}}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"A user with the email address already exists\n"}],"default":{"kind":"nothing"},"name":"email_in_use","serializedName":"email_in_use","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}},{"annotations":[],"default":{"kind":"nothing"},"name":"invalid_role","serializedName":"invalid_role","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"expires_in_days is zero or more than the configured maximum\n"}],"default":{"kind":"nothing"},"name":"invalid_expiry","serializedName":"invalid_expiry","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+
+export const snCreateInvitationResp: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"CreateInvitationResp"};
+
+export function texprCreateInvitationResp(): ADL.ATypeExpr<CreateInvitationResp> {
+  return {value : {typeRef : {kind: "reference", value : snCreateInvitationResp}, parameters : []}};
+}
+
+export interface QueryInvitationsReq {
+  page: PageReq;
+  status: (InvitationStatus|null);
+}
+
+export function makeQueryInvitationsReq(
+  input: {
+    page?: PageReq,
+    status?: (InvitationStatus|null),
+  }
+): QueryInvitationsReq {
+  return {
+    page: input.page === undefined ? {offset : 0, limit : 20} : input.page,
+    status: input.status === undefined ? null : input.status,
+  };
+}
+
+const QueryInvitationsReq_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"QueryInvitationsReq","type_":{"kind":"struct_","value":{"fields":[{"annotations":[],"default":{"kind":"just","value":{}},"name":"page","serializedName":"page","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"PageReq"}}}},{"annotations":[],"default":{"kind":"just","value":null},"name":"status","serializedName":"status","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"InvitationStatus"}}}],"typeRef":{"kind":"primitive","value":"Nullable"}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+
+export const snQueryInvitationsReq: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"QueryInvitationsReq"};
+
+export function texprQueryInvitationsReq(): ADL.ATypeExpr<QueryInvitationsReq> {
+  return {value : {typeRef : {kind: "reference", value : snQueryInvitationsReq}, parameters : []}};
+}
+
+export type InvitationStatus = 'pending' | 'used' | 'revoked' | 'expired';
+export const valuesInvitationStatus : InvitationStatus[] = ['pending', 'used', 'revoked', 'expired'];
+
+const InvitationStatus_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"InvitationStatus","type_":{"kind":"union_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"pending","serializedName":"pending","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}},{"annotations":[],"default":{"kind":"nothing"},"name":"used","serializedName":"used","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}},{"annotations":[],"default":{"kind":"nothing"},"name":"revoked","serializedName":"revoked","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}},{"annotations":[],"default":{"kind":"nothing"},"name":"expired","serializedName":"expired","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+
+export const snInvitationStatus: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"InvitationStatus"};
+
+export function texprInvitationStatus(): ADL.ATypeExpr<InvitationStatus> {
+  return {value : {typeRef : {kind: "reference", value : snInvitationStatus}, parameters : []}};
+}
+
+export interface InvitationSummary {
+  email: common_strings.StringNE;
+  roles: protoapp_db.RoleId[];
+  created_by: protoapp_db.AppUserId;
+  created_at: common_time.Instant;
+  expires_at: common_time.Instant;
+  used_at: (common_time.Instant|null);
+  user_id: (protoapp_db.AppUserId|null);
+  revoked_at: (common_time.Instant|null);
+}
+
+export function makeInvitationSummary(
+  input: {
+    email: common_strings.StringNE,
+    roles: protoapp_db.RoleId[],
+    created_by: protoapp_db.AppUserId,
+    created_at: common_time.Instant,
+    expires_at: common_time.Instant,
+    used_at: (common_time.Instant|null),
+    user_id: (protoapp_db.AppUserId|null),
+    revoked_at: (common_time.Instant|null),
+  }
+): InvitationSummary {
+  return {
+    email: input.email,
+    roles: input.roles,
+    created_by: input.created_by,
+    created_at: input.created_at,
+    expires_at: input.expires_at,
+    used_at: input.used_at,
+    user_id: input.user_id,
+    revoked_at: input.revoked_at,
+  };
+}
+
+const InvitationSummary_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"InvitationSummary","type_":{"kind":"struct_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"email","serializedName":"email","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.strings","name":"StringNE"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"roles","serializedName":"roles","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"RoleId"}}}],"typeRef":{"kind":"primitive","value":"Vector"}}},{"annotations":[],"default":{"kind":"nothing"},"name":"created_by","serializedName":"created_by","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"AppUserId"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"created_at","serializedName":"created_at","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.time","name":"Instant"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"expires_at","serializedName":"expires_at","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.time","name":"Instant"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"used_at","serializedName":"used_at","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.time","name":"Instant"}}}],"typeRef":{"kind":"primitive","value":"Nullable"}}},{"annotations":[],"default":{"kind":"nothing"},"name":"user_id","serializedName":"user_id","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"AppUserId"}}}],"typeRef":{"kind":"primitive","value":"Nullable"}}},{"annotations":[],"default":{"kind":"nothing"},"name":"revoked_at","serializedName":"revoked_at","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.time","name":"Instant"}}}],"typeRef":{"kind":"primitive","value":"Nullable"}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+
+export const snInvitationSummary: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"InvitationSummary"};
+
+export function texprInvitationSummary(): ADL.ATypeExpr<InvitationSummary> {
+  return {value : {typeRef : {kind: "reference", value : snInvitationSummary}, parameters : []}};
+}
+
+export type InvitationSummaryWithId = WithId<protoapp_db.InvitationId, InvitationSummary>;
+
+const InvitationSummaryWithId_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"InvitationSummaryWithId","type_":{"kind":"type_","value":{"typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"InvitationId"}}},{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"InvitationSummary"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"WithId"}}},"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+
+export const snInvitationSummaryWithId: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"InvitationSummaryWithId"};
+
+export function texprInvitationSummaryWithId(): ADL.ATypeExpr<InvitationSummaryWithId> {
+  return {value : {typeRef : {kind: "reference", value : snInvitationSummaryWithId}, parameters : []}};
+}
+
+export interface SessionSummary {
+  user_agent: (string|null);
+  client_ip: (string|null);
+  created_at: common_time.Instant;
+  last_refreshed_at: common_time.Instant;
+  /**
+   * True for the session making the request
+   */
+  current: boolean;
+}
+
+export function makeSessionSummary(
+  input: {
+    user_agent: (string|null),
+    client_ip: (string|null),
+    created_at: common_time.Instant,
+    last_refreshed_at: common_time.Instant,
+    current: boolean,
+  }
+): SessionSummary {
+  return {
+    user_agent: input.user_agent,
+    client_ip: input.client_ip,
+    created_at: input.created_at,
+    last_refreshed_at: input.last_refreshed_at,
+    current: input.current,
+  };
+}
+
+const SessionSummary_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"SessionSummary","type_":{"kind":"struct_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"user_agent","serializedName":"user_agent","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"primitive","value":"String"}}],"typeRef":{"kind":"primitive","value":"Nullable"}}},{"annotations":[],"default":{"kind":"nothing"},"name":"client_ip","serializedName":"client_ip","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"primitive","value":"String"}}],"typeRef":{"kind":"primitive","value":"Nullable"}}},{"annotations":[],"default":{"kind":"nothing"},"name":"created_at","serializedName":"created_at","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.time","name":"Instant"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"last_refreshed_at","serializedName":"last_refreshed_at","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.time","name":"Instant"}}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"True for the session making the request\n"}],"default":{"kind":"nothing"},"name":"current","serializedName":"current","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Bool"}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+
+export const snSessionSummary: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"SessionSummary"};
+
+export function texprSessionSummary(): ADL.ATypeExpr<SessionSummary> {
+  return {value : {typeRef : {kind: "reference", value : snSessionSummary}, parameters : []}};
+}
+
+export type SessionSummaryWithId = WithId<protoapp_db.SessionId, SessionSummary>;
+
+const SessionSummaryWithId_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"SessionSummaryWithId","type_":{"kind":"type_","value":{"typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"SessionId"}}},{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"SessionSummary"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"WithId"}}},"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+
+export const snSessionSummaryWithId: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"SessionSummaryWithId"};
+
+export function texprSessionSummaryWithId(): ADL.ATypeExpr<SessionSummaryWithId> {
+  return {value : {typeRef : {kind: "reference", value : snSessionSummaryWithId}, parameters : []}};
+}
+
+export interface UpdateProfileReq {
+  fullname: common_strings.StringNE;
+  email: common_strings.EmailAddress;
+  /**
+   * Required to change the email address, as a stolen access token
+   * could otherwise take over the account via a password reset
+   */
+  current_password: (common_strings.Password|null);
+}
+
+export function makeUpdateProfileReq(
+  input: {
+    fullname: common_strings.StringNE,
+    email: common_strings.EmailAddress,
+    current_password?: (common_strings.Password|null),
+  }
+): UpdateProfileReq {
+  return {
+    fullname: input.fullname,
+    email: input.email,
+    current_password: input.current_password === undefined ? null : input.current_password,
+  };
+}
+
+const UpdateProfileReq_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"UpdateProfileReq","type_":{"kind":"struct_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"fullname","serializedName":"fullname","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.strings","name":"StringNE"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"email","serializedName":"email","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.strings","name":"EmailAddress"}}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"Required to change the email address, as a stolen access token\ncould otherwise take over the account via a password reset\n"}],"default":{"kind":"just","value":null},"name":"current_password","serializedName":"current_password","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.strings","name":"Password"}}}],"typeRef":{"kind":"primitive","value":"Nullable"}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+
+export const snUpdateProfileReq: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"UpdateProfileReq"};
+
+export function texprUpdateProfileReq(): ADL.ATypeExpr<UpdateProfileReq> {
+  return {value : {typeRef : {kind: "reference", value : snUpdateProfileReq}, parameters : []}};
+}
+
+export interface UpdateProfileResp_Success {
+  kind: 'success';
+}
+export interface UpdateProfileResp_Email_in_use {
+  kind: 'email_in_use';
+}
+export interface UpdateProfileResp_Invalid_credentials {
+  kind: 'invalid_credentials';
+}
+export interface UpdateProfileResp_Too_many_attempts {
+  kind: 'too_many_attempts';
+  value: number;
+}
+
+export type UpdateProfileResp = UpdateProfileResp_Success | UpdateProfileResp_Email_in_use | UpdateProfileResp_Invalid_credentials | UpdateProfileResp_Too_many_attempts;
+
+export interface UpdateProfileRespOpts {
+  success: null;
+  email_in_use: null;
+  invalid_credentials: null;
+  too_many_attempts: number;
+}
+
+export function makeUpdateProfileResp<K extends keyof UpdateProfileRespOpts>(kind: K, value: UpdateProfileRespOpts[K]) { return {kind, value}; }
+
+const UpdateProfileResp_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"UpdateProfileResp","type_":{"kind":"union_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"success","serializedName":"success","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"Another user has the email address\n"}],"default":{"kind":"nothing"},"name":"email_in_use","serializedName":"email_in_use","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"The email address was changed without the correct current password\n"}],"default":{"kind":"nothing"},"name":"invalid_credentials","serializedName":"invalid_credentials","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"As for `LoginResp.too_many_attempts`. Wrong current passwords count\nas failed logins.\n"}],"default":{"kind":"nothing"},"name":"too_many_attempts","serializedName":"too_many_attempts","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Word32"}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+
+export const snUpdateProfileResp: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"UpdateProfileResp"};
+
+export function texprUpdateProfileResp(): ADL.ATypeExpr<UpdateProfileResp> {
+  return {value : {typeRef : {kind: "reference", value : snUpdateProfileResp}, parameters : []}};
+}
+
+export interface ChangePasswordReq {
+  current_password: common_strings.Password;
+  new_password: common_strings.Password;
+}
+
+export function makeChangePasswordReq(
+  input: {
+    current_password: common_strings.Password,
+    new_password: common_strings.Password,
+  }
+): ChangePasswordReq {
+  return {
+    current_password: input.current_password,
+    new_password: input.new_password,
+  };
+}
+
+const ChangePasswordReq_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"ChangePasswordReq","type_":{"kind":"struct_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"current_password","serializedName":"current_password","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.strings","name":"Password"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"new_password","serializedName":"new_password","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.strings","name":"Password"}}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+
+export const snChangePasswordReq: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"ChangePasswordReq"};
+
+export function texprChangePasswordReq(): ADL.ATypeExpr<ChangePasswordReq> {
+  return {value : {typeRef : {kind: "reference", value : snChangePasswordReq}, parameters : []}};
+}
+
+export interface ChangePasswordResp_Success {
+  kind: 'success';
+}
+export interface ChangePasswordResp_Invalid_credentials {
+  kind: 'invalid_credentials';
+}
+export interface ChangePasswordResp_Weak_password {
+  kind: 'weak_password';
+  value: PasswordViolation[];
+}
+export interface ChangePasswordResp_Too_many_attempts {
+  kind: 'too_many_attempts';
+  value: number;
+}
+
+export type ChangePasswordResp = ChangePasswordResp_Success | ChangePasswordResp_Invalid_credentials | ChangePasswordResp_Weak_password | ChangePasswordResp_Too_many_attempts;
+
+export interface ChangePasswordRespOpts {
+  success: null;
+  invalid_credentials: null;
+  weak_password: PasswordViolation[];
+  too_many_attempts: number;
+}
+
+export function makeChangePasswordResp<K extends keyof ChangePasswordRespOpts>(kind: K, value: ChangePasswordRespOpts[K]) { return {kind, value}; }
+
+const ChangePasswordResp_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"ChangePasswordResp","type_":{"kind":"union_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"success","serializedName":"success","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}},{"annotations":[],"default":{"kind":"nothing"},"name":"invalid_credentials","serializedName":"invalid_credentials","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}},{"annotations":[],"default":{"kind":"nothing"},"name":"weak_password","serializedName":"weak_password","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"PasswordViolation"}}}],"typeRef":{"kind":"primitive","value":"Vector"}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"As for `LoginResp.too_many_attempts`. Wrong current passwords count\nas failed logins.\n"}],"default":{"kind":"nothing"},"name":"too_many_attempts","serializedName":"too_many_attempts","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Word32"}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+
+export const snChangePasswordResp: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"ChangePasswordResp"};
+
+export function texprChangePasswordResp(): ADL.ATypeExpr<ChangePasswordResp> {
+  return {value : {typeRef : {kind: "reference", value : snChangePasswordResp}, parameters : []}};
+}
+
+export interface UserDetails {
+  fullname: common_strings.StringNE;
+  email: common_strings.EmailAddress;
+  password: common_strings.Password;
+}
+
+export function makeUserDetails(
+  input: {
+    fullname: common_strings.StringNE,
+    email: common_strings.EmailAddress,
+    password: common_strings.Password,
+  }
+): UserDetails {
+  return {
+    fullname: input.fullname,
+    email: input.email,
+    password: input.password,
+  };
+}
+
+const UserDetails_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"UserDetails","type_":{"kind":"struct_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"fullname","serializedName":"fullname","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.strings","name":"StringNE"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"email","serializedName":"email","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.strings","name":"EmailAddress"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"password","serializedName":"password","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.strings","name":"Password"}}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+
+export const snUserDetails: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"UserDetails"};
+
+export function texprUserDetails(): ADL.ATypeExpr<UserDetails> {
+  return {value : {typeRef : {kind: "reference", value : snUserDetails}, parameters : []}};
+}
+
+export interface CreateUserResp_Success {
+  kind: 'success';
+  value: protoapp_db.AppUserId;
+}
+export interface CreateUserResp_Weak_password {
+  kind: 'weak_password';
+  value: PasswordViolation[];
+}
+
+export type CreateUserResp = CreateUserResp_Success | CreateUserResp_Weak_password;
+
+export interface CreateUserRespOpts {
+  success: protoapp_db.AppUserId;
+  weak_password: PasswordViolation[];
+}
+
+export function makeCreateUserResp<K extends keyof CreateUserRespOpts>(kind: K, value: CreateUserRespOpts[K]) { return {kind, value}; }
+
+const CreateUserResp_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"CreateUserResp","type_":{"kind":"union_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"success","serializedName":"success","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"AppUserId"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"weak_password","serializedName":"weak_password","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"PasswordViolation"}}}],"typeRef":{"kind":"primitive","value":"Vector"}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+
+export const snCreateUserResp: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"CreateUserResp"};
+
+export function texprCreateUserResp(): ADL.ATypeExpr<CreateUserResp> {
+  return {value : {typeRef : {kind: "reference", value : snCreateUserResp}, parameters : []}};
+}
+
+export interface UpdateUserResp_Success {
+  kind: 'success';
+}
+export interface UpdateUserResp_Weak_password {
+  kind: 'weak_password';
+  value: PasswordViolation[];
+}
+
+export type UpdateUserResp = UpdateUserResp_Success | UpdateUserResp_Weak_password;
+
+export interface UpdateUserRespOpts {
+  success: null;
+  weak_password: PasswordViolation[];
+}
+
+export function makeUpdateUserResp<K extends keyof UpdateUserRespOpts>(kind: K, value: UpdateUserRespOpts[K]) { return {kind, value}; }
+
+const UpdateUserResp_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"UpdateUserResp","type_":{"kind":"union_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"success","serializedName":"success","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}},{"annotations":[],"default":{"kind":"nothing"},"name":"weak_password","serializedName":"weak_password","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"PasswordViolation"}}}],"typeRef":{"kind":"primitive","value":"Vector"}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+
+export const snUpdateUserResp: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"UpdateUserResp"};
+
+export function texprUpdateUserResp(): ADL.ATypeExpr<UpdateUserResp> {
+  return {value : {typeRef : {kind: "reference", value : snUpdateUserResp}, parameters : []}};
+}
+
+export interface SetUserRolesReq {
+  user_id: protoapp_db.AppUserId;
+  roles: protoapp_db.RoleId[];
+}
+
+export function makeSetUserRolesReq(
+  input: {
+    user_id: protoapp_db.AppUserId,
+    roles: protoapp_db.RoleId[],
+  }
+): SetUserRolesReq {
+  return {
+    user_id: input.user_id,
+    roles: input.roles,
+  };
+}
+
+const SetUserRolesReq_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"SetUserRolesReq","type_":{"kind":"struct_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"user_id","serializedName":"user_id","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"AppUserId"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"roles","serializedName":"roles","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"RoleId"}}}],"typeRef":{"kind":"primitive","value":"Vector"}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+
+export const snSetUserRolesReq: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"SetUserRolesReq"};
+
+export function texprSetUserRolesReq(): ADL.ATypeExpr<SetUserRolesReq> {
+  return {value : {typeRef : {kind: "reference", value : snSetUserRolesReq}, parameters : []}};
+}
+
+export interface CreateOrganisationReq {
+  name: common_strings.StringNE;
+}
+
+export function makeCreateOrganisationReq(
+  input: {
+    name: common_strings.StringNE,
+  }
+): CreateOrganisationReq {
+  return {
+    name: input.name,
+  };
+}
+
+const CreateOrganisationReq_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"CreateOrganisationReq","type_":{"kind":"struct_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"name","serializedName":"name","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.strings","name":"StringNE"}}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+
+export const snCreateOrganisationReq: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"CreateOrganisationReq"};
+
+export function texprCreateOrganisationReq(): ADL.ATypeExpr<CreateOrganisationReq> {
+  return {value : {typeRef : {kind: "reference", value : snCreateOrganisationReq}, parameters : []}};
+}
+
+export interface CreateOrganisationResp_Success {
+  kind: 'success';
+  value: protoapp_db.OrganisationId;
+}
+export interface CreateOrganisationResp_Name_in_use {
+  kind: 'name_in_use';
+}
+
+export type CreateOrganisationResp = CreateOrganisationResp_Success | CreateOrganisationResp_Name_in_use;
+
+export interface CreateOrganisationRespOpts {
+  success: protoapp_db.OrganisationId;
+  name_in_use: null;
+}
+
+export function makeCreateOrganisationResp<K extends keyof CreateOrganisationRespOpts>(kind: K, value: CreateOrganisationRespOpts[K]) { return {kind, value}; }
+
+const CreateOrganisationResp_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"CreateOrganisationResp","type_":{"kind":"union_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"success","serializedName":"success","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"OrganisationId"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"name_in_use","serializedName":"name_in_use","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+
+export const snCreateOrganisationResp: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"CreateOrganisationResp"};
+
+export function texprCreateOrganisationResp(): ADL.ATypeExpr<CreateOrganisationResp> {
+  return {value : {typeRef : {kind: "reference", value : snCreateOrganisationResp}, parameters : []}};
+}
+
+export interface QueryOrganisationsReq {
+  page: PageReq;
+}
+
+export function makeQueryOrganisationsReq(
+  input: {
+    page?: PageReq,
+  }
+): QueryOrganisationsReq {
+  return {
+    page: input.page === undefined ? {offset : 0, limit : 20} : input.page,
+  };
+}
+
+const QueryOrganisationsReq_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"QueryOrganisationsReq","type_":{"kind":"struct_","value":{"fields":[{"annotations":[],"default":{"kind":"just","value":{}},"name":"page","serializedName":"page","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"PageReq"}}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+
+export const snQueryOrganisationsReq: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"QueryOrganisationsReq"};
+
+export function texprQueryOrganisationsReq(): ADL.ATypeExpr<QueryOrganisationsReq> {
+  return {value : {typeRef : {kind: "reference", value : snQueryOrganisationsReq}, parameters : []}};
+}
+
+export type OrganisationWithId = WithId<protoapp_db.OrganisationId, protoapp_db.Organisation>;
+
+const OrganisationWithId_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"OrganisationWithId","type_":{"kind":"type_","value":{"typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"OrganisationId"}}},{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"Organisation"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"WithId"}}},"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+
+export const snOrganisationWithId: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"OrganisationWithId"};
+
+export function texprOrganisationWithId(): ADL.ATypeExpr<OrganisationWithId> {
+  return {value : {typeRef : {kind: "reference", value : snOrganisationWithId}, parameters : []}};
+}
+
+export interface SetOrganisationMemberReq {
+  organisation_id: protoapp_db.OrganisationId;
+  user_id: protoapp_db.AppUserId;
+  roles: protoapp_db.RoleId[];
+}
+
+export function makeSetOrganisationMemberReq(
+  input: {
+    organisation_id: protoapp_db.OrganisationId,
+    user_id: protoapp_db.AppUserId,
+    roles?: protoapp_db.RoleId[],
+  }
+): SetOrganisationMemberReq {
+  return {
+    organisation_id: input.organisation_id,
+    user_id: input.user_id,
+    roles: input.roles === undefined ? [] : input.roles,
+  };
+}
+
+const SetOrganisationMemberReq_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"SetOrganisationMemberReq","type_":{"kind":"struct_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"organisation_id","serializedName":"organisation_id","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"OrganisationId"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"user_id","serializedName":"user_id","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"AppUserId"}}}},{"annotations":[],"default":{"kind":"just","value":[]},"name":"roles","serializedName":"roles","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"RoleId"}}}],"typeRef":{"kind":"primitive","value":"Vector"}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+
+export const snSetOrganisationMemberReq: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"SetOrganisationMemberReq"};
+
+export function texprSetOrganisationMemberReq(): ADL.ATypeExpr<SetOrganisationMemberReq> {
+  return {value : {typeRef : {kind: "reference", value : snSetOrganisationMemberReq}, parameters : []}};
+}
+
+export type SetOrganisationMemberResp = 'success' | 'invalid_organisation' | 'invalid_user' | 'invalid_role';
+export const valuesSetOrganisationMemberResp : SetOrganisationMemberResp[] = ['success', 'invalid_organisation', 'invalid_user', 'invalid_role'];
+
+const SetOrganisationMemberResp_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"SetOrganisationMemberResp","type_":{"kind":"union_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"success","serializedName":"success","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}},{"annotations":[],"default":{"kind":"nothing"},"name":"invalid_organisation","serializedName":"invalid_organisation","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}},{"annotations":[],"default":{"kind":"nothing"},"name":"invalid_user","serializedName":"invalid_user","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}},{"annotations":[],"default":{"kind":"nothing"},"name":"invalid_role","serializedName":"invalid_role","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+
+export const snSetOrganisationMemberResp: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"SetOrganisationMemberResp"};
+
+export function texprSetOrganisationMemberResp(): ADL.ATypeExpr<SetOrganisationMemberResp> {
+  return {value : {typeRef : {kind: "reference", value : snSetOrganisationMemberResp}, parameters : []}};
+}
+
+export interface RemoveOrganisationMemberReq {
+  organisation_id: protoapp_db.OrganisationId;
+  user_id: protoapp_db.AppUserId;
+}
+
+export function makeRemoveOrganisationMemberReq(
+  input: {
+    organisation_id: protoapp_db.OrganisationId,
+    user_id: protoapp_db.AppUserId,
+  }
+): RemoveOrganisationMemberReq {
+  return {
+    organisation_id: input.organisation_id,
+    user_id: input.user_id,
+  };
+}
+
+const RemoveOrganisationMemberReq_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"RemoveOrganisationMemberReq","type_":{"kind":"struct_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"organisation_id","serializedName":"organisation_id","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"OrganisationId"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"user_id","serializedName":"user_id","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"AppUserId"}}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+
+export const snRemoveOrganisationMemberReq: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"RemoveOrganisationMemberReq"};
+
+export function texprRemoveOrganisationMemberReq(): ADL.ATypeExpr<RemoveOrganisationMemberReq> {
+  return {value : {typeRef : {kind: "reference", value : snRemoveOrganisationMemberReq}, parameters : []}};
+}
+
+export interface SwitchOrganisationReq {
+  organisation_id: (protoapp_db.OrganisationId|null);
+}
+
+export function makeSwitchOrganisationReq(
+  input: {
+    organisation_id: (protoapp_db.OrganisationId|null),
+  }
+): SwitchOrganisationReq {
+  return {
+    organisation_id: input.organisation_id,
+  };
+}
+
+const SwitchOrganisationReq_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"SwitchOrganisationReq","type_":{"kind":"struct_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"organisation_id","serializedName":"organisation_id","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"OrganisationId"}}}],"typeRef":{"kind":"primitive","value":"Nullable"}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+
+export const snSwitchOrganisationReq: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"SwitchOrganisationReq"};
+
+export function texprSwitchOrganisationReq(): ADL.ATypeExpr<SwitchOrganisationReq> {
+  return {value : {typeRef : {kind: "reference", value : snSwitchOrganisationReq}, parameters : []}};
+}
+
+export interface SwitchOrganisationResp_Access_jwt {
+  kind: 'access_jwt';
+  value: common_strings.StringNE;
+}
+export interface SwitchOrganisationResp_Invalid_organisation {
+  kind: 'invalid_organisation';
+}
+
+export type SwitchOrganisationResp = SwitchOrganisationResp_Access_jwt | SwitchOrganisationResp_Invalid_organisation;
+
+export interface SwitchOrganisationRespOpts {
+  access_jwt: common_strings.StringNE;
+  invalid_organisation: null;
+}
+
+export function makeSwitchOrganisationResp<K extends keyof SwitchOrganisationRespOpts>(kind: K, value: SwitchOrganisationRespOpts[K]) { return {kind, value}; }
+
+const SwitchOrganisationResp_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"SwitchOrganisationResp","type_":{"kind":"union_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"access_jwt","serializedName":"access_jwt","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.strings","name":"StringNE"}}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"The organisation doesn't exist, or the user isn't a member, or\nisn't an admin when switching to none\n"}],"default":{"kind":"nothing"},"name":"invalid_organisation","serializedName":"invalid_organisation","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+
+export const snSwitchOrganisationResp: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"SwitchOrganisationResp"};
+
+export function texprSwitchOrganisationResp(): ADL.ATypeExpr<SwitchOrganisationResp> {
+  return {value : {typeRef : {kind: "reference", value : snSwitchOrganisationResp}, parameters : []}};
+}
+
+export type DeactivateUserResp = 'success' | 'invalid_user' | 'cannot_deactivate_self';
+export const valuesDeactivateUserResp : DeactivateUserResp[] = ['success', 'invalid_user', 'cannot_deactivate_self'];
+
+const DeactivateUserResp_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"DeactivateUserResp","type_":{"kind":"union_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"success","serializedName":"success","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}},{"annotations":[],"default":{"kind":"nothing"},"name":"invalid_user","serializedName":"invalid_user","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"Admins can't deactivate themselves\n"}],"default":{"kind":"nothing"},"name":"cannot_deactivate_self","serializedName":"cannot_deactivate_self","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+
+export const snDeactivateUserResp: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"DeactivateUserResp"};
+
+export function texprDeactivateUserResp(): ADL.ATypeExpr<DeactivateUserResp> {
+  return {value : {typeRef : {kind: "reference", value : snDeactivateUserResp}, parameters : []}};
+}
+
+export type ReactivateUserResp = 'success' | 'invalid_user';
+export const valuesReactivateUserResp : ReactivateUserResp[] = ['success', 'invalid_user'];
+
+const ReactivateUserResp_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"ReactivateUserResp","type_":{"kind":"union_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"success","serializedName":"success","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}},{"annotations":[],"default":{"kind":"nothing"},"name":"invalid_user","serializedName":"invalid_user","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+
+export const snReactivateUserResp: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"ReactivateUserResp"};
+
+export function texprReactivateUserResp(): ADL.ATypeExpr<ReactivateUserResp> {
+  return {value : {typeRef : {kind: "reference", value : snReactivateUserResp}, parameters : []}};
+}
+
+export type SetUserRolesResp = 'success' | 'invalid_user' | 'invalid_role';
+export const valuesSetUserRolesResp : SetUserRolesResp[] = ['success', 'invalid_user', 'invalid_role'];
+
+const SetUserRolesResp_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"SetUserRolesResp","type_":{"kind":"union_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"success","serializedName":"success","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}},{"annotations":[],"default":{"kind":"nothing"},"name":"invalid_user","serializedName":"invalid_user","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}},{"annotations":[],"default":{"kind":"nothing"},"name":"invalid_role","serializedName":"invalid_role","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+
+export const snSetUserRolesResp: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"SetUserRolesResp"};
+
+export function texprSetUserRolesResp(): ADL.ATypeExpr<SetUserRolesResp> {
+  return {value : {typeRef : {kind: "reference", value : snSetUserRolesResp}, parameters : []}};
+}
+
+export interface CreateRoleReq {
+  name: common_strings.StringNE;
+  description: string;
+  permissions: common_strings.StringNE[];
+}
+
+export function makeCreateRoleReq(
+  input: {
+    name: common_strings.StringNE,
+    description?: string,
+    permissions?: common_strings.StringNE[],
+  }
+): CreateRoleReq {
+  return {
+    name: input.name,
+    description: input.description === undefined ? "" : input.description,
+    permissions: input.permissions === undefined ? [] : input.permissions,
+  };
+}
+
+const CreateRoleReq_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"CreateRoleReq","type_":{"kind":"struct_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"name","serializedName":"name","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.strings","name":"StringNE"}}}},{"annotations":[],"default":{"kind":"just","value":""},"name":"description","serializedName":"description","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"String"}}},{"annotations":[],"default":{"kind":"just","value":[]},"name":"permissions","serializedName":"permissions","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.strings","name":"StringNE"}}}],"typeRef":{"kind":"primitive","value":"Vector"}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+
+export const snCreateRoleReq: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"CreateRoleReq"};
+
+export function texprCreateRoleReq(): ADL.ATypeExpr<CreateRoleReq> {
+  return {value : {typeRef : {kind: "reference", value : snCreateRoleReq}, parameters : []}};
+}
+
+export interface CreateRoleResp_Success {
+  kind: 'success';
+  value: protoapp_db.RoleId;
+}
+export interface CreateRoleResp_Name_in_use {
+  kind: 'name_in_use';
+}
+
+export type CreateRoleResp = CreateRoleResp_Success | CreateRoleResp_Name_in_use;
+
+export interface CreateRoleRespOpts {
+  success: protoapp_db.RoleId;
+  name_in_use: null;
+}
+
+export function makeCreateRoleResp<K extends keyof CreateRoleRespOpts>(kind: K, value: CreateRoleRespOpts[K]) { return {kind, value}; }
+
+const CreateRoleResp_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"CreateRoleResp","type_":{"kind":"union_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"success","serializedName":"success","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"RoleId"}}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"Another role has the name, or it is the reserved \"user\" role\n"}],"default":{"kind":"nothing"},"name":"name_in_use","serializedName":"name_in_use","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+
+export const snCreateRoleResp: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"CreateRoleResp"};
+
+export function texprCreateRoleResp(): ADL.ATypeExpr<CreateRoleResp> {
+  return {value : {typeRef : {kind: "reference", value : snCreateRoleResp}, parameters : []}};
+}
+
+export interface QueryRolesReq {
+  page: PageReq;
+}
+
+export function makeQueryRolesReq(
+  input: {
+    page?: PageReq,
+  }
+): QueryRolesReq {
+  return {
+    page: input.page === undefined ? {offset : 0, limit : 20} : input.page,
+  };
+}
+
+const QueryRolesReq_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"QueryRolesReq","type_":{"kind":"struct_","value":{"fields":[{"annotations":[],"default":{"kind":"just","value":{}},"name":"page","serializedName":"page","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"PageReq"}}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+
+export const snQueryRolesReq: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"QueryRolesReq"};
+
+export function texprQueryRolesReq(): ADL.ATypeExpr<QueryRolesReq> {
+  return {value : {typeRef : {kind: "reference", value : snQueryRolesReq}, parameters : []}};
+}
+
+export interface RoleDetails {
+  name: common_strings.StringNE;
+  description: string;
+  permissions: common_strings.StringNE[];
+}
+
+export function makeRoleDetails(
+  input: {
+    name: common_strings.StringNE,
+    description: string,
+    permissions: common_strings.StringNE[],
+  }
+): RoleDetails {
+  return {
+    name: input.name,
+    description: input.description,
+    permissions: input.permissions,
+  };
+}
+
+const RoleDetails_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"RoleDetails","type_":{"kind":"struct_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"name","serializedName":"name","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.strings","name":"StringNE"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"description","serializedName":"description","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"String"}}},{"annotations":[],"default":{"kind":"nothing"},"name":"permissions","serializedName":"permissions","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.strings","name":"StringNE"}}}],"typeRef":{"kind":"primitive","value":"Vector"}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+
+export const snRoleDetails: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"RoleDetails"};
+
+export function texprRoleDetails(): ADL.ATypeExpr<RoleDetails> {
+  return {value : {typeRef : {kind: "reference", value : snRoleDetails}, parameters : []}};
+}
+
+export type RoleDetailsWithId = WithId<protoapp_db.RoleId, RoleDetails>;
+
+const RoleDetailsWithId_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"RoleDetailsWithId","type_":{"kind":"type_","value":{"typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"RoleId"}}},{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"RoleDetails"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.apis.ui","name":"WithId"}}},"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+
+export const snRoleDetailsWithId: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"RoleDetailsWithId"};
+
+export function texprRoleDetailsWithId(): ADL.ATypeExpr<RoleDetailsWithId> {
+  return {value : {typeRef : {kind: "reference", value : snRoleDetailsWithId}, parameters : []}};
+}
+
+export interface SetRolePermissionsReq {
+  role_id: protoapp_db.RoleId;
+  permissions: common_strings.StringNE[];
+}
+
+export function makeSetRolePermissionsReq(
+  input: {
+    role_id: protoapp_db.RoleId,
+    permissions: common_strings.StringNE[],
+  }
+): SetRolePermissionsReq {
+  return {
+    role_id: input.role_id,
+    permissions: input.permissions,
+  };
+}
+
+const SetRolePermissionsReq_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"SetRolePermissionsReq","type_":{"kind":"struct_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"role_id","serializedName":"role_id","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"RoleId"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"permissions","serializedName":"permissions","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.strings","name":"StringNE"}}}],"typeRef":{"kind":"primitive","value":"Vector"}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+
+export const snSetRolePermissionsReq: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"SetRolePermissionsReq"};
+
+export function texprSetRolePermissionsReq(): ADL.ATypeExpr<SetRolePermissionsReq> {
+  return {value : {typeRef : {kind: "reference", value : snSetRolePermissionsReq}, parameters : []}};
+}
+
+export type SetRolePermissionsResp = 'success' | 'invalid_role';
+export const valuesSetRolePermissionsResp : SetRolePermissionsResp[] = ['success', 'invalid_role'];
+
+const SetRolePermissionsResp_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"SetRolePermissionsResp","type_":{"kind":"union_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"success","serializedName":"success","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}},{"annotations":[],"default":{"kind":"nothing"},"name":"invalid_role","serializedName":"invalid_role","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+
+export const snSetRolePermissionsResp: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"SetRolePermissionsResp"};
+
+export function texprSetRolePermissionsResp(): ADL.ATypeExpr<SetRolePermissionsResp> {
+  return {value : {typeRef : {kind: "reference", value : snSetRolePermissionsResp}, parameters : []}};
+}
+
+export type DeleteRoleResp = 'success' | 'invalid_role' | 'builtin_role';
+export const valuesDeleteRoleResp : DeleteRoleResp[] = ['success', 'invalid_role', 'builtin_role'];
+
+const DeleteRoleResp_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"DeleteRoleResp","type_":{"kind":"union_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"success","serializedName":"success","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}},{"annotations":[],"default":{"kind":"nothing"},"name":"invalid_role","serializedName":"invalid_role","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}},{"annotations":[{"key":{"moduleName":"sys.annotations","name":"Doc"},"value":"The admin role can't be deleted, as admin only endpoints require it\n"}],"default":{"kind":"nothing"},"name":"builtin_role","serializedName":"builtin_role","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.apis.ui"};
+
+export const snDeleteRoleResp: ADL.ScopedName = {moduleName:"protoapp.apis.ui", name:"DeleteRoleResp"};
+
+export function texprDeleteRoleResp(): ADL.ATypeExpr<DeleteRoleResp> {
+  return {value : {typeRef : {kind: "reference", value : snDeleteRoleResp}, parameters : []}};
 }
 
 export interface WithId<I, T> {
@@ -484,19 +2812,97 @@ export function texprWithId<I, T>(texprI : ADL.ATypeExpr<I>, texprT : ADL.ATypeE
 
 export const _AST_MAP: { [key: string]: ADL.ScopedDecl } = {
   "protoapp.apis.ui.ApiRequests" : ApiRequests_AST,
+  "protoapp.apis.ui.Jwks" : Jwks_AST,
   "protoapp.apis.ui.LoginReq" : LoginReq_AST,
   "protoapp.apis.ui.LoginResp" : LoginResp_AST,
+  "protoapp.apis.ui.LoginTotpReq" : LoginTotpReq_AST,
+  "protoapp.apis.ui.LoginTotpResp" : LoginTotpResp_AST,
+  "protoapp.apis.ui.OidcLoginStartResp" : OidcLoginStartResp_AST,
+  "protoapp.apis.ui.OidcLoginReq" : OidcLoginReq_AST,
+  "protoapp.apis.ui.OidcLoginResp" : OidcLoginResp_AST,
   "protoapp.apis.ui.RefreshReq" : RefreshReq_AST,
   "protoapp.apis.ui.RefreshResp" : RefreshResp_AST,
+  "protoapp.apis.ui.LogoutReq" : LogoutReq_AST,
   "protoapp.apis.ui.LoginTokens" : LoginTokens_AST,
+  "protoapp.apis.ui.RequestPasswordResetReq" : RequestPasswordResetReq_AST,
+  "protoapp.apis.ui.ResetPasswordReq" : ResetPasswordReq_AST,
+  "protoapp.apis.ui.ResetPasswordResp" : ResetPasswordResp_AST,
+  "protoapp.apis.ui.PasswordViolation" : PasswordViolation_AST,
+  "protoapp.apis.ui.VerifyEmailReq" : VerifyEmailReq_AST,
+  "protoapp.apis.ui.VerifyEmailResp" : VerifyEmailResp_AST,
+  "protoapp.apis.ui.SignupReq" : SignupReq_AST,
+  "protoapp.apis.ui.SignupResp" : SignupResp_AST,
+  "protoapp.apis.ui.ResendVerificationEmailReq" : ResendVerificationEmailReq_AST,
+  "protoapp.apis.ui.BeginTotpEnrolmentResp" : BeginTotpEnrolmentResp_AST,
+  "protoapp.apis.ui.TotpEnrolment" : TotpEnrolment_AST,
+  "protoapp.apis.ui.ConfirmTotpEnrolmentReq" : ConfirmTotpEnrolmentReq_AST,
+  "protoapp.apis.ui.ConfirmTotpEnrolmentResp" : ConfirmTotpEnrolmentResp_AST,
   "protoapp.apis.ui.NewMessageReq" : NewMessageReq_AST,
   "protoapp.apis.ui.RecentMessagesReq" : RecentMessagesReq_AST,
+  "protoapp.apis.ui.ReplyToMessageReq" : ReplyToMessageReq_AST,
+  "protoapp.apis.ui.ReplyToMessageResp" : ReplyToMessageResp_AST,
+  "protoapp.apis.ui.MessageThreadReq" : MessageThreadReq_AST,
+  "protoapp.apis.ui.MessageThreadResp" : MessageThreadResp_AST,
+  "protoapp.apis.ui.MessageThread" : MessageThread_AST,
+  "protoapp.apis.ui.MessageReactionReq" : MessageReactionReq_AST,
+  "protoapp.apis.ui.AddReactionResp" : AddReactionResp_AST,
+  "protoapp.apis.ui.EditMessageReq" : EditMessageReq_AST,
+  "protoapp.apis.ui.EditMessageResp" : EditMessageResp_AST,
+  "protoapp.apis.ui.DeleteMessageResp" : DeleteMessageResp_AST,
+  "protoapp.apis.ui.MessageRevisionsResp" : MessageRevisionsResp_AST,
   "protoapp.apis.ui.PageReq" : PageReq_AST,
   "protoapp.apis.ui.Paginated" : Paginated_AST,
   "protoapp.apis.ui.Message" : Message_AST,
+  "protoapp.apis.ui.ReactionCount" : ReactionCount_AST,
   "protoapp.apis.ui.QueryUsersReq" : QueryUsersReq_AST,
   "protoapp.apis.ui.User" : User_AST,
   "protoapp.apis.ui.UserWithId" : UserWithId_AST,
+  "protoapp.apis.ui.ImpersonateUserResp" : ImpersonateUserResp_AST,
+  "protoapp.apis.ui.JwtFailureCounts" : JwtFailureCounts_AST,
+  "protoapp.apis.ui.JwtFailureCount" : JwtFailureCount_AST,
+  "protoapp.apis.ui.QueryAuditLogReq" : QueryAuditLogReq_AST,
+  "protoapp.apis.ui.AuditLogEntry" : AuditLogEntry_AST,
+  "protoapp.apis.ui.CreateApiKeyReq" : CreateApiKeyReq_AST,
+  "protoapp.apis.ui.CreateApiKeyResp" : CreateApiKeyResp_AST,
+  "protoapp.apis.ui.NewApiKey" : NewApiKey_AST,
+  "protoapp.apis.ui.QueryApiKeysReq" : QueryApiKeysReq_AST,
+  "protoapp.apis.ui.ApiKeySummary" : ApiKeySummary_AST,
+  "protoapp.apis.ui.ApiKeySummaryWithId" : ApiKeySummaryWithId_AST,
+  "protoapp.apis.ui.CreateInvitationReq" : CreateInvitationReq_AST,
+  "protoapp.apis.ui.CreateInvitationResp" : CreateInvitationResp_AST,
+  "protoapp.apis.ui.QueryInvitationsReq" : QueryInvitationsReq_AST,
+  "protoapp.apis.ui.InvitationStatus" : InvitationStatus_AST,
+  "protoapp.apis.ui.InvitationSummary" : InvitationSummary_AST,
+  "protoapp.apis.ui.InvitationSummaryWithId" : InvitationSummaryWithId_AST,
+  "protoapp.apis.ui.SessionSummary" : SessionSummary_AST,
+  "protoapp.apis.ui.SessionSummaryWithId" : SessionSummaryWithId_AST,
+  "protoapp.apis.ui.UpdateProfileReq" : UpdateProfileReq_AST,
+  "protoapp.apis.ui.UpdateProfileResp" : UpdateProfileResp_AST,
+  "protoapp.apis.ui.ChangePasswordReq" : ChangePasswordReq_AST,
+  "protoapp.apis.ui.ChangePasswordResp" : ChangePasswordResp_AST,
   "protoapp.apis.ui.UserDetails" : UserDetails_AST,
+  "protoapp.apis.ui.CreateUserResp" : CreateUserResp_AST,
+  "protoapp.apis.ui.UpdateUserResp" : UpdateUserResp_AST,
+  "protoapp.apis.ui.SetUserRolesReq" : SetUserRolesReq_AST,
+  "protoapp.apis.ui.CreateOrganisationReq" : CreateOrganisationReq_AST,
+  "protoapp.apis.ui.CreateOrganisationResp" : CreateOrganisationResp_AST,
+  "protoapp.apis.ui.QueryOrganisationsReq" : QueryOrganisationsReq_AST,
+  "protoapp.apis.ui.OrganisationWithId" : OrganisationWithId_AST,
+  "protoapp.apis.ui.SetOrganisationMemberReq" : SetOrganisationMemberReq_AST,
+  "protoapp.apis.ui.SetOrganisationMemberResp" : SetOrganisationMemberResp_AST,
+  "protoapp.apis.ui.RemoveOrganisationMemberReq" : RemoveOrganisationMemberReq_AST,
+  "protoapp.apis.ui.SwitchOrganisationReq" : SwitchOrganisationReq_AST,
+  "protoapp.apis.ui.SwitchOrganisationResp" : SwitchOrganisationResp_AST,
+  "protoapp.apis.ui.DeactivateUserResp" : DeactivateUserResp_AST,
+  "protoapp.apis.ui.ReactivateUserResp" : ReactivateUserResp_AST,
+  "protoapp.apis.ui.SetUserRolesResp" : SetUserRolesResp_AST,
+  "protoapp.apis.ui.CreateRoleReq" : CreateRoleReq_AST,
+  "protoapp.apis.ui.CreateRoleResp" : CreateRoleResp_AST,
+  "protoapp.apis.ui.QueryRolesReq" : QueryRolesReq_AST,
+  "protoapp.apis.ui.RoleDetails" : RoleDetails_AST,
+  "protoapp.apis.ui.RoleDetailsWithId" : RoleDetailsWithId_AST,
+  "protoapp.apis.ui.SetRolePermissionsReq" : SetRolePermissionsReq_AST,
+  "protoapp.apis.ui.SetRolePermissionsResp" : SetRolePermissionsResp_AST,
+  "protoapp.apis.ui.DeleteRoleResp" : DeleteRoleResp_AST,
   "protoapp.apis.ui.WithId" : WithId_AST
 };
@@ -8,28 +8,34 @@ import * as common_time from './../common/time';
 export interface AppUser {
   fullname: common_strings.StringNE;
   email: common_strings.StringNE;
-  is_admin: boolean;
-  hashed_password: common_strings.StringNE;
+  hashed_password: (common_strings.StringNE|null);
+  verified_at: (common_time.Instant|null);
+  verification_sent_at: (common_time.Instant|null);
+  deactivated_at: (common_time.Instant|null);
 }
 
 export function makeAppUser(
   input: {
     fullname: common_strings.StringNE,
     email: common_strings.StringNE,
-    is_admin: boolean,
-    hashed_password?: common_strings.StringNE,
+    hashed_password?: (common_strings.StringNE|null),
+    verified_at?: (common_time.Instant|null),
+    verification_sent_at?: (common_time.Instant|null),
+    deactivated_at?: (common_time.Instant|null),
   }
 ): AppUser {
   return {
     fullname: input.fullname,
     email: input.email,
-    is_admin: input.is_admin,
-    hashed_password: input.hashed_password === undefined ? "" : input.hashed_password,
+    hashed_password: input.hashed_password === undefined ? null : input.hashed_password,
+    verified_at: input.verified_at === undefined ? null : input.verified_at,
+    verification_sent_at: input.verification_sent_at === undefined ? null : input.verification_sent_at,
+    deactivated_at: input.deactivated_at === undefined ? null : input.deactivated_at,
   };
 }
 
 const AppUser_AST : ADL.ScopedDecl =
-  {"decl":{"annotations":[],"name":"AppUser","type_":{"kind":"struct_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"fullname","serializedName":"fullname","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.strings","name":"StringNE"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"email","serializedName":"email","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.strings","name":"StringNE"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"is_admin","serializedName":"is_admin","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Bool"}}},{"annotations":[],"default":{"kind":"just","value":""},"name":"hashed_password","serializedName":"hashed_password","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.strings","name":"StringNE"}}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.db"};
+  {"decl":{"annotations":[],"name":"AppUser","type_":{"kind":"struct_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"fullname","serializedName":"fullname","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.strings","name":"StringNE"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"email","serializedName":"email","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.strings","name":"StringNE"}}}},{"annotations":[],"default":{"kind":"just","value":null},"name":"hashed_password","serializedName":"hashed_password","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.strings","name":"StringNE"}}}],"typeRef":{"kind":"primitive","value":"Nullable"}}},{"annotations":[],"default":{"kind":"just","value":null},"name":"verified_at","serializedName":"verified_at","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.time","name":"Instant"}}}],"typeRef":{"kind":"primitive","value":"Nullable"}}},{"annotations":[],"default":{"kind":"just","value":null},"name":"verification_sent_at","serializedName":"verification_sent_at","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.time","name":"Instant"}}}],"typeRef":{"kind":"primitive","value":"Nullable"}}},{"annotations":[],"default":{"kind":"just","value":null},"name":"deactivated_at","serializedName":"deactivated_at","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.time","name":"Instant"}}}],"typeRef":{"kind":"primitive","value":"Nullable"}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.db"};
 
 export const snAppUser: ADL.ScopedName = {moduleName:"protoapp.db", name:"AppUser"};
 
@@ -59,10 +65,260 @@ export function texprAppUserId(): ADL.ATypeExpr<AppUserId> {
   return {value : {typeRef : {kind: "reference", value : snAppUserId}, parameters : []}};
 }
 
+export interface Role {
+  name: common_strings.StringNE;
+  description: string;
+}
+
+export function makeRole(
+  input: {
+    name: common_strings.StringNE,
+    description?: string,
+  }
+): Role {
+  return {
+    name: input.name,
+    description: input.description === undefined ? "" : input.description,
+  };
+}
+
+const Role_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"Role","type_":{"kind":"struct_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"name","serializedName":"name","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.strings","name":"StringNE"}}}},{"annotations":[],"default":{"kind":"just","value":""},"name":"description","serializedName":"description","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"String"}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.db"};
+
+export const snRole: ADL.ScopedName = {moduleName:"protoapp.db", name:"Role"};
+
+export function texprRole(): ADL.ATypeExpr<Role> {
+  return {value : {typeRef : {kind: "reference", value : snRole}, parameters : []}};
+}
+
+export type RoleTable = common_db.WithId<Role>;
+
+const RoleTable_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[{"key":{"moduleName":"common.db","name":"DbTable"},"value":{"id_prefix":"G-","uniqueness_constraints":[["name"]]}}],"name":"RoleTable","type_":{"kind":"newtype_","value":{"default":{"kind":"nothing"},"typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"Role"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.db","name":"WithId"}}},"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.db"};
+
+export const snRoleTable: ADL.ScopedName = {moduleName:"protoapp.db", name:"RoleTable"};
+
+export function texprRoleTable(): ADL.ATypeExpr<RoleTable> {
+  return {value : {typeRef : {kind: "reference", value : snRoleTable}, parameters : []}};
+}
+
+export type RoleId = common_db.DbKey<RoleTable>;
+
+const RoleId_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"RoleId","type_":{"kind":"type_","value":{"typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"RoleTable"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.db","name":"DbKey"}}},"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.db"};
+
+export const snRoleId: ADL.ScopedName = {moduleName:"protoapp.db", name:"RoleId"};
+
+export function texprRoleId(): ADL.ATypeExpr<RoleId> {
+  return {value : {typeRef : {kind: "reference", value : snRoleId}, parameters : []}};
+}
+
+export interface Permission {
+  role_id: RoleId;
+  name: common_strings.StringNE;
+}
+
+export function makePermission(
+  input: {
+    role_id: RoleId,
+    name: common_strings.StringNE,
+  }
+): Permission {
+  return {
+    role_id: input.role_id,
+    name: input.name,
+  };
+}
+
+const Permission_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"Permission","type_":{"kind":"struct_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"role_id","serializedName":"role_id","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"RoleId"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"name","serializedName":"name","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.strings","name":"StringNE"}}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.db"};
+
+export const snPermission: ADL.ScopedName = {moduleName:"protoapp.db", name:"Permission"};
+
+export function texprPermission(): ADL.ATypeExpr<Permission> {
+  return {value : {typeRef : {kind: "reference", value : snPermission}, parameters : []}};
+}
+
+export type PermissionTable = common_db.WithId<Permission>;
+
+const PermissionTable_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[{"key":{"moduleName":"common.db","name":"DbTable"},"value":{"id_prefix":"H-","uniqueness_constraints":[["role_id","name"]]}}],"name":"PermissionTable","type_":{"kind":"newtype_","value":{"default":{"kind":"nothing"},"typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"Permission"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.db","name":"WithId"}}},"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.db"};
+
+export const snPermissionTable: ADL.ScopedName = {moduleName:"protoapp.db", name:"PermissionTable"};
+
+export function texprPermissionTable(): ADL.ATypeExpr<PermissionTable> {
+  return {value : {typeRef : {kind: "reference", value : snPermissionTable}, parameters : []}};
+}
+
+export type PermissionId = common_db.DbKey<PermissionTable>;
+
+const PermissionId_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"PermissionId","type_":{"kind":"type_","value":{"typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"PermissionTable"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.db","name":"DbKey"}}},"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.db"};
+
+export const snPermissionId: ADL.ScopedName = {moduleName:"protoapp.db", name:"PermissionId"};
+
+export function texprPermissionId(): ADL.ATypeExpr<PermissionId> {
+  return {value : {typeRef : {kind: "reference", value : snPermissionId}, parameters : []}};
+}
+
+export interface UserRole {
+  user_id: AppUserId;
+  role_id: RoleId;
+}
+
+export function makeUserRole(
+  input: {
+    user_id: AppUserId,
+    role_id: RoleId,
+  }
+): UserRole {
+  return {
+    user_id: input.user_id,
+    role_id: input.role_id,
+  };
+}
+
+const UserRole_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"UserRole","type_":{"kind":"struct_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"user_id","serializedName":"user_id","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"AppUserId"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"role_id","serializedName":"role_id","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"RoleId"}}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.db"};
+
+export const snUserRole: ADL.ScopedName = {moduleName:"protoapp.db", name:"UserRole"};
+
+export function texprUserRole(): ADL.ATypeExpr<UserRole> {
+  return {value : {typeRef : {kind: "reference", value : snUserRole}, parameters : []}};
+}
+
+export type UserRoleTable = common_db.WithId<UserRole>;
+
+const UserRoleTable_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[{"key":{"moduleName":"common.db","name":"DbTable"},"value":{"id_prefix":"J-","indexes":[["role_id"]],"uniqueness_constraints":[["user_id","role_id"]]}}],"name":"UserRoleTable","type_":{"kind":"newtype_","value":{"default":{"kind":"nothing"},"typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"UserRole"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.db","name":"WithId"}}},"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.db"};
+
+export const snUserRoleTable: ADL.ScopedName = {moduleName:"protoapp.db", name:"UserRoleTable"};
+
+export function texprUserRoleTable(): ADL.ATypeExpr<UserRoleTable> {
+  return {value : {typeRef : {kind: "reference", value : snUserRoleTable}, parameters : []}};
+}
+
+export type UserRoleId = common_db.DbKey<UserRoleTable>;
+
+const UserRoleId_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"UserRoleId","type_":{"kind":"type_","value":{"typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"UserRoleTable"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.db","name":"DbKey"}}},"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.db"};
+
+export const snUserRoleId: ADL.ScopedName = {moduleName:"protoapp.db", name:"UserRoleId"};
+
+export function texprUserRoleId(): ADL.ATypeExpr<UserRoleId> {
+  return {value : {typeRef : {kind: "reference", value : snUserRoleId}, parameters : []}};
+}
+
+export interface Organisation {
+  name: common_strings.StringNE;
+  created_at: common_time.Instant;
+}
+
+export function makeOrganisation(
+  input: {
+    name: common_strings.StringNE,
+    created_at: common_time.Instant,
+  }
+): Organisation {
+  return {
+    name: input.name,
+    created_at: input.created_at,
+  };
+}
+
+const Organisation_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"Organisation","type_":{"kind":"struct_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"name","serializedName":"name","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.strings","name":"StringNE"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"created_at","serializedName":"created_at","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.time","name":"Instant"}}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.db"};
+
+export const snOrganisation: ADL.ScopedName = {moduleName:"protoapp.db", name:"Organisation"};
+
+export function texprOrganisation(): ADL.ATypeExpr<Organisation> {
+  return {value : {typeRef : {kind: "reference", value : snOrganisation}, parameters : []}};
+}
+
+export type OrganisationTable = common_db.WithId<Organisation>;
+
+const OrganisationTable_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[{"key":{"moduleName":"common.db","name":"DbTable"},"value":{"id_prefix":"B-","uniqueness_constraints":[["name"]]}}],"name":"OrganisationTable","type_":{"kind":"newtype_","value":{"default":{"kind":"nothing"},"typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"Organisation"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.db","name":"WithId"}}},"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.db"};
+
+export const snOrganisationTable: ADL.ScopedName = {moduleName:"protoapp.db", name:"OrganisationTable"};
+
+export function texprOrganisationTable(): ADL.ATypeExpr<OrganisationTable> {
+  return {value : {typeRef : {kind: "reference", value : snOrganisationTable}, parameters : []}};
+}
+
+export type OrganisationId = common_db.DbKey<OrganisationTable>;
+
+const OrganisationId_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"OrganisationId","type_":{"kind":"type_","value":{"typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"OrganisationTable"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.db","name":"DbKey"}}},"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.db"};
+
+export const snOrganisationId: ADL.ScopedName = {moduleName:"protoapp.db", name:"OrganisationId"};
+
+export function texprOrganisationId(): ADL.ATypeExpr<OrganisationId> {
+  return {value : {typeRef : {kind: "reference", value : snOrganisationId}, parameters : []}};
+}
+
+export interface OrganisationMember {
+  organisation_id: OrganisationId;
+  user_id: AppUserId;
+  roles: RoleId[];
+  joined_at: common_time.Instant;
+}
+
+export function makeOrganisationMember(
+  input: {
+    organisation_id: OrganisationId,
+    user_id: AppUserId,
+    roles?: RoleId[],
+    joined_at: common_time.Instant,
+  }
+): OrganisationMember {
+  return {
+    organisation_id: input.organisation_id,
+    user_id: input.user_id,
+    roles: input.roles === undefined ? [] : input.roles,
+    joined_at: input.joined_at,
+  };
+}
+
+const OrganisationMember_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"OrganisationMember","type_":{"kind":"struct_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"organisation_id","serializedName":"organisation_id","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"OrganisationId"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"user_id","serializedName":"user_id","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"AppUserId"}}}},{"annotations":[],"default":{"kind":"just","value":[]},"name":"roles","serializedName":"roles","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"RoleId"}}}],"typeRef":{"kind":"primitive","value":"Vector"}}},{"annotations":[],"default":{"kind":"nothing"},"name":"joined_at","serializedName":"joined_at","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.time","name":"Instant"}}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.db"};
+
+export const snOrganisationMember: ADL.ScopedName = {moduleName:"protoapp.db", name:"OrganisationMember"};
+
+export function texprOrganisationMember(): ADL.ATypeExpr<OrganisationMember> {
+  return {value : {typeRef : {kind: "reference", value : snOrganisationMember}, parameters : []}};
+}
+
+export type OrganisationMemberTable = common_db.WithId<OrganisationMember>;
+
+const OrganisationMemberTable_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[{"key":{"moduleName":"common.db","name":"DbTable"},"value":{"id_prefix":"E-","indexes":[["user_id"]],"uniqueness_constraints":[["organisation_id","user_id"]]}}],"name":"OrganisationMemberTable","type_":{"kind":"newtype_","value":{"default":{"kind":"nothing"},"typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"OrganisationMember"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.db","name":"WithId"}}},"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.db"};
+
+export const snOrganisationMemberTable: ADL.ScopedName = {moduleName:"protoapp.db", name:"OrganisationMemberTable"};
+
+export function texprOrganisationMemberTable(): ADL.ATypeExpr<OrganisationMemberTable> {
+  return {value : {typeRef : {kind: "reference", value : snOrganisationMemberTable}, parameters : []}};
+}
+
+export type OrganisationMemberId = common_db.DbKey<OrganisationMemberTable>;
+
+const OrganisationMemberId_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"OrganisationMemberId","type_":{"kind":"type_","value":{"typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"OrganisationMemberTable"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.db","name":"DbKey"}}},"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.db"};
+
+export const snOrganisationMemberId: ADL.ScopedName = {moduleName:"protoapp.db", name:"OrganisationMemberId"};
+
+export function texprOrganisationMemberId(): ADL.ATypeExpr<OrganisationMemberId> {
+  return {value : {typeRef : {kind: "reference", value : snOrganisationMemberId}, parameters : []}};
+}
+
 export interface Message {
   posted_at: common_time.Instant;
   posted_by: AppUserId;
   message: common_strings.StringML;
+  organisation_id: (OrganisationId|null);
+  reply_to: (MessageId|null);
+  edited_at: (common_time.Instant|null);
+  deleted_at: (common_time.Instant|null);
 }
 
 export function makeMessage(
@@ -70,17 +326,25 @@ export function makeMessage(
     posted_at: common_time.Instant,
     posted_by: AppUserId,
     message: common_strings.StringML,
+    organisation_id?: (OrganisationId|null),
+    reply_to?: (MessageId|null),
+    edited_at?: (common_time.Instant|null),
+    deleted_at?: (common_time.Instant|null),
   }
 ): Message {
   return {
     posted_at: input.posted_at,
     posted_by: input.posted_by,
     message: input.message,
+    organisation_id: input.organisation_id === undefined ? null : input.organisation_id,
+    reply_to: input.reply_to === undefined ? null : input.reply_to,
+    edited_at: input.edited_at === undefined ? null : input.edited_at,
+    deleted_at: input.deleted_at === undefined ? null : input.deleted_at,
   };
 }
 
 const Message_AST : ADL.ScopedDecl =
-  {"decl":{"annotations":[],"name":"Message","type_":{"kind":"struct_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"posted_at","serializedName":"posted_at","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.time","name":"Instant"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"posted_by","serializedName":"posted_by","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"AppUserId"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"message","serializedName":"message","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.strings","name":"StringML"}}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.db"};
+  {"decl":{"annotations":[],"name":"Message","type_":{"kind":"struct_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"posted_at","serializedName":"posted_at","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.time","name":"Instant"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"posted_by","serializedName":"posted_by","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"AppUserId"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"message","serializedName":"message","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.strings","name":"StringML"}}}},{"annotations":[],"default":{"kind":"just","value":null},"name":"organisation_id","serializedName":"organisation_id","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"OrganisationId"}}}],"typeRef":{"kind":"primitive","value":"Nullable"}}},{"annotations":[],"default":{"kind":"just","value":null},"name":"reply_to","serializedName":"reply_to","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"MessageId"}}}],"typeRef":{"kind":"primitive","value":"Nullable"}}},{"annotations":[],"default":{"kind":"just","value":null},"name":"edited_at","serializedName":"edited_at","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.time","name":"Instant"}}}],"typeRef":{"kind":"primitive","value":"Nullable"}}},{"annotations":[],"default":{"kind":"just","value":null},"name":"deleted_at","serializedName":"deleted_at","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.time","name":"Instant"}}}],"typeRef":{"kind":"primitive","value":"Nullable"}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.db"};
 
 export const snMessage: ADL.ScopedName = {moduleName:"protoapp.db", name:"Message"};
 
@@ -91,7 +355,7 @@ export function texprMessage(): ADL.ATypeExpr<Message> {
 export type MessageTable = common_db.WithId<Message>;
 
 const MessageTable_AST : ADL.ScopedDecl =
-  {"decl":{"annotations":[{"key":{"moduleName":"common.db","name":"DbTable"},"value":{"id_prefix":"M-","indexes":[["posted_at"]]}}],"name":"MessageTable","type_":{"kind":"newtype_","value":{"default":{"kind":"nothing"},"typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"Message"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.db","name":"WithId"}}},"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.db"};
+  {"decl":{"annotations":[{"key":{"moduleName":"common.db","name":"DbTable"},"value":{"id_prefix":"M-","indexes":[["posted_at"],["organisation_id"],["reply_to"]]}}],"name":"MessageTable","type_":{"kind":"newtype_","value":{"default":{"kind":"nothing"},"typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"Message"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.db","name":"WithId"}}},"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.db"};
 
 export const snMessageTable: ADL.ScopedName = {moduleName:"protoapp.db", name:"MessageTable"};
 
@@ -110,11 +374,853 @@ export function texprMessageId(): ADL.ATypeExpr<MessageId> {
   return {value : {typeRef : {kind: "reference", value : snMessageId}, parameters : []}};
 }
 
+export interface MessageReaction {
+  message_id: MessageId;
+  user_id: AppUserId;
+  emoji: common_strings.StringNE;
+  reacted_at: common_time.Instant;
+}
+
+export function makeMessageReaction(
+  input: {
+    message_id: MessageId,
+    user_id: AppUserId,
+    emoji: common_strings.StringNE,
+    reacted_at: common_time.Instant,
+  }
+): MessageReaction {
+  return {
+    message_id: input.message_id,
+    user_id: input.user_id,
+    emoji: input.emoji,
+    reacted_at: input.reacted_at,
+  };
+}
+
+const MessageReaction_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"MessageReaction","type_":{"kind":"struct_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"message_id","serializedName":"message_id","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"MessageId"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"user_id","serializedName":"user_id","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"AppUserId"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"emoji","serializedName":"emoji","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.strings","name":"StringNE"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"reacted_at","serializedName":"reacted_at","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.time","name":"Instant"}}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.db"};
+
+export const snMessageReaction: ADL.ScopedName = {moduleName:"protoapp.db", name:"MessageReaction"};
+
+export function texprMessageReaction(): ADL.ATypeExpr<MessageReaction> {
+  return {value : {typeRef : {kind: "reference", value : snMessageReaction}, parameters : []}};
+}
+
+export type MessageReactionTable = common_db.WithId<MessageReaction>;
+
+const MessageReactionTable_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[{"key":{"moduleName":"common.db","name":"DbTable"},"value":{"id_prefix":"X-","uniqueness_constraints":[["message_id","user_id","emoji"]]}}],"name":"MessageReactionTable","type_":{"kind":"newtype_","value":{"default":{"kind":"nothing"},"typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"MessageReaction"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.db","name":"WithId"}}},"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.db"};
+
+export const snMessageReactionTable: ADL.ScopedName = {moduleName:"protoapp.db", name:"MessageReactionTable"};
+
+export function texprMessageReactionTable(): ADL.ATypeExpr<MessageReactionTable> {
+  return {value : {typeRef : {kind: "reference", value : snMessageReactionTable}, parameters : []}};
+}
+
+export type MessageReactionId = common_db.DbKey<MessageReactionTable>;
+
+const MessageReactionId_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"MessageReactionId","type_":{"kind":"type_","value":{"typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"MessageReactionTable"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.db","name":"DbKey"}}},"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.db"};
+
+export const snMessageReactionId: ADL.ScopedName = {moduleName:"protoapp.db", name:"MessageReactionId"};
+
+export function texprMessageReactionId(): ADL.ATypeExpr<MessageReactionId> {
+  return {value : {typeRef : {kind: "reference", value : snMessageReactionId}, parameters : []}};
+}
+
+export interface MessageRevision {
+  message_id: MessageId;
+  message: common_strings.StringML;
+  written_at: common_time.Instant;
+  replaced_at: common_time.Instant;
+  replaced_by: AppUserId;
+}
+
+export function makeMessageRevision(
+  input: {
+    message_id: MessageId,
+    message: common_strings.StringML,
+    written_at: common_time.Instant,
+    replaced_at: common_time.Instant,
+    replaced_by: AppUserId,
+  }
+): MessageRevision {
+  return {
+    message_id: input.message_id,
+    message: input.message,
+    written_at: input.written_at,
+    replaced_at: input.replaced_at,
+    replaced_by: input.replaced_by,
+  };
+}
+
+const MessageRevision_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"MessageRevision","type_":{"kind":"struct_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"message_id","serializedName":"message_id","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"MessageId"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"message","serializedName":"message","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.strings","name":"StringML"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"written_at","serializedName":"written_at","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.time","name":"Instant"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"replaced_at","serializedName":"replaced_at","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.time","name":"Instant"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"replaced_by","serializedName":"replaced_by","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"AppUserId"}}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.db"};
+
+export const snMessageRevision: ADL.ScopedName = {moduleName:"protoapp.db", name:"MessageRevision"};
+
+export function texprMessageRevision(): ADL.ATypeExpr<MessageRevision> {
+  return {value : {typeRef : {kind: "reference", value : snMessageRevision}, parameters : []}};
+}
+
+export type MessageRevisionTable = common_db.WithId<MessageRevision>;
+
+const MessageRevisionTable_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[{"key":{"moduleName":"common.db","name":"DbTable"},"value":{"id_prefix":"V-","indexes":[["message_id"]]}}],"name":"MessageRevisionTable","type_":{"kind":"newtype_","value":{"default":{"kind":"nothing"},"typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"MessageRevision"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.db","name":"WithId"}}},"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.db"};
+
+export const snMessageRevisionTable: ADL.ScopedName = {moduleName:"protoapp.db", name:"MessageRevisionTable"};
+
+export function texprMessageRevisionTable(): ADL.ATypeExpr<MessageRevisionTable> {
+  return {value : {typeRef : {kind: "reference", value : snMessageRevisionTable}, parameters : []}};
+}
+
+export type MessageRevisionId = common_db.DbKey<MessageRevisionTable>;
+
+const MessageRevisionId_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"MessageRevisionId","type_":{"kind":"type_","value":{"typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"MessageRevisionTable"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.db","name":"DbKey"}}},"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.db"};
+
+export const snMessageRevisionId: ADL.ScopedName = {moduleName:"protoapp.db", name:"MessageRevisionId"};
+
+export function texprMessageRevisionId(): ADL.ATypeExpr<MessageRevisionId> {
+  return {value : {typeRef : {kind: "reference", value : snMessageRevisionId}, parameters : []}};
+}
+
+export interface RefreshToken {
+  user_id: AppUserId;
+  family: string;
+  issued_at: common_time.Instant;
+  expires_at: common_time.Instant;
+  used_at: (common_time.Instant|null);
+  revoked_at: (common_time.Instant|null);
+}
+
+export function makeRefreshToken(
+  input: {
+    user_id: AppUserId,
+    family: string,
+    issued_at: common_time.Instant,
+    expires_at: common_time.Instant,
+    used_at: (common_time.Instant|null),
+    revoked_at: (common_time.Instant|null),
+  }
+): RefreshToken {
+  return {
+    user_id: input.user_id,
+    family: input.family,
+    issued_at: input.issued_at,
+    expires_at: input.expires_at,
+    used_at: input.used_at,
+    revoked_at: input.revoked_at,
+  };
+}
+
+const RefreshToken_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"RefreshToken","type_":{"kind":"struct_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"user_id","serializedName":"user_id","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"AppUserId"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"family","serializedName":"family","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"String"}}},{"annotations":[],"default":{"kind":"nothing"},"name":"issued_at","serializedName":"issued_at","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.time","name":"Instant"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"expires_at","serializedName":"expires_at","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.time","name":"Instant"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"used_at","serializedName":"used_at","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.time","name":"Instant"}}}],"typeRef":{"kind":"primitive","value":"Nullable"}}},{"annotations":[],"default":{"kind":"nothing"},"name":"revoked_at","serializedName":"revoked_at","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.time","name":"Instant"}}}],"typeRef":{"kind":"primitive","value":"Nullable"}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.db"};
+
+export const snRefreshToken: ADL.ScopedName = {moduleName:"protoapp.db", name:"RefreshToken"};
+
+export function texprRefreshToken(): ADL.ATypeExpr<RefreshToken> {
+  return {value : {typeRef : {kind: "reference", value : snRefreshToken}, parameters : []}};
+}
+
+export type RefreshTokenTable = common_db.WithId<RefreshToken>;
+
+const RefreshTokenTable_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[{"key":{"moduleName":"common.db","name":"DbTable"},"value":{"id_prefix":"R-","indexes":[["user_id"],["family"]]}}],"name":"RefreshTokenTable","type_":{"kind":"newtype_","value":{"default":{"kind":"nothing"},"typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"RefreshToken"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.db","name":"WithId"}}},"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.db"};
+
+export const snRefreshTokenTable: ADL.ScopedName = {moduleName:"protoapp.db", name:"RefreshTokenTable"};
+
+export function texprRefreshTokenTable(): ADL.ATypeExpr<RefreshTokenTable> {
+  return {value : {typeRef : {kind: "reference", value : snRefreshTokenTable}, parameters : []}};
+}
+
+export type RefreshTokenId = common_db.DbKey<RefreshTokenTable>;
+
+const RefreshTokenId_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"RefreshTokenId","type_":{"kind":"type_","value":{"typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"RefreshTokenTable"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.db","name":"DbKey"}}},"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.db"};
+
+export const snRefreshTokenId: ADL.ScopedName = {moduleName:"protoapp.db", name:"RefreshTokenId"};
+
+export function texprRefreshTokenId(): ADL.ATypeExpr<RefreshTokenId> {
+  return {value : {typeRef : {kind: "reference", value : snRefreshTokenId}, parameters : []}};
+}
+
+export interface Session {
+  user_id: AppUserId;
+  user_agent: (string|null);
+  client_ip: (string|null);
+  created_at: common_time.Instant;
+  last_refreshed_at: common_time.Instant;
+  revoked_at: (common_time.Instant|null);
+  organisation_id: (OrganisationId|null);
+}
+
+export function makeSession(
+  input: {
+    user_id: AppUserId,
+    user_agent: (string|null),
+    client_ip: (string|null),
+    created_at: common_time.Instant,
+    last_refreshed_at: common_time.Instant,
+    revoked_at: (common_time.Instant|null),
+    organisation_id?: (OrganisationId|null),
+  }
+): Session {
+  return {
+    user_id: input.user_id,
+    user_agent: input.user_agent,
+    client_ip: input.client_ip,
+    created_at: input.created_at,
+    last_refreshed_at: input.last_refreshed_at,
+    revoked_at: input.revoked_at,
+    organisation_id: input.organisation_id === undefined ? null : input.organisation_id,
+  };
+}
+
+const Session_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"Session","type_":{"kind":"struct_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"user_id","serializedName":"user_id","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"AppUserId"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"user_agent","serializedName":"user_agent","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"primitive","value":"String"}}],"typeRef":{"kind":"primitive","value":"Nullable"}}},{"annotations":[],"default":{"kind":"nothing"},"name":"client_ip","serializedName":"client_ip","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"primitive","value":"String"}}],"typeRef":{"kind":"primitive","value":"Nullable"}}},{"annotations":[],"default":{"kind":"nothing"},"name":"created_at","serializedName":"created_at","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.time","name":"Instant"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"last_refreshed_at","serializedName":"last_refreshed_at","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.time","name":"Instant"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"revoked_at","serializedName":"revoked_at","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.time","name":"Instant"}}}],"typeRef":{"kind":"primitive","value":"Nullable"}}},{"annotations":[],"default":{"kind":"just","value":null},"name":"organisation_id","serializedName":"organisation_id","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"OrganisationId"}}}],"typeRef":{"kind":"primitive","value":"Nullable"}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.db"};
+
+export const snSession: ADL.ScopedName = {moduleName:"protoapp.db", name:"Session"};
+
+export function texprSession(): ADL.ATypeExpr<Session> {
+  return {value : {typeRef : {kind: "reference", value : snSession}, parameters : []}};
+}
+
+export type SessionTable = common_db.WithId<Session>;
+
+const SessionTable_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[{"key":{"moduleName":"common.db","name":"DbTable"},"value":{"id_prefix":"S-","indexes":[["user_id"]]}}],"name":"SessionTable","type_":{"kind":"newtype_","value":{"default":{"kind":"nothing"},"typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"Session"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.db","name":"WithId"}}},"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.db"};
+
+export const snSessionTable: ADL.ScopedName = {moduleName:"protoapp.db", name:"SessionTable"};
+
+export function texprSessionTable(): ADL.ATypeExpr<SessionTable> {
+  return {value : {typeRef : {kind: "reference", value : snSessionTable}, parameters : []}};
+}
+
+export type SessionId = common_db.DbKey<SessionTable>;
+
+const SessionId_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"SessionId","type_":{"kind":"type_","value":{"typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"SessionTable"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.db","name":"DbKey"}}},"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.db"};
+
+export const snSessionId: ADL.ScopedName = {moduleName:"protoapp.db", name:"SessionId"};
+
+export function texprSessionId(): ADL.ATypeExpr<SessionId> {
+  return {value : {typeRef : {kind: "reference", value : snSessionId}, parameters : []}};
+}
+
+export interface PasswordResetToken {
+  user_id: AppUserId;
+  token_hash: string;
+  created_at: common_time.Instant;
+  expires_at: common_time.Instant;
+  used_at: (common_time.Instant|null);
+}
+
+export function makePasswordResetToken(
+  input: {
+    user_id: AppUserId,
+    token_hash: string,
+    created_at: common_time.Instant,
+    expires_at: common_time.Instant,
+    used_at: (common_time.Instant|null),
+  }
+): PasswordResetToken {
+  return {
+    user_id: input.user_id,
+    token_hash: input.token_hash,
+    created_at: input.created_at,
+    expires_at: input.expires_at,
+    used_at: input.used_at,
+  };
+}
+
+const PasswordResetToken_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"PasswordResetToken","type_":{"kind":"struct_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"user_id","serializedName":"user_id","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"AppUserId"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"token_hash","serializedName":"token_hash","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"String"}}},{"annotations":[],"default":{"kind":"nothing"},"name":"created_at","serializedName":"created_at","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.time","name":"Instant"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"expires_at","serializedName":"expires_at","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.time","name":"Instant"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"used_at","serializedName":"used_at","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.time","name":"Instant"}}}],"typeRef":{"kind":"primitive","value":"Nullable"}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.db"};
+
+export const snPasswordResetToken: ADL.ScopedName = {moduleName:"protoapp.db", name:"PasswordResetToken"};
+
+export function texprPasswordResetToken(): ADL.ATypeExpr<PasswordResetToken> {
+  return {value : {typeRef : {kind: "reference", value : snPasswordResetToken}, parameters : []}};
+}
+
+export type PasswordResetTokenTable = common_db.WithId<PasswordResetToken>;
+
+const PasswordResetTokenTable_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[{"key":{"moduleName":"common.db","name":"DbTable"},"value":{"id_prefix":"P-","indexes":[["user_id"]],"uniqueness_constraints":[["token_hash"]]}}],"name":"PasswordResetTokenTable","type_":{"kind":"newtype_","value":{"default":{"kind":"nothing"},"typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"PasswordResetToken"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.db","name":"WithId"}}},"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.db"};
+
+export const snPasswordResetTokenTable: ADL.ScopedName = {moduleName:"protoapp.db", name:"PasswordResetTokenTable"};
+
+export function texprPasswordResetTokenTable(): ADL.ATypeExpr<PasswordResetTokenTable> {
+  return {value : {typeRef : {kind: "reference", value : snPasswordResetTokenTable}, parameters : []}};
+}
+
+export type PasswordResetTokenId = common_db.DbKey<PasswordResetTokenTable>;
+
+const PasswordResetTokenId_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"PasswordResetTokenId","type_":{"kind":"type_","value":{"typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"PasswordResetTokenTable"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.db","name":"DbKey"}}},"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.db"};
+
+export const snPasswordResetTokenId: ADL.ScopedName = {moduleName:"protoapp.db", name:"PasswordResetTokenId"};
+
+export function texprPasswordResetTokenId(): ADL.ATypeExpr<PasswordResetTokenId> {
+  return {value : {typeRef : {kind: "reference", value : snPasswordResetTokenId}, parameters : []}};
+}
+
+export interface Invitation {
+  email: common_strings.StringNE;
+  roles: RoleId[];
+  token_hash: string;
+  created_by: AppUserId;
+  created_at: common_time.Instant;
+  expires_at: common_time.Instant;
+  used_at: (common_time.Instant|null);
+  user_id: (AppUserId|null);
+  revoked_at: (common_time.Instant|null);
+}
+
+export function makeInvitation(
+  input: {
+    email: common_strings.StringNE,
+    roles: RoleId[],
+    token_hash: string,
+    created_by: AppUserId,
+    created_at: common_time.Instant,
+    expires_at: common_time.Instant,
+    used_at: (common_time.Instant|null),
+    user_id: (AppUserId|null),
+    revoked_at: (common_time.Instant|null),
+  }
+): Invitation {
+  return {
+    email: input.email,
+    roles: input.roles,
+    token_hash: input.token_hash,
+    created_by: input.created_by,
+    created_at: input.created_at,
+    expires_at: input.expires_at,
+    used_at: input.used_at,
+    user_id: input.user_id,
+    revoked_at: input.revoked_at,
+  };
+}
+
+const Invitation_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"Invitation","type_":{"kind":"struct_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"email","serializedName":"email","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.strings","name":"StringNE"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"roles","serializedName":"roles","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"RoleId"}}}],"typeRef":{"kind":"primitive","value":"Vector"}}},{"annotations":[],"default":{"kind":"nothing"},"name":"token_hash","serializedName":"token_hash","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"String"}}},{"annotations":[],"default":{"kind":"nothing"},"name":"created_by","serializedName":"created_by","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"AppUserId"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"created_at","serializedName":"created_at","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.time","name":"Instant"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"expires_at","serializedName":"expires_at","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.time","name":"Instant"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"used_at","serializedName":"used_at","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.time","name":"Instant"}}}],"typeRef":{"kind":"primitive","value":"Nullable"}}},{"annotations":[],"default":{"kind":"nothing"},"name":"user_id","serializedName":"user_id","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"AppUserId"}}}],"typeRef":{"kind":"primitive","value":"Nullable"}}},{"annotations":[],"default":{"kind":"nothing"},"name":"revoked_at","serializedName":"revoked_at","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.time","name":"Instant"}}}],"typeRef":{"kind":"primitive","value":"Nullable"}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.db"};
+
+export const snInvitation: ADL.ScopedName = {moduleName:"protoapp.db", name:"Invitation"};
+
+export function texprInvitation(): ADL.ATypeExpr<Invitation> {
+  return {value : {typeRef : {kind: "reference", value : snInvitation}, parameters : []}};
+}
+
+export type InvitationTable = common_db.WithId<Invitation>;
+
+const InvitationTable_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[{"key":{"moduleName":"common.db","name":"DbTable"},"value":{"id_prefix":"N-","indexes":[["email"]],"uniqueness_constraints":[["token_hash"]]}}],"name":"InvitationTable","type_":{"kind":"newtype_","value":{"default":{"kind":"nothing"},"typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"Invitation"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.db","name":"WithId"}}},"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.db"};
+
+export const snInvitationTable: ADL.ScopedName = {moduleName:"protoapp.db", name:"InvitationTable"};
+
+export function texprInvitationTable(): ADL.ATypeExpr<InvitationTable> {
+  return {value : {typeRef : {kind: "reference", value : snInvitationTable}, parameters : []}};
+}
+
+export type InvitationId = common_db.DbKey<InvitationTable>;
+
+const InvitationId_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"InvitationId","type_":{"kind":"type_","value":{"typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"InvitationTable"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.db","name":"DbKey"}}},"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.db"};
+
+export const snInvitationId: ADL.ScopedName = {moduleName:"protoapp.db", name:"InvitationId"};
+
+export function texprInvitationId(): ADL.ATypeExpr<InvitationId> {
+  return {value : {typeRef : {kind: "reference", value : snInvitationId}, parameters : []}};
+}
+
+export interface UserTotp {
+  user_id: AppUserId;
+  secret: string;
+  confirmed_at: (common_time.Instant|null);
+  last_used_step: (number|null);
+}
+
+export function makeUserTotp(
+  input: {
+    user_id: AppUserId,
+    secret: string,
+    confirmed_at: (common_time.Instant|null),
+    last_used_step: (number|null),
+  }
+): UserTotp {
+  return {
+    user_id: input.user_id,
+    secret: input.secret,
+    confirmed_at: input.confirmed_at,
+    last_used_step: input.last_used_step,
+  };
+}
+
+const UserTotp_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"UserTotp","type_":{"kind":"struct_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"user_id","serializedName":"user_id","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"AppUserId"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"secret","serializedName":"secret","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"String"}}},{"annotations":[],"default":{"kind":"nothing"},"name":"confirmed_at","serializedName":"confirmed_at","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.time","name":"Instant"}}}],"typeRef":{"kind":"primitive","value":"Nullable"}}},{"annotations":[],"default":{"kind":"nothing"},"name":"last_used_step","serializedName":"last_used_step","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"primitive","value":"Word64"}}],"typeRef":{"kind":"primitive","value":"Nullable"}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.db"};
+
+export const snUserTotp: ADL.ScopedName = {moduleName:"protoapp.db", name:"UserTotp"};
+
+export function texprUserTotp(): ADL.ATypeExpr<UserTotp> {
+  return {value : {typeRef : {kind: "reference", value : snUserTotp}, parameters : []}};
+}
+
+export type UserTotpTable = common_db.WithId<UserTotp>;
+
+const UserTotpTable_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[{"key":{"moduleName":"common.db","name":"DbTable"},"value":{"id_prefix":"T-","uniqueness_constraints":[["user_id"]]}}],"name":"UserTotpTable","type_":{"kind":"newtype_","value":{"default":{"kind":"nothing"},"typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"UserTotp"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.db","name":"WithId"}}},"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.db"};
+
+export const snUserTotpTable: ADL.ScopedName = {moduleName:"protoapp.db", name:"UserTotpTable"};
+
+export function texprUserTotpTable(): ADL.ATypeExpr<UserTotpTable> {
+  return {value : {typeRef : {kind: "reference", value : snUserTotpTable}, parameters : []}};
+}
+
+export type UserTotpId = common_db.DbKey<UserTotpTable>;
+
+const UserTotpId_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"UserTotpId","type_":{"kind":"type_","value":{"typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"UserTotpTable"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.db","name":"DbKey"}}},"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.db"};
+
+export const snUserTotpId: ADL.ScopedName = {moduleName:"protoapp.db", name:"UserTotpId"};
+
+export function texprUserTotpId(): ADL.ATypeExpr<UserTotpId> {
+  return {value : {typeRef : {kind: "reference", value : snUserTotpId}, parameters : []}};
+}
+
+export interface TotpRecoveryCode {
+  user_id: AppUserId;
+  code_hash: string;
+  used_at: (common_time.Instant|null);
+}
+
+export function makeTotpRecoveryCode(
+  input: {
+    user_id: AppUserId,
+    code_hash: string,
+    used_at: (common_time.Instant|null),
+  }
+): TotpRecoveryCode {
+  return {
+    user_id: input.user_id,
+    code_hash: input.code_hash,
+    used_at: input.used_at,
+  };
+}
+
+const TotpRecoveryCode_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"TotpRecoveryCode","type_":{"kind":"struct_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"user_id","serializedName":"user_id","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"AppUserId"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"code_hash","serializedName":"code_hash","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"String"}}},{"annotations":[],"default":{"kind":"nothing"},"name":"used_at","serializedName":"used_at","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.time","name":"Instant"}}}],"typeRef":{"kind":"primitive","value":"Nullable"}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.db"};
+
+export const snTotpRecoveryCode: ADL.ScopedName = {moduleName:"protoapp.db", name:"TotpRecoveryCode"};
+
+export function texprTotpRecoveryCode(): ADL.ATypeExpr<TotpRecoveryCode> {
+  return {value : {typeRef : {kind: "reference", value : snTotpRecoveryCode}, parameters : []}};
+}
+
+export type TotpRecoveryCodeTable = common_db.WithId<TotpRecoveryCode>;
+
+const TotpRecoveryCodeTable_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[{"key":{"moduleName":"common.db","name":"DbTable"},"value":{"id_prefix":"C-","indexes":[["user_id"]]}}],"name":"TotpRecoveryCodeTable","type_":{"kind":"newtype_","value":{"default":{"kind":"nothing"},"typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"TotpRecoveryCode"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.db","name":"WithId"}}},"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.db"};
+
+export const snTotpRecoveryCodeTable: ADL.ScopedName = {moduleName:"protoapp.db", name:"TotpRecoveryCodeTable"};
+
+export function texprTotpRecoveryCodeTable(): ADL.ATypeExpr<TotpRecoveryCodeTable> {
+  return {value : {typeRef : {kind: "reference", value : snTotpRecoveryCodeTable}, parameters : []}};
+}
+
+export type TotpRecoveryCodeId = common_db.DbKey<TotpRecoveryCodeTable>;
+
+const TotpRecoveryCodeId_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"TotpRecoveryCodeId","type_":{"kind":"type_","value":{"typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"TotpRecoveryCodeTable"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.db","name":"DbKey"}}},"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.db"};
+
+export const snTotpRecoveryCodeId: ADL.ScopedName = {moduleName:"protoapp.db", name:"TotpRecoveryCodeId"};
+
+export function texprTotpRecoveryCodeId(): ADL.ATypeExpr<TotpRecoveryCodeId> {
+  return {value : {typeRef : {kind: "reference", value : snTotpRecoveryCodeId}, parameters : []}};
+}
+
+export interface ApiKey {
+  user_id: AppUserId;
+  name: common_strings.StringNE;
+  key_hash: string;
+  scopes: ApiKeyScope[];
+  created_at: common_time.Instant;
+  expires_at: common_time.Instant;
+  last_used_at: (common_time.Instant|null);
+  revoked_at: (common_time.Instant|null);
+}
+
+export function makeApiKey(
+  input: {
+    user_id: AppUserId,
+    name: common_strings.StringNE,
+    key_hash: string,
+    scopes: ApiKeyScope[],
+    created_at: common_time.Instant,
+    expires_at: common_time.Instant,
+    last_used_at: (common_time.Instant|null),
+    revoked_at: (common_time.Instant|null),
+  }
+): ApiKey {
+  return {
+    user_id: input.user_id,
+    name: input.name,
+    key_hash: input.key_hash,
+    scopes: input.scopes,
+    created_at: input.created_at,
+    expires_at: input.expires_at,
+    last_used_at: input.last_used_at,
+    revoked_at: input.revoked_at,
+  };
+}
+
+const ApiKey_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"ApiKey","type_":{"kind":"struct_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"user_id","serializedName":"user_id","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"AppUserId"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"name","serializedName":"name","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.strings","name":"StringNE"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"key_hash","serializedName":"key_hash","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"String"}}},{"annotations":[],"default":{"kind":"nothing"},"name":"scopes","serializedName":"scopes","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"ApiKeyScope"}}}],"typeRef":{"kind":"primitive","value":"Vector"}}},{"annotations":[],"default":{"kind":"nothing"},"name":"created_at","serializedName":"created_at","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.time","name":"Instant"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"expires_at","serializedName":"expires_at","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.time","name":"Instant"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"last_used_at","serializedName":"last_used_at","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.time","name":"Instant"}}}],"typeRef":{"kind":"primitive","value":"Nullable"}}},{"annotations":[],"default":{"kind":"nothing"},"name":"revoked_at","serializedName":"revoked_at","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.time","name":"Instant"}}}],"typeRef":{"kind":"primitive","value":"Nullable"}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.db"};
+
+export const snApiKey: ADL.ScopedName = {moduleName:"protoapp.db", name:"ApiKey"};
+
+export function texprApiKey(): ADL.ATypeExpr<ApiKey> {
+  return {value : {typeRef : {kind: "reference", value : snApiKey}, parameters : []}};
+}
+
+export type ApiKeyTable = common_db.WithId<ApiKey>;
+
+const ApiKeyTable_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[{"key":{"moduleName":"common.db","name":"DbTable"},"value":{"id_prefix":"K-","indexes":[["user_id"]],"uniqueness_constraints":[["key_hash"]]}}],"name":"ApiKeyTable","type_":{"kind":"newtype_","value":{"default":{"kind":"nothing"},"typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"ApiKey"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.db","name":"WithId"}}},"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.db"};
+
+export const snApiKeyTable: ADL.ScopedName = {moduleName:"protoapp.db", name:"ApiKeyTable"};
+
+export function texprApiKeyTable(): ADL.ATypeExpr<ApiKeyTable> {
+  return {value : {typeRef : {kind: "reference", value : snApiKeyTable}, parameters : []}};
+}
+
+export type ApiKeyId = common_db.DbKey<ApiKeyTable>;
+
+const ApiKeyId_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"ApiKeyId","type_":{"kind":"type_","value":{"typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"ApiKeyTable"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.db","name":"DbKey"}}},"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.db"};
+
+export const snApiKeyId: ADL.ScopedName = {moduleName:"protoapp.db", name:"ApiKeyId"};
+
+export function texprApiKeyId(): ADL.ATypeExpr<ApiKeyId> {
+  return {value : {typeRef : {kind: "reference", value : snApiKeyId}, parameters : []}};
+}
+
+export type ApiKeyScope = 'user' | 'admin';
+export const valuesApiKeyScope : ApiKeyScope[] = ['user', 'admin'];
+
+const ApiKeyScope_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"ApiKeyScope","type_":{"kind":"union_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"user","serializedName":"user","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}},{"annotations":[],"default":{"kind":"nothing"},"name":"admin","serializedName":"admin","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.db"};
+
+export const snApiKeyScope: ADL.ScopedName = {moduleName:"protoapp.db", name:"ApiKeyScope"};
+
+export function texprApiKeyScope(): ADL.ATypeExpr<ApiKeyScope> {
+  return {value : {typeRef : {kind: "reference", value : snApiKeyScope}, parameters : []}};
+}
+
+export interface OidcLogin {
+  state_hash: string;
+  code_verifier: string;
+  nonce: string;
+  created_at: common_time.Instant;
+  expires_at: common_time.Instant;
+  used_at: (common_time.Instant|null);
+}
+
+export function makeOidcLogin(
+  input: {
+    state_hash: string,
+    code_verifier: string,
+    nonce: string,
+    created_at: common_time.Instant,
+    expires_at: common_time.Instant,
+    used_at: (common_time.Instant|null),
+  }
+): OidcLogin {
+  return {
+    state_hash: input.state_hash,
+    code_verifier: input.code_verifier,
+    nonce: input.nonce,
+    created_at: input.created_at,
+    expires_at: input.expires_at,
+    used_at: input.used_at,
+  };
+}
+
+const OidcLogin_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"OidcLogin","type_":{"kind":"struct_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"state_hash","serializedName":"state_hash","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"String"}}},{"annotations":[],"default":{"kind":"nothing"},"name":"code_verifier","serializedName":"code_verifier","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"String"}}},{"annotations":[],"default":{"kind":"nothing"},"name":"nonce","serializedName":"nonce","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"String"}}},{"annotations":[],"default":{"kind":"nothing"},"name":"created_at","serializedName":"created_at","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.time","name":"Instant"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"expires_at","serializedName":"expires_at","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.time","name":"Instant"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"used_at","serializedName":"used_at","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.time","name":"Instant"}}}],"typeRef":{"kind":"primitive","value":"Nullable"}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.db"};
+
+export const snOidcLogin: ADL.ScopedName = {moduleName:"protoapp.db", name:"OidcLogin"};
+
+export function texprOidcLogin(): ADL.ATypeExpr<OidcLogin> {
+  return {value : {typeRef : {kind: "reference", value : snOidcLogin}, parameters : []}};
+}
+
+export type OidcLoginTable = common_db.WithId<OidcLogin>;
+
+const OidcLoginTable_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[{"key":{"moduleName":"common.db","name":"DbTable"},"value":{"id_prefix":"O-","uniqueness_constraints":[["state_hash"]]}}],"name":"OidcLoginTable","type_":{"kind":"newtype_","value":{"default":{"kind":"nothing"},"typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"OidcLogin"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.db","name":"WithId"}}},"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.db"};
+
+export const snOidcLoginTable: ADL.ScopedName = {moduleName:"protoapp.db", name:"OidcLoginTable"};
+
+export function texprOidcLoginTable(): ADL.ATypeExpr<OidcLoginTable> {
+  return {value : {typeRef : {kind: "reference", value : snOidcLoginTable}, parameters : []}};
+}
+
+export type OidcLoginId = common_db.DbKey<OidcLoginTable>;
+
+const OidcLoginId_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"OidcLoginId","type_":{"kind":"type_","value":{"typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"OidcLoginTable"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.db","name":"DbKey"}}},"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.db"};
+
+export const snOidcLoginId: ADL.ScopedName = {moduleName:"protoapp.db", name:"OidcLoginId"};
+
+export function texprOidcLoginId(): ADL.ATypeExpr<OidcLoginId> {
+  return {value : {typeRef : {kind: "reference", value : snOidcLoginId}, parameters : []}};
+}
+
+export interface OidcIdentity {
+  user_id: AppUserId;
+  issuer: string;
+  subject: string;
+  linked_at: common_time.Instant;
+}
+
+export function makeOidcIdentity(
+  input: {
+    user_id: AppUserId,
+    issuer: string,
+    subject: string,
+    linked_at: common_time.Instant,
+  }
+): OidcIdentity {
+  return {
+    user_id: input.user_id,
+    issuer: input.issuer,
+    subject: input.subject,
+    linked_at: input.linked_at,
+  };
+}
+
+const OidcIdentity_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"OidcIdentity","type_":{"kind":"struct_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"user_id","serializedName":"user_id","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"AppUserId"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"issuer","serializedName":"issuer","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"String"}}},{"annotations":[],"default":{"kind":"nothing"},"name":"subject","serializedName":"subject","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"String"}}},{"annotations":[],"default":{"kind":"nothing"},"name":"linked_at","serializedName":"linked_at","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.time","name":"Instant"}}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.db"};
+
+export const snOidcIdentity: ADL.ScopedName = {moduleName:"protoapp.db", name:"OidcIdentity"};
+
+export function texprOidcIdentity(): ADL.ATypeExpr<OidcIdentity> {
+  return {value : {typeRef : {kind: "reference", value : snOidcIdentity}, parameters : []}};
+}
+
+export type OidcIdentityTable = common_db.WithId<OidcIdentity>;
+
+const OidcIdentityTable_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[{"key":{"moduleName":"common.db","name":"DbTable"},"value":{"id_prefix":"I-","indexes":[["user_id"]],"uniqueness_constraints":[["issuer","subject"]]}}],"name":"OidcIdentityTable","type_":{"kind":"newtype_","value":{"default":{"kind":"nothing"},"typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"OidcIdentity"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.db","name":"WithId"}}},"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.db"};
+
+export const snOidcIdentityTable: ADL.ScopedName = {moduleName:"protoapp.db", name:"OidcIdentityTable"};
+
+export function texprOidcIdentityTable(): ADL.ATypeExpr<OidcIdentityTable> {
+  return {value : {typeRef : {kind: "reference", value : snOidcIdentityTable}, parameters : []}};
+}
+
+export type OidcIdentityId = common_db.DbKey<OidcIdentityTable>;
+
+const OidcIdentityId_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"OidcIdentityId","type_":{"kind":"type_","value":{"typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"OidcIdentityTable"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.db","name":"DbKey"}}},"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.db"};
+
+export const snOidcIdentityId: ADL.ScopedName = {moduleName:"protoapp.db", name:"OidcIdentityId"};
+
+export function texprOidcIdentityId(): ADL.ATypeExpr<OidcIdentityId> {
+  return {value : {typeRef : {kind: "reference", value : snOidcIdentityId}, parameters : []}};
+}
+
+export interface LoginFailure {
+  email: common_strings.StringNE;
+  failed_count: number;
+  last_failed_at: common_time.Instant;
+  locked_until: (common_time.Instant|null);
+}
+
+export function makeLoginFailure(
+  input: {
+    email: common_strings.StringNE,
+    failed_count: number,
+    last_failed_at: common_time.Instant,
+    locked_until: (common_time.Instant|null),
+  }
+): LoginFailure {
+  return {
+    email: input.email,
+    failed_count: input.failed_count,
+    last_failed_at: input.last_failed_at,
+    locked_until: input.locked_until,
+  };
+}
+
+const LoginFailure_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"LoginFailure","type_":{"kind":"struct_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"email","serializedName":"email","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.strings","name":"StringNE"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"failed_count","serializedName":"failed_count","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Word32"}}},{"annotations":[],"default":{"kind":"nothing"},"name":"last_failed_at","serializedName":"last_failed_at","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.time","name":"Instant"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"locked_until","serializedName":"locked_until","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.time","name":"Instant"}}}],"typeRef":{"kind":"primitive","value":"Nullable"}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.db"};
+
+export const snLoginFailure: ADL.ScopedName = {moduleName:"protoapp.db", name:"LoginFailure"};
+
+export function texprLoginFailure(): ADL.ATypeExpr<LoginFailure> {
+  return {value : {typeRef : {kind: "reference", value : snLoginFailure}, parameters : []}};
+}
+
+export type LoginFailureTable = common_db.WithId<LoginFailure>;
+
+const LoginFailureTable_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[{"key":{"moduleName":"common.db","name":"DbTable"},"value":{"id_prefix":"L-","uniqueness_constraints":[["email"]]}}],"name":"LoginFailureTable","type_":{"kind":"newtype_","value":{"default":{"kind":"nothing"},"typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"LoginFailure"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.db","name":"WithId"}}},"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.db"};
+
+export const snLoginFailureTable: ADL.ScopedName = {moduleName:"protoapp.db", name:"LoginFailureTable"};
+
+export function texprLoginFailureTable(): ADL.ATypeExpr<LoginFailureTable> {
+  return {value : {typeRef : {kind: "reference", value : snLoginFailureTable}, parameters : []}};
+}
+
+export type LoginFailureId = common_db.DbKey<LoginFailureTable>;
+
+const LoginFailureId_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"LoginFailureId","type_":{"kind":"type_","value":{"typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"LoginFailureTable"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.db","name":"DbKey"}}},"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.db"};
+
+export const snLoginFailureId: ADL.ScopedName = {moduleName:"protoapp.db", name:"LoginFailureId"};
+
+export function texprLoginFailureId(): ADL.ATypeExpr<LoginFailureId> {
+  return {value : {typeRef : {kind: "reference", value : snLoginFailureId}, parameters : []}};
+}
+
+export interface AuditLog {
+  logged_at: common_time.Instant;
+  actor: AppUserId;
+  impersonated: (AppUserId|null);
+  endpoint: string;
+  input: {}|null;
+  outcome: AuditOutcome;
+  client_ip: (string|null);
+}
+
+export function makeAuditLog(
+  input: {
+    logged_at: common_time.Instant,
+    actor: AppUserId,
+    impersonated?: (AppUserId|null),
+    endpoint: string,
+    input: {}|null,
+    outcome: AuditOutcome,
+    client_ip: (string|null),
+  }
+): AuditLog {
+  return {
+    logged_at: input.logged_at,
+    actor: input.actor,
+    impersonated: input.impersonated === undefined ? null : input.impersonated,
+    endpoint: input.endpoint,
+    input: input.input,
+    outcome: input.outcome,
+    client_ip: input.client_ip,
+  };
+}
+
+const AuditLog_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"AuditLog","type_":{"kind":"struct_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"logged_at","serializedName":"logged_at","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"common.time","name":"Instant"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"actor","serializedName":"actor","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"AppUserId"}}}},{"annotations":[],"default":{"kind":"just","value":null},"name":"impersonated","serializedName":"impersonated","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"AppUserId"}}}],"typeRef":{"kind":"primitive","value":"Nullable"}}},{"annotations":[],"default":{"kind":"nothing"},"name":"endpoint","serializedName":"endpoint","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"String"}}},{"annotations":[],"default":{"kind":"nothing"},"name":"input","serializedName":"input","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Json"}}},{"annotations":[],"default":{"kind":"nothing"},"name":"outcome","serializedName":"outcome","typeExpr":{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"AuditOutcome"}}}},{"annotations":[],"default":{"kind":"nothing"},"name":"client_ip","serializedName":"client_ip","typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"primitive","value":"String"}}],"typeRef":{"kind":"primitive","value":"Nullable"}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.db"};
+
+export const snAuditLog: ADL.ScopedName = {moduleName:"protoapp.db", name:"AuditLog"};
+
+export function texprAuditLog(): ADL.ATypeExpr<AuditLog> {
+  return {value : {typeRef : {kind: "reference", value : snAuditLog}, parameters : []}};
+}
+
+export type AuditLogTable = common_db.WithId<AuditLog>;
+
+const AuditLogTable_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[{"key":{"moduleName":"common.db","name":"DbTable"},"value":{"id_prefix":"A-","indexes":[["logged_at"],["actor"]]}}],"name":"AuditLogTable","type_":{"kind":"newtype_","value":{"default":{"kind":"nothing"},"typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"AuditLog"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.db","name":"WithId"}}},"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.db"};
+
+export const snAuditLogTable: ADL.ScopedName = {moduleName:"protoapp.db", name:"AuditLogTable"};
+
+export function texprAuditLogTable(): ADL.ATypeExpr<AuditLogTable> {
+  return {value : {typeRef : {kind: "reference", value : snAuditLogTable}, parameters : []}};
+}
+
+export type AuditLogId = common_db.DbKey<AuditLogTable>;
+
+const AuditLogId_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"AuditLogId","type_":{"kind":"type_","value":{"typeExpr":{"parameters":[{"parameters":[],"typeRef":{"kind":"reference","value":{"moduleName":"protoapp.db","name":"AuditLogTable"}}}],"typeRef":{"kind":"reference","value":{"moduleName":"common.db","name":"DbKey"}}},"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.db"};
+
+export const snAuditLogId: ADL.ScopedName = {moduleName:"protoapp.db", name:"AuditLogId"};
+
+export function texprAuditLogId(): ADL.ATypeExpr<AuditLogId> {
+  return {value : {typeRef : {kind: "reference", value : snAuditLogId}, parameters : []}};
+}
+
+export type AuditOutcome = 'success' | 'forbidden' | 'failed';
+export const valuesAuditOutcome : AuditOutcome[] = ['success', 'forbidden', 'failed'];
+
+const AuditOutcome_AST : ADL.ScopedDecl =
+  {"decl":{"annotations":[],"name":"AuditOutcome","type_":{"kind":"union_","value":{"fields":[{"annotations":[],"default":{"kind":"nothing"},"name":"success","serializedName":"success","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}},{"annotations":[],"default":{"kind":"nothing"},"name":"forbidden","serializedName":"forbidden","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}},{"annotations":[],"default":{"kind":"nothing"},"name":"failed","serializedName":"failed","typeExpr":{"parameters":[],"typeRef":{"kind":"primitive","value":"Void"}}}],"typeParams":[]}},"version":{"kind":"nothing"}},"moduleName":"protoapp.db"};
+
+export const snAuditOutcome: ADL.ScopedName = {moduleName:"protoapp.db", name:"AuditOutcome"};
+
+export function texprAuditOutcome(): ADL.ATypeExpr<AuditOutcome> {
+  return {value : {typeRef : {kind: "reference", value : snAuditOutcome}, parameters : []}};
+}
+
 export const _AST_MAP: { [key: string]: ADL.ScopedDecl } = {
   "protoapp.db.AppUser" : AppUser_AST,
   "protoapp.db.AppUserTable" : AppUserTable_AST,
   "protoapp.db.AppUserId" : AppUserId_AST,
+  "protoapp.db.Role" : Role_AST,
+  "protoapp.db.RoleTable" : RoleTable_AST,
+  "protoapp.db.RoleId" : RoleId_AST,
+  "protoapp.db.Permission" : Permission_AST,
+  "protoapp.db.PermissionTable" : PermissionTable_AST,
+  "protoapp.db.PermissionId" : PermissionId_AST,
+  "protoapp.db.UserRole" : UserRole_AST,
+  "protoapp.db.UserRoleTable" : UserRoleTable_AST,
+  "protoapp.db.UserRoleId" : UserRoleId_AST,
+  "protoapp.db.Organisation" : Organisation_AST,
+  "protoapp.db.OrganisationTable" : OrganisationTable_AST,
+  "protoapp.db.OrganisationId" : OrganisationId_AST,
+  "protoapp.db.OrganisationMember" : OrganisationMember_AST,
+  "protoapp.db.OrganisationMemberTable" : OrganisationMemberTable_AST,
+  "protoapp.db.OrganisationMemberId" : OrganisationMemberId_AST,
   "protoapp.db.Message" : Message_AST,
   "protoapp.db.MessageTable" : MessageTable_AST,
-  "protoapp.db.MessageId" : MessageId_AST
+  "protoapp.db.MessageId" : MessageId_AST,
+  "protoapp.db.MessageReaction" : MessageReaction_AST,
+  "protoapp.db.MessageReactionTable" : MessageReactionTable_AST,
+  "protoapp.db.MessageReactionId" : MessageReactionId_AST,
+  "protoapp.db.MessageRevision" : MessageRevision_AST,
+  "protoapp.db.MessageRevisionTable" : MessageRevisionTable_AST,
+  "protoapp.db.MessageRevisionId" : MessageRevisionId_AST,
+  "protoapp.db.RefreshToken" : RefreshToken_AST,
+  "protoapp.db.RefreshTokenTable" : RefreshTokenTable_AST,
+  "protoapp.db.RefreshTokenId" : RefreshTokenId_AST,
+  "protoapp.db.Session" : Session_AST,
+  "protoapp.db.SessionTable" : SessionTable_AST,
+  "protoapp.db.SessionId" : SessionId_AST,
+  "protoapp.db.PasswordResetToken" : PasswordResetToken_AST,
+  "protoapp.db.PasswordResetTokenTable" : PasswordResetTokenTable_AST,
+  "protoapp.db.PasswordResetTokenId" : PasswordResetTokenId_AST,
+  "protoapp.db.Invitation" : Invitation_AST,
+  "protoapp.db.InvitationTable" : InvitationTable_AST,
+  "protoapp.db.InvitationId" : InvitationId_AST,
+  "protoapp.db.UserTotp" : UserTotp_AST,
+  "protoapp.db.UserTotpTable" : UserTotpTable_AST,
+  "protoapp.db.UserTotpId" : UserTotpId_AST,
+  "protoapp.db.TotpRecoveryCode" : TotpRecoveryCode_AST,
+  "protoapp.db.TotpRecoveryCodeTable" : TotpRecoveryCodeTable_AST,
+  "protoapp.db.TotpRecoveryCodeId" : TotpRecoveryCodeId_AST,
+  "protoapp.db.ApiKey" : ApiKey_AST,
+  "protoapp.db.ApiKeyTable" : ApiKeyTable_AST,
+  "protoapp.db.ApiKeyId" : ApiKeyId_AST,
+  "protoapp.db.ApiKeyScope" : ApiKeyScope_AST,
+  "protoapp.db.OidcLogin" : OidcLogin_AST,
+  "protoapp.db.OidcLoginTable" : OidcLoginTable_AST,
+  "protoapp.db.OidcLoginId" : OidcLoginId_AST,
+  "protoapp.db.OidcIdentity" : OidcIdentity_AST,
+  "protoapp.db.OidcIdentityTable" : OidcIdentityTable_AST,
+  "protoapp.db.OidcIdentityId" : OidcIdentityId_AST,
+  "protoapp.db.LoginFailure" : LoginFailure_AST,
+  "protoapp.db.LoginFailureTable" : LoginFailureTable_AST,
+  "protoapp.db.LoginFailureId" : LoginFailureId_AST,
+  "protoapp.db.AuditLog" : AuditLog_AST,
+  "protoapp.db.AuditLogTable" : AuditLogTable_AST,
+  "protoapp.db.AuditLogId" : AuditLogId_AST,
+  "protoapp.db.AuditOutcome" : AuditOutcome_AST
 };
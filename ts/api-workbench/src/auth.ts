@@ -8,7 +8,11 @@ export interface Auth {
 export interface JwtClaims {
   sub: string;
   exp: number;
-  role: string;
+  roles: string[];
+  // Present when an admin is impersonating the subject
+  act?: { sub: string };
+  // The organisation that the subject is acting within, if any
+  org?: string;
 }
 
 export interface ApiWithToken {
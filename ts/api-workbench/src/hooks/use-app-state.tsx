@@ -4,7 +4,7 @@ import { FetchHttp } from "../service/fetch-http";
 import { ApiWithToken, Auth, JwtClaims, expiry_secs } from "../auth";
 import { jwtDecode } from "jwt-decode";
 
-import { LoginResp, makeRefreshReq } from "@protoapp/adl/protoapp/apis/ui";
+import { LoginResp, makeLogoutReq, makeRefreshReq } from "@protoapp/adl/protoapp/apis/ui";
 import { useNavigate } from "raviger";
 
 const protoappApi = new Service(new FetchHttp(), "/api");
@@ -38,7 +38,11 @@ export function AppStateProvider(props: { children?: React.ReactNode }) {
         setAuthState({ kind: "auth", auth });
         break;
       }
-      case "invalid_credentials": {
+      case "invalid_credentials":
+      case "email_not_verified":
+      case "deactivated":
+      case "totp_required":
+      case "too_many_attempts": {
         setAuthState({ kind: "authfailed" });
         break;
       }
@@ -46,7 +50,7 @@ export function AppStateProvider(props: { children?: React.ReactNode }) {
   }
 
   async function logout() {
-    await protoappApi.logout({});
+    await protoappApi.logout(makeLogoutReq({}));
     console.log(`logout`);
     setAuthState({ kind: "noauth" });
   }
@@ -58,8 +62,8 @@ export function AppStateProvider(props: { children?: React.ReactNode }) {
       case "invalid_refresh_token":
         setAuthState({ kind: "noauth" });
         break;
-      case "access_token":
-        const jwt = resp.value;
+      case "tokens":
+        const jwt = resp.value.access_jwt;
         const jwt_decoded = jwtDecode(jwt) as JwtClaims;
         console.log("jwt", jwt_decoded);
         let auth = { jwt, jwt_decoded };
@@ -61,7 +61,7 @@ export function ApiWorkbench() {
       (ep) =>
         ep.security.kind === "public" ||
         (ep.security.kind === "token" && authState.kind == "auth") ||
-        (ep.security.kind === "tokenWithRole" && jwt_decoded && ep.security.value === jwt_decoded.role),
+        (ep.security.kind === "tokenWithRole" && jwt_decoded && jwt_decoded.roles.includes(ep.security.value)),
     );
   }, [authState]);
 
@@ -162,7 +162,8 @@ export function ApiWorkbench() {
           </Button>
           {jwt_decoded && (
             <Box sx={{ fontSize: "0.9rem" }}>
-              sub: {jwt_decoded.sub} / role: {jwt_decoded.role}
+              sub: {jwt_decoded.sub} / roles: {jwt_decoded.roles.join(", ")}
+              {jwt_decoded.act && <> / impersonated by: {jwt_decoded.act.sub}</>}
             </Box>
           )}
         </Box>
@@ -50,6 +50,8 @@ export class ServiceBase {
       headers["Authorization"] = "Bearer " + authToken;
     }
     headers["Content-Type"] = "application/json";
+    // Required by the server's CSRF checks on requests that use cookies
+    headers["X-Requested-With"] = "fetch";
     const httpReq: HttpRequest = {
       url: this.baseUrl + path + (queryString === undefined ? "" : "?" + queryString),
       headers,
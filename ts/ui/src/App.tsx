@@ -98,6 +98,14 @@ const App: React.FC = () => {
       } else if (response.kind === "invalid_credentials") {
         // Add user feedback for invalid credentials
         alert("Invalid email or password.");
+      } else if (response.kind === "email_not_verified") {
+        alert("Please verify your email address, using the link we emailed you.");
+      } else if (response.kind === "deactivated") {
+        alert("This account has been deactivated.");
+      } else if (response.kind === "totp_required") {
+        alert("This account requires a two-factor authentication code.");
+      } else if (response.kind === "too_many_attempts") {
+        alert(`Too many failed logins. Please try again in ${response.value} seconds.`);
       }
     } catch (err) {
       handleApiError(err);
@@ -108,7 +116,7 @@ const App: React.FC = () => {
   const handleLogout = useCallback(async () => {
     if (!accessToken) return; // Should not happen if called from MessagesScreen, but good practice
     try {
-      await service.logout({});
+      await service.logout(API.makeLogoutReq({}));
     } catch (err) {
       handleApiError(err);
       // Still log out client-side even if API fails
@@ -32,18 +32,36 @@ const mockMessages: API.Message[] = [
     message: "Hello there! This is the first message.",
     user_fullname: "User One",
     posted_at: new Date(Date.now() - 60000 * 5).getTime(), // Use getTime() for number timestamp
+    edited_at: null,
+    deleted_at: null,
+    reply_count: 2,
+    latest_reply_at: new Date(Date.now() - 60000 * 3).getTime(),
+    reactions: [
+      { emoji: "👍", count: 3, reacted_by_me: true },
+      { emoji: "🎉", count: 1, reacted_by_me: false },
+    ],
   },
   {
     id: "2",
     message: "Hi! How are you doing today?",
     user_fullname: "User Two",
     posted_at: new Date(Date.now() - 60000 * 2).getTime(), // Use getTime() for number timestamp
+    edited_at: new Date(Date.now() - 60000).getTime(),
+    deleted_at: null,
+    reply_count: 0,
+    latest_reply_at: null,
+    reactions: [],
   },
   {
     id: "3",
-    message: "Just testing the message view.",
+    message: "",
     user_fullname: "User One",
     posted_at: new Date().getTime(), // Use getTime() for number timestamp
+    edited_at: null,
+    deleted_at: new Date().getTime(),
+    reply_count: 0,
+    latest_reply_at: null,
+    reactions: [],
   },
 ];
 
@@ -54,10 +54,29 @@ export const MessagesScreenView: React.FC<MessagesScreenViewProps> = ({
                 <p className="font-bold text-sm">{msg.user_fullname || "Unknown User"}</p>
               </CardHeader>
               <CardContent>
-                <p className="text-sm">{msg.message}</p>
+                {msg.deleted_at !== null ? (
+                  <p className="text-sm italic text-muted-foreground">This message was deleted</p>
+                ) : (
+                  <p className="text-sm">{msg.message}</p>
+                )}
+                {msg.reactions.length > 0 && (
+                  <div className="flex gap-2 mt-2">
+                    {msg.reactions.map((r) => (
+                      <span
+                        key={r.emoji}
+                        className={`text-xs rounded-full border px-2 ${r.reacted_by_me ? "bg-muted" : ""}`}
+                      >
+                        {r.emoji} {r.count}
+                      </span>
+                    ))}
+                  </div>
+                )}
               </CardContent>
               <CardFooter className="text-xs text-muted-foreground">
                 {new Date(msg.posted_at).toLocaleString()}
+                {msg.edited_at !== null && msg.deleted_at === null && " (edited)"}
+                {msg.reply_count > 0 &&
+                  ` · ${msg.reply_count} ${msg.reply_count === 1 ? "reply" : "replies"}`}
               </CardFooter>
             </Card>
           ))}